@@ -0,0 +1,62 @@
+// Service worker: keeps the journal pages usable offline. Pages are fetched from
+// the network when possible and from the cache otherwise; saves made while
+// offline are queued by the page itself and sent to /journal/sync on reconnect.
+
+const CACHE = 'journal-v1';
+// The path the app is served under ("" at the domain root), from where this script lives
+const BASE = new URL(self.registration.scope).pathname.replace(/\/$/, '');
+const APP_SHELL = [`${BASE}/journal`, `${BASE}/manifest.webmanifest`, `${BASE}/icon.svg`];
+
+self.addEventListener('install', (event) => {
+    event.waitUntil(
+        caches.open(CACHE)
+            .then((cache) => cache.addAll(APP_SHELL))
+            .catch(() => {}) // Not logged in yet; pages are cached as they're visited
+            .then(() => self.skipWaiting())
+    );
+});
+
+self.addEventListener('activate', (event) => {
+    event.waitUntil(
+        caches.keys()
+            .then((keys) => Promise.all(keys.filter((key) => key !== CACHE).map((key) => caches.delete(key))))
+            .then(() => self.clients.claim())
+    );
+});
+
+self.addEventListener('fetch', (event) => {
+    const request = event.request;
+    const url = new URL(request.url);
+    if (request.method !== 'GET' || url.origin !== self.location.origin) {
+        return;
+    }
+    // Live data is never served stale
+    const path = url.pathname.slice(BASE.length);
+    if (path.startsWith('/api/') || path === '/journal/events' || path === '/journal/live') {
+        return;
+    }
+
+    if (path.startsWith('/static/')) {
+        // Static files change rarely; serve them from the cache first
+        event.respondWith(
+            caches.match(request).then((cached) => cached || fetchAndCache(request))
+        );
+        return;
+    }
+
+    event.respondWith(
+        fetchAndCache(request).catch(() =>
+            caches.match(request).then((cached) => cached || caches.match(`${BASE}/journal`))
+        )
+    );
+});
+
+async function fetchAndCache(request) {
+    const response = await fetch(request);
+    // Only successful pages; a login redirect shouldn't replace a cached journal page
+    if (response.ok && !response.redirected) {
+        const copy = response.clone();
+        caches.open(CACHE).then((cache) => cache.put(request, copy));
+    }
+    return response;
+}
@@ -0,0 +1,263 @@
+//! The access log: every request as a JSON line (method, route pattern, status, latency,
+//! and the device's name), written by a background task and rotated by size. The admin
+//! requests page summarizes it by route, slowest first.
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::header,
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Instant;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+
+use crate::AppState;
+
+/// Rotated files kept besides the current one (access.log.1 is the newest)
+const KEEP_ROTATED: usize = 3;
+/// Route shown for requests no route matched
+const NO_ROUTE: &str = "(no route)";
+
+/// One request, as logged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessRecord {
+    pub at: DateTime<Local>,
+    pub method: String,
+    /// The route's pattern ("/journal/entry/:date"), so requests for different days group together
+    pub route: String,
+    pub status: u16,
+    /// Time until the response started; streamed bodies take longer to finish
+    pub latency_ms: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+/// Where records go; the file is written by a background task so requests never wait on it
+#[derive(Debug)]
+pub struct AccessLog {
+    path: PathBuf,
+    sender: mpsc::UnboundedSender<AccessRecord>,
+}
+
+static ACCESS_LOG: OnceLock<AccessLog> = OnceLock::new();
+
+/// Start writing the access log to `path`, rotating it once it reaches `max_kb`; done once at startup
+pub fn start(path: &str, max_kb: u64) {
+    let path = PathBuf::from(path);
+    let (sender, receiver) = mpsc::unbounded_channel();
+    tokio::spawn(write_records(path.clone(), max_kb.max(1) * 1024, receiver));
+    if ACCESS_LOG.set(AccessLog { path, sender }).is_err() {
+        tracing::warn!("The access log was already started; keeping the first one");
+    }
+}
+
+/// The access log, if one is being written
+pub fn access_log() -> Option<&'static AccessLog> {
+    ACCESS_LOG.get()
+}
+
+impl AccessLog {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn record(&self, record: AccessRecord) {
+        // Only fails once the writer has stopped, and then there's nowhere to log to
+        let _ = self.sender.send(record);
+    }
+}
+
+/// Middleware logging each request once its response is ready
+pub async fn log_requests(State(app_state): State<AppState>, request: Request, next: Next) -> Response {
+    let Some(log) = access_log() else {
+        return next.run(request).await;
+    };
+    let started = Instant::now();
+    let method = request.method().to_string();
+    let route = match request.extensions().get::<MatchedPath>() {
+        Some(path) => route_pattern(path.as_str()),
+        // Static files are served by a nested service, which isn't a route
+        None if request.uri().path().starts_with(&crate::handlers::app_url("/static/")) => crate::handlers::app_url("/static/*"),
+        None => NO_ROUTE.to_string(),
+    };
+    let token = crate::handlers::extract_session_token(request.headers()).or_else(|| {
+        request.headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(|token| token.trim().to_string())
+    });
+    let device = match token {
+        Some(token) => app_state.auth_manager.get_session_info(&token).await.and_then(|session| session.device_name),
+        None => None,
+    };
+
+    let response = next.run(request).await;
+    log.record(AccessRecord {
+        at: Local::now(),
+        method,
+        route,
+        status: response.status().as_u16(),
+        latency_ms: started.elapsed().as_secs_f64() * 1000.0,
+        device,
+        request_id: response.headers()
+            .get(crate::error::REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+    });
+    response
+}
+
+/// A matched path as people write it: nested services' catch-all parameter becomes `*`
+fn route_pattern(matched: &str) -> String {
+    match matched.split_once("/*") {
+        Some((prefix, _)) => format!("{}/*", prefix),
+        None => matched.to_string(),
+    }
+}
+
+async fn write_records(path: PathBuf, max_bytes: u64, mut receiver: mpsc::UnboundedReceiver<AccessRecord>) {
+    while let Some(record) = receiver.recv().await {
+        if let Err(e) = append(&path, max_bytes, &record).await {
+            tracing::warn!("Failed to write the access log {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Append a record, rotating the file first if it's full
+async fn append(path: &Path, max_bytes: u64, record: &AccessRecord) -> Result<(), Box<dyn std::error::Error>> {
+    let size = tokio::fs::metadata(path).await.map(|metadata| metadata.len()).unwrap_or(0);
+    if size >= max_bytes {
+        rotate(path).await?;
+    }
+    let mut file = tokio::fs::OpenOptions::new().create(true).append(true).open(path).await?;
+    file.write_all(format!("{}\n", serde_json::to_string(record)?).as_bytes()).await?;
+    Ok(())
+}
+
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// Shift access.log to access.log.1, .1 to .2, and so on, dropping the oldest
+async fn rotate(path: &Path) -> std::io::Result<()> {
+    for n in (1..KEEP_ROTATED).rev() {
+        let from = rotated_path(path, n);
+        if tokio::fs::try_exists(&from).await? {
+            tokio::fs::rename(&from, rotated_path(path, n + 1)).await?;
+        }
+    }
+    tokio::fs::rename(path, rotated_path(path, 1)).await
+}
+
+/// Every logged request still on disk, oldest first. Unreadable lines are skipped.
+pub async fn load(path: &Path) -> Vec<AccessRecord> {
+    let mut records = Vec::new();
+    let files = (1..=KEEP_ROTATED).rev().map(|n| rotated_path(path, n)).chain([path.to_path_buf()]);
+    for file in files {
+        if let Ok(text) = tokio::fs::read_to_string(&file).await {
+            records.extend(text.lines().filter_map(|line| serde_json::from_str::<AccessRecord>(line).ok()));
+        }
+    }
+    records
+}
+
+/// How one route has been performing
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteSummary {
+    pub method: String,
+    pub route: String,
+    pub requests: usize,
+    /// Responses with a 5xx status
+    pub errors: usize,
+    pub average_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Per-route request counts and latencies, slowest (by 95th percentile) first
+pub fn summarize(records: &[AccessRecord]) -> Vec<RouteSummary> {
+    let mut by_route: std::collections::HashMap<(&str, &str), Vec<&AccessRecord>> = std::collections::HashMap::new();
+    for record in records {
+        by_route.entry((&record.method, &record.route)).or_default().push(record);
+    }
+
+    let mut summaries: Vec<RouteSummary> = by_route
+        .into_iter()
+        .map(|((method, route), records)| {
+            let mut latencies: Vec<f64> = records.iter().map(|record| record.latency_ms).collect();
+            latencies.sort_by(f64::total_cmp);
+            let p95_index = (latencies.len() * 95).div_ceil(100).saturating_sub(1);
+            RouteSummary {
+                method: method.to_string(),
+                route: route.to_string(),
+                requests: records.len(),
+                errors: records.iter().filter(|record| record.status >= 500).count(),
+                average_ms: latencies.iter().sum::<f64>() / latencies.len() as f64,
+                p95_ms: latencies[p95_index],
+                max_ms: latencies[latencies.len() - 1],
+            }
+        })
+        .collect();
+    summaries.sort_by(|a, b| b.p95_ms.total_cmp(&a.p95_ms).then_with(|| a.route.cmp(&b.route)));
+    summaries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn record(route: &str, status: u16, latency_ms: f64) -> AccessRecord {
+        AccessRecord {
+            at: Local::now(),
+            method: "GET".to_string(),
+            route: route.to_string(),
+            status,
+            latency_ms,
+            device: None,
+            request_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_append_rotate_and_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("access.log");
+        // Each record is well over 100 bytes, so every append after the first rotates
+        for n in 0..6 {
+            append(&path, 100, &record(&format!("/route{}", n), 200, 1.0)).await.unwrap();
+        }
+        assert!(rotated_path(&path, KEEP_ROTATED).exists());
+        assert!(!rotated_path(&path, KEEP_ROTATED + 1).exists());
+
+        let routes: Vec<String> = load(&path).await.into_iter().map(|record| record.route).collect();
+        assert_eq!(routes, ["/route2", "/route3", "/route4", "/route5"]);
+    }
+
+    #[test]
+    fn test_summarize() {
+        let mut records: Vec<AccessRecord> = (1..=20).map(|n| record("/journal", 200, n as f64)).collect();
+        records.push(record("/healthz", 200, 0.5));
+        records.push(record("/journal", 500, 100.0));
+
+        let summaries = summarize(&records);
+        assert_eq!(summaries[0].route, "/journal");
+        assert_eq!((summaries[0].requests, summaries[0].errors), (21, 1));
+        assert_eq!(summaries[0].p95_ms, 20.0);
+        assert_eq!(summaries[0].max_ms, 100.0);
+        assert_eq!(summaries[1].route, "/healthz");
+
+        assert_eq!(route_pattern("/static/*__private__axum_nest_tail_param"), "/static/*");
+        assert_eq!(route_pattern("/api/v1/entries/:date"), "/api/v1/entries/:date");
+    }
+}
@@ -0,0 +1,197 @@
+use crate::cycle_date::CycleDate;
+use crate::journal::{JournalManager, PromptOutcome};
+use crate::prompts::PromptsConfig;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Keyword lists used to sort prompts into rough themes. The first theme with a match wins.
+const THEMES: &[(&str, &[&str])] = &[
+    ("gratitude", &["grateful", "gratitude", "thankful", "appreciate"]),
+    ("relationships", &["friend", "family", "partner", "relationship", "someone", "people", "connect"]),
+    ("work", &["work", "job", "career", "project", "colleague", "productive"]),
+    ("health", &["health", "body", "sleep", "exercise", "energy", "rest"]),
+    ("emotions", &["feel", "feeling", "emotion", "mood", "anxious", "joy", "stress"]),
+    ("growth", &["learn", "grow", "growth", "change", "goal", "challenge", "habit"]),
+    ("creativity", &["create", "creative", "imagine", "idea", "curious", "play"]),
+    ("memories", &["remember", "memory", "childhood", "past", "nostalgia"]),
+];
+
+/// How often prompts in one group led to an entry
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AnswerRate {
+    pub key: String,
+    pub prompts: usize,
+    pub answered: usize,
+    /// Fraction of prompts that led to an entry (0.0 - 1.0)
+    pub answer_rate: f32,
+    /// Average length of the entries these prompts led to
+    pub average_words: Option<f64>,
+}
+
+impl AnswerRate {
+    /// Answer rate as a whole percentage, for display
+    pub fn answer_percent(&self) -> u32 {
+        (self.answer_rate * 100.0).round() as u32
+    }
+
+    /// Average entry length rounded to whole words, for display
+    pub fn average_words_rounded(&self) -> String {
+        self.average_words.map(|w| format!("{:.0}", w)).unwrap_or_else(|| "-".to_string())
+    }
+}
+
+/// Answer rates grouped by prompt type, template variation, theme, and generating model
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptAnalytics {
+    pub total_prompts: usize,
+    pub by_type: Vec<AnswerRate>,
+    pub by_variant: Vec<AnswerRate>,
+    pub by_theme: Vec<AnswerRate>,
+    pub by_model: Vec<AnswerRate>,
+    /// How many ensemble comparisons each model won
+    pub ensemble_wins: BTreeMap<String, usize>,
+}
+
+impl PromptAnalytics {
+    /// Group prompt outcomes into answer-rate reports
+    pub fn from_outcomes(outcomes: &[PromptOutcome]) -> Self {
+        Self {
+            total_prompts: outcomes.len(),
+            by_type: group_by(outcomes, |o| o.prompt_type.to_string()),
+            by_variant: group_by(outcomes, |o| PromptsConfig::variant_name(o.prompt_number).to_string()),
+            by_theme: group_by(outcomes, |o| classify_theme(&o.prompt).to_string()),
+            by_model: group_by(outcomes, |o| o.model.clone().unwrap_or_else(|| "unrecorded".to_string())),
+            ensemble_wins: outcomes
+                .iter()
+                .filter(|o| o.ensemble_winner)
+                .fold(BTreeMap::new(), |mut wins, o| {
+                    *wins.entry(o.model.clone().unwrap_or_default()).or_default() += 1;
+                    wins
+                }),
+        }
+    }
+
+    /// Each grouping with a heading, for display
+    pub fn groups(&self) -> [(&'static str, &[AnswerRate]); 4] {
+        [
+            ("Prompt type", &self.by_type),
+            ("Template variation", &self.by_variant),
+            ("Theme", &self.by_theme),
+            ("Model", &self.by_model),
+        ]
+    }
+}
+
+/// Sort a prompt into a theme by keyword, or "other"
+pub fn classify_theme(prompt: &str) -> &'static str {
+    let words: Vec<String> = prompt
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect();
+
+    THEMES
+        .iter()
+        .find(|(_, keywords)| words.iter().any(|w| keywords.iter().any(|k| w.starts_with(k))))
+        .map(|(theme, _)| *theme)
+        .unwrap_or("other")
+}
+
+fn group_by(outcomes: &[PromptOutcome], key: impl Fn(&PromptOutcome) -> String) -> Vec<AnswerRate> {
+    let mut groups: BTreeMap<String, Vec<&PromptOutcome>> = BTreeMap::new();
+    for outcome in outcomes {
+        groups.entry(key(outcome)).or_default().push(outcome);
+    }
+
+    let mut rates: Vec<AnswerRate> = groups
+        .into_iter()
+        .map(|(key, group)| {
+            let answered: Vec<usize> = group.iter().filter(|o| o.answered).filter_map(|o| o.entry_words).collect();
+            let answered_count = group.iter().filter(|o| o.answered).count();
+            AnswerRate {
+                key,
+                prompts: group.len(),
+                answered: answered_count,
+                answer_rate: answered_count as f32 / group.len() as f32,
+                average_words: if answered.is_empty() {
+                    None
+                } else {
+                    Some(answered.iter().sum::<usize>() as f64 / answered.len() as f64)
+                },
+            }
+        })
+        .collect();
+
+    // Least inspiring first, so pruning candidates are at the top
+    rates.sort_by(|a, b| a.answer_rate.total_cmp(&b.answer_rate).then_with(|| a.key.cmp(&b.key)));
+    rates
+}
+
+/// Build the answer-rate report across every past prompt
+pub async fn prompt_analytics(journal_manager: &JournalManager) -> Result<PromptAnalytics, Box<dyn std::error::Error>> {
+    let outcomes = journal_manager.list_prompt_outcomes(&CycleDate::today()).await?;
+    Ok(PromptAnalytics::from_outcomes(&outcomes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::PromptType;
+
+    fn outcome(prompt_number: u8, prompt: &str, entry_words: Option<usize>) -> PromptOutcome {
+        PromptOutcome {
+            cycle_date: CycleDate::new(1, 0, 0, 1).unwrap(),
+            prompt_type: PromptType::Daily,
+            prompt_number,
+            prompt: prompt.to_string(),
+            answered: entry_words.is_some(),
+            entry_words,
+            model: None,
+            ensemble_winner: false,
+        }
+    }
+
+    #[test]
+    fn test_classify_theme() {
+        assert_eq!(classify_theme("What are you grateful for this morning?"), "gratitude");
+        assert_eq!(classify_theme("How did your friends show up for you?"), "relationships");
+        assert_eq!(classify_theme("Describe the view from your window."), "other");
+    }
+
+    #[test]
+    fn test_answer_rates() {
+        let outcomes = vec![
+            outcome(1, "What are you grateful for?", Some(200)),
+            outcome(2, "What did you learn at work?", None),
+            outcome(1, "Who made you feel welcome?", Some(100)),
+            outcome(2, "What challenge are you avoiding?", Some(50)),
+        ];
+        let analytics = PromptAnalytics::from_outcomes(&outcomes);
+
+        assert_eq!(analytics.total_prompts, 4);
+        assert_eq!(analytics.by_type.len(), 1);
+        assert_eq!(analytics.by_type[0].answered, 3);
+        assert_eq!(analytics.by_type[0].average_words, Some(350.0 / 3.0));
+
+        let second = &analytics.by_variant[0];
+        assert_eq!(second.key, "second");
+        assert_eq!(second.answer_rate, 0.5);
+        let primary = &analytics.by_variant[1];
+        assert_eq!(primary.key, "primary");
+        assert_eq!(primary.average_words, Some(150.0));
+        assert_eq!(analytics.by_model[0].key, "unrecorded");
+    }
+
+    #[test]
+    fn test_ensemble_wins() {
+        let mut won = outcome(1, "What surprised you today?", Some(80));
+        won.model = Some("llama3".to_string());
+        won.ensemble_winner = true;
+        let mut other = outcome(2, "What are you grateful for?", None);
+        other.model = Some("mistral".to_string());
+
+        let analytics = PromptAnalytics::from_outcomes(&[won, other]);
+        assert_eq!(analytics.ensemble_wins, BTreeMap::from([("llama3".to_string(), 1)]));
+        assert_eq!(analytics.by_model.iter().map(|r| r.key.as_str()).collect::<Vec<_>>(), vec!["mistral", "llama3"]);
+    }
+}
@@ -0,0 +1,973 @@
+//! Versioned JSON API (`/api/v1`) for mobile clients and scripts.
+//!
+//! Every endpoint answers in JSON, including failures, which are `AppError`s in the
+//! shared envelope (see `crate::error`).
+
+use axum::{
+    async_trait,
+    extract::{
+        rejection::{JsonRejection, QueryRejection},
+        FromRequestParts, Path, Query, State,
+    },
+    http::{header, request::Parts, HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
+    response::Response,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::auth::{AuthContext, Scope};
+use crate::compression::FileValidator;
+use crate::config::{CorsConfig, ServerConfig};
+use crate::cycle_date::CycleDate;
+use crate::error::AppError;
+use crate::notebook::{ActiveNotebook, Notebook};
+use crate::AppState;
+
+/// Routes mounted under `/api/v1`
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/auth/request", post(request_passcode))
+        .route("/auth/login", post(login))
+        .route("/auth/session", get(current_session).delete(logout))
+        .route("/entries", get(list_entries))
+        .route("/entries/:date", get(get_entry).put(put_entry))
+        .route("/entries/:date/prompts", get(get_prompts))
+        .route("/entries/:date/summary", get(get_summary))
+        .route("/stats", get(get_stats))
+        .route("/sync", get(sync_changes))
+        .route("/batch", batch_route())
+        .route("/devices", get(list_devices))
+        .route("/devices/:id", axum::routing::patch(update_device))
+        .route("/devices/current/push", axum::routing::put(register_push).delete(unregister_push))
+        .route("/personalization/:file", get(get_personal_file).put(put_personal_file))
+        .route("/holidays", get(list_holidays).post(add_holiday))
+        .route("/holidays/:index", axum::routing::put(update_holiday).delete(delete_holiday))
+        .route("/queue", get(get_queue))
+        .route("/queue/:id", axum::routing::delete(dismiss_queued))
+        .route("/queue/:id/retry", post(retry_queued))
+        .fallback(|| async { AppError::not_found("No such API endpoint") })
+}
+
+/// Several reads in one request (POST /api/v1/batch, also served at /api/batch)
+pub fn batch_route() -> axum::routing::MethodRouter<AppState> {
+    post(batch)
+}
+
+/// CORS for the `/api` routes per `[server.cors]`, or None when no origins are allowed
+pub fn cors(config: &CorsConfig) -> Result<Option<CorsLayer>, String> {
+    if config.allowed_origins.is_empty() {
+        return Ok(None);
+    }
+    let any = config.allowed_origins.iter().any(|origin| origin.trim() == "*");
+    let allow_origin = if any {
+        if config.allow_credentials {
+            return Err("allow_credentials needs the allowed origins listed, not \"*\"".to_string());
+        }
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(config.allowed_origins.iter().map(|origin| parse_origin(origin)).collect::<Result<Vec<_>, _>>()?)
+    };
+
+    Ok(Some(
+        CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_credentials(config.allow_credentials)
+            .allow_methods([Method::GET, Method::POST, Method::PUT, Method::PATCH, Method::DELETE])
+            .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE, header::ACCEPT, HeaderName::from_static("x-api-key")])
+            .expose_headers([header::LOCATION, header::RETRY_AFTER, HeaderName::from_static("x-request-id")])
+            .max_age(std::time::Duration::from_secs(3600)),
+    ))
+}
+
+/// Whether a browser page at `origin` may open a cookie-authenticated connection, like
+/// the live sync WebSocket, which CORS doesn't cover. The page must be served from this
+/// instance (`hosts` are the request's Host and X-Forwarded-Host), from the configured
+/// `server.host`, or from an origin `[server.cors]` lets send credentials.
+pub fn allows_origin(server: &ServerConfig, origin: &str, hosts: &[&str]) -> bool {
+    let origin = origin.trim().trim_end_matches('/').to_ascii_lowercase();
+    let Some(authority) = origin.strip_prefix("https://").or_else(|| origin.strip_prefix("http://")) else {
+        return false;
+    };
+    let hostname = authority.rsplit_once(':').map_or(authority, |(hostname, _)| hostname);
+    hosts.iter().any(|host| host.trim().eq_ignore_ascii_case(authority))
+        || (!matches!(server.host.as_str(), "" | "0.0.0.0" | "::") && hostname.eq_ignore_ascii_case(&server.host))
+        || (server.cors.allow_credentials
+            && server.cors.allowed_origins.iter().any(|allowed| parse_origin(allowed).is_ok_and(|allowed| allowed == origin.as_str())))
+}
+
+/// An origin as browsers send it: scheme, host, and any port, without a path
+fn parse_origin(origin: &str) -> Result<HeaderValue, String> {
+    let origin = origin.trim().trim_end_matches('/');
+    let host = origin.strip_prefix("https://").or_else(|| origin.strip_prefix("http://")).unwrap_or_default();
+    if host.is_empty() || host.contains(['/', '?', '#', ' ']) {
+        return Err(format!("'{}' isn't an origin like \"https://dash.home.example\"", origin));
+    }
+    HeaderValue::from_str(&origin.to_ascii_lowercase()).map_err(|_| format!("'{}' isn't a valid origin", origin))
+}
+
+type ApiResult<T> = Result<Json<T>, AppError>;
+
+/// The caller's identity, rejecting with the error envelope instead of plain text
+struct ApiAuth(AuthContext);
+
+impl ApiAuth {
+    fn require(&self, scope: Scope) -> Result<(), AppError> {
+        self.0.require(scope).map_err(|(_, message)| AppError::forbidden(message))
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for ApiAuth {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        AuthContext::from_request_parts(parts, state)
+            .await
+            .map(ApiAuth)
+            .map_err(|rejection| AppError::unauthorized(rejection.message()))
+    }
+}
+
+/// The addressed notebook, rejecting with the error envelope instead of plain text
+struct ApiNotebook(Arc<Notebook>);
+
+#[async_trait]
+impl FromRequestParts<AppState> for ApiNotebook {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        ActiveNotebook::from_request_parts(parts, state)
+            .await
+            .map(|ActiveNotebook(notebook)| ApiNotebook(notebook))
+            .map_err(|rejection| AppError::not_found(rejection.to_string()))
+    }
+}
+
+/// A date path segment: "today", a cycle date ("03B25"), or a real date ("2025-03-02")
+fn parse_date(value: &str) -> Result<CycleDate, AppError> {
+    if value == "today" {
+        return Ok(CycleDate::today());
+    }
+    crate::handlers::parse_date_param(value).map_err(AppError::bad_request)
+}
+
+/// Request body for asking the server to print a login passcode
+#[derive(Deserialize)]
+struct PasscodeRequest {
+    device_name: Option<String>,
+}
+
+/// Print a fresh passcode on the server terminal (POST /api/v1/auth/request)
+async fn request_passcode(
+    State(app_state): State<AppState>,
+    payload: Result<Json<PasscodeRequest>, JsonRejection>,
+) -> Result<(StatusCode, Json<serde_json::Value>), AppError> {
+    let Json(request) = payload?;
+    app_state.auth_manager.create_auth_request(request.device_name, false).await;
+    Ok((StatusCode::ACCEPTED, Json(serde_json::json!({ "message": "Check the server terminal for your passcode" }))))
+}
+
+/// Request body for exchanging a passcode for a session token
+#[derive(Deserialize)]
+struct LoginRequest {
+    passcode: String,
+    device_name: Option<String>,
+    /// Register for push notifications in the same step
+    push: Option<PushRequest>,
+}
+
+/// A new session; send the token as `Authorization: Bearer <token>`
+#[derive(Serialize)]
+struct LoginResponse {
+    token: String,
+    expires_in: u64,
+    device_id: String,
+}
+
+/// Exchange a passcode for a session token (POST /api/v1/auth/login)
+async fn login(
+    State(app_state): State<AppState>,
+    payload: Result<Json<LoginRequest>, JsonRejection>,
+) -> ApiResult<LoginResponse> {
+    let Json(request) = payload?;
+    let Some(token) = app_state.auth_manager.authenticate(&request.passcode, request.device_name, false).await else {
+        return Err(AppError::unauthorized("Invalid or expired passcode"));
+    };
+    if let Some(push) = request.push {
+        app_state.auth_manager.set_push_registration(&token, Some(push.into_registration())).await;
+    }
+    app_state.auth_manager.save_sessions_to_file(&app_state.tokens_file_manager).await;
+    let device_id = match app_state.auth_manager.get_session_info(&token).await {
+        Some(session) => session.device_id(),
+        None => return Err(AppError::internal("Error creating session", "session vanished after login")),
+    };
+    Ok(Json(LoginResponse { token, expires_in: app_state.config.auth.session_duration_seconds, device_id }))
+}
+
+/// Who the caller is and what they may do
+#[derive(Serialize)]
+struct SessionInfo {
+    /// "session" or "api_key"
+    kind: &'static str,
+    name: String,
+    /// Absent for sessions, which have full access
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scopes: Option<Vec<Scope>>,
+}
+
+/// Describe the caller's credentials (GET /api/v1/auth/session)
+async fn current_session(ApiAuth(auth): ApiAuth) -> ApiResult<SessionInfo> {
+    let scopes = match &auth {
+        AuthContext::Session(_) => None,
+        AuthContext::ApiKey(api_key) => Some(api_key.scopes.clone()),
+    };
+    let kind = if scopes.is_some() { "api_key" } else { "session" };
+    Ok(Json(SessionInfo { kind, name: auth.label().to_string(), scopes }))
+}
+
+/// End the caller's session (DELETE /api/v1/auth/session)
+async fn logout(State(app_state): State<AppState>, ApiAuth(auth): ApiAuth) -> Result<StatusCode, AppError> {
+    let AuthContext::Session(session) = auth else {
+        return Err(AppError::bad_request("API keys are revoked from the admin page, not logged out"));
+    };
+    app_state.auth_manager.remove_session(&session.token).await;
+    app_state.auth_manager.save_sessions_to_file(&app_state.tokens_file_manager).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List entries with optional filters and pagination (GET /api/v1/entries)
+async fn list_entries(
+    ApiNotebook(notebook): ApiNotebook,
+    auth: ApiAuth,
+    query: Result<Query<crate::handlers::EntryListQuery>, QueryRejection>,
+) -> ApiResult<crate::handlers::EntryListResponse> {
+    auth.require(Scope::ReadEntries)?;
+    let Query(params) = query?;
+    crate::handlers::list_entry_page(&notebook.journal_manager, &params)
+        .await
+        .map(Json)
+}
+
+/// An entry with the revision to send back when replacing it
+#[derive(Serialize)]
+struct EntryResponse {
+    #[serde(flatten)]
+    entry: crate::journal::JournalEntry,
+    revision: String,
+}
+
+/// Fetch one day's entry (GET /api/v1/entries/{date}), or 304 if it hasn't changed since the
+/// `If-None-Match`/`If-Modified-Since` sent
+async fn get_entry(
+    ApiNotebook(notebook): ApiNotebook,
+    auth: ApiAuth,
+    Path(date): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    auth.require(Scope::ReadEntries)?;
+    let cycle_date = parse_date(&date)?;
+    let validator = FileValidator::of(&notebook.journal_manager.entry_files(&cycle_date)).await;
+    if let Some(not_modified) = validator.not_modified(&headers) {
+        return Ok(not_modified);
+    }
+    match notebook.journal_manager.load_entry(&cycle_date).await {
+        Ok(Some(entry)) => {
+            let revision = crate::journal::entry_revision(&entry.content);
+            Ok(validator.apply(Json(EntryResponse { entry, revision })))
+        }
+        Ok(None) => Err(AppError::not_found(format!("No entry for {}", cycle_date))),
+        Err(e) => Err(AppError::internal("Error loading entry", e)),
+    }
+}
+
+/// Request body for saving an entry
+#[derive(Deserialize)]
+struct PutEntryRequest {
+    content: String,
+    /// Revision the client last loaded ("" if there was no entry); absent to save unconditionally
+    revision: Option<String>,
+    /// Prompt the entry answers
+    prompt_number: Option<u8>,
+    /// Save even though the entry is past the edit window (needs the admin scope)
+    #[serde(default)]
+    override_lock: bool,
+}
+
+/// Create or replace one day's entry (PUT /api/v1/entries/{date}).
+/// Answers 409 with the current entry in `details` if it changed since `revision`.
+async fn put_entry(
+    ApiNotebook(notebook): ApiNotebook,
+    auth: ApiAuth,
+    Path(date): Path<String>,
+    payload: Result<Json<PutEntryRequest>, JsonRejection>,
+) -> ApiResult<serde_json::Value> {
+    auth.require(Scope::WriteEntries)?;
+    let cycle_date = parse_date(&date)?;
+    let Json(request) = payload?;
+
+    if request.override_lock {
+        auth.require(Scope::Admin)?;
+    }
+    crate::journal::check_entry_date_editable(
+        &cycle_date,
+        &CycleDate::today(),
+        crate::config::live().journal.edit_window(request.override_lock),
+    )
+    .map_err(AppError::forbidden)?;
+
+    let journal_manager = &notebook.journal_manager;
+    let now = chrono::Local::now();
+    let entry = crate::journal::JournalEntry {
+        cycle_date,
+        content: request.content,
+        created_at: now,
+        modified_at: now,
+    };
+
+    let saved = journal_manager
+        .save_entry_if_unchanged(&entry, request.revision.as_deref())
+        .await
+        .map_err(|e| e.to_string());
+    match saved {
+        Ok(crate::journal::EntrySaveResult::Saved(revision)) => {
+            tracing::info!("Journal entry saved for {} from {}", cycle_date, auth.0.label());
+            if let Some(prompt_number) = request.prompt_number {
+                if let Err(e) = journal_manager.record_answered_prompt(&cycle_date, prompt_number).await.map_err(|e| e.to_string()) {
+                    tracing::warn!("Failed to record answered prompt: {}", e);
+                }
+            }
+            if let Err(e) = crate::stats::refresh_stats(journal_manager).await {
+                tracing::warn!("Failed to update writing stats: {}", e);
+            }
+            Ok(Json(serde_json::json!({ "revision": revision })))
+        }
+        Ok(crate::journal::EntrySaveResult::Conflict { current, revision }) => {
+            tracing::info!("Entry conflict for {} (client revision {:?}, current {})", cycle_date, request.revision, revision);
+            let conflict = crate::handlers::EntryConflict {
+                current_content: current.content,
+                current_revision: revision,
+                current_modified_at: current.modified_at,
+                submitted_content: entry.content,
+            };
+            Err(AppError::conflict(format!("The entry for {} changed since it was loaded", cycle_date)).with_details(&conflict))
+        }
+        Err(e) => Err(AppError::internal("Error saving entry", e)),
+    }
+}
+
+/// The prompts generated for one day (GET /api/v1/entries/{date}/prompts), or 304 like
+/// `get_entry`
+async fn get_prompts(
+    ApiNotebook(notebook): ApiNotebook,
+    auth: ApiAuth,
+    Path(date): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    auth.require(Scope::ReadPrompts)?;
+    let cycle_date = parse_date(&date)?;
+    let validator = FileValidator::of(&notebook.journal_manager.prompt_files(&cycle_date)).await;
+    if let Some(not_modified) = validator.not_modified(&headers) {
+        return Ok(not_modified);
+    }
+    Ok(validator.apply(Json(notebook.journal_manager.load_all_prompts(&cycle_date).await)))
+}
+
+/// The generated summary of one day's entry (GET /api/v1/entries/{date}/summary)
+async fn get_summary(
+    ApiNotebook(notebook): ApiNotebook,
+    auth: ApiAuth,
+    Path(date): Path<String>,
+) -> ApiResult<crate::journal::JournalSummary> {
+    auth.require(Scope::ReadEntries)?;
+    let cycle_date = parse_date(&date)?;
+    match notebook.journal_manager.load_summary(&cycle_date).await {
+        Ok(Some(summary)) => Ok(Json(summary)),
+        Ok(None) => Err(AppError::not_found(format!("No summary for {}", cycle_date))),
+        Err(e) => Err(AppError::internal("Error loading summary", e)),
+    }
+}
+
+/// Streak, writing, prompt, and chapter statistics (GET /api/v1/stats)
+async fn get_stats(
+    State(app_state): State<AppState>,
+    ApiNotebook(notebook): ApiNotebook,
+    auth: ApiAuth,
+) -> ApiResult<crate::handlers::StatsResponse> {
+    auth.require(Scope::ReadEntries)?;
+    crate::handlers::load_all_stats(&notebook.journal_manager, &app_state.config)
+        .await
+        .map(Json)
+        .map_err(|e| AppError::internal("Error computing statistics", e))
+}
+
+/// Request body for registering a device's push token
+#[derive(Deserialize)]
+struct PushRequest {
+    platform: crate::auth::PushPlatform,
+    token: String,
+}
+
+impl PushRequest {
+    fn into_registration(self) -> crate::auth::PushRegistration {
+        crate::auth::PushRegistration { platform: self.platform, token: self.token, registered_at: chrono::Utc::now() }
+    }
+}
+
+/// The caller's own session; API keys aren't devices
+fn require_session(auth: &ApiAuth) -> Result<&crate::auth::Session, AppError> {
+    match &auth.0 {
+        AuthContext::Session(session) => Ok(session),
+        AuthContext::ApiKey(_) => Err(AppError::bad_request("Only logged-in devices can do this, not API keys")),
+    }
+}
+
+/// Register where to send this device's push notifications (PUT /api/v1/devices/current/push)
+async fn register_push(
+    State(app_state): State<AppState>,
+    auth: ApiAuth,
+    payload: Result<Json<PushRequest>, JsonRejection>,
+) -> Result<StatusCode, AppError> {
+    let session = require_session(&auth)?;
+    let Json(request) = payload?;
+    if request.token.trim().is_empty() {
+        return Err(AppError::bad_request("The push token is empty"));
+    }
+    app_state.auth_manager.set_push_registration(&session.token, Some(request.into_registration())).await;
+    app_state.auth_manager.save_sessions_to_file(&app_state.tokens_file_manager).await;
+    tracing::info!("Registered push notifications for {}", auth.0.label());
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Stop sending this device push notifications (DELETE /api/v1/devices/current/push)
+async fn unregister_push(State(app_state): State<AppState>, auth: ApiAuth) -> Result<StatusCode, AppError> {
+    let session = require_session(&auth)?;
+    app_state.auth_manager.set_push_registration(&session.token, None).await;
+    app_state.auth_manager.save_sessions_to_file(&app_state.tokens_file_manager).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// One of the notebook's personalization files
+#[derive(Serialize)]
+struct PersonalFileResponse {
+    file: &'static str,
+    content: String,
+}
+
+fn parse_personal_file(value: &str) -> Result<crate::personal_files::PersonalFile, AppError> {
+    value.parse().map_err(AppError::bad_request)
+}
+
+/// profile.txt, style.txt, prompts.json, or language.txt as saved (GET /api/v1/personalization/{profile|style|prompts|language})
+async fn get_personal_file(
+    ApiNotebook(notebook): ApiNotebook,
+    auth: ApiAuth,
+    Path(file): Path<String>,
+) -> ApiResult<PersonalFileResponse> {
+    auth.require(Scope::Admin)?;
+    let file = parse_personal_file(&file)?;
+    let content = crate::personal_files::read(&notebook, file)
+        .await
+        .map_err(|e| AppError::internal("Error reading personalization", e))?;
+    Ok(Json(PersonalFileResponse { file: file.file_name(), content }))
+}
+
+/// Request body for replacing a personalization file
+#[derive(Deserialize)]
+struct PutPersonalFileRequest {
+    content: String,
+}
+
+/// Check, save, and reload a personalization file (PUT /api/v1/personalization/{profile|style|prompts|language}).
+/// Answers 400 without saving if the content is refused, e.g. a template missing its placeholders.
+async fn put_personal_file(
+    State(app_state): State<AppState>,
+    ApiNotebook(notebook): ApiNotebook,
+    auth: ApiAuth,
+    Path(file): Path<String>,
+    payload: Result<Json<PutPersonalFileRequest>, JsonRejection>,
+) -> ApiResult<serde_json::Value> {
+    auth.require(Scope::Admin)?;
+    let file = parse_personal_file(&file)?;
+    let Json(request) = payload?;
+    let snapshot_id = crate::personal_files::save(&app_state.config, &notebook, file, &request.content).await?;
+    tracing::info!("{} edited in {} by {}", file.file_name(), notebook.name, auth.0.label());
+    Ok(Json(serde_json::json!({ "snapshot_id": snapshot_id })))
+}
+
+/// The holidays, addressed by position, with the upcoming events as the model sees them
+/// (GET /api/v1/holidays)
+async fn list_holidays(ApiNotebook(notebook): ApiNotebook, auth: ApiAuth) -> ApiResult<crate::holidays::HolidayList> {
+    auth.require(Scope::Admin)?;
+    let holidays = crate::holidays::load(&notebook)
+        .await
+        .map_err(|e| AppError::internal("Error reading holidays", e))?;
+    Ok(Json(crate::holidays::HolidayList { holidays, preview: crate::holidays::preview(&notebook) }))
+}
+
+/// Save a holiday edit and answer with the list as it now is
+async fn edit_holidays(
+    app_state: &AppState,
+    notebook: &Notebook,
+    auth: &ApiAuth,
+    edit: Result<crate::holidays::HolidayEdit, String>,
+) -> ApiResult<crate::holidays::HolidayList> {
+    crate::holidays::save(&app_state.config, notebook, edit.map_err(AppError::bad_request)?).await?;
+    tracing::info!("Holidays edited in {} by {}", notebook.name, auth.0.label());
+    let holidays = crate::holidays::load(notebook)
+        .await
+        .map_err(|e| AppError::internal("Error reading holidays", e))?;
+    Ok(Json(crate::holidays::HolidayList { holidays, preview: crate::holidays::preview(notebook) }))
+}
+
+/// Add a holiday (POST /api/v1/holidays)
+async fn add_holiday(
+    State(app_state): State<AppState>,
+    ApiNotebook(notebook): ApiNotebook,
+    auth: ApiAuth,
+    payload: Result<Json<crate::holidays::HolidayInput>, JsonRejection>,
+) -> ApiResult<crate::holidays::HolidayList> {
+    auth.require(Scope::Admin)?;
+    let Json(input) = payload?;
+    let edit = input.into_holiday().map(crate::holidays::HolidayEdit::Add);
+    edit_holidays(&app_state, &notebook, &auth, edit).await
+}
+
+/// Replace the holiday at a position (PUT /api/v1/holidays/{index})
+async fn update_holiday(
+    State(app_state): State<AppState>,
+    ApiNotebook(notebook): ApiNotebook,
+    auth: ApiAuth,
+    Path(index): Path<usize>,
+    payload: Result<Json<crate::holidays::HolidayInput>, JsonRejection>,
+) -> ApiResult<crate::holidays::HolidayList> {
+    auth.require(Scope::Admin)?;
+    let Json(input) = payload?;
+    let edit = input.into_holiday().map(|holiday| crate::holidays::HolidayEdit::Update(index, holiday));
+    edit_holidays(&app_state, &notebook, &auth, edit).await
+}
+
+/// Remove the holiday at a position (DELETE /api/v1/holidays/{index})
+async fn delete_holiday(
+    State(app_state): State<AppState>,
+    ApiNotebook(notebook): ApiNotebook,
+    auth: ApiAuth,
+    Path(index): Path<usize>,
+) -> ApiResult<crate::holidays::HolidayList> {
+    auth.require(Scope::Admin)?;
+    edit_holidays(&app_state, &notebook, &auth, Ok(crate::holidays::HolidayEdit::Delete(index))).await
+}
+
+/// Failed summaries and prompts waiting to be retried, and those given up on (GET /api/v1/queue)
+async fn get_queue(ApiNotebook(notebook): ApiNotebook, auth: ApiAuth) -> ApiResult<crate::work_queue::QueueState> {
+    auth.require(Scope::Admin)?;
+    Ok(Json(crate::work_queue::queue_of(&notebook)?.state().await))
+}
+
+/// Give a task that was given up on another set of attempts (POST /api/v1/queue/{id}/retry)
+async fn retry_queued(
+    ApiNotebook(notebook): ApiNotebook,
+    auth: ApiAuth,
+    Path(id): Path<String>,
+) -> ApiResult<crate::work_queue::QueueState> {
+    auth.require(Scope::Admin)?;
+    let queue = crate::work_queue::queue_of(&notebook)?;
+    if !queue.revive(&id).await {
+        return Err(AppError::not_found("No given-up task with that id"));
+    }
+    Ok(Json(queue.state().await))
+}
+
+/// Forget a task that was given up on (DELETE /api/v1/queue/{id})
+async fn dismiss_queued(
+    ApiNotebook(notebook): ApiNotebook,
+    auth: ApiAuth,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    auth.require(Scope::Admin)?;
+    if !crate::work_queue::queue_of(&notebook)?.dismiss(&id).await {
+        return Err(AppError::not_found("No given-up task with that id"));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// A logged-in device, without its token
+#[derive(Serialize)]
+struct DeviceInfo {
+    id: String,
+    name: Option<String>,
+    note: Option<String>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    last_used: chrono::DateTime<chrono::Utc>,
+    push_platform: Option<crate::auth::PushPlatform>,
+    /// Whether this is the device asking
+    current: bool,
+}
+
+impl DeviceInfo {
+    /// `current` is the asking device's token, if a device is asking
+    fn new(session: crate::auth::Session, current: Option<&str>) -> Self {
+        DeviceInfo {
+            id: session.device_id(),
+            current: current == Some(session.token.as_str()),
+            name: session.device_name,
+            note: session.note,
+            created_at: session.created_at,
+            last_used: session.last_used,
+            push_platform: session.push.map(|push| push.platform),
+        }
+    }
+}
+
+/// Every logged-in device, most recently used first (GET /api/v1/devices)
+async fn list_devices(State(app_state): State<AppState>, auth: ApiAuth) -> ApiResult<Vec<DeviceInfo>> {
+    auth.require(Scope::Admin)?;
+    let current = match &auth.0 {
+        AuthContext::Session(session) => Some(session.token.as_str()),
+        AuthContext::ApiKey(_) => None,
+    };
+    let devices = app_state.auth_manager.list_sessions().await.into_iter()
+        .map(|session| DeviceInfo::new(session, current))
+        .collect();
+    Ok(Json(devices))
+}
+
+/// Rename a device or change its note (PATCH /api/v1/devices/{id}). Fields left out are
+/// kept; empty ones are cleared.
+async fn update_device(
+    State(app_state): State<AppState>,
+    auth: ApiAuth,
+    Path(id): Path<String>,
+    payload: Result<Json<crate::devices::DeviceDetails>, JsonRejection>,
+) -> ApiResult<DeviceInfo> {
+    auth.require(Scope::Admin)?;
+    let Json(details) = payload?;
+    details.check().map_err(AppError::bad_request)?;
+    let session = app_state.auth_manager.update_device(&id, details).await
+        .ok_or_else(|| AppError::not_found("No device with that id"))?;
+    app_state.auth_manager.save_sessions_to_file(&app_state.tokens_file_manager).await;
+    tracing::info!("{} updated device {}", auth.0.label(), id);
+    let current = match &auth.0 {
+        AuthContext::Session(current) => Some(current.token.as_str()),
+        AuthContext::ApiKey(_) => None,
+    };
+    Ok(Json(DeviceInfo::new(session, current)))
+}
+
+/// Most changes returned by one sync call when the client doesn't say
+const DEFAULT_SYNC_LIMIT: usize = 200;
+/// Most changes returned by one sync call
+const MAX_SYNC_LIMIT: usize = 1000;
+
+/// Query parameters for delta sync
+#[derive(Deserialize)]
+struct SyncQuery {
+    /// Cursor from the previous sync; absent for everything
+    since: Option<String>,
+    limit: Option<usize>,
+}
+
+/// Entries and prompts changed since a cursor, oldest change first
+#[derive(Serialize)]
+struct SyncResponse {
+    entries: Vec<EntryResponse>,
+    prompts: Vec<crate::journal::JournalPrompt>,
+    /// Pass back as `since` on the next sync
+    cursor: String,
+    /// More changes are waiting; sync again right away
+    has_more: bool,
+}
+
+/// Something saved after the sync cursor
+enum Change {
+    Entry(CycleDate),
+    Prompt(CycleDate, u8),
+}
+
+/// Entries and prompts saved since the client's last sync, so a companion app can keep
+/// a local copy without downloading the whole journal each time (GET /api/v1/sync)
+async fn sync_changes(
+    ApiNotebook(notebook): ApiNotebook,
+    auth: ApiAuth,
+    query: Result<Query<SyncQuery>, QueryRejection>,
+) -> ApiResult<SyncResponse> {
+    auth.require(Scope::ReadEntries)?;
+    let Query(params) = query?;
+    let since = params.since.as_deref()
+        .map(|cursor| chrono::DateTime::parse_from_rfc3339(cursor).map(|time| time.with_timezone(&chrono::Local)))
+        .transpose()
+        .map_err(|_| AppError::bad_request("Invalid sync cursor"))?;
+    let limit = params.limit.unwrap_or(DEFAULT_SYNC_LIMIT).clamp(1, MAX_SYNC_LIMIT);
+    let include_prompts = auth.0.has_scope(Scope::ReadPrompts);
+
+    let journal_manager = &notebook.journal_manager;
+    let dates = journal_manager.list_dates().await.map_err(|e| AppError::internal("Error listing entries", e))?;
+    let mut changes = Vec::new();
+    for cycle_date in dates {
+        let metadata = journal_manager.load_metadata(&cycle_date).await.map_err(|e| AppError::internal("Error reading metadata", e))?;
+        let newer = |time: &chrono::DateTime<chrono::Local>| since.is_none_or(|since| *time > since);
+        if let Some(entry) = metadata.entry.filter(|entry| newer(&entry.modified_at)) {
+            changes.push((entry.modified_at, Change::Entry(cycle_date)));
+        }
+        if include_prompts {
+            for (prompt_number, prompt) in metadata.prompts.iter().filter(|(_, prompt)| newer(&prompt.generated_at)) {
+                changes.push((prompt.generated_at, Change::Prompt(cycle_date, *prompt_number)));
+            }
+        }
+    }
+    changes.sort_by_key(|(time, _)| *time);
+    let has_more = changes.len() > limit;
+    changes.truncate(limit);
+
+    let cursor = changes.last().map(|(time, _)| *time).or(since)
+        // In UTC, so the cursor has no "+" to escape in a query string
+        .map(|time| time.with_timezone(&chrono::Utc).to_rfc3339_opts(chrono::SecondsFormat::Nanos, true))
+        .unwrap_or_default();
+    let mut response = SyncResponse { entries: Vec::new(), prompts: Vec::new(), cursor, has_more };
+    for (_, change) in changes {
+        match change {
+            Change::Entry(cycle_date) => {
+                let entry = journal_manager.load_entry(&cycle_date).await.map_err(|e| AppError::internal("Error loading entry", e))?;
+                if let Some(entry) = entry {
+                    let revision = crate::journal::entry_revision(&entry.content);
+                    response.entries.push(EntryResponse { entry, revision });
+                }
+            }
+            Change::Prompt(cycle_date, prompt_number) => {
+                let prompt = journal_manager.load_prompt(&cycle_date, prompt_number).await.map_err(|e| AppError::internal("Error loading prompt", e))?;
+                response.prompts.extend(prompt);
+            }
+        }
+    }
+    Ok(Json(response))
+}
+
+/// Most operations in one batch
+const MAX_BATCH_OPERATIONS: usize = 20;
+
+/// Several reads sent together
+#[derive(Deserialize)]
+struct BatchRequest {
+    operations: Vec<BatchOperation>,
+}
+
+/// One read in a batch: what to fetch, for one `date` or the days `from` through `to`
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum BatchOperation {
+    Entries(BatchRange),
+    Prompts(BatchRange),
+    Summaries(BatchRange),
+}
+
+#[derive(Deserialize)]
+struct BatchRange {
+    date: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+}
+
+impl BatchRange {
+    /// First and last days; at most `export::MAX_RANGE_DAYS` of them
+    fn days(&self) -> Result<(CycleDate, CycleDate), AppError> {
+        let (from, to) = match (&self.date, &self.from, &self.to) {
+            (Some(date), None, None) => {
+                let date = parse_date(date)?;
+                (date, date)
+            }
+            (None, Some(from), Some(to)) => (parse_date(from)?, parse_date(to)?),
+            _ => return Err(AppError::bad_request("Give either date, or both from and to")),
+        };
+        let days = (to.to_real_date() - from.to_real_date()).num_days() + 1;
+        if days < 1 {
+            return Err(AppError::bad_request("The range ends before it starts"));
+        }
+        if days > crate::export::MAX_RANGE_DAYS {
+            return Err(AppError::bad_request(format!("Read at most {} days in one operation", crate::export::MAX_RANGE_DAYS)));
+        }
+        Ok((from, to))
+    }
+}
+
+/// How one operation went: the status and body it would have had as its own request
+#[derive(Serialize)]
+struct BatchResult {
+    status: u16,
+    body: serde_json::Value,
+}
+
+/// Run several reads in one round trip, for clients on slow connections (POST /api/v1/batch).
+/// Operations run in order and succeed or fail on their own; each result carries the status
+/// and body it would have had as a separate request, errors in the usual envelope.
+async fn batch(
+    ApiNotebook(notebook): ApiNotebook,
+    auth: ApiAuth,
+    payload: Result<Json<BatchRequest>, JsonRejection>,
+) -> ApiResult<serde_json::Value> {
+    let Json(request) = payload?;
+    if request.operations.len() > MAX_BATCH_OPERATIONS {
+        return Err(AppError::bad_request(format!("Send at most {} operations in one batch", MAX_BATCH_OPERATIONS)));
+    }
+
+    let mut results = Vec::new();
+    for operation in &request.operations {
+        let result = match run_batch_operation(&notebook, &auth, operation).await {
+            Ok(body) => BatchResult { status: StatusCode::OK.as_u16(), body },
+            Err(e) => BatchResult { status: e.status.as_u16(), body: e.envelope() },
+        };
+        results.push(result);
+    }
+    Ok(Json(serde_json::json!({ "results": results })))
+}
+
+async fn run_batch_operation(notebook: &Notebook, auth: &ApiAuth, operation: &BatchOperation) -> Result<serde_json::Value, AppError> {
+    let journal_manager = &notebook.journal_manager;
+    match operation {
+        BatchOperation::Entries(range) => {
+            auth.require(Scope::ReadEntries)?;
+            let (from, to) = range.days()?;
+            let mut entries = Vec::new();
+            for cycle_date in CycleDate::range(from, to) {
+                let entry = journal_manager.load_entry(&cycle_date).await.map_err(|e| AppError::internal("Error loading entry", e))?;
+                if let Some(entry) = entry {
+                    let revision = crate::journal::entry_revision(&entry.content);
+                    entries.push(EntryResponse { entry, revision });
+                }
+            }
+            serde_json::to_value(entries).map_err(|e| AppError::internal("Error writing entries", e))
+        }
+        BatchOperation::Prompts(range) => {
+            auth.require(Scope::ReadPrompts)?;
+            let (from, to) = range.days()?;
+            let mut prompts = Vec::new();
+            for cycle_date in CycleDate::range(from, to) {
+                prompts.extend(journal_manager.load_all_prompts(&cycle_date).await);
+            }
+            serde_json::to_value(prompts).map_err(|e| AppError::internal("Error writing prompts", e))
+        }
+        BatchOperation::Summaries(range) => {
+            auth.require(Scope::ReadEntries)?;
+            let (from, to) = range.days()?;
+            let mut summaries = Vec::new();
+            for cycle_date in CycleDate::range(from, to) {
+                let summary = journal_manager.load_summary(&cycle_date).await.map_err(|e| AppError::internal("Error loading summary", e))?;
+                summaries.extend(summary);
+            }
+            serde_json::to_value(summaries).map_err(|e| AppError::internal("Error writing summaries", e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("today").unwrap(), CycleDate::today());
+        assert!(parse_date("2025-03-02").is_ok());
+        let error = parse_date("nonsense").unwrap_err();
+        assert_eq!((error.status, error.code), (StatusCode::BAD_REQUEST, "bad_request"));
+    }
+
+    #[test]
+    fn test_cors() {
+        let config = |origins: &[&str], allow_credentials| CorsConfig {
+            allowed_origins: origins.iter().map(|o| o.to_string()).collect(),
+            allow_credentials,
+        };
+        assert!(cors(&config(&[], true)).unwrap().is_none());
+        assert!(cors(&config(&["https://dash.home.example/", "http://localhost:5173"], true)).unwrap().is_some());
+        assert!(cors(&config(&["*"], false)).unwrap().is_some());
+
+        assert!(cors(&config(&["*"], true)).is_err());
+        assert!(cors(&config(&["dash.home.example"], false)).is_err());
+        assert!(cors(&config(&["https://dash.home.example/widget"], false)).is_err());
+    }
+
+    #[test]
+    fn test_allows_origin() {
+        let mut server = crate::config::Config::default().server;
+        server.cors = CorsConfig { allowed_origins: vec!["https://dash.home.example".to_string()], allow_credentials: true };
+        assert!(allows_origin(&server, "https://home.example", &["home.example"]));
+        assert!(allows_origin(&server, "http://localhost:3000", &["localhost:3000"]));
+        assert!(allows_origin(&server, "https://home.example", &["127.0.0.1:3000", "home.example"]));
+        assert!(allows_origin(&server, "https://dash.home.example/", &["home.example"]));
+        assert!(!allows_origin(&server, "https://evil.example", &["home.example"]));
+        assert!(!allows_origin(&server, "https://home.example:8443", &["home.example"]));
+        assert!(!allows_origin(&server, "null", &["home.example"]));
+
+        server.host = "journal.lan".to_string();
+        assert!(allows_origin(&server, "http://journal.lan:3000", &["10.0.0.2:3000"]));
+        server.cors.allow_credentials = false;
+        assert!(!allows_origin(&server, "https://dash.home.example", &["home.example"]));
+    }
+
+    #[test]
+    fn test_batch_range() {
+        let range = |date: Option<&str>, from: Option<&str>, to: Option<&str>| BatchRange {
+            date: date.map(str::to_string),
+            from: from.map(str::to_string),
+            to: to.map(str::to_string),
+        };
+        let today = CycleDate::today();
+        assert_eq!(range(Some("today"), None, None).days().unwrap(), (today, today));
+        let (from, to) = range(None, Some("2025-03-01"), Some("2025-03-31")).days().unwrap();
+        assert_eq!((to.to_real_date() - from.to_real_date()).num_days(), 30);
+
+        assert!(range(None, Some("2025-03-01"), None).days().is_err());
+        assert!(range(Some("today"), Some("2025-03-01"), Some("2025-03-31")).days().is_err());
+        assert_eq!(range(None, Some("2025-03-31"), Some("2025-03-01")).days().unwrap_err().message, "The range ends before it starts");
+        assert!(range(None, Some("2020-01-01"), Some("2025-03-01")).days().is_err());
+
+        let request: BatchRequest = serde_json::from_str(r#"{"operations": [{"op": "entries", "date": "today"}, {"op": "summaries", "from": "2025-03-01", "to": "2025-03-07"}]}"#).unwrap();
+        assert!(matches!(request.operations[..], [BatchOperation::Entries(_), BatchOperation::Summaries(_)]));
+        assert!(serde_json::from_str::<BatchRequest>(r#"{"operations": [{"op": "delete", "date": "today"}]}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sync_returns_offline_entries_written_before_the_cursor() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let notebook = Arc::new(crate::test_support::notebook(dir.path()));
+        let now = chrono::Utc::now();
+        let session = crate::auth::Session {
+            token: "token".to_string(),
+            device_name: None,
+            created_at: now,
+            last_used: now,
+            is_physical_device: false,
+            push: None,
+            theme: crate::theme::Theme::default(),
+            note: None,
+        };
+        let sync = |since: Option<String>| {
+            let notebook = ApiNotebook(Arc::clone(&notebook));
+            let auth = ApiAuth(AuthContext::Session(session.clone()));
+            async move { sync_changes(notebook, auth, Ok(Query(SyncQuery { since, limit: None }))).await.unwrap().0 }
+        };
+
+        let written_at = chrono::Local::now() - chrono::Duration::hours(2);
+        let first = CycleDate::from_real_date(crate::test_support::date(2025, 3, 1));
+        let entry = crate::journal::JournalEntry { cycle_date: first, content: "At home".to_string(), created_at: written_at, modified_at: chrono::Local::now() };
+        notebook.journal_manager.save_entry(&entry).await.unwrap();
+        let cursor = sync(None).await.cursor;
+
+        // Written on a plane before the cursor, synced after it
+        let second = CycleDate::from_real_date(crate::test_support::date(2025, 3, 2));
+        let offline = crate::pwa::OfflineEntry {
+            cycle_date: second.to_string(),
+            content: "On the plane".to_string(),
+            written_at,
+            base_revision: Some(String::new()),
+            base_content: None,
+        };
+        crate::pwa::sync_entry(&notebook.journal_manager, second, &offline).await.unwrap();
+
+        let changes = sync(Some(cursor)).await;
+        let dates: Vec<CycleDate> = changes.entries.iter().map(|synced| synced.entry.cycle_date).collect();
+        assert_eq!(dates, vec![second]);
+        assert_eq!(changes.entries[0].entry.created_at, written_at);
+    }
+}
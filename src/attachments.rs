@@ -0,0 +1,418 @@
+//! Photos and documents attached to a day's entry, uploaded by dropping them onto the
+//! editor. An upload's declared type has to match what its first bytes say it is. Images
+//! get a small JPEG thumbnail in the attachments directory's `.thumbnails` folder, made
+//! again on request if it's missing (after a restore, say).
+
+use axum::{
+    body::Bytes,
+    extract::{DefaultBodyLimit, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{delete, get},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use crate::auth::{AuthContext, Scope};
+use crate::cycle_date::CycleDate;
+use crate::error::AppError;
+use crate::handlers::{app_url, attachment_content_type};
+use crate::journal::JournalManager;
+use crate::notebook::ActiveNotebook;
+use crate::AppState;
+
+/// Largest file accepted, well above a phone photo
+pub const MAX_ATTACHMENT_BYTES: usize = 20 * 1024 * 1024;
+
+/// Folder in a date's attachments directory holding thumbnails, hidden from listings
+const THUMBNAIL_DIR: &str = ".thumbnails";
+
+/// Longest side of a thumbnail, in pixels
+const THUMBNAIL_SIZE: u32 = 320;
+
+/// Types accepted for upload, with the extension they're saved under
+const ACCEPTED_TYPES: [(&str, &str); 5] = [
+    ("image/jpeg", "jpg"),
+    ("image/png", "png"),
+    ("image/gif", "gif"),
+    ("image/webp", "webp"),
+    ("application/pdf", "pdf"),
+];
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/journal/:date/attachments",
+            get(list_attachments).post(upload_attachment).layer(DefaultBodyLimit::max(MAX_ATTACHMENT_BYTES)),
+        )
+        .route("/journal/:date/attachments/:name", delete(delete_attachment))
+        .route("/journal/:date/attachments/:name/thumbnail", get(get_thumbnail))
+}
+
+/// An attached file as listed to the editor
+#[derive(Debug, Serialize)]
+pub struct Attachment {
+    pub name: String,
+    pub content_type: &'static str,
+    pub size: u64,
+    pub url: String,
+    /// Set for images
+    pub thumbnail_url: Option<String>,
+}
+
+impl Attachment {
+    fn new(cycle_date: &CycleDate, name: String, size: u64) -> Self {
+        let content_type = attachment_content_type(&name);
+        let thumbnail_url = content_type
+            .starts_with("image/")
+            .then(|| app_url(&format!("/journal/{}/attachments/{}/thumbnail", cycle_date, name)));
+        Self {
+            url: app_url(&format!("/journal/attachment?date={}&file={}", cycle_date, name)),
+            content_type,
+            size,
+            thumbnail_url,
+            name,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct AttachmentList {
+    pub attachments: Vec<Attachment>,
+}
+
+/// Query parameters for an upload
+#[derive(Deserialize)]
+pub struct UploadQuery {
+    /// The file's original name, kept in simplified form
+    pub name: Option<String>,
+    /// Attach even though the entry is past the edit window (needs the admin scope)
+    #[serde(default)]
+    pub override_lock: bool,
+}
+
+/// Query parameters for a deletion
+#[derive(Deserialize)]
+pub struct DeleteQuery {
+    #[serde(default)]
+    pub override_lock: bool,
+}
+
+/// The accepted type a file's first bytes identify it as
+fn sniff(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if data.starts_with(b"%PDF-") {
+        Some("application/pdf")
+    } else {
+        None
+    }
+}
+
+/// Check an upload's declared type against its contents, returning the extension to
+/// save it under
+fn check_type(declared: &str, data: &[u8]) -> Result<&'static str, AppError> {
+    let declared = declared.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    let Some(&(content_type, extension)) = ACCEPTED_TYPES.iter().find(|(content_type, _)| *content_type == declared) else {
+        return Err(AppError::from_status(StatusCode::UNSUPPORTED_MEDIA_TYPE, "Attachments must be JPEG, PNG, GIF, or WebP images, or PDFs"));
+    };
+    if data.is_empty() {
+        return Err(AppError::bad_request("The file is empty"));
+    }
+    if sniff(data) != Some(content_type) {
+        return Err(AppError::from_status(StatusCode::UNSUPPORTED_MEDIA_TYPE, format!("The file was sent as {} but isn't one", content_type)));
+    }
+    Ok(extension)
+}
+
+/// Name to save an upload under: the letters, digits, dashes, and underscores of the name
+/// it was sent with, or "photo"/"document" and the time, and the extension for its type
+fn file_name(requested: Option<&str>, extension: &str, now: chrono::NaiveTime) -> String {
+    let stem = requested.unwrap_or_default().rsplit(['/', '\\']).next().unwrap_or_default();
+    let stem = stem.rsplit_once('.').map_or(stem, |(stem, _)| stem);
+    let stem: String = stem
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '-' })
+        .collect();
+    let stem: String = stem.split('-').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("-").chars().take(60).collect();
+    let stem = stem.trim_end_matches('-');
+    match stem {
+        "" if extension == "pdf" => format!("document-{}.{}", now.format("%H%M%S"), extension),
+        "" => format!("photo-{}.{}", now.format("%H%M%S"), extension),
+        stem => format!("{}.{}", stem, extension),
+    }
+}
+
+/// Where an attachment's thumbnail is kept
+fn thumbnail_path(journal_manager: &JournalManager, cycle_date: &CycleDate, name: &str) -> Option<PathBuf> {
+    let original = journal_manager.attachment_path(cycle_date, name)?;
+    let file_name = original.file_name()?.to_str()?.to_string();
+    Some(original.parent()?.join(THUMBNAIL_DIR).join(format!("{}.jpg", file_name)))
+}
+
+/// A JPEG no larger than `THUMBNAIL_SIZE` on either side, turned upright by the photo's
+/// EXIF orientation, with any transparency laid over white
+fn make_thumbnail(data: &[u8]) -> Result<Vec<u8>, String> {
+    use image::{DynamicImage, ImageDecoder, ImageReader, Rgb, RgbImage};
+
+    let mut decoder = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|e| e.to_string())?
+        .into_decoder()
+        .map_err(|e| e.to_string())?;
+    let orientation = decoder.orientation().map_err(|e| e.to_string())?;
+    let mut image = DynamicImage::from_decoder(decoder).map_err(|e| e.to_string())?;
+    image.apply_orientation(orientation);
+
+    let thumbnail = image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).to_rgba8();
+    let flattened = RgbImage::from_fn(thumbnail.width(), thumbnail.height(), |x, y| {
+        let [r, g, b, alpha] = thumbnail.get_pixel(x, y).0;
+        let over_white = |channel: u8| ((channel as u32 * alpha as u32 + 255 * (255 - alpha as u32)) / 255) as u8;
+        Rgb([over_white(r), over_white(g), over_white(b)])
+    });
+
+    let mut jpeg = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, 80)
+        .encode_image(&flattened)
+        .map_err(|e| e.to_string())?;
+    Ok(jpeg)
+}
+
+/// Make the thumbnail for an image attachment and save it beside the others
+async fn write_thumbnail(original: PathBuf, thumbnail: PathBuf) -> Result<Vec<u8>, String> {
+    tokio::task::spawn_blocking(move || {
+        let jpeg = make_thumbnail(&std::fs::read(&original).map_err(|e| e.to_string())?)?;
+        if let Some(dir) = thumbnail.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(&thumbnail, &jpeg).map_err(|e| e.to_string())?;
+        Ok(jpeg)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Parse the date in an attachment route
+fn parse_date(date: &str) -> Result<CycleDate, AppError> {
+    CycleDate::from_string(date).map_err(|_| AppError::bad_request(format!("Invalid cycle date '{}'", date)))
+}
+
+/// Refuse changes to a day's attachments past the edit window, as for its entry
+fn check_editable(auth: &AuthContext, cycle_date: &CycleDate, override_lock: bool) -> Result<(), AppError> {
+    if override_lock {
+        auth.require(Scope::Admin).map_err(|(status, message)| AppError::from_status(status, message))?;
+    }
+    crate::journal::check_entry_date_editable(cycle_date, &CycleDate::today(), crate::config::live().journal.edit_window(override_lock))
+        .map_err(AppError::forbidden)
+}
+
+/// The files attached to a day (GET /journal/:date/attachments)
+async fn list_attachments(
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Path(date): Path<String>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::ReadEntries) {
+        return rejection.into_response();
+    }
+    let cycle_date = match parse_date(&date) {
+        Ok(date) => date,
+        Err(e) => return e.into_response(),
+    };
+
+    match notebook.journal_manager.list_attachments(&cycle_date).await {
+        Ok(files) => Json(AttachmentList {
+            attachments: files.into_iter().map(|(name, size)| Attachment::new(&cycle_date, name, size)).collect(),
+        })
+        .into_response(),
+        Err(e) => AppError::internal("Error listing attachments", format!("{}: {}", cycle_date, e)).into_response(),
+    }
+}
+
+/// Attach a photo or PDF to a day (POST /journal/:date/attachments?name=..., body is the
+/// file). Responds 201 with the saved attachment; a taken name gets a number added.
+async fn upload_attachment(
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Path(date): Path<String>,
+    Query(params): Query<UploadQuery>,
+    headers: HeaderMap,
+    data: Bytes,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::WriteEntries) {
+        return rejection.into_response();
+    }
+    let cycle_date = match parse_date(&date) {
+        Ok(date) => date,
+        Err(e) => return e.into_response(),
+    };
+    if let Err(e) = check_editable(&auth, &cycle_date, params.override_lock) {
+        return e.into_response();
+    }
+
+    let declared = headers.get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).unwrap_or_default();
+    let extension = match check_type(declared, &data) {
+        Ok(extension) => extension,
+        Err(e) => return e.into_response(),
+    };
+
+    let journal_manager = &notebook.journal_manager;
+    let filename = file_name(params.name.as_deref(), extension, chrono::Local::now().time());
+    let saved = journal_manager.add_attachment(&cycle_date, &filename, &data).await.map_err(|e| e.to_string());
+    let name = match saved {
+        Ok(name) => name,
+        Err(e) => return AppError::internal("Error saving the attachment", format!("{}: {}", cycle_date, e)).into_response(),
+    };
+
+    // A missing thumbnail is made when it's first asked for, so a failure here isn't fatal
+    if extension != "pdf" {
+        if let (Some(original), Some(thumbnail)) = (journal_manager.attachment_path(&cycle_date, &name), thumbnail_path(journal_manager, &cycle_date, &name)) {
+            if let Err(e) = write_thumbnail(original, thumbnail).await {
+                tracing::warn!("Couldn't make a thumbnail of {} for {}: {}", name, cycle_date, e);
+            }
+        }
+    }
+
+    tracing::info!("Attached {} to {} ({})", name, cycle_date, auth.label());
+    let attachment = Attachment::new(&cycle_date, name, data.len() as u64);
+    (StatusCode::CREATED, [(header::LOCATION, attachment.url.clone())], Json(attachment)).into_response()
+}
+
+/// Remove an attachment and its thumbnail (DELETE /journal/:date/attachments/:name). The
+/// file is snapshotted first. Recordings and scans that entry text was transcribed from
+/// are kept, since the review queue shows them beside it.
+async fn delete_attachment(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Path((date, name)): Path<(String, String)>,
+    Query(params): Query<DeleteQuery>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::WriteEntries) {
+        return rejection.into_response();
+    }
+    let cycle_date = match parse_date(&date) {
+        Ok(date) => date,
+        Err(e) => return e.into_response(),
+    };
+    if let Err(e) = check_editable(&auth, &cycle_date, params.override_lock) {
+        return e.into_response();
+    }
+
+    let journal_manager = &notebook.journal_manager;
+    let Some(path) = journal_manager.attachment_path(&cycle_date, &name) else {
+        return AppError::bad_request("Invalid file name").into_response();
+    };
+    if !path.is_file() {
+        return AppError::not_found(format!("No attachment '{}' on {}", name, cycle_date)).into_response();
+    }
+    let metadata = journal_manager.load_metadata(&cycle_date).await.map_err(|e| e.to_string());
+    let transcribed_from = metadata.ok().and_then(|m| m.entry).and_then(|e| e.transcription).map(|t| t.source_media);
+    if transcribed_from.as_deref() == Some(name.as_str()) {
+        return AppError::conflict(format!("{} is what this day's entry was transcribed from, so it's kept", name)).into_response();
+    }
+
+    let operation = format!("Remove attachment {} from {} in {}", name, cycle_date, notebook.name);
+    if let Err(e) = crate::snapshot::before(&app_state.config, operation, vec![path]).await {
+        return AppError::internal("Could not snapshot the attachment; nothing was changed", e).into_response();
+    }
+    if let Some(thumbnail) = thumbnail_path(journal_manager, &cycle_date, &name) {
+        let _ = tokio::fs::remove_file(thumbnail).await;
+    }
+    let removed = journal_manager.remove_attachment(&cycle_date, &name).await.map_err(|e| e.to_string());
+    match removed {
+        Ok(true) => {
+            tracing::info!("Removed attachment {} from {} ({})", name, cycle_date, auth.label());
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Ok(false) => AppError::not_found(format!("No attachment '{}' on {}", name, cycle_date)).into_response(),
+        Err(e) => AppError::internal("Error removing the attachment", format!("{}: {}", cycle_date, e)).into_response(),
+    }
+}
+
+/// An image attachment's thumbnail (GET /journal/:date/attachments/:name/thumbnail)
+async fn get_thumbnail(
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Path((date, name)): Path<(String, String)>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::ReadEntries) {
+        return rejection.into_response();
+    }
+    let cycle_date = match parse_date(&date) {
+        Ok(date) => date,
+        Err(e) => return e.into_response(),
+    };
+
+    let journal_manager = &notebook.journal_manager;
+    let (Some(original), Some(thumbnail)) = (journal_manager.attachment_path(&cycle_date, &name), thumbnail_path(journal_manager, &cycle_date, &name)) else {
+        return AppError::bad_request("Invalid file name").into_response();
+    };
+    if !original.is_file() {
+        return AppError::not_found("Attachment not found").into_response();
+    }
+    if !attachment_content_type(&name).starts_with("image/") {
+        return AppError::not_found(format!("{} isn't an image, so it has no thumbnail", name)).into_response();
+    }
+
+    let jpeg = match tokio::fs::read(&thumbnail).await {
+        Ok(jpeg) => jpeg,
+        Err(_) => match write_thumbnail(original, thumbnail).await {
+            Ok(jpeg) => jpeg,
+            Err(e) => return AppError::from_status(StatusCode::UNPROCESSABLE_ENTITY, format!("Couldn't make a thumbnail of {}: {}", name, e)).into_response(),
+        },
+    };
+    ([(header::CONTENT_TYPE, "image/jpeg")], jpeg).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A small PNG with a transparent half
+    fn png() -> Vec<u8> {
+        let image = image::RgbaImage::from_fn(800, 400, |x, _| if x < 400 { image::Rgba([200, 40, 40, 255]) } else { image::Rgba([0, 0, 0, 0]) });
+        let mut png = Vec::new();
+        image::DynamicImage::ImageRgba8(image).write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png).unwrap();
+        png
+    }
+
+    #[test]
+    fn test_check_type() {
+        assert_eq!(check_type("image/png", &png()).unwrap(), "png");
+        assert_eq!(check_type("application/pdf", b"%PDF-1.7\n").unwrap(), "pdf");
+        assert_eq!(check_type("image/webp", b"RIFF\x10\0\0\0WEBPVP8 ").unwrap(), "webp");
+
+        // Declared types have to be accepted ones, and match what the bytes are
+        assert_eq!(check_type("text/html", b"<html>").unwrap_err().status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(check_type("image/jpeg", &png()).unwrap_err().status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(check_type("image/jpeg", b"").unwrap_err().status, StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_file_name() {
+        let noon = chrono::NaiveTime::from_hms_opt(12, 30, 5).unwrap();
+        assert_eq!(file_name(Some("IMG_2041.HEIC.jpeg"), "jpg", noon), "IMG_2041-HEIC.jpg");
+        assert_eq!(file_name(Some("Beach day (2).png"), "png", noon), "Beach-day-2.png");
+        assert_eq!(file_name(Some("../../etc"), "png", noon), "etc.png");
+        assert_eq!(file_name(Some("...."), "jpg", noon), "photo-123005.jpg");
+        assert_eq!(file_name(None, "pdf", noon), "document-123005.pdf");
+    }
+
+    #[test]
+    fn test_make_thumbnail() {
+        let thumbnail = image::load_from_memory(&make_thumbnail(&png()).unwrap()).unwrap().to_rgb8();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (THUMBNAIL_SIZE, THUMBNAIL_SIZE / 2));
+        // The transparent half is white, not black
+        assert!(thumbnail.get_pixel(THUMBNAIL_SIZE - 5, 10).0.iter().all(|&c| c > 240));
+        assert!(make_thumbnail(b"%PDF-1.7\n").is_err());
+    }
+}
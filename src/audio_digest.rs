@@ -0,0 +1,277 @@
+use crate::config::SpeechConfig;
+use crate::cycle_date::CycleDate;
+use crate::feed::escape_xml;
+use crate::hooks::shell_command_with_paths;
+use crate::journal::JournalManager;
+use chrono::{DateTime, Local};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tokio::time::{timeout, Duration};
+
+/// Spoken digests in the journal directory, one per day
+const DIGESTS_DIR: &str = "digests";
+/// Days of digests kept, and listed in the podcast feed
+pub const DIGEST_DAYS: usize = 7;
+
+/// A day's spoken digest on disk
+#[derive(Debug, Clone)]
+pub struct Digest {
+    pub cycle_date: CycleDate,
+    pub path: PathBuf,
+    pub bytes: u64,
+    pub created_at: DateTime<Local>,
+}
+
+/// Whether digests can be spoken
+pub fn available(config: &SpeechConfig) -> bool {
+    config.command.as_deref().is_some_and(|c| !c.trim().is_empty())
+}
+
+/// Content type of the audio the speech command writes
+pub fn content_type(config: &SpeechConfig) -> &'static str {
+    match config.format.as_str() {
+        "ogg" | "opus" => "audio/ogg",
+        "m4a" | "aac" => "audio/mp4",
+        "wav" => "audio/wav",
+        _ => "audio/mpeg",
+    }
+}
+
+/// What the digest for `today` says: yesterday's summary, then today's first prompt
+pub async fn script(journal_manager: &JournalManager, today: &CycleDate) -> Result<String, Box<dyn std::error::Error>> {
+    let yesterday = today.previous_day();
+    let summary = journal_manager.load_summary(&yesterday).await?.map(|s| s.summary);
+    let prompt = match journal_manager.prompts_expired(today).await {
+        true => None,
+        false => journal_manager.load_prompt(today, 1).await?.map(|p| p.prompt),
+    };
+    Ok(compose(today, summary.as_deref(), prompt.as_deref()))
+}
+
+/// The digest text, written to be listened to
+fn compose(today: &CycleDate, summary: Option<&str>, prompt: Option<&str>) -> String {
+    let mut script = format!("Good morning. Here's your journal for {}.\n\n", today.to_real_date().format("%A, %B %-d"));
+    match summary {
+        Some(summary) => script.push_str(&format!("Yesterday, in summary. {}\n\n", speakable(summary))),
+        None => script.push_str("There's no summary of yesterday's entry.\n\n"),
+    }
+    match prompt {
+        Some(prompt) => script.push_str(&format!("Today's prompt. {}\n", speakable(prompt))),
+        None => script.push_str("Today's prompts aren't ready yet.\n"),
+    }
+    script
+}
+
+/// Drop Markdown marks a speech engine would read out or stumble on
+fn speakable(text: &str) -> String {
+    text.lines()
+        .map(|line| line.trim_start_matches(['#', '>', '-', '*', ' ']).replace(['*', '_', '`'], ""))
+        .filter(|line| !line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The digest for `today`, speaking it again whenever its text changed (say, once the day's
+/// prompts were generated). Older digests beyond `DIGEST_DAYS` are deleted.
+pub async fn today_digest(config: &SpeechConfig, journal_manager: &JournalManager, today: &CycleDate) -> Result<Digest, String> {
+    let dir = journal_manager.base_path().join(DIGESTS_DIR);
+    let path = digest_path(&dir, config, today);
+    let script_path = dir.join(format!("{}.txt", today));
+    let script = script(journal_manager, today).await.map_err(|e| format!("Failed to write the digest: {}", e))?;
+    let spoken = fs::read_to_string(&script_path).await.ok();
+    if !path.exists() || spoken.as_deref() != Some(script.as_str()) {
+        fs::create_dir_all(&dir).await.map_err(|e| e.to_string())?;
+        speak(config, &script, &path).await?;
+        fs::write(&script_path, &script).await.map_err(|e| e.to_string())?;
+        tracing::info!("Spoke audio digest for {}", today);
+        prune(&dir).await;
+    }
+    load(&dir, config, today).await.ok_or_else(|| "The speech command wrote no audio".to_string())
+}
+
+/// A day's digest if it was spoken
+pub async fn find(config: &SpeechConfig, journal_manager: &JournalManager, cycle_date: &CycleDate) -> Option<Digest> {
+    load(&journal_manager.base_path().join(DIGESTS_DIR), config, cycle_date).await
+}
+
+/// Spoken digests still on disk, newest first
+pub async fn list(config: &SpeechConfig, journal_manager: &JournalManager) -> Vec<Digest> {
+    let dir = journal_manager.base_path().join(DIGESTS_DIR);
+    let mut digests = Vec::new();
+    let Ok(mut entries) = fs::read_dir(&dir).await else {
+        return digests;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(cycle_date) = name.strip_suffix(&format!(".{}", config.format)).and_then(|d| CycleDate::from_string(d).ok()) else {
+            continue;
+        };
+        if let Some(digest) = load(&dir, config, &cycle_date).await {
+            digests.push(digest);
+        }
+    }
+    digests.sort_by_key(|d| std::cmp::Reverse(d.cycle_date.to_real_date()));
+    digests
+}
+
+fn digest_path(dir: &Path, config: &SpeechConfig, cycle_date: &CycleDate) -> PathBuf {
+    dir.join(format!("{}.{}", cycle_date, config.format))
+}
+
+async fn load(dir: &Path, config: &SpeechConfig, cycle_date: &CycleDate) -> Option<Digest> {
+    let path = digest_path(dir, config, cycle_date);
+    let metadata = fs::metadata(&path).await.ok().filter(|m| m.len() > 0)?;
+    Some(Digest {
+        cycle_date: *cycle_date,
+        bytes: metadata.len(),
+        created_at: metadata.modified().ok().map(DateTime::<Local>::from).unwrap_or_else(Local::now),
+        path,
+    })
+}
+
+/// Run the speech command with the script on stdin, writing to a temporary file that's
+/// renamed into place once complete so a half-written digest is never served
+async fn speak(config: &SpeechConfig, script: &str, path: &Path) -> Result<(), String> {
+    let command_line = config.command.as_deref().filter(|c| !c.trim().is_empty()).ok_or("No speech command is configured")?;
+    let partial = path.with_extension(format!("partial.{}", config.format));
+    let mut child = shell_command_with_paths(command_line, &[("file", &partial)])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to start speech command: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(script.as_bytes()).await.map_err(|e| format!("Failed to send the digest to the speech command: {}", e))?;
+    }
+
+    let output = match timeout(Duration::from_secs(config.timeout_seconds), child.wait_with_output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => return Err(format!("Failed to wait for speech command: {}", e)),
+        Err(_) => return Err(format!("Speech command timed out after {}s", config.timeout_seconds)),
+    };
+    if !output.status.success() {
+        let _ = fs::remove_file(&partial).await;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Speech command exited with {}: {}", output.status, stderr.trim()));
+    }
+    fs::rename(&partial, path).await.map_err(|e| format!("The speech command wrote no audio: {}", e))
+}
+
+/// Delete the audio and text of all but the newest `DIGEST_DAYS` digests
+async fn prune(dir: &Path) {
+    let Ok(mut entries) = fs::read_dir(dir).await else {
+        return;
+    };
+    let mut files = Vec::new();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        let date = path.file_stem().and_then(|s| CycleDate::from_string(&s.to_string_lossy()).ok());
+        if let Some(date) = date {
+            files.push((date.to_real_date(), path));
+        }
+    }
+    let mut dates: Vec<_> = files.iter().map(|(date, _)| *date).collect();
+    dates.sort_unstable_by_key(|date| std::cmp::Reverse(*date));
+    dates.dedup();
+    dates.truncate(DIGEST_DAYS);
+    let Some(&oldest_kept) = dates.last() else {
+        return;
+    };
+    for (_, path) in files.into_iter().filter(|(date, _)| *date < oldest_kept) {
+        if let Err(e) = fs::remove_file(&path).await {
+            tracing::warn!("Failed to remove old digest {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Podcast RSS listing the spoken digests, newest first. `base_url` is the server's address
+/// as the podcast app reached it; `key` is appended to each audio link so the app can fetch it.
+pub fn render_podcast(notebook: &str, base_url: &str, key: &str, config: &SpeechConfig, digests: &[Digest]) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<rss version=\"2.0\" xmlns:itunes=\"http://www.itunes.com/dtds/podcast-1.0.dtd\">\n");
+    xml.push_str("  <channel>\n");
+    xml.push_str(&format!("    <title>Journal digest: {}</title>\n", escape_xml(notebook)));
+    xml.push_str(&format!("    <link>{}</link>\n", escape_xml(&format!("{}/journal?notebook={}", base_url, notebook))));
+    xml.push_str("    <description>Yesterday's summary and today's prompt, read aloud each morning</description>\n");
+    xml.push_str("    <itunes:block>Yes</itunes:block>\n");
+
+    for digest in digests {
+        let audio = format!("{}/digest/audio?date={}&key={}&notebook={}", base_url, digest.cycle_date, key, notebook);
+        xml.push_str("    <item>\n");
+        xml.push_str(&format!(
+            "      <title>{}</title>\n",
+            escape_xml(&format!("Digest for {} ({})", digest.cycle_date.to_real_date().format("%A, %B %-d"), digest.cycle_date))
+        ));
+        xml.push_str(&format!("      <guid isPermaLink=\"false\">urn:llm-journal:{}:digest:{}</guid>\n", escape_xml(notebook), digest.cycle_date));
+        xml.push_str(&format!("      <pubDate>{}</pubDate>\n", digest.created_at.to_rfc2822()));
+        xml.push_str(&format!(
+            "      <enclosure url=\"{}\" length=\"{}\" type=\"{}\"/>\n",
+            escape_xml(&audio),
+            digest.bytes,
+            content_type(config)
+        ));
+        xml.push_str("    </item>\n");
+    }
+
+    xml.push_str("  </channel>\n");
+    xml.push_str("</rss>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_compose() {
+        let today = CycleDate::new(1, 2, 1, 3).unwrap();
+        let script = compose(&today, Some("## Highlights\n- A **long** walk by the river"), Some("What surprised you?"));
+        assert!(script.contains("Yesterday, in summary. Highlights A long walk by the river"));
+        assert!(script.contains("Today's prompt. What surprised you?"));
+        assert!(compose(&today, None, None).contains("Today's prompts aren't ready yet."));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_today_digest_speaks_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        let today = CycleDate::new(1, 2, 1, 3).unwrap();
+        let config = SpeechConfig {
+            command: Some("cat > {file}".to_string()),
+            format: "mp3".to_string(),
+            timeout_seconds: 10,
+        };
+
+        let digest = today_digest(&config, &manager, &today).await.unwrap();
+        let spoken = std::fs::read_to_string(&digest.path).unwrap();
+        assert!(spoken.starts_with("Good morning."));
+        assert_eq!(digest.bytes, spoken.len() as u64);
+
+        // Served from disk the second time, even once the command stops working
+        let broken = SpeechConfig { command: Some("false".to_string()), ..config.clone() };
+        assert_eq!(today_digest(&broken, &manager, &today).await.unwrap().path, digest.path);
+        assert_eq!(list(&config, &manager).await.len(), 1);
+
+        // Spoken again once the day's prompt arrives
+        manager.save_prompt(&crate::journal::JournalPrompt {
+            cycle_date: today,
+            prompt: "What surprised you?".to_string(),
+            prompt_number: 1,
+            generated_at: Local::now(),
+            prompt_type: crate::journal::PromptType::Daily,
+        }).await.unwrap();
+        assert!(today_digest(&broken, &manager, &today).await.is_err());
+        let digest = today_digest(&config, &manager, &today).await.unwrap();
+        assert!(std::fs::read_to_string(&digest.path).unwrap().contains("What surprised you?"));
+
+        let xml = render_podcast("default", "http://localhost", "k", &config, &list(&config, &manager).await);
+        assert!(xml.contains(&format!("/digest/audio?date={}&amp;key=k&amp;notebook=default", today)));
+        assert!(xml.contains("type=\"audio/mpeg\""));
+    }
+}
@@ -1,9 +1,17 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+use crate::AppState;
+
 // Forward declare so we can use it in AuthManager
 use crate::file_manager::TokensFileManager;
 
@@ -25,12 +33,80 @@ pub struct Session {
     pub last_used: chrono::DateTime<chrono::Utc>,
     #[serde(default)]
     pub is_physical_device: bool,
+    /// Where to send push notifications for this device, if its app registered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub push: Option<PushRegistration>,
+    /// Colors this device's pages use
+    #[serde(default)]
+    pub theme: crate::theme::Theme,
+    /// Anything to tell the device apart by, like "kitchen tablet"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl Session {
+    /// Stable identifier for the device that doesn't reveal its token
+    pub fn device_id(&self) -> String {
+        use sha2::{Digest, Sha256};
+        hex::encode(&Sha256::digest(self.token.as_bytes())[..8])
+    }
+}
+
+/// Push notification services a companion app can register with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PushPlatform {
+    /// Apple Push Notification service (iOS)
+    Apns,
+    /// Firebase Cloud Messaging (Android)
+    Fcm,
+}
+
+/// A device's push token, as handed out by its platform's push service
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PushRegistration {
+    pub platform: PushPlatform,
+    pub token: String,
+    pub registered_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Permission scopes that can be granted to an API key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Scope {
+    ReadPrompts,
+    ReadEntries,
+    WriteEntries,
+    /// Implies every other scope
+    Admin,
+}
+
+/// A long-lived key for scripts and devices, limited to a set of scopes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub id: String,
+    pub key: String,
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub last_used: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl ApiKey {
+    /// Check whether this key grants a scope
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes.contains(&Scope::Admin) || self.scopes.contains(&scope)
+    }
 }
 
 /// Collection of all persistent sessions
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionsData {
     pub sessions: Vec<Session>,
+    #[serde(default)]
+    pub api_keys: Vec<ApiKey>,
+    #[serde(default)]
+    pub shares: Vec<crate::shares::Share>,
     pub version: u32,
 }
 
@@ -41,12 +117,20 @@ pub struct AuthManager {
     pub pending_auths: Arc<RwLock<HashMap<String, PendingAuth>>>,
     /// Valid session tokens (token -> Session)
     pub sessions: Arc<RwLock<HashMap<String, Session>>>,
+    /// API keys (key -> ApiKey)
+    pub api_keys: Arc<RwLock<HashMap<String, ApiKey>>>,
+    /// Read-only links to a range of entries (token -> Share)
+    pub shares: Arc<RwLock<HashMap<String, crate::shares::Share>>>,
+    /// Whether the sessions file has been read
+    sessions_loaded: std::sync::atomic::AtomicBool,
 }
 
 impl SessionsData {
     pub fn new() -> Self {
         Self {
             sessions: Vec::new(),
+            api_keys: Vec::new(),
+            shares: Vec::new(),
             version: 1,
         }
     }
@@ -60,9 +144,17 @@ impl AuthManager {
         Self {
             pending_auths: Arc::new(RwLock::new(HashMap::new())),
             sessions: Arc::new(RwLock::new(HashMap::new())),
+            api_keys: Arc::new(RwLock::new(HashMap::new())),
+            shares: Arc::new(RwLock::new(HashMap::new())),
+            sessions_loaded: std::sync::atomic::AtomicBool::new(false),
         }
     }
 
+    /// Whether saved sessions have been loaded; until then no device can stay logged in
+    pub fn sessions_loaded(&self) -> bool {
+        self.sessions_loaded.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
     /// Load persistent sessions from SessionsData
     pub async fn load_sessions(&self, sessions_data: &SessionsData) {
         let mut sessions = self.sessions.write().await;
@@ -73,15 +165,36 @@ impl AuthManager {
         }
         
         tracing::info!("Loaded {} persistent device sessions", sessions.len());
+        drop(sessions);
+        
+        let mut api_keys = self.api_keys.write().await;
+        api_keys.clear();
+        for api_key in &sessions_data.api_keys {
+            api_keys.insert(api_key.key.clone(), api_key.clone());
+        }
+        
+        tracing::info!("Loaded {} API keys", api_keys.len());
+        drop(api_keys);
+
+        let mut shares = self.shares.write().await;
+        shares.clear();
+        for share in &sessions_data.shares {
+            shares.insert(share.token.clone(), share.clone());
+        }
+        self.sessions_loaded.store(true, std::sync::atomic::Ordering::Relaxed);
     }
 
     /// Get current sessions as SessionsData for saving
     pub async fn get_sessions_data(&self) -> SessionsData {
         let sessions = self.sessions.read().await;
         let sessions_vec: Vec<Session> = sessions.values().cloned().collect();
+        let api_keys_vec: Vec<ApiKey> = self.api_keys.read().await.values().cloned().collect();
+        let shares_vec: Vec<crate::shares::Share> = self.shares.read().await.values().cloned().collect();
         
         SessionsData {
             sessions: sessions_vec,
+            api_keys: api_keys_vec,
+            shares: shares_vec,
             version: 1,
         }
     }
@@ -144,6 +257,9 @@ impl AuthManager {
                 created_at: now,
                 last_used: now,
                 is_physical_device,
+                push: None,
+                theme: crate::theme::Theme::default(),
+                note: None,
             };
             
             // Remove the used passcode
@@ -182,6 +298,286 @@ impl AuthManager {
     pub async fn remove_session(&self, token: &str) {
         self.sessions.write().await.remove(token);
     }
+
+    /// All logged-in devices, most recently used first
+    pub async fn list_sessions(&self) -> Vec<Session> {
+        let mut sessions: Vec<Session> = self.sessions.read().await.values().cloned().collect();
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.last_used));
+        sessions
+    }
+
+    /// Set or clear a session's push registration; false if the session doesn't exist
+    pub async fn set_push_registration(&self, token: &str, push: Option<PushRegistration>) -> bool {
+        match self.sessions.write().await.get_mut(token) {
+            Some(session) => {
+                session.push = push;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Set a session's theme; false if the session doesn't exist
+    pub async fn set_theme(&self, token: &str, theme: crate::theme::Theme) -> bool {
+        match self.sessions.write().await.get_mut(token) {
+            Some(session) => {
+                session.theme = theme;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Rename or annotate the session with this device id, returning it as changed
+    pub async fn update_device(&self, device_id: &str, details: crate::devices::DeviceDetails) -> Option<Session> {
+        let mut sessions = self.sessions.write().await;
+        let session = sessions.values_mut().find(|session| session.device_id() == device_id)?;
+        details.apply(session);
+        Some(session.clone())
+    }
+}
+
+impl AuthManager {
+    /// Create a new API key with the given scopes
+    pub async fn create_api_key(&self, name: String, scopes: Vec<Scope>) -> ApiKey {
+        let api_key = ApiKey {
+            id: Uuid::new_v4().to_string(),
+            key: generate_secure_passcode(),
+            name,
+            scopes,
+            created_at: chrono::Utc::now(),
+            last_used: None,
+        };
+        
+        self.api_keys.write().await.insert(api_key.key.clone(), api_key.clone());
+        tracing::info!(" New API key created: {} ({:?})", api_key.name, api_key.scopes);
+        api_key
+    }
+
+    /// Validates an API key, returning it (with updated last_used) if valid
+    pub async fn validate_api_key(&self, key: &str) -> Option<ApiKey> {
+        let mut api_keys = self.api_keys.write().await;
+        let api_key = api_keys.get_mut(key)?;
+        api_key.last_used = Some(chrono::Utc::now());
+        Some(api_key.clone())
+    }
+
+    /// List all API keys
+    pub async fn list_api_keys(&self) -> Vec<ApiKey> {
+        let mut keys: Vec<ApiKey> = self.api_keys.read().await.values().cloned().collect();
+        keys.sort_by_key(|k| k.created_at);
+        keys
+    }
+
+    /// Revoke an API key by its id, returning whether it existed
+    pub async fn revoke_api_key(&self, id: &str) -> bool {
+        let mut api_keys = self.api_keys.write().await;
+        let before = api_keys.len();
+        api_keys.retain(|_, k| k.id != id);
+        api_keys.len() != before
+    }
+}
+
+impl AuthManager {
+    /// Add a share of `notebook`'s entries from `from` through `to`, giving out a fresh token
+    pub async fn create_share(&self, notebook: String, input: crate::shares::ShareRange) -> crate::shares::Share {
+        let share = crate::shares::Share::new(Uuid::new_v4().to_string(), generate_secure_passcode(), notebook, input, chrono::Utc::now());
+        self.shares.write().await.insert(share.token.clone(), share.clone());
+        tracing::info!(" New share created: {} ({} to {})", share.label, share.from, share.to);
+        share
+    }
+
+    /// Count a view of the share with this token and return it, or why it can't be viewed
+    pub async fn open_share(&self, token: &str) -> Result<crate::shares::Share, crate::shares::ShareState> {
+        let mut shares = self.shares.write().await;
+        let share = shares.get_mut(token).ok_or(crate::shares::ShareState::Revoked)?;
+        match share.state(chrono::Utc::now()) {
+            crate::shares::ShareState::Open => {
+                share.views += 1;
+                Ok(share.clone())
+            }
+            closed => Err(closed),
+        }
+    }
+
+    /// All shares, newest first
+    pub async fn list_shares(&self) -> Vec<crate::shares::Share> {
+        let mut shares: Vec<crate::shares::Share> = self.shares.read().await.values().cloned().collect();
+        shares.sort_by_key(|share| std::cmp::Reverse(share.created_at));
+        shares
+    }
+
+    /// Revoke a share by its id, returning whether it existed
+    pub async fn revoke_share(&self, id: &str) -> bool {
+        let mut shares = self.shares.write().await;
+        let before = shares.len();
+        shares.retain(|_, share| share.id != id);
+        shares.len() != before
+    }
+}
+
+/// Cookie holding the session token
+pub const SESSION_COOKIE: &str = "session_token";
+
+/// How this instance names and scopes its cookies, so several instances behind one domain
+/// (one per family member, say) each keep their own login and notebook choice
+#[derive(Debug, Clone, PartialEq)]
+pub struct CookieScope {
+    /// Prepended to every cookie name ("alice_" makes "alice_session_token")
+    pub prefix: String,
+    /// Path the cookies are sent for, e.g. "/alice" behind a reverse proxy
+    pub path: String,
+}
+
+impl Default for CookieScope {
+    fn default() -> Self {
+        Self { prefix: String::new(), path: "/".to_string() }
+    }
+}
+
+impl CookieScope {
+    /// The full name of a cookie
+    pub fn name(&self, base: &str) -> String {
+        format!("{}{}", self.prefix, base)
+    }
+
+    /// A cookie's value from request headers
+    pub fn read(&self, headers: &axum::http::HeaderMap, base: &str) -> Option<String> {
+        let name = self.name(base);
+        headers
+            .get(header::COOKIE)
+            .and_then(|cookie| cookie.to_str().ok())
+            .and_then(|cookie_str| {
+                cookie_str
+                    .split(';')
+                    .filter_map(|part| part.trim().split_once('='))
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            })
+    }
+
+    /// A `Set-Cookie` value storing a cookie for `max_age` seconds
+    pub fn set(&self, base: &str, value: &str, max_age: u64, http_only: bool) -> String {
+        let http_only = if http_only { " HttpOnly;" } else { "" };
+        format!("{}={}; Path={};{} SameSite=Strict; Max-Age={}", self.name(base), value, self.path, http_only, max_age)
+    }
+
+    /// A `Set-Cookie` value removing a cookie
+    pub fn clear(&self, base: &str) -> String {
+        format!("{}=; Path={}; HttpOnly; SameSite=Strict; Max-Age=0", self.name(base), self.path)
+    }
+}
+
+static COOKIE_SCOPE: OnceLock<CookieScope> = OnceLock::new();
+
+/// Set how cookies are named and scoped; done once at startup, before serving requests
+pub fn set_cookie_scope(scope: CookieScope) {
+    if COOKIE_SCOPE.set(scope).is_err() {
+        tracing::warn!("The cookie scope was already set; keeping the first one");
+    }
+}
+
+/// The configured cookie scope, or unprefixed cookies for the whole site if none was set
+pub fn cookie_scope() -> &'static CookieScope {
+    COOKIE_SCOPE.get_or_init(CookieScope::default)
+}
+
+/// Identity of an authenticated request, from either a session cookie or an API key
+#[derive(Debug, Clone)]
+pub enum AuthContext {
+    /// Logged-in browser or device session (full access)
+    Session(Session),
+    /// Script or device using an API key (limited to its scopes)
+    ApiKey(ApiKey),
+}
+
+impl AuthContext {
+    /// Check whether this identity grants a scope
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        match self {
+            AuthContext::Session(_) => true,
+            AuthContext::ApiKey(api_key) => api_key.has_scope(scope),
+        }
+    }
+
+    /// Require a scope, producing a 403 rejection if it isn't granted
+    pub fn require(&self, scope: Scope) -> Result<(), (StatusCode, String)> {
+        if self.has_scope(scope) {
+            Ok(())
+        } else {
+            Err((StatusCode::FORBIDDEN, format!("Missing required scope: {:?}", scope)))
+        }
+    }
+
+    /// Human-readable name of the device or key, for logs
+    pub fn label(&self) -> &str {
+        match self {
+            AuthContext::Session(session) => session.device_name.as_deref().unwrap_or("Unknown device"),
+            AuthContext::ApiKey(api_key) => &api_key.name,
+        }
+    }
+}
+
+/// Why a request couldn't be tied to a session or API key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthRejection {
+    /// A key was presented that matches no API key or session
+    InvalidKey,
+    /// No credentials, or an expired session cookie
+    Unauthorized,
+}
+
+impl AuthRejection {
+    pub fn message(self) -> &'static str {
+        match self {
+            AuthRejection::InvalidKey => "Invalid API key",
+            AuthRejection::Unauthorized => "Unauthorized",
+        }
+    }
+}
+
+impl IntoResponse for AuthRejection {
+    fn into_response(self) -> Response {
+        crate::error::AppError::unauthorized(self.message()).into_response()
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for AuthContext {
+    type Rejection = AuthRejection;
+
+    /// Accepts `Authorization: Bearer <key>`, `X-Api-Key: <key>`, or the session cookie.
+    /// The bearer may also be a session token, for devices that log in through the JSON API.
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let api_key = parts.headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .or_else(|| parts.headers.get("X-Api-Key").and_then(|value| value.to_str().ok()));
+
+        if let Some(key) = api_key {
+            let key = key.trim();
+            if let Some(api_key) = state.auth_manager.validate_api_key(key).await {
+                return Ok(AuthContext::ApiKey(api_key));
+            }
+            if state.auth_manager.validate_session(key).await {
+                if let Some(session) = state.auth_manager.get_session_info(key).await {
+                    return Ok(AuthContext::Session(session));
+                }
+            }
+            return Err(AuthRejection::InvalidKey);
+        }
+
+        if let Some(token) = crate::handlers::extract_session_token(&parts.headers) {
+            if state.auth_manager.validate_session(&token).await {
+                if let Some(session) = state.auth_manager.get_session_info(&token).await {
+                    return Ok(AuthContext::Session(session));
+                }
+            }
+        }
+
+        Err(AuthRejection::Unauthorized)
+    }
 }
 
 /// Generates a cryptographically secure 256-bit passcode
@@ -198,3 +594,74 @@ fn generate_secure_passcode() -> String {
     // Let's use hex for better readability in terminal
     hex::encode(bytes)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_api_key_scopes() {
+        let auth_manager = AuthManager::new();
+        let display_key = auth_manager.create_api_key("wall display".to_string(), vec![Scope::ReadPrompts]).await;
+        let admin_key = auth_manager.create_api_key("backup script".to_string(), vec![Scope::Admin]).await;
+        
+        let display = AuthContext::ApiKey(auth_manager.validate_api_key(&display_key.key).await.unwrap());
+        assert!(display.has_scope(Scope::ReadPrompts));
+        assert!(!display.has_scope(Scope::ReadEntries));
+        assert!(display.require(Scope::WriteEntries).is_err());
+        
+        let admin = AuthContext::ApiKey(auth_manager.validate_api_key(&admin_key.key).await.unwrap());
+        assert!(admin.has_scope(Scope::ReadEntries));
+        assert!(admin.has_scope(Scope::WriteEntries));
+    }
+
+    #[tokio::test]
+    async fn test_api_keys_persist_and_revoke() {
+        let auth_manager = AuthManager::new();
+        let api_key = auth_manager.create_api_key("script".to_string(), vec![Scope::ReadEntries]).await;
+        
+        let data = auth_manager.get_sessions_data().await;
+        let restored = AuthManager::new();
+        restored.load_sessions(&data).await;
+        assert!(restored.validate_api_key(&api_key.key).await.is_some());
+        
+        assert!(restored.revoke_api_key(&api_key.id).await);
+        assert!(restored.validate_api_key(&api_key.key).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_push_registration_persists() {
+        let auth_manager = AuthManager::new();
+        let passcode = auth_manager.create_auth_request(Some("phone".to_string()), false).await;
+        let token = auth_manager.authenticate(&passcode, Some("phone".to_string()), false).await.unwrap();
+        let push = PushRegistration { platform: PushPlatform::Apns, token: "apns-token".to_string(), registered_at: chrono::Utc::now() };
+        assert!(auth_manager.set_push_registration(&token, Some(push.clone())).await);
+        assert!(!auth_manager.set_push_registration("no-such-session", None).await);
+
+        let restored = AuthManager::new();
+        restored.load_sessions(&auth_manager.get_sessions_data().await).await;
+        let session = restored.get_session_info(&token).await.unwrap();
+        assert_eq!(session.push, Some(push));
+        assert_eq!(session.device_id().len(), 16);
+        assert!(!token.contains(&session.device_id()));
+    }
+
+    #[test]
+    fn test_cookie_scope() {
+        let scope = CookieScope { prefix: "alice_".to_string(), path: "/alice".to_string() };
+        let mut headers = axum::http::HeaderMap::new();
+        headers.insert(header::COOKIE, "session_token=bob; alice_session_token=alice; notebook=work".parse().unwrap());
+        assert_eq!(scope.read(&headers, SESSION_COOKIE), Some("alice".to_string()));
+        assert_eq!(CookieScope::default().read(&headers, SESSION_COOKIE), Some("bob".to_string()));
+        assert_eq!(scope.read(&headers, "notebook"), None);
+        assert_eq!(scope.set(SESSION_COOKIE, "t", 60, true), "alice_session_token=t; Path=/alice; HttpOnly; SameSite=Strict; Max-Age=60");
+        assert_eq!(scope.set("notebook", "work", 60, false), "alice_notebook=work; Path=/alice; SameSite=Strict; Max-Age=60");
+        assert!(scope.clear(SESSION_COOKIE).starts_with("alice_session_token=; Path=/alice;"));
+    }
+
+    #[test]
+    fn test_sessions_data_without_api_keys_still_loads() {
+        let data: SessionsData = serde_json::from_str(r#"{"sessions": [], "version": 1}"#).unwrap();
+        assert!(data.api_keys.is_empty());
+    }
+}
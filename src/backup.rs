@@ -0,0 +1,318 @@
+use crate::config::Config;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+type BackupResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+const ARCHIVE_PREFIX: &str = "llm-journal-";
+const JOURNAL_DIR_IN_ARCHIVE: &str = "journal";
+const NOTEBOOKS_DIR_IN_ARCHIVE: &str = "notebooks";
+const TOKENS_IN_ARCHIVE: &str = "tokens.json";
+const CONFIG_FILE: &str = "config.toml";
+
+/// Snapshot the journal directory (and any additional notebooks), tokens file, and config into a timestamped zip archive
+/// in the backup destination, then prune old archives beyond the retention count.
+/// Afterwards the archive is pushed to remote storage, if configured.
+pub async fn run_backup(config: &Config) -> BackupResult<PathBuf> {
+    let local_config = config.clone();
+    let archive = tokio::task::spawn_blocking(move || -> BackupResult<PathBuf> {
+        let archive = create_backup(&local_config, Path::new(&local_config.backup.destination))?;
+        let removed = prune_backups(Path::new(&local_config.backup.destination), local_config.backup.keep)?;
+        if removed > 0 {
+            tracing::info!("Removed {} old backups", removed);
+        }
+        Ok(archive)
+    })
+    .await??;
+
+    crate::remote::after_backup(config, &archive).await;
+    Ok(archive)
+}
+
+/// A file in a backup archive, with where it lives on disk
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    pub path: PathBuf,
+    /// Name inside the archive
+    pub name: String,
+}
+
+/// Name for a new backup archive
+pub fn archive_name() -> String {
+    format!("{}{}.zip", ARCHIVE_PREFIX, Local::now().format("%Y%m%d-%H%M%S"))
+}
+
+/// Write a backup archive into `destination`
+pub fn create_backup(config: &Config, destination: &Path) -> BackupResult<PathBuf> {
+    fs::create_dir_all(destination)?;
+    let archive_path = destination.join(archive_name());
+    append_entries(&archive_path, &backup_entries(config)?)?;
+
+    tracing::info!("Backup written to {}", archive_path.display());
+    Ok(archive_path)
+}
+
+/// Every file a backup contains: the journal directory, additional notebooks, tokens file, and config
+pub fn backup_entries(config: &Config) -> BackupResult<Vec<ArchiveEntry>> {
+    let mut entries = Vec::new();
+    let journal_dir = Path::new(&config.journal.journal_directory);
+    if journal_dir.exists() {
+        add_directory(&mut entries, journal_dir, JOURNAL_DIR_IN_ARCHIVE)?;
+    }
+    for notebook in &config.journal.notebooks {
+        let dir = Path::new(&notebook.directory);
+        if dir.exists() {
+            add_directory(&mut entries, dir, &format!("{}/{}", NOTEBOOKS_DIR_IN_ARCHIVE, notebook.name))?;
+        }
+    }
+    add_file_if_exists(&mut entries, Path::new(&config.files.tokens_file), TOKENS_IN_ARCHIVE);
+    add_file_if_exists(&mut entries, Path::new(CONFIG_FILE), CONFIG_FILE);
+    Ok(entries)
+}
+
+fn add_directory(entries: &mut Vec<ArchiveEntry>, dir: &Path, prefix: &str) -> BackupResult<()> {
+    let mut dir_entries: Vec<_> = fs::read_dir(dir)?.collect::<Result<_, _>>()?;
+    dir_entries.sort_by_key(|e| e.file_name());
+
+    for entry in dir_entries {
+        let name = format!("{}/{}", prefix, entry.file_name().to_string_lossy());
+        let path = entry.path();
+        if path.is_dir() {
+            add_directory(entries, &path, &name)?;
+        } else {
+            add_file_if_exists(entries, &path, &name);
+        }
+    }
+    Ok(())
+}
+
+fn add_file_if_exists(entries: &mut Vec<ArchiveEntry>, path: &Path, name: &str) {
+    if path.is_file() {
+        entries.push(ArchiveEntry {
+            path: path.to_path_buf(),
+            name: name.to_string(),
+        });
+    }
+}
+
+/// Add files to an archive, creating it if needed. The archive is complete and readable
+/// after each call, so a large archive can be written a chunk at a time.
+pub fn append_entries(archive_path: &Path, entries: &[ArchiveEntry]) -> BackupResult<()> {
+    let mut zip = if archive_path.exists() {
+        ZipWriter::new_append(File::options().read(true).write(true).open(archive_path)?)?
+    } else {
+        ZipWriter::new(File::options().read(true).write(true).create_new(true).open(archive_path)?)
+    };
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    for entry in entries {
+        // Files can vanish between listing and archiving, e.g. a draft being saved
+        let mut contents = Vec::new();
+        match File::open(&entry.path) {
+            Ok(mut file) => file.read_to_end(&mut contents)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        zip.start_file(entry.name.as_str(), options)?;
+        zip.write_all(&contents)?;
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+/// Backup archives in `destination`, newest first
+pub fn list_backups(destination: &Path) -> BackupResult<Vec<PathBuf>> {
+    if !destination.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut archives: Vec<PathBuf> = fs::read_dir(destination)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .map(|n| n.to_string_lossy())
+                .is_some_and(|n| n.starts_with(ARCHIVE_PREFIX) && n.ends_with(".zip"))
+        })
+        .collect();
+
+    // Timestamped names sort chronologically
+    archives.sort_by(|a, b| b.cmp(a));
+    Ok(archives)
+}
+
+/// Delete all but the newest `keep` archives, returning how many were removed
+pub fn prune_backups(destination: &Path, keep: usize) -> BackupResult<usize> {
+    let archives = list_backups(destination)?;
+    let mut removed = 0;
+    for archive in archives.iter().skip(keep.max(1)) {
+        fs::remove_file(archive)?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// What a restore did
+#[derive(Debug)]
+pub struct RestoreSummary {
+    pub restored: usize,
+    /// Snapshot of the files the restore overwrote, for rolling it back
+    pub snapshot_id: String,
+}
+
+/// Restore a backup archive over the configured journal directory, tokens file, and config.
+/// Files in the archive overwrite existing ones; files not in the archive are left alone.
+/// The files about to be overwritten are snapshotted first.
+pub fn restore_backup(config: &Config, archive_path: &Path) -> BackupResult<RestoreSummary> {
+    let entries = restore_entries(config, archive_path)?;
+    let snapshot = snapshot_before_restore(config, archive_path, &entries)?;
+    let restored = restore_files(archive_path, &entries)?;
+    invalidate_journal_indexes(config);
+
+    tracing::info!("Restored {} files from {} (snapshot {})", restored, archive_path.display(), snapshot);
+    Ok(RestoreSummary {
+        restored,
+        snapshot_id: snapshot,
+    })
+}
+
+/// The files in an archive that a restore writes, with their targets on disk
+pub fn restore_entries(config: &Config, archive_path: &Path) -> BackupResult<Vec<ArchiveEntry>> {
+    let mut archive = ZipArchive::new(File::open(archive_path)?)?;
+    let mut entries = Vec::new();
+    for i in 0..archive.len() {
+        let file = archive.by_index(i)?;
+        if file.is_dir() {
+            continue;
+        }
+        if let Some(path) = restore_target(config, &file) {
+            entries.push(ArchiveEntry {
+                path,
+                name: file.name().to_string(),
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// Snapshot the files a restore will overwrite, returning the snapshot id
+pub fn snapshot_before_restore(config: &Config, archive_path: &Path, entries: &[ArchiveEntry]) -> BackupResult<String> {
+    let overwritten: Vec<PathBuf> = entries.iter().map(|e| e.path.clone()).collect();
+    let archive_name = archive_path.file_name().unwrap_or_default().to_string_lossy();
+    Ok(crate::snapshot::take(config, &format!("Restore {}", archive_name), &overwritten)?.id)
+}
+
+/// Write archive files to their targets, returning how many were written
+pub fn restore_files(archive_path: &Path, entries: &[ArchiveEntry]) -> BackupResult<usize> {
+    let mut archive = ZipArchive::new(File::open(archive_path)?)?;
+    for entry in entries {
+        let mut file = archive.by_name(&entry.name)?;
+        if let Some(parent) = entry.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        io::copy(&mut file, &mut File::create(&entry.path)?)?;
+    }
+    Ok(entries.len())
+}
+
+/// Drop the journal indexes of every notebook after their files were restored, so they're rescanned
+pub fn invalidate_journal_indexes(config: &Config) {
+    for notebook in config.journal.all_notebooks() {
+        crate::journal_index::invalidate(Path::new(&notebook.directory));
+    }
+}
+
+/// Where a file from a backup archive is restored to, or None to skip it
+fn restore_target(config: &Config, file: &zip::read::ZipFile) -> Option<PathBuf> {
+    let journal_dir = Path::new(&config.journal.journal_directory);
+    let Some(name) = file.enclosed_name() else {
+        tracing::warn!("Skipping unsafe path in backup: {}", file.name());
+        return None;
+    };
+
+    if let Ok(relative) = name.strip_prefix(JOURNAL_DIR_IN_ARCHIVE) {
+        Some(journal_dir.join(relative))
+    } else if let Ok(in_notebooks) = name.strip_prefix(NOTEBOOKS_DIR_IN_ARCHIVE) {
+        let notebook = config.journal.notebooks.iter().find_map(|n| {
+            in_notebooks.strip_prefix(&n.name).ok().map(|relative| Path::new(&n.directory).join(relative))
+        });
+        if notebook.is_none() {
+            tracing::warn!("Skipping file from a notebook that isn't configured: {}", name.display());
+        }
+        notebook
+    } else if name == Path::new(TOKENS_IN_ARCHIVE) {
+        Some(PathBuf::from(&config.files.tokens_file))
+    } else if name == Path::new(CONFIG_FILE) {
+        Some(PathBuf::from(CONFIG_FILE))
+    } else {
+        tracing::warn!("Skipping unexpected file in backup: {}", name.display());
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn test_config(root: &Path) -> Config {
+        let mut config = Config::default();
+        config.journal.journal_directory = root.join("journal").to_string_lossy().to_string();
+        config.files.tokens_file = root.join("tokens.json").to_string_lossy().to_string();
+        config.backup.destination = root.join("backups").to_string_lossy().to_string();
+        config.journal.notebooks.push(crate::config::NotebookConfig {
+            name: "work".to_string(),
+            directory: root.join("work").to_string_lossy().to_string(),
+            reflect_before_saving: Vec::new(),
+        });
+        config
+    }
+
+    #[test]
+    fn test_backup_and_restore() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = test_config(temp_dir.path());
+        let entry_dir = Path::new(&config.journal.journal_directory).join("01213");
+        fs::create_dir_all(&entry_dir).unwrap();
+        fs::write(entry_dir.join("entry.txt"), "A quiet morning").unwrap();
+        fs::write(&config.files.tokens_file, "{\"sessions\": [], \"version\": 1}").unwrap();
+        let work_dir = Path::new(&config.journal.notebooks[0].directory).join("01213");
+        fs::create_dir_all(&work_dir).unwrap();
+        fs::write(work_dir.join("entry.txt"), "Shipped the release").unwrap();
+
+        let destination = temp_dir.path().join("backups");
+        let archive = create_backup(&config, &destination).unwrap();
+        assert_eq!(list_backups(&destination).unwrap(), vec![archive.clone()]);
+
+        // Lose the data, then restore it
+        fs::remove_dir_all(&config.journal.journal_directory).unwrap();
+        fs::remove_file(&config.files.tokens_file).unwrap();
+        fs::remove_dir_all(&config.journal.notebooks[0].directory).unwrap();
+        let summary = restore_backup(&config, &archive).unwrap();
+        assert_eq!(summary.restored, 3);
+
+        assert_eq!(fs::read_to_string(entry_dir.join("entry.txt")).unwrap(), "A quiet morning");
+        assert_eq!(fs::read_to_string(work_dir.join("entry.txt")).unwrap(), "Shipped the release");
+        assert!(Path::new(&config.files.tokens_file).exists());
+    }
+
+    #[test]
+    fn test_prune_keeps_newest() {
+        let temp_dir = TempDir::new().unwrap();
+        for stamp in ["20250101-020000", "20250102-020000", "20250103-020000"] {
+            fs::write(temp_dir.path().join(format!("{}{}.zip", ARCHIVE_PREFIX, stamp)), "").unwrap();
+        }
+        fs::write(temp_dir.path().join("notes.txt"), "not a backup").unwrap();
+
+        assert_eq!(prune_backups(temp_dir.path(), 2).unwrap(), 1);
+        let remaining = list_backups(temp_dir.path()).unwrap();
+        assert_eq!(remaining.len(), 2);
+        assert!(remaining[0].ends_with("llm-journal-20250103-020000.zip"));
+        assert!(temp_dir.path().join("notes.txt").exists());
+    }
+}
@@ -0,0 +1,200 @@
+use crate::chapters::Chapter;
+use crate::cycle_date::CycleDate;
+use crate::journal::EntryListing;
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Number of shading levels above "no entry" in the heatmap
+const LEVELS: u8 = 4;
+/// Days in an activity heatmap: one cycle year
+pub const HEATMAP_DAYS: i64 = 364;
+
+/// One day cell of the calendar heatmap
+#[derive(Debug, Clone)]
+pub struct CalendarDay {
+    pub real_date: NaiveDate,
+    pub cycle_date: CycleDate,
+    pub word_count: usize,
+    /// 0 for no entry, 1..=4 shading by word count relative to the busiest day shown
+    pub level: u8,
+    /// Name of the life chapter that starts on this day
+    pub chapter_start: Option<String>,
+}
+
+/// A Gregorian month laid out in Monday-first weeks; `None` pads days outside the month
+#[derive(Debug, Clone)]
+pub struct CalendarMonth {
+    pub year: i32,
+    pub month: u32,
+    pub title: String,
+    pub weeks: Vec<Vec<Option<CalendarDay>>>,
+}
+
+/// Build month grids for the given months, shading days by word count and marking where
+/// chapters start. Shading is relative to the busiest day across all months, so a year view
+/// is comparable.
+pub fn build_months(months: &[(i32, u32)], listings: &[EntryListing], chapters: &[Chapter]) -> Vec<CalendarMonth> {
+    let words_by_date: HashMap<NaiveDate, usize> = listings.iter().map(|l| (l.real_date, l.word_count)).collect();
+    let max_words = months
+        .iter()
+        .flat_map(|&(year, month)| month_days(year, month))
+        .filter_map(|date| words_by_date.get(&date).copied())
+        .max()
+        .unwrap_or(0);
+
+    months
+        .iter()
+        .filter_map(|&(year, month)| {
+            let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+            let mut weeks = Vec::new();
+            let mut week: Vec<Option<CalendarDay>> = vec![None; first.weekday().num_days_from_monday() as usize];
+
+            for real_date in month_days(year, month) {
+                let word_count = words_by_date.get(&real_date).copied().unwrap_or(0);
+                week.push(Some(CalendarDay {
+                    real_date,
+                    cycle_date: CycleDate::from_real_date(real_date),
+                    word_count,
+                    level: intensity_level(word_count, max_words, words_by_date.contains_key(&real_date)),
+                    chapter_start: chapters.iter().find(|c| c.start == real_date).map(|c| c.name.clone()),
+                }));
+                if week.len() == 7 {
+                    weeks.push(std::mem::take(&mut week));
+                }
+            }
+            if !week.is_empty() {
+                week.resize(7, None);
+                weeks.push(week);
+            }
+
+            Some(CalendarMonth {
+                year,
+                month,
+                title: first.format("%B %Y").to_string(),
+                weeks,
+            })
+        })
+        .collect()
+}
+
+/// Per-day writing activity over a span of days, for a GitHub-style heatmap
+#[derive(Debug, Clone, Serialize)]
+pub struct Heatmap {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Highest word count of a day in the span, which level 4 is relative to
+    pub max_words: usize,
+    pub total_words: usize,
+    pub active_days: usize,
+    /// Every day of the span, oldest first
+    pub days: Vec<HeatmapDay>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HeatmapDay {
+    pub date: NaiveDate,
+    pub cycle_date: String,
+    /// Monday is 0, to lay days out in weekday rows
+    pub weekday: u32,
+    pub words: usize,
+    /// 0 for no entry, 1..=4 by word count
+    pub level: u8,
+}
+
+/// Activity for the `HEATMAP_DAYS` days ending on `to`
+pub fn heatmap(listings: &[EntryListing], to: NaiveDate) -> Heatmap {
+    let from = to - Duration::days(HEATMAP_DAYS - 1);
+    let words_by_date: HashMap<NaiveDate, usize> = listings
+        .iter()
+        .filter(|l| l.real_date >= from && l.real_date <= to)
+        .map(|l| (l.real_date, l.word_count))
+        .collect();
+    let max_words = words_by_date.values().copied().max().unwrap_or(0);
+
+    let days = from
+        .iter_days()
+        .take_while(|date| *date <= to)
+        .map(|date| {
+            let words = words_by_date.get(&date).copied().unwrap_or(0);
+            HeatmapDay {
+                date,
+                cycle_date: CycleDate::from_real_date(date).to_string(),
+                weekday: date.weekday().num_days_from_monday(),
+                words,
+                level: intensity_level(words, max_words, words_by_date.contains_key(&date)),
+            }
+        })
+        .collect();
+    Heatmap {
+        from,
+        to,
+        max_words,
+        total_words: words_by_date.values().sum(),
+        active_days: words_by_date.len(),
+        days,
+    }
+}
+
+/// Every date in a Gregorian month
+fn month_days(year: i32, month: u32) -> impl Iterator<Item = NaiveDate> {
+    (1..=31).filter_map(move |day| NaiveDate::from_ymd_opt(year, month, day))
+}
+
+/// Shading level for a day; any entry gets at least level 1
+fn intensity_level(word_count: usize, max_words: usize, has_entry: bool) -> u8 {
+    if !has_entry {
+        return 0;
+    }
+    if max_words == 0 {
+        return 1;
+    }
+    let scaled = (word_count * LEVELS as usize).div_ceil(max_words);
+    scaled.clamp(1, LEVELS as usize) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::listing;
+
+    #[test]
+    fn test_month_layout() {
+        // March 2025 starts on a Saturday and has 31 days
+        let months = build_months(&[(2025, 3)], &[], &crate::chapters::parse("2025-03-31|Spring"));
+        let march = &months[0];
+
+        assert_eq!(march.title, "March 2025");
+        assert_eq!(march.weeks.len(), 6);
+        assert!(march.weeks.iter().all(|w| w.len() == 7));
+        assert!(march.weeks[0][4].is_none());
+        assert_eq!(march.weeks[0][5].as_ref().unwrap().real_date.day(), 1);
+        assert_eq!(march.weeks[5][0].as_ref().unwrap().real_date.day(), 31);
+        assert_eq!(march.weeks[5][0].as_ref().unwrap().chapter_start.as_deref(), Some("Spring"));
+        assert!(march.weeks[4][0].as_ref().unwrap().chapter_start.is_none());
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let to = NaiveDate::from_ymd_opt(2025, 3, 9).unwrap();
+        let listings = [listing(to, 300), listing(to - Duration::days(1), 100), listing(to - Duration::days(HEATMAP_DAYS), 900)];
+        let heatmap = heatmap(&listings, to);
+
+        assert_eq!(heatmap.days.len(), HEATMAP_DAYS as usize);
+        assert_eq!(heatmap.from, to - Duration::days(HEATMAP_DAYS - 1));
+        assert_eq!((heatmap.max_words, heatmap.total_words, heatmap.active_days), (300, 400, 2));
+        let last = heatmap.days.last().unwrap();
+        assert_eq!((last.date, last.weekday, last.level), (to, 6, 4));
+        assert_eq!(heatmap.days[HEATMAP_DAYS as usize - 2].level, 2);
+        assert_eq!(heatmap.days[0].level, 0);
+    }
+
+    #[test]
+    fn test_shading_levels() {
+        let day = |d| NaiveDate::from_ymd_opt(2025, 3, d).unwrap();
+        let months = build_months(&[(2025, 3)], &[listing(day(3), 400), listing(day(4), 100), listing(day(5), 0)], &[]);
+        let levels: Vec<u8> = months[0].weeks[1][..4].iter().map(|d| d.as_ref().unwrap().level).collect();
+
+        assert_eq!(levels, vec![4, 1, 1, 0]);
+    }
+}
@@ -0,0 +1,232 @@
+use crate::chapters::Chapter;
+use crate::cycle_date::CycleDate;
+use crate::embeddings::{cosine_similarity, EmbeddingIndex};
+use crate::journal::JournalManager;
+use crate::status::SECTIONS;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::Path;
+use tokio::fs;
+
+/// Suggestions the user dismissed, in the journal directory
+const DISMISSED_FILE: &str = "chapter_suggestions.json";
+/// Entries compared on each side of a candidate boundary
+const WINDOW: usize = 10;
+/// Days around a candidate boundary in which status changes count toward it
+const STATUS_WINDOW_DAYS: i64 = 7;
+/// Suggestions closer than this to a chapter start, a dismissed suggestion, or each other
+/// are dropped
+const MIN_GAP_DAYS: i64 = 30;
+/// Score a date needs to be suggested: standard deviations its topic shift is above the
+/// journal's usual drift, plus half a point per status section that changed nearby
+const MIN_SCORE: f32 = 2.0;
+/// Suggestions shown at once
+const MAX_SUGGESTIONS: usize = 3;
+
+/// A date where the journal seems to turn a corner
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BoundarySuggestion {
+    pub cycle_date: CycleDate,
+    pub real_date: NaiveDate,
+    /// Whether what the entries talk about shifted
+    pub topic_shift: bool,
+    /// Status sections that changed around the date
+    pub changed_sections: Vec<&'static str>,
+    pub score: f32,
+}
+
+impl BoundarySuggestion {
+    /// "your entries changed a lot around 03A20" with the evidence behind it
+    pub fn message(&self) -> String {
+        let mut reasons = Vec::new();
+        if self.topic_shift {
+            reasons.push("what you write about shifted".to_string());
+        }
+        if !self.changed_sections.is_empty() {
+            reasons.push(format!("your status changed ({})", self.changed_sections.join(", ")));
+        }
+        format!(
+            "Your entries changed a lot around {} ({}): {}. Start a new chapter?",
+            self.cycle_date,
+            self.real_date.format("%B %-d, %Y"),
+            reasons.join(" and ")
+        )
+    }
+}
+
+/// Dates the user said aren't chapter boundaries
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Dismissed {
+    pub dates: BTreeSet<String>,
+}
+
+impl Dismissed {
+    pub async fn load(journal_dir: &Path) -> Self {
+        match fs::read_to_string(journal_dir.join(DISMISSED_FILE)).await {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_default(),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub async fn save(&self, journal_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(journal_dir.join(DISMISSED_FILE), serde_json::to_string_pretty(self)?).await?;
+        Ok(())
+    }
+}
+
+/// Record that `cycle_date` isn't a chapter boundary, so it isn't suggested again
+pub async fn dismiss(journal_dir: &Path, cycle_date: &CycleDate) -> Result<(), Box<dyn std::error::Error>> {
+    let mut dismissed = Dismissed::load(journal_dir).await;
+    dismissed.dates.insert(cycle_date.to_string());
+    dismissed.save(journal_dir).await
+}
+
+/// Suggest chapter boundaries from the journal's entry embeddings and status history
+pub async fn suggest(journal_manager: &JournalManager, chapters: &[Chapter]) -> Result<Vec<BoundarySuggestion>, Box<dyn std::error::Error>> {
+    let journal_dir = journal_manager.base_path();
+    let index = EmbeddingIndex::load(journal_dir).await;
+    let mut topics: Vec<(NaiveDate, Vec<f32>)> = index
+        .entries
+        .iter()
+        .filter_map(|(date, stored)| Some((CycleDate::from_string(date).ok()?.to_real_date(), stored.vector.clone())))
+        .collect();
+    topics.sort_by_key(|(date, _)| *date);
+
+    let statuses = journal_manager.list_statuses().await?;
+    let status_changes: Vec<(NaiveDate, Vec<&'static str>)> = statuses
+        .windows(2)
+        .map(|pair| {
+            let labels = pair[1].status.changes_since(&pair[0].status).into_iter().map(|c| c.label).collect();
+            (pair[1].cycle_date.to_real_date(), labels)
+        })
+        .collect();
+
+    let dismissed = Dismissed::load(journal_dir).await;
+    Ok(find_boundaries(&topics, &status_changes, chapters, &dismissed))
+}
+
+/// Score every entry date by how much the topics of the entries after it differ from those
+/// before it, relative to the journal's usual drift, plus how much of the status changed
+/// nearby. The best-scoring dates away from existing chapters become suggestions.
+pub fn find_boundaries(
+    topics: &[(NaiveDate, Vec<f32>)],
+    status_changes: &[(NaiveDate, Vec<&'static str>)],
+    chapters: &[Chapter],
+    dismissed: &Dismissed,
+) -> Vec<BoundarySuggestion> {
+    let shifts: Vec<(NaiveDate, f32)> = (WINDOW..topics.len().saturating_sub(WINDOW - 1))
+        .filter_map(|i| {
+            let before = centroid(&topics[i - WINDOW..i])?;
+            let after = centroid(&topics[i..i + WINDOW])?;
+            Some((topics[i].0, 1.0 - cosine_similarity(&before, &after)))
+        })
+        .collect();
+    let mean = shifts.iter().map(|(_, s)| s).sum::<f32>() / shifts.len().max(1) as f32;
+    let deviation = (shifts.iter().map(|(_, s)| (s - mean).powi(2)).sum::<f32>() / shifts.len().max(1) as f32).sqrt();
+
+    // A dismissed date rules out the days around it too, as an existing chapter does
+    let blocked: Vec<NaiveDate> = chapters
+        .iter()
+        .map(|c| c.start)
+        .chain(dismissed.dates.iter().filter_map(|d| CycleDate::from_string(d).ok()).map(|d| d.to_real_date()))
+        .collect();
+
+    // Candidates are entry dates with a topic score, plus status-change dates
+    let mut candidates: BTreeSet<NaiveDate> = shifts.iter().map(|(date, _)| *date).collect();
+    candidates.extend(status_changes.iter().map(|(date, _)| *date));
+
+    let mut scored: Vec<BoundarySuggestion> = candidates
+        .into_iter()
+        .map(|date| {
+            let topic_score = shifts
+                .iter()
+                .find(|(d, _)| *d == date)
+                .filter(|_| deviation > f32::EPSILON)
+                .map_or(0.0, |(_, shift)| (shift - mean) / deviation);
+            let changed_sections = sections_changed_near(status_changes, date);
+            let score = topic_score.max(0.0) + changed_sections.len() as f32 * 0.5;
+            BoundarySuggestion {
+                cycle_date: CycleDate::from_real_date(date),
+                real_date: date,
+                topic_shift: topic_score >= 1.0,
+                changed_sections,
+                score,
+            }
+        })
+        .filter(|s| s.score >= MIN_SCORE)
+        .filter(|s| blocked.iter().all(|date| (*date - s.real_date).num_days().abs() >= MIN_GAP_DAYS))
+        .collect();
+
+    scored.sort_by(|a, b| b.score.total_cmp(&a.score));
+    let mut suggestions: Vec<BoundarySuggestion> = Vec::new();
+    for candidate in scored {
+        if suggestions.iter().all(|s| (s.real_date - candidate.real_date).num_days().abs() >= MIN_GAP_DAYS) {
+            suggestions.push(candidate);
+        }
+        if suggestions.len() == MAX_SUGGESTIONS {
+            break;
+        }
+    }
+    suggestions.sort_by_key(|s| s.real_date);
+    suggestions
+}
+
+/// Status sections changed within a week either side of `date`, in status order
+fn sections_changed_near(status_changes: &[(NaiveDate, Vec<&'static str>)], date: NaiveDate) -> Vec<&'static str> {
+    let changed: BTreeSet<&'static str> = status_changes
+        .iter()
+        .filter(|(d, _)| (*d - date).num_days().abs() <= STATUS_WINDOW_DAYS)
+        .flat_map(|(_, labels)| labels.iter().copied())
+        .collect();
+    SECTIONS.iter().map(|(_, label)| *label).filter(|label| changed.contains(label)).collect()
+}
+
+/// Average of the vectors, or None when their lengths disagree
+fn centroid(topics: &[(NaiveDate, Vec<f32>)]) -> Option<Vec<f32>> {
+    let length = topics.first()?.1.len();
+    let mut sum = vec![0.0; length];
+    for (_, vector) in topics {
+        if vector.len() != length {
+            return None;
+        }
+        for (total, value) in sum.iter_mut().zip(vector) {
+            *total += value;
+        }
+    }
+    Some(sum.into_iter().map(|total| total / topics.len() as f32).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_find_boundaries() {
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        // Sixty days about one thing, then sixty about another, with a little noise throughout
+        let topics: Vec<(NaiveDate, Vec<f32>)> = (0..120)
+            .map(|i| {
+                let wobble = (i % 3) as f32 * 0.05;
+                let vector = if i < 60 { vec![1.0, wobble, 0.1] } else { vec![0.1, wobble, 1.0] };
+                (start + Duration::days(i), vector)
+            })
+            .collect();
+        let turn = start + Duration::days(60);
+        let status_changes = vec![(turn + Duration::days(2), vec!["Work", "Relationships"])];
+
+        let suggestions = find_boundaries(&topics, &status_changes, &[], &Dismissed::default());
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].real_date, turn);
+        assert!(suggestions[0].topic_shift);
+        assert_eq!(suggestions[0].changed_sections, vec!["Work", "Relationships"]);
+        assert!(suggestions[0].message().contains("Start a new chapter?"));
+
+        // Not suggested again once dismissed, or when a chapter already starts nearby
+        let dismissed = Dismissed { dates: [CycleDate::from_real_date(turn).to_string()].into() };
+        assert!(find_boundaries(&topics, &status_changes, &[], &dismissed).is_empty());
+        let chapters = crate::chapters::parse(&format!("{}|New job", (turn - Duration::days(5)).format("%Y-%m-%d")));
+        assert!(find_boundaries(&topics, &status_changes, &chapters, &Dismissed::default()).is_empty());
+    }
+}
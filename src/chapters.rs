@@ -0,0 +1,259 @@
+use crate::journal::EntryListing;
+use chrono::{Months, NaiveDate};
+use serde::Serialize;
+use std::path::Path;
+
+/// Chapters file in the journal directory, one chapter per line
+pub const CHAPTERS_FILE: &str = "chapters.txt";
+
+/// A named stretch of life ("Moved to Lisbon", "New job"), running from its start date
+/// until the next chapter starts
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Chapter {
+    pub name: String,
+    pub start: NaiveDate,
+    pub description: Option<String>,
+}
+
+/// Load the chapters declared in a journal directory, oldest first. A missing file has none.
+pub fn load(journal_dir: &Path) -> Vec<Chapter> {
+    match std::fs::read_to_string(journal_dir.join(CHAPTERS_FILE)) {
+        Ok(content) => parse(&content),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Write the commented example chapters file if the journal doesn't have one yet
+pub fn create_default(journal_dir: &Path) -> std::io::Result<()> {
+    let path = journal_dir.join(CHAPTERS_FILE);
+    if path.exists() {
+        return Ok(());
+    }
+    tracing::info!("chapters.txt does not exist, creating with example content");
+    std::fs::write(path, DEFAULT_CONTENT)
+}
+
+/// Add a chapter to the end of the journal's chapters file
+pub fn append(journal_dir: &Path, chapter: &Chapter) -> std::io::Result<()> {
+    use std::io::Write;
+    let clean = |text: &str| text.replace(['|', '\n', '\r'], " ").trim().to_string();
+    let mut line = format!("{}|{}", chapter.start.format("%Y-%m-%d"), clean(&chapter.name));
+    if let Some(description) = &chapter.description {
+        line.push_str(&format!("|{}", clean(description)));
+    }
+
+    let path = journal_dir.join(CHAPTERS_FILE);
+    let needs_newline = std::fs::read_to_string(&path).is_ok_and(|content| !content.is_empty() && !content.ends_with('\n'));
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}{}", if needs_newline { "\n" } else { "" }, line)
+}
+
+/// Parse "YYYY-MM-DD|NAME|DESCRIPTION" lines; the description is optional and malformed
+/// lines are skipped
+pub fn parse(content: &str) -> Vec<Chapter> {
+    let mut chapters: Vec<Chapter> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split('|').map(str::trim).collect();
+            let start = NaiveDate::parse_from_str(parts.first()?, "%Y-%m-%d").ok()?;
+            let name = parts.get(1).filter(|n| !n.is_empty())?.to_string();
+            Some(Chapter {
+                name,
+                start,
+                description: parts.get(2).filter(|d| !d.is_empty()).map(|d| d.to_string()),
+            })
+        })
+        .collect();
+    chapters.sort_by_key(|c| c.start);
+    chapters
+}
+
+/// The chapter `date` falls in, if it's after the first chapter started
+pub fn chapter_at(chapters: &[Chapter], date: NaiveDate) -> Option<&Chapter> {
+    chapters.iter().rev().find(|c| c.start <= date)
+}
+
+/// The day after `chapter` ends, i.e. when the next one starts (None for the current chapter)
+pub fn chapter_end(chapters: &[Chapter], chapter: &Chapter) -> Option<NaiveDate> {
+    chapters.iter().find(|c| c.start > chapter.start).map(|c| c.start)
+}
+
+/// Chapters that overlap `from..=to`, oldest first
+pub fn chapters_between(chapters: &[Chapter], from: NaiveDate, to: NaiveDate) -> Vec<&Chapter> {
+    chapters
+        .iter()
+        .filter(|c| c.start <= to && chapter_end(chapters, c).is_none_or(|end| end > from))
+        .collect()
+}
+
+/// Temporal context naming the chapter `today` is in
+pub fn temporal_context(chapters: &[Chapter], today: NaiveDate) -> Option<String> {
+    let chapter = chapter_at(chapters, today)?;
+    let mut context = format!(
+        "CURRENT LIFE CHAPTER: {} (since {}, {})\n",
+        chapter.name,
+        chapter.start.format("%B %-d, %Y"),
+        describe_length(chapter.start, today),
+    );
+    if let Some(description) = &chapter.description {
+        context.push_str(&format!("{}\n", description));
+    }
+    if let Some(previous) = chapters.iter().rev().find(|c| c.start < chapter.start) {
+        context.push_str(&format!("Previous chapter: {}\n", previous.name));
+    }
+    context.push('\n');
+    Some(context)
+}
+
+/// An instruction framing a yearly reflection around the chapters of the year ending on
+/// `year_end`, when there were any
+pub fn yearly_framing(chapters: &[Chapter], year_end: NaiveDate) -> Option<String> {
+    let year_start = year_end.checked_sub_months(Months::new(12))?;
+    let spans: Vec<String> = chapters_between(chapters, year_start, year_end)
+        .into_iter()
+        .map(|chapter| {
+            let from = chapter.start.max(year_start).format("%B %Y");
+            match chapter_end(chapters, chapter).filter(|end| *end <= year_end) {
+                Some(end) => format!("\"{}\" ({} to {})", chapter.name, from, end.format("%B %Y")),
+                None => format!("\"{}\" (from {})", chapter.name, from),
+            }
+        })
+        .collect();
+    if spans.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "The past year spanned these life chapters: {}. Frame the reflection chapter by chapter, \
+         asking what each one brought and what carried over into the next.",
+        spans.join(", ")
+    ))
+}
+
+/// Writing totals for one chapter
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChapterStats {
+    pub name: String,
+    pub start: NaiveDate,
+    /// The last day of the chapter; None while it's ongoing
+    pub end: Option<NaiveDate>,
+    pub entries: usize,
+    pub words: usize,
+    pub average_words: usize,
+    /// Share of the chapter's days (through today) that have an entry, 0-100
+    pub completion_percent: u32,
+}
+
+/// Split entry totals by chapter, oldest chapter first. Entries before the first chapter
+/// aren't counted.
+pub fn chapter_stats(chapters: &[Chapter], listings: &[EntryListing], today: NaiveDate) -> Vec<ChapterStats> {
+    chapters
+        .iter()
+        .filter(|chapter| chapter.start <= today)
+        .map(|chapter| {
+            let end = chapter_end(chapters, chapter).and_then(|next| next.pred_opt());
+            let last_day = end.map_or(today, |end| end.min(today));
+            let in_chapter: Vec<&EntryListing> = listings
+                .iter()
+                .filter(|l| l.real_date >= chapter.start && l.real_date <= last_day)
+                .collect();
+            let entries = in_chapter.len();
+            let words: usize = in_chapter.iter().map(|l| l.word_count).sum();
+            let days = (last_day - chapter.start).num_days() + 1;
+            ChapterStats {
+                name: chapter.name.clone(),
+                start: chapter.start,
+                end,
+                entries,
+                words,
+                average_words: words.checked_div(entries).unwrap_or(0),
+                completion_percent: (entries as f64 * 100.0 / days as f64).round() as u32,
+            }
+        })
+        .collect()
+}
+
+/// "3 months", "2 years and 1 month", or "12 days" for a chapter still short of a month
+fn describe_length(start: NaiveDate, today: NaiveDate) -> String {
+    let plural = |n: i64, unit: &str| format!("{} {}{}", n, unit, if n == 1 { "" } else { "s" });
+    let mut months = 0;
+    while start.checked_add_months(Months::new(months + 1)).is_some_and(|d| d <= today) {
+        months += 1;
+    }
+    match (months / 12, months % 12) {
+        (0, 0) => plural((today - start).num_days(), "day"),
+        (0, m) => plural(m as i64, "month"),
+        (y, 0) => plural(y as i64, "year"),
+        (y, m) => format!("{} and {}", plural(y as i64, "year"), plural(m as i64, "month")),
+    }
+}
+
+const DEFAULT_CONTENT: &str = r#"# Chapters of your life, used to frame prompts, statistics, and yearly reflections.
+# Each chapter lasts until the next one starts.
+#
+# Format: YYYY-MM-DD|NAME|DESCRIPTION (description optional)
+#
+# Examples:
+# 2023-09-01|Moved to Lisbon|Starting over in a new city and learning Portuguese
+# 2024-05-15|New job at the library
+# 2025-02-10|Parenthood|Our daughter was born
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{date, listing};
+
+    #[test]
+    fn test_parse_and_lookup() {
+        let chapters = parse("# comment\n2025-02-10|Parenthood|Our daughter was born\nnot a chapter\n2023-09-01|Moved to Lisbon\n");
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[0].name, "Moved to Lisbon");
+        assert_eq!(chapters[1].description.as_deref(), Some("Our daughter was born"));
+
+        assert!(chapter_at(&chapters, date(2023, 8, 31)).is_none());
+        assert_eq!(chapter_at(&chapters, date(2025, 2, 9)).unwrap().name, "Moved to Lisbon");
+        assert_eq!(chapter_end(&chapters, &chapters[0]), Some(date(2025, 2, 10)));
+
+        let context = temporal_context(&chapters, date(2026, 5, 20)).unwrap();
+        assert!(context.contains("Parenthood (since February 10, 2025, 1 year and 3 months)"));
+        assert!(context.contains("Previous chapter: Moved to Lisbon"));
+
+        let framing = yearly_framing(&chapters, date(2025, 9, 1)).unwrap();
+        assert!(framing.contains("\"Moved to Lisbon\" (September 2024 to February 2025), \"Parenthood\" (from February 2025)"));
+        assert!(yearly_framing(&chapters, date(2022, 1, 1)).is_none());
+    }
+
+    #[test]
+    fn test_append() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join(CHAPTERS_FILE), "2023-09-01|Moved to Lisbon").unwrap();
+        append(temp_dir.path(), &Chapter {
+            name: "New job | library".to_string(),
+            start: date(2024, 5, 15),
+            description: None,
+        }).unwrap();
+
+        let chapters = load(temp_dir.path());
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[1].name, "New job   library");
+    }
+
+    #[test]
+    fn test_chapter_stats() {
+        let chapters = parse("2025-03-01|Old job\n2025-03-05|New job\n");
+        let listings = vec![
+            listing(date(2025, 2, 28), 50),
+            listing(date(2025, 3, 1), 100),
+            listing(date(2025, 3, 4), 300),
+            listing(date(2025, 3, 6), 200),
+        ];
+        let stats = chapter_stats(&chapters, &listings, date(2025, 3, 8));
+
+        assert_eq!(stats[0].end, Some(date(2025, 3, 4)));
+        assert_eq!((stats[0].entries, stats[0].words, stats[0].average_words, stats[0].completion_percent), (2, 400, 200, 50));
+        assert_eq!(stats[1].end, None);
+        assert_eq!((stats[1].entries, stats[1].completion_percent), (1, 25));
+    }
+}
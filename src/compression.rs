@@ -0,0 +1,162 @@
+//! Smaller, cacheable responses: text bodies are gzipped or brotli-compressed for
+//! clients that accept it, static files may be reused for an hour, and pages are
+//! revalidated on every load. Streams (server-sent events, WebSockets), images, and
+//! small bodies pass through untouched. Endpoints that clients poll use `FileValidator`
+//! to answer 304 before doing any work.
+
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use tower_http::compression::{
+    predicate::{And, DefaultPredicate, Predicate, SizeAbove},
+    CompressionLayer,
+};
+use tower_http::set_header::SetResponseHeaderLayer;
+
+/// Bodies smaller than this aren't worth compressing
+const MIN_COMPRESS_BYTES: u16 = 1024;
+/// Static files change only with an upgrade or a new override, so browsers may reuse them for an hour
+const STATIC_CACHE_CONTROL: &str = "public, max-age=3600";
+/// Pages and polled JSON show live data, so browsers revalidate them on every load
+/// (usually getting a 304)
+const PAGE_CACHE_CONTROL: &str = "private, no-cache";
+
+/// Gzip or brotli, whichever the client prefers, for bodies worth compressing
+pub fn compression() -> CompressionLayer<And<DefaultPredicate, SizeAbove>> {
+    CompressionLayer::new().compress_when(DefaultPredicate::new().and(SizeAbove::new(MIN_COMPRESS_BYTES)))
+}
+
+/// Picks a response's Cache-Control, if it should have one
+type CacheControl<B> = fn(&axum::http::Response<B>) -> Option<HeaderValue>;
+
+/// Cache-Control for static files that were found, unless the response already has one
+pub fn static_cache_control<B>() -> SetResponseHeaderLayer<CacheControl<B>> {
+    SetResponseHeaderLayer::if_not_present(header::CACHE_CONTROL, |response: &axum::http::Response<B>| {
+        response.status().is_success().then(|| HeaderValue::from_static(STATIC_CACHE_CONTROL))
+    })
+}
+
+/// Cache-Control for rendered pages, unless the response already has one
+pub fn page_cache_control() -> SetResponseHeaderLayer<CacheControl<axum::body::Body>> {
+    SetResponseHeaderLayer::if_not_present(header::CACHE_CONTROL, |response: &Response| {
+        let is_page = response.headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|content_type| content_type.starts_with("text/html"));
+        is_page.then(|| HeaderValue::from_static(PAGE_CACHE_CONTROL))
+    })
+}
+
+/// Whether an `If-None-Match` list names `etag` (weak comparison)
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    if_none_match.trim() == "*" || if_none_match.split(',').any(|candidate| opaque(candidate) == opaque(etag))
+}
+
+/// Validators for a response built from files, taken from their sizes and modification
+/// times, so a polled endpoint can answer 304 without reading or serializing anything
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileValidator {
+    etag: String,
+    last_modified: Option<DateTime<Utc>>,
+}
+
+impl FileValidator {
+    /// Missing files count too, so one appearing changes the ETag
+    pub async fn of(paths: &[PathBuf]) -> Self {
+        let mut hasher = Sha256::new();
+        let mut last_modified = None;
+        for path in paths {
+            hasher.update(path.as_os_str().as_encoded_bytes());
+            match tokio::fs::metadata(path).await.and_then(|metadata| Ok((metadata.len(), metadata.modified()?))) {
+                Ok((len, modified)) => {
+                    let modified = DateTime::<Utc>::from(modified);
+                    hasher.update(len.to_le_bytes());
+                    hasher.update(modified.timestamp_nanos_opt().unwrap_or_default().to_le_bytes());
+                    last_modified = last_modified.max(Some(modified));
+                }
+                Err(_) => hasher.update(b"missing"),
+            }
+        }
+        Self {
+            etag: format!("W/\"f{}\"", hex::encode(&hasher.finalize()[..16])),
+            last_modified,
+        }
+    }
+
+    /// 304 Not Modified when the request's `If-None-Match`, or without one its
+    /// `If-Modified-Since`, shows the client already has this version
+    pub fn not_modified(&self, headers: &HeaderMap) -> Option<Response> {
+        let current = match headers.get(header::IF_NONE_MATCH) {
+            Some(if_none_match) => etag_matches(if_none_match.to_str().ok()?, &self.etag),
+            None => {
+                let since = headers.get(header::IF_MODIFIED_SINCE)?.to_str().ok()?;
+                let since = DateTime::parse_from_rfc2822(since).ok()?;
+                self.last_modified.is_some_and(|modified| modified.timestamp() <= since.timestamp())
+            }
+        };
+        current.then(|| self.apply(StatusCode::NOT_MODIFIED))
+    }
+
+    /// Add the validators to a response, telling browsers to check back each time rather
+    /// than reuse it
+    pub fn apply(&self, response: impl IntoResponse) -> Response {
+        let mut response = response.into_response();
+        let headers = response.headers_mut();
+        if let Ok(etag) = HeaderValue::from_str(&self.etag) {
+            headers.insert(header::ETAG, etag);
+        }
+        if let Some(modified) = self.last_modified {
+            if let Ok(modified) = HeaderValue::from_str(&modified.format("%a, %d %b %Y %H:%M:%S GMT").to_string()) {
+                headers.insert(header::LAST_MODIFIED, modified);
+            }
+        }
+        headers.insert(header::CACHE_CONTROL, HeaderValue::from_static(PAGE_CACHE_CONTROL));
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_etag_matches() {
+        let tag = "W/\"f0123\"";
+        assert!(etag_matches(tag, tag));
+        assert!(etag_matches("\"other\", \"f0123\"", tag));
+        assert!(etag_matches("*", tag));
+        assert!(!etag_matches("\"other\"", tag));
+    }
+
+    #[tokio::test]
+    async fn test_file_validator() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (entry, prompt) = (temp_dir.path().join("entry.txt"), temp_dir.path().join("prompt1.txt"));
+        std::fs::write(&entry, "Morning pages").unwrap();
+        let paths = [entry.clone(), prompt.clone()];
+        let validator = FileValidator::of(&paths).await;
+        assert_eq!(FileValidator::of(&paths).await, validator);
+
+        let response = validator.apply(StatusCode::OK);
+        let mut headers = HeaderMap::new();
+        assert!(validator.not_modified(&headers).is_none());
+        headers.insert(header::IF_NONE_MATCH, response.headers()[header::ETAG].clone());
+        assert_eq!(validator.not_modified(&headers).unwrap().status(), StatusCode::NOT_MODIFIED);
+
+        // Without an ETag, the modification time decides
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_MODIFIED_SINCE, response.headers()[header::LAST_MODIFIED].clone());
+        assert!(validator.not_modified(&headers).is_some());
+        headers.insert(header::IF_MODIFIED_SINCE, HeaderValue::from_static("Sun, 06 Nov 1994 08:49:37 GMT"));
+        assert!(validator.not_modified(&headers).is_none());
+
+        // A file appearing is a change
+        std::fs::write(&prompt, "What surprised you today?").unwrap();
+        assert_ne!(FileValidator::of(&paths).await, validator);
+    }
+}
@@ -1,5 +1,23 @@
 use serde::Deserialize;
 use std::fs;
+use std::sync::{Arc, RwLock};
+
+/// The configuration file, in the working directory
+pub const CONFIG_FILE: &str = "config.toml";
+
+/// The configuration as last saved from the settings page, for settings that apply
+/// without a restart; everything else keeps reading the configuration it started with
+static LIVE: RwLock<Option<Arc<Config>>> = RwLock::new(None);
+
+/// Make `config` the live configuration; done at startup and whenever settings are saved
+pub fn set_live(config: Arc<Config>) {
+    *LIVE.write().unwrap() = Some(config);
+}
+
+/// The live configuration (the defaults if none was set)
+pub fn live() -> Arc<Config> {
+    LIVE.read().unwrap().clone().unwrap_or_else(|| Arc::new(Config::default()))
+}
 
 /// Application configuration
 #[derive(Debug, Clone, Deserialize)]
@@ -14,6 +32,39 @@ pub struct Config {
     pub journal: JournalConfig,
     /// LLM settings
     pub llm: LlmConfig,
+    /// Local automation hooks
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    /// Receipt printer output
+    #[serde(default)]
+    pub printer: PrinterConfig,
+    /// Scheduled backups
+    #[serde(default)]
+    pub backup: BackupConfig,
+    /// Encrypted off-site copies of backups and the journal
+    #[serde(default)]
+    pub remote: RemoteConfig,
+    /// Git history for the journal directory
+    #[serde(default)]
+    pub git: GitConfig,
+    /// Public prompt-of-the-day endpoint
+    #[serde(default)]
+    pub guest: GuestConfig,
+    /// Journal entries sent in by email
+    #[serde(default)]
+    pub email_in: EmailInConfig,
+    /// Speech-to-text for voice notes
+    #[serde(default)]
+    pub transcription: TranscriptionConfig,
+    /// Text-to-speech for the morning audio digest
+    #[serde(default)]
+    pub speech: SpeechConfig,
+    /// How long machine-generated artifacts are kept
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    /// The book compiled from each finished year
+    #[serde(default)]
+    pub year_review: YearReviewConfig,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -22,6 +73,131 @@ pub struct ServerConfig {
     pub port: u16,
     /// Host to bind to
     pub host: String,
+    /// Prepended to cookie names, so instances sharing a domain don't log each other out
+    #[serde(default)]
+    pub cookie_prefix: String,
+    /// Path the app is served under when a reverse proxy mounts it below the domain root,
+    /// e.g. "/journal" for https://home.example/journal ("" at the root)
+    #[serde(default)]
+    pub base_path: String,
+    /// Path cookies are scoped to, e.g. "/alice" when a reverse proxy serves this instance there
+    /// (`base_path`, or "/" if that isn't set)
+    #[serde(default)]
+    pub cookie_path: Option<String>,
+    /// Language real dates are written in on pages and in prompts ("en-US", "de", "fr", ...)
+    #[serde(default = "default_locale")]
+    pub locale: String,
+    /// Directory static files are served from
+    #[serde(default = "default_static_dir")]
+    pub static_dir: String,
+    /// Largest request body accepted, in KB (voice notes have their own limit)
+    #[serde(default = "default_max_body_kb")]
+    pub max_body_kb: usize,
+    /// Requests per minute accepted from one address (0 for no limit)
+    #[serde(default = "default_requests_per_minute")]
+    pub requests_per_minute: u32,
+    /// Requests per minute to expensive routes (prompt generation, transcription, search,
+    /// exports, logins) accepted from one address or session (0 for no limit)
+    #[serde(default = "default_expensive_requests_per_minute")]
+    pub expensive_requests_per_minute: u32,
+    /// Addresses of the reverse proxies in front of this instance. Requests they pass on
+    /// are limited by the client address in `forwarded_header`, not the proxy's.
+    #[serde(default)]
+    pub trusted_proxies: Vec<std::net::IpAddr>,
+    /// Header a trusted proxy puts the client's address in
+    #[serde(default = "default_forwarded_header")]
+    pub forwarded_header: String,
+    /// File each request is logged to as a JSON line ("" to turn the log off)
+    #[serde(default = "default_access_log")]
+    pub access_log: String,
+    /// Size, in KB, at which the access log is rotated
+    #[serde(default = "default_access_log_max_kb")]
+    pub access_log_max_kb: u64,
+    /// Directory of customizations: files in its `static/` shadow the built-in ones, and
+    /// `templates/head.html` and `templates/footer.html` are added to every page
+    #[serde(default)]
+    pub override_dir: Option<String>,
+    /// Which other sites' pages may call the JSON API from the browser (`[server.cors]`)
+    #[serde(default)]
+    pub cors: CorsConfig,
+}
+
+/// Browser access to the JSON API (`/api/...`) from pages served elsewhere, like a
+/// separately hosted app or a dashboard widget. Off until origins are listed.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CorsConfig {
+    /// Origins allowed to call the API, e.g. "https://dash.home.example"; "*" allows any
+    /// (but not with credentials)
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+    /// Let those origins send the session cookie, not only an Authorization header
+    #[serde(default)]
+    pub allow_credentials: bool,
+}
+
+fn default_locale() -> String {
+    "en-US".to_string()
+}
+
+fn default_static_dir() -> String {
+    "static".to_string()
+}
+
+fn default_max_body_kb() -> usize {
+    2048
+}
+
+fn default_requests_per_minute() -> u32 {
+    600
+}
+
+fn default_expensive_requests_per_minute() -> u32 {
+    20
+}
+
+fn default_forwarded_header() -> String {
+    "X-Forwarded-For".to_string()
+}
+
+fn default_access_log() -> String {
+    "access.log".to_string()
+}
+
+fn default_access_log_max_kb() -> u64 {
+    1024
+}
+
+impl ServerConfig {
+    /// `max_body_kb` in bytes
+    pub fn max_body_bytes(&self) -> usize {
+        self.max_body_kb.max(1).saturating_mul(1024)
+    }
+
+    /// `base_path` without its trailing slash, so "/journal/" and "/journal" both give
+    /// "/journal" and "/" gives ""
+    pub fn base_path(&self) -> Result<String, String> {
+        let path = self.base_path.trim_end_matches('/');
+        let allowed = |c: char| c.is_ascii_alphanumeric() || matches!(c, '/' | '-' | '_' | '.' | '~');
+        if !path.is_empty() && (!path.starts_with('/') || !path.chars().all(allowed) || path.contains("//")) {
+            return Err(format!("base_path '{}' must be a path like \"/journal\"", self.base_path));
+        }
+        Ok(path.to_string())
+    }
+
+    /// How cookies are named and scoped, per `cookie_prefix` and `cookie_path`
+    pub fn cookie_scope(&self) -> Result<crate::auth::CookieScope, String> {
+        if !self.cookie_prefix.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_')) {
+            return Err(format!("cookie_prefix '{}' may only use letters, digits, '-', and '_'", self.cookie_prefix));
+        }
+        let path = match &self.cookie_path {
+            Some(path) => path.clone(),
+            None => Some(self.base_path()?).filter(|base| !base.is_empty()).unwrap_or_else(|| "/".to_string()),
+        };
+        if !path.starts_with('/') || path.contains([';', ' ']) {
+            return Err(format!("cookie_path '{}' must be a path starting with '/'", path));
+        }
+        Ok(crate::auth::CookieScope { prefix: self.cookie_prefix.clone(), path })
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -46,8 +222,165 @@ pub struct JournalConfig {
     pub processing_time: String,
     /// Time to generate daily prompts (in 24-hour format, e.g., "06:00")
     pub prompt_generation_time: String,
+    /// IANA timezone dates and scheduled times are reckoned in, e.g. "America/Chicago"
+    /// (the server's local time if not set)
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Time the journal day ends (24-hour format); writing before it counts toward the
+    /// previous date, so "03:00" keeps late nights with the evening they started
+    #[serde(default = "default_day_ends_at")]
+    pub day_ends_at: String,
+    /// "cycle" (13 months of 4 weeks, the default), "gregorian" (calendar months and ISO weeks),
+    /// or "iso-week" (YYYY-Www-D week dates)
+    #[serde(default)]
+    pub calendar: crate::cycle_date::CalendarMode,
+    /// Give cycle years a leap week (week 4 of month C) every five or six years, so each
+    /// year starts within half a week of the same time of year
+    #[serde(default)]
+    pub leap_weeks: bool,
+    /// Months per year, weeks per month, days per week, and the start of the cycle calendar
+    /// (`[journal.cycle]`; 13 months of 4 weeks of 7 days from January 7, 2024 by default)
+    #[serde(default)]
+    pub cycle: crate::cycle_date::CycleShape,
+    /// Names for the cycle months, one per month (empty for "Month 0" to "Month C")
+    #[serde(default)]
+    pub month_names: Vec<String>,
+    /// Names for the weekdays, Sunday first (empty for the usual ones)
+    #[serde(default)]
+    pub day_names: Vec<String>,
+    /// Generate prompts shortly before the time entries usually get started, learned from
+    /// recent writing, instead of at `prompt_generation_time` (which remains the fallback)
+    #[serde(default)]
+    pub adaptive_prompt_time: bool,
+    /// How many minutes before the usual writing time prompts are suggested
+    #[serde(default = "default_prompt_lead_minutes")]
+    pub prompt_lead_minutes: u32,
     /// Maximum number of prompts to generate per day
     pub max_prompts_per_day: u8,
+    /// How many days back entries may still be saved; older entries are read-only unless an
+    /// admin explicitly overrides the lock (unlimited if not set)
+    #[serde(default)]
+    pub max_edit_age_days: Option<u32>,
+    /// Transcriptions (OCR/speech-to-text) below this confidence wait for review before summarizing
+    #[serde(default = "default_transcription_review_threshold")]
+    pub transcription_review_threshold: f32,
+    /// Unanswered prompts older than this many days are marked expired and hidden (never if not set)
+    #[serde(default)]
+    pub prompt_expiry_days: Option<u32>,
+    /// Mention the current writing streak in the prompt context for encouragement
+    #[serde(default)]
+    pub streak_in_prompt_context: bool,
+    /// Weave the home page's "memory lane" entry into the day's prompt context
+    #[serde(default)]
+    pub memory_lane_in_prompt_context: bool,
+    /// How many past days of prompts new prompts are compared against (0 disables deduplication)
+    #[serde(default = "default_dedup_window_days")]
+    pub dedup_window_days: u32,
+    /// Similarity (0.0-1.0) above which a new prompt counts as a repeat and is regenerated
+    #[serde(default = "default_dedup_threshold")]
+    pub dedup_threshold: f64,
+    /// How many times to regenerate a repeated prompt before keeping the most distinct one
+    #[serde(default = "default_dedup_max_retries")]
+    pub dedup_max_retries: u8,
+    /// Name of the notebook stored in `journal_directory`
+    #[serde(default = "default_notebook_name")]
+    pub notebook_name: String,
+    /// Day types ("daily", "weekly", "monthly", "yearly") on which the default notebook asks
+    /// one reflection question before an entry is saved (never if empty)
+    #[serde(default)]
+    pub reflect_before_saving: Vec<String>,
+    /// Additional notebooks, each with its own directory, prompts, and personalization
+    #[serde(default)]
+    pub notebooks: Vec<NotebookConfig>,
+}
+
+fn default_day_ends_at() -> String {
+    "00:00".to_string()
+}
+
+fn default_prompt_lead_minutes() -> u32 {
+    60
+}
+
+fn default_transcription_review_threshold() -> f32 {
+    0.85
+}
+
+fn default_dedup_window_days() -> u32 {
+    14
+}
+
+fn default_dedup_threshold() -> f64 {
+    0.6
+}
+
+fn default_dedup_max_retries() -> u8 {
+    2
+}
+
+fn default_notebook_name() -> String {
+    "personal".to_string()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotebookConfig {
+    /// Name used in the UI and the `notebook` query parameter (letters, digits, `-`, `_`)
+    pub name: String,
+    /// Directory holding this notebook's entries, prompts.json, and profile
+    pub directory: String,
+    /// Day types on which this notebook asks a reflection question before saving
+    #[serde(default)]
+    pub reflect_before_saving: Vec<String>,
+}
+
+impl JournalConfig {
+    /// The clock "today" is read from, per `timezone` and `day_ends_at`
+    pub fn day_clock(&self) -> Result<crate::cycle_date::DayClock, String> {
+        let timezone = match self.timezone.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+            Some(name) => Some(name.parse::<chrono_tz::Tz>().map_err(|e| format!("Unknown timezone '{}': {}", name, e))?),
+            None => None,
+        };
+        let day_ends_at = chrono::NaiveTime::parse_from_str(&self.day_ends_at, "%H:%M")
+            .map_err(|e| format!("Invalid day_ends_at '{}': {}", self.day_ends_at, e))?;
+        Ok(crate::cycle_date::DayClock { timezone, day_ends_at })
+    }
+
+    /// The month and weekday names dates are described with, per `month_names` and `day_names`
+    pub fn date_names(&self) -> Result<crate::cycle_date::DateNames, String> {
+        let months = self.cycle.months_per_year as usize;
+        if !self.month_names.is_empty() && self.month_names.len() != months {
+            return Err(format!("month_names has {} names; it needs all {} (or none)", self.month_names.len(), months));
+        }
+        if !self.day_names.is_empty() && self.day_names.len() != 7 {
+            return Err(format!("day_names has {} names; it needs all 7 (or none)", self.day_names.len()));
+        }
+        Ok(crate::cycle_date::DateNames { months: self.month_names.clone(), days: self.day_names.clone() })
+    }
+
+    /// Every notebook as (name, directory), the default one first
+    pub fn all_notebooks(&self) -> Vec<NotebookConfig> {
+        let mut notebooks = vec![NotebookConfig {
+            name: self.notebook_name.clone(),
+            directory: self.journal_directory.clone(),
+            reflect_before_saving: self.reflect_before_saving.clone(),
+        }];
+        notebooks.extend(self.notebooks.iter().cloned());
+        notebooks
+    }
+
+    /// The edit window to enforce on a save, lifted when an admin explicitly overrides the lock
+    pub fn edit_window(&self, admin_override: bool) -> Option<u32> {
+        self.max_edit_age_days.filter(|_| !admin_override)
+    }
+
+    /// Whether `notebook` asks a reflection question before saving entries of `prompt_type`.
+    /// Unrecognized day types in the config are ignored.
+    pub fn reflects_before_saving(&self, notebook: &str, prompt_type: &crate::journal::PromptType) -> bool {
+        self.all_notebooks()
+            .into_iter()
+            .find(|n| n.name == notebook)
+            .is_some_and(|n| n.reflect_before_saving.iter().any(|t| t.parse::<crate::journal::PromptType>().as_ref() == Ok(prompt_type)))
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -60,6 +393,287 @@ pub struct LlmConfig {
     pub temperature: f32,
     /// Maximum tokens to generate
     pub max_tokens: usize,
+    /// Ollama model used to embed entries for semantic search and related entries
+    #[serde(default = "default_embedding_model")]
+    pub embedding_model: String,
+    /// Second Ollama model that also writes a candidate for every prompt
+    #[serde(default)]
+    pub ensemble_model: Option<String>,
+    /// What to do with the two candidates when `ensemble_model` is set
+    #[serde(default)]
+    pub ensemble_mode: EnsembleMode,
+}
+
+/// How a prompt is picked from the two ensemble candidates
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnsembleMode {
+    /// The main model judges which candidate is better; only the winner is shown
+    #[default]
+    Judge,
+    /// Show both candidates and let the user pick
+    Both,
+}
+
+fn default_embedding_model() -> String {
+    "nomic-embed-text".to_string()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HooksConfig {
+    /// Shell command run after the daily prompts are generated (prompt text is passed on stdin)
+    pub prompt_ready_command: Option<String>,
+    /// Maximum time a hook command may run before it is killed
+    pub command_timeout_seconds: u64,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            prompt_ready_command: None,
+            command_timeout_seconds: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PrinterConfig {
+    /// Print the morning prompts on an ESC/POS receipt printer
+    pub enabled: bool,
+    /// Device file (e.g., "/dev/usb/lp0") or network address (e.g., "192.168.1.50:9100")
+    pub target: String,
+    /// Characters per line (32 for 58mm paper, 48 for 80mm)
+    pub line_width: usize,
+}
+
+impl Default for PrinterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target: "/dev/usb/lp0".to_string(),
+            line_width: 32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BackupConfig {
+    /// Back up automatically every day at `time`
+    pub enabled: bool,
+    /// Time to run the daily backup (in 24-hour format, e.g., "02:00")
+    pub time: String,
+    /// Directory where backup archives are written
+    pub destination: String,
+    /// Number of archives to keep; older ones are deleted
+    pub keep: usize,
+    /// Number of automatic snapshots (taken before restores, regenerations, and edits) to keep
+    #[serde(default = "default_keep_snapshots")]
+    pub keep_snapshots: usize,
+}
+
+fn default_keep_snapshots() -> usize {
+    50
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            time: "02:00".to_string(),
+            destination: "backups".to_string(),
+            keep: 14,
+            keep_snapshots: default_keep_snapshots(),
+        }
+    }
+}
+
+/// Kind of remote storage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RemoteKind {
+    /// S3-compatible object storage (path-style addressing)
+    S3,
+    Webdav,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RemoteConfig {
+    /// Push to remote storage after every backup
+    pub enabled: bool,
+    pub kind: RemoteKind,
+    /// Base URL (e.g., "https://s3.us-east-1.amazonaws.com" or a WebDAV folder URL)
+    pub endpoint: String,
+    /// S3 bucket name
+    pub bucket: Option<String>,
+    /// S3 region used for request signing
+    pub region: String,
+    pub access_key: Option<String>,
+    pub secret_key: Option<String>,
+    /// WebDAV basic auth credentials
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Folder (or key prefix) everything is stored under
+    pub prefix: String,
+    /// Passphrase files are encrypted with before upload; required
+    pub passphrase: Option<String>,
+    /// Upload each backup archive
+    pub upload_backups: bool,
+    /// Upload changed journal files individually
+    pub sync_journal: bool,
+}
+
+impl Default for RemoteConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            kind: RemoteKind::S3,
+            endpoint: String::new(),
+            bucket: None,
+            region: "us-east-1".to_string(),
+            access_key: None,
+            secret_key: None,
+            username: None,
+            password: None,
+            prefix: "llm-journal".to_string(),
+            passphrase: None,
+            upload_backups: true,
+            sync_journal: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GitConfig {
+    /// Commit the journal directory after every save
+    pub enabled: bool,
+    /// Push after each commit
+    pub push: bool,
+    /// Remote to push to
+    pub remote: String,
+    /// Identity used for journal commits
+    pub author_name: String,
+    pub author_email: String,
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            push: false,
+            remote: "origin".to_string(),
+            author_name: "LLM Journal".to_string(),
+            author_email: "journal@localhost".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GuestConfig {
+    /// Serve a generic daily prompt at /guest/prompt without logging in
+    pub enabled: bool,
+    /// Template the guest prompt is generated from. Nothing personal is added to it.
+    /// Placeholders: {date}, {weekday}
+    pub template: String,
+    /// Requests allowed per visitor address per minute
+    pub requests_per_minute: u32,
+}
+
+impl Default for GuestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            template: "Write one thoughtful journal prompt that anyone could answer today, {weekday}, {date}. Keep it to one or two sentences. Reply with the prompt only.\n\nJournal prompt:".to_string(),
+            requests_per_minute: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EmailInConfig {
+    /// Accept emails forwarded by a mail service's inbound webhook at /inbound/email
+    pub enabled: bool,
+    /// Shared secret the webhook must send, as ?secret= or an X-Webhook-Secret header
+    pub secret: String,
+    /// Sender addresses allowed to journal by email; empty allows any sender
+    pub allowed_senders: Vec<String>,
+    /// Notebook the entries go to; the default notebook when unset
+    pub notebook: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TranscriptionConfig {
+    /// Shell command that transcribes an audio file: {file} stands for its path, and
+    /// the transcript is read from stdout (plain text, or JSON with "text" and "confidence")
+    pub command: Option<String>,
+    /// Maximum time a transcription may run before it is killed
+    pub timeout_seconds: u64,
+}
+
+impl Default for TranscriptionConfig {
+    fn default() -> Self {
+        Self {
+            command: None,
+            timeout_seconds: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpeechConfig {
+    /// Shell command that reads text on stdin and writes speech to {file} (its path), enabling the
+    /// audio digest of yesterday's summary and today's prompt
+    pub command: Option<String>,
+    /// Extension of the audio the command writes ("mp3", "ogg", "m4a", or "wav")
+    pub format: String,
+    /// Maximum time speaking a digest may take before it is killed
+    pub timeout_seconds: u64,
+}
+
+impl Default for SpeechConfig {
+    fn default() -> Self {
+        Self {
+            command: None,
+            format: "mp3".to_string(),
+            timeout_seconds: 120,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RetentionConfig {
+    /// Delete prompts on days that never got an entry after this many days (kept if not set)
+    pub unanswered_prompts_days: Option<u32>,
+    /// Delete summaries after this many days, once the entry has a one-line summary to stand
+    /// in for it in long-horizon context (kept if not set)
+    pub summaries_days: Option<u32>,
+    /// Forget which model and steering produced prompts and summaries after this many days
+    /// (kept if not set)
+    pub provenance_days: Option<u32>,
+    /// Delete processing reports after this many days (kept if not set)
+    pub reports_days: Option<u32>,
+    /// Have the nightly cleanup only report what it would delete
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct YearReviewConfig {
+    /// Compile last year's review a week into each new year
+    pub enabled: bool,
+    /// Shell command converting the review's Markdown to PDF: {input} and {output} stand
+    /// for the file paths (Markdown only if not set)
+    pub pdf_command: Option<String>,
+    /// Maximum time the PDF conversion may run before it is killed
+    pub timeout_seconds: u64,
+}
+
+impl Default for YearReviewConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            pdf_command: None,
+            timeout_seconds: 300,
+        }
+    }
 }
 
 impl Default for Config {
@@ -68,6 +682,20 @@ impl Default for Config {
             server: ServerConfig {
                 port: 3000,
                 host: "0.0.0.0".to_string(),
+                cookie_prefix: String::new(),
+                base_path: String::new(),
+                cookie_path: None,
+                locale: default_locale(),
+                static_dir: default_static_dir(),
+                max_body_kb: default_max_body_kb(),
+                requests_per_minute: default_requests_per_minute(),
+                expensive_requests_per_minute: default_expensive_requests_per_minute(),
+                trusted_proxies: Vec::new(),
+                forwarded_header: default_forwarded_header(),
+                access_log: default_access_log(),
+                access_log_max_kb: default_access_log_max_kb(),
+                override_dir: None,
+                cors: CorsConfig::default(),
             },
             files: FileConfig {
                 tokens_file: "tokens.json".to_string(),
@@ -80,14 +708,48 @@ impl Default for Config {
                 journal_directory: "journal".to_string(),
                 processing_time: "03:00".to_string(),  // Will be deprecated
                 prompt_generation_time: "03:00".to_string(),  // Unified processing at 3 AM
+                timezone: None,
+                day_ends_at: default_day_ends_at(),
+                calendar: crate::cycle_date::CalendarMode::default(),
+                leap_weeks: false,
+                cycle: crate::cycle_date::CycleShape::default(),
+                month_names: Vec::new(),
+                day_names: Vec::new(),
+                adaptive_prompt_time: false,
+                prompt_lead_minutes: default_prompt_lead_minutes(),
                 max_prompts_per_day: 3,
+                max_edit_age_days: None,
+                transcription_review_threshold: default_transcription_review_threshold(),
+                prompt_expiry_days: None,
+                streak_in_prompt_context: false,
+                memory_lane_in_prompt_context: false,
+                dedup_window_days: default_dedup_window_days(),
+                dedup_threshold: default_dedup_threshold(),
+                dedup_max_retries: default_dedup_max_retries(),
+                notebook_name: default_notebook_name(),
+                reflect_before_saving: Vec::new(),
+                notebooks: Vec::new(),
             },
             llm: LlmConfig {
                 model_path: "models/gpt-oss-20b.gguf".to_string(),
                 context_length: 128000,
                 temperature: 0.7,
                 max_tokens: 512,
+                embedding_model: default_embedding_model(),
+                ensemble_model: None,
+                ensemble_mode: EnsembleMode::default(),
             },
+            hooks: HooksConfig::default(),
+            printer: PrinterConfig::default(),
+            backup: BackupConfig::default(),
+            remote: RemoteConfig::default(),
+            git: GitConfig::default(),
+            guest: GuestConfig::default(),
+            email_in: EmailInConfig::default(),
+            transcription: TranscriptionConfig::default(),
+            speech: SpeechConfig::default(),
+            retention: RetentionConfig::default(),
+            year_review: YearReviewConfig::default(),
         }
     }
 }
@@ -95,11 +757,11 @@ impl Default for Config {
 impl Config {
     /// Load configuration from file, falling back to defaults
     pub fn load() -> Self {
-        match fs::read_to_string("config.toml") {
+        match fs::read_to_string(CONFIG_FILE) {
             Ok(content) => {
                 match toml::from_str(&content) {
                     Ok(config) => {
-                        tracing::info!("Loaded configuration from config.toml");
+                        tracing::info!("Loaded configuration from {}", CONFIG_FILE);
                         config
                     }
                     Err(e) => {
@@ -122,6 +784,45 @@ impl Config {
 [server]
 port = 3000
 host = "0.0.0.0"
+# Path the app is served under when a reverse proxy mounts it below the domain root
+# (e.g. "/journal" for https://home.example/journal); pages, links, and redirects all use it.
+# The proxy should pass the path through unchanged.
+base_path = ""
+# When several instances share a domain (say one per family member behind a reverse proxy),
+# give each its own cookie prefix, and the path it's served under, so their logins don't
+# collide. Each instance also needs its own tokens_file. cookie_path defaults to base_path.
+cookie_prefix = ""
+# cookie_path = "/"
+# Language real dates are written in on pages and in the date given to the AI: en-US,
+# en-GB, de, es, fr, it, nl, or pt
+locale = "en-US"
+# Where static files (styles, scripts, background) are served from
+static_dir = "static"
+# Largest request accepted, in KB; longer entries are saved from the page in parts
+max_body_kb = 2048
+# Requests per minute accepted from one address, and to expensive routes (prompt generation,
+# transcription, search, exports, logins) from one address or session; 0 turns a limit off
+requests_per_minute = 600
+expensive_requests_per_minute = 20
+# Behind a reverse proxy every request comes from the proxy's address. List the proxy here
+# to limit each client by the address it passes in forwarded_header instead.
+# trusted_proxies = ["127.0.0.1"]
+# forwarded_header = "X-Forwarded-For"
+# Every request as a JSON line (method, route, status, latency, device), rotated at
+# access_log_max_kb with the last few files kept; the admin page summarizes it. "" turns it off.
+access_log = "access.log"
+access_log_max_kb = 1024
+# Customize pages without rebuilding: files in <override_dir>/static/ replace the built-in
+# ones of the same name, and <override_dir>/templates/head.html and footer.html are added
+# to the end of every page's <head> and <body> (read at startup)
+# override_dir = "custom"
+
+# Let pages on other sites (a separately hosted app, a dashboard widget) call the JSON API
+# under /api from the browser. Off while allowed_origins is empty; "*" allows any origin.
+# allow_credentials sends the session cookie along, and needs the origins listed.
+# [server.cors]
+# allowed_origins = ["https://dash.home.example"]
+# allow_credentials = false
 
 [files]
 tokens_file = "tokens.json"
@@ -139,8 +840,74 @@ journal_directory = "journal"
 processing_time = "03:00"
 # Time to generate daily prompts (24-hour format)
 prompt_generation_time = "06:00"
+# Timezone for dates and the times above, e.g. "America/Chicago" (omit for the server's)
+# timezone = "America/Chicago"
+# When the journal day ends; writing before this time counts toward the previous date.
+# Keep prompt_generation_time at or after it so prompts are made for the new day.
+day_ends_at = "00:00"
+# "cycle": 13 months of 4 weeks, days named like 01A23. "gregorian": calendar months and
+# ISO weeks starting Monday, days named YYYY-MM-DD. "iso-week": ISO 8601 week dates like
+# 2025-W11-3 (Monday is day 1), months made of the weeks whose Thursday falls in them.
+# After switching an existing journal, rename its day folders with `--convert-calendar <old>`
+# (cycle, cycle+leap, gregorian, or iso-week; add --apply once the listed renames look right).
+calendar = "cycle"
+# 364-day cycle years drift about a day and a quarter a year against the seasons. Add a
+# leap week (week 4 of month C) every five or six years to keep them in place. Turning
+# this on moves cycle dates from year 02's leap week on; convert an existing journal with
+# `--convert-calendar cycle` (or `cycle+leap` when turning it off).
+leap_weeks = false
+# Names for the cycle months (13 of them, 0 to C) and the weekdays (Sunday first), so days read
+# like "Sol, Week 2, Wednesday". Prompt templates can use them as {month_name}, {day_name},
+# and {date_name}. Leave empty for "Month B" and the usual weekday names.
+month_names = []
+day_names = []
+# Move prompt generation to an hour (prompt_lead_minutes) before you usually start writing,
+# learned from recent entries; the stats page shows the suggestion either way
+adaptive_prompt_time = false
+prompt_lead_minutes = 60
 # Maximum number of prompts to generate per day
 max_prompts_per_day = 3
+# How many days back entries may still be edited; older entries become read-only,
+# with an explicit admin override to edit them anyway (omit for no limit)
+# max_edit_age_days = 7
+# Transcribed entries below this confidence wait in the review queue
+transcription_review_threshold = 0.85
+# Hide prompts that went unanswered for this many days (omit to keep them forever)
+# prompt_expiry_days = 14
+# Mention your current writing streak when generating prompts
+streak_in_prompt_context = false
+# Invite the day's prompts to look back at the past entry shown on the home page
+memory_lane_in_prompt_context = false
+# Regenerate prompts that repeat one from the last N days (0 to disable)
+dedup_window_days = 14
+# How similar (0.0-1.0) a prompt may be to a recent one before it's regenerated
+dedup_threshold = 0.6
+# Regeneration attempts before keeping the most distinct candidate
+dedup_max_retries = 2
+# Name of the notebook kept in journal_directory
+notebook_name = "personal"
+# Before saving, ask one AI question about what you wrote and add your answer to the
+# entry. Off unless listed; choose from "daily", "weekly", "monthly", "yearly".
+# reflect_before_saving = ["weekly", "monthly"]
+# The shape of the cycle calendar, 13 months of 4 weeks of 7 days from the first Sunday of
+# 2024 unless set. Day folders stay five characters, so up to 36 months (0-9, then A-Z),
+# 9 weeks a month, and 10 days a week; leap weeks need years within a week of 365 days.
+# Weekly, monthly, and yearly reflections follow it. Settle on a shape before you start:
+# changing it later changes which day every existing folder name means.
+# [journal.cycle]
+# months_per_year = 13
+# weeks_per_month = 4
+# days_per_week = 7
+# epoch = "2024-01-07"
+# More notebooks, each with its own entries, prompts.json, and profile.
+# Switch between them on the home page, or pass ?notebook=<name> to any route.
+# [[journal.notebooks]]
+# name = "work"
+# directory = "notebooks/work"
+# reflect_before_saving = ["daily"]
+# [[journal.notebooks]]
+# name = "dreams"
+# directory = "notebooks/dreams"
 
 [llm]
 # Model identifier for HuggingFace Hub
@@ -151,6 +918,147 @@ summary_max_tokens = 100
 prompt_max_tokens = 150
 # Use GPU acceleration (requires CUDA)
 use_gpu = true
+# Ollama model for semantic search and related entries (run: ollama pull nomic-embed-text)
+embedding_model = "nomic-embed-text"
+# Have a second model write a candidate for every prompt as well
+# ensemble_model = "llama3.1:8b"
+# "judge" to let the main model keep the better candidate, or "both" to show
+# both and pick yourself. Winners are recorded in the prompt analytics.
+ensemble_mode = "judge"
+
+[hooks]
+# Command to run after the daily prompts are generated. The prompts are passed
+# on stdin (separated by blank lines) and as LLM_JOURNAL_PROMPT_1..N env vars.
+# prompt_ready_command = "notify-send 'Journal' \"$LLM_JOURNAL_PROMPT_1\""
+# Maximum time a hook command may run (seconds)
+command_timeout_seconds = 30
+
+[printer]
+# Print the morning prompts and yesterday's summary on an ESC/POS receipt printer
+enabled = false
+# Device file or network address (e.g., "192.168.1.50:9100")
+target = "/dev/usb/lp0"
+# Characters per line (32 for 58mm paper, 48 for 80mm)
+line_width = 32
+
+[backup]
+# Zip the journal directory, tokens, and config every day
+# (restore with: llm_journal --restore <archive>)
+enabled = false
+# Time to run the backup (24-hour format)
+time = "02:00"
+# Directory where archives are written
+destination = "backups"
+# Number of archives to keep
+keep = 14
+# Files are also snapshotted automatically before restores, regenerations, and
+# edits that overwrite them; roll back from the admin page. Number to keep:
+keep_snapshots = 50
+
+[remote]
+# Push encrypted copies to S3-compatible or WebDAV storage after each backup
+# (fetch one with: llm_journal --remote-restore <archive name>)
+enabled = false
+# "s3" or "webdav"
+kind = "s3"
+endpoint = "https://s3.us-east-1.amazonaws.com"
+# bucket = "my-journal"
+region = "us-east-1"
+# access_key = ""
+# secret_key = ""
+# For WebDAV, endpoint is the folder URL and these are the login
+# username = ""
+# password = ""
+# Folder everything is stored under
+prefix = "llm-journal"
+# Everything is encrypted with this passphrase before upload. Keep a copy
+# somewhere safe: without it the remote copies cannot be decrypted.
+# passphrase = ""
+# Upload each backup archive
+upload_backups = true
+# Also upload changed journal files one by one
+sync_journal = false
+
+[git]
+# Keep the journal directory in git, committing after every save
+enabled = false
+# Push each commit (add a remote first: git -C journal remote add origin <url>)
+push = false
+remote = "origin"
+author_name = "LLM Journal"
+author_email = "journal@localhost"
+
+[guest]
+# Serve a generic prompt of the day at /guest/prompt (JSON, or ?format=text)
+# without logging in, e.g. to embed on a personal website. It's generated
+# from the template below only; no entries, profile, or status are used.
+enabled = false
+# Placeholders: {date}, {weekday}
+template = "Write one thoughtful journal prompt that anyone could answer today, {weekday}, {date}. Keep it to one or two sentences. Reply with the prompt only.\n\nJournal prompt:"
+# Requests allowed per visitor address per minute
+requests_per_minute = 30
+
+[email_in]
+# Turn emails into journal entries for the day they were sent, appending to an
+# existing entry. Point your mail service's inbound webhook (Mailgun, Postmark,
+# or anything posting JSON or a URL-encoded form) at
+#   https://your-server/inbound/email?secret=<secret>
+# and send mail to the private address it gives you.
+enabled = false
+secret = ""
+# Only accept mail from these addresses (recommended)
+allowed_senders = []
+# Notebook for emailed entries; the default notebook when unset
+# notebook = "work"
+
+[transcription]
+# Command that turns a recorded voice note into entry text, enabling the record
+# button on the journal page. {file} is the audio file (WebM or Ogg from the
+# browser); the transcript is read from stdout. Print JSON like
+# {"text": "...", "confidence": 0.8} to send unsure transcripts to the review queue.
+# Example using ffmpeg and whisper.cpp:
+# command = "ffmpeg -y -loglevel error -i {file} -ar 16000 -ac 1 /tmp/voice-note.wav && whisper-cli -m models/ggml-base.en.bin -nt -np -f /tmp/voice-note.wav"
+# Maximum time a transcription may run
+timeout_seconds = 300
+
+[speech]
+# Command that reads text on stdin and writes spoken audio to {file}, enabling a
+# morning audio digest of yesterday's summary and today's prompt. Subscribe to
+#   https://your-server/digest.rss?key=<api key with the read-entries scope>
+# in a podcast app, or have a smart speaker routine play
+#   https://your-server/digest/audio?key=<api key>
+# Example using piper and ffmpeg:
+# command = "piper --model en_US-lessac-medium.onnx --output_file /tmp/digest.wav && ffmpeg -y -loglevel error -i /tmp/digest.wav {file}"
+# Extension of the audio the command writes: mp3, ogg, m4a, or wav
+format = "mp3"
+# Maximum time speaking a digest may take
+timeout_seconds = 120
+
+[retention]
+# How long machine-generated artifacts are kept; your entries are never touched.
+# Leave a setting out to keep those artifacts forever. The nightly run enforces these,
+# snapshotting what it deletes; preview a cleanup from the admin page or with
+#   llm_journal --retention
+# Delete prompts on days you didn't write after this many days
+# unanswered_prompts_days = 90
+# Delete summaries of entries that already have a one-line summary
+# summaries_days = 730
+# Forget which model and steering produced each prompt and summary
+# provenance_days = 365
+# Delete processing reports
+# reports_days = 90
+# Only report what the nightly cleanup would delete
+dry_run = false
+
+[year_review]
+# A week into each new year, compile the year that ended into a book: a chapter
+# per month written from your monthly reflections and fullest entries, plus the year's
+# numbers. Download it from the statistics page.
+enabled = true
+# Command converting the book's Markdown to PDF; {input} and {output} are file paths
+# pdf_command = "pandoc {input} -o {output}"
+# Maximum time the PDF conversion may take
+timeout_seconds = 300
 "#;
         
         fs::write("config.toml.example", sample_config)?;
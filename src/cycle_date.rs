@@ -1,116 +1,771 @@
-use chrono::{Datelike, Duration, Local, NaiveDate};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::sync::OnceLock;
+
+/// How the current moment maps to a journal day: the timezone the wall clock is read in,
+/// and the time the day ends, so late-night writing counts toward the evening's date
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DayClock {
+    /// None for the server's local time
+    pub timezone: Option<Tz>,
+    /// Writing before this time counts toward the previous day (midnight by default)
+    pub day_ends_at: NaiveTime,
+}
+
+impl Default for DayClock {
+    fn default() -> Self {
+        Self {
+            timezone: None,
+            day_ends_at: NaiveTime::MIN,
+        }
+    }
+}
+
+impl DayClock {
+    /// Wall-clock time of an instant in the clock's timezone
+    pub fn wall_time(&self, at: DateTime<Local>) -> NaiveDateTime {
+        match self.timezone {
+            Some(tz) => at.with_timezone(&tz).naive_local(),
+            None => at.naive_local(),
+        }
+    }
+
+    /// Wall-clock time now
+    pub fn now(&self) -> NaiveDateTime {
+        self.wall_time(Local::now())
+    }
+
+    /// The journal day a wall-clock time belongs to
+    pub fn day_of(&self, time: NaiveDateTime) -> NaiveDate {
+        (time - self.day_ends_at.signed_duration_since(NaiveTime::MIN)).date()
+    }
+
+    /// How long until the wall clock next reads `time`
+    pub fn until(&self, time: NaiveTime) -> Result<std::time::Duration, String> {
+        match self.timezone {
+            Some(tz) => until_in(Utc::now().with_timezone(&tz), time),
+            None => until_in(Local::now(), time),
+        }
+    }
+}
+
+/// Time from `now` until the next `time` on the same clock (tomorrow's, if it has passed)
+fn until_in<Z: TimeZone>(now: DateTime<Z>, time: NaiveTime) -> Result<std::time::Duration, String> {
+    let mut target = now.date_naive().and_time(time);
+    if target <= now.naive_local() {
+        target += Duration::days(1);
+    }
+    let target = now.timezone().from_local_datetime(&target).earliest().ok_or("Failed to create target datetime")?;
+    target.signed_duration_since(now).to_std().map_err(|e| format!("Duration conversion failed: {}", e))
+}
+
+/// How days are named and grouped into weeks, months, and years
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CalendarMode {
+    /// 13 months of 4 weeks, days named YYMWD
+    #[default]
+    Cycle,
+    /// Calendar months and ISO weeks (starting Monday), days named YYYY-MM-DD
+    Gregorian,
+    /// ISO 8601 week dates, days named YYYY-Www-D (Monday is day 1). Years are ISO years,
+    /// and each month is the weeks whose Thursday falls in that calendar month.
+    #[serde(rename = "iso-week")]
+    IsoWeek,
+}
+
+impl CalendarMode {
+    /// Whether `date` starts a week
+    pub fn starts_week(self, date: NaiveDate) -> bool {
+        match self {
+            CalendarMode::Cycle => CycleDate::from_real_date(date).day == 0,
+            CalendarMode::Gregorian | CalendarMode::IsoWeek => date.weekday() == chrono::Weekday::Mon,
+        }
+    }
+
+    /// Whether `date` starts a month
+    pub fn starts_month(self, date: NaiveDate) -> bool {
+        match self {
+            CalendarMode::Cycle => {
+                let cycle_date = CycleDate::from_real_date(date);
+                cycle_date.week == 0 && cycle_date.day == 0
+            }
+            CalendarMode::Gregorian => date.day() == 1,
+            CalendarMode::IsoWeek => self.starts_week(date) && iso_thursday(date).month() != iso_thursday(date - Duration::days(7)).month(),
+        }
+    }
+
+    /// Whether `date` starts a year
+    pub fn starts_year(self, date: NaiveDate) -> bool {
+        match self {
+            CalendarMode::Cycle => {
+                let cycle_date = CycleDate::from_real_date(date);
+                cycle_date.month == 0 && cycle_date.week == 0 && cycle_date.day == 0
+            }
+            CalendarMode::Gregorian => date.ordinal() == 1,
+            CalendarMode::IsoWeek => self.starts_week(date) && date.iso_week().week() == 1,
+        }
+    }
+
+    /// When `date` starts a year, month, or week, the first day of the one before it
+    /// (of the largest period it starts)
+    pub fn previous_period_start(self, date: NaiveDate) -> Option<NaiveDate> {
+        if self.starts_year(date) {
+            Some(match self {
+                CalendarMode::Cycle => {
+                    let previous_year = CycleDate::from_real_date(date - Duration::days(1)).year();
+                    let leap_week = if has_leap_week_in(previous_year, leap_weeks()) { shape().days_per_week as i64 } else { 0 };
+                    date - Duration::days(shape().days_per_year() + leap_week)
+                }
+                CalendarMode::Gregorian => date.with_year(date.year() - 1)?,
+                CalendarMode::IsoWeek => NaiveDate::from_isoywd_opt(date.iso_week().year() - 1, 1, chrono::Weekday::Mon)?,
+            })
+        } else if self.starts_month(date) {
+            Some(match self {
+                CalendarMode::Cycle => date - Duration::days(shape().days_per_month()),
+                CalendarMode::Gregorian => date.checked_sub_months(chrono::Months::new(1))?,
+                CalendarMode::IsoWeek => {
+                    let mut start = date - Duration::days(7);
+                    while !self.starts_month(start) {
+                        start -= Duration::days(7);
+                    }
+                    start
+                }
+            })
+        } else if self.starts_week(date) {
+            Some(date - Duration::days(self.days_per_week()))
+        } else {
+            None
+        }
+    }
+
+    /// The year `date` falls in: its year cycle, calendar year, or ISO year
+    pub fn year_of(self, date: NaiveDate) -> i32 {
+        match self {
+            CalendarMode::Cycle => CycleDate::from_real_date(date).year(),
+            CalendarMode::Gregorian => date.year(),
+            CalendarMode::IsoWeek => date.iso_week().year(),
+        }
+    }
+
+    /// The first and last days of a year numbered as `year_of` numbers them, unless it's a
+    /// year cycle dates can't be named in
+    pub fn year_bounds(self, year: i32) -> Option<(NaiveDate, NaiveDate)> {
+        match self {
+            CalendarMode::Cycle => {
+                let first = CycleDate::validated(year, 0, 0, 0, leap_weeks()).ok()?.to_real_date();
+                let leap_week = if has_leap_week_in(year, leap_weeks()) { shape().days_per_week as i64 } else { 0 };
+                Some((first, first + Duration::days(shape().days_per_year() + leap_week - 1)))
+            }
+            CalendarMode::Gregorian => Some((NaiveDate::from_ymd_opt(year, 1, 1)?, NaiveDate::from_ymd_opt(year, 12, 31)?)),
+            CalendarMode::IsoWeek => {
+                let first = NaiveDate::from_isoywd_opt(year, 1, chrono::Weekday::Mon)?;
+                let next = NaiveDate::from_isoywd_opt(year + 1, 1, chrono::Weekday::Mon)?;
+                Some((first, next - Duration::days(1)))
+            }
+        }
+    }
+
+    /// The first and last days of the month `date` falls in
+    pub fn month_bounds(self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        // A cycle month with the leap week, or five ISO weeks
+        let longest = (shape().days_per_month() + self.days_per_week()).max(35);
+        let start = (0..longest)
+            .map(|back| date - Duration::days(back))
+            .find(|d| self.starts_month(*d))
+            .unwrap_or(date);
+        let end = (1..=longest)
+            .map(|ahead| date + Duration::days(ahead))
+            .find(|d| self.starts_month(*d))
+            .map_or(date, |next| next - Duration::days(1));
+        (start, end)
+    }
+
+    /// The first and last days of each month of a year, in order
+    pub fn months_of_year(self, year: i32) -> Vec<(NaiveDate, NaiveDate)> {
+        let Some((first, last)) = self.year_bounds(year) else {
+            return Vec::new();
+        };
+        let mut months = Vec::new();
+        let mut start = first;
+        while start <= last {
+            let (_, end) = self.month_bounds(start);
+            months.push((start, end.min(last)));
+            start = end + Duration::days(1);
+        }
+        months
+    }
+
+    /// How many days a week has: the cycle shape's, otherwise seven
+    pub fn days_per_week(self) -> i64 {
+        match self {
+            CalendarMode::Cycle => shape().days_per_week as i64,
+            CalendarMode::Gregorian | CalendarMode::IsoWeek => 7,
+        }
+    }
+
+    /// A day's name, which is also its directory name
+    pub fn name(self, cycle_date: &CycleDate) -> String {
+        match self {
+            CalendarMode::Cycle => cycle_name(cycle_date),
+            CalendarMode::Gregorian => cycle_date.to_real_date().format("%Y-%m-%d").to_string(),
+            CalendarMode::IsoWeek => iso_week_name(cycle_date.to_real_date()),
+        }
+    }
+}
+
+/// The Thursday of a date's ISO week, which decides the week's year and month
+fn iso_thursday(date: NaiveDate) -> NaiveDate {
+    date + Duration::days(3 - date.weekday().num_days_from_monday() as i64)
+}
+
+/// "2025-W11-1"
+fn iso_week_name(date: NaiveDate) -> String {
+    date.format("%G-W%V-%u").to_string()
+}
+
+/// A real date written YYYY-MM-DD or as an ISO week date, YYYY-Www-D
+pub fn parse_real_date(s: &str) -> Result<NaiveDate, String> {
+    let format = if s.contains('W') { "%G-W%V-%u" } else { "%Y-%m-%d" };
+    NaiveDate::parse_from_str(s, format).map_err(|e| format!("Invalid date '{}': {}", s, e))
+}
+
+/// "03B25"
+fn cycle_name(cycle_date: &CycleDate) -> String {
+    let sign = if cycle_date.before_epoch { "-" } else { "" };
+    format!("{}{:02}{}{}{}", sign, cycle_date.year_cycle, month_char(cycle_date.month), cycle_date.week, cycle_date.day)
+}
+
+/// The character a cycle month is named by: 0-9, then A-Z
+pub fn month_char(month: u8) -> char {
+    char::from_digit(month as u32, 36).map(|c| c.to_ascii_uppercase()).unwrap_or('?')
+}
+
+/// The shape of the cycle calendar: months in a year, weeks in a month, days in a week, and
+/// the day year 00 starts on. Day folders stay five characters (YYMWD), so a year has at most
+/// 36 months (named 0-9 then A-Z), and weeks and days are a digit each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CycleShape {
+    pub months_per_year: u8,
+    pub weeks_per_month: u8,
+    pub days_per_week: u8,
+    pub epoch: NaiveDate,
+}
+
+impl Default for CycleShape {
+    /// 13 months of 4 weeks of 7 days, from the first Sunday of 2024
+    fn default() -> Self {
+        Self {
+            months_per_year: 13,
+            weeks_per_month: 4,
+            days_per_week: 7,
+            epoch: NaiveDate::from_ymd_opt(2024, 1, 7).unwrap(),
+        }
+    }
+}
+
+impl CycleShape {
+    pub fn days_per_month(&self) -> i64 {
+        self.weeks_per_month as i64 * self.days_per_week as i64
+    }
+
+    pub fn days_per_year(&self) -> i64 {
+        self.months_per_year as i64 * self.days_per_month()
+    }
+
+    /// A year gets a leap week when the next would otherwise start more than this many days
+    /// before the anniversary of the cycle start: just under half a week
+    fn max_drift_days(&self) -> i64 {
+        self.days_per_week as i64 / 2
+    }
+
+    /// Check the shape names its days in five characters, and that leap weeks, if on, can
+    /// keep its years in step with the seasons
+    pub fn validate(&self, leap_weeks: bool) -> Result<(), String> {
+        if !(1..=36).contains(&self.months_per_year) {
+            return Err(format!("months_per_year is {}; it must be 1 to 36", self.months_per_year));
+        }
+        // The leap week is numbered one past the last, so it needs a digit too
+        if !(1..=9).contains(&self.weeks_per_month) {
+            return Err(format!("weeks_per_month is {}; it must be 1 to 9", self.weeks_per_month));
+        }
+        if !(1..=10).contains(&self.days_per_week) {
+            return Err(format!("days_per_week is {}; it must be 1 to 10", self.days_per_week));
+        }
+        if leap_weeks && (365 - self.days_per_year()).abs() >= self.days_per_week as i64 {
+            return Err(format!("Leap weeks need years within a week of 365 days; this shape's are {}", self.days_per_year()));
+        }
+        Ok(())
+    }
+
+    /// Days into the year of a month, week, and day
+    pub fn day_index(&self, month: u8, week: u8, day: u8) -> i64 {
+        month as i64 * self.days_per_month() + week as i64 * self.days_per_week as i64 + day as i64
+    }
+
+    /// The month, week, and day a number of days into the year falls on; days past the last
+    /// month belong to its leap week
+    fn split(&self, days_in_year: i64) -> (u8, u8, u8) {
+        let month = (days_in_year / self.days_per_month()).min(self.months_per_year as i64 - 1);
+        let days_in_month = days_in_year - month * self.days_per_month();
+        let days_per_week = self.days_per_week as i64;
+        (month as u8, (days_in_month / days_per_week) as u8, (days_in_month % days_per_week) as u8)
+    }
+}
+
+/// A way of naming day folders: a calendar, and the leap week rule cycle dates are counted by.
+/// Dates are named by the configured scheme; others describe a journal being converted from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateScheme {
+    pub calendar: CalendarMode,
+    pub leap_weeks: bool,
+}
+
+impl DateScheme {
+    /// The scheme the journal is configured with
+    pub fn current() -> Self {
+        Self { calendar: calendar(), leap_weeks: leap_weeks() }
+    }
+
+    /// The real date a day folder name stands for; only names in this scheme's format are read
+    pub fn parse(&self, name: &str) -> Result<NaiveDate, String> {
+        match self.calendar {
+            CalendarMode::Cycle => Ok(CycleDate::from_cycle_name(name, self.leap_weeks)?.to_real_date_in(self.leap_weeks)),
+            CalendarMode::Gregorian if name.len() == 10 => {
+                NaiveDate::parse_from_str(name, "%Y-%m-%d").map_err(|e| format!("Invalid date '{}': {}", name, e))
+            }
+            CalendarMode::Gregorian => Err("Date must be YYYY-MM-DD".to_string()),
+            CalendarMode::IsoWeek if name.len() == 10 && name.contains('W') => parse_real_date(name),
+            CalendarMode::IsoWeek => Err("Date must be YYYY-Www-D".to_string()),
+        }
+    }
+
+    /// The cycle date a real date has in this scheme
+    pub fn cycle_date(&self, date: NaiveDate) -> CycleDate {
+        CycleDate::from_real_date_in(date, self.leap_weeks)
+    }
+
+    /// A date's folder name, or None if it's outside the range the journal can hold
+    pub fn name(&self, date: NaiveDate) -> Option<String> {
+        let cycle_date = self.cycle_date(date);
+        if cycle_date.to_real_date_in(self.leap_weeks) != date {
+            return None;
+        }
+        Some(match self.calendar {
+            CalendarMode::Cycle => cycle_name(&cycle_date),
+            CalendarMode::Gregorian => date.format("%Y-%m-%d").to_string(),
+            CalendarMode::IsoWeek => iso_week_name(date),
+        })
+    }
+}
+
+impl std::str::FromStr for DateScheme {
+    type Err = String;
+
+    /// "cycle", "cycle+leap" (cycle dates with leap weeks), "gregorian", or "iso-week"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cycle" => Ok(Self { calendar: CalendarMode::Cycle, leap_weeks: false }),
+            "cycle+leap" => Ok(Self { calendar: CalendarMode::Cycle, leap_weeks: true }),
+            "gregorian" => Ok(Self { calendar: CalendarMode::Gregorian, leap_weeks: false }),
+            "iso-week" => Ok(Self { calendar: CalendarMode::IsoWeek, leap_weeks: false }),
+            _ => Err(format!("Unknown date scheme '{}'; expected cycle, cycle+leap, gregorian, or iso-week", s)),
+        }
+    }
+}
+
+/// Display names for the cycle's months and the 7 weekdays (Sunday first); either list
+/// may be empty, leaving "Month B" and the usual weekday names
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DateNames {
+    pub months: Vec<String>,
+    pub days: Vec<String>,
+}
+
+impl DateNames {
+    /// The name of the month a date falls in, in `calendar`
+    pub fn month(&self, cycle_date: &CycleDate, calendar: CalendarMode) -> String {
+        match calendar {
+            CalendarMode::Cycle => match self.months.get(cycle_date.month as usize) {
+                Some(name) => name.clone(),
+                None => format!("Month {}", month_char(cycle_date.month)),
+            },
+            CalendarMode::Gregorian => cycle_date.to_real_date().format("%B").to_string(),
+            CalendarMode::IsoWeek => iso_thursday(cycle_date.to_real_date()).format("%B").to_string(),
+        }
+    }
+
+    /// The name of a date's weekday
+    pub fn day(&self, cycle_date: &CycleDate) -> String {
+        let date = cycle_date.to_real_date();
+        match self.days.get(date.weekday().num_days_from_sunday() as usize) {
+            Some(name) => name.clone(),
+            None => date.format("%A").to_string(),
+        }
+    }
+
+    /// "Sol, Week 2, Wednesday" ("Wednesday, March 5" in the Gregorian calendar, and
+    /// "Wednesday, Week 10 of 2025" in ISO weeks)
+    pub fn describe(&self, cycle_date: &CycleDate, calendar: CalendarMode) -> String {
+        match calendar {
+            CalendarMode::Cycle if cycle_date.week == shape().weeks_per_month => format!("{}, Leap Week, {}", self.month(cycle_date, calendar), self.day(cycle_date)),
+            CalendarMode::Cycle => format!("{}, Week {}, {}", self.month(cycle_date, calendar), cycle_date.week, self.day(cycle_date)),
+            CalendarMode::Gregorian => format!("{}, {}", self.day(cycle_date), cycle_date.to_real_date().format("%B %-d")),
+            CalendarMode::IsoWeek => format!("{}, {}", self.day(cycle_date), cycle_date.to_real_date().format("Week %-V of %G")),
+        }
+    }
+}
+
+static CLOCK: OnceLock<DayClock> = OnceLock::new();
+static CALENDAR: OnceLock<CalendarMode> = OnceLock::new();
+static LEAP_WEEKS: OnceLock<bool> = OnceLock::new();
+static SHAPE: OnceLock<CycleShape> = OnceLock::new();
+static NAMES: OnceLock<DateNames> = OnceLock::new();
+
+/// Year cycles dates are named in from the cycle start (00-99)
+const YEAR_CYCLES: usize = 100;
+/// Year cycles before the cycle start dates can be named in (-01 back to -99)
+const PRE_EPOCH_YEARS: usize = 99;
+
+/// Set the clock "today" is reckoned by; done once at startup, before any dates are read
+pub fn set_clock(clock: DayClock) {
+    if CLOCK.set(clock).is_err() {
+        tracing::warn!("The day clock was already set; keeping the first one");
+    }
+}
+
+/// The configured clock, or local midnight rollover if none was set
+pub fn clock() -> DayClock {
+    CLOCK.get().copied().unwrap_or_default()
+}
+
+/// Set the calendar dates are named and grouped by; done once at startup, before any dates are read
+pub fn set_calendar(mode: CalendarMode) {
+    if CALENDAR.set(mode).is_err() {
+        tracing::warn!("The calendar was already set; keeping the first one");
+    }
+}
+
+/// The configured calendar, or the cycle calendar if none was set
+pub fn calendar() -> CalendarMode {
+    CALENDAR.get().copied().unwrap_or_default()
+}
+
+/// Set the shape of the cycle calendar; done once at startup, before any dates are read
+pub fn set_shape(shape: CycleShape) {
+    if SHAPE.set(shape).is_err() {
+        tracing::warn!("The cycle shape was already set; keeping the first one");
+    }
+}
+
+/// The configured cycle shape, or 13 months of 4 weeks if none was set
+pub fn shape() -> &'static CycleShape {
+    SHAPE.get_or_init(CycleShape::default)
+}
+
+/// Set the month and weekday names dates are described with; done once at startup
+pub fn set_names(names: DateNames) {
+    if NAMES.set(names).is_err() {
+        tracing::warn!("The date names were already set; keeping the first ones");
+    }
+}
+
+/// The configured month and weekday names (none if not set)
+pub fn names() -> &'static DateNames {
+    static NO_NAMES: DateNames = DateNames { months: Vec::new(), days: Vec::new() };
+    NAMES.get().unwrap_or(&NO_NAMES)
+}
+
+/// Set whether cycle years take leap weeks; done once at startup, before any dates are read
+pub fn set_leap_weeks(enabled: bool) {
+    if LEAP_WEEKS.set(enabled).is_err() {
+        tracing::warn!("The leap week rule was already set; keeping the first one");
+    }
+}
+
+/// Whether cycle years take leap weeks (off unless configured)
+pub fn leap_weeks() -> bool {
+    LEAP_WEEKS.get().copied().unwrap_or_default()
+}
+
+/// The first day of each year cycle from -99, plus the day after year 99, in the configured
+/// shape (see `compute_year_starts`)
+fn year_starts(leap_weeks: bool) -> &'static [NaiveDate] {
+    static PLAIN: OnceLock<Vec<NaiveDate>> = OnceLock::new();
+    static LEAP: OnceLock<Vec<NaiveDate>> = OnceLock::new();
+    let starts = if leap_weeks { &LEAP } else { &PLAIN };
+    starts.get_or_init(|| compute_year_starts(shape(), leap_weeks))
+}
+
+/// The first day of each year cycle from -99, plus the day after year 99. With leap weeks on,
+/// a year gets an extra week whenever the next would otherwise start more than
+/// `max_drift_days` early (or, counting back before the cycle start, whenever it would start
+/// that late), keeping every year within half a week of its place in the seasons (for 364-day
+/// years, a leap week every five or six years).
+fn compute_year_starts(shape: &CycleShape, leap_weeks: bool) -> Vec<NaiveDate> {
+    let cycle_start = shape.epoch;
+    let year_length = Duration::days(shape.days_per_year());
+    let leap_week = Duration::days(shape.days_per_week as i64);
+    let anniversary = |year: i32| cycle_start.with_year(cycle_start.year() + year).unwrap_or(cycle_start);
+
+    let mut earlier = Vec::new();
+    let mut start = cycle_start;
+    for year in 1..=PRE_EPOCH_YEARS as i32 {
+        start -= year_length;
+        if leap_weeks && (start - anniversary(-year)).num_days() > shape.max_drift_days() {
+            start -= leap_week;
+        }
+        earlier.push(start);
+    }
+
+    let mut starts: Vec<NaiveDate> = earlier.into_iter().rev().collect();
+    starts.push(cycle_start);
+    for year in 1..=YEAR_CYCLES as i32 {
+        let mut start = starts[starts.len() - 1] + year_length;
+        if leap_weeks && (anniversary(year) - start).num_days() > shape.max_drift_days() {
+            start += leap_week;
+        }
+        starts.push(start);
+    }
+    starts
+}
+
+/// Where a year (negative before the cycle start) is in `year_starts`
+fn year_index(year: i32) -> usize {
+    (year + PRE_EPOCH_YEARS as i32) as usize
+}
+
+fn has_leap_week_in(year: i32, leap_weeks: bool) -> bool {
+    let starts = year_starts(leap_weeks);
+    let index = year_index(year);
+    index + 1 < starts.len() && (starts[index + 1] - starts[index]).num_days() > shape().days_per_year()
+}
+
+/// Whether a folder name looks like a day's, in any calendar (a digit, or a minus and a digit
+/// before the epoch), as opposed to the journal's other folders
+pub fn is_date_like(name: &str) -> bool {
+    name.strip_prefix('-').unwrap_or(name).starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// The real date of the current journal day
+pub fn current_day() -> NaiveDate {
+    let clock = clock();
+    clock.day_of(clock.now())
+}
+
+/// A real date outside the years the journal can name (-99 to 99), which `from_real_date`
+/// would clamp or wrap onto another day
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateOutOfRange(pub NaiveDate);
+
+impl fmt::Display for DateOutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let starts = year_starts(leap_weeks());
+        let last = starts[starts.len() - 1] - Duration::days(1);
+        write!(f, "{} is outside the range the journal can hold ({} to {})", self.0, starts[0], last)
+    }
+}
+
+impl std::error::Error for DateOutOfRange {}
 
 /// Represents a date in the 5-character cycle format: YYMWD
 /// YY = Year cycle (00-99, each "year" is exactly 52 weeks = 364 days)
 /// M = Month (0-C, representing 13 months of 4 weeks each)
-/// W = Week within month (0-3)
+/// W = Week within month (0-3, or 4 for the leap week at the end of month C)
 /// D = Day within week (0-6, Sunday=0)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Those are the default `CycleShape`; a configured one changes the counts.
+/// Dates before the cycle start count years back from it with a leading minus: -01C36 is
+/// the day before 00000.
+/// Serialized as its name ("03B25", or as the configured calendar names it); the struct of
+/// fields older files hold still reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct CycleDate {
-    pub year_cycle: u8,  // 0-99
-    pub month: u8,       // 0-12 (displayed as 0-C)
-    pub week: u8,        // 0-3
-    pub day: u8,         // 0-6
+    pub year_cycle: u8,  // 0-99 (1-99 before the epoch)
+    pub month: u8,       // 0-12 (displayed as 0-C) by default
+    pub week: u8,        // 0-3 (4 in a leap week) by default
+    pub day: u8,         // 0-6 by default
+    /// Set for years before the cycle start, where `year_cycle` counts back from it
+    pub before_epoch: bool,
+}
+
+impl Serialize for CycleDate {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// How a `CycleDate` may appear in JSON: its name, or the fields it used to be written as
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CycleDateRepr {
+    Name(String),
+    Fields {
+        year_cycle: u8,
+        month: u8,
+        week: u8,
+        day: u8,
+        #[serde(default)]
+        before_epoch: bool,
+    },
+}
+
+impl<'de> Deserialize<'de> for CycleDate {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match CycleDateRepr::deserialize(deserializer)? {
+            CycleDateRepr::Name(name) => CycleDate::from_string(&name).map_err(serde::de::Error::custom),
+            CycleDateRepr::Fields { year_cycle, month, week, day, before_epoch } => Ok(CycleDate { year_cycle, month, week, day, before_epoch }),
+        }
+    }
 }
 
 impl CycleDate {
     /// Create a new CycleDate
     pub fn new(year_cycle: u8, month: u8, week: u8, day: u8) -> Result<Self, String> {
-        if year_cycle > 99 {
-            return Err("Year cycle must be 0-99".to_string());
+        Self::validated(year_cycle as i32, month, week, day, leap_weeks())
+    }
+
+    /// Create a CycleDate in a year before the cycle start: `years_back` 1 is year -01
+    pub fn before_epoch(years_back: u8, month: u8, week: u8, day: u8) -> Result<Self, String> {
+        Self::validated(-(years_back as i32), month, week, day, leap_weeks())
+    }
+
+    fn validated(year: i32, month: u8, week: u8, day: u8, leap_weeks: bool) -> Result<Self, String> {
+        if !(-(PRE_EPOCH_YEARS as i32)..YEAR_CYCLES as i32).contains(&year) {
+            return Err("Year cycle must be 0-99, or -01 to -99 before the epoch".to_string());
         }
-        if month > 12 {
-            return Err("Month must be 0-12".to_string());
+        let shape = shape();
+        let last_month = shape.months_per_year - 1;
+        if month > last_month {
+            return Err(format!("Month must be 0-{}", month_char(last_month)));
         }
-        if week > 3 {
-            return Err("Week must be 0-3".to_string());
+        let last_week = shape.weeks_per_month - 1;
+        if week > last_week && !(week == shape.weeks_per_month && month == last_month && has_leap_week_in(year, leap_weeks)) {
+            return Err(format!(
+                "Week must be 0-{} (or {} in month {} of a year with a leap week)",
+                last_week, shape.weeks_per_month, month_char(last_month)
+            ));
         }
-        if day > 6 {
-            return Err("Day must be 0-6".to_string());
+        if day >= shape.days_per_week {
+            return Err(format!("Day must be 0-{}", shape.days_per_week - 1));
         }
         
         Ok(CycleDate {
-            year_cycle,
+            year_cycle: year.unsigned_abs() as u8,
             month,
             week,
             day,
+            before_epoch: year < 0,
         })
     }
+
+    /// The year cycle, negative before the cycle start
+    pub fn year(&self) -> i32 {
+        if self.before_epoch {
+            -(self.year_cycle as i32)
+        } else {
+            self.year_cycle as i32
+        }
+    }
     
-    /// Convert a real date to cycle date
-    /// The epoch starts on the first Sunday of the system (you can adjust this)
+    /// Convert a real date to cycle date. Dates before year -99 clamp to its first day;
+    /// after year 99 the years wrap around to 00
     pub fn from_real_date(date: NaiveDate) -> Self {
-        // Define epoch as January 1, 2024 (adjust as needed)
-        let epoch = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
-        
-        // Find the first Sunday on or after the epoch
-        let days_to_sunday = (7 - epoch.weekday().num_days_from_sunday()) % 7;
-        let cycle_start = epoch + Duration::days(days_to_sunday as i64);
-        
-        let days_since_start = (date - cycle_start).num_days();
-        
-        if days_since_start < 0 {
-            // Handle dates before the cycle start
-            return CycleDate::new(0, 0, 0, 0).unwrap();
+        Self::from_real_date_in(date, leap_weeks())
+    }
+
+    /// Convert a real date to cycle date, refusing dates `from_real_date` would clamp or wrap;
+    /// for dates from outside the journal, like imports and date pickers
+    pub fn try_from_real_date(date: NaiveDate) -> Result<Self, DateOutOfRange> {
+        let cycle_date = Self::from_real_date(date);
+        if cycle_date.to_real_date() != date {
+            return Err(DateOutOfRange(date));
+        }
+        Ok(cycle_date)
+    }
+
+    fn from_real_date_in(date: NaiveDate, leap_weeks: bool) -> Self {
+        let starts = year_starts(leap_weeks);
+        let cycle_start = starts[year_index(0)];
+        let date = if date < starts[0] {
+            starts[0]
+        } else if date >= cycle_start {
+            let span = (starts[year_index(YEAR_CYCLES as i32)] - cycle_start).num_days();
+            cycle_start + Duration::days((date - cycle_start).num_days() % span)
+        } else {
+            date
+        };
+
+        let index = starts.partition_point(|start| *start <= date) - 1;
+        let year = index as i32 - PRE_EPOCH_YEARS as i32;
+        let days_in_year = (date - starts[index]).num_days();
+
+        // Every month has the same weeks; a leap week follows the last month's
+        let (month, week, day) = shape().split(days_in_year);
+
+        CycleDate {
+            year_cycle: year.unsigned_abs() as u8,
+            month,
+            week,
+            day,
+            before_epoch: year < 0,
         }
-        
-        let total_days = days_since_start as u64;
-        
-        // Each year is exactly 364 days (52 weeks)
-        let year_cycle = ((total_days / 364) % 100) as u8;
-        let days_in_year = total_days % 364;
-        
-        // Each month is exactly 28 days (4 weeks)
-        let month = (days_in_year / 28) as u8;
-        let days_in_month = days_in_year % 28;
-        
-        // Each week is 7 days
-        let week = (days_in_month / 7) as u8;
-        let day = (days_in_month % 7) as u8;
-        
-        CycleDate::new(year_cycle, month, week, day).unwrap()
     }
     
     /// Convert cycle date back to real date
-    pub fn to_real_date(&self) -> NaiveDate {
-        let epoch = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
-        let days_to_sunday = (7 - epoch.weekday().num_days_from_sunday()) % 7;
-        let cycle_start = epoch + Duration::days(days_to_sunday as i64);
-        
-        let total_days = (self.year_cycle as u64 * 364) +
-                        (self.month as u64 * 28) +
-                        (self.week as u64 * 7) +
-                        self.day as u64;
-        
-        cycle_start + Duration::days(total_days as i64)
+    pub fn to_real_date(self) -> NaiveDate {
+        self.to_real_date_in(leap_weeks())
     }
-    
-    /// Get current cycle date
-    pub fn today() -> Self {
-        Self::from_real_date(Local::now().date_naive())
+
+    fn to_real_date_in(self, leap_weeks: bool) -> NaiveDate {
+        let days_in_year = shape().day_index(self.month, self.week, self.day);
+        year_starts(leap_weeks)[year_index(self.year())] + Duration::days(days_in_year)
     }
-    
-    /// Format as 5-character string
-    pub fn to_string(&self) -> String {
-        let month_char = match self.month {
-            0..=9 => (b'0' + self.month) as char,
-            10 => 'A',
-            11 => 'B',
-            12 => 'C',
-            _ => '?',
+
+    /// Whether a year cycle ends with a leap week
+    pub fn has_leap_week(year_cycle: u8) -> bool {
+        has_leap_week_in(year_cycle as i32, leap_weeks())
+    }
+
+    /// The last day of a month: the end of its last week, or of the leap week in the last
+    /// month of a year that has one
+    pub fn last_day_of_month(year_cycle: u8, month: u8) -> Self {
+        let shape = shape();
+        let week = if month == shape.months_per_year - 1 && Self::has_leap_week(year_cycle) {
+            shape.weeks_per_month
+        } else {
+            shape.weeks_per_month - 1
         };
-        
-        format!("{:02}{}{}{}", 
-                self.year_cycle, 
-                month_char, 
-                self.week, 
-                self.day)
+        CycleDate { year_cycle, month, week, day: shape.days_per_week - 1, before_epoch: false }
+    }
+
+    /// The last day of a year cycle
+    pub fn last_day_of_year(year_cycle: u8) -> Self {
+        Self::last_day_of_month(year_cycle, shape().months_per_year - 1)
     }
     
-    /// Parse from 5-character string
+    /// Get current cycle date, by the configured timezone and day rollover
+    pub fn today() -> Self {
+        Self::from_real_date(current_day())
+    }
+    
+    /// Parse from a 5-character cycle date (6 with a leading minus before the epoch) or, in
+    /// any calendar, a YYYY-MM-DD date or YYYY-Www-D ISO week date
     pub fn from_string(s: &str) -> Result<Self, String> {
+        if s.len() == 10 {
+            return Self::try_from_real_date(parse_real_date(s)?).map_err(|e| e.to_string());
+        }
+        Self::from_cycle_name(s, leap_weeks())
+    }
+
+    /// Parse a 5-character cycle date, minus-prefixed before the epoch
+    fn from_cycle_name(s: &str, leap_weeks: bool) -> Result<Self, String> {
+        let (sign, s) = match s.strip_prefix('-') {
+            Some(rest) => (-1, rest),
+            None => (1, s),
+        };
         if s.len() != 5 {
             return Err("Cycle date must be exactly 5 characters".to_string());
         }
@@ -121,13 +776,8 @@ impl CycleDate {
             .parse()
             .map_err(|_| "Invalid year cycle")?;
         
-        let month = match chars[2] {
-            '0'..='9' => chars[2] as u8 - b'0',
-            'A' | 'a' => 10,
-            'B' | 'b' => 11,
-            'C' | 'c' => 12,
-            _ => return Err("Invalid month character".to_string()),
-        };
+        let month = chars[2].to_digit(36)
+            .ok_or("Invalid month character")? as u8;
         
         let week: u8 = chars[3].to_digit(10)
             .ok_or("Invalid week")? as u8;
@@ -135,92 +785,360 @@ impl CycleDate {
         let day: u8 = chars[4].to_digit(10)
             .ok_or("Invalid day")? as u8;
         
-        Self::new(year_cycle, month, week, day)
+        if sign < 0 && year_cycle == 0 {
+            return Err("Years before the epoch count back from -01".to_string());
+        }
+        Self::validated(sign * year_cycle as i32, month, week, day, leap_weeks)
     }
     
     /// Check if this is the first day of a week
     pub fn is_first_day_of_week(&self) -> bool {
-        self.day == 0
+        calendar().starts_week(self.to_real_date())
     }
     
     /// Check if this is the first day of a month
     pub fn is_first_day_of_month(&self) -> bool {
-        self.week == 0 && self.day == 0
+        calendar().starts_month(self.to_real_date())
     }
     
     /// Check if this is the first day of a year
     pub fn is_first_day_of_year(&self) -> bool {
-        self.month == 0 && self.week == 0 && self.day == 0
+        calendar().starts_year(self.to_real_date())
     }
-    
-    /// Get the previous day
-    pub fn previous_day(&self) -> Self {
-        if self.day > 0 {
-            return CycleDate::new(self.year_cycle, self.month, self.week, self.day - 1).unwrap();
-        }
-        
-        if self.week > 0 {
-            return CycleDate::new(self.year_cycle, self.month, self.week - 1, 6).unwrap();
+
+    /// The (year, month) this date falls in, in the configured calendar
+    pub fn month_key(&self) -> (i32, u32) {
+        match calendar() {
+            CalendarMode::Cycle => (self.year(), self.month as u32),
+            CalendarMode::Gregorian => {
+                let date = self.to_real_date();
+                (date.year(), date.month())
+            }
+            CalendarMode::IsoWeek => {
+                let thursday = iso_thursday(self.to_real_date());
+                (thursday.year(), thursday.month())
+            }
         }
-        
-        if self.month > 0 {
-            return CycleDate::new(self.year_cycle, self.month - 1, 3, 6).unwrap();
+    }
+
+    /// Label for the week this date starts: its number in the month (cycle) or ISO week number
+    pub fn week_label(&self) -> String {
+        match calendar() {
+            CalendarMode::Cycle => self.week.to_string(),
+            CalendarMode::Gregorian | CalendarMode::IsoWeek => self.to_real_date().iso_week().week().to_string(),
         }
-        
-        if self.year_cycle > 0 {
-            return CycleDate::new(self.year_cycle - 1, 12, 3, 6).unwrap();
+    }
+
+    /// Label for the month this date falls in: its number (cycle) or name
+    pub fn month_label(&self) -> String {
+        match calendar() {
+            CalendarMode::Cycle => self.month.to_string(),
+            CalendarMode::Gregorian => self.to_real_date().format("%B").to_string(),
+            CalendarMode::IsoWeek => iso_thursday(self.to_real_date()).format("%B").to_string(),
         }
-        
-        // Can't go before the first day of the first year
-        CycleDate::new(0, 0, 0, 0).unwrap()
     }
-    
-    /// Get the next day
-    pub fn next_day(&self) -> Self {
-        if self.day < 6 {
-            return CycleDate::new(self.year_cycle, self.month, self.week, self.day + 1).unwrap();
+
+    /// This date's day of the year in the configured calendar, from 1
+    pub fn day_of_year(&self) -> u32 {
+        let date = self.to_real_date();
+        match calendar() {
+            CalendarMode::Cycle => shape().day_index(self.month, self.week, self.day) as u32 + 1,
+            CalendarMode::Gregorian => date.ordinal(),
+            CalendarMode::IsoWeek => (date.iso_week().week() - 1) * 7 + date.weekday().number_from_monday(),
         }
-        
-        if self.week < 3 {
-            return CycleDate::new(self.year_cycle, self.month, self.week + 1, 0).unwrap();
+    }
+
+    /// How many days this date's year has: 364 (371 with a leap week) in the default cycle
+    /// calendar, 365 or 366 in the Gregorian, and 52 or 53 weeks of them in ISO weeks
+    pub fn days_in_year(&self) -> u32 {
+        let date = self.to_real_date();
+        let shape = shape();
+        match calendar() {
+            CalendarMode::Cycle if has_leap_week_in(self.year(), leap_weeks()) => (shape.days_per_year() + shape.days_per_week as i64) as u32,
+            CalendarMode::Cycle => shape.days_per_year() as u32,
+            CalendarMode::Gregorian if date.leap_year() => 366,
+            CalendarMode::Gregorian => 365,
+            CalendarMode::IsoWeek => {
+                let last_week = NaiveDate::from_ymd_opt(date.iso_week().year(), 12, 28).map(|d| d.iso_week().week()).unwrap_or(52);
+                last_week * 7
+            }
         }
-        
-        if self.month < 12 {
-            return CycleDate::new(self.year_cycle, self.month + 1, 0, 0).unwrap();
+    }
+
+    /// This date's week of the year, from 1 (the ISO week outside the cycle calendar)
+    pub fn week_of_year(&self) -> u32 {
+        match calendar() {
+            CalendarMode::Cycle => (self.day_of_year() - 1) / shape().days_per_week as u32 + 1,
+            CalendarMode::Gregorian | CalendarMode::IsoWeek => self.to_real_date().iso_week().week(),
         }
-        
-        if self.year_cycle < 99 {
-            return CycleDate::new(self.year_cycle + 1, 0, 0, 0).unwrap();
+    }
+
+    /// How much of the year has gone by at the end of this day, as a percentage
+    pub fn percent_of_year_elapsed(&self) -> f64 {
+        self.day_of_year() as f64 * 100.0 / self.days_in_year() as f64
+    }
+
+    /// "Day 203 of 364"
+    pub fn year_progress(&self) -> String {
+        format!("Day {} of {}", self.day_of_year(), self.days_in_year())
+    }
+
+    /// The configured name of this date's month
+    pub fn month_name(&self) -> String {
+        names().month(self, calendar())
+    }
+
+    /// The configured name of this date's weekday
+    pub fn day_name(&self) -> String {
+        names().day(self)
+    }
+
+    /// The date in words, by the configured names: "Sol, Week 2, Wednesday"
+    pub fn date_name(&self) -> String {
+        names().describe(self, calendar())
+    }
+
+    /// Every day from `from` through `to`, in order (none if `to` comes first)
+    pub fn range(from: CycleDate, to: CycleDate) -> impl Iterator<Item = CycleDate> {
+        let end = to.to_real_date();
+        from.to_real_date().iter_days().take_while(move |d| *d <= end).map(Self::from_real_date)
+    }
+
+    /// The days of the year, month, or week that ended yesterday, when this date starts a
+    /// new one (the largest period it starts); empty on other days
+    pub fn previous_period(&self) -> Vec<CycleDate> {
+        match calendar().previous_period_start(self.to_real_date()) {
+            Some(start) => Self::range(Self::from_real_date(start), self.previous_day()).collect(),
+            None => Vec::new(),
         }
-        
-        // Wrap around after year 99
-        CycleDate::new(0, 0, 0, 0).unwrap()
     }
     
-    /// Get previous 7 days (including self)
+    /// Get the previous day
+    pub fn previous_day(&self) -> Self {
+        // Can't go before the first day of year -99
+        Self::from_real_date(self.to_real_date() - Duration::days(1))
+    }
+    
+    /// Get the next day, wrapping around after year 99
+    pub fn next_day(&self) -> Self {
+        Self::from_real_date(self.to_real_date() + Duration::days(1))
+    }
+    
+    /// The first and last days of the week this date falls in, in the configured calendar
+    pub fn week_bounds(&self) -> (CycleDate, CycleDate) {
+        let calendar = calendar();
+        let date = self.to_real_date();
+        let start = (0..calendar.days_per_week())
+            .map(|back| date - Duration::days(back))
+            .find(|d| calendar.starts_week(*d))
+            .unwrap_or(date);
+        let end = start + Duration::days(calendar.days_per_week() - 1);
+        (Self::from_real_date(start), Self::from_real_date(end))
+    }
+
+    /// The first and last days of the month this date falls in, in the configured calendar
+    pub fn month_bounds(&self) -> (CycleDate, CycleDate) {
+        let (start, end) = calendar().month_bounds(self.to_real_date());
+        (Self::from_real_date(start), Self::from_real_date(end))
+    }
+
+    /// Get a week's worth of days up to and including this one
     pub fn previous_week(&self) -> Vec<CycleDate> {
-        let mut dates = Vec::new();
-        let mut current = *self;
-        
-        for _ in 0..7 {
-            dates.push(current);
-            current = current.previous_day();
-        }
-        
-        dates.reverse();
-        dates
+        let days = calendar().days_per_week();
+        Self::range(Self::from_real_date(self.to_real_date() - Duration::days(days - 1)), *self).collect()
     }
 }
 
 impl fmt::Display for CycleDate {
+    /// Format as 5-character string, YYYY-MM-DD in the Gregorian calendar, or YYYY-Www-D in ISO weeks
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.to_string())
+        write!(f, "{}", calendar().name(self))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn test_gregorian_calendar() {
+        let gregorian = CalendarMode::Gregorian;
+        let date = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+
+        assert!(gregorian.starts_week(date(2025, 3, 3)) && !gregorian.starts_week(date(2025, 3, 2)));
+        assert!(gregorian.starts_month(date(2025, 3, 1)) && !gregorian.starts_month(date(2025, 3, 2)));
+        assert!(gregorian.starts_year(date(2025, 1, 1)) && !gregorian.starts_year(date(2025, 2, 1)));
+
+        assert_eq!(gregorian.previous_period_start(date(2025, 1, 1)), Some(date(2024, 1, 1)));
+        assert_eq!(gregorian.previous_period_start(date(2025, 3, 1)), Some(date(2025, 2, 1)));
+        assert_eq!(gregorian.previous_period_start(date(2025, 3, 10)), Some(date(2025, 3, 3)));
+        assert_eq!(gregorian.previous_period_start(date(2025, 3, 11)), None);
+        assert_eq!(CalendarMode::Cycle.previous_period_start(CycleDate::new(1, 3, 0, 0).unwrap().to_real_date()), Some(CycleDate::new(1, 2, 0, 0).unwrap().to_real_date()));
+
+        assert_eq!(gregorian.year_of(date(2025, 12, 31)), 2025);
+        assert_eq!(gregorian.year_bounds(2024), Some((date(2024, 1, 1), date(2024, 12, 31))));
+        let months = gregorian.months_of_year(2024);
+        assert_eq!((months.len(), months[1]), (12, (date(2024, 2, 1), date(2024, 2, 29))));
+        let cycle_months = CalendarMode::Cycle.months_of_year(1);
+        assert_eq!(cycle_months.len(), 13);
+        assert_eq!(cycle_months[2].0, CycleDate::new(1, 2, 0, 0).unwrap().to_real_date());
+        assert_eq!(CalendarMode::Cycle.year_bounds(1).unwrap().1, CycleDate::last_day_of_year(1).to_real_date());
+        assert_eq!(CalendarMode::Cycle.year_bounds(100), None);
+
+        // Either calendar's names parse; dates the journal can't hold are refused, not clamped
+        let cycle_date = CycleDate::from_string("2025-03-10").unwrap();
+        assert_eq!(gregorian.name(&cycle_date), "2025-03-10");
+        assert_eq!(CycleDate::from_string(&CalendarMode::Cycle.name(&cycle_date)).unwrap(), cycle_date);
+        assert!(CycleDate::from_string("1900-12-31").is_err());
+        assert!(CycleDate::from_string("2025-02-30").is_err());
+    }
+
+    #[test]
+    fn test_iso_week_calendar() {
+        let iso = CalendarMode::IsoWeek;
+        let date = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+
+        // 2024-12-30 is a Monday in week 1 of 2025; the ISO year 2025 has 52 weeks
+        assert!(iso.starts_year(date(2024, 12, 30)) && !iso.starts_year(date(2025, 1, 1)));
+        assert_eq!(iso.previous_period_start(date(2025, 12, 29)), Some(date(2024, 12, 30)));
+        // March 2025's weeks start on the 3rd (a Thursday the 6th), February's on February 3rd
+        assert!(iso.starts_month(date(2025, 3, 3)) && !iso.starts_month(date(2025, 3, 10)));
+        assert_eq!(iso.previous_period_start(date(2025, 3, 3)), Some(date(2025, 2, 3)));
+        assert_eq!(iso.previous_period_start(date(2025, 3, 10)), Some(date(2025, 3, 3)));
+        assert_eq!(iso.year_of(date(2024, 12, 30)), 2025);
+        assert_eq!(iso.year_bounds(2025), Some((date(2024, 12, 30), date(2025, 12, 28))));
+        let months = iso.months_of_year(2025);
+        assert_eq!((months.len(), months[2]), (12, (date(2025, 3, 3), date(2025, 3, 30))));
+
+        let scheme: DateScheme = "iso-week".parse().unwrap();
+        assert_eq!(scheme.name(date(2025, 3, 12)).unwrap(), "2025-W11-3");
+        assert_eq!(scheme.parse("2025-W11-3"), Ok(date(2025, 3, 12)));
+        assert_eq!(scheme.parse("2020-W53-7"), Ok(date(2021, 1, 3)));
+        assert!(scheme.parse("2025-03-12").is_err() && scheme.parse("2021-W53-1").is_err());
+        assert_eq!(CycleDate::from_string("2025-W11-3").unwrap().to_real_date(), date(2025, 3, 12));
+        assert_eq!(DateNames::default().describe(&CycleDate::from_real_date(date(2025, 3, 12)), iso), "Wednesday, Week 11 of 2025");
+    }
+
+    #[test]
+    fn test_leap_weeks() {
+        let years = -(PRE_EPOCH_YEARS as i32)..YEAR_CYCLES as i32;
+        let leap_years: Vec<i32> = years.clone().filter(|&year| has_leap_week_in(year, true)).collect();
+        assert!(years.clone().all(|year| !has_leap_week_in(year, false)));
+        assert_eq!(leap_years.iter().filter(|&&year| year >= 0).take(3).collect::<Vec<_>>(), [&2, &8, &13]);
+        assert!(leap_years.windows(2).all(|pair| matches!(pair[1] - pair[0], 5 | 6)));
+
+        // Every year starts within half a week of the cycle start's anniversary
+        let starts = year_starts(true);
+        let cycle_start = starts[year_index(0)];
+        for year in years.clone() {
+            let anniversary = cycle_start.with_year(cycle_start.year() + year).unwrap();
+            let start = starts[year_index(year)];
+            assert!((anniversary - start).num_days().abs() <= shape().max_drift_days(), "year {} starts {}", year, start);
+        }
+
+        // Every day round-trips, and consecutive days get consecutive names, in both modes
+        for leap_weeks in [false, true] {
+            let mut previous: Option<CycleDate> = None;
+            let starts = year_starts(leap_weeks);
+            for date in starts[0].iter_days().take_while(|d| *d < starts[starts.len() - 1]) {
+                let cycle_date = CycleDate::from_real_date_in(date, leap_weeks);
+                assert_eq!(cycle_date.to_real_date_in(leap_weeks), date);
+                let key = |d: CycleDate| (d.year(), d.month, d.week, d.day);
+                assert!(previous.is_none_or(|p| key(p) < key(cycle_date)), "{:?} after {:?}", cycle_date, previous);
+                assert!(cycle_date.week < 4 || (leap_weeks && cycle_date.month == 12 && has_leap_week_in(cycle_date.year(), true)));
+                assert_eq!(CycleDate::from_cycle_name(&cycle_name(&cycle_date), leap_weeks), Ok(cycle_date));
+                previous = Some(cycle_date);
+            }
+        }
+
+        // The leap week follows month C's fourth week, and dates before it are unchanged
+        let leap_week = CycleDate { year_cycle: 2, month: 12, week: 4, day: 0, before_epoch: false };
+        let date = CycleDate { year_cycle: 2, month: 12, week: 3, day: 6, before_epoch: false }.to_real_date_in(true) + Duration::days(1);
+        assert_eq!(CycleDate::from_real_date_in(date, true), leap_week);
+        assert_eq!(CycleDate::from_real_date_in(date, false), CycleDate::new(3, 0, 0, 0).unwrap());
+        assert_eq!(CycleDate::from_real_date_in(date + Duration::days(7), true), CycleDate::new(3, 0, 0, 0).unwrap());
+        assert!(CycleDate::new(2, 12, 4, 0).is_err());
+    }
+
+    #[test]
+    fn test_before_epoch() {
+        let day_before = CycleDate::new(0, 0, 0, 0).unwrap().previous_day();
+        assert_eq!(day_before, CycleDate::before_epoch(1, 12, 3, 6).unwrap());
+        assert_eq!((day_before.to_string(), day_before.year()), ("-01C36".to_string(), -1));
+        assert_eq!(day_before.next_day(), CycleDate::new(0, 0, 0, 0).unwrap());
+        assert_eq!(CycleDate::from_string("-01C36"), Ok(day_before));
+        assert_eq!(CycleDate::from_string("2024-01-06"), Ok(day_before));
+        assert!(CycleDate::from_string("-00000").is_err() && CycleDate::from_string("--1000").is_err());
+
+        // Ten years back is a plain date, not the first day of the journal
+        let old = NaiveDate::from_ymd_opt(2014, 6, 15).unwrap();
+        let cycle_date = CycleDate::from_real_date(old);
+        assert!(cycle_date.before_epoch && cycle_date.year() == -10);
+        assert_eq!(cycle_date.to_real_date(), old);
+        assert!(CycleDate::from_string("1900-01-01").is_err());
+        let too_old = NaiveDate::from_ymd_opt(1900, 1, 1).unwrap();
+        assert_eq!(CycleDate::try_from_real_date(too_old), Err(DateOutOfRange(too_old)));
+        assert_eq!(CycleDate::try_from_real_date(old), Ok(cycle_date));
+        let too_new = year_starts(false)[year_index(YEAR_CYCLES as i32)];
+        assert!(CycleDate::try_from_real_date(too_new).is_err() && CycleDate::try_from_real_date(too_new - Duration::days(1)).is_ok());
+
+        // Older JSON, written as fields and without the flag, still reads
+        let json: CycleDate = serde_json::from_str(r#"{"year_cycle":1,"month":2,"week":3,"day":4}"#).unwrap();
+        assert_eq!(json, CycleDate::new(1, 2, 3, 4).unwrap());
+        let old: CycleDate = serde_json::from_str(r#"{"year_cycle":1,"month":12,"week":3,"day":6,"before_epoch":true}"#).unwrap();
+        assert_eq!(old, day_before);
+    }
+
+    #[test]
+    fn test_serialized_as_name() {
+        let date = CycleDate::new(3, 11, 2, 5).unwrap();
+        assert_eq!(serde_json::to_string(&date).unwrap(), r#""03B25""#);
+        assert_eq!(serde_json::from_str::<CycleDate>(r#""03B25""#).unwrap(), date);
+        let day_before = CycleDate::before_epoch(1, 12, 3, 6).unwrap();
+        assert_eq!(serde_json::from_str::<CycleDate>(&serde_json::to_string(&day_before).unwrap()).unwrap(), day_before);
+        assert_eq!(serde_json::from_str::<CycleDate>(r#""2024-01-06""#).unwrap(), day_before);
+        assert!(serde_json::from_str::<CycleDate>(r#""03D25""#).is_err());
+    }
+
+    #[test]
+    fn test_date_names() {
+        let date = CycleDate::new(1, 11, 2, 3).unwrap();
+        let unnamed = DateNames::default();
+        assert_eq!(unnamed.describe(&date, CalendarMode::Cycle), "Month B, Week 2, Wednesday");
+
+        let mut names = DateNames {
+            months: ["Ember", "Frost", "Thaw", "Bloom", "Rain", "Green", "Sol", "High", "Amber", "Harvest", "Mist", "Sol", "Deep"].map(String::from).to_vec(),
+            days: Vec::new(),
+        };
+        assert_eq!(names.describe(&date, CalendarMode::Cycle), "Sol, Week 2, Wednesday");
+        names.days = ["Sunna", "Mona", "Tiw", "Woden", "Thunor", "Frige", "Saturn"].map(String::from).to_vec();
+        assert_eq!(names.day(&date), "Woden");
+        assert_eq!(names.describe(&date, CalendarMode::Gregorian), format!("Woden, {}", date.to_real_date().format("%B %-d")));
+        let leap_week = CycleDate { year_cycle: 2, month: 12, week: 4, day: 0, before_epoch: false };
+        assert_eq!(names.describe(&leap_week, CalendarMode::Cycle), "Deep, Leap Week, Sunna");
+    }
+
+    #[test]
+    fn test_day_clock() {
+        let clock = DayClock {
+            timezone: Some(chrono_tz::America::Chicago),
+            day_ends_at: NaiveTime::from_hms_opt(3, 0, 0).unwrap(),
+        };
+        let evening = NaiveDate::from_ymd_opt(2025, 3, 8).unwrap();
+        assert_eq!(clock.day_of(evening.and_hms_opt(23, 30, 0).unwrap()), evening);
+        assert_eq!(clock.day_of(evening.succ_opt().unwrap().and_hms_opt(2, 59, 0).unwrap()), evening);
+        assert_eq!(clock.day_of(evening.succ_opt().unwrap().and_hms_opt(3, 0, 0).unwrap()), evening.succ_opt().unwrap());
+        assert_eq!(DayClock::default().day_of(evening.and_hms_opt(0, 0, 0).unwrap()), evening);
+
+        // 04:00 UTC is 22:00 the evening before in Chicago
+        let utc = Utc.with_ymd_and_hms(2025, 3, 9, 4, 0, 0).unwrap();
+        assert_eq!(clock.wall_time(utc.with_timezone(&Local)), evening.and_hms_opt(22, 0, 0).unwrap());
+
+        // Clocks spring forward at 2am on March 9th, so 6am is four real hours after 1am
+        let chicago = chrono_tz::America::Chicago.with_ymd_and_hms(2025, 3, 9, 1, 0, 0).unwrap();
+        assert_eq!(until_in(chicago, NaiveTime::from_hms_opt(6, 0, 0).unwrap()).unwrap().as_secs(), 4 * 3600);
+        assert_eq!(until_in(chicago, NaiveTime::from_hms_opt(1, 0, 0).unwrap()).unwrap().as_secs(), 24 * 3600 - 3600);
+    }
     
     #[test]
     fn test_cycle_date_creation() {
@@ -262,4 +1180,68 @@ mod tests {
         let prev = next.previous_day();
         assert_eq!(date, prev);
     }
+
+    #[test]
+    fn test_year_progress() {
+        let date = CycleDate::new(1, 7, 0, 6).unwrap();
+        assert_eq!((date.day_of_year(), date.days_in_year(), date.week_of_year()), (203, 364, 29));
+        assert_eq!(date.year_progress(), "Day 203 of 364");
+        assert_eq!(date.percent_of_year_elapsed().round(), 56.0);
+        let first = CycleDate::new(1, 0, 0, 0).unwrap();
+        assert_eq!((first.day_of_year(), first.week_of_year()), (1, 1));
+        assert_eq!(CycleDate::last_day_of_month(1, 12).percent_of_year_elapsed(), 100.0);
+    }
+
+    #[test]
+    fn test_cycle_shape() {
+        assert_eq!(CycleShape::default().epoch.weekday(), chrono::Weekday::Sun);
+        assert_eq!(compute_year_starts(&CycleShape::default(), true), year_starts(true));
+
+        // Ten months of five six-day weeks: 300-day years, too short to keep with the seasons
+        let shape = CycleShape { months_per_year: 10, weeks_per_month: 5, days_per_week: 6, ..CycleShape::default() };
+        assert!(shape.validate(false).is_ok() && shape.validate(true).is_err());
+        let starts = compute_year_starts(&shape, false);
+        assert!(starts.windows(2).all(|pair| (pair[1] - pair[0]).num_days() == 300));
+        assert_eq!(starts[year_index(0)], shape.epoch);
+        assert_eq!(shape.split(299), (9, 4, 5));
+        assert_eq!(shape.split(shape.day_index(3, 2, 1)), (3, 2, 1));
+
+        // Twelve months of five six-day weeks keep with the seasons given leap weeks
+        let shape = CycleShape { months_per_year: 12, ..shape };
+        assert!(shape.validate(true).is_ok());
+        let starts = compute_year_starts(&shape, true);
+        assert!(starts.windows(2).all(|pair| matches!((pair[1] - pair[0]).num_days(), 360 | 366)));
+        assert_eq!(shape.split(362), (11, 5, 2));
+
+        assert_eq!((month_char(11), month_char(35)), ('B', 'Z'));
+        assert!(CycleShape { months_per_year: 37, ..shape }.validate(false).is_err());
+        assert!(CycleShape { weeks_per_month: 10, ..shape }.validate(false).is_err());
+    }
+
+    #[test]
+    fn test_week_bounds() {
+        let date = CycleDate::new(1, 5, 2, 3).unwrap();
+        assert_eq!(date.week_bounds(), (CycleDate::new(1, 5, 2, 0).unwrap(), CycleDate::new(1, 5, 2, 6).unwrap()));
+        let start = CycleDate::new(1, 0, 0, 0).unwrap();
+        assert_eq!(start.week_bounds().0, start);
+    }
+
+    #[test]
+    fn test_month_bounds() {
+        let date = CycleDate::new(1, 5, 2, 3).unwrap();
+        assert_eq!(date.month_bounds(), (CycleDate::new(1, 5, 0, 0).unwrap(), CycleDate::last_day_of_month(1, 5)));
+        let last = CycleDate::last_day_of_month(2, 12);
+        assert_eq!(last.month_bounds(), (CycleDate::new(2, 12, 0, 0).unwrap(), last));
+    }
+
+    #[test]
+    fn test_range() {
+        let from = CycleDate::new(1, 5, 3, 5).unwrap();
+        let to = CycleDate::new(1, 6, 0, 1).unwrap();
+        let days: Vec<String> = CycleDate::range(from, to).map(|d| d.to_string()).collect();
+        assert_eq!(days, ["01535", "01536", "01600", "01601"]);
+        assert_eq!(CycleDate::range(to, to).count(), 1);
+        assert_eq!(CycleDate::range(to, from).count(), 0);
+        assert_eq!(to.previous_week().first(), Some(&CycleDate::new(1, 5, 3, 2).unwrap()));
+    }
 }
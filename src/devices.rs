@@ -0,0 +1,169 @@
+//! Naming logged-in devices after the fact. A device's name is whatever was typed when it
+//! logged in, which is often nothing or has stopped fitting; the devices page (and
+//! PATCH /api/v1/devices/:id) renames it and attaches a note to tell it apart by.
+
+use askama::Template;
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Response},
+    routing::{get, post},
+    Form, Router,
+};
+use serde::Deserialize;
+
+use crate::auth::{AuthContext, Scope, Session};
+use crate::error::AppError;
+use crate::AppState;
+
+/// Longest device name, the same as the login form allows
+pub const MAX_NAME_CHARS: usize = 50;
+/// Longest note on a device
+pub const MAX_NOTE_CHARS: usize = 200;
+
+/// A new name and note for a device. A field left out stays as it is; an empty one is cleared.
+#[derive(Debug, Default, Deserialize)]
+pub struct DeviceDetails {
+    pub name: Option<String>,
+    pub note: Option<String>,
+}
+
+impl DeviceDetails {
+    /// Why these details can't be saved, if they can't
+    pub fn check(&self) -> Result<(), String> {
+        for (field, value, max) in [("name", &self.name, MAX_NAME_CHARS), ("note", &self.note, MAX_NOTE_CHARS)] {
+            let Some(value) = value else { continue };
+            if value.trim().chars().count() > max {
+                return Err(format!("The {} is longer than {} characters", field, max));
+            }
+            if value.chars().any(char::is_control) {
+                return Err(format!("The {} must be a single line", field));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn apply(self, session: &mut Session) {
+        fn cleared(value: String) -> Option<String> {
+            let value = value.trim();
+            (!value.is_empty()).then(|| value.to_string())
+        }
+        if let Some(name) = self.name {
+            session.device_name = cleared(name);
+        }
+        if let Some(note) = self.note {
+            session.note = cleared(note);
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "devices.html")]
+pub struct DevicesTemplate {
+    pub devices: Vec<Session>,
+    /// Device id of the device viewing the page
+    pub current: Option<String>,
+    /// Device id of the device just saved
+    pub saved: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct DevicesQuery {
+    saved: Option<String>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/devices", get(devices_page))
+        .route("/admin/devices/:id", post(save_device))
+}
+
+async fn render(app_state: &AppState, auth: &AuthContext, saved: Option<String>, error: Option<String>) -> Response {
+    let current = match auth {
+        AuthContext::Session(session) => Some(session.device_id()),
+        AuthContext::ApiKey(_) => None,
+    };
+    let status = if error.is_some() { StatusCode::BAD_REQUEST } else { StatusCode::OK };
+    let template = DevicesTemplate {
+        devices: app_state.auth_manager.list_sessions().await,
+        current,
+        saved,
+        error,
+    };
+    match template.render() {
+        Ok(html) => (status, Html(html)).into_response(),
+        Err(e) => AppError::internal("Error rendering page", e).into_response(),
+    }
+}
+
+/// Every logged-in device, each with its name and note to edit (GET /admin/devices)
+async fn devices_page(
+    State(app_state): State<AppState>,
+    auth: AuthContext,
+    Query(query): Query<DevicesQuery>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+    render(&app_state, &auth, query.saved, None).await
+}
+
+/// Rename or annotate a device (POST /admin/devices/:id)
+async fn save_device(
+    State(app_state): State<AppState>,
+    auth: AuthContext,
+    Path(id): Path<String>,
+    Form(details): Form<DeviceDetails>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+    if let Err(e) = details.check() {
+        return render(&app_state, &auth, None, Some(e)).await;
+    }
+    if app_state.auth_manager.update_device(&id, details).await.is_none() {
+        return AppError::not_found("No device with that id").into_response();
+    }
+    app_state.auth_manager.save_sessions_to_file(&app_state.tokens_file_manager).await;
+    tracing::info!("{} updated device {}", auth.label(), id);
+    let location = crate::handlers::app_url(&format!("/admin/devices?saved={}", id));
+    (StatusCode::SEE_OTHER, [(header::LOCATION, location)]).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn details(name: Option<&str>, note: Option<&str>) -> DeviceDetails {
+        DeviceDetails { name: name.map(str::to_string), note: note.map(str::to_string) }
+    }
+
+    #[test]
+    fn test_device_details() {
+        assert!(details(Some("Tablet"), Some("kitchen tablet")).check().is_ok());
+        assert!(details(Some(&"x".repeat(MAX_NAME_CHARS + 1)), None).check().is_err());
+        assert!(details(None, Some(&"x".repeat(MAX_NOTE_CHARS + 1))).check().is_err());
+        assert!(details(None, Some("two\nlines")).check().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_update_device_persists() {
+        let auth_manager = crate::auth::AuthManager::new();
+        let passcode = auth_manager.create_auth_request(Some("iPad".to_string()), false).await;
+        let token = auth_manager.authenticate(&passcode, Some("iPad".to_string()), false).await.unwrap();
+        let device_id = auth_manager.get_session_info(&token).await.unwrap().device_id();
+
+        let updated = auth_manager.update_device(&device_id, details(None, Some(" kitchen tablet "))).await.unwrap();
+        assert_eq!(updated.device_name.as_deref(), Some("iPad"));
+        assert_eq!(updated.note.as_deref(), Some("kitchen tablet"));
+        auth_manager.update_device(&device_id, details(Some("Kitchen iPad"), Some(""))).await.unwrap();
+        assert!(auth_manager.update_device("no-such-device", DeviceDetails::default()).await.is_none());
+
+        let restored = crate::auth::AuthManager::new();
+        restored.load_sessions(&auth_manager.get_sessions_data().await).await;
+        let session = restored.get_session_info(&token).await.unwrap();
+        assert_eq!(session.device_name.as_deref(), Some("Kitchen iPad"));
+        assert_eq!(session.note, None);
+    }
+}
@@ -0,0 +1,400 @@
+use crate::config::Config;
+use crate::cycle_date::CycleDate;
+use crate::journal::JournalManager;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// Suffix given to files set aside because they couldn't be read
+const QUARANTINE_SUFFIX: &str = ".unreadable";
+/// Rounds of repair; renaming a malformed directory can expose issues inside it
+const MAX_PASSES: usize = 2;
+
+/// What's wrong with part of the journal directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    MalformedDirectory,
+    OrphanPrompt,
+    EmptyEntry,
+    MissingSummary,
+    UnreadableFile,
+}
+
+impl IssueKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            IssueKind::MalformedDirectory => "Malformed date folder",
+            IssueKind::OrphanPrompt => "Orphan prompt",
+            IssueKind::EmptyEntry => "Empty entry",
+            IssueKind::MissingSummary => "Missing summary",
+            IssueKind::UnreadableFile => "Unreadable file",
+        }
+    }
+}
+
+/// A change that fixes an issue
+#[derive(Debug, Clone, PartialEq)]
+enum Repair {
+    /// Give a date folder its canonical name
+    RenameDirectory { from: PathBuf, to: PathBuf },
+    /// Close gaps in a day's prompt numbering, moving prompt metadata along with the files
+    RenumberPrompts { cycle_date: CycleDate, moves: Vec<(u8, u8)> },
+    RemoveFile(PathBuf),
+    /// Set a file aside under a `.unreadable` name so it stops breaking loads
+    Quarantine(PathBuf),
+}
+
+/// One problem found in a journal directory
+#[derive(Debug, Clone, Serialize)]
+pub struct Issue {
+    pub kind: IssueKind,
+    /// Relative to the journal directory
+    pub path: String,
+    pub detail: String,
+    /// What repairing does about it; None when it needs a person (or the nightly run)
+    pub fix: Option<String>,
+    #[serde(skip)]
+    repair: Option<Repair>,
+}
+
+/// Issues found by a check, and those fixed by a repair
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DoctorReport {
+    pub issues: Vec<Issue>,
+    pub fixed: Vec<Issue>,
+    /// Snapshot of the files the repair changed, for rolling it back
+    pub snapshot_id: Option<String>,
+}
+
+impl DoctorReport {
+    pub fn fixable(&self) -> usize {
+        self.issues.iter().filter(|i| i.fix.is_some()).count()
+    }
+}
+
+/// Scan a journal directory for problems without changing anything
+pub async fn check(journal_manager: &JournalManager) -> Result<DoctorReport, Box<dyn std::error::Error>> {
+    Ok(DoctorReport {
+        issues: scan(journal_manager, &CycleDate::today()).await?,
+        ..DoctorReport::default()
+    })
+}
+
+/// Fix what can be fixed, snapshotting every file touched first, then report what's left
+pub async fn repair(config: &Config, journal_manager: &JournalManager) -> Result<DoctorReport, Box<dyn std::error::Error>> {
+    let today = CycleDate::today();
+    let journal_dir = journal_manager.base_path();
+    let mut report = DoctorReport::default();
+
+    for _ in 0..MAX_PASSES {
+        let (fixable, _): (Vec<Issue>, Vec<Issue>) = scan(journal_manager, &today).await?.into_iter().partition(|i| i.repair.is_some());
+        if fixable.is_empty() {
+            break;
+        }
+
+        let mut touched = Vec::new();
+        for repair in fixable.iter().filter_map(|i| i.repair.as_ref()) {
+            touched.extend(touched_paths(journal_manager, repair)?);
+        }
+        let snapshot_id = crate::snapshot::before(config, format!("Repair journal {}", journal_dir.display()), touched).await?;
+        report.snapshot_id.get_or_insert(snapshot_id);
+
+        for issue in fixable {
+            if let Some(repair) = &issue.repair {
+                apply(journal_manager, repair).await?;
+            }
+            tracing::info!("Repaired {}: {}", issue.path, issue.fix.as_deref().unwrap_or_default());
+            report.fixed.push(issue);
+        }
+        journal_manager.rebuild_index().await?;
+    }
+
+    if !report.fixed.is_empty() {
+        journal_manager.record_change(format!("Repair {} journal issues", report.fixed.len())).await;
+    }
+    report.issues = scan(journal_manager, &today).await?;
+    Ok(report)
+}
+
+/// Find every issue, directory by directory in name order
+async fn scan(journal_manager: &JournalManager, today: &CycleDate) -> Result<Vec<Issue>, Box<dyn std::error::Error>> {
+    let journal_dir = journal_manager.base_path();
+    let mut issues = Vec::new();
+    if !journal_dir.exists() {
+        return Ok(issues);
+    }
+
+    let mut names = Vec::new();
+    let mut dir_entries = fs::read_dir(journal_dir).await?;
+    while let Some(entry) = dir_entries.next_entry().await? {
+        names.push((entry.file_name().to_string_lossy().to_string(), entry.file_type().await?.is_dir()));
+    }
+    names.sort();
+
+    for (name, is_dir) in names {
+        if !is_dir {
+            // Config files at the top level are reported but left for a person to look at
+            if let Some(detail) = unreadable(&journal_dir.join(&name)).await {
+                issues.push(issue(IssueKind::UnreadableFile, name, detail, None));
+            }
+            continue;
+        }
+
+        // Anything named like a date is meant to be one; other folders (reports, .git) aren't ours
+        if !crate::cycle_date::is_date_like(&name) {
+            continue;
+        }
+        match CycleDate::from_string(name.trim()) {
+            Ok(cycle_date) if cycle_date.to_string() == name => {
+                scan_day(journal_manager, &cycle_date, today, &mut issues).await?;
+            }
+            Ok(cycle_date) => {
+                let to = journal_dir.join(cycle_date.to_string());
+                let issue = if to.exists() {
+                    issue(IssueKind::MalformedDirectory, name, format!("Should be named {}, which already exists; merge them by hand", cycle_date), None)
+                } else {
+                    Issue {
+                        kind: IssueKind::MalformedDirectory,
+                        detail: format!("Should be named {}", cycle_date),
+                        fix: Some(format!("Rename to {}", cycle_date)),
+                        repair: Some(Repair::RenameDirectory { from: journal_dir.join(&name), to }),
+                        path: name,
+                    }
+                };
+                issues.push(issue);
+            }
+            Err(e) => issues.push(issue(IssueKind::MalformedDirectory, name, format!("Not a cycle date: {}", e), None)),
+        }
+    }
+    Ok(issues)
+}
+
+/// Check the files in one date folder
+async fn scan_day(journal_manager: &JournalManager, cycle_date: &CycleDate, today: &CycleDate, issues: &mut Vec<Issue>) -> Result<(), Box<dyn std::error::Error>> {
+    let date_dir = journal_manager.base_path().join(cycle_date.to_string());
+    let relative = |file: &str| format!("{}/{}", cycle_date, file);
+
+    let mut files = Vec::new();
+    let mut dir_entries = fs::read_dir(&date_dir).await?;
+    while let Some(entry) = dir_entries.next_entry().await? {
+        if entry.file_type().await?.is_file() {
+            files.push(entry.file_name().to_string_lossy().to_string());
+        }
+    }
+    files.sort();
+
+    let mut unreadable_files = Vec::new();
+    for file in &files {
+        let path = date_dir.join(file);
+        if let Some(detail) = unreadable(&path).await {
+            // A file that can't be opened can't be snapshotted either, so only content problems are fixed
+            let repair = std::fs::read(&path).is_ok().then(|| Repair::Quarantine(path.clone()));
+            let fix = repair.as_ref().map(|_| format!("Rename to {}{}", file, QUARANTINE_SUFFIX));
+            issues.push(Issue { kind: IssueKind::UnreadableFile, path: relative(file), detail, fix, repair });
+            unreadable_files.push(file.as_str());
+        }
+    }
+
+    // Prompts are loaded from 1 up to the first missing number; any after a gap never show
+    let mut numbers: Vec<u8> = files
+        .iter()
+        .filter(|f| !unreadable_files.contains(&f.as_str()))
+        .filter_map(|f| f.strip_prefix("prompt")?.strip_suffix(".txt")?.parse().ok())
+        .filter(|n| *n > 0)
+        .collect();
+    numbers.sort_unstable();
+    let moves: Vec<(u8, u8)> = numbers
+        .iter()
+        .zip(1..)
+        .filter(|(number, position)| *number != position)
+        .map(|(number, position)| (*number, position))
+        .collect();
+    if !moves.is_empty() {
+        let described: Vec<String> = moves.iter().map(|(from, to)| format!("prompt{} to prompt{}", from, to)).collect();
+        let orphans: Vec<String> = moves.iter().map(|(from, _)| format!("prompt{}.txt", from)).collect();
+        issues.push(Issue {
+            kind: IssueKind::OrphanPrompt,
+            path: relative(&orphans.join(", ")),
+            detail: "Numbered after a gap, so never shown".to_string(),
+            fix: Some(format!("Renumber {}", described.join(", "))),
+            repair: Some(Repair::RenumberPrompts { cycle_date: *cycle_date, moves }),
+        });
+    }
+
+    let paths = journal_manager.get_file_paths(cycle_date);
+    if !paths.entry.exists() || unreadable_files.contains(&"entry.txt") {
+        return Ok(());
+    }
+    let content = fs::read_to_string(&paths.entry).await?;
+    if content.trim().is_empty() {
+        issues.push(Issue {
+            kind: IssueKind::EmptyEntry,
+            path: relative("entry.txt"),
+            detail: "Has no text".to_string(),
+            fix: Some("Delete it".to_string()),
+            repair: Some(Repair::RemoveFile(paths.entry)),
+        });
+    } else if !paths.summary.exists() && !paths.one_line.exists() && cycle_date.to_real_date() < today.to_real_date() && !journal_manager.is_pending_review(cycle_date).await {
+        issues.push(issue(
+            IssueKind::MissingSummary,
+            relative("summary.txt"),
+            "The entry has no summary; the next processing run writes one".to_string(),
+            None,
+        ));
+    }
+    Ok(())
+}
+
+fn issue(kind: IssueKind, path: String, detail: String, fix: Option<String>) -> Issue {
+    Issue { kind, path, detail, fix, repair: None }
+}
+
+/// Why a text or JSON file can't be used, if it can't
+async fn unreadable(path: &Path) -> Option<String> {
+    let extension = path.extension().and_then(|e| e.to_str())?;
+    if extension != "txt" && extension != "json" {
+        return None;
+    }
+    let bytes = match fs::read(path).await {
+        Ok(bytes) => bytes,
+        Err(e) => return Some(format!("Can't be read: {}", e)),
+    };
+    let Ok(text) = String::from_utf8(bytes) else {
+        return Some("Not valid UTF-8 text".to_string());
+    };
+    if extension == "json" {
+        if let Err(e) = serde_json::from_str::<serde_json::Value>(&text) {
+            return Some(format!("Not valid JSON: {}", e));
+        }
+    }
+    None
+}
+
+/// Files a repair creates, changes, or removes, for the snapshot taken before it
+fn touched_paths(journal_manager: &JournalManager, repair: &Repair) -> std::io::Result<Vec<PathBuf>> {
+    Ok(match repair {
+        Repair::RenameDirectory { from, to } => {
+            let mut paths = Vec::new();
+            for file in files_under(from)? {
+                if let Ok(relative) = file.strip_prefix(from) {
+                    paths.push(to.join(relative));
+                }
+                paths.push(file);
+            }
+            paths
+        }
+        Repair::RenumberPrompts { cycle_date, moves } => {
+            let date_dir = journal_manager.base_path().join(cycle_date.to_string());
+            let mut paths = vec![journal_manager.get_file_paths(cycle_date).metadata];
+            for (from, to) in moves {
+                paths.push(date_dir.join(format!("prompt{}.txt", from)));
+                paths.push(date_dir.join(format!("prompt{}.txt", to)));
+            }
+            paths
+        }
+        Repair::RemoveFile(path) => vec![path.clone()],
+        Repair::Quarantine(path) => vec![path.clone(), quarantine_path(path)],
+    })
+}
+
+fn files_under(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(files_under(&path)?);
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+fn quarantine_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(QUARANTINE_SUFFIX);
+    path.with_file_name(name)
+}
+
+async fn apply(journal_manager: &JournalManager, repair: &Repair) -> Result<(), Box<dyn std::error::Error>> {
+    match repair {
+        Repair::RenameDirectory { from, to } => fs::rename(from, to).await?,
+        Repair::RenumberPrompts { cycle_date, moves } => {
+            let date_dir = journal_manager.base_path().join(cycle_date.to_string());
+            // Moves go downward in ascending order, so each target is already free
+            for (from, to) in moves {
+                fs::rename(date_dir.join(format!("prompt{}.txt", from)), date_dir.join(format!("prompt{}.txt", to))).await?;
+            }
+            let mut metadata = journal_manager.load_metadata(cycle_date).await?;
+            if !metadata.prompts.is_empty() {
+                for (from, to) in moves {
+                    match metadata.prompts.remove(from) {
+                        Some(prompt) => metadata.prompts.insert(*to, prompt),
+                        None => metadata.prompts.remove(to),
+                    };
+                }
+                journal_manager.save_metadata(cycle_date, &metadata).await?;
+            }
+        }
+        Repair::RemoveFile(path) => fs::remove_file(path).await?,
+        Repair::Quarantine(path) => fs::rename(path, quarantine_path(path)).await?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_check_and_repair() {
+        let temp_dir = TempDir::new().unwrap();
+        let journal_dir = temp_dir.path().join("journal");
+        let write = |path: &str, content: &[u8]| {
+            let path = journal_dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write("01a20/entry.txt", b"Lowercase folder");
+        write("01A21/entry.txt", b"  \n");
+        write("01A22/entry.txt", b"Written and summarized");
+        write("01A22/summary.txt", b"Summary");
+        write("01A22/prompt1.txt", b"First");
+        write("01A22/prompt3.txt", b"Stranded");
+        write("01A22/meta.json", b"{\"prompts\": {\"3\": {\"generated_at\": \"2024-01-01T08:00:00+00:00\", \"prompt_type\": \"Daily\"}}}");
+        write("01A23/status.txt", &[0xff, 0xfe, 0x00]);
+        write("1A2/entry.txt", b"Unparseable");
+        std::fs::create_dir_all(journal_dir.join("reports")).unwrap();
+
+        let journal_manager = JournalManager::new(&journal_dir);
+        let report = check(&journal_manager).await.unwrap();
+        let kinds: Vec<(IssueKind, &str)> = report.issues.iter().map(|i| (i.kind, i.path.as_str())).collect();
+        assert_eq!(kinds, vec![
+            (IssueKind::EmptyEntry, "01A21/entry.txt"),
+            (IssueKind::OrphanPrompt, "01A22/prompt3.txt"),
+            (IssueKind::UnreadableFile, "01A23/status.txt"),
+            (IssueKind::MalformedDirectory, "01a20"),
+            (IssueKind::MalformedDirectory, "1A2"),
+        ]);
+        assert_eq!(report.fixable(), 4);
+
+        let mut config = Config::default();
+        config.backup.destination = temp_dir.path().join("backups").to_string_lossy().to_string();
+        let report = repair(&config, &journal_manager).await.unwrap();
+        assert_eq!(report.fixed.len(), 4);
+        assert!(report.snapshot_id.is_some());
+
+        // The renamed folder's entry has no summary, which only the nightly run can fix
+        let remaining: Vec<(IssueKind, &str)> = report.issues.iter().map(|i| (i.kind, i.path.as_str())).collect();
+        assert_eq!(remaining, vec![(IssueKind::MissingSummary, "01A20/summary.txt"), (IssueKind::MalformedDirectory, "1A2")]);
+        assert!(journal_dir.join("01A20/entry.txt").exists());
+        assert!(!journal_dir.join("01A21/entry.txt").exists());
+        assert!(journal_dir.join("01A23/status.txt.unreadable").exists());
+
+        let date = CycleDate::from_string("01A22").unwrap();
+        assert_eq!(journal_manager.load_all_prompts(&date).await.len(), 2);
+        assert!(journal_manager.load_metadata(&date).await.unwrap().prompts.contains_key(&2));
+    }
+}
@@ -0,0 +1,166 @@
+use crate::config::EmailInConfig;
+use chrono::{DateTime, Local, TimeZone};
+use std::collections::HashMap;
+
+/// An email posted by a mail service's inbound webhook
+#[derive(Debug, Clone, PartialEq)]
+pub struct InboundEmail {
+    pub from: String,
+    pub subject: String,
+    pub text: String,
+    pub sent_at: DateTime<Local>,
+}
+
+/// Webhook field names used by common mail services for each part of the message
+const FROM_FIELDS: &[&str] = &["from", "From", "sender"];
+const SUBJECT_FIELDS: &[&str] = &["subject", "Subject"];
+const TEXT_FIELDS: &[&str] = &["stripped-text", "body-plain", "TextBody", "text", "plain"];
+const DATE_FIELDS: &[&str] = &["date", "Date", "timestamp"];
+
+impl InboundEmail {
+    /// Read an email from webhook fields, accepting the names Mailgun and Postmark use.
+    /// A missing or unreadable date falls back to when the webhook arrived.
+    pub fn from_fields(fields: &HashMap<String, String>) -> Result<Self, String> {
+        let field = |names: &[&str]| names.iter().find_map(|name| fields.get(*name)).map(|v| v.trim().to_string());
+
+        let from = field(FROM_FIELDS).filter(|f| !f.is_empty()).ok_or("Email has no sender")?;
+        let text = field(TEXT_FIELDS).unwrap_or_default();
+        let subject = field(SUBJECT_FIELDS).unwrap_or_default();
+        if text.is_empty() && subject.is_empty() {
+            return Err("Email has no text".to_string());
+        }
+
+        Ok(Self {
+            from,
+            subject,
+            text,
+            sent_at: field(DATE_FIELDS).and_then(|d| parse_date(&d)).unwrap_or_else(Local::now),
+        })
+    }
+
+    /// Bare address of the sender, lowercased: "Ana <ana@example.com>" becomes "ana@example.com"
+    pub fn sender_address(&self) -> String {
+        let address = match (self.from.rfind('<'), self.from.rfind('>')) {
+            (Some(start), Some(end)) if start < end => &self.from[start + 1..end],
+            _ => self.from.as_str(),
+        };
+        address.trim().to_lowercase()
+    }
+
+    /// The text to add to the journal: the subject as a heading, then the body without
+    /// quoted replies or the signature
+    pub fn entry_text(&self) -> String {
+        let mut body = Vec::new();
+        for line in self.text.lines() {
+            // "-- " is the standard signature separator
+            if line == "-- " || line.trim_start().starts_with('>') {
+                break;
+            }
+            body.push(line.trim_end());
+        }
+        let body = body.join("\n").trim().to_string();
+
+        match (self.subject.is_empty(), body.is_empty()) {
+            (true, _) => body,
+            (false, true) => self.subject.clone(),
+            (false, false) => format!("## {}\n\n{}", self.subject, body),
+        }
+    }
+}
+
+/// The top-level string and number fields of a JSON webhook body
+pub fn json_fields(body: &serde_json::Value) -> HashMap<String, String> {
+    body.as_object()
+        .map(|object| {
+            object
+                .iter()
+                .filter_map(|(key, value)| match value {
+                    serde_json::Value::String(text) => Some((key.clone(), text.clone())),
+                    serde_json::Value::Number(number) => Some((key.clone(), number.to_string())),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether `email` may journal under `config`
+pub fn sender_allowed(config: &EmailInConfig, email: &InboundEmail) -> bool {
+    let address = email.sender_address();
+    config.allowed_senders.is_empty() || config.allowed_senders.iter().any(|allowed| allowed.trim().eq_ignore_ascii_case(&address))
+}
+
+/// Compare the webhook's secret to the configured one without leaking where they differ
+pub fn secret_matches(config: &EmailInConfig, given: &str) -> bool {
+    let expected = config.secret.as_bytes();
+    let given = given.as_bytes();
+    !expected.is_empty()
+        && expected.len() == given.len()
+        && expected.iter().zip(given).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// An RFC 2822 date header, or Unix seconds as Mailgun sends them
+fn parse_date(value: &str) -> Option<DateTime<Local>> {
+    if let Ok(date) = DateTime::parse_from_rfc2822(value) {
+        return Some(date.with_timezone(&Local));
+    }
+    value.parse::<i64>().ok().and_then(|seconds| Local.timestamp_opt(seconds, 0).single())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_parse_mailgun_fields() {
+        let email = InboundEmail::from_fields(&fields(&[
+            ("sender", "Ana <Ana@Example.com>"),
+            ("subject", "On the train"),
+            ("body-plain", "Saw the sea from the window.\n\n-- \nSent from my phone"),
+            ("timestamp", "1740909600"),
+        ])).unwrap();
+
+        assert_eq!(email.sender_address(), "ana@example.com");
+        assert_eq!(email.sent_at, Local.timestamp_opt(1740909600, 0).unwrap());
+        assert_eq!(email.entry_text(), "## On the train\n\nSaw the sea from the window.");
+        assert!(InboundEmail::from_fields(&fields(&[("subject", "No sender")])).is_err());
+
+        // Postmark posts JSON with nested objects alongside the plain fields
+        let body = serde_json::json!({
+            "From": "ana@example.com",
+            "FromFull": { "Email": "ana@example.com" },
+            "Subject": "",
+            "TextBody": "Quiet day.\n> On Monday you wrote:\n> hello",
+            "Date": "Sun, 2 Mar 2025 10:00:00 +0000",
+        });
+        let email = InboundEmail::from_fields(&json_fields(&body)).unwrap();
+        assert_eq!(email.entry_text(), "Quiet day.");
+        assert_eq!(email.sent_at, DateTime::parse_from_rfc2822("Sun, 2 Mar 2025 10:00:00 +0000").unwrap());
+    }
+
+    #[test]
+    fn test_sender_and_secret_checks() {
+        let config = EmailInConfig {
+            enabled: true,
+            secret: "s3cret".to_string(),
+            allowed_senders: vec!["ana@example.com".to_string()],
+            notebook: None,
+        };
+        let email = |from: &str| InboundEmail {
+            from: from.to_string(),
+            subject: String::new(),
+            text: "Hello".to_string(),
+            sent_at: Local::now(),
+        };
+
+        assert!(sender_allowed(&config, &email("Ana <ANA@example.com>")));
+        assert!(!sender_allowed(&config, &email("mallory@example.com")));
+        assert!(secret_matches(&config, "s3cret"));
+        assert!(!secret_matches(&config, "s3cre"));
+        assert!(!secret_matches(&EmailInConfig::default(), ""));
+    }
+}
@@ -0,0 +1,209 @@
+use crate::cycle_date::CycleDate;
+use crate::journal::JournalManager;
+use crate::llm_worker::LlmWorker;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tokio::fs;
+
+const EMBEDDINGS_FILE: &str = "embeddings.json";
+
+/// Entries embedded per request to Ollama
+const BATCH_SIZE: usize = 16;
+
+/// Embedding of one entry, with a hash of the text it was computed from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredEmbedding {
+    pub content_hash: String,
+    pub vector: Vec<f32>,
+}
+
+/// Entry embeddings keyed by cycle date, persisted to `embeddings.json` in the journal directory.
+/// Derived data: it can be deleted at any time and is rebuilt on the next refresh.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EmbeddingIndex {
+    /// Model the vectors came from; switching models invalidates all of them
+    pub model: String,
+    pub entries: BTreeMap<String, StoredEmbedding>,
+}
+
+impl EmbeddingIndex {
+    /// Load the index from a journal directory; a missing or unreadable index is empty
+    pub async fn load(base_path: &Path) -> Self {
+        match fs::read_to_string(base_path.join(EMBEDDINGS_FILE)).await {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring unreadable embedding index: {}", e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub async fn save(&self, base_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(base_path.join(EMBEDDINGS_FILE), serde_json::to_string(self)?).await?;
+        Ok(())
+    }
+
+    /// Whether the stored embedding for `date` is missing or was computed from different text
+    fn is_stale(&self, date: &str, content_hash: &str) -> bool {
+        self.entries.get(date).is_none_or(|stored| stored.content_hash != content_hash)
+    }
+
+    /// Dates whose entries are closest to `query`, most similar first
+    pub fn nearest(&self, query: &[f32], limit: usize, exclude: Option<&str>) -> Vec<(CycleDate, f32)> {
+        let mut scored: Vec<(CycleDate, f32)> = self
+            .entries
+            .iter()
+            .filter(|(date, _)| Some(date.as_str()) != exclude)
+            .filter_map(|(date, stored)| {
+                let cycle_date = CycleDate::from_string(date).ok()?;
+                Some((cycle_date, cosine_similarity(query, &stored.vector)))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.truncate(limit);
+        scored
+    }
+
+    /// Entries most similar to the entry on `cycle_date`; empty if that entry isn't embedded yet
+    pub fn related(&self, cycle_date: &CycleDate, limit: usize) -> Vec<(CycleDate, f32)> {
+        let date = cycle_date.to_string();
+        match self.entries.get(&date) {
+            Some(stored) => self.nearest(&stored.vector, limit, Some(&date)),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Cosine similarity of two vectors; 0.0 when either is empty or their lengths differ
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.is_empty() || a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+fn content_hash(content: &str) -> String {
+    hex::encode(Sha256::digest(content.as_bytes()))
+}
+
+/// Embed entries that are new or changed since the index was last refreshed, and drop
+/// embeddings of deleted entries. Returns the number of entries embedded.
+pub async fn refresh_index(
+    journal_manager: &JournalManager,
+    llm_worker: &LlmWorker,
+    model: &str,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let base_path = journal_manager.base_path();
+    let mut index = EmbeddingIndex::load(base_path).await;
+    if index.model != model {
+        index = EmbeddingIndex {
+            model: model.to_string(),
+            entries: BTreeMap::new(),
+        };
+    }
+
+    let mut pending = Vec::new();
+    let mut existing = std::collections::HashSet::new();
+    let dates = journal_manager.list_dates().await?;
+    for cycle_date in dates {
+        let Some(entry) = journal_manager.load_entry(&cycle_date).await? else {
+            continue;
+        };
+        let date = cycle_date.to_string();
+        let hash = content_hash(&entry.content);
+        if index.is_stale(&date, &hash) {
+            pending.push((date.clone(), hash, entry.content));
+        }
+        existing.insert(date);
+    }
+
+    let removed = index.entries.len();
+    index.entries.retain(|date, _| existing.contains(date));
+    let removed = removed - index.entries.len();
+    if pending.is_empty() && removed == 0 {
+        return Ok(0);
+    }
+
+    let embedded = pending.len();
+    for batch in pending.chunks(BATCH_SIZE) {
+        let vectors = llm_worker.embed(batch.iter().map(|(_, _, content)| content.clone()).collect()).await?;
+        for ((date, hash, _), vector) in batch.iter().zip(vectors) {
+            index.entries.insert(date.clone(), StoredEmbedding {
+                content_hash: hash.clone(),
+                vector,
+            });
+        }
+        // Save as we go so a failure partway through keeps the finished batches
+        index.save(base_path).await?;
+    }
+    if embedded == 0 {
+        index.save(base_path).await?;
+    }
+
+    tracing::info!("Embedded {} entries in {}", embedded, base_path.display());
+    Ok(embedded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn index_with(entries: &[(&str, Vec<f32>)]) -> EmbeddingIndex {
+        EmbeddingIndex {
+            model: "test".to_string(),
+            entries: entries
+                .iter()
+                .map(|(date, vector)| (date.to_string(), StoredEmbedding {
+                    content_hash: content_hash(date),
+                    vector: vector.clone(),
+                }))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 3.0]).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 0.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    fn test_related_entries() {
+        let index = index_with(&[
+            ("01210", vec![1.0, 0.0, 0.0]),
+            ("01211", vec![0.9, 0.1, 0.0]),
+            ("01212", vec![0.0, 0.0, 1.0]),
+            ("01213", vec![0.6, 0.4, 0.0]),
+        ]);
+        let related = index.related(&CycleDate::from_string("01210").unwrap(), 2);
+        let dates: Vec<String> = related.iter().map(|(d, _)| d.to_string()).collect();
+        assert_eq!(dates, vec!["01211", "01213"]);
+        assert!(index.related(&CycleDate::from_string("01214").unwrap(), 2).is_empty());
+        assert!(index.is_stale("01210", "other hash"));
+        assert!(!index.is_stale("01210", &content_hash("01210")));
+    }
+
+    #[tokio::test]
+    async fn test_index_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(EmbeddingIndex::load(temp_dir.path()).await.entries.is_empty());
+
+        let index = index_with(&[("01210", vec![0.5, 0.5])]);
+        index.save(temp_dir.path()).await.unwrap();
+        let loaded = EmbeddingIndex::load(temp_dir.path()).await;
+        assert_eq!(loaded.model, "test");
+        assert_eq!(loaded.entries["01210"].vector, vec![0.5, 0.5]);
+    }
+}
@@ -0,0 +1,286 @@
+//! The application's error type, shared by pages and the JSON API.
+//!
+//! Handlers return an `AppError`; the `handle_errors` middleware renders it as a
+//! friendly page for browsers, or as the JSON envelope for API clients:
+//! `{"error": {"code": "not_found", "message": "No entry for 03B25", "request_id": "..."}}`,
+//! with an optional `details` object (e.g., the current entry on a 409 conflict).
+//! Every request gets an ID, logged with everything the request does and returned in
+//! the `X-Request-Id` header, so a reported error can be found in the logs.
+
+use askama::Template;
+use axum::{
+    extract::{
+        rejection::{JsonRejection, QueryRejection},
+        Request,
+    },
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{Html, IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use tracing::Instrument;
+
+/// Header carrying the request ID, in both directions
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// A failed request: an HTTP status, a stable machine-readable code, and a message
+/// safe to show the user
+#[derive(Debug, Clone)]
+pub struct AppError {
+    pub status: StatusCode,
+    /// Stable, machine-readable reason ("not_found", "conflict", ...)
+    pub code: &'static str,
+    pub message: String,
+    pub details: Option<serde_json::Value>,
+}
+
+#[derive(Serialize)]
+struct ErrorEnvelope<'a> {
+    error: ErrorBody<'a>,
+}
+
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    code: &'a str,
+    message: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<&'a serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<&'a str>,
+}
+
+/// Page shown to browsers when a request fails
+#[derive(Template)]
+#[template(path = "error.html")]
+struct ErrorTemplate<'a> {
+    title: &'a str,
+    message: &'a str,
+    request_id: &'a str,
+}
+
+impl AppError {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self { status, code, message: message.into(), details: None }
+    }
+
+    pub fn with_details<T: Serialize>(mut self, details: &T) -> Self {
+        self.details = serde_json::to_value(details).ok();
+        self
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "bad_request", message)
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNAUTHORIZED, "unauthorized", message)
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::FORBIDDEN, "forbidden", message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, "not_found", message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::CONFLICT, "conflict", message)
+    }
+
+    /// A feature that isn't configured or a service that isn't running
+    pub fn unavailable(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::SERVICE_UNAVAILABLE, "unavailable", message)
+    }
+
+    /// Log the underlying error and answer with a generic message
+    pub fn internal(context: &str, error: impl std::fmt::Display) -> Self {
+        tracing::error!("{}: {}", context, error);
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "internal", context)
+    }
+
+    /// Map a handler-style `(status, message)` failure onto an error
+    pub fn from_status(status: StatusCode, message: impl Into<String>) -> Self {
+        let code = match status {
+            StatusCode::BAD_REQUEST => "bad_request",
+            StatusCode::UNAUTHORIZED => "unauthorized",
+            StatusCode::FORBIDDEN => "forbidden",
+            StatusCode::NOT_FOUND => "not_found",
+            StatusCode::CONFLICT => "conflict",
+            StatusCode::PAYLOAD_TOO_LARGE => "body_too_large",
+            StatusCode::UNSUPPORTED_MEDIA_TYPE => "unsupported_media_type",
+            StatusCode::UNPROCESSABLE_ENTITY => "unprocessable",
+            StatusCode::TOO_MANY_REQUESTS => "rate_limited",
+            StatusCode::BAD_GATEWAY => "upstream_failed",
+            StatusCode::SERVICE_UNAVAILABLE => "unavailable",
+            _ => "internal",
+        };
+        Self::new(status, code, message)
+    }
+
+    /// The JSON envelope on its own, for reporting one failure inside a larger response
+    pub fn envelope(&self) -> serde_json::Value {
+        let envelope = ErrorEnvelope {
+            error: ErrorBody { code: self.code, message: &self.message, details: self.details.as_ref(), request_id: None },
+        };
+        serde_json::to_value(envelope).unwrap_or_default()
+    }
+
+    fn json(&self, request_id: Option<&str>) -> Response {
+        let envelope = ErrorEnvelope {
+            error: ErrorBody { code: self.code, message: &self.message, details: self.details.as_ref(), request_id },
+        };
+        (self.status, Json(envelope)).into_response()
+    }
+
+    fn page(&self, request_id: &str) -> Response {
+        let template = ErrorTemplate {
+            title: self.status.canonical_reason().unwrap_or("Error"),
+            message: &self.message,
+            request_id,
+        };
+        match template.render() {
+            Ok(html) => (self.status, Html(html)).into_response(),
+            Err(e) => {
+                tracing::error!("Failed to render error page: {}", e);
+                (self.status, Html(format!("<p>{}</p>", crate::feed::escape_xml(&self.message)))).into_response()
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} ({})", self.message, self.status)
+    }
+}
+
+impl IntoResponse for AppError {
+    /// The JSON envelope, kept with the error itself so `handle_errors` can re-render
+    /// it with the request ID, or as a page for browsers
+    fn into_response(self) -> Response {
+        let mut response = self.json(None);
+        response.extensions_mut().insert(self);
+        response
+    }
+}
+
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE {
+            return Self::new(rejection.status(), "body_too_large", "The request body is larger than this server accepts");
+        }
+        Self::new(rejection.status(), "invalid_body", rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for AppError {
+    fn from(rejection: QueryRejection) -> Self {
+        Self::bad_request(rejection.body_text())
+    }
+}
+
+/// Middleware giving each request an ID (a proxy's `X-Request-Id`, if it sent a sane one),
+/// logging under it, and rendering any `AppError` for whoever asked: JSON for API paths
+/// and clients that accept or send it, a page for everyone else
+pub async fn handle_errors(request: Request, next: Next) -> Response {
+    let request_id = request.headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
+        .map(str::to_string)
+        .unwrap_or_else(new_request_id);
+    let wants_json = request.uri().path().starts_with("/api/") || accepts_json(request.headers()) || sends_json(request.headers());
+
+    let span = tracing::info_span!("request", id = %request_id);
+    let mut response = next.run(request).instrument(span).await;
+    if let Some(error) = response.extensions_mut().remove::<AppError>() {
+        let rendered = if wants_json { error.json(Some(&request_id)) } else { error.page(&request_id) };
+        // Keep headers the handler set (e.g. Retry-After), but not the old body's
+        let (mut parts, _) = response.into_parts();
+        let (rendered_parts, body) = rendered.into_parts();
+        parts.headers.remove(header::CONTENT_LENGTH);
+        if let Some(content_type) = rendered_parts.headers.get(header::CONTENT_TYPE) {
+            parts.headers.insert(header::CONTENT_TYPE, content_type.clone());
+        }
+        response = Response::from_parts(parts, body);
+    }
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+fn new_request_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..16].to_string()
+}
+
+/// IDs from upstream are logged and echoed, so only short, plain ones are kept
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= 64 && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+fn accepts_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("application/json"))
+}
+
+/// Scripts posting JSON expect a JSON error back, whatever they accept
+fn sends_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("application/json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_error_envelope() {
+        let response = AppError::conflict("Entry changed").with_details(&serde_json::json!({ "current_revision": "abc" })).json(Some("r1"));
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["error"]["code"], "conflict");
+        assert_eq!(json["error"]["message"], "Entry changed");
+        assert_eq!(json["error"]["details"]["current_revision"], "abc");
+        assert_eq!(json["error"]["request_id"], "r1");
+
+        // Without the middleware, the envelope is still complete apart from the ID
+        let response = AppError::not_found("No entry").into_response();
+        assert!(response.extensions().get::<AppError>().is_some());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(json["error"].get("details").is_none());
+        assert!(json["error"].get("request_id").is_none());
+
+        let error = AppError::from_status(StatusCode::FORBIDDEN, "locked");
+        assert_eq!((error.status, error.code), (StatusCode::FORBIDDEN, "forbidden"));
+    }
+
+    #[tokio::test]
+    async fn test_error_page() {
+        let response = AppError::internal("Error saving entry", "disk full").page("r2");
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let html = String::from_utf8(body.to_vec()).unwrap();
+        assert!(html.contains("Error saving entry"));
+        assert!(html.contains("r2"));
+        // The underlying error is logged, never shown
+        assert!(!html.contains("disk full"));
+    }
+
+    #[test]
+    fn test_request_ids() {
+        assert_eq!(new_request_id().len(), 16);
+        assert!(is_valid_request_id("7f3c-a1_b.2"));
+        assert!(!is_valid_request_id(""));
+        assert!(!is_valid_request_id("bad id"));
+        assert!(!is_valid_request_id(&"x".repeat(65)));
+    }
+}
@@ -0,0 +1,168 @@
+//! Plain exports of days' entries, for downloading a day or a range of days as Markdown,
+//! text, or JSON from the browser or a script. Backup archives of the whole journal are
+//! `backup`'s job; this is for pulling out and sharing a few days.
+
+use crate::cycle_date::CycleDate;
+use crate::journal::{EntryFilter, JournalManager, PromptType};
+use chrono::{DateTime, Local, NaiveDate};
+use serde::Serialize;
+use std::str::FromStr;
+
+/// Longest range one download may cover
+pub const MAX_RANGE_DAYS: i64 = 400;
+
+/// How a download is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Markdown,
+    Text,
+    Json,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "md" => Ok(ExportFormat::Markdown),
+            "txt" => Ok(ExportFormat::Text),
+            "json" => Ok(ExportFormat::Json),
+            other => Err(format!("Unknown format '{}'; use md, txt, or json", other)),
+        }
+    }
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Text => "txt",
+            ExportFormat::Json => "json",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "text/markdown; charset=utf-8",
+            ExportFormat::Text => "text/plain; charset=utf-8",
+            ExportFormat::Json => "application/json",
+        }
+    }
+}
+
+/// One day's entry with its prompts
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportedDay {
+    pub cycle_date: CycleDate,
+    pub date: NaiveDate,
+    pub date_name: String,
+    pub entry_type: PromptType,
+    pub prompts: Vec<String>,
+    pub entry: String,
+    pub created_at: DateTime<Local>,
+    pub modified_at: DateTime<Local>,
+}
+
+/// The days from `from` through `to` that have an entry, oldest first
+pub async fn collect(journal_manager: &JournalManager, from: CycleDate, to: CycleDate) -> Result<Vec<ExportedDay>, Box<dyn std::error::Error>> {
+    let filter = EntryFilter { from: Some(from), to: Some(to), ..EntryFilter::default() };
+    let listings = journal_manager.list_entries(&filter).await?;
+    let mut days = Vec::new();
+    for listing in listings {
+        let Some(entry) = journal_manager.load_entry(&listing.cycle_date).await? else {
+            continue;
+        };
+        days.push(ExportedDay {
+            cycle_date: listing.cycle_date,
+            date: listing.real_date,
+            date_name: listing.cycle_date.date_name(),
+            entry_type: listing.entry_type,
+            prompts: journal_manager.load_all_prompts(&listing.cycle_date).await.into_iter().map(|p| p.prompt).collect(),
+            entry: entry.content,
+            created_at: entry.created_at,
+            modified_at: entry.modified_at,
+        });
+    }
+    Ok(days)
+}
+
+/// Write days out in `format`
+pub fn render(days: &[ExportedDay], format: ExportFormat) -> Result<String, serde_json::Error> {
+    Ok(match format {
+        ExportFormat::Markdown => days.iter().map(markdown_day).collect::<Vec<_>>().join("\n---\n\n"),
+        ExportFormat::Text => days.iter().map(text_day).collect::<Vec<_>>().join("\n\n"),
+        ExportFormat::Json => serde_json::to_string_pretty(days)? + "\n",
+    })
+}
+
+fn markdown_day(day: &ExportedDay) -> String {
+    let mut markdown = format!("# {}\n\n*{} · {} · {}*\n\n", day.date_name, day.date.format("%B %-d, %Y"), day.cycle_date, day.entry_type);
+    for prompt in &day.prompts {
+        markdown.push_str(&format!("> {}\n\n", prompt.trim().replace('\n', "\n> ")));
+    }
+    markdown.push_str(day.entry.trim());
+    markdown.push('\n');
+    markdown
+}
+
+fn text_day(day: &ExportedDay) -> String {
+    let heading = format!("{} ({}, {})", day.date_name, day.date.format("%B %-d, %Y"), day.cycle_date);
+    let mut text = format!("{}\n{}\n\n", heading, "=".repeat(heading.chars().count()));
+    for prompt in &day.prompts {
+        text.push_str(&format!("Prompt: {}\n\n", prompt.trim()));
+    }
+    text.push_str(day.entry.trim());
+    text.push('\n');
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::{JournalEntry, JournalPrompt};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_collect_and_render() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        let first = CycleDate::new(1, 2, 1, 2).unwrap();
+        let skipped = first.next_day();
+        let last = skipped.next_day();
+
+        for (cycle_date, content) in [(first, "Tea & biscuits."), (last, "A long walk.")] {
+            manager.save_entry(&JournalEntry {
+                cycle_date,
+                content: content.to_string(),
+                created_at: Local::now(),
+                modified_at: Local::now(),
+            }).await.unwrap();
+        }
+        manager.save_prompt(&JournalPrompt {
+            cycle_date: first,
+            prompt: "What did you eat?".to_string(),
+            prompt_number: 1,
+            generated_at: Local::now(),
+            prompt_type: PromptType::Daily,
+        }).await.unwrap();
+
+        let days = collect(&manager, first, last).await.unwrap();
+        assert_eq!(days.iter().map(|d| d.cycle_date).collect::<Vec<_>>(), [first, last]);
+        assert!(collect(&manager, skipped, skipped).await.unwrap().is_empty());
+
+        let markdown = render(&days, ExportFormat::Markdown).unwrap();
+        assert!(markdown.starts_with(&format!("# {}\n", first.date_name())));
+        assert!(markdown.contains("> What did you eat?\n\nTea & biscuits.\n\n---\n\n# "));
+
+        let text = render(&days[..1], ExportFormat::Text).unwrap();
+        assert!(text.contains("Prompt: What did you eat?\n\nTea & biscuits.\n"));
+
+        let json: serde_json::Value = serde_json::from_str(&render(&days, ExportFormat::Json).unwrap()).unwrap();
+        assert_eq!(json[1]["entry"], "A long walk.");
+        assert_eq!(json[0]["prompts"][0], "What did you eat?");
+
+        assert_eq!("txt".parse::<ExportFormat>(), Ok(ExportFormat::Text));
+        assert!("pdf".parse::<ExportFormat>().is_err());
+    }
+}
@@ -0,0 +1,155 @@
+use crate::cycle_date::CycleDate;
+use crate::journal::{EntryFilter, JournalManager, JournalPrompt};
+use chrono::{DateTime, Local, TimeZone};
+
+/// Number of days included in the feed
+pub const FEED_ENTRIES: usize = 20;
+
+/// One day in the feed: its entry, its prompts, or both
+#[derive(Debug, Clone)]
+pub struct FeedItem {
+    pub cycle_date: CycleDate,
+    pub title: String,
+    pub updated: DateTime<Local>,
+    pub prompts: Vec<String>,
+    pub entry: Option<String>,
+}
+
+/// The most recent `limit` entries, newest first, plus today's prompts when today has no
+/// entry yet, so a feed reader shows the prompts waiting to be answered
+pub async fn recent_items(journal_manager: &JournalManager, today: &CycleDate, limit: usize) -> Result<Vec<FeedItem>, Box<dyn std::error::Error>> {
+    let listings = journal_manager.list_entries(&EntryFilter::default()).await?;
+    let mut items = Vec::new();
+
+    if !listings.iter().any(|l| l.cycle_date == *today) {
+        let prompts = journal_manager.load_all_prompts(today).await;
+        if let Some(updated) = prompts.iter().map(|p| p.generated_at).max() {
+            items.push(FeedItem {
+                cycle_date: *today,
+                title: format!("Prompts for {}", describe_day(today)),
+                updated,
+                prompts: prompt_texts(prompts),
+                entry: None,
+            });
+        }
+    }
+
+    for listing in listings.iter().rev().take(limit) {
+        let Some(entry) = journal_manager.load_entry(&listing.cycle_date).await? else {
+            continue;
+        };
+        items.push(FeedItem {
+            cycle_date: listing.cycle_date,
+            title: format!("{} entry for {}", listing.entry_type, describe_day(&listing.cycle_date)),
+            updated: listing.modified_at.unwrap_or(entry.modified_at),
+            prompts: prompt_texts(journal_manager.load_all_prompts(&listing.cycle_date).await),
+            entry: Some(entry.content),
+        });
+    }
+
+    Ok(items)
+}
+
+/// Render items as an Atom feed. `base_url` is the server's address as the reader reached it,
+/// used for links back to each day.
+pub fn render_atom(notebook: &str, base_url: &str, items: &[FeedItem]) -> String {
+    let updated = items
+        .iter()
+        .map(|i| i.updated)
+        .max()
+        .unwrap_or_else(|| Local.timestamp_opt(0, 0).unwrap());
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str(&format!("  <title>Journal: {}</title>\n", escape_xml(notebook)));
+    xml.push_str(&format!("  <id>urn:llm-journal:{}</id>\n", escape_xml(notebook)));
+    xml.push_str(&format!("  <updated>{}</updated>\n", updated.to_rfc3339()));
+    xml.push_str(&format!("  <link href=\"{}\"/>\n", escape_xml(&format!("{}/journal?notebook={}", base_url, notebook))));
+    xml.push_str("  <author><name>LLM Journal</name></author>\n");
+
+    for item in items {
+        let link = format!("{}/journal?date={}&notebook={}", base_url, item.cycle_date, notebook);
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!("    <title>{}</title>\n", escape_xml(&item.title)));
+        xml.push_str(&format!("    <id>urn:llm-journal:{}:{}</id>\n", escape_xml(notebook), item.cycle_date));
+        xml.push_str(&format!("    <updated>{}</updated>\n", item.updated.to_rfc3339()));
+        xml.push_str(&format!("    <link href=\"{}\"/>\n", escape_xml(&link)));
+        xml.push_str(&format!("    <content type=\"html\">{}</content>\n", escape_xml(&item_html(item))));
+        xml.push_str("  </entry>\n");
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+/// The day's prompts as quotes, followed by the entry rendered from Markdown
+fn item_html(item: &FeedItem) -> String {
+    let mut html = String::new();
+    for prompt in &item.prompts {
+        html.push_str(&format!("<blockquote><p>{}</p></blockquote>", escape_xml(prompt)));
+    }
+    if let Some(entry) = &item.entry {
+        html.push_str(&crate::markdown::to_html(entry));
+    }
+    html
+}
+
+fn prompt_texts(prompts: Vec<JournalPrompt>) -> Vec<String> {
+    prompts.into_iter().map(|p| p.prompt).collect()
+}
+
+fn describe_day(cycle_date: &CycleDate) -> String {
+    format!("{} ({})", cycle_date.to_real_date().format("%B %-d, %Y"), cycle_date)
+}
+
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::{JournalEntry, PromptType};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_feed_items_and_rendering() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        let written = CycleDate::new(1, 2, 1, 2).unwrap();
+        let today = CycleDate::new(1, 2, 1, 3).unwrap();
+
+        for cycle_date in [written, today] {
+            manager.save_prompt(&JournalPrompt {
+                cycle_date,
+                prompt: "What made you <laugh> today?".to_string(),
+                prompt_number: 1,
+                generated_at: Local::now(),
+                prompt_type: PromptType::Daily,
+            }).await.unwrap();
+        }
+        manager.save_entry(&JournalEntry {
+            cycle_date: written,
+            content: "Tea & biscuits.\n\nThen a long walk.".to_string(),
+            created_at: Local::now(),
+            modified_at: Local::now(),
+        }).await.unwrap();
+
+        let items = recent_items(&manager, &today, FEED_ENTRIES).await.unwrap();
+        assert_eq!(items.len(), 2);
+        assert!(items[0].entry.is_none() && items[0].cycle_date == today);
+        assert_eq!(items[1].entry.as_deref(), Some("Tea & biscuits.\n\nThen a long walk."));
+
+        let xml = render_atom("default", "http://journal.local", &items);
+        assert!(xml.contains("<id>urn:llm-journal:default:01212</id>"));
+        assert!(xml.contains("date=01212&amp;notebook=default"));
+        // Entry HTML is escaped once more inside the XML
+        assert!(xml.contains("&lt;p&gt;Tea &amp;amp; biscuits.&lt;/p&gt;\n&lt;p&gt;Then a long walk.&lt;/p&gt;"));
+        assert!(xml.contains("What made you &amp;lt;laugh&amp;gt; today?"));
+    }
+}
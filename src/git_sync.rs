@@ -0,0 +1,113 @@
+use crate::config::GitConfig;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+use tokio::sync::Mutex;
+
+/// Files in the journal directory that change constantly and aren't worth history
+const IGNORED: &str = "draft.json\ndevice-drafts.json\nstats.json\nembeddings.json\nindex.json\ntelemetry.jsonl\n.remote-sync.json\ndigests/\n.thumbnails/\n";
+
+/// Commits the journal directory to git after every save, optionally pushing to a remote
+pub struct GitJournal {
+    dir: PathBuf,
+    config: GitConfig,
+    /// git holds an index lock while committing; run one command sequence at a time
+    lock: Mutex<()>,
+}
+
+impl GitJournal {
+    pub fn new(dir: &Path, config: &GitConfig) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            config: config.clone(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Turn the journal directory into a repository if it isn't one yet
+    pub async fn init(&self) -> Result<(), String> {
+        if self.dir.join(".git").exists() {
+            return Ok(());
+        }
+
+        tokio::fs::create_dir_all(&self.dir).await.map_err(|e| e.to_string())?;
+        self.git(&["init", "--quiet"]).await?;
+        let gitignore = self.dir.join(".gitignore");
+        if !gitignore.exists() {
+            tokio::fs::write(&gitignore, IGNORED).await.map_err(|e| e.to_string())?;
+        }
+        tracing::info!("Initialized git repository in {}", self.dir.display());
+        self.commit("Start journal history").await.map(|_| ())
+    }
+
+    /// Stage everything and commit with `message`. Returns false when there was nothing to commit.
+    /// Pushing happens in the background so saves don't wait on the network.
+    pub async fn commit(&self, message: &str) -> Result<bool, String> {
+        let _guard = self.lock.lock().await;
+
+        self.git(&["add", "--all"]).await?;
+        if self.git(&["diff", "--cached", "--quiet"]).await.is_ok() {
+            return Ok(false);
+        }
+
+        let name = format!("user.name={}", self.config.author_name);
+        let email = format!("user.email={}", self.config.author_email);
+        self.git(&["-c", &name, "-c", &email, "commit", "--quiet", "--message", message]).await?;
+        tracing::debug!("Committed journal change: {}", message);
+
+        if self.config.push {
+            let dir = self.dir.clone();
+            let remote = self.config.remote.clone();
+            tokio::spawn(async move {
+                if let Err(e) = run_git(&dir, &["push", "--quiet", &remote, "HEAD"]).await {
+                    tracing::warn!("Failed to push journal history to {}: {}", remote, e);
+                }
+            });
+        }
+        Ok(true)
+    }
+
+    async fn git(&self, args: &[&str]) -> Result<String, String> {
+        run_git(&self.dir, args).await
+    }
+}
+
+async fn run_git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(format!("git {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_commits_each_change() {
+        let temp_dir = TempDir::new().unwrap();
+        let git = GitJournal::new(temp_dir.path(), &GitConfig::default());
+        git.init().await.unwrap();
+
+        std::fs::write(temp_dir.path().join("entry.txt"), "First entry").unwrap();
+        assert!(git.commit("Add entry for 01213").await.unwrap());
+        // Nothing changed, so nothing to commit
+        assert!(!git.commit("Add entry for 01213").await.unwrap());
+        // Drafts are ignored
+        std::fs::write(temp_dir.path().join("draft.json"), "{}").unwrap();
+        assert!(!git.commit("Save draft").await.unwrap());
+
+        let log = git.git(&["log", "--format=%s"]).await.unwrap();
+        assert_eq!(log.lines().collect::<Vec<_>>(), vec!["Add entry for 01213", "Start journal history"]);
+    }
+}
@@ -0,0 +1,88 @@
+use crate::config::GuestConfig;
+use crate::cycle_date::CycleDate;
+use crate::llm_worker::LlmWorker;
+use crate::rate_limit::RateLimiter;
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// The public prompt of the day. Generated from `guest.template` alone, with no entries,
+/// profile, or status, so it's safe to show to anyone.
+#[derive(Debug, Clone, Serialize)]
+pub struct GuestPrompt {
+    pub date: NaiveDate,
+    pub cycle_date: String,
+    pub prompt: String,
+}
+
+/// Serves the guest prompt endpoint: one generic prompt per day, shared by every visitor
+pub struct GuestPrompts {
+    config: GuestConfig,
+    llm_worker: Arc<LlmWorker>,
+    /// Today's prompt once generated; the lock also keeps concurrent visitors from
+    /// generating it twice
+    today: tokio::sync::Mutex<Option<GuestPrompt>>,
+    limiter: RateLimiter<IpAddr>,
+}
+
+impl GuestPrompts {
+    pub fn new(config: GuestConfig, llm_worker: Arc<LlmWorker>) -> Self {
+        let limiter = RateLimiter::new(config.requests_per_minute, Duration::from_secs(60));
+        Self {
+            config,
+            llm_worker,
+            today: tokio::sync::Mutex::new(None),
+            limiter,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Count a request from `client`, returning whether it's within the rate limit
+    pub fn allow(&self, client: IpAddr) -> bool {
+        self.limiter.check(client, Instant::now()).is_ok()
+    }
+
+    /// Today's guest prompt, generating it on the first request of the day
+    pub async fn today(&self) -> Result<GuestPrompt, Box<dyn std::error::Error>> {
+        let date = crate::cycle_date::current_day();
+        let mut today = self.today.lock().await;
+        if let Some(prompt) = today.as_ref().filter(|p| p.date == date) {
+            return Ok(prompt.clone());
+        }
+
+        let model = crate::scheduler::lock_model("Guest prompt").await;
+        let generated = self.llm_worker.generate_text(&render_template(&self.config.template, date), 150).await?;
+        drop(model);
+        let prompt = GuestPrompt {
+            date,
+            cycle_date: CycleDate::from_real_date(date).to_string(),
+            prompt: generated.trim().trim_matches('"').to_string(),
+        };
+        tracing::info!("Generated guest prompt for {}", date);
+        *today = Some(prompt.clone());
+        Ok(prompt)
+    }
+}
+
+/// Fill in the guest template's placeholders: {date} and {weekday}
+fn render_template(template: &str, date: NaiveDate) -> String {
+    template
+        .replace("{date}", &date.format("%B %-d, %Y").to_string())
+        .replace("{weekday}", &date.format("%A").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_template() {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 2).unwrap();
+        assert_eq!(render_template("Today is {weekday}, {date}.", date), "Today is Sunday, March 2, 2025.");
+    }
+}
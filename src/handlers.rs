@@ -1,13 +1,21 @@
 use axum::{
-    extract::{Query, State},
+    body::Bytes,
+    extract::{
+        ws::{Message, WebSocketUpgrade},
+        ConnectInfo, DefaultBodyLimit, FromRequest, Path, Query, RawQuery, State,
+    },
     http::{header, HeaderMap, StatusCode},
     response::{Html, IntoResponse, Redirect, Response},
     routing::{get, post},
     Form, Json, Router,
 };
 use askama::Template;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
+use crate::auth::{AuthContext, Scope};
+use crate::error::AppError;
+use crate::notebook::ActiveNotebook;
+use crate::prompt_jobs::ModelTask;
 use crate::AppState;
 
 #[derive(Deserialize)]
@@ -23,19 +31,502 @@ pub struct LoginForm {
 pub struct JournalTemplate {
     pub cycle_date: String,
     pub real_date_iso: String,  // For the date picker (YYYY-MM-DD format)
+    /// The date in words, by the configured month and weekday names
+    pub date_name: String,
+    /// "Day 203 of 364"
+    pub year_progress: String,
     pub entry_type: String,
     pub existing_content: String,
     pub prompts: Vec<crate::journal::JournalPrompt>,
     pub is_today: bool,
+    /// Dated before the cycle start, so its name counts years back from 00
+    pub before_epoch: bool,
     pub prev_date: String,
     pub next_date: String,
+    pub has_draft: bool,
+    pub draft_revision: u64,
+    /// Text another device was typing more recently than what the editor shows
+    pub newer_draft: Option<crate::journal::DeviceDraft>,
+    /// Revision of the saved entry the page was loaded with ("" if none)
+    pub entry_revision: String,
+    pub prompts_expired: bool,
+    pub summary: Option<String>,
+    /// Shown when more than one notebook is configured
+    pub notebook: Option<String>,
+    /// Entries closest in meaning to this one
+    pub related: Vec<SearchResult>,
+    /// Second ensemble candidates the user can swap in, by prompt number
+    pub ensemble_alternatives: Vec<(u8, crate::journal::EnsembleCandidate)>,
+    /// Whether a transcription command is configured for voice notes
+    pub voice_notes: bool,
+    /// Whether this notebook asks a reflection question before saving this day's entry
+    pub reflect_before_saving: bool,
+    /// Days after which entries lock, when they do
+    pub max_edit_age_days: Option<u32>,
+    /// Past the edit window: shown read-only unless overriding
+    pub locked: bool,
+    /// Editing a locked entry anyway, by explicit admin override
+    pub override_lock: bool,
+    /// The day is marked as needing no prompt
+    pub no_prompt_needed: bool,
+    /// Largest request the server accepts; longer entries are saved in parts
+    pub max_body_bytes: usize,
+}
+
+impl JournalTemplate {
+    /// The undecided ensemble alternative for a prompt, if any
+    pub fn alternative_for(&self, prompt_number: &u8) -> Option<&crate::journal::EnsembleCandidate> {
+        self.ensemble_alternatives.iter().find(|(n, _)| n == prompt_number).map(|(_, c)| c)
+    }
+}
+
+/// Template for keyword and semantic search
+#[derive(Template)]
+#[template(path = "search.html")]
+pub struct SearchTemplate {
+    pub query: String,
+    pub semantic: bool,
+    pub results: Vec<SearchResult>,
+    pub error: Option<String>,
+}
+
+/// An entry found by search or listed as related, with a preview of its text
+pub struct SearchResult {
+    pub cycle_date: String,
+    pub real_date: String,
+    pub snippet: String,
+    pub score_percent: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    pub q: Option<String>,
+    /// "keyword" (default) or "semantic"
+    pub mode: Option<String>,
+}
+
+/// Template for viewing and editing the structured status
+#[derive(Template)]
+#[template(path = "status.html")]
+pub struct StatusTemplate {
+    pub sections: Vec<StatusSection>,
+    pub history: Vec<StatusHistoryDay>,
+}
+
+pub struct StatusSection {
+    pub key: &'static str,
+    pub label: &'static str,
+    pub text: String,
+}
+
+/// Sections a day's entry changed in the status
+pub struct StatusHistoryDay {
+    pub cycle_date: String,
+    pub real_date: String,
+    pub changes: Vec<crate::status::SectionChange>,
+}
+
+/// Days of status history shown on the status page
+const STATUS_HISTORY_DAYS: usize = 30;
+
+/// Number of entries shown by search and in the related-entries list
+const SEARCH_LIMIT: usize = 20;
+const RELATED_LIMIT: usize = 3;
+
+/// Shown when an entry save is rejected because another device changed the entry first
+#[derive(Template)]
+#[template(path = "conflict.html")]
+pub struct EntryConflictTemplate {
+    pub cycle_date: String,
+    pub prompt_number: Option<u8>,
+    pub conflict: EntryConflict,
+    /// Carried over so saving the merge still overrides the entry lock
+    pub override_lock: bool,
+}
+
+/// Both versions of an entry that two devices saved concurrently
+#[derive(Serialize)]
+pub struct EntryConflict {
+    /// The entry as it is saved now
+    pub current_content: String,
+    /// Revision to send with the next save to replace the current entry
+    pub current_revision: String,
+    pub current_modified_at: chrono::DateTime<chrono::Local>,
+    /// The text whose save was rejected
+    pub submitted_content: String,
+}
+
+/// Template for the transcription review queue
+#[derive(Template)]
+#[template(path = "review.html")]
+pub struct ReviewTemplate {
+    pub items: Vec<ReviewItem>,
+}
+
+/// A transcription awaiting review, shown next to its original media
+pub struct ReviewItem {
+    pub cycle_date: String,
+    pub real_date: String,
+    pub content: String,
+    pub media_url: String,
+    pub media_kind: String,
+    pub confidence_percent: u32,
+}
+
+/// Template for the writing statistics dashboard
+#[derive(Template)]
+#[template(path = "stats.html")]
+pub struct StatsTemplate {
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    pub average_words: String,
+    pub words_this_year: usize,
+    pub most_active_weekday: String,
+    pub summary_coverage_percent: u32,
+    pub total_entries: usize,
+    pub weeks: Vec<WeekBar>,
+    pub analytics: crate::analytics::PromptAnalytics,
+    pub context: crate::telemetry::BudgetReport,
+    pub chapters: Vec<crate::chapters::ChapterStats>,
+    /// Possible chapter boundaries the user hasn't acted on
+    pub chapter_suggestions: Vec<crate::chapter_suggestions::BoundarySuggestion>,
+    /// Compiled year-in-review books, newest first
+    pub year_reviews: Vec<crate::year_review::Review>,
+    /// Finished years that can be compiled (again), newest first
+    pub review_years: Vec<i32>,
+    /// Years with a question book, newest first (the current one so far included)
+    pub question_years: Vec<i32>,
+    pub writing_window: Option<crate::writing_times::WritingWindow>,
+    /// Configured prompt generation time ("HH:MM")
+    pub prompt_generation_time: String,
+    /// Whether prompt generation follows the writing window
+    pub adaptive_prompt_time: bool,
+}
+
+/// One bar of the entries-per-week chart
+pub struct WeekBar {
+    pub label: String,
+    pub entries: u32,
+    pub words: usize,
+    pub height_percent: u32,
+}
+
+/// Template for the calendar heatmap (one month, or a whole year)
+#[derive(Template)]
+#[template(path = "calendar.html")]
+pub struct CalendarTemplate {
+    pub title: String,
+    pub months: Vec<crate::calendar::CalendarMonth>,
+    /// Life chapters overlapping the days shown, e.g. "New job (since Mar 5, 2025)"
+    pub chapters: Vec<String>,
+    pub prev_url: String,
+    pub next_url: String,
+    pub toggle_url: String,
+    pub toggle_label: String,
+}
+
+/// Query parameters for the calendar; a year without a month shows the whole year
+#[derive(Deserialize)]
+pub struct CalendarQuery {
+    pub year: Option<i32>,
+    pub month: Option<u32>,
+}
+
+/// Template for the admin page
+#[derive(Template)]
+#[template(path = "admin.html")]
+pub struct AdminTemplate {
+    /// Processing report file names, newest first
+    pub reports: Vec<String>,
+    /// Backup archive file names, newest first
+    pub backups: Vec<String>,
+    pub backup_enabled: bool,
+    pub backup_time: String,
+    pub backup_destination: String,
+    /// Automatic snapshots, newest first
+    pub snapshots: Vec<crate::snapshot::Snapshot>,
+    /// Export and import jobs, newest first
+    pub jobs: Vec<crate::jobs::Job>,
+    /// Processing started from this page, newest first
+    pub runs: Vec<crate::prompt_jobs::PromptJob>,
+    /// The notebook "Run now" works on
+    pub notebook: String,
+}
+
+/// Template for the journal integrity check
+#[derive(Template)]
+#[template(path = "doctor.html")]
+pub struct DoctorTemplate {
+    pub notebook: String,
+    pub report: crate::doctor::DoctorReport,
+    /// Whether the report follows a repair rather than a check
+    pub repaired: bool,
+}
+
+/// Template for previewing and running the retention cleanup
+#[derive(Template)]
+#[template(path = "retention.html")]
+pub struct RetentionTemplate {
+    pub notebook: String,
+    pub policy: crate::config::RetentionConfig,
+    pub report: crate::retention::RetentionReport,
+}
+
+/// Template summarizing the access log by route
+#[derive(Template)]
+#[template(path = "requests.html")]
+pub struct RequestsTemplate {
+    /// The access log's file, if one is written
+    pub log_file: Option<String>,
+    pub requests: usize,
+    pub since: Option<String>,
+    pub routes: Vec<crate::access_log::RouteSummary>,
+}
+
+#[derive(Deserialize)]
+pub struct RollbackForm {
+    pub id: String,
+}
+
+/// Identifies a job to cancel or resume (form or JSON body)
+#[derive(Deserialize)]
+pub struct JobRequest {
+    pub id: String,
+}
+
+/// Query parameters for the guest prompt
+#[derive(Deserialize)]
+pub struct GuestPromptQuery {
+    /// "text" for the bare prompt; JSON otherwise
+    pub format: Option<String>,
+}
+
+/// Query parameters for the entry feed
+#[derive(Deserialize)]
+pub struct FeedQuery {
+    /// API key with the read-entries scope; feed readers usually can't send headers
+    pub key: Option<String>,
+}
+
+/// Query parameters for the audio digest
+#[derive(Deserialize)]
+pub struct DigestQuery {
+    /// API key with the read-entries scope, as for the entry feed
+    pub key: Option<String>,
+    /// Day of the digest; today's when absent
+    pub date: Option<String>,
+}
+
+/// Query parameters for the date conversion API: one date, in either form
+#[derive(Deserialize)]
+pub struct DateConvertQuery {
+    /// A real date (YYYY-MM-DD)
+    pub gregorian: Option<chrono::NaiveDate>,
+    /// A cycle date ("03B25"), or a day named in the configured calendar
+    pub cycle: Option<String>,
+}
+
+/// A date in every form the journal uses, with what it's written as and the week around it
+#[derive(Serialize)]
+pub struct DateConversion {
+    /// The cycle date ("03B25"), whichever calendar is configured
+    pub cycle: String,
+    pub gregorian: chrono::NaiveDate,
+    /// The day's folder name in the configured calendar
+    pub name: String,
+    /// Daily entry, or the reflection the day starts
+    pub entry_type: crate::journal::PromptType,
+    pub week_start: String,
+    pub week_end: String,
+    pub week_start_gregorian: chrono::NaiveDate,
+    pub week_end_gregorian: chrono::NaiveDate,
+}
+
+impl DateConversion {
+    fn of(cycle_date: &crate::cycle_date::CycleDate) -> Self {
+        let (week_start, week_end) = cycle_date.week_bounds();
+        Self {
+            cycle: crate::cycle_date::CalendarMode::Cycle.name(cycle_date),
+            gregorian: cycle_date.to_real_date(),
+            name: cycle_date.to_string(),
+            entry_type: crate::journal::PromptType::for_date(cycle_date),
+            week_start: week_start.to_string(),
+            week_end: week_end.to_string(),
+            week_start_gregorian: week_start.to_real_date(),
+            week_end_gregorian: week_end.to_real_date(),
+        }
+    }
+}
+
+/// Query parameters for the activity heatmap API
+#[derive(Deserialize)]
+pub struct HeatmapQuery {
+    /// Last day shown (YYYY-MM-DD), today if not set
+    pub to: Option<chrono::NaiveDate>,
+}
+
+/// Query parameters for the memory lane API
+#[derive(Deserialize)]
+pub struct MemoryLaneQuery {
+    /// Pick a fresh entry instead of the memory of the day
+    #[serde(default)]
+    pub shuffle: bool,
+}
+
+/// Query parameters for downloading a year-in-review book
+#[derive(Deserialize)]
+pub struct YearReviewQuery {
+    pub year: i32,
+    /// "md" (the default) or "pdf"
+    pub format: Option<String>,
+}
+
+/// Year to compile a review of, numbered as the configured calendar numbers years
+#[derive(Deserialize)]
+pub struct YearReviewForm {
+    pub year: i32,
+}
+
+/// Year to export the question book of, numbered as the configured calendar numbers years
+#[derive(Deserialize)]
+pub struct QuestionBookQuery {
+    pub year: i32,
+}
+
+/// Query parameters for the inbound email webhook
+#[derive(Deserialize)]
+pub struct InboundEmailQuery {
+    pub secret: Option<String>,
+}
+
+/// Query parameters for a job's status
+#[derive(Deserialize)]
+pub struct JobQuery {
+    pub id: String,
+}
+
+/// Backup archive to import (form or JSON body)
+#[derive(Deserialize)]
+pub struct ImportRequest {
+    pub archive: String,
+}
+
+/// Query parameters for viewing a processing report
+#[derive(Deserialize)]
+pub struct ReportQuery {
+    pub file: String,
+}
+
+/// Combined statistics returned by the stats API
+#[derive(Serialize)]
+pub struct StatsResponse {
+    pub streak: crate::stats::JournalStats,
+    pub writing: crate::stats::WritingStats,
+    pub prompts: crate::analytics::PromptAnalytics,
+    /// How recent prompt generations split their context
+    pub context: crate::telemetry::BudgetReport,
+    /// Writing totals per life chapter, oldest first
+    pub chapters: Vec<crate::chapters::ChapterStats>,
+    /// When entries usually get started, once there are enough recent ones
+    pub writing_window: Option<crate::writing_times::WritingWindow>,
+}
+
+/// Form for approving a transcription
+#[derive(Deserialize)]
+pub struct ApproveTranscriptionForm {
+    pub cycle_date: String,
+    pub content: Option<String>,
+}
+
+/// Query parameters for the entry listing API
+#[derive(Deserialize)]
+pub struct EntryListQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    #[serde(rename = "type")]
+    pub entry_type: Option<String>,
+    pub has_summary: Option<bool>,
+    /// Only entries written during the named life chapter
+    pub chapter: Option<String>,
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// Page of entry listings
+#[derive(Serialize)]
+pub struct EntryListResponse {
+    pub entries: Vec<crate::journal::EntryListing>,
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+/// Query parameters for fetching an attachment
+#[derive(Deserialize)]
+pub struct AttachmentQuery {
+    pub date: String,
+    pub file: String,
 }
 
 /// Form for journal entry submission
 #[derive(Deserialize)]
 pub struct JournalEntryForm {
+    #[serde(default)]
+    pub content: String,
+    pub cycle_date: Option<String>,
+    /// Prompt on screen when the entry was saved
+    pub prompt_number: Option<u8>,
+    /// Entry revision the page was loaded with ("" if there was no entry); saving is
+    /// refused with 409 if the entry has changed since. Absent to save unconditionally.
+    pub revision: Option<String>,
+    /// Save even though the entry is past the edit window (admin override)
+    #[serde(default)]
+    pub override_lock: bool,
+    /// Save the day's draft instead of `content`; an entry too large for one request
+    /// is sent ahead as the draft in parts
+    #[serde(default)]
+    pub from_draft: bool,
+}
+
+/// Request body for saving a draft
+#[derive(Deserialize)]
+pub struct JournalDraftRequest {
     pub content: String,
     pub cycle_date: Option<String>,
+    /// Draft revision the client last saw (absent if it never loaded a draft)
+    pub revision: Option<u64>,
+    /// Save even though the entry is past the edit window (needs the admin scope)
+    #[serde(default)]
+    pub override_lock: bool,
+    /// Add `content` to the end of the draft instead of replacing it, for entries
+    /// too large to send in one request
+    #[serde(default)]
+    pub append: bool,
+}
+
+/// Format of a day or range download
+#[derive(Deserialize)]
+pub struct DownloadQuery {
+    /// md (the default), txt, or json
+    pub format: Option<String>,
+}
+
+/// Days to download, by cycle date
+#[derive(Deserialize)]
+pub struct RangeDownloadQuery {
+    pub from: String,
+    pub to: String,
+    pub format: Option<String>,
+}
+
+/// Editor heartbeat: the text being typed for a date
+#[derive(Deserialize)]
+pub struct DraftHeartbeatRequest {
+    pub cycle_date: String,
+    pub content: String,
+    /// Typing in an entry past the edit window (needs the admin scope)
+    #[serde(default)]
+    pub override_lock: bool,
 }
 
 /// Query parameters for journal date
@@ -43,28 +534,220 @@ pub struct JournalEntryForm {
 pub struct JournalDateQuery {
     pub date: Option<String>,
     pub gregorian_date: Option<String>,
+    /// Show prompts that expired unanswered
+    pub show_expired: Option<bool>,
+    /// Edit an entry past the edit window anyway (admin override)
+    pub edit: Option<bool>,
+}
+
+/// Snippets from the override directory's `templates/` added to every page: `head.html` at
+/// the end of `<head>` (say, a stylesheet restyling the theme) and `footer.html` at the end
+/// of `<body>`. Pages themselves are compiled in.
+#[derive(Debug, Clone, Default)]
+pub struct PageOverrides {
+    pub head: String,
+    pub footer: String,
+}
+
+impl PageOverrides {
+    /// Read the snippets from an override directory; missing ones are left empty
+    pub fn load(override_dir: &std::path::Path) -> Self {
+        let read = |name: &str| {
+            let path = override_dir.join("templates").join(name);
+            match std::fs::read_to_string(&path) {
+                Ok(snippet) => snippet,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+                Err(e) => {
+                    tracing::warn!("Failed to read {}: {}", path.display(), e);
+                    String::new()
+                }
+            }
+        };
+        Self { head: read("head.html"), footer: read("footer.html") }
+    }
+}
+
+static PAGE_OVERRIDES: std::sync::OnceLock<PageOverrides> = std::sync::OnceLock::new();
+
+/// Set the snippets added to every page; done once at startup
+pub fn set_page_overrides(overrides: PageOverrides) {
+    if PAGE_OVERRIDES.set(overrides).is_err() {
+        tracing::warn!("The page overrides were already set; keeping the first ones");
+    }
+}
+
+/// The snippets added to every page (none unless an override directory is configured)
+pub fn page_overrides() -> &'static PageOverrides {
+    PAGE_OVERRIDES.get_or_init(PageOverrides::default)
+}
+
+static BASE_PATH: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+
+/// Set the path the app is served under; done once at startup
+pub fn set_base_path(base_path: String) {
+    if BASE_PATH.set(base_path).is_err() {
+        tracing::warn!("The base path was already set; keeping the first one");
+    }
+}
+
+/// The path the app is served under behind a reverse proxy, without a trailing
+/// slash ("" when it owns the domain root)
+pub fn base_path() -> &'static str {
+    BASE_PATH.get().map(String::as_str).unwrap_or("")
 }
 
-/// Creates all routes - simple and clean
-pub fn create_routes() -> Router<AppState> {
+/// A path within the app ("/journal?date=...") as the browser should request it
+pub fn app_url(path: &str) -> String {
+    match (base_path(), path) {
+        // The home page is the base path itself, not the base path with a trailing slash
+        (base_path, "/") if !base_path.is_empty() => base_path.to_string(),
+        (base_path, path) => format!("{}{}", base_path, path),
+    }
+}
+
+/// The JSON API under `/api`, with CORS per `[server.cors]` so pages on other sites can
+/// call it from the browser
+fn api_routes(cors: &crate::config::CorsConfig) -> Router<AppState> {
+    let router = Router::new()
+        .route("/api/entries", get(list_entries_api))
+        .route("/api/memory-lane", get(memory_lane_api))
+        .route("/api/stats", get(stats_api))
+        .route("/api/heatmap", get(heatmap_api))
+        .route("/api/date/convert", get(convert_date_api))
+        .route("/api/stats/prompts", get(prompt_analytics_api))
+        .route("/api/notebooks", get(list_notebooks_api))
+        .route("/api/jobs", get(list_jobs_api))
+        .route("/api/jobs/status", get(job_status_api))
+        .route("/api/jobs/:id", get(job_api))
+        .route("/api/jobs/export", post(start_export_api))
+        .route("/api/jobs/import", post(start_import_api))
+        .route("/api/jobs/cancel", post(cancel_job_api))
+        .route("/api/jobs/resume", post(resume_job_api))
+        .route("/api/keys", get(list_api_keys).post(create_api_key))
+        .route("/api/keys/revoke", post(revoke_api_key))
+        .route("/api/batch", crate::api::batch_route())
+        .nest("/api/v1", crate::api::routes());
+    match crate::api::cors(cors) {
+        Ok(Some(cors)) => router.layer(cors),
+        Ok(None) => router,
+        Err(e) => {
+            tracing::warn!("{}; the API only answers pages from this site", e);
+            router
+        }
+    }
+}
+
+/// Creates all routes - simple and clean. Static files come from `static_dir`, with any
+/// of the same name in the override directory's `static/` served instead.
+pub fn create_routes(server: &crate::config::ServerConfig) -> Router<AppState> {
+    use tower::Layer;
     use tower_http::services::ServeDir;
-    Router::new()
+    let router = Router::new()
         .route("/", get(journal_home_page))
         .route("/login", get(login_page).post(handle_login))
         .route("/logout", post(handle_logout))
+        .route("/notebook", post(switch_notebook))
         // Journal routes
         .route("/journal", get(journal_page))
         .route("/journal/entry", post(submit_journal_entry))
-        .route("/journal/entry.json", get(get_journal_entry_json))
+        .route("/journal/entry.json", get(get_journal_entry_json).put(save_journal_draft_json))
+        .route("/journal/draft", post(draft_heartbeat))
         .route("/journal/generate-prompt", post(generate_prompt_endpoint))
         .route("/journal/navigate-prompt", post(navigate_prompt_endpoint))
         .route("/journal/check-prompt-status", post(check_prompt_status_endpoint))
-        .nest_service("/static", ServeDir::new("static"))
+        .route("/journal/events", get(generation_events))
+        .route("/journal/live", get(live_sync))
+        .route("/journal/regenerate", post(regenerate_endpoint))
+        .route("/journal/choose-prompt", post(choose_prompt_endpoint))
+        .route("/journal/reflect", post(reflect_endpoint))
+        .route("/journal/preview", post(preview_endpoint))
+        .route("/journal/no-prompt", post(no_prompt_form))
+        .route("/journal/voice-note", post(upload_voice_note).layer(DefaultBodyLimit::max(MAX_VOICE_NOTE_BYTES)))
+        .route("/journal/review", get(review_queue_page))
+        .route("/journal/review/approve", post(approve_transcription))
+        .route("/journal/attachment", get(get_attachment))
+        .route("/journal/search", get(redirect_to_search))
+        .route("/journal/download", get(download_range))
+        .route("/journal/:date/download", get(download_day))
+        .route("/search", get(search_page))
+        .route("/stats", get(stats_page))
+        .route("/status", get(status_page).post(save_status_form))
+        .route("/chapters/suggestions/accept", post(accept_chapter_suggestion))
+        .route("/chapters/suggestions/dismiss", post(dismiss_chapter_suggestion))
+        .route("/year-review", get(download_year_review).post(compile_year_review))
+        .route("/question-book", get(download_question_book))
+        .route("/calendar", get(calendar_page))
+        // Admin
+        .route("/admin", get(admin_page))
+        .route("/admin/report", get(view_report))
+        .route("/admin/backup", post(trigger_backup))
+        .route("/admin/rollback", post(rollback_snapshot))
+        .route("/admin/doctor", get(journal_doctor).post(repair_journal))
+        .route("/admin/retention", get(preview_retention).post(run_retention))
+        .route("/admin/requests", get(request_metrics_page))
+        .route("/admin/import", post(start_import_form))
+        .route("/admin/jobs/cancel", post(cancel_job_form))
+        .route("/admin/jobs/resume", post(resume_job_form))
+        // Guest prompt, feeds, and webhooks
+        .route("/guest/prompt", get(guest_prompt))
+        .route("/feed.atom", get(entry_feed))
+        .route("/digest.rss", get(digest_podcast))
+        .route("/digest/audio", get(digest_audio))
+        .route("/inbound/email", post(inbound_email))
+        // JSON API
+        .merge(api_routes(&server.cors))
+        // App shell and health checks
+        .merge(crate::pwa::routes())
+        .merge(crate::health::routes())
+        .merge(crate::theme::routes())
+        // Settings and personalization
+        .merge(crate::settings::routes())
+        .merge(crate::personal_files::routes())
+        .merge(crate::holidays::routes())
+        // Journal features
+        .merge(crate::insights::routes())
+        .merge(crate::attachments::routes())
+        .merge(crate::printable::routes())
+        .merge(crate::shares::routes())
+        // Admin pages
+        .merge(crate::devices::routes())
+        .merge(crate::work_queue::routes())
+        .merge(crate::scheduler::routes());
+
+    let static_files = ServeDir::new(&server.static_dir);
+    let static_cache = crate::compression::static_cache_control();
+    let router = match &server.override_dir {
+        Some(dir) => router.nest_service("/static", static_cache.layer(ServeDir::new(std::path::Path::new(dir).join("static")).fallback(static_files))),
+        None => router.nest_service("/static", static_cache.layer(static_files)),
+    };
+    let max_body_kb = server.max_body_kb;
+    let limits = std::sync::Arc::new(
+        crate::rate_limit::RateLimits::new(server.requests_per_minute, server.expensive_requests_per_minute)
+            .behind(&server.trusted_proxies, &server.forwarded_header),
+    );
+    let router = router
+        .fallback(|| async { AppError::not_found("There's nothing at this address") })
+        .layer(DefaultBodyLimit::max(server.max_body_bytes()))
+        .layer(axum::middleware::from_fn(move |request, next| explain_body_too_large(max_body_kb, request, next)))
+        .layer(axum::middleware::from_fn(move |request, next| {
+            let limits = std::sync::Arc::clone(&limits);
+            async move { crate::rate_limit::rate_limit(&limits, request, next).await }
+        }))
+        .layer(axum::middleware::from_fn(crate::error::handle_errors))
+        .layer(crate::compression::page_cache_control())
+        .layer(crate::compression::compression());
+    match base_path() {
+        "" => router,
+        base_path => Router::new()
+            .route(&format!("{}/", base_path), get(|| async { Redirect::permanent(base_path) }))
+            .nest(base_path, router),
+    }
 }
 
 /// Home page - simple journal landing page
 async fn journal_home_page(
     State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
     headers: HeaderMap,
 ) -> Response {
     // Extract token from cookie
@@ -74,7 +757,60 @@ async fn journal_home_page(
     if let Some(token) = token {
         if app_state.auth_manager.validate_session(&token).await {
             let cycle_date = crate::cycle_date::CycleDate::today();
-            let real_date = cycle_date.to_real_date().format("%A, %B %d, %Y").to_string();
+            let real_date = crate::locale::long_date(cycle_date.to_real_date());
+
+            // Stats are refreshed whenever an entry is saved; compute them on first visit
+            let stats = match crate::stats::load_stats(&notebook.journal_manager).await {
+                Some(stats) => Some(stats),
+                None => crate::stats::refresh_stats(&notebook.journal_manager).await.ok(),
+            };
+            let notebook_names = app_state.notebooks.names();
+            let notebook_switcher = if notebook_names.len() > 1 {
+                let options: String = notebook_names.iter()
+                    .map(|name| format!(
+                        r#"<option value="{0}"{1}>{0}</option>"#,
+                        name,
+                        if *name == notebook.name { " selected" } else { "" },
+                    ))
+                    .collect();
+                format!(
+                    r#"<form method="post" action="{}/notebook"><strong>Notebook:</strong> <select name="name" onchange="this.form.submit()">{}</select> <noscript><button type="submit">Switch</button></noscript></form>"#,
+                    base_path(),
+                    options,
+                )
+            } else {
+                String::new()
+            };
+            let streak_info = match stats {
+                Some(stats) => format!(
+                    "<strong>Streak:</strong> {} days (longest {})<br>\n            <strong>Completion:</strong> {:.0}% of {} days",
+                    stats.current_streak,
+                    stats.longest_streak,
+                    stats.completion_rate * 100.0,
+                    stats.days_tracked,
+                ),
+                None => String::new(),
+            };
+            let memory = crate::memory_lane::of_the_day(&notebook.journal_manager, &cycle_date).await.map_err(|e| e.to_string());
+            let memory_lane = match memory {
+                Ok(Some(memory)) => format!(
+                    r#"<div class="memory-lane">
+            <strong>Memory lane: {}</strong><br>
+            <em>{}</em><br>
+            <a href="{}">Read the entry</a>
+        </div>"#,
+                    memory.heading(),
+                    crate::feed::escape_xml(&memory.excerpt),
+                    app_url(&format!("/journal?date={}", memory.cycle_date)),
+                ),
+                Ok(None) => String::new(),
+                Err(e) => {
+                    tracing::warn!("Failed to pick a memory for the home page: {}", e);
+                    String::new()
+                }
+            };
+            
+            let theme_picker = crate::theme::session_theme(&app_state, &headers).await.picker(&app_url("/"));
             
             let html = format!(r#"
 <!DOCTYPE html>
@@ -82,16 +818,21 @@ async fn journal_home_page(
 <head>
     <title>LLM Journal</title>
     <meta name="viewport" content="width=device-width, initial-scale=1">
+    <link rel="stylesheet" href="{base}/theme.css">
     <style>
-        body {{ font-family: Arial, sans-serif; max-width: 800px; margin: 50px auto; padding: 20px; background: #f5f5f5; }}
-        .container {{ background: white; padding: 30px; border-radius: 10px; box-shadow: 0 2px 10px rgba(0,0,0,0.1); }}
-        h1 {{ color: #333; border-bottom: 2px solid #007acc; padding-bottom: 10px; }}
-        .date-info {{ background: #e7f3ff; padding: 15px; border-radius: 5px; margin: 20px 0; }}
+        body {{ font-family: Arial, sans-serif; max-width: 800px; margin: 50px auto; padding: 20px; background: var(--bg-primary); color: var(--text-primary); }}
+        .container {{ background: var(--bg-secondary); padding: 30px; border-radius: 10px; box-shadow: 0 2px 10px var(--shadow-medium); }}
+        h1 {{ color: var(--text-primary); border-bottom: 2px solid var(--accent-primary); padding-bottom: 10px; }}
+        a {{ color: var(--accent-primary); }}
+        .date-info {{ background: var(--bg-primary); padding: 15px; border-radius: 5px; margin: 20px 0; }}
         .nav {{ margin: 20px 0; }}
-        .nav a {{ display: inline-block; margin-right: 15px; padding: 10px 20px; background: #007acc; color: white; text-decoration: none; border-radius: 5px; }}
-        .nav a:hover {{ background: #005a9e; }}
+        .nav a {{ display: inline-block; margin-right: 15px; padding: 10px 20px; background: var(--accent-primary); color: var(--bg-primary); text-decoration: none; border-radius: 5px; }}
+        .nav a:hover {{ background: var(--accent-hover); }}
         .logout {{ float: right; background: #dc3545; }}
         .logout:hover {{ background: #c82333; }}
+        .memory-lane {{ background: var(--bg-primary); border-left: 4px solid var(--accent-warm); padding: 15px; border-radius: 5px; margin: 20px 0; }}
+        .theme-picker {{ color: var(--text-secondary); }}
+        select, .theme-picker button {{ background: var(--input-bg); color: var(--text-primary); border: 1px solid var(--input-border); border-radius: 5px; padding: 4px 8px; }}
     </style>
 </head>
 <body>
@@ -101,18 +842,29 @@ async fn journal_home_page(
             <strong>Today:</strong> {}<br>
             <strong>Cycle Date:</strong> {}
         </div>
+        {}
+        <div class="date-info">
+            {}
+        </div>
+        {}
         <div class="nav">
-            <a href="/journal">Write Entry</a>
-            <a href="/journal/history">View History</a>
-            <form method="post" action="/logout" style="display: inline;">
+            <a href="{base}/journal">Write Entry</a>
+            <a href="{base}/journal/history">View History</a>
+            <a href="{base}/search">Search</a>
+            <a href="{base}/calendar">Calendar</a>
+            <a href="{base}/stats">Statistics</a>
+            <a href="{base}/insights">Insights</a>
+            <a href="{base}/admin">Admin</a>
+            <form method="post" action="{base}/logout" style="display: inline;">
                 <button type="submit" class="nav logout">Logout</button>
             </form>
         </div>
         <p>Welcome to your LLM-powered journal! Choose an action above to get started.</p>
+        {}
     </div>
 </body>
 </html>
-            "#, real_date, cycle_date.to_string());
+            "#, real_date, cycle_date, notebook_switcher, streak_info, memory_lane, theme_picker, base = base_path());
             
             return Html(html).into_response();
         }
@@ -124,24 +876,44 @@ async fn journal_home_page(
 
 
 
+/// Replace the bare 413 for a request over the body limit with an explanation, which
+/// `handle_errors` shows as a page or JSON (API errors already explain)
+async fn explain_body_too_large(max_body_kb: usize, request: axum::extract::Request, next: axum::middleware::Next) -> Response {
+    let response = next.run(request).await;
+    if response.status() != StatusCode::PAYLOAD_TOO_LARGE || response.extensions().get::<AppError>().is_some() {
+        return response;
+    }
+
+    tracing::warn!("Rejected a request over the {} KB body limit", max_body_kb);
+    let message = format!(
+        "The request was larger than the {} KB this server accepts, so nothing was saved. Go back to keep your text: \
+         with scripts enabled, the journal page saves long entries in parts, and an admin can raise max_body_kb in config.toml.",
+        max_body_kb,
+    );
+    AppError::from_status(StatusCode::PAYLOAD_TOO_LARGE, message)
+        .with_details(&serde_json::json!({ "max_body_kb": max_body_kb }))
+        .into_response()
+}
+
 /// Login page
 async fn login_page(State(app_state): State<AppState>) -> Html<String> {
     // Generate passcode and show login form
     let _passcode = app_state.auth_manager.create_auth_request(None, false).await;
     
-    let html = r#"
+    let html = format!(r#"
 <!DOCTYPE html>
 <html>
 <head>
     <title>LLM Journal - Login</title>
     <meta name="viewport" content="width=device-width, initial-scale=1">
+    <link rel="stylesheet" href="{base}/theme.css">
     <style>
-        body { font-family: Arial, sans-serif; max-width: 400px; margin: 100px auto; padding: 20px; background: #f0f0f0; }
-        .login-box { background: white; padding: 30px; border-radius: 10px; box-shadow: 0 2px 10px rgba(0,0,0,0.1); }
-        input[type="text"], input[type="password"] { width: 100%; padding: 12px; margin: 10px 0; border: 1px solid #ddd; border-radius: 5px; box-sizing: border-box; }
-        button { width: 100%; padding: 12px; background: #007acc; color: white; border: none; border-radius: 5px; cursor: pointer; font-size: 16px; }
-        button:hover { background: #005a9e; }
-        .info { background: #e7f3ff; padding: 15px; border-radius: 5px; margin-bottom: 20px; border-left: 4px solid #007acc; }
+        body {{ font-family: Arial, sans-serif; max-width: 400px; margin: 100px auto; padding: 20px; background: var(--bg-primary); color: var(--text-primary); }}
+        .login-box {{ background: var(--bg-secondary); padding: 30px; border-radius: 10px; box-shadow: 0 2px 10px var(--shadow-medium); }}
+        input[type="text"], input[type="password"] {{ width: 100%; padding: 12px; margin: 10px 0; background: var(--input-bg); color: var(--text-primary); border: 1px solid var(--input-border); border-radius: 5px; box-sizing: border-box; }}
+        button {{ width: 100%; padding: 12px; background: var(--accent-primary); color: var(--bg-primary); border: none; border-radius: 5px; cursor: pointer; font-size: 16px; }}
+        button:hover {{ background: var(--accent-hover); }}
+        .info {{ background: var(--bg-primary); padding: 15px; border-radius: 5px; margin-bottom: 20px; border-left: 4px solid var(--accent-primary); }}
     </style>
 </head>
 <body>
@@ -151,7 +923,7 @@ async fn login_page(State(app_state): State<AppState>) -> Html<String> {
             <strong>Device Authentication</strong><br>
             Check the server terminal for your unique passcode.
         </div>
-        <form method="post" action="/login">
+        <form method="post" action="{base}/login">
             <input type="text" name="device_name" placeholder="Device name (optional)" maxlength="50">
             <input type="password" name="passcode" placeholder="Enter passcode from terminal" required autofocus>
             <label style="display: flex; align-items: center; margin: 10px 0; cursor: pointer;">
@@ -164,7 +936,7 @@ async fn login_page(State(app_state): State<AppState>) -> Html<String> {
     </div>
 </body>
 </html>
-    "#.to_string();
+    "#, base = base_path());
     
     Html(html)
 }
@@ -182,23 +954,23 @@ async fn handle_login(
         
         // Use the configured session duration from config
         let max_age = app_state.config.auth.session_duration_seconds;
-        let cookie = format!("session_token={}; Path=/; HttpOnly; SameSite=Strict; Max-Age={}", token, max_age);
+        let cookie = crate::auth::cookie_scope().set(crate::auth::SESSION_COOKIE, &token, max_age, true);
         
         (
             StatusCode::OK,
             [("Set-Cookie", cookie.as_str())],
-            Redirect::to("/"),            
+            Redirect::to(&app_url("/")),
         ).into_response()
     } else {
         (
             StatusCode::UNAUTHORIZED,
-            Html(r#"
+            Html(format!(r#"
 <!DOCTYPE html>
 <html>
-<head><title>Login Failed</title><meta http-equiv="refresh" content="3;url=/login"></head>
+<head><title>Login Failed</title><meta http-equiv="refresh" content="3;url={}/login"></head>
 <body><h2>Invalid Passcode</h2><p>Redirecting...</p></body>
 </html>
-            "#),
+            "#, base_path())),
         ).into_response()
     }
 }
@@ -214,33 +986,88 @@ async fn handle_logout(
     }
     
     // Clear cookie and redirect (303 forces GET request)
+    let cookie = crate::auth::cookie_scope().clear(crate::auth::SESSION_COOKIE);
     (
         StatusCode::SEE_OTHER,
         [
-            ("Location", "/login"),
-            ("Set-Cookie", "session_token=; Path=/; HttpOnly; Max-Age=0"),
+            ("Location", app_url("/login").as_str()),
+            ("Set-Cookie", cookie.as_str()),
         ],
         Html("Logged out"),
     ).into_response()
 }
 
-/// Extract session token from request headers
-fn extract_session_token(headers: &HeaderMap) -> Option<String> {
-    headers
-        .get(header::COOKIE)
-        .and_then(|cookie| cookie.to_str().ok())
-        .and_then(|cookie_str| {
-            cookie_str
-                .split(';')
-                .find(|part| part.trim().starts_with("session_token="))
-                .map(|part| part.trim().strip_prefix("session_token=").unwrap_or("").to_string())
+/// Form for switching the notebook shown in the web UI
+#[derive(Deserialize)]
+pub struct SwitchNotebookForm {
+    pub name: String,
+}
+
+/// Remember the chosen notebook in a cookie and go back home
+async fn switch_notebook(
+    State(app_state): State<AppState>,
+    auth: AuthContext,
+    Form(form): Form<SwitchNotebookForm>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::ReadEntries) {
+        return rejection.into_response();
+    }
+    if app_state.notebooks.get(&form.name).is_none() {
+        return AppError::not_found(format!("Unknown notebook '{}'", form.name)).into_response();
+    }
+
+    let max_age = app_state.config.auth.session_duration_seconds;
+    let cookie = crate::auth::cookie_scope().set(crate::notebook::NOTEBOOK_COOKIE, &form.name, max_age, false);
+    (
+        StatusCode::SEE_OTHER,
+        [("Location", app_url("/").as_str()), ("Set-Cookie", cookie.as_str())],
+    ).into_response()
+}
+
+/// Response listing the configured notebooks
+#[derive(Serialize)]
+pub struct NotebookListResponse {
+    pub notebooks: Vec<String>,
+    pub default: String,
+}
+
+/// List notebooks; pass `?notebook=<name>` to any route to address one
+async fn list_notebooks_api(State(app_state): State<AppState>, auth: AuthContext) -> Response {
+    if let Err(rejection) = auth.require(Scope::ReadEntries) {
+        return rejection.into_response();
+    }
+
+    json_response(StatusCode::OK, &NotebookListResponse {
+        notebooks: app_state.notebooks.names(),
+        default: app_state.notebooks.default_notebook().name.clone(),
+    })
+}
+
+/// The latest text typed on another device, if it's newer than and different from what
+/// the editor will show (`shown`: its text and when that was saved)
+fn newer_device_draft(
+    device_drafts: Vec<crate::journal::DeviceDraft>,
+    this_device: &str,
+    shown: Option<(&str, chrono::DateTime<chrono::Local>)>,
+) -> Option<crate::journal::DeviceDraft> {
+    device_drafts.into_iter()
+        .filter(|d| d.device != this_device && !d.content.trim().is_empty())
+        .find(|d| match shown {
+            Some((content, saved_at)) => d.saved_at > saved_at && d.content.trim() != content.trim(),
+            None => true,
         })
 }
 
+/// Extract session token from request headers
+pub(crate) fn extract_session_token(headers: &HeaderMap) -> Option<String> {
+    crate::auth::cookie_scope().read(headers, crate::auth::SESSION_COOKIE)
+}
+
 // Journal-specific handlers
 /// Journal page - shows today's prompt and entry form
 async fn journal_page(
     State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
     headers: HeaderMap,
     Query(params): Query<JournalDateQuery>,
 ) -> Response {
@@ -254,7 +1081,10 @@ async fn journal_page(
             let cycle_date = if let Some(gregorian_date_str) = params.gregorian_date {
                 // Convert Gregorian date to cycle date
                 match chrono::NaiveDate::parse_from_str(&gregorian_date_str, "%Y-%m-%d") {
-                    Ok(gregorian_date) => crate::cycle_date::CycleDate::from_real_date(gregorian_date),
+                    Ok(gregorian_date) => match crate::cycle_date::CycleDate::try_from_real_date(gregorian_date) {
+                        Ok(cycle_date) => cycle_date,
+                        Err(e) => return AppError::bad_request(e.to_string()).into_response(),
+                    },
                     Err(_) => {
                         tracing::warn!("Invalid gregorian date format: {}", gregorian_date_str);
                         crate::cycle_date::CycleDate::today()
@@ -271,7 +1101,7 @@ async fn journal_page(
             };
 
             // Use shared journal manager
-            let journal_manager = &app_state.journal_manager;
+            let journal_manager = &notebook.journal_manager;
 
             // Load existing entry if it exists
             let existing_entry = match journal_manager.load_entry(&cycle_date).await {
@@ -282,40 +1112,100 @@ async fn journal_page(
                 }
             };
 
+            // An unsaved draft takes precedence over the committed entry in the editor
+            let draft = match journal_manager.load_draft(&cycle_date).await {
+                Ok(draft) => draft,
+                Err(e) => {
+                    tracing::error!("Failed to load journal draft: {}", e);
+                    None
+                }
+            };
+
+            // Another device may have been typing since, without saving
+            let device = app_state.auth_manager.get_session_info(&token).await.and_then(|session| session.device_name);
+            let newer_draft = match journal_manager.load_device_drafts(&cycle_date).await {
+                Ok(device_drafts) => newer_device_draft(
+                    device_drafts,
+                    device.as_deref().unwrap_or("Unknown device"),
+                    draft.as_ref().map(|d| (d.content.as_str(), d.saved_at))
+                        .or(existing_entry.as_ref().map(|e| (e.content.as_str(), e.modified_at))),
+                ),
+                Err(e) => {
+                    tracing::error!("Failed to load draft heartbeats: {}", e);
+                    None
+                }
+            };
+
             // Load prompts for this date
-            let mut prompts = Vec::new();
             // Instead of limiting to max_prompts_per_day, load all available prompts
-            let mut prompt_number = 1;
-            loop {
-                match journal_manager.load_prompt(&cycle_date, prompt_number).await {
-                    Ok(Some(prompt)) => {
-                        prompts.push(prompt);
-                        prompt_number += 1;
-                    }
-                    Ok(None) => break, // No more prompts found
-                    Err(_) => break,   // Error loading, stop trying
+            // Prompts that expired unanswered are hidden unless asked for
+            let prompts_expired = journal_manager.prompts_expired(&cycle_date).await;
+            let prompts = if prompts_expired && !params.show_expired.unwrap_or(false) {
+                Vec::new()
+            } else {
+                journal_manager.load_all_prompts(&cycle_date).await
+            };
+
+            let ensemble_alternatives = if prompts.is_empty() {
+                Vec::new()
+            } else {
+                journal_manager.undecided_ensemble_candidates(&cycle_date).await
+            };
+
+            let summary = match journal_manager.load_summary(&cycle_date).await {
+                Ok(summary) => summary.map(|s| s.summary),
+                Err(e) => {
+                    tracing::error!("Failed to load journal summary: {}", e);
+                    None
                 }
+            };
+
+            // Related entries come from the stored embeddings, so showing them needs no LLM call
+            let index = crate::embeddings::EmbeddingIndex::load(journal_manager.base_path()).await;
+            let mut related = Vec::new();
+            for (date, score) in index.related(&cycle_date, RELATED_LIMIT) {
+                related.push(search_result(journal_manager, date, Some(score)).await);
             }
 
-            // Determine entry type based on cycle date pattern
-            let cycle_str = cycle_date.to_string();
-            let entry_type = if cycle_str.ends_with("000") {
-                "Yearly Reflection"
-            } else if cycle_str.ends_with("00") {
-                "Monthly Reflection"
-            } else if cycle_str.ends_with("0") {
-                "Weekly Reflection"
-            } else {
-                "Daily Entry"
+            let max_edit_age_days = crate::config::live().journal.max_edit_age_days;
+            let locked = crate::journal::is_entry_locked(&cycle_date, &crate::cycle_date::CycleDate::today(), max_edit_age_days);
+
+            // Determine entry type from the date's place in the calendar
+            let entry_type = match crate::journal::PromptType::for_date(&cycle_date) {
+                crate::journal::PromptType::Daily => "Daily Entry".to_string(),
+                reflection => reflection.to_string(),
             };
 
             let template = JournalTemplate {
                 cycle_date: cycle_date.to_string(),
                 real_date_iso: cycle_date.to_real_date().format("%Y-%m-%d").to_string(),
-                entry_type: entry_type.to_string(),
-                existing_content: existing_entry.map(|e| e.content).unwrap_or_default(),
+                date_name: cycle_date.date_name(),
+                year_progress: cycle_date.year_progress(),
+                entry_type,
+                has_draft: draft.is_some(),
+                draft_revision: draft.as_ref().map(|d| d.revision).unwrap_or(0),
+                newer_draft,
+                entry_revision: existing_entry.as_ref().map(|e| crate::journal::entry_revision(&e.content)).unwrap_or_default(),
+                prompts_expired,
+                summary,
+                notebook: (app_state.notebooks.names().len() > 1).then(|| notebook.name.clone()),
+                related,
+                ensemble_alternatives,
+                voice_notes: crate::transcription::available(&app_state.config.transcription),
+                reflect_before_saving: notebook.prompt_generator.is_some()
+                    && app_state.config.journal.reflects_before_saving(&notebook.name, &crate::journal::PromptType::for_date(&cycle_date)),
+                max_edit_age_days,
+                locked,
+                override_lock: locked && params.edit.unwrap_or(false),
+                no_prompt_needed: journal_manager.no_prompt_needed(&cycle_date).await,
+                max_body_bytes: app_state.config.server.max_body_bytes(),
+                existing_content: match draft {
+                    Some(draft) => draft.content,
+                    None => existing_entry.map(|e| e.content).unwrap_or_default(),
+                },
                 prompts,
                 is_today: cycle_date == crate::cycle_date::CycleDate::today(),
+                before_epoch: cycle_date.before_epoch,
                 prev_date: cycle_date.previous_day().to_string(),
                 next_date: cycle_date.next_day().to_string(),
             };
@@ -323,8 +1213,7 @@ async fn journal_page(
             return match template.render() {
                 Ok(html) => Html(html).into_response(),
                 Err(e) => {
-                    tracing::error!("Failed to render journal template: {}", e);
-                    (StatusCode::INTERNAL_SERVER_ERROR, Html("Error rendering page")).into_response()
+                    AppError::internal("Error rendering page", e).into_response()
                 }
             };
         }
@@ -334,11 +1223,44 @@ async fn journal_page(
     redirect_to_login().into_response()
 }
 
-/// Handle journal entry submission
-async fn submit_journal_entry(
+/// Describe an entry for a result list, previewed by its summary or else its opening text
+async fn search_result(
+    journal_manager: &crate::journal::JournalManager,
+    cycle_date: crate::cycle_date::CycleDate,
+    score: Option<f32>,
+) -> SearchResult {
+    let summary = journal_manager.load_summary(&cycle_date).await.ok().flatten();
+    let snippet = match summary {
+        Some(summary) => summary.summary,
+        None => match journal_manager.load_entry(&cycle_date).await.ok().flatten() {
+            Some(entry) => crate::search::snippet(&entry.content, ""),
+            None => String::new(),
+        },
+    };
+    SearchResult {
+        cycle_date: cycle_date.to_string(),
+        real_date: crate::locale::date(cycle_date.to_real_date()),
+        snippet,
+        score_percent: score.map(|s| (s.clamp(0.0, 1.0) * 100.0).round() as u32),
+    }
+}
+
+/// The search page's old address, kept working for bookmarks
+async fn redirect_to_search(RawQuery(query): RawQuery) -> Redirect {
+    match query {
+        Some(query) => Redirect::permanent(&app_url(&format!("/search?{}", query))),
+        None => Redirect::permanent(&app_url("/search")),
+    }
+}
+
+/// Search entries by their words, or with `mode=semantic` by meaning. The query can narrow
+/// results with `tag:`, `before:`, `after:`, and `type:`, and "quote" phrases (see
+/// `search::ParsedQuery`).
+async fn search_page(
     State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
     headers: HeaderMap,
-    Form(form): Form<JournalEntryForm>,
+    Query(params): Query<SearchQuery>,
 ) -> Response {
     // Extract token from cookie
     let token = extract_session_token(&headers);
@@ -346,53 +1268,90 @@ async fn submit_journal_entry(
     // Check if authenticated
     if let Some(token) = token {
         if app_state.auth_manager.validate_session(&token).await {
-            // Use the cycle_date from the form if provided, otherwise default to today
-            let cycle_date = if let Some(ref date_str) = form.cycle_date {
-                tracing::info!("Form provided cycle_date: '{}'", date_str);
-                match crate::cycle_date::CycleDate::from_string(date_str) {
-                    Ok(date) => {
-                        tracing::info!("Successfully parsed cycle_date: {}", date);
-                        date
-                    },
-                    Err(e) => {
-                        tracing::warn!("Invalid cycle date in form '{}': {}, using today instead", date_str, e);
-                        crate::cycle_date::CycleDate::today()
-                    }
-                }
-            } else {
-                tracing::info!("No cycle_date provided in form, using today");
-                crate::cycle_date::CycleDate::today()
-            };
-            
-            let journal_manager = &app_state.journal_manager;
+            use crate::search::SearchMode;
 
-            let entry = crate::journal::JournalEntry {
-                cycle_date,
-                content: form.content,
-                created_at: chrono::Local::now(),
-                modified_at: chrono::Local::now(),
+            let query = params.q.unwrap_or_default().trim().to_string();
+            let mode = match params.mode.as_deref().unwrap_or("").parse::<SearchMode>() {
+                Ok(mode) => mode,
+                Err(e) => return AppError::bad_request(e).into_response(),
             };
 
-            match journal_manager.save_entry(&entry).await {
-                Ok(()) => {
-                    tracing::info!("Journal entry saved for {}", entry.cycle_date);
-                    // Redirect back to the same journal page date
-                    let redirect_url = if entry.cycle_date == crate::cycle_date::CycleDate::today() {
-                        "/journal".to_string()
-                    } else {
-                        format!("/journal?date={}", entry.cycle_date)
-                    };
-                    return (
-                        StatusCode::SEE_OTHER,
-                        [("Location", redirect_url.as_str())],
-                        Html("Entry saved successfully"),
-                    ).into_response();
-                }
+            let mut results = Vec::new();
+            let mut error = None;
+            let parsed = match crate::search::ParsedQuery::parse(&query) {
+                Ok(parsed) => parsed,
                 Err(e) => {
-                    tracing::error!("Failed to save journal entry: {}", e);
-                    return (StatusCode::INTERNAL_SERVER_ERROR, Html("Error saving entry")).into_response();
+                    error = Some(e);
+                    crate::search::ParsedQuery::default()
+                }
+            };
+            if !parsed.is_empty() {
+                // Searching by meaning needs words; filters alone list what they match
+                let semantic_hits = match (mode, &notebook.prompt_generator) {
+                    (SearchMode::Semantic, _) if parsed.terms.is_empty() => None,
+                    (SearchMode::Keyword, _) => None,
+                    (SearchMode::Semantic, Some(generator)) => {
+                        // Filters apply afterwards, so look further down the ranking
+                        let hits = generator.semantic_search(&parsed.text(), SEARCH_LIMIT * 5).await.map_err(|e| e.to_string());
+                        match hits {
+                            Ok(hits) => Some(hits),
+                            Err(e) => {
+                                tracing::warn!("Semantic search failed: {}", e);
+                                error = Some(format!("Semantic search is unavailable ({}); showing entries with these words instead.", e));
+                                None
+                            }
+                        }
+                    }
+                    (SearchMode::Semantic, None) => {
+                        error = Some("Semantic search needs the LLM, which isn't running; showing entries with these words instead.".to_string());
+                        None
+                    }
+                };
+
+                match semantic_hits {
+                    Some(hits) => {
+                        for (date, score) in hits {
+                            let content = notebook.journal_manager.load_entry(&date).await.ok().flatten().map(|e| e.content);
+                            if !content.is_some_and(|content| parsed.matches_filters(&date, &content)) {
+                                continue;
+                            }
+                            results.push(search_result(&notebook.journal_manager, date, Some(score)).await);
+                            if results.len() >= SEARCH_LIMIT {
+                                break;
+                            }
+                        }
+                    }
+                    None => {
+                        let hits = crate::search::keyword_search(&notebook.journal_manager, &parsed, SEARCH_LIMIT)
+                            .await
+                            .map_err(|e| e.to_string());
+                        match hits {
+                            Ok(hits) => results.extend(hits.into_iter().map(|hit| SearchResult {
+                                cycle_date: hit.cycle_date.to_string(),
+                                real_date: crate::locale::date(hit.cycle_date.to_real_date()),
+                                snippet: hit.snippet,
+                                score_percent: None,
+                            })),
+                            Err(e) => {
+                                return AppError::internal("Error searching entries", e).into_response();
+                            }
+                        }
+                    }
                 }
             }
+
+            let template = SearchTemplate {
+                query,
+                semantic: mode == SearchMode::Semantic,
+                results,
+                error,
+            };
+            return match template.render() {
+                Ok(html) => Html(html).into_response(),
+                Err(e) => {
+                    AppError::internal("Error rendering page", e).into_response()
+                }
+            };
         }
     }
 
@@ -400,11 +1359,12 @@ async fn submit_journal_entry(
     redirect_to_login().into_response()
 }
 
-/// Get journal entry as JSON (for auto-save functionality)
-async fn get_journal_entry_json(
+/// Handle journal entry submission
+async fn submit_journal_entry(
     State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
     headers: HeaderMap,
-    Query(params): Query<JournalDateQuery>,
+    Form(form): Form<JournalEntryForm>,
 ) -> Response {
     // Extract token from cookie
     let token = extract_session_token(&headers);
@@ -412,47 +1372,268 @@ async fn get_journal_entry_json(
     // Check if authenticated
     if let Some(token) = token {
         if app_state.auth_manager.validate_session(&token).await {
-            let cycle_date = if let Some(date_str) = params.date {
-                match crate::cycle_date::CycleDate::from_string(&date_str) {
+            // Use the cycle_date from the form if provided, otherwise default to today
+            let cycle_date = match form.cycle_date.as_deref() {
+                Some(date_str) => match crate::cycle_date::CycleDate::from_string(date_str) {
                     Ok(date) => date,
-                    Err(_) => crate::cycle_date::CycleDate::today(),
+                    Err(e) => {
+                        tracing::warn!("Invalid cycle date in form '{}': {}", date_str, e);
+                        return AppError::bad_request("Invalid cycle date").into_response();
+                    }
+                },
+                None => crate::cycle_date::CycleDate::today(),
+            };
+
+            // Sessions have full access, so the form may override the entry lock
+            if let Err(e) = crate::journal::check_entry_date_editable(
+                &cycle_date,
+                &crate::cycle_date::CycleDate::today(),
+                crate::config::live().journal.edit_window(form.override_lock),
+            ) {
+                tracing::warn!("Rejected entry save: {}", e);
+                return AppError::forbidden(e).into_response();
+            }
+            if form.override_lock && crate::journal::is_entry_locked(&cycle_date, &crate::cycle_date::CycleDate::today(), crate::config::live().journal.max_edit_age_days) {
+                tracing::warn!("Saving locked entry for {} by admin override", cycle_date);
+            }
+            
+            let journal_manager = &notebook.journal_manager;
+
+            let content = if form.from_draft {
+                match journal_manager.load_draft(&cycle_date).await.map_err(|e| e.to_string()) {
+                    Ok(Some(draft)) => draft.content,
+                    Ok(None) => return AppError::bad_request("There is no draft to save").into_response(),
+                    Err(e) => {
+                        return AppError::internal("Error loading draft", e).into_response();
+                    }
                 }
             } else {
-                crate::cycle_date::CycleDate::today()
+                form.content
+            };
+            let entry = crate::journal::JournalEntry {
+                cycle_date,
+                content,
+                created_at: chrono::Local::now(),
+                modified_at: chrono::Local::now(),
             };
 
-            let journal_manager = &app_state.journal_manager;
-            
-            match journal_manager.load_entry(&cycle_date).await {
-                Ok(Some(entry)) => {
-                    match serde_json::to_string(&entry) {
-                        Ok(json) => {
-                            return Response::builder()
-                                .header("Content-Type", "application/json")
-                                .body(json.into())
-                                .unwrap();
+            let saved = journal_manager
+                .save_entry_if_unchanged(&entry, form.revision.as_deref())
+                .await
+                .map_err(|e| e.to_string());
+            match saved {
+                Ok(crate::journal::EntrySaveResult::Saved(revision)) => {
+                    tracing::info!("Journal entry saved for {}", entry.cycle_date);
+                    if let Some(prompt_number) = form.prompt_number {
+                        if let Err(e) = journal_manager.record_answered_prompt(&entry.cycle_date, prompt_number).await.map_err(|e| e.to_string()) {
+                            tracing::warn!("Failed to record answered prompt: {}", e);
                         }
+                    }
+                    if let Err(e) = crate::stats::refresh_stats(journal_manager).await {
+                        tracing::warn!("Failed to update writing stats: {}", e);
+                    }
+                    if wants_json(&headers) {
+                        return json_response(StatusCode::OK, &serde_json::json!({ "revision": revision }));
+                    }
+                    // Redirect back to the same journal page date
+                    let redirect_url = if entry.cycle_date == crate::cycle_date::CycleDate::today() {
+                        app_url("/journal")
+                    } else {
+                        app_url(&format!("/journal?date={}", entry.cycle_date))
+                    };
+                    return (
+                        StatusCode::SEE_OTHER,
+                        [("Location", redirect_url.as_str())],
+                        Html("Entry saved successfully"),
+                    ).into_response();
+                }
+                Ok(crate::journal::EntrySaveResult::Conflict { current, revision }) => {
+                    tracing::info!("Entry conflict for {} (client revision {:?}, current {})", cycle_date, form.revision, revision);
+                    let conflict = EntryConflict {
+                        current_content: current.content,
+                        current_revision: revision,
+                        current_modified_at: current.modified_at,
+                        submitted_content: entry.content,
+                    };
+                    if wants_json(&headers) {
+                        return json_response(StatusCode::CONFLICT, &conflict);
+                    }
+                    let template = EntryConflictTemplate {
+                        cycle_date: cycle_date.to_string(),
+                        prompt_number: form.prompt_number,
+                        conflict,
+                        override_lock: form.override_lock,
+                    };
+                    return match template.render() {
+                        Ok(html) => (StatusCode::CONFLICT, Html(html)).into_response(),
                         Err(e) => {
-                            tracing::error!("Failed to serialize entry: {}", e);
-                            return (StatusCode::INTERNAL_SERVER_ERROR, "Error serializing entry").into_response();
+                            AppError::internal("Error rendering page", e).into_response()
                         }
-                    }
+                    };
+                }
+                Err(e) => {
+                    return AppError::internal("Error saving entry", e).into_response();
                 }
-                Ok(None) => {
-                    return Response::builder()
-                        .header("Content-Type", "application/json")
-                        .body("null".into())
-                        .unwrap();
+            }
+        }
+    }
+
+    // Not authenticated - redirect to login
+    redirect_to_login().into_response()
+}
+
+/// Get journal entry as JSON (for auto-save functionality)
+async fn get_journal_entry_json(
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Query(params): Query<JournalDateQuery>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::ReadEntries) {
+        return rejection.into_response();
+    }
+
+    let cycle_date = if let Some(date_str) = params.date {
+        match crate::cycle_date::CycleDate::from_string(&date_str) {
+            Ok(date) => date,
+            Err(_) => crate::cycle_date::CycleDate::today(),
+        }
+    } else {
+        crate::cycle_date::CycleDate::today()
+    };
+
+    let journal_manager = &notebook.journal_manager;
+    // Pages and devices poll this, so an unchanged entry is answered without loading it
+    let validator = crate::compression::FileValidator::of(&journal_manager.entry_files(&cycle_date)).await;
+    if let Some(not_modified) = validator.not_modified(&headers) {
+        return not_modified;
+    }
+    
+    match journal_manager.load_entry(&cycle_date).await {
+        Ok(Some(entry)) => {
+            // The revision goes back with the next save so a concurrent edit is detected
+            let revision = crate::journal::entry_revision(&entry.content);
+            match serde_json::to_value(&entry) {
+                Ok(mut json) => {
+                    json["revision"] = serde_json::Value::String(revision);
+                    validator.apply(json_response(StatusCode::OK, &json))
                 }
                 Err(e) => {
-                    tracing::error!("Failed to load entry: {}", e);
-                    return (StatusCode::INTERNAL_SERVER_ERROR, "Error loading entry").into_response();
+                    AppError::internal("Error serializing entry", e).into_response()
                 }
             }
         }
+        Ok(None) => {
+            validator.apply(([(header::CONTENT_TYPE, "application/json")], "null"))
+        }
+        Err(e) => {
+            AppError::internal("Error loading entry", e).into_response()
+        }
+    }
+}
+
+/// Save an autosave draft (PUT /journal/entry.json)
+/// Returns the saved draft, or 409 with the current draft if another device saved first.
+async fn save_journal_draft_json(
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Json(request): Json<JournalDraftRequest>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::WriteEntries) {
+        return rejection.into_response();
+    }
+
+    let cycle_date = match request.cycle_date.as_deref() {
+        Some(date_str) => match crate::cycle_date::CycleDate::from_string(date_str) {
+            Ok(date) => date,
+            Err(e) => {
+                tracing::error!("Invalid cycle date: {}", e);
+                return AppError::bad_request("Invalid cycle date").into_response();
+            }
+        },
+        None => crate::cycle_date::CycleDate::today(),
+    };
+
+    if request.override_lock {
+        if let Err(rejection) = auth.require(Scope::Admin) {
+            return rejection.into_response();
+        }
+    }
+    if let Err(e) = crate::journal::check_entry_date_editable(
+        &cycle_date,
+        &crate::cycle_date::CycleDate::today(),
+        crate::config::live().journal.edit_window(request.override_lock),
+    ) {
+        return AppError::forbidden(e).into_response();
+    }
+
+    let content = if request.append {
+        // A revision mismatch is caught by `save_draft` as a conflict
+        match notebook.journal_manager.load_draft(&cycle_date).await.map_err(|e| e.to_string()) {
+            Ok(draft) => format!("{}{}", draft.map(|d| d.content).unwrap_or_default(), request.content),
+            Err(e) => {
+                return AppError::internal("Error loading draft", e).into_response();
+            }
+        }
+    } else {
+        request.content
+    };
+    let (status, draft) = match notebook.journal_manager.save_draft(&cycle_date, &content, request.revision).await {
+        Ok(crate::journal::DraftSaveResult::Saved(draft)) => {
+            tracing::debug!("Draft revision {} saved for {} from {}", draft.revision, cycle_date, auth.label());
+            (StatusCode::OK, draft)
+        }
+        Ok(crate::journal::DraftSaveResult::Conflict(current)) => {
+            tracing::info!("Draft conflict for {} (client revision {:?}, current {})", cycle_date, request.revision, current.revision);
+            (StatusCode::CONFLICT, current)
+        }
+        Err(e) => {
+            return AppError::internal("Error saving draft", e).into_response();
+        }
+    };
+
+    json_response(status, &draft)
+}
+
+/// Record the text a device's editor holds (POST /journal/draft). Sent every few seconds
+/// while typing, so another device opening the day can offer to recover it.
+async fn draft_heartbeat(
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Json(request): Json<DraftHeartbeatRequest>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::WriteEntries) {
+        return rejection.into_response();
+    }
+
+    let cycle_date = match crate::cycle_date::CycleDate::from_string(&request.cycle_date) {
+        Ok(date) => date,
+        Err(e) => {
+            tracing::error!("Invalid cycle date: {}", e);
+            return AppError::bad_request("Invalid cycle date").into_response();
+        }
+    };
+    if request.override_lock {
+        if let Err(rejection) = auth.require(Scope::Admin) {
+            return rejection.into_response();
+        }
+    }
+    if let Err(e) = crate::journal::check_entry_date_editable(
+        &cycle_date,
+        &crate::cycle_date::CycleDate::today(),
+        crate::config::live().journal.edit_window(request.override_lock),
+    ) {
+        return AppError::forbidden(e).into_response();
     }
 
-    (StatusCode::UNAUTHORIZED, "Unauthorized").into_response()
+    match notebook.journal_manager.save_device_draft(&cycle_date, auth.label(), &request.content).await {
+        Ok(draft) => json_response(StatusCode::OK, &serde_json::json!({
+            "device": draft.device,
+            "started_at": draft.started_at,
+            "saved_at": draft.saved_at,
+        })),
+        Err(e) => AppError::internal("Error saving draft heartbeat", e).into_response(),
+    }
 }
 
 /// Form for prompt generation request
@@ -462,94 +1643,71 @@ pub struct GeneratePromptForm {
     pub cycle_date: String,
 }
 
-/// Response for prompt generation
+/// Response for prompt generation and other model work: the job to poll for the result
 #[derive(serde::Serialize)]
 pub struct GeneratePromptResponse {
-    pub prompt: String,
+    pub job_id: String,
+    pub status_url: String,
 }
 
-/// Generate LLM prompt endpoint
+/// Start generating an LLM prompt; answers 202 with a job to poll at /api/jobs/:id
 async fn generate_prompt_endpoint(
     State(app_state): State<AppState>,
-    headers: HeaderMap,
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
     Json(form): Json<GeneratePromptForm>,
 ) -> Response {
-    // Extract token from cookie
-    let token = extract_session_token(&headers);
-
-    // Check if authenticated
-    if let Some(token) = token {
-        if app_state.auth_manager.validate_session(&token).await {
-            tracing::info!(" Generating prompt for entry type: {}", form.entry_type);
-            
-            // Parse cycle date
-            let _cycle_date = match crate::cycle_date::CycleDate::from_string(&form.cycle_date) {
-                Ok(date) => date,
-                Err(e) => {
-                    tracing::error!("Invalid cycle date: {}", e);
-                    return (StatusCode::BAD_REQUEST, "Invalid cycle date").into_response();
-                }
-            };
+    // Starting the model is writing, not reading, so read-only display keys can't
+    if let Err(rejection) = auth.require(Scope::WriteEntries) {
+        return rejection.into_response();
+    }
 
-            // Create LLM worker (this will be moved to app state in the future)
-            let model_path = app_state.config.llm.model_path.clone();
-            
-            let llm_worker = match crate::llm_worker::LlmWorker::new(
-                model_path, 
-                app_state.config.llm.temperature, 
-                app_state.config.llm.max_tokens
-            ) {
-                Ok(worker) => worker,
-                Err(e) => {
-                    tracing::error!("Failed to create LLM worker: {}", e);
-                    return (StatusCode::INTERNAL_SERVER_ERROR, "LLM initialization failed").into_response();
-                }
-            };
+    tracing::info!(" Generating prompt for entry type: {}", form.entry_type);
+    
+    // Parse cycle date
+    let cycle_date = match crate::cycle_date::CycleDate::from_string(&form.cycle_date) {
+        Ok(date) => date,
+        Err(e) => {
+            tracing::error!("Invalid cycle date: {}", e);
+            return AppError::bad_request("Invalid cycle date").into_response();
+        }
+    };
+    let Some(prompt_generator) = &notebook.prompt_generator else {
+        return AppError::unavailable("Prompt generator not available").into_response();
+    };
 
-            // Load model if not already loaded
-            if let Err(e) = llm_worker.load_model().await {
-                tracing::error!("Failed to load LLM model: {}", e);
-                return (StatusCode::INTERNAL_SERVER_ERROR, "Model loading failed").into_response();
-            }
+    // Create prompt based on entry type
+    let prompt_request = match form.entry_type.as_str() {
+        "Daily Entry" => "Create a thoughtful journal prompt for daily reflection",
+        "Weekly Reflection" => "Create a journal prompt for weekly reflection and growth",
+        "Monthly Reflection" => "Create a journal prompt for monthly introspection and goal assessment",
+        "Yearly Reflection" => "Create a journal prompt for deep yearly reflection and life review",
+        _ => "Create a meaningful journal prompt for personal reflection",
+    };
+    let prompt_request = format!(
+        "{} for {} ({}).",
+        prompt_request,
+        crate::locale::long_date(cycle_date.to_real_date()),
+        cycle_date,
+    );
 
-            // Create prompt based on entry type
-            let prompt_request = match form.entry_type.as_str() {
-                "Daily Entry" => "Create a thoughtful journal prompt for daily reflection",
-                "Weekly Reflection" => "Create a journal prompt for weekly reflection and growth",
-                "Monthly Reflection" => "Create a journal prompt for monthly introspection and goal assessment",
-                "Yearly Reflection" => "Create a journal prompt for deep yearly reflection and life review",
-                _ => "Create a meaningful journal prompt for personal reflection",
-            };
+    // Generate in the background; the model can take a minute
+    let job = app_state.prompt_jobs.start(prompt_generator.worker(), &notebook.name, &form.entry_type, prompt_request);
+    job_accepted(&job)
+}
 
-            // Generate the prompt
-            match llm_worker.generate_text(prompt_request, 200).await {
-                Ok(generated_prompt) => {
-                    let response = GeneratePromptResponse {
-                        prompt: generated_prompt,
-                    };
-                    
-                    match serde_json::to_string(&response) {
-                        Ok(json) => {
-                            return Response::builder()
-                                .header("Content-Type", "application/json")
-                                .body(json.into())
-                                .unwrap();
-                        }
-                        Err(e) => {
-                            tracing::error!("Failed to serialize prompt response: {}", e);
-                            return (StatusCode::INTERNAL_SERVER_ERROR, "Serialization error").into_response();
-                        }
-                    }
-                }
-                Err(e) => {
-                    tracing::error!("Failed to generate prompt: {}", e);
-                    return (StatusCode::INTERNAL_SERVER_ERROR, "Prompt generation failed").into_response();
-                }
-            }
-        }
+/// 202 Accepted pointing at a background job to poll
+pub(crate) fn job_accepted(job: &crate::prompt_jobs::PromptJob) -> Response {
+    let status_url = app_url(&format!("/api/jobs/{}", job.id));
+    let mut response = json_response(StatusCode::ACCEPTED, &GeneratePromptResponse {
+        job_id: job.id.clone(),
+        status_url: status_url.clone(),
+    });
+    if let Ok(location) = header::HeaderValue::from_str(&status_url) {
+        response.headers_mut().insert(header::LOCATION, location);
     }
-
-    (StatusCode::UNAUTHORIZED, "Unauthorized").into_response()
+    response.headers_mut().insert(header::RETRY_AFTER, header::HeaderValue::from(crate::prompt_jobs::POLL_SECONDS));
+    response
 }
 
 /// Form for prompt navigation request
@@ -560,22 +1718,128 @@ pub struct PromptNavigationForm {
     pub direction: String, // "next" or "prev"
 }
 
+/// Whether a prompt can be shown yet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptStatus {
+    Ready,
+    /// Queued or being generated; poll /journal/check-prompt-status or watch /journal/events
+    Pending,
+    Failed,
+}
+
 /// Response for prompt navigation
 #[derive(serde::Serialize)]
 pub struct PromptNavigationResponse {
     pub prompt: Option<String>,
     pub prompt_number: u32,
     pub prompt_type: String,
+    pub status: PromptStatus,
     pub has_prev: bool,
     pub has_next: bool,
+    /// This request queued the prompt's generation
     pub generated_new: bool,
 }
 
-/// Navigate between prompts (next/previous)
+/// Navigate between prompts (next/previous). Today's next prompt is queued for generation
+/// when it doesn't exist yet, for callers who can write; other days only have the prompts
+/// already saved.
 async fn navigate_prompt_endpoint(
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Json(form): Json<PromptNavigationForm>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::ReadPrompts) {
+        return rejection.into_response();
+    }
+
+    tracing::info!(" Navigation request: current_prompt={}, direction={}, cycle_date={}", 
+        form.current_prompt, form.direction, form.cycle_date);
+    
+    // Parse cycle date
+    let cycle_date = match crate::cycle_date::CycleDate::from_string(&form.cycle_date) {
+        Ok(date) => date,
+        Err(e) => {
+            tracing::error!("Invalid cycle date: {}", e);
+            return AppError::bad_request("Invalid cycle date").into_response();
+        }
+    };
+    
+    // Calculate new prompt number based on direction
+    let new_prompt_number = match form.direction.as_str() {
+        "next" => form.current_prompt + 1,
+        "prev" => form.current_prompt.saturating_sub(1).max(1),
+        _ => {
+            return AppError::bad_request("Invalid direction").into_response();
+        }
+    };
+    let Ok(prompt_number) = u8::try_from(new_prompt_number) else {
+        return AppError::bad_request("Invalid prompt number").into_response();
+    };
+
+    let journal_manager = &notebook.journal_manager;
+    let prompt_count = journal_manager.count_prompt_files(&cycle_date);
+    let existing = match journal_manager.load_prompt(&cycle_date, prompt_number).await.map_err(|e| e.to_string()) {
+        Ok(existing) => existing,
+        Err(e) => return AppError::internal("Failed to read prompt", e).into_response(),
+    };
+    if let Some(prompt) = existing {
+        return json_response(StatusCode::OK, &PromptNavigationResponse {
+            prompt: Some(prompt.prompt.trim().to_string()),
+            prompt_number: new_prompt_number,
+            prompt_type: prompt.prompt_type.to_string(),
+            status: PromptStatus::Ready,
+            has_prev: prompt_number > 1,
+            has_next: prompt_count > prompt_number as usize,
+            generated_new: false,
+        });
+    }
+
+    // Prompts are numbered consecutively, so only the one after the last can be generated,
+    // and only for today
+    if prompt_number as usize > prompt_count + 1 || cycle_date != crate::cycle_date::CycleDate::today() {
+        return AppError::not_found(format!("There is no prompt #{} for {}", prompt_number, cycle_date)).into_response();
+    }
+    if let Err(rejection) = auth.require(Scope::WriteEntries) {
+        return rejection.into_response();
+    }
+    if journal_manager.no_prompt_needed(&cycle_date).await {
+        return AppError::conflict("This day is marked as needing no prompt").into_response();
+    }
+    let Some(prompt_generator) = &notebook.prompt_generator else {
+        return AppError::unavailable("Prompt generator not available").into_response();
+    };
+
+    // Don't queue a second generation while one is under way
+    let generated_new = !prompt_generator.progress().in_progress(cycle_date, prompt_number);
+    if generated_new {
+        tracing::info!(" Starting background generation for prompt #{}", prompt_number);
+        prompt_generator.queue_prompt_generation(cycle_date, prompt_number, &notebook.personalization_config.current().prompts);
+    }
+    json_response(StatusCode::ACCEPTED, &PromptNavigationResponse {
+        prompt: None,
+        prompt_number: new_prompt_number,
+        prompt_type: crate::journal::PromptType::for_date(&cycle_date).to_string(),
+        status: PromptStatus::Pending,
+        has_prev: prompt_number > 1,
+        has_next: false,
+        generated_new,
+    })
+}
+
+/// Mark a day as needing no prompt, or undo it
+#[derive(Deserialize)]
+pub struct NoPromptForm {
+    pub cycle_date: String,
+    pub no_prompt: bool,
+}
+
+/// Skip prompt generation for a day the user already knows what to write about (POST /journal/no-prompt)
+async fn no_prompt_form(
     State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
     headers: HeaderMap,
-    Json(form): Json<PromptNavigationForm>,
+    Form(form): Form<NoPromptForm>,
 ) -> Response {
     // Extract token from cookie
     let token = extract_session_token(&headers);
@@ -583,111 +1847,19 @@ async fn navigate_prompt_endpoint(
     // Check if authenticated
     if let Some(token) = token {
         if app_state.auth_manager.validate_session(&token).await {
-            tracing::info!(" Navigation request: current_prompt={}, direction={}, cycle_date={}", 
-                form.current_prompt, form.direction, form.cycle_date);
-            
-            // Parse cycle date
-            let cycle_date = match crate::cycle_date::CycleDate::from_string(&form.cycle_date) {
-                Ok(date) => date,
-                Err(e) => {
-                    tracing::error!("Invalid cycle date: {}", e);
-                    return (StatusCode::BAD_REQUEST, "Invalid cycle date").into_response();
-                }
-            };
-            
-            // Calculate new prompt number based on direction
-            let new_prompt_number = match form.direction.as_str() {
-                "next" => form.current_prompt + 1,
-                "prev" => {
-                    if form.current_prompt > 1 {
-                        form.current_prompt - 1
-                    } else {
-                        1
-                    }
-                }
-                _ => {
-                    return (StatusCode::BAD_REQUEST, "Invalid direction").into_response();
-                }
-            };
-
-            // Check if the prompt file already exists
-            let prompt_path = if new_prompt_number <= 3 {
-                format!("journal/{}/prompt{}.txt", cycle_date.to_string(), new_prompt_number)
-            } else {
-                // For prompts beyond 3, use the same date directory format
-                format!("journal/{}/prompt{}.txt", cycle_date.to_string(), new_prompt_number)
+            let Ok(cycle_date) = crate::cycle_date::CycleDate::from_string(&form.cycle_date) else {
+                return AppError::bad_request("Invalid cycle date").into_response();
             };
-            
-            if std::path::Path::new(&prompt_path).exists() {
-                // Prompt already exists, read and return it
-                match std::fs::read_to_string(&prompt_path) {
-                    Ok(prompt_content) => {
-                        let response = PromptNavigationResponse {
-                            prompt: Some(prompt_content.trim().to_string()),
-                            prompt_number: new_prompt_number,
-                            prompt_type: "Daily".to_string(),
-                            has_prev: new_prompt_number > 1,
-                            has_next: true,
-                            generated_new: false,
-                        };
-                        
-                        match serde_json::to_string(&response) {
-                            Ok(json) => {
-                                return Response::builder()
-                                    .header("Content-Type", "application/json")
-                                    .body(json.into())
-                                    .unwrap();
-                            }
-                            Err(e) => {
-                                tracing::error!("Failed to serialize navigation response: {}", e);
-                                return (StatusCode::INTERNAL_SERVER_ERROR, "Serialization error").into_response();
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to read existing prompt file: {}", e);
-                        return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to read prompt").into_response();
-                    }
-                }
-            } else {
-                // Prompt doesn't exist, start background generation
-                tracing::info!(" Starting background generation for prompt #{}", new_prompt_number);
-                
-                // Queue prompt generation in background
-                if let Some(prompt_generator) = &app_state.prompt_generator {
-                    prompt_generator.queue_prompt_generation(cycle_date, new_prompt_number as u8, &app_state.personalization_config.prompts);
-                } else {
-                    tracing::error!("Prompt generator not available");
-                    return (StatusCode::INTERNAL_SERVER_ERROR, "Prompt generator not available").into_response();
-                }
-                
-                // Return "generating" status immediately
-                let response = PromptNavigationResponse {
-                    prompt: None, // No prompt content yet
-                    prompt_number: new_prompt_number,
-                    prompt_type: "Daily".to_string(),
-                    has_prev: new_prompt_number > 1,
-                    has_next: true,
-                    generated_new: true, // Indicates generation in progress
-                };
-                
-                match serde_json::to_string(&response) {
-                    Ok(json) => {
-                        return Response::builder()
-                            .header("Content-Type", "application/json")
-                            .body(json.into())
-                            .unwrap();
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to serialize navigation response: {}", e);
-                        return (StatusCode::INTERNAL_SERVER_ERROR, "Serialization error").into_response();
-                    }
-                }
+            let result = notebook.journal_manager.set_no_prompt_needed(&cycle_date, form.no_prompt).await.map_err(|e| e.to_string());
+            if let Err(e) = result {
+                return AppError::internal("Error saving the day's setting", format!("{}: {}", cycle_date, e)).into_response();
             }
+            return Redirect::to(&app_url(&format!("/journal?date={}", cycle_date))).into_response();
         }
     }
 
-    (StatusCode::UNAUTHORIZED, "Unauthorized").into_response()
+    // Not authenticated - redirect to login
+    redirect_to_login().into_response()
 }
 
 /// Form for checking prompt status
@@ -701,96 +1873,2198 @@ pub struct PromptStatusForm {
 #[derive(serde::Serialize)]
 pub struct PromptStatusResponse {
     pub ready: bool,
+    pub status: PromptStatus,
     pub prompt: Option<String>,
+    /// Why generation failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 /// Check if a prompt is ready (for polling by frontend)
 async fn check_prompt_status_endpoint(
-    State(app_state): State<AppState>,
-    headers: HeaderMap,
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
     Json(form): Json<PromptStatusForm>,
 ) -> Response {
-    // Extract token from cookie
-    let token = extract_session_token(&headers);
+    if let Err(rejection) = auth.require(Scope::ReadPrompts) {
+        return rejection.into_response();
+    }
 
-    // Check if authenticated
-    if let Some(token) = token {
-        if app_state.auth_manager.validate_session(&token).await {
-            // Parse cycle date
-            let cycle_date = match crate::cycle_date::CycleDate::from_string(&form.cycle_date) {
-                Ok(date) => date,
-                Err(e) => {
-                    tracing::error!("Invalid cycle date: {}", e);
-                    return (StatusCode::BAD_REQUEST, "Invalid cycle date").into_response();
-                }
-            };
+    // Parse cycle date
+    let cycle_date = match crate::cycle_date::CycleDate::from_string(&form.cycle_date) {
+        Ok(date) => date,
+        Err(e) => {
+            tracing::error!("Invalid cycle date: {}", e);
+            return AppError::bad_request("Invalid cycle date").into_response();
+        }
+    };
+    let Ok(prompt_number) = u8::try_from(form.prompt_number) else {
+        return AppError::bad_request("Invalid prompt number").into_response();
+    };
 
-            // Check if the prompt file exists
-            let prompt_path = if form.prompt_number <= 3 {
-                format!("journal/{}/prompt{}.txt", cycle_date.to_string(), form.prompt_number)
-            } else {
-                // For prompts beyond 3, use the same date directory format
-                format!("journal/{}/prompt{}.txt", cycle_date.to_string(), form.prompt_number)
+    let response = match notebook.journal_manager.load_prompt(&cycle_date, prompt_number).await.map_err(|e| e.to_string()) {
+        Ok(Some(prompt)) => PromptStatusResponse {
+            ready: true,
+            status: PromptStatus::Ready,
+            prompt: Some(prompt.prompt.trim().to_string()),
+            error: None,
+        },
+        Ok(None) => {
+            let failure = match &notebook.prompt_generator {
+                Some(generator) => generator.progress().latest(cycle_date, prompt_number).and_then(|event| event.error),
+                None => Some("Prompt generator not available".to_string()),
             };
-            
-            if std::path::Path::new(&prompt_path).exists() {
-                // Prompt is ready, read and return it
-                match std::fs::read_to_string(&prompt_path) {
-                    Ok(prompt_content) => {
-                        let response = PromptStatusResponse {
-                            ready: true,
-                            prompt: Some(prompt_content.trim().to_string()),
-                        };
-                        
-                        match serde_json::to_string(&response) {
-                            Ok(json) => {
-                                return Response::builder()
-                                    .header("Content-Type", "application/json")
-                                    .body(json.into())
-                                    .unwrap();
-                            }
-                            Err(e) => {
-                                tracing::error!("Failed to serialize status response: {}", e);
-                                return (StatusCode::INTERNAL_SERVER_ERROR, "Serialization error").into_response();
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to read prompt file: {}", e);
-                        return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to read prompt").into_response();
-                    }
-                }
-            } else {
-                // Prompt not ready yet
-                let response = PromptStatusResponse {
-                    ready: false,
-                    prompt: None,
-                };
-                
-                match serde_json::to_string(&response) {
-                    Ok(json) => {
-                        return Response::builder()
-                            .header("Content-Type", "application/json")
-                            .body(json.into())
-                            .unwrap();
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to serialize status response: {}", e);
-                        return (StatusCode::INTERNAL_SERVER_ERROR, "Serialization error").into_response();
-                    }
-                }
+            PromptStatusResponse {
+                ready: false,
+                status: if failure.is_some() { PromptStatus::Failed } else { PromptStatus::Pending },
+                prompt: None,
+                error: failure,
             }
         }
+        Err(e) => return AppError::internal("Failed to read prompt", e).into_response(),
+    };
+    json_response(StatusCode::OK, &response)
+}
+
+/// Query parameters for the generation event stream
+#[derive(Deserialize)]
+pub struct GenerationEventsQuery {
+    /// Only events for this day
+    pub date: Option<String>,
+}
+
+/// Stream prompt generation progress (queued, generating, saved, failed) as
+/// server-sent events, one JSON object per event (GET /journal/events)
+async fn generation_events(
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Query(params): Query<GenerationEventsQuery>,
+) -> Response {
+    use axum::response::sse::{Event, KeepAlive, Sse};
+    use tokio_stream::StreamExt;
+
+    if let Err(rejection) = auth.require(Scope::ReadPrompts) {
+        return rejection.into_response();
+    }
+
+    let only_date = match params.date.as_deref().map(parse_date_param).transpose() {
+        Ok(date) => date,
+        Err(e) => return AppError::bad_request(e).into_response(),
+    };
+    let Some(prompt_generator) = &notebook.prompt_generator else {
+        return AppError::unavailable("Prompt generation is not available").into_response();
+    };
+
+    // A listener that falls behind skips the events it missed rather than closing the stream
+    let events = tokio_stream::wrappers::BroadcastStream::new(prompt_generator.progress().subscribe())
+        .filter_map(move |event| {
+            let event = event.ok()?;
+            if only_date.is_some_and(|date| date != event.cycle_date) {
+                return None;
+            }
+            Event::default().json_data(&event).ok()
+        })
+        .map(Ok::<_, std::convert::Infallible>);
+
+    Sse::new(events).keep_alive(KeepAlive::default()).into_response()
+}
+
+/// Push saved entries, prompts, and statuses to every connected device as JSON
+/// messages over a WebSocket (GET /journal/live), and the new day when the date rolls
+/// over. Today's date is also sent on connecting, for a device that slept through it.
+/// Browsers send the session cookie with any site's WebSocket, so pages from other
+/// origins are turned away.
+async fn live_sync(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    headers: HeaderMap,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    use tokio::sync::broadcast::error::RecvError;
+
+    if let Err(rejection) = auth.require(Scope::ReadEntries) {
+        return rejection.into_response();
+    }
+    if let Some(origin) = headers.get(header::ORIGIN) {
+        let hosts: Vec<&str> = [header::HOST.as_str(), "x-forwarded-host"]
+            .iter()
+            .filter_map(|name| headers.get(*name)?.to_str().ok())
+            .collect();
+        let allowed = origin.to_str().is_ok_and(|origin| crate::api::allows_origin(&app_state.config.server, origin, &hosts));
+        if !allowed {
+            tracing::warn!("Refused live sync from origin {:?}", origin);
+            return AppError::forbidden("Live sync isn't available to other sites").into_response();
+        }
+    }
+
+    let label = auth.label().to_string();
+    let mut changes = notebook.journal_manager.subscribe_changes();
+    upgrade.on_upgrade(move |mut socket| async move {
+        tracing::info!("Live sync connected: {}", label);
+        let today = crate::journal::JournalChange::DayStarted { cycle_date: crate::cycle_date::CycleDate::today() };
+        if socket.send(Message::Text(serde_json::to_string(&today).unwrap_or_default())).await.is_err() {
+            return;
+        }
+        loop {
+            let message = tokio::select! {
+                change = changes.recv() => match change {
+                    Ok(change) => serde_json::to_string(&change).unwrap_or_default(),
+                    // Tell the device to reload rather than replay what it missed
+                    Err(RecvError::Lagged(_)) => r#"{"type":"resync"}"#.to_string(),
+                    Err(RecvError::Closed) => break,
+                },
+                // Clients only listen; anything they send other than a close is ignored
+                // (axum answers pings itself)
+                received = socket.recv() => match received {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                },
+            };
+            if socket.send(Message::Text(message)).await.is_err() {
+                break;
+            }
+        }
+        tracing::info!("Live sync disconnected: {}", label);
+    })
+}
+
+/// Longest steering instruction accepted for a regeneration
+const MAX_STEERING_LENGTH: usize = 300;
+
+/// Request to regenerate a prompt or summary
+#[derive(Deserialize)]
+pub struct RegenerateRequest {
+    pub cycle_date: String,
+    /// "prompt" or "summary"
+    pub target: String,
+    pub prompt_number: Option<u8>,
+    /// One-off instruction appended to the template for this generation only
+    pub steering: Option<String>,
+}
+
+/// Response carrying the regenerated text
+#[derive(Serialize)]
+pub struct RegenerateResponse {
+    pub text: String,
+    pub steering: Option<String>,
+    /// Snapshot of the replaced text, for rolling back from the admin page
+    pub snapshot_id: String,
+}
+
+/// Regenerate a prompt or summary, optionally steered by a short instruction
+async fn regenerate_endpoint(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Json(request): Json<RegenerateRequest>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::WriteEntries) {
+        return rejection.into_response();
+    }
+
+    let cycle_date = match crate::cycle_date::CycleDate::from_string(&request.cycle_date) {
+        Ok(date) => date,
+        Err(e) => {
+            tracing::error!("Invalid cycle date: {}", e);
+            return AppError::bad_request("Invalid cycle date").into_response();
+        }
+    };
+
+    let steering = request.steering.as_deref().map(str::trim).filter(|s| !s.is_empty());
+    if steering.is_some_and(|s| s.chars().count() > MAX_STEERING_LENGTH) {
+        return AppError::bad_request(format!("Steering instruction must be at most {} characters", MAX_STEERING_LENGTH)).into_response();
+    }
+
+    let Some(prompt_generator) = &notebook.prompt_generator else {
+        return AppError::unavailable("Prompt generation is not available").into_response();
+    };
+
+    let paths = notebook.journal_manager.get_file_paths(&cycle_date);
+    let prompt_number = request.prompt_number.unwrap_or(1).max(1);
+    let replaced = match request.target.as_str() {
+        "prompt" => paths.metadata.with_file_name(format!("prompt{}.txt", prompt_number)),
+        _ => paths.summary.clone(),
+    };
+    let operation = format!("Regenerate {} for {} in {}", request.target, cycle_date, notebook.name);
+    let snapshot_id = match crate::snapshot::before(&app_state.config, operation, vec![replaced, paths.metadata]).await {
+        Ok(id) => id,
+        Err(e) => {
+            return AppError::internal("Could not snapshot the current text; nothing was regenerated", e).into_response();
+        }
+    };
+
+    tracing::info!("Regenerating {} for {} ({})", request.target, cycle_date, auth.label());
+    let model = crate::scheduler::lock_model("Regeneration").await;
+    let result = match request.target.as_str() {
+        "prompt" => {
+            prompt_generator.regenerate_prompt(&cycle_date, prompt_number, steering).await
+                .map(|p| p.prompt)
+                .map_err(|e| e.to_string())
+        }
+        "summary" => {
+            prompt_generator.regenerate_summary(&cycle_date, steering).await
+                .map(|s| s.summary)
+                .map_err(|e| e.to_string())
+        }
+        other => return AppError::bad_request(format!("Unknown regeneration target '{}'", other)).into_response(),
+    };
+    drop(model);
+
+    match result {
+        Ok(text) => json_response(StatusCode::OK, &RegenerateResponse {
+            text,
+            steering: steering.map(str::to_string),
+            snapshot_id,
+        }),
+        Err(e) => {
+            AppError::internal("Regeneration failed", format!("{} for {}: {}", request.target, cycle_date, e)).into_response()
+        }
+    }
+}
+
+/// Request to replace a prompt with the other ensemble candidate
+#[derive(Deserialize)]
+pub struct ChoosePromptRequest {
+    pub cycle_date: String,
+    pub prompt_number: u8,
+    /// Model whose candidate the user preferred
+    pub model: String,
+}
+
+/// Keep the user's pick between two ensemble candidates, recording that model as the winner
+async fn choose_prompt_endpoint(
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Json(request): Json<ChoosePromptRequest>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::WriteEntries) {
+        return rejection.into_response();
+    }
+
+    let cycle_date = match crate::cycle_date::CycleDate::from_string(&request.cycle_date) {
+        Ok(date) => date,
+        Err(e) => {
+            tracing::error!("Invalid cycle date: {}", e);
+            return AppError::bad_request("Invalid cycle date").into_response();
+        }
+    };
+
+    let result = notebook.journal_manager
+        .choose_ensemble_candidate(&cycle_date, request.prompt_number, &request.model)
+        .await
+        .map_err(|e| e.to_string());
+    match result {
+        Ok(prompt) => json_response(StatusCode::OK, &serde_json::json!({ "text": prompt.prompt })),
+        Err(e) => {
+            tracing::warn!("Failed to choose ensemble prompt {} for {}: {}", request.prompt_number, cycle_date, e);
+            AppError::bad_request(e).into_response()
+        }
+    }
+}
+
+/// Entry text to preview
+#[derive(Deserialize)]
+pub struct PreviewRequest {
+    pub content: String,
+}
+
+/// An entry rendered as it'll be shown
+#[derive(Serialize)]
+pub struct PreviewResponse {
+    pub html: String,
+}
+
+/// Render unsaved entry Markdown to sanitized HTML for the editor's preview (POST /journal/preview)
+async fn preview_endpoint(auth: AuthContext, Json(request): Json<PreviewRequest>) -> Response {
+    if let Err(rejection) = auth.require(Scope::WriteEntries) {
+        return rejection.into_response();
+    }
+
+    json_response(StatusCode::OK, &PreviewResponse { html: crate::markdown::to_html(&request.content) })
+}
+
+/// Request for a reflection question about an entry about to be saved
+#[derive(Deserialize)]
+pub struct ReflectRequest {
+    pub cycle_date: String,
+    pub content: String,
+    /// The prompt the entry answers, if the writer picked one
+    pub prompt_number: Option<u8>,
+}
+
+/// Ask one clarifying question about an unsaved entry. Only answers on day types the
+/// notebook has opted into with `reflect_before_saving`.
+async fn reflect_endpoint(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Json(request): Json<ReflectRequest>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::WriteEntries) {
+        return rejection.into_response();
+    }
+
+    let cycle_date = match crate::cycle_date::CycleDate::from_string(&request.cycle_date) {
+        Ok(date) => date,
+        Err(e) => {
+            tracing::error!("Invalid cycle date: {}", e);
+            return AppError::bad_request("Invalid cycle date").into_response();
+        }
+    };
+
+    let prompt_type = crate::journal::PromptType::for_date(&cycle_date);
+    if !app_state.config.journal.reflects_before_saving(&notebook.name, &prompt_type) {
+        return AppError::not_found(format!("Reflection before saving is not enabled for {} entries", prompt_type)).into_response();
+    }
+    if request.content.trim().is_empty() {
+        return AppError::bad_request("Entry is empty").into_response();
+    }
+
+    let Some(prompt_generator) = &notebook.prompt_generator else {
+        return AppError::unavailable("Prompt generation is not available").into_response();
+    };
+
+    let _model = crate::scheduler::lock_model("Reflection question").await;
+    let result = prompt_generator
+        .reflection_question(&cycle_date, request.prompt_number, request.content.trim())
+        .await
+        .map_err(|e| e.to_string());
+    match result {
+        Ok(question) => json_response(StatusCode::OK, &serde_json::json!({ "question": question })),
+        Err(e) => {
+            AppError::internal("Could not generate a question", format!("{}: {}", cycle_date, e)).into_response()
+        }
+    }
+}
+
+/// Largest voice note accepted; about half an hour of browser-recorded Opus
+const MAX_VOICE_NOTE_BYTES: usize = 32 * 1024 * 1024;
+
+/// Query parameters for a voice note upload
+#[derive(Deserialize)]
+pub struct VoiceNoteQuery {
+    pub date: String,
+    /// Entry revision the page was loaded with, to hand back the revision after the append
+    pub revision: Option<String>,
+    /// Append even though the entry is past the edit window (needs the admin scope)
+    #[serde(default)]
+    pub override_lock: bool,
+}
+
+/// Result of transcribing a voice note
+#[derive(Serialize)]
+pub struct VoiceNoteResponse {
+    pub text: String,
+    pub appended: bool,
+    /// Low-confidence transcripts wait in the review queue before being summarized
+    pub needs_review: bool,
+    /// Revision of the draft the transcript was also added to, if one was in progress
+    pub draft_revision: Option<u64>,
+    /// The entry's new revision, when the page's copy was current before the append; a page
+    /// holding an older revision keeps it so its next save still reports the conflict
+    pub entry_revision: Option<String>,
+}
+
+/// Save a recorded voice note as an attachment and add its transcript to the day's entry
+/// (POST /journal/voice-note?date=..., body is the audio)
+async fn upload_voice_note(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Query(params): Query<VoiceNoteQuery>,
+    headers: HeaderMap,
+    audio: Bytes,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::WriteEntries) {
+        return rejection.into_response();
+    }
+    if !crate::transcription::available(&app_state.config.transcription) {
+        return AppError::unavailable("No transcription command is configured").into_response();
+    }
+
+    let cycle_date = match crate::cycle_date::CycleDate::from_string(&params.date) {
+        Ok(date) => date,
+        Err(_) => return AppError::bad_request("Invalid cycle date").into_response(),
+    };
+    if params.override_lock {
+        if let Err(rejection) = auth.require(Scope::Admin) {
+            return rejection.into_response();
+        }
+    }
+    if let Err(e) = crate::journal::check_entry_date_editable(&cycle_date, &crate::cycle_date::CycleDate::today(), crate::config::live().journal.edit_window(params.override_lock)) {
+        return AppError::forbidden(e).into_response();
+    }
+
+    let content_type = headers.get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).unwrap_or_default();
+    let extension = match content_type.split(';').next().unwrap_or_default().trim() {
+        "audio/webm" | "video/webm" => "webm",
+        "audio/ogg" => "ogg",
+        "audio/mp4" | "audio/x-m4a" => "m4a",
+        "audio/mpeg" => "mp3",
+        "audio/wav" | "audio/x-wav" => "wav",
+        _ => return AppError::from_status(StatusCode::UNSUPPORTED_MEDIA_TYPE, "Voice notes must be WebM, Ogg, M4A, MP3, or WAV audio").into_response(),
+    };
+    if audio.is_empty() {
+        return AppError::bad_request("The recording is empty").into_response();
+    }
+
+    let journal_manager = &notebook.journal_manager;
+    let filename = format!("voice-{}.{}", chrono::Local::now().format("%H%M%S"), extension);
+    let saved = journal_manager.save_attachment(&cycle_date, &filename, &audio).await.map_err(|e| e.to_string());
+    let filename = match saved {
+        Ok(filename) => filename,
+        Err(e) => {
+            return AppError::internal("Error saving the recording", format!("{}: {}", cycle_date, e)).into_response();
+        }
+    };
+
+    let Some(path) = journal_manager.attachment_path(&cycle_date, &filename) else {
+        return AppError::internal("Error saving the recording", format!("no attachment path for {}", filename)).into_response();
+    };
+    let transcript = match crate::transcription::transcribe(&app_state.config.transcription, &path).await {
+        Ok(transcript) => transcript,
+        Err(e) => {
+            tracing::error!("Failed to transcribe voice note for {}: {}", cycle_date, e);
+            return AppError::from_status(StatusCode::BAD_GATEWAY, format!("Transcription failed ({}). The recording was kept as {}.", e, filename)).into_response();
+        }
+    };
+
+    let revision_before = journal_manager.load_entry(&cycle_date).await.ok().flatten()
+        .map(|e| crate::journal::entry_revision(&e.content))
+        .unwrap_or_default();
+    let threshold = crate::config::live().journal.transcription_review_threshold;
+    let result = journal_manager
+        .append_transcription(&cycle_date, &transcript.text, &filename, transcript.confidence, threshold)
+        .await
+        .map_err(|e| e.to_string());
+    let appended = match result {
+        Ok(appended) => appended,
+        Err(e) => {
+            return AppError::internal("Error saving the transcript", format!("{}: {}", cycle_date, e)).into_response();
+        }
+    };
+
+    tracing::info!("Transcribed voice note for {} ({})", cycle_date, auth.label());
+    let draft_revision = journal_manager.load_draft(&cycle_date).await.ok().flatten().map(|d| d.revision);
+    let entry_revision = match params.revision {
+        Some(revision) if revision == revision_before => journal_manager.load_entry(&cycle_date).await.ok().flatten()
+            .map(|e| crate::journal::entry_revision(&e.content)),
+        _ => None,
+    };
+    json_response(StatusCode::OK, &VoiceNoteResponse {
+        text: transcript.text,
+        appended,
+        needs_review: transcript.confidence < threshold,
+        draft_revision,
+        entry_revision,
+    })
+}
+
+/// Transcription review queue - low-confidence OCR/STT entries beside their original media
+async fn review_queue_page(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    headers: HeaderMap,
+) -> Response {
+    // Extract token from cookie
+    let token = extract_session_token(&headers);
+
+    // Check if authenticated
+    if let Some(token) = token {
+        if app_state.auth_manager.validate_session(&token).await {
+            let journal_manager = &notebook.journal_manager;
+
+            let pending = match journal_manager.find_transcriptions_needing_review().await {
+                Ok(pending) => pending,
+                Err(e) => {
+                    return AppError::internal("Error loading review queue", e).into_response();
+                }
+            };
+
+            let mut items = Vec::new();
+            for (cycle_date, transcription) in pending {
+                let content = match journal_manager.load_entry(&cycle_date).await {
+                    Ok(Some(entry)) => entry.content,
+                    _ => String::new(),
+                };
+                items.push(ReviewItem {
+                    cycle_date: cycle_date.to_string(),
+                    real_date: crate::locale::long_date(cycle_date.to_real_date()),
+                    content,
+                    media_url: app_url(&format!("/journal/attachment?date={}&file={}", cycle_date, transcription.source_media)),
+                    media_kind: media_kind(&transcription.source_media).to_string(),
+                    confidence_percent: (transcription.confidence * 100.0).round() as u32,
+                });
+            }
+
+            let template = ReviewTemplate { items };
+            return match template.render() {
+                Ok(html) => Html(html).into_response(),
+                Err(e) => {
+                    AppError::internal("Error rendering page", e).into_response()
+                }
+            };
+        }
+    }
+
+    // Not authenticated - redirect to login
+    redirect_to_login().into_response()
+}
+
+/// Approve a transcription (with optional corrections) so it is included in summaries
+async fn approve_transcription(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    headers: HeaderMap,
+    Form(form): Form<ApproveTranscriptionForm>,
+) -> Response {
+    // Extract token from cookie
+    let token = extract_session_token(&headers);
+
+    // Check if authenticated
+    if let Some(token) = token {
+        if app_state.auth_manager.validate_session(&token).await {
+            let cycle_date = match crate::cycle_date::CycleDate::from_string(&form.cycle_date) {
+                Ok(date) => date,
+                Err(e) => {
+                    tracing::error!("Invalid cycle date: {}", e);
+                    return AppError::bad_request("Invalid cycle date").into_response();
+                }
+            };
+
+            // Approving with corrections overwrites the transcribed text
+            if form.content.is_some() {
+                let paths = notebook.journal_manager.get_file_paths(&cycle_date);
+                let operation = format!("Correct transcription for {} in {}", cycle_date, notebook.name);
+                if let Err(e) = crate::snapshot::before(&app_state.config, operation, vec![paths.entry, paths.metadata]).await {
+                    return AppError::internal("Could not snapshot the transcription; nothing was changed", e).into_response();
+                }
+            }
+
+            return match notebook.journal_manager.approve_transcription(&cycle_date, form.content).await {
+                Ok(()) => {
+                    tracing::info!("Transcription approved for {}", cycle_date);
+                    (
+                        StatusCode::SEE_OTHER,
+                        [("Location", app_url("/journal/review"))],
+                        Html("Transcription approved"),
+                    ).into_response()
+                }
+                Err(e) => {
+                    AppError::internal("Error approving transcription", e).into_response()
+                }
+            };
+        }
+    }
+
+    // Not authenticated - redirect to login
+    redirect_to_login().into_response()
+}
+
+/// Serve a file from a date's attachments directory
+async fn get_attachment(
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Query(params): Query<AttachmentQuery>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::ReadEntries) {
+        return rejection.into_response();
+    }
+
+    let cycle_date = match crate::cycle_date::CycleDate::from_string(&params.date) {
+        Ok(date) => date,
+        Err(_) => return AppError::bad_request("Invalid cycle date").into_response(),
+    };
+
+    let Some(path) = notebook.journal_manager.attachment_path(&cycle_date, &params.file) else {
+        return AppError::bad_request("Invalid file name").into_response();
+    };
+
+    return match tokio::fs::read(&path).await {
+        Ok(bytes) => Response::builder()
+            .header("Content-Type", attachment_content_type(&params.file))
+            .body(bytes.into())
+            .unwrap(),
+        Err(_) => AppError::not_found("Attachment not found").into_response(),
+    };
+}
+
+/// List entry metadata with optional filters and pagination (GET /api/entries)
+async fn list_entries_api(
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Query(params): Query<EntryListQuery>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::ReadEntries) {
+        return rejection.into_response();
+    }
+
+    match list_entry_page(&notebook.journal_manager, &params).await {
+        Ok(response) => json_response(StatusCode::OK, &response),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// One page of entry listings matching the query's filters, newest first
+pub(crate) async fn list_entry_page(
+    journal_manager: &crate::journal::JournalManager,
+    params: &EntryListQuery,
+) -> Result<EntryListResponse, AppError> {
+    let mut filter = build_entry_filter(params).map_err(AppError::bad_request)?;
+
+    if let Some(name) = &params.chapter {
+        let chapters = crate::chapters::load(journal_manager.base_path());
+        let Some(chapter) = chapters.iter().find(|c| c.name.eq_ignore_ascii_case(name.trim())) else {
+            return Err(AppError::not_found(format!("No chapter named '{}'", name)));
+        };
+        let start = crate::cycle_date::CycleDate::from_real_date(chapter.start);
+        filter.from = Some(filter.from.map_or(start, |from| if from.to_real_date() > chapter.start { from } else { start }));
+        if let Some(last) = crate::chapters::chapter_end(&chapters, chapter).and_then(|end| end.pred_opt()) {
+            let last = crate::cycle_date::CycleDate::from_real_date(last);
+            filter.to = Some(filter.to.map_or(last, |to| if to.to_real_date() < last.to_real_date() { to } else { last }));
+        }
+    }
+
+    let entries = journal_manager.list_entries(&filter).await.map_err(|e| AppError::internal("Error listing entries", e))?;
+
+    let offset = params.offset.unwrap_or(0);
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+    Ok(EntryListResponse {
+        total: entries.len(),
+        entries: entries.into_iter().skip(offset).take(limit).collect(),
+        offset,
+        limit,
+    })
+}
+
+/// Load streak and writing statistics together
+pub(crate) async fn load_all_stats(journal_manager: &crate::journal::JournalManager, config: &crate::config::Config) -> Result<StatsResponse, String> {
+    let streak = crate::stats::refresh_stats(journal_manager).await.map_err(|e| e.to_string())?;
+    let writing = crate::stats::writing_stats(journal_manager).await.map_err(|e| e.to_string())?;
+    let prompts = crate::analytics::prompt_analytics(journal_manager).await.map_err(|e| e.to_string())?;
+    let records = crate::telemetry::load(journal_manager.base_path()).await;
+    let context = crate::telemetry::BudgetReport::from_records(&records, crate::telemetry::REPORT_WINDOW);
+    let chapters = crate::stats::chapter_stats(journal_manager).await.map_err(|e| e.to_string())?;
+    let writing_window = crate::writing_times::writing_window(journal_manager, &config.journal).await.map_err(|e| e.to_string())?;
+    Ok(StatsResponse { streak, writing, prompts, context, chapters, writing_window })
+}
+
+/// Writing statistics dashboard
+async fn stats_page(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    headers: HeaderMap,
+) -> Response {
+    // Extract token from cookie
+    let token = extract_session_token(&headers);
+
+    // Check if authenticated
+    if let Some(token) = token {
+        if app_state.auth_manager.validate_session(&token).await {
+            let StatsResponse { streak, writing, prompts, context, chapters, writing_window } = match load_all_stats(&notebook.journal_manager, &app_state.config).await {
+                Ok(stats) => stats,
+                Err(e) => {
+                    return AppError::internal("Error loading statistics", e).into_response();
+                }
+            };
+
+            let chapter_list = crate::chapters::load(notebook.journal_manager.base_path());
+            let chapter_suggestions = match crate::chapter_suggestions::suggest(&notebook.journal_manager, &chapter_list).await.map_err(|e| e.to_string()) {
+                Ok(suggestions) => suggestions,
+                Err(e) => {
+                    tracing::warn!("Failed to look for chapter boundaries: {}", e);
+                    Vec::new()
+                }
+            };
+
+            let calendar = crate::cycle_date::calendar();
+            let question_years = match crate::year_review::years(&notebook.journal_manager, calendar).await.map_err(|e| e.to_string()) {
+                Ok(years) => years,
+                Err(e) => {
+                    tracing::warn!("Failed to list the journal's years: {}", e);
+                    Vec::new()
+                }
+            };
+
+            let busiest_week = writing.entries_per_week.iter().map(|w| w.entries).max().unwrap_or(0).max(1);
+            let weeks = writing.entries_per_week.iter().map(|w| WeekBar {
+                label: w.week_start.format("%b %d").to_string(),
+                entries: w.entries,
+                words: w.words,
+                height_percent: w.entries * 100 / busiest_week,
+            }).collect();
+
+            let template = StatsTemplate {
+                current_streak: streak.current_streak,
+                longest_streak: streak.longest_streak,
+                average_words: format!("{:.0}", writing.average_word_count),
+                words_this_year: writing.words_this_year,
+                most_active_weekday: writing.most_active_weekday.unwrap_or_else(|| "-".to_string()),
+                summary_coverage_percent: (writing.summary_coverage * 100.0).round() as u32,
+                total_entries: writing.total_entries,
+                weeks,
+                analytics: prompts,
+                context,
+                chapters,
+                chapter_suggestions,
+                year_reviews: crate::year_review::list(&notebook.journal_manager),
+                review_years: question_years.iter().skip(1).copied().collect(),
+                question_years,
+                writing_window,
+                prompt_generation_time: app_state.config.journal.prompt_generation_time.clone(),
+                adaptive_prompt_time: app_state.config.journal.adaptive_prompt_time,
+            };
+            return match template.render() {
+                Ok(html) => Html(html).into_response(),
+                Err(e) => {
+                    AppError::internal("Error rendering page", e).into_response()
+                }
+            };
+        }
+    }
+
+    // Not authenticated - redirect to login
+    redirect_to_login().into_response()
+}
+
+/// Download one day's entry with its prompts (GET /journal/:date/download?format=md|txt|json)
+async fn download_day(
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Path(date): Path<String>,
+    Query(query): Query<DownloadQuery>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::ReadEntries) {
+        return rejection.into_response();
+    }
+
+    let cycle_date = match crate::cycle_date::CycleDate::from_string(&date) {
+        Ok(date) => date,
+        Err(_) => return AppError::bad_request(format!("Invalid cycle date '{}'", date)).into_response(),
+    };
+    export_download(&notebook, cycle_date, cycle_date, query.format.as_deref(), &cycle_date.to_string()).await
+}
+
+/// Download the entries of a range of days (GET /journal/download?from=..&to=..&format=md|txt|json)
+async fn download_range(
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Query(query): Query<RangeDownloadQuery>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::ReadEntries) {
+        return rejection.into_response();
+    }
+
+    let (from, to) = match (crate::cycle_date::CycleDate::from_string(&query.from), crate::cycle_date::CycleDate::from_string(&query.to)) {
+        (Ok(from), Ok(to)) => (from, to),
+        _ => return AppError::bad_request("Invalid cycle date in from or to").into_response(),
+    };
+    let days = (to.to_real_date() - from.to_real_date()).num_days() + 1;
+    if days < 1 {
+        return AppError::bad_request("The range ends before it starts").into_response();
+    }
+    if days > crate::export::MAX_RANGE_DAYS {
+        return AppError::bad_request(format!("Download at most {} days at a time", crate::export::MAX_RANGE_DAYS)).into_response();
+    }
+    export_download(&notebook, from, to, query.format.as_deref(), &format!("{}-{}", from, to)).await
+}
+
+/// The days' entries in the asked-for format, as an attachment named after the notebook and `label`
+async fn export_download(
+    notebook: &crate::notebook::Notebook,
+    from: crate::cycle_date::CycleDate,
+    to: crate::cycle_date::CycleDate,
+    format: Option<&str>,
+    label: &str,
+) -> Response {
+    let format: crate::export::ExportFormat = match format.map(str::parse).transpose() {
+        Ok(format) => format.unwrap_or_default(),
+        Err(e) => return AppError::bad_request(e).into_response(),
+    };
+    let days = match crate::export::collect(&notebook.journal_manager, from, to).await.map_err(|e| e.to_string()) {
+        Ok(days) => days,
+        Err(e) => return AppError::internal("Error collecting entries", e).into_response(),
+    };
+    if days.is_empty() {
+        return AppError::not_found(if from == to {
+            format!("There's no entry for {}", from)
+        } else {
+            format!("There are no entries from {} to {}", from, to)
+        }).into_response();
+    }
+
+    match crate::export::render(&days, format) {
+        Ok(body) => (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, format.content_type().to_string()),
+                (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}-{}.{}\"", notebook.name, label, format.extension())),
+            ],
+            body,
+        ).into_response(),
+        Err(e) => AppError::internal("Error writing the download", e).into_response(),
+    }
+}
+
+/// Download a compiled year-in-review book as Markdown or PDF (GET /year-review)
+async fn download_year_review(
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Query(query): Query<YearReviewQuery>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::ReadEntries) {
+        return rejection.into_response();
+    }
+
+    let Some(review) = crate::year_review::find(&notebook.journal_manager, query.year) else {
+        return AppError::not_found(format!("Year {:02} hasn't been reviewed yet", query.year)).into_response();
+    };
+    let (path, content_type, extension) = match query.format.as_deref().unwrap_or("md") {
+        "md" => (review.markdown, "text/markdown; charset=utf-8", "md"),
+        "pdf" => match review.pdf {
+            Some(pdf) => (pdf, "application/pdf", "pdf"),
+            None => return AppError::not_found("There's no PDF of this review; set year_review.pdf_command to make one").into_response(),
+        },
+        other => return AppError::bad_request(format!("Unknown format '{}'", other)).into_response(),
+    };
+
+    match tokio::fs::read(&path).await {
+        Ok(bytes) => (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, content_type.to_string()),
+                (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}-year-{:02}-review.{}\"", notebook.name, query.year, extension)),
+            ],
+            bytes,
+        ).into_response(),
+        Err(e) => {
+            AppError::internal("Error reading the review", format!("{}: {}", path.display(), e)).into_response()
+        }
+    }
+}
+
+/// Compile a year-in-review book in the background (POST /year-review)
+async fn compile_year_review(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    headers: HeaderMap,
+    Form(form): Form<YearReviewForm>,
+) -> Response {
+    // Extract token from cookie
+    let token = extract_session_token(&headers);
+
+    // Check if authenticated
+    if let Some(token) = token {
+        if app_state.auth_manager.validate_session(&token).await {
+            let calendar = crate::cycle_date::calendar();
+            if form.year >= calendar.year_of(crate::cycle_date::current_day()) || calendar.year_bounds(form.year).is_none() {
+                return AppError::bad_request("Only finished years can be reviewed").into_response();
+            }
+            let Some(prompt_generator) = notebook.prompt_generator.clone() else {
+                return AppError::unavailable("Prompt generation is not available").into_response();
+            };
+
+            // Writing a chapter per month takes a while; the book appears on the stats page when done
+            let year = form.year;
+            tokio::spawn(async move {
+                let _model = crate::scheduler::lock_model("Year review").await;
+                match prompt_generator.compile_year_review(year).await.map_err(|e| e.to_string()) {
+                    Ok(review) => tracing::info!("Compiled the year {:02} review to {}", year, review.markdown.display()),
+                    Err(e) => tracing::error!("Failed to compile the year {:02} review: {}", year, e),
+                }
+            });
+            return (StatusCode::SEE_OTHER, [("Location", app_url("/stats"))], Html("Compiling the review")).into_response();
+        }
+    }
+
+    // Not authenticated - redirect to login
+    redirect_to_login().into_response()
+}
+
+/// Download the prompts answered during a year as a Markdown "question book" (GET /question-book)
+async fn download_question_book(
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Query(query): Query<QuestionBookQuery>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::ReadEntries) {
+        return rejection.into_response();
+    }
+
+    let calendar = crate::cycle_date::calendar();
+    if calendar.year_bounds(query.year).is_none() {
+        return AppError::not_found(format!("Year {:02} is outside the journal's dates", query.year)).into_response();
+    }
+    let questions = crate::question_book::gather(&notebook.journal_manager, calendar, query.year).await.map_err(|e| e.to_string());
+    match questions {
+        Ok(questions) => (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, "text/markdown; charset=utf-8".to_string()),
+                (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}-year-{:02}-questions.md\"", notebook.name, query.year)),
+            ],
+            crate::question_book::render(calendar, query.year, &questions),
+        ).into_response(),
+        Err(e) => {
+            AppError::internal("Error building the question book", format!("year {:02}: {}", query.year, e)).into_response()
+        }
+    }
+}
+
+/// Calendar heatmap of journaling activity, shaded by word count
+async fn calendar_page(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    headers: HeaderMap,
+    Query(params): Query<CalendarQuery>,
+) -> Response {
+    // Extract token from cookie
+    let token = extract_session_token(&headers);
+
+    // Check if authenticated
+    if let Some(token) = token {
+        if app_state.auth_manager.validate_session(&token).await {
+            use chrono::Datelike;
+
+            let today = crate::cycle_date::CycleDate::today().to_real_date();
+            let year = params.year.unwrap_or(today.year());
+            let year_view = params.year.is_some() && params.month.is_none();
+            let month = params.month.unwrap_or(if params.year.is_some() { 1 } else { today.month() });
+            if !(1..=12).contains(&month) {
+                return AppError::bad_request("Invalid month").into_response();
+            }
+
+            let months: Vec<(i32, u32)> = if year_view {
+                (1..=12).map(|m| (year, m)).collect()
+            } else {
+                vec![(year, month)]
+            };
+            let (Some(from), Some(to)) = (
+                chrono::NaiveDate::from_ymd_opt(year, months[0].1, 1),
+                chrono::NaiveDate::from_ymd_opt(year, months[months.len() - 1].1, 1)
+                    .and_then(|d| d.checked_add_months(chrono::Months::new(1)))
+                    .and_then(|d| d.pred_opt()),
+            ) else {
+                return AppError::bad_request("Invalid date").into_response();
+            };
+
+            let filter = crate::journal::EntryFilter {
+                from: Some(crate::cycle_date::CycleDate::from_real_date(from)),
+                to: Some(crate::cycle_date::CycleDate::from_real_date(to)),
+                ..Default::default()
+            };
+            let listings = match notebook.journal_manager.list_entries(&filter).await.map_err(|e| e.to_string()) {
+                Ok(listings) => listings,
+                Err(e) => {
+                    return AppError::internal("Error loading calendar", e).into_response();
+                }
+            };
+            let chapters = crate::chapters::load(notebook.journal_manager.base_path());
+            let months = crate::calendar::build_months(&months, &listings, &chapters);
+            let chapter_labels: Vec<String> = crate::chapters::chapters_between(&chapters, from, to)
+                .into_iter()
+                .map(|chapter| match crate::chapters::chapter_end(&chapters, chapter) {
+                    Some(end) => format!("{} ({} – {})", chapter.name, crate::locale::date(chapter.start), crate::locale::date(end.pred_opt().unwrap_or(end))),
+                    None => format!("{} (since {})", chapter.name, crate::locale::date(chapter.start)),
+                })
+                .collect();
+
+            let template = if year_view {
+                CalendarTemplate {
+                    title: year.to_string(),
+                    months,
+                    chapters: chapter_labels,
+                    prev_url: app_url(&format!("/calendar?year={}", year - 1)),
+                    next_url: app_url(&format!("/calendar?year={}", year + 1)),
+                    toggle_url: app_url(&format!("/calendar?year={}&month={}", year, if year == today.year() { today.month() } else { 1 })),
+                    toggle_label: "Month view".to_string(),
+                }
+            } else {
+                let (prev_year, prev_month) = if month == 1 { (year - 1, 12) } else { (year, month - 1) };
+                let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+                CalendarTemplate {
+                    title: months.first().map(|m| m.title.clone()).unwrap_or_default(),
+                    months,
+                    chapters: chapter_labels,
+                    prev_url: app_url(&format!("/calendar?year={}&month={}", prev_year, prev_month)),
+                    next_url: app_url(&format!("/calendar?year={}&month={}", next_year, next_month)),
+                    toggle_url: app_url(&format!("/calendar?year={}", year)),
+                    toggle_label: "Year view".to_string(),
+                }
+            };
+
+            return match template.render() {
+                Ok(html) => Html(html).into_response(),
+                Err(e) => {
+                    AppError::internal("Error rendering page", e).into_response()
+                }
+            };
+        }
+    }
+
+    // Not authenticated - redirect to login
+    redirect_to_login().into_response()
+}
+
+/// The structured status, editable by section, with the changes recent entries made to it
+async fn status_page(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    headers: HeaderMap,
+) -> Response {
+    // Extract token from cookie
+    let token = extract_session_token(&headers);
+
+    // Check if authenticated
+    if let Some(token) = token {
+        if app_state.auth_manager.validate_session(&token).await {
+            use crate::status::StructuredStatus;
+
+            let status = notebook.personalization_config.current().current_status();
+            let sections = crate::status::SECTIONS
+                .iter()
+                .map(|(key, label)| StatusSection {
+                    key,
+                    label,
+                    text: status.section(key).unwrap_or_default().to_string(),
+                })
+                .collect();
+
+            // Each day's status file holds the full status after that entry; diff consecutive ones
+            let journal_manager = &notebook.journal_manager;
+            let statuses = journal_manager.list_statuses().await.map_err(|e| e.to_string());
+            let mut history = Vec::new();
+            let mut previous = StructuredStatus::default();
+            for day in statuses.unwrap_or_default() {
+                let changes = day.status.changes_since(&previous);
+                if !changes.is_empty() {
+                    history.push(StatusHistoryDay {
+                        cycle_date: day.cycle_date.to_string(),
+                        real_date: crate::locale::date(day.cycle_date.to_real_date()),
+                        changes,
+                    });
+                }
+                previous = day.status;
+            }
+            history.reverse();
+            history.truncate(STATUS_HISTORY_DAYS);
+
+            let template = StatusTemplate { sections, history };
+            return match template.render() {
+                Ok(html) => Html(html).into_response(),
+                Err(e) => {
+                    AppError::internal("Error rendering page", e).into_response()
+                }
+            };
+        }
+    }
+
+    // Not authenticated - redirect to login
+    redirect_to_login().into_response()
+}
+
+/// Save the status edited on the status page
+async fn save_status_form(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    headers: HeaderMap,
+    Form(status): Form<crate::status::StructuredStatus>,
+) -> Response {
+    // Extract token from cookie
+    let token = extract_session_token(&headers);
+
+    // Check if authenticated
+    if let Some(token) = token {
+        if app_state.auth_manager.validate_session(&token).await {
+            let base_path = notebook.journal_manager.base_path();
+            let operation = format!("Edit status in {}", notebook.name);
+            if let Err(e) = crate::snapshot::before(&app_state.config, operation, vec![base_path.join("status.toml")]).await {
+                return AppError::internal("Could not snapshot the status; nothing was changed", e).into_response();
+            }
+            let result = status.save(base_path).map_err(|e| e.to_string());
+            return match result {
+                Ok(()) => {
+                    notebook.journal_manager.record_change("Edit status".to_string()).await;
+                    tracing::info!("Status edited for notebook {}", notebook.name);
+                    (StatusCode::SEE_OTHER, [("Location", app_url("/status"))], Html("Status saved")).into_response()
+                }
+                Err(e) => {
+                    AppError::internal("Error saving status", e).into_response()
+                }
+            };
+        }
+    }
+
+    // Not authenticated - redirect to login
+    redirect_to_login().into_response()
+}
+
+/// Form acting on a suggested chapter boundary
+#[derive(Deserialize)]
+pub struct ChapterSuggestionForm {
+    pub cycle_date: String,
+    /// Name for the new chapter (accepting only)
+    pub name: Option<String>,
+}
+
+/// Start a chapter on a suggested boundary
+async fn accept_chapter_suggestion(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    headers: HeaderMap,
+    Form(form): Form<ChapterSuggestionForm>,
+) -> Response {
+    // Extract token from cookie
+    let token = extract_session_token(&headers);
+
+    // Check if authenticated
+    if let Some(token) = token {
+        if app_state.auth_manager.validate_session(&token).await {
+            let Ok(cycle_date) = crate::cycle_date::CycleDate::from_string(&form.cycle_date) else {
+                return AppError::bad_request("Invalid cycle date").into_response();
+            };
+            let Some(name) = form.name.as_deref().map(str::trim).filter(|n| !n.is_empty()) else {
+                return AppError::bad_request("A chapter needs a name").into_response();
+            };
+
+            let chapter = crate::chapters::Chapter {
+                name: name.to_string(),
+                start: cycle_date.to_real_date(),
+                description: None,
+            };
+            return match crate::chapters::append(notebook.journal_manager.base_path(), &chapter) {
+                Ok(()) => {
+                    notebook.journal_manager.record_change(format!("Start chapter \"{}\"", chapter.name)).await;
+                    tracing::info!("Started chapter \"{}\" on {} in {}", chapter.name, cycle_date, notebook.name);
+                    (StatusCode::SEE_OTHER, [("Location", app_url("/stats"))], Html("Chapter added")).into_response()
+                }
+                Err(e) => {
+                    AppError::internal("Error adding chapter", e).into_response()
+                }
+            };
+        }
+    }
+
+    // Not authenticated - redirect to login
+    redirect_to_login().into_response()
+}
+
+/// Stop suggesting a chapter boundary
+async fn dismiss_chapter_suggestion(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    headers: HeaderMap,
+    Form(form): Form<ChapterSuggestionForm>,
+) -> Response {
+    // Extract token from cookie
+    let token = extract_session_token(&headers);
+
+    // Check if authenticated
+    if let Some(token) = token {
+        if app_state.auth_manager.validate_session(&token).await {
+            let Ok(cycle_date) = crate::cycle_date::CycleDate::from_string(&form.cycle_date) else {
+                return AppError::bad_request("Invalid cycle date").into_response();
+            };
+            let result = crate::chapter_suggestions::dismiss(notebook.journal_manager.base_path(), &cycle_date).await.map_err(|e| e.to_string());
+            return match result {
+                Ok(()) => (StatusCode::SEE_OTHER, [("Location", app_url("/stats"))], Html("Suggestion dismissed")).into_response(),
+                Err(e) => {
+                    AppError::internal("Error dismissing suggestion", e).into_response()
+                }
+            };
+        }
+    }
+
+    // Not authenticated - redirect to login
+    redirect_to_login().into_response()
+}
+
+/// Admin page with links to processing reports
+async fn admin_page(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    headers: HeaderMap,
+) -> Response {
+    // Extract token from cookie
+    let token = extract_session_token(&headers);
+
+    // Check if authenticated
+    if let Some(token) = token {
+        if app_state.auth_manager.validate_session(&token).await {
+            let reports = match crate::report::list_reports(&notebook.journal_manager).await.map_err(|e| e.to_string()) {
+                Ok(reports) => reports,
+                Err(e) => {
+                    tracing::error!("Failed to list processing reports: {}", e);
+                    Vec::new()
+                }
+            };
+
+            let backup_config = &app_state.config.backup;
+            let backups = match crate::backup::list_backups(std::path::Path::new(&backup_config.destination)) {
+                Ok(archives) => archives
+                    .iter()
+                    .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+                    .collect(),
+                Err(e) => {
+                    tracing::error!("Failed to list backups: {}", e);
+                    Vec::new()
+                }
+            };
+
+            let snapshots = match crate::snapshot::list(&app_state.config) {
+                Ok(snapshots) => snapshots,
+                Err(e) => {
+                    tracing::error!("Failed to list snapshots: {}", e);
+                    Vec::new()
+                }
+            };
+
+            let template = AdminTemplate {
+                reports,
+                backups,
+                snapshots,
+                jobs: app_state.jobs.list(),
+                runs: app_state.prompt_jobs.list(&[ModelTask::Processing, ModelTask::Summaries, ModelTask::Prompts], &notebook.name),
+                notebook: notebook.name.clone(),
+                backup_enabled: backup_config.enabled,
+                backup_time: backup_config.time.clone(),
+                backup_destination: backup_config.destination.clone(),
+            };
+            return match template.render() {
+                Ok(html) => Html(html).into_response(),
+                Err(e) => {
+                    AppError::internal("Error rendering page", e).into_response()
+                }
+            };
+        }
+    }
+
+    // Not authenticated - redirect to login
+    redirect_to_login().into_response()
+}
+
+/// Create a backup now (POST /admin/backup)
+async fn trigger_backup(State(app_state): State<AppState>, auth: AuthContext) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+
+    // Large journals take a while to archive; run it as a job and show progress on the admin page
+    match app_state.jobs.start_export().await {
+        Ok(job) => {
+            tracing::info!("Manual backup started by {} as job {}", auth.label(), job.id);
+            Redirect::to(&app_url("/admin")).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Manual backup failed to start: {}", e);
+            AppError::conflict(format!("Backup failed to start: {}", e)).into_response()
+        }
+    }
+}
+
+/// Start restoring a backup from the admin page
+async fn start_import_form(
+    State(app_state): State<AppState>,
+    auth: AuthContext,
+    Form(form): Form<ImportRequest>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+
+    match app_state.jobs.start_import(&form.archive).await {
+        Ok(job) => {
+            tracing::info!("Import of {} started by {} as job {}", form.archive, auth.label(), job.id);
+            Redirect::to(&app_url("/admin")).into_response()
+        }
+        Err(e) => AppError::conflict(format!("Import failed to start: {}", e)).into_response(),
+    }
+}
+
+/// Cancel a running job from the admin page
+async fn cancel_job_form(
+    State(app_state): State<AppState>,
+    auth: AuthContext,
+    Form(form): Form<JobRequest>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+
+    match app_state.jobs.cancel(&form.id) {
+        Ok(()) => Redirect::to(&app_url("/admin")).into_response(),
+        Err(e) => AppError::conflict(e).into_response(),
+    }
+}
+
+/// Resume a cancelled or failed job from the admin page
+async fn resume_job_form(
+    State(app_state): State<AppState>,
+    auth: AuthContext,
+    Form(form): Form<JobRequest>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+
+    match app_state.jobs.resume(&form.id) {
+        Ok(_) => Redirect::to(&app_url("/admin")).into_response(),
+        Err(e) => AppError::conflict(e).into_response(),
+    }
+}
+
+/// Roll back the files an operation changed to their snapshot
+async fn rollback_snapshot(
+    State(app_state): State<AppState>,
+    auth: AuthContext,
+    Form(form): Form<RollbackForm>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+
+    let config = app_state.config.as_ref().clone();
+    let id = form.id.clone();
+    let result = tokio::task::spawn_blocking(move || crate::snapshot::rollback(&config, &id).map_err(|e| e.to_string()))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|r| r);
+    match result {
+        Ok(undo_id) => {
+            tracing::info!("Snapshot {} rolled back by {} (undo with snapshot {})", form.id, auth.label(), undo_id);
+            for notebook in app_state.notebooks.iter() {
+                if let Err(e) = notebook.journal_manager.rebuild_index().await {
+                    tracing::warn!("Failed to reindex notebook {} after rollback: {}", notebook.name, e);
+                }
+                notebook.journal_manager.record_change(format!("Roll back snapshot {}", form.id)).await;
+            }
+            Redirect::to(&app_url("/admin")).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Rollback of snapshot {} failed: {}", form.id, e);
+            AppError::bad_request(format!("Rollback failed: {}", e)).into_response()
+        }
+    }
+}
+
+/// Check the active notebook's journal directory for problems (GET /admin/doctor)
+async fn journal_doctor(
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+
+    let result = crate::doctor::check(&notebook.journal_manager).await.map_err(|e| e.to_string());
+    match result {
+        Ok(report) => doctor_response(&notebook.name, report, false, &headers),
+        Err(e) => {
+            AppError::internal("Journal check failed", e).into_response()
+        }
+    }
+}
+
+/// Fix what the journal check can fix (POST /admin/doctor)
+async fn repair_journal(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+
+    let result = crate::doctor::repair(&app_state.config, &notebook.journal_manager).await.map_err(|e| e.to_string());
+    match result {
+        Ok(report) => {
+            tracing::info!("Journal {} repaired by {}: {} issues fixed", notebook.name, auth.label(), report.fixed.len());
+            doctor_response(&notebook.name, report, true, &headers)
+        }
+        Err(e) => {
+            AppError::internal("Journal repair failed", e).into_response()
+        }
+    }
+}
+
+/// A doctor report as JSON for API clients, or the report page
+fn doctor_response(notebook: &str, report: crate::doctor::DoctorReport, repaired: bool, headers: &HeaderMap) -> Response {
+    if wants_json(headers) {
+        return json_response(StatusCode::OK, &report);
+    }
+    let template = DoctorTemplate {
+        notebook: notebook.to_string(),
+        report,
+        repaired,
+    };
+    match template.render() {
+        Ok(html) => Html(html).into_response(),
+        Err(e) => {
+            AppError::internal("Error rendering page", e).into_response()
+        }
+    }
+}
+
+/// List the generated artifacts past their retention period (GET /admin/retention)
+async fn preview_retention(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+
+    let today = crate::cycle_date::CycleDate::today();
+    let result = crate::retention::enforce(&app_state.config, &notebook.journal_manager, &today, true).await.map_err(|e| e.to_string());
+    match result {
+        Ok(report) => retention_response(&app_state.config, &notebook.name, report, &headers),
+        Err(e) => {
+            AppError::internal("Retention preview failed", e).into_response()
+        }
+    }
+}
+
+/// Remove the generated artifacts past their retention period now (POST /admin/retention)
+async fn run_retention(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+
+    let today = crate::cycle_date::CycleDate::today();
+    let result = crate::retention::enforce(&app_state.config, &notebook.journal_manager, &today, false).await.map_err(|e| e.to_string());
+    match result {
+        Ok(report) => {
+            tracing::info!("Retention cleanup of {} run by {}: {} artifacts removed", notebook.name, auth.label(), report.expired.len());
+            retention_response(&app_state.config, &notebook.name, report, &headers)
+        }
+        Err(e) => {
+            AppError::internal("Retention cleanup failed", e).into_response()
+        }
+    }
+}
+
+/// Request counts and latencies by route from the access log, slowest first (GET /admin/requests)
+async fn request_metrics_page(auth: AuthContext, headers: HeaderMap) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+
+    let log = crate::access_log::access_log();
+    let records = match log {
+        Some(log) => crate::access_log::load(log.path()).await,
+        None => Vec::new(),
+    };
+    let routes = crate::access_log::summarize(&records);
+    if wants_json(&headers) {
+        return json_response(StatusCode::OK, &serde_json::json!({ "requests": records.len(), "routes": routes }));
+    }
+    let template = RequestsTemplate {
+        log_file: log.map(|log| log.path().display().to_string()),
+        requests: records.len(),
+        since: records.first().map(|record| record.at.format("%Y-%m-%d %H:%M").to_string()),
+        routes,
+    };
+    match template.render() {
+        Ok(html) => Html(html).into_response(),
+        Err(e) => AppError::internal("Error rendering page", e).into_response(),
+    }
+}
+
+/// A retention report as JSON for API clients, or the cleanup page
+fn retention_response(config: &crate::config::Config, notebook: &str, report: crate::retention::RetentionReport, headers: &HeaderMap) -> Response {
+    if wants_json(headers) {
+        return json_response(StatusCode::OK, &report);
+    }
+    let template = RetentionTemplate {
+        notebook: notebook.to_string(),
+        policy: config.retention.clone(),
+        report,
+    };
+    match template.render() {
+        Ok(html) => Html(html).into_response(),
+        Err(e) => {
+            AppError::internal("Error rendering page", e).into_response()
+        }
+    }
+}
+
+/// Show a processing report as plain Markdown text
+async fn view_report(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    headers: HeaderMap,
+    Query(params): Query<ReportQuery>,
+) -> Response {
+    // Extract token from cookie
+    let token = extract_session_token(&headers);
+
+    // Check if authenticated
+    if let Some(token) = token {
+        if app_state.auth_manager.validate_session(&token).await {
+            let report = crate::report::load_report(&notebook.journal_manager, &params.file).await.map_err(|e| e.to_string());
+            return match report {
+                Ok(Some(text)) => (
+                    [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+                    text,
+                ).into_response(),
+                Ok(None) => AppError::not_found("Report not found").into_response(),
+                Err(e) => {
+                    AppError::internal("Error loading report", e).into_response()
+                }
+            };
+        }
+    }
+
+    // Not authenticated - redirect to login
+    redirect_to_login().into_response()
+}
+
+/// A past entry brought back up (GET /api/memory-lane); `null` when there are none yet
+async fn memory_lane_api(
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Query(query): Query<MemoryLaneQuery>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::ReadEntries) {
+        return rejection.into_response();
+    }
+
+    let today = crate::cycle_date::CycleDate::today();
+    let memory = if query.shuffle {
+        crate::memory_lane::shuffle(&notebook.journal_manager, &today).await
+    } else {
+        crate::memory_lane::of_the_day(&notebook.journal_manager, &today).await
+    };
+    match memory.map_err(|e| e.to_string()) {
+        Ok(memory) => json_response(StatusCode::OK, &memory),
+        Err(e) => {
+            AppError::internal("Error picking a memory", e).into_response()
+        }
+    }
+}
+
+/// Convert a date between Gregorian and cycle form (GET /api/date/convert?gregorian=2025-03-02,
+/// or ?cycle=03B25), so clients needn't reimplement the calendar. No journal data is read,
+/// so a prompts-only display key may use it.
+async fn convert_date_api(auth: AuthContext, Query(query): Query<DateConvertQuery>) -> Response {
+    if let Err(rejection) = auth.require(Scope::ReadPrompts) {
+        return rejection.into_response();
+    }
+
+    let cycle_date = match (query.gregorian, query.cycle) {
+        (Some(date), None) => crate::cycle_date::CycleDate::try_from_real_date(date).map_err(|e| e.to_string()),
+        (None, Some(name)) => crate::cycle_date::CycleDate::from_string(&name),
+        _ => Err("Give exactly one of gregorian or cycle".to_string()),
+    };
+    match cycle_date {
+        Ok(cycle_date) => json_response(StatusCode::OK, &DateConversion::of(&cycle_date)),
+        Err(e) => AppError::bad_request(e).into_response(),
+    }
+}
+
+/// Words written per day over the last cycle year, for activity heatmaps (GET /api/heatmap)
+async fn heatmap_api(
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Query(query): Query<HeatmapQuery>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::ReadEntries) {
+        return rejection.into_response();
+    }
+
+    let to = query.to.unwrap_or_else(crate::cycle_date::current_day);
+    let filter = crate::journal::EntryFilter {
+        from: Some(crate::cycle_date::CycleDate::from_real_date(to - chrono::Duration::days(crate::calendar::HEATMAP_DAYS))),
+        to: Some(crate::cycle_date::CycleDate::from_real_date(to)),
+        ..Default::default()
+    };
+    match notebook.journal_manager.list_entries(&filter).await.map_err(|e| e.to_string()) {
+        Ok(listings) => json_response(StatusCode::OK, &crate::calendar::heatmap(&listings, to)),
+        Err(e) => {
+            AppError::internal("Error loading activity", e).into_response()
+        }
+    }
+}
+
+/// Writing statistics as JSON (GET /api/stats)
+async fn stats_api(State(app_state): State<AppState>, ActiveNotebook(notebook): ActiveNotebook, auth: AuthContext) -> Response {
+    if let Err(rejection) = auth.require(Scope::ReadEntries) {
+        return rejection.into_response();
+    }
+
+    match load_all_stats(&notebook.journal_manager, &app_state.config).await {
+        Ok(stats) => json_response(StatusCode::OK, &stats),
+        Err(e) => {
+            AppError::internal("Error computing statistics", e).into_response()
+        }
+    }
+}
+
+/// Prompt answer-rate report (GET /api/stats/prompts)
+async fn prompt_analytics_api(ActiveNotebook(notebook): ActiveNotebook, auth: AuthContext) -> Response {
+    if let Err(rejection) = auth.require(Scope::ReadEntries) {
+        return rejection.into_response();
+    }
+
+    let analytics = crate::analytics::prompt_analytics(&notebook.journal_manager).await.map_err(|e| e.to_string());
+    match analytics {
+        Ok(analytics) => json_response(StatusCode::OK, &analytics),
+        Err(e) => {
+            AppError::internal("Error computing prompt analytics", e).into_response()
+        }
+    }
+}
+
+/// Request body for creating an API key
+#[derive(Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    pub scopes: Vec<Scope>,
+}
+
+/// Request body for revoking an API key
+#[derive(Deserialize)]
+pub struct RevokeApiKeyRequest {
+    pub id: String,
+}
+
+/// API key as listed to admins (the secret itself is only shown once, on creation)
+#[derive(Serialize)]
+pub struct ApiKeySummary {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub last_used: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// List API keys (GET /api/keys)
+async fn list_api_keys(State(app_state): State<AppState>, auth: AuthContext) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+
+    let keys: Vec<ApiKeySummary> = app_state
+        .auth_manager
+        .list_api_keys()
+        .await
+        .into_iter()
+        .map(|k| ApiKeySummary {
+            id: k.id,
+            name: k.name,
+            scopes: k.scopes,
+            created_at: k.created_at,
+            last_used: k.last_used,
+        })
+        .collect();
+
+    json_response(StatusCode::OK, &keys)
+}
+
+/// Create an API key (POST /api/keys), returning the new key including its secret
+async fn create_api_key(
+    State(app_state): State<AppState>,
+    auth: AuthContext,
+    Json(request): Json<CreateApiKeyRequest>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+
+    let name = request.name.trim();
+    if name.is_empty() || request.scopes.is_empty() {
+        return AppError::bad_request("An API key needs a name and at least one scope").into_response();
+    }
+
+    let api_key = app_state.auth_manager.create_api_key(name.to_string(), request.scopes).await;
+    app_state.auth_manager.save_sessions_to_file(&app_state.tokens_file_manager).await;
+    tracing::info!("API key '{}' created by {}", api_key.name, auth.label());
+
+    json_response(StatusCode::CREATED, &api_key)
+}
+
+/// Revoke an API key (POST /api/keys/revoke)
+async fn revoke_api_key(
+    State(app_state): State<AppState>,
+    auth: AuthContext,
+    Json(request): Json<RevokeApiKeyRequest>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+
+    if !app_state.auth_manager.revoke_api_key(&request.id).await {
+        return AppError::not_found("API key not found").into_response();
+    }
+    app_state.auth_manager.save_sessions_to_file(&app_state.tokens_file_manager).await;
+    tracing::info!("API key {} revoked by {}", request.id, auth.label());
+
+    StatusCode::NO_CONTENT.into_response()
+}
+
+/// Generic prompt of the day for embedding on other sites (GET /guest/prompt).
+/// Needs no login, so it only exists when `guest.enabled` is set and is rate limited per
+/// client address (as a trusted proxy reports it).
+async fn guest_prompt(
+    State(app_state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<std::net::SocketAddr>,
+    headers: HeaderMap,
+    Query(params): Query<GuestPromptQuery>,
+) -> Response {
+    let guest = &app_state.guest;
+    if !guest.enabled() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let server = &app_state.config.server;
+    let client = crate::rate_limit::client_address(peer.ip(), &headers, &server.trusted_proxies, &server.forwarded_header);
+    if !guest.allow(client) {
+        return (StatusCode::TOO_MANY_REQUESTS, [(header::RETRY_AFTER, "60")], "Too many requests").into_response();
+    }
+
+    let result = guest.today().await.map_err(|e| e.to_string());
+    let mut response = match result {
+        Ok(prompt) if params.format.as_deref() == Some("text") => prompt.prompt.into_response(),
+        Ok(prompt) => json_response(StatusCode::OK, &prompt),
+        Err(e) => {
+            tracing::error!("Failed to generate guest prompt: {}", e);
+            return AppError::unavailable("Today's prompt isn't available yet").into_response();
+        }
+    };
+    // Meant to be fetched from other sites' pages, and the same for everyone all day
+    let headers = response.headers_mut();
+    headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, header::HeaderValue::from_static("*"));
+    headers.insert(header::CACHE_CONTROL, header::HeaderValue::from_static("public, max-age=300"));
+    response
+}
+
+/// Atom feed of recent entries and today's prompts for a feed reader (GET /feed.atom?key=...).
+/// Takes an API key with the read-entries scope, in the query string or the usual headers.
+async fn entry_feed(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    headers: HeaderMap,
+    Query(params): Query<FeedQuery>,
+) -> Response {
+    if let Err(rejection) = require_feed_key(&app_state, &headers, params.key).await {
+        return rejection;
+    }
+
+    let today = crate::cycle_date::CycleDate::today();
+    let items = crate::feed::recent_items(&notebook.journal_manager, &today, crate::feed::FEED_ENTRIES)
+        .await
+        .map_err(|e| e.to_string());
+    let items = match items {
+        Ok(items) => items,
+        Err(e) => {
+            return AppError::internal("Error building feed", e).into_response();
+        }
+    };
+
+    let host = headers.get(header::HOST).and_then(|h| h.to_str().ok()).unwrap_or("localhost");
+    let scheme = headers.get("X-Forwarded-Proto").and_then(|h| h.to_str().ok()).unwrap_or("http");
+    let xml = crate::feed::render_atom(&notebook.name, &format!("{}://{}{}", scheme, host, base_path()), &items);
+    (
+        [
+            (header::CONTENT_TYPE, "application/atom+xml; charset=utf-8"),
+            (header::CACHE_CONTROL, "private, no-cache"),
+        ],
+        xml,
+    ).into_response()
+}
+
+/// Check the API key of a feed-style URL, given in the query string or the usual headers,
+/// which needs the read-entries scope. Returns the key for links back to the server.
+async fn require_feed_key(app_state: &AppState, headers: &HeaderMap, key: Option<String>) -> Result<String, Response> {
+    let key = key.or_else(|| {
+        headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::to_string)
+    });
+    let Some(key) = key else {
+        return Err(AppError::unauthorized("Feed requires an API key").into_response());
+    };
+    let key = key.trim().to_string();
+    match app_state.auth_manager.validate_api_key(&key).await {
+        Some(api_key) if api_key.has_scope(Scope::ReadEntries) => Ok(key),
+        Some(api_key) => Err(AppError::forbidden(format!("API key '{}' lacks the read-entries scope", api_key.name)).into_response()),
+        None => Err(AppError::unauthorized("Invalid API key").into_response()),
+    }
+}
+
+/// Podcast feed of the morning audio digests (GET /digest.rss?key=...). Fetching it speaks
+/// today's digest if it hasn't been yet.
+async fn digest_podcast(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    headers: HeaderMap,
+    Query(params): Query<DigestQuery>,
+) -> Response {
+    let key = match require_feed_key(&app_state, &headers, params.key).await {
+        Ok(key) => key,
+        Err(rejection) => return rejection,
+    };
+    let speech = &app_state.config.speech;
+    if !crate::audio_digest::available(speech) {
+        return AppError::not_found("No speech command is configured").into_response();
+    }
+
+    // Still list earlier digests when today's can't be spoken
+    let today = crate::cycle_date::CycleDate::today();
+    if let Err(e) = crate::audio_digest::today_digest(speech, &notebook.journal_manager, &today).await {
+        tracing::error!("Failed to speak audio digest: {}", e);
+    }
+    let digests = crate::audio_digest::list(speech, &notebook.journal_manager).await;
+
+    let host = headers.get(header::HOST).and_then(|h| h.to_str().ok()).unwrap_or("localhost");
+    let scheme = headers.get("X-Forwarded-Proto").and_then(|h| h.to_str().ok()).unwrap_or("http");
+    let xml = crate::audio_digest::render_podcast(&notebook.name, &format!("{}://{}{}", scheme, host, base_path()), &key, speech, &digests);
+    (
+        [
+            (header::CONTENT_TYPE, "application/rss+xml; charset=utf-8"),
+            (header::CACHE_CONTROL, "private, no-cache"),
+        ],
+        xml,
+    ).into_response()
+}
+
+/// A day's audio digest (GET /digest/audio?key=...&date=...). Without a date it's today's,
+/// spoken on first request, so the URL stays the same for a smart speaker routine.
+async fn digest_audio(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    headers: HeaderMap,
+    Query(params): Query<DigestQuery>,
+) -> Response {
+    if let Err(rejection) = require_feed_key(&app_state, &headers, params.key).await {
+        return rejection;
+    }
+    let speech = &app_state.config.speech;
+    if !crate::audio_digest::available(speech) {
+        return AppError::not_found("No speech command is configured").into_response();
+    }
+
+    let today = crate::cycle_date::CycleDate::today();
+    let cycle_date = match params.date.as_deref().map(crate::cycle_date::CycleDate::from_string) {
+        Some(Ok(date)) => date,
+        Some(Err(_)) => return AppError::bad_request("Invalid cycle date").into_response(),
+        None => today,
+    };
+    let digest = if cycle_date == today {
+        match crate::audio_digest::today_digest(speech, &notebook.journal_manager, &today).await {
+            Ok(digest) => digest,
+            Err(e) => {
+                return AppError::internal("Failed to speak digest", e).into_response();
+            }
+        }
+    } else {
+        match crate::audio_digest::find(speech, &notebook.journal_manager, &cycle_date).await {
+            Some(digest) => digest,
+            None => return AppError::not_found(format!("No digest for {}", cycle_date)).into_response(),
+        }
+    };
+
+    match tokio::fs::read(&digest.path).await {
+        Ok(audio) => (
+            [
+                (header::CONTENT_TYPE, crate::audio_digest::content_type(speech)),
+                (header::CACHE_CONTROL, "private, max-age=300"),
+            ],
+            audio,
+        ).into_response(),
+        Err(e) => {
+            AppError::internal("Error reading digest", e).into_response()
+        }
+    }
+}
+
+/// Inbound email webhook (POST /inbound/email?secret=...): a mail service posts each email
+/// sent to the private address, as JSON or a URL-encoded form, and it's added to the entry
+/// for the day it was sent
+async fn inbound_email(
+    State(app_state): State<AppState>,
+    Query(params): Query<InboundEmailQuery>,
+    request: axum::extract::Request,
+) -> Response {
+    let config = &app_state.config.email_in;
+    if !config.enabled {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let secret = params.secret.or_else(|| {
+        request.headers().get("X-Webhook-Secret").and_then(|v| v.to_str().ok()).map(str::to_string)
+    });
+    if !secret.is_some_and(|s| crate::email_in::secret_matches(config, &s)) {
+        return AppError::unauthorized("Invalid webhook secret").into_response();
+    }
+
+    let is_json = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    let fields = if is_json {
+        match Json::<serde_json::Value>::from_request(request, &()).await {
+            Ok(Json(body)) => crate::email_in::json_fields(&body),
+            Err(rejection) => return rejection.into_response(),
+        }
+    } else {
+        match Form::<std::collections::HashMap<String, String>>::from_request(request, &()).await {
+            Ok(Form(fields)) => fields,
+            Err(rejection) => return rejection.into_response(),
+        }
+    };
+
+    let email = match crate::email_in::InboundEmail::from_fields(&fields) {
+        Ok(email) => email,
+        Err(e) => return AppError::bad_request(e).into_response(),
+    };
+    if !crate::email_in::sender_allowed(config, &email) {
+        tracing::warn!("Ignoring journal email from unlisted sender {}", email.sender_address());
+        // Accept it anyway so the mail service doesn't retry
+        return (StatusCode::OK, "Sender not allowed").into_response();
+    }
+
+    let notebook = match config.notebook.as_deref() {
+        Some(name) => match app_state.notebooks.get(name) {
+            Some(notebook) => notebook,
+            None => return AppError::not_found(format!("Unknown notebook '{}'", name)).into_response(),
+        },
+        None => app_state.notebooks.default_notebook(),
+    };
+    let clock = crate::cycle_date::clock();
+    let cycle_date = match crate::cycle_date::CycleDate::try_from_real_date(clock.day_of(clock.wall_time(email.sent_at))) {
+        Ok(cycle_date) => cycle_date,
+        Err(e) => {
+            tracing::warn!("Rejected journal email: {}", e);
+            return AppError::from_status(StatusCode::UNPROCESSABLE_ENTITY, e.to_string()).into_response();
+        }
+    };
+    if let Err(e) = crate::journal::check_entry_date_editable(&cycle_date, &crate::cycle_date::CycleDate::today(), crate::config::live().journal.max_edit_age_days) {
+        tracing::warn!("Rejected journal email for {}: {}", cycle_date, e);
+        return AppError::from_status(StatusCode::UNPROCESSABLE_ENTITY, e).into_response();
+    }
+
+    let result = notebook.journal_manager.append_to_entry(&cycle_date, &email.entry_text()).await.map_err(|e| e.to_string());
+    match result {
+        Ok(appended) => {
+            tracing::info!("{} entry for {} from email", if appended { "Appended to" } else { "Created" }, cycle_date);
+            json_response(StatusCode::OK, &serde_json::json!({ "cycle_date": cycle_date.to_string(), "appended": appended }))
+        }
+        Err(e) => {
+            AppError::internal("Error saving entry", format!("{}: {}", cycle_date, e)).into_response()
+        }
+    }
+}
+
+/// Export and import jobs, newest first (GET /api/jobs)
+async fn list_jobs_api(State(app_state): State<AppState>, auth: AuthContext) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+
+    json_response(StatusCode::OK, &app_state.jobs.list())
+}
+
+/// Progress of one job (GET /api/jobs/status?id=...)
+async fn job_status_api(
+    State(app_state): State<AppState>,
+    auth: AuthContext,
+    Query(params): Query<JobQuery>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+
+    match app_state.jobs.get(&params.id) {
+        Ok(job) => json_response(StatusCode::OK, &job),
+        Err(e) => AppError::not_found(e).into_response(),
+    }
+}
+
+/// State of one job (GET /api/jobs/:id): a model job for the active notebook, with its
+/// prompt once completed, or an export or import job for admins. Running jobs say when
+/// to poll again.
+async fn job_api(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Path(id): Path<String>,
+) -> Response {
+    if let Some(job) = app_state.prompt_jobs.get(&notebook.name, &id) {
+        if let Err(rejection) = auth.require(Scope::ReadPrompts) {
+            return rejection.into_response();
+        }
+        let mut response = json_response(StatusCode::OK, &job);
+        if !job.is_finished() {
+            response.headers_mut().insert(header::RETRY_AFTER, header::HeaderValue::from(crate::prompt_jobs::POLL_SECONDS));
+        }
+        return response;
+    }
+
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+    match app_state.jobs.get(&id) {
+        Ok(job) => json_response(StatusCode::OK, &job),
+        Err(e) => AppError::not_found(e).into_response(),
+    }
+}
+
+/// Start writing a backup archive; poll the returned job for progress (POST /api/jobs/export)
+async fn start_export_api(State(app_state): State<AppState>, auth: AuthContext) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+
+    match app_state.jobs.start_export().await {
+        Ok(job) => {
+            tracing::info!("Export job {} started by {}", job.id, auth.label());
+            json_response(StatusCode::ACCEPTED, &job)
+        }
+        Err(e) => AppError::conflict(e).into_response(),
+    }
+}
+
+/// Start restoring a backup archive from the backup destination (POST /api/jobs/import)
+async fn start_import_api(
+    State(app_state): State<AppState>,
+    auth: AuthContext,
+    Json(request): Json<ImportRequest>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+
+    match app_state.jobs.start_import(&request.archive).await {
+        Ok(job) => {
+            tracing::info!("Import job {} for {} started by {}", job.id, request.archive, auth.label());
+            json_response(StatusCode::ACCEPTED, &job)
+        }
+        Err(e) => AppError::conflict(e).into_response(),
+    }
+}
+
+/// Stop a running job after its current chunk (POST /api/jobs/cancel)
+async fn cancel_job_api(
+    State(app_state): State<AppState>,
+    auth: AuthContext,
+    Json(request): Json<JobRequest>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+
+    match app_state.jobs.cancel(&request.id) {
+        Ok(()) => StatusCode::ACCEPTED.into_response(),
+        Err(e) => AppError::conflict(e).into_response(),
+    }
+}
+
+/// Continue a cancelled or failed job from its last completed chunk (POST /api/jobs/resume)
+async fn resume_job_api(
+    State(app_state): State<AppState>,
+    auth: AuthContext,
+    Json(request): Json<JobRequest>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+
+    match app_state.jobs.resume(&request.id) {
+        Ok(job) => json_response(StatusCode::ACCEPTED, &job),
+        Err(e) => AppError::conflict(e).into_response(),
+    }
+}
+
+/// Build an entry filter from API query parameters
+fn build_entry_filter(params: &EntryListQuery) -> Result<crate::journal::EntryFilter, String> {
+    Ok(crate::journal::EntryFilter {
+        from: params.from.as_deref().map(parse_date_param).transpose()?,
+        to: params.to.as_deref().map(parse_date_param).transpose()?,
+        entry_type: params.entry_type.as_deref().map(str::parse).transpose()?,
+        has_summary: params.has_summary,
+    })
+}
+
+/// Parse a date query parameter given as a cycle date ("03B25"), Gregorian ("2025-03-02"),
+/// or ISO week date ("2025-W10-7")
+pub(crate) fn parse_date_param(value: &str) -> Result<crate::cycle_date::CycleDate, String> {
+    if value.len() == 10 {
+        crate::cycle_date::CycleDate::try_from_real_date(crate::cycle_date::parse_real_date(value)?).map_err(|e| e.to_string())
+    } else {
+        crate::cycle_date::CycleDate::from_string(value)
+            .map_err(|e| format!("Invalid date '{}': {}", value, e))
+    }
+}
+
+/// Serialize a value as a JSON response with the given status
+fn json_response<T: Serialize>(status: StatusCode, value: &T) -> Response {
+    match serde_json::to_string(value) {
+        Ok(json) => Response::builder()
+            .status(status)
+            .header("Content-Type", "application/json")
+            .body(json.into())
+            .unwrap(),
+        Err(e) => {
+            AppError::internal("Serialization error", e).into_response()
+        }
+    }
+}
+
+/// Whether the client asked for JSON rather than a page (`Accept: application/json`)
+pub(crate) fn wants_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("application/json"))
+}
+
+/// Content type for an attachment based on its extension
+pub(crate) fn attachment_content_type(filename: &str) -> &'static str {
+    let extension = filename.rsplit('.').next().unwrap_or_default().to_ascii_lowercase();
+    match extension.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" | "oga" => "audio/ogg",
+        "m4a" => "audio/mp4",
+        "webm" => "audio/webm",
+        "pdf" => "application/pdf",
+        _ => "application/octet-stream",
     }
+}
 
-    (StatusCode::UNAUTHORIZED, "Unauthorized").into_response()
+/// Broad media category used to pick how an attachment is displayed
+fn media_kind(filename: &str) -> &'static str {
+    let content_type = attachment_content_type(filename);
+    if content_type.starts_with("image/") {
+        "image"
+    } else if content_type.starts_with("audio/") {
+        "audio"
+    } else {
+        "other"
+    }
 }
 
 /// Redirect to login page
-fn redirect_to_login() -> (StatusCode, [(&'static str, &'static str); 1], Html<&'static str>) {
+fn redirect_to_login() -> (StatusCode, [(&'static str, String); 1], Html<&'static str>) {
     (
         StatusCode::TEMPORARY_REDIRECT,
-        [("Location", "/login")],
+        [("Location", app_url("/login"))],
         Html("Redirecting to login..."),
     )
 }
@@ -0,0 +1,137 @@
+//! Health checks for supervisors and uptime monitors: `/healthz` answers whenever the
+//! process is serving requests, `/readyz` only while it can actually keep a journal.
+//! Neither needs a login, and neither says more than which check failed and why.
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde::Serialize;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::scheduler::{Job, Scheduler};
+use crate::AppState;
+
+/// File briefly written to each journal directory to prove it's writable
+const PROBE_FILE: &str = ".readyz";
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/healthz", get(liveness))
+        .route("/readyz", get(readiness))
+}
+
+/// One readiness check's outcome
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub name: String,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Check {
+    fn new(name: impl Into<String>, result: Result<(), String>) -> Self {
+        let name = name.into();
+        match result {
+            Ok(()) => Self { name, ok: true, error: None },
+            Err(e) => Self { name, ok: false, error: Some(e) },
+        }
+    }
+}
+
+/// One check per enabled job, failing when its loop was never started or has ended
+fn scheduler_checks(scheduler: &Scheduler, config: &Config) -> Vec<Check> {
+    Job::ALL
+        .into_iter()
+        .filter(|job| job.enabled(config))
+        .map(|job| {
+            let running = match scheduler.is_running(job) {
+                true => Ok(()),
+                false => Err(format!("The {} job isn't scheduled", job.name())),
+            };
+            Check::new(format!("scheduler:{}", job.name()), running)
+        })
+        .collect()
+}
+
+/// The process is up and answering (GET /healthz)
+async fn liveness() -> Response {
+    Json(serde_json::json!({ "status": "ok", "version": env!("CARGO_PKG_VERSION") })).into_response()
+}
+
+/// Every notebook's directory is writable, saved sessions were loaded, and every scheduled
+/// job is running (GET /readyz); 503 with the failed checks otherwise
+async fn readiness(State(app_state): State<AppState>) -> Response {
+    let mut checks = Vec::new();
+    for notebook in app_state.notebooks.iter() {
+        let writable = check_writable(notebook.journal_manager.base_path()).await;
+        checks.push(Check::new(format!("journal_writable:{}", notebook.name), writable));
+    }
+    checks.extend(scheduler_checks(&app_state.scheduler, &app_state.config));
+    let sessions = match app_state.auth_manager.sessions_loaded() {
+        true => Ok(()),
+        false => Err("Saved sessions couldn't be loaded".to_string()),
+    };
+    checks.push(Check::new("sessions_loaded", sessions));
+
+    let ready = checks.iter().all(|check| check.ok);
+    if !ready {
+        let failed: Vec<&str> = checks.iter().filter(|check| !check.ok).map(|check| check.name.as_str()).collect();
+        tracing::warn!("Readiness check failed: {}", failed.join(", "));
+    }
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    let body = serde_json::json!({ "status": if ready { "ready" } else { "not_ready" }, "checks": checks });
+    (status, Json(body)).into_response()
+}
+
+/// Write and remove a small file in `dir`
+async fn check_writable(dir: &Path) -> Result<(), String> {
+    let probe = dir.join(PROBE_FILE);
+    tokio::fs::write(&probe, b"ok").await.map_err(|e| format!("Journal directory isn't writable: {}", e))?;
+    if let Err(e) = tokio::fs::remove_file(&probe).await {
+        tracing::warn!("Failed to remove readiness probe {}: {}", probe.display(), e);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notebook::Notebooks;
+    use crate::test_support::notebook;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_check_writable() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(check_writable(temp_dir.path()).await.is_ok());
+        assert!(!temp_dir.path().join(PROBE_FILE).exists());
+        assert!(check_writable(&temp_dir.path().join("missing")).await.is_err());
+
+        let failed = serde_json::to_value(Check::new("sessions_loaded", Err("Nope".to_string()))).unwrap();
+        assert_eq!(failed, serde_json::json!({ "name": "sessions_loaded", "ok": false, "error": "Nope" }));
+        let passed = serde_json::to_value(Check::new("sessions_loaded", Ok(()))).unwrap();
+        assert!(passed.get("error").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_checks() {
+        let temp_dir = TempDir::new().unwrap();
+        let notebooks = Arc::new(Notebooks::new(vec![notebook(temp_dir.path())]));
+        let config = Arc::new(Config::default());
+        let scheduler = Scheduler::new(notebooks, Arc::clone(&config));
+        scheduler.track(Job::Processing, tokio::spawn(async {}));
+        scheduler.track(Job::Rollover, tokio::spawn(std::future::pending::<()>()));
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        let failed: Vec<String> =
+            scheduler_checks(&scheduler, &config).into_iter().filter(|check| !check.ok).map(|check| check.name).collect();
+        assert_eq!(failed, vec!["scheduler:processing", "scheduler:retries"]);
+    }
+}
@@ -0,0 +1,350 @@
+//! Managing holidays.txt from the admin page and the API, instead of hand-editing its
+//! pipe-delimited lines. Holidays are addressed by their position among the file's holiday
+//! lines; comments and blank lines are left where they are. Each edit is snapshotted and
+//! reloads the notebook's personalization, so the next prompt sees it.
+
+use askama::Template;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+    routing::{get, post},
+    Form, Router,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::auth::{AuthContext, Scope};
+use crate::config::Config;
+use crate::error::AppError;
+use crate::notebook::{ActiveNotebook, Notebook};
+use crate::personalization::Holiday;
+use crate::AppState;
+
+const HOLIDAYS_FILE: &str = "holidays.txt";
+
+/// Categories offered in the form; others are accepted too
+pub const CATEGORIES: [&str; 8] = ["birthday", "anniversary", "holiday", "religious", "cultural", "personal", "seasonal", "work"];
+
+/// A holiday as entered in the form or sent to the API
+#[derive(Debug, Clone, Deserialize)]
+pub struct HolidayInput {
+    pub name: String,
+    /// YYYY-MM-DD, or MM-DD for a recurring holiday; only the month and day of a recurring
+    /// holiday are kept
+    pub date: String,
+    pub category: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Every year on the same date, rather than once
+    #[serde(default)]
+    pub recurring: bool,
+}
+
+impl HolidayInput {
+    /// Check the input and turn it into a line-ready holiday
+    pub fn into_holiday(self) -> Result<Holiday, String> {
+        let field = |label: &str, value: &str| -> Result<String, String> {
+            let value = value.trim();
+            if value.contains(['|', '\n', '\r']) {
+                return Err(format!("{} can't contain '|' or line breaks", label));
+            }
+            Ok(value.to_string())
+        };
+        let name = field("The name", &self.name)?;
+        if name.is_empty() {
+            return Err("The name can't be empty".to_string());
+        }
+        let category = field("The category", &self.category)?.to_lowercase();
+        if category.is_empty() {
+            return Err("The category can't be empty".to_string());
+        }
+        let description = field("The description", self.description.as_deref().unwrap_or_default())?;
+
+        let date = self.date.trim();
+        let full_date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok();
+        // 2000 is a leap year, so February 29 is accepted
+        let month_day = full_date.or_else(|| NaiveDate::parse_from_str(&format!("2000-{}", date), "%Y-%m-%d").ok());
+        let date = match (self.recurring, full_date, month_day) {
+            (true, _, Some(day)) => day.format("%m-%d").to_string(),
+            (false, Some(day), _) => day.format("%Y-%m-%d").to_string(),
+            (false, None, Some(_)) => return Err("A one-time holiday needs a year: YYYY-MM-DD".to_string()),
+            (_, _, None) => return Err(format!("'{}' isn't a date; use YYYY-MM-DD or MM-DD", date)),
+        };
+
+        Ok(Holiday {
+            name,
+            date,
+            category,
+            description: (!description.is_empty()).then_some(description),
+            recurring: self.recurring,
+        })
+    }
+}
+
+/// A change to holidays.txt
+pub enum HolidayEdit {
+    Add(Holiday),
+    Update(usize, Holiday),
+    Delete(usize),
+}
+
+/// Why an edit didn't happen
+#[derive(Debug)]
+pub enum EditError {
+    /// The input was refused; nothing was written
+    Invalid(String),
+    NotFound(usize),
+    Failed(String),
+}
+
+impl From<EditError> for AppError {
+    fn from(error: EditError) -> Self {
+        match error {
+            EditError::Invalid(message) => AppError::bad_request(message),
+            EditError::NotFound(index) => AppError::not_found(format!("No holiday {}", index)),
+            EditError::Failed(message) => AppError::internal("Error saving holidays", message),
+        }
+    }
+}
+
+/// The holidays in the file's text, in order
+pub fn parse(content: &str) -> Vec<Holiday> {
+    content.lines().filter_map(Holiday::parse_line).collect()
+}
+
+/// Apply an edit to the file's text, keeping its other lines as they are
+fn apply(content: &str, edit: &HolidayEdit) -> Result<String, EditError> {
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    match edit {
+        HolidayEdit::Add(holiday) => lines.push(holiday.to_line()),
+        HolidayEdit::Update(index, _) | HolidayEdit::Delete(index) => {
+            let position = lines
+                .iter()
+                .enumerate()
+                .filter(|(_, line)| Holiday::parse_line(line).is_some())
+                .nth(*index)
+                .map(|(position, _)| position)
+                .ok_or(EditError::NotFound(*index))?;
+            match edit {
+                HolidayEdit::Update(_, holiday) => lines[position] = holiday.to_line(),
+                _ => {
+                    lines.remove(position);
+                }
+            }
+        }
+    }
+    Ok(lines.join("\n") + "\n")
+}
+
+fn path(notebook: &Notebook) -> std::path::PathBuf {
+    notebook.journal_manager.base_path().join(HOLIDAYS_FILE)
+}
+
+/// The notebook's holidays as saved
+pub async fn load(notebook: &Notebook) -> Result<Vec<Holiday>, String> {
+    match tokio::fs::read_to_string(path(notebook)).await {
+        Ok(content) => Ok(parse(&content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("{}: {}", HOLIDAYS_FILE, e)),
+    }
+}
+
+/// Save an edit, snapshotting the file first, then reload the notebook's personalization
+pub async fn save(config: &Config, notebook: &Notebook, edit: HolidayEdit) -> Result<(), EditError> {
+    let path = path(notebook);
+    let content = match tokio::fs::read_to_string(&path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(EditError::Failed(format!("{}: {}", path.display(), e))),
+    };
+    let content = apply(&content, &edit)?;
+
+    let operation = format!("Edit {} in {}", HOLIDAYS_FILE, notebook.name);
+    crate::snapshot::before(config, operation, vec![path.clone()]).await.map_err(EditError::Failed)?;
+    let temporary = path.with_extension("tmp");
+    let written = match tokio::fs::write(&temporary, &content).await {
+        Ok(()) => tokio::fs::rename(&temporary, &path).await,
+        Err(e) => Err(e),
+    };
+    written.map_err(|e| EditError::Failed(format!("{}: {}", path.display(), e)))?;
+
+    notebook.personalization_config
+        .reload(notebook.journal_manager.base_path())
+        .map_err(|e| EditError::Failed(format!("Saved, but reloading failed: {}", e)))?;
+    notebook.journal_manager.record_change(format!("Edit {}", HOLIDAYS_FILE)).await;
+    Ok(())
+}
+
+/// The dated part of the prompt context, exactly as the model is sent it
+pub fn preview(notebook: &Notebook) -> String {
+    notebook.personalization_config.current().get_temporal_context()
+}
+
+/// Holidays with the upcoming-events preview, as the API returns them
+#[derive(Serialize)]
+pub struct HolidayList {
+    pub holidays: Vec<Holiday>,
+    pub preview: String,
+}
+
+/// One row of the holidays page
+pub struct HolidayRow {
+    pub index: usize,
+    pub holiday: Holiday,
+    /// The date for the form's date input; recurring holidays show this year's
+    pub input_date: String,
+}
+
+#[derive(Template)]
+#[template(path = "holidays.html")]
+pub struct HolidaysTemplate {
+    pub notebook: String,
+    pub rows: Vec<HolidayRow>,
+    pub preview: String,
+    pub error: Option<String>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/holidays", get(holidays_page).post(add_holiday))
+        .route("/admin/holidays/:index", post(update_holiday))
+        .route("/admin/holidays/:index/delete", post(delete_holiday))
+}
+
+async fn render(notebook: &Notebook, error: Option<String>) -> Response {
+    let holidays = match load(notebook).await {
+        Ok(holidays) => holidays,
+        Err(e) => return AppError::internal("Error reading holidays", e).into_response(),
+    };
+    let year = crate::cycle_date::current_day().format("%Y").to_string();
+    let rows = holidays
+        .into_iter()
+        .enumerate()
+        .map(|(index, holiday)| HolidayRow {
+            index,
+            input_date: if holiday.recurring { format!("{}-{}", year, holiday.date) } else { holiday.date.clone() },
+            holiday,
+        })
+        .collect();
+    let status = if error.is_some() { StatusCode::BAD_REQUEST } else { StatusCode::OK };
+    let template = HolidaysTemplate {
+        notebook: notebook.name.clone(),
+        rows,
+        preview: preview(notebook),
+        error,
+    };
+    match template.render() {
+        Ok(html) => (status, Html(html)).into_response(),
+        Err(e) => AppError::internal("Error rendering page", e).into_response(),
+    }
+}
+
+/// Save an edit and show the page again, with the problem if it was refused
+async fn save_and_render(app_state: &AppState, notebook: &Notebook, auth: &AuthContext, edit: Result<HolidayEdit, String>) -> Response {
+    let saved = match edit {
+        Ok(edit) => save(&app_state.config, notebook, edit).await,
+        Err(e) => Err(EditError::Invalid(e)),
+    };
+    match saved {
+        Ok(()) => {
+            tracing::info!("Holidays edited in {} by {}", notebook.name, auth.label());
+            (StatusCode::SEE_OTHER, [("Location", crate::handlers::app_url("/admin/holidays"))]).into_response()
+        }
+        Err(EditError::Invalid(message)) => render(notebook, Some(message)).await,
+        Err(e) => AppError::from(e).into_response(),
+    }
+}
+
+/// The holidays, editable, with the upcoming events as the model sees them (GET /admin/holidays)
+async fn holidays_page(ActiveNotebook(notebook): ActiveNotebook, auth: AuthContext) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+    render(&notebook, None).await
+}
+
+/// Add a holiday (POST /admin/holidays)
+async fn add_holiday(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Form(input): Form<HolidayInput>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+    save_and_render(&app_state, &notebook, &auth, input.into_holiday().map(HolidayEdit::Add)).await
+}
+
+/// Change a holiday (POST /admin/holidays/:index)
+async fn update_holiday(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Path(index): Path<usize>,
+    Form(input): Form<HolidayInput>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+    save_and_render(&app_state, &notebook, &auth, input.into_holiday().map(|holiday| HolidayEdit::Update(index, holiday))).await
+}
+
+/// Remove a holiday (POST /admin/holidays/:index/delete)
+async fn delete_holiday(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Path(index): Path<usize>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+    save_and_render(&app_state, &notebook, &auth, Ok(HolidayEdit::Delete(index))).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(name: &str, date: &str, recurring: bool) -> HolidayInput {
+        HolidayInput {
+            name: name.to_string(),
+            date: date.to_string(),
+            category: "Birthday".to_string(),
+            description: None,
+            recurring,
+        }
+    }
+
+    #[test]
+    fn test_holiday_input() {
+        let birthday = input("Mum's birthday", "1961-03-14", true).into_holiday().unwrap();
+        assert_eq!(birthday.to_line(), "03-14|birthday|Mum's birthday");
+        assert!(input("Leap day", "02-29", true).into_holiday().is_ok());
+        assert_eq!(input("Move", "2026-09-01", false).into_holiday().unwrap().date, "2026-09-01");
+
+        assert!(input("Move", "09-01", false).into_holiday().unwrap_err().contains("needs a year"));
+        assert!(input("Move", "2026-13-01", false).into_holiday().unwrap_err().contains("isn't a date"));
+        assert!(input("A|B", "03-14", true).into_holiday().is_err());
+        assert!(input(" ", "03-14", true).into_holiday().is_err());
+    }
+
+    #[test]
+    fn test_apply_keeps_comments() {
+        let content = "# Format: DATE|CATEGORY|NAME\n12-25|religious|Christmas\n\n01-01|holiday|New Year|Fresh start\n";
+        let party = input("Party", "2026-06-01", false).into_holiday().unwrap();
+
+        let added = apply(content, &HolidayEdit::Add(party.clone())).unwrap();
+        assert!(added.ends_with("Fresh start\n2026-06-01|birthday|Party\n"));
+
+        let updated = apply(content, &HolidayEdit::Update(1, party)).unwrap();
+        assert_eq!(updated, "# Format: DATE|CATEGORY|NAME\n12-25|religious|Christmas\n\n2026-06-01|birthday|Party\n");
+
+        let deleted = apply(content, &HolidayEdit::Delete(0)).unwrap();
+        assert_eq!(parse(&deleted).len(), 1);
+        assert!(deleted.starts_with("# Format"));
+        assert!(matches!(apply(content, &HolidayEdit::Delete(2)), Err(EditError::NotFound(2))));
+    }
+}
@@ -0,0 +1,164 @@
+use crate::config::HooksConfig;
+use crate::cycle_date::CycleDate;
+use crate::journal::JournalPrompt;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::time::{timeout, Duration};
+
+/// A command line run through the platform's shell
+pub fn shell_command(command_line: &str) -> Command {
+    if cfg!(target_os = "windows") {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command_line]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command_line]);
+        cmd
+    }
+}
+
+/// A configured command line run through the platform's shell, with each `{placeholder}`
+/// standing for a path. The paths reach the shell as arguments (`"$1"`, `"$2"`, ... for sh)
+/// rather than being pasted into the line, so they need no quoting.
+pub fn shell_command_with_paths(command_line: &str, paths: &[(&str, &Path)]) -> Command {
+    let mut line = command_line.to_string();
+    if cfg!(target_os = "windows") {
+        // cmd has no positional arguments, but expands %variables% the same way
+        let mut cmd = Command::new("cmd");
+        for (i, (placeholder, path)) in paths.iter().enumerate() {
+            let variable = format!("LLM_JOURNAL_PATH_{}", i + 1);
+            line = line.replace(&format!("{{{}}}", placeholder), &format!("\"%{}%\"", variable));
+            cmd.env(variable, path);
+        }
+        cmd.args(["/C", &line]);
+        cmd
+    } else {
+        for (i, (placeholder, _)) in paths.iter().enumerate() {
+            line = line.replace(&format!("{{{}}}", placeholder), &format!("\"${}\"", i + 1));
+        }
+        let mut cmd = Command::new("sh");
+        // The word after the script is $0
+        cmd.args(["-c", &line, "sh"]);
+        for (_, path) in paths {
+            cmd.arg(path);
+        }
+        cmd
+    }
+}
+
+/// Run the configured prompt-ready command for a date's freshly generated prompts.
+/// The prompts are written to the command's stdin separated by blank lines, and are
+/// also exposed as environment variables for scripts that prefer them.
+pub async fn run_prompt_ready_hook(
+    hooks: &HooksConfig,
+    cycle_date: &CycleDate,
+    prompts: &[JournalPrompt],
+) -> Result<(), String> {
+    let Some(command_line) = hooks.prompt_ready_command.as_deref() else {
+        return Ok(());
+    };
+
+    let mut cmd = shell_command(command_line);
+    cmd.env("LLM_JOURNAL_DATE", cycle_date.to_string())
+        .env("LLM_JOURNAL_GREGORIAN_DATE", cycle_date.to_real_date().format("%Y-%m-%d").to_string())
+        .env("LLM_JOURNAL_PROMPT_COUNT", prompts.len().to_string())
+        .stdin(Stdio::piped())
+        .kill_on_drop(true);
+    for prompt in prompts {
+        cmd.env(format!("LLM_JOURNAL_PROMPT_{}", prompt.prompt_number), &prompt.prompt);
+    }
+
+    tracing::info!("Running prompt-ready hook for {}", cycle_date);
+    let mut child = cmd.spawn().map_err(|e| format!("Failed to start prompt-ready hook: {}", e))?;
+
+    let input = prompts.iter().map(|p| p.prompt.as_str()).collect::<Vec<_>>().join("\n\n");
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that ignores stdin may close it early; that's not an error
+        if let Err(e) = stdin.write_all(input.as_bytes()).await {
+            tracing::debug!("Prompt-ready hook did not read stdin: {}", e);
+        }
+    }
+
+    match timeout(Duration::from_secs(hooks.command_timeout_seconds), child.wait()).await {
+        Ok(Ok(status)) if status.success() => {
+            tracing::info!("Prompt-ready hook completed for {}", cycle_date);
+            Ok(())
+        }
+        Ok(Ok(status)) => Err(format!("Prompt-ready hook exited with {}", status)),
+        Ok(Err(e)) => Err(format!("Failed to wait for prompt-ready hook: {}", e)),
+        Err(_) => {
+            let _ = child.kill().await;
+            Err(format!("Prompt-ready hook timed out after {}s", hooks.command_timeout_seconds))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::PromptType;
+    use chrono::Local;
+    use tempfile::TempDir;
+
+    fn sample_prompts(cycle_date: CycleDate) -> Vec<JournalPrompt> {
+        vec![
+            JournalPrompt {
+                cycle_date,
+                prompt: "What gave you energy today?".to_string(),
+                prompt_number: 1,
+                generated_at: Local::now(),
+                prompt_type: PromptType::Daily,
+            },
+            JournalPrompt {
+                cycle_date,
+                prompt: "What drained it?".to_string(),
+                prompt_number: 2,
+                generated_at: Local::now(),
+                prompt_type: PromptType::Daily,
+            },
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_no_command_configured_is_noop() {
+        let cycle_date = CycleDate::new(1, 2, 1, 3).unwrap();
+        let result = run_prompt_ready_hook(&HooksConfig::default(), &cycle_date, &sample_prompts(cycle_date)).await;
+        assert!(result.is_ok());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_command_receives_prompts() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("out.txt");
+        let hooks = HooksConfig {
+            prompt_ready_command: Some(format!(
+                "cat > '{}' && echo \"$LLM_JOURNAL_DATE $LLM_JOURNAL_PROMPT_COUNT\" >> '{}'",
+                output.display(),
+                output.display()
+            )),
+            command_timeout_seconds: 10,
+        };
+        let cycle_date = CycleDate::new(1, 2, 1, 3).unwrap();
+
+        run_prompt_ready_hook(&hooks, &cycle_date, &sample_prompts(cycle_date)).await.unwrap();
+
+        let written = std::fs::read_to_string(&output).unwrap();
+        assert!(written.starts_with("What gave you energy today?\n\nWhat drained it?"));
+        assert!(written.contains("01213 2"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_failing_command_reports_error() {
+        let hooks = HooksConfig {
+            prompt_ready_command: Some("exit 3".to_string()),
+            command_timeout_seconds: 10,
+        };
+        let cycle_date = CycleDate::new(1, 2, 1, 3).unwrap();
+        assert!(run_prompt_ready_hook(&hooks, &cycle_date, &[]).await.is_err());
+    }
+}
@@ -0,0 +1,329 @@
+//! The insights page: charts of recent writing beside a short analysis the model writes of
+//! the last month's summaries, how the status described the writer's mood, and the tags
+//! they used. The analysis is refreshed about weekly by the nightly run, or on request as
+//! a background job, never while the page loads; recent ones are kept in insights.json.
+
+use askama::Template;
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Response},
+    routing::{get, post},
+    Router,
+};
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::auth::{AuthContext, Scope};
+use crate::cycle_date::CycleDate;
+use crate::error::AppError;
+use crate::journal::{EntryFilter, JournalManager};
+use crate::llm_worker::LlmWorker;
+use crate::notebook::ActiveNotebook;
+use crate::prompt_jobs::{ModelTask, PromptJob};
+use crate::prompts::PromptsConfig;
+use crate::AppState;
+
+const INSIGHTS_FILE: &str = "insights.json";
+/// Days of writing an analysis covers, ending today
+pub const WINDOW_DAYS: i64 = 30;
+/// Days before the nightly run writes a fresh analysis
+pub const REFRESH_DAYS: i64 = 7;
+/// Past analyses kept
+const KEEP: usize = 12;
+/// Tags given to the model and charted
+const TOP_TAGS: usize = 12;
+
+/// One analysis, as saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Insight {
+    pub generated_at: DateTime<Local>,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Entries written in the period
+    pub entries: usize,
+    pub analysis: String,
+}
+
+/// What an analysis is written from
+#[derive(Debug, Clone, PartialEq)]
+pub struct InsightMaterial {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub entries: usize,
+    /// Summaries of the period's entries, oldest first
+    pub summaries: Vec<(NaiveDate, String)>,
+    /// The status's mood baseline each time it changed, oldest first
+    pub moods: Vec<(NaiveDate, String)>,
+    /// Tags with the number of entries using them, most used first
+    pub tags: Vec<(String, usize)>,
+}
+
+impl InsightMaterial {
+    /// "September 18 to October 17, 2026"
+    pub fn period(&self) -> String {
+        format!("{} to {}", self.from.format("%B %-d"), self.to.format("%B %-d, %Y"))
+    }
+
+    /// The analysis prompt, with the material filled in
+    pub fn request(&self, prompts: &PromptsConfig) -> String {
+        let dated = |lines: &[(NaiveDate, String)]| -> String {
+            if lines.is_empty() {
+                return "(none)".to_string();
+            }
+            lines.iter().map(|(date, text)| format!("- {}: {}\n", date.format("%B %-d"), text.trim())).collect()
+        };
+        let tags: String = if self.tags.is_empty() {
+            "(none)".to_string()
+        } else {
+            self.tags.iter().map(|(tag, count)| format!("- {} ({})\n", tag, count)).collect()
+        };
+        prompts.get_insights_prompt(&self.period(), &dated(&self.summaries), &dated(&self.moods), &tags)
+    }
+}
+
+/// The summaries, mood changes, and tags of the `WINDOW_DAYS` ending on `today`
+pub async fn gather(journal_manager: &JournalManager, today: NaiveDate) -> Result<InsightMaterial, Box<dyn std::error::Error>> {
+    let from = today - Duration::days(WINDOW_DAYS - 1);
+    let filter = EntryFilter {
+        from: Some(CycleDate::from_real_date(from)),
+        to: Some(CycleDate::from_real_date(today)),
+        ..EntryFilter::default()
+    };
+    let listings = journal_manager.list_entries(&filter).await?;
+
+    let mut summaries = Vec::new();
+    let mut tag_counts: HashMap<String, usize> = HashMap::new();
+    for listing in &listings {
+        if let Some(entry) = journal_manager.load_entry(&listing.cycle_date).await? {
+            for tag in crate::memory_lane::tags(&entry.content) {
+                *tag_counts.entry(tag).or_default() += 1;
+            }
+        }
+        if let Some(summary) = journal_manager.load_summary(&listing.cycle_date).await? {
+            summaries.push((listing.real_date, summary.summary));
+        }
+    }
+    let mut tags: Vec<(String, usize)> = tag_counts.into_iter().collect();
+    tags.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    tags.truncate(TOP_TAGS);
+
+    let mut moods: Vec<(NaiveDate, String)> = Vec::new();
+    for day in journal_manager.list_statuses().await? {
+        let date = day.cycle_date.to_real_date();
+        let mood = day.status.section("mood_baseline").unwrap_or_default().trim().to_string();
+        if date < from || date > today || mood.is_empty() || moods.last().is_some_and(|(_, last)| *last == mood) {
+            continue;
+        }
+        moods.push((date, mood));
+    }
+
+    Ok(InsightMaterial { from, to: today, entries: listings.len(), summaries, moods, tags })
+}
+
+/// Saved analyses, newest first
+pub async fn load(journal_dir: &Path) -> Vec<Insight> {
+    let Ok(json) = tokio::fs::read_to_string(journal_dir.join(INSIGHTS_FILE)).await else {
+        return Vec::new();
+    };
+    serde_json::from_str(&json).unwrap_or_else(|e| {
+        tracing::warn!("Ignoring unreadable {}: {}", INSIGHTS_FILE, e);
+        Vec::new()
+    })
+}
+
+async fn save(journal_dir: &Path, insight: Insight) -> Result<(), Box<dyn std::error::Error>> {
+    let mut insights = load(journal_dir).await;
+    insights.insert(0, insight);
+    insights.truncate(KEEP);
+    tokio::fs::write(journal_dir.join(INSIGHTS_FILE), serde_json::to_string_pretty(&insights)?).await?;
+    Ok(())
+}
+
+/// Whether the newest analysis is old enough to write another
+pub fn is_due(insights: &[Insight], now: DateTime<Local>) -> bool {
+    insights.first().is_none_or(|latest| now - latest.generated_at >= Duration::days(REFRESH_DAYS))
+}
+
+/// Write and save an analysis of the last `WINDOW_DAYS`
+pub async fn generate(
+    journal_manager: &JournalManager,
+    llm_worker: &LlmWorker,
+    prompts: &PromptsConfig,
+) -> Result<Insight, Box<dyn std::error::Error>> {
+    let today = crate::cycle_date::current_day();
+    let material = gather(journal_manager, today).await?;
+    if material.entries == 0 {
+        return Err(format!("No entries in the last {} days to analyze", WINDOW_DAYS).into());
+    }
+
+    let analysis = llm_worker.generate_text(&material.request(prompts), 400).await?;
+    let analysis = analysis.trim().to_string();
+    if analysis.is_empty() {
+        return Err("The model returned an empty analysis".into());
+    }
+    let insight = Insight {
+        generated_at: Local::now(),
+        from: material.from,
+        to: material.to,
+        entries: material.entries,
+        analysis,
+    };
+    save(journal_manager.base_path(), insight.clone()).await?;
+    tracing::info!("Wrote insights for {} from {} entries", material.period(), material.entries);
+    Ok(insight)
+}
+
+/// One bar of a chart
+pub struct Bar {
+    pub label: String,
+    pub value: usize,
+    pub percent: usize,
+}
+
+fn bars(values: impl Iterator<Item = (String, usize)>) -> Vec<Bar> {
+    let values: Vec<_> = values.collect();
+    let largest = values.iter().map(|(_, value)| *value).max().unwrap_or(0).max(1);
+    values.into_iter().map(|(label, value)| Bar { label, value, percent: value * 100 / largest }).collect()
+}
+
+#[derive(Template)]
+#[template(path = "insights.html")]
+pub struct InsightsTemplate {
+    pub latest: Option<Insight>,
+    pub earlier: Vec<Insight>,
+    pub period: String,
+    pub entries: usize,
+    pub weeks: Vec<Bar>,
+    pub tags: Vec<Bar>,
+    pub moods: Vec<(NaiveDate, String)>,
+    /// A refresh in progress, to keep polling after a reload
+    pub running: Option<PromptJob>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/insights", get(insights_page))
+        .route("/insights/refresh", post(refresh_insights))
+}
+
+/// The latest analysis beside charts of the same weeks (GET /insights)
+async fn insights_page(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::ReadEntries) {
+        return rejection.into_response();
+    }
+
+    let journal_manager = &notebook.journal_manager;
+    let today = crate::cycle_date::current_day();
+    let material = gather(journal_manager, today).await.map_err(|e| e.to_string());
+    let material = match material {
+        Ok(material) => material,
+        Err(e) => return AppError::internal("Error gathering insights", e).into_response(),
+    };
+    let writing = crate::stats::writing_stats(journal_manager).await.map_err(|e| e.to_string());
+    let writing = match writing {
+        Ok(writing) => writing,
+        Err(e) => return AppError::internal("Error loading statistics", e).into_response(),
+    };
+
+    let mut insights = load(journal_manager.base_path()).await;
+    let latest = (!insights.is_empty()).then(|| insights.remove(0));
+    let recent_weeks = writing.entries_per_week.len().saturating_sub(((WINDOW_DAYS + 6) / 7) as usize);
+    let template = InsightsTemplate {
+        latest,
+        earlier: insights,
+        period: material.period(),
+        entries: material.entries,
+        weeks: bars(writing.entries_per_week[recent_weeks..].iter().map(|w| (w.week_start.format("%b %d").to_string(), w.entries as usize))),
+        tags: bars(material.tags.into_iter()),
+        moods: material.moods,
+        running: app_state.prompt_jobs.running(ModelTask::Insights, &notebook.name),
+    };
+    match template.render() {
+        Ok(html) => Html(html).into_response(),
+        Err(e) => AppError::internal("Error rendering page", e).into_response(),
+    }
+}
+
+/// Write a fresh analysis in the background; answers 202 with the job to poll, or the
+/// one already running (POST /insights/refresh)
+async fn refresh_insights(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::ReadEntries) {
+        return rejection.into_response();
+    }
+    let Some(prompt_generator) = notebook.prompt_generator.clone() else {
+        return AppError::unavailable("The model isn't available").into_response();
+    };
+
+    let job = match app_state.prompt_jobs.running(ModelTask::Insights, &notebook.name) {
+        Some(job) => job,
+        None => {
+            tracing::info!("Insights refresh for {} requested by {}", notebook.name, auth.label());
+            app_state.prompt_jobs.spawn(ModelTask::Insights, &notebook.name, None, async move {
+                let _model = crate::scheduler::lock_model("Insights refresh").await;
+                prompt_generator.write_insights().await.map(|_| None).map_err(|e| e.to_string())
+            })
+        }
+    };
+    if crate::handlers::wants_json(&headers) {
+        crate::handlers::job_accepted(&job)
+    } else {
+        (StatusCode::SEE_OTHER, [("Location", crate::handlers::app_url("/insights"))]).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::{JournalEntry, JournalSummary};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_gather() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        let today = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+
+        let days = [(today, "More #garden work, and #reading."), (today - Duration::days(3), "Planted bulbs. #garden"), (today - Duration::days(40), "Too old. #garden")];
+        for (date, content) in days {
+            let cycle_date = CycleDate::from_real_date(date);
+            manager.save_entry(&JournalEntry {
+                cycle_date,
+                content: content.to_string(),
+                created_at: Local::now(),
+                modified_at: Local::now(),
+            }).await.unwrap();
+        }
+        manager.save_summary(&JournalSummary {
+            cycle_date: CycleDate::from_real_date(today),
+            summary: "Gardened and read.".to_string(),
+            generated_at: Local::now(),
+        }).await.unwrap();
+
+        let material = gather(&manager, today).await.unwrap();
+        assert_eq!(material.entries, 2);
+        assert_eq!(material.tags, [("garden".to_string(), 2), ("reading".to_string(), 1)]);
+        assert_eq!(material.summaries, [(today, "Gardened and read.".to_string())]);
+        assert_eq!(material.period(), "September 18 to October 17, 2026");
+
+        let request = material.request(&PromptsConfig::default());
+        assert!(request.contains("- October 17: Gardened and read.") && request.contains("- garden (2)"));
+        assert!(request.contains("HOW THEIR MOOD WAS DESCRIBED:\n(none)"));
+
+        let insight = Insight { generated_at: Local::now() - Duration::days(8), from: material.from, to: today, entries: 2, analysis: "Growing things.".to_string() };
+        assert!(is_due(&[], Local::now()));
+        assert!(is_due(std::slice::from_ref(&insight), Local::now()));
+        assert!(!is_due(&[Insight { generated_at: Local::now(), ..insight }], Local::now()));
+    }
+}
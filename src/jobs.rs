@@ -0,0 +1,462 @@
+use crate::backup::{self, ArchiveEntry};
+use crate::config::Config;
+use crate::notebook::Notebooks;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+type JobResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+const JOBS_DIR: &str = "jobs";
+const JOB_FILE: &str = "job.json";
+const MANIFEST_FILE: &str = "manifest.json";
+/// Partial archive an export writes into before it's moved next to the other backups
+const PARTIAL_ARCHIVE: &str = "export.zip";
+
+/// Files written or restored between progress saves (and cancellation checks)
+const CHUNK_FILES: usize = 100;
+/// Finished jobs to keep records of
+const KEEP_FINISHED: usize = 20;
+
+/// What a job does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// Write a backup archive of the journal, notebooks, tokens, and config
+    Export,
+    /// Restore a backup archive over the current files
+    Import,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// A long-running export or import. Its file list is fixed when it starts and split into
+/// chunks; progress is saved after every chunk, so a cancelled, failed, or interrupted job
+/// resumes where it stopped. Stored under `<backup destination>/jobs/<id>/`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: String,
+    pub kind: JobKind,
+    pub state: JobState,
+    /// Backup archive file name: written by an export, read by an import
+    pub archive: String,
+    pub created_at: DateTime<Local>,
+    pub updated_at: DateTime<Local>,
+    pub total_files: usize,
+    pub files_done: usize,
+    pub total_chunks: usize,
+    pub completed_chunks: usize,
+    /// Snapshot of the files an import overwrites, taken before its first chunk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Length of the partial export archive after the last completed chunk; anything past it
+    /// was written by an interrupted chunk and is cut off before resuming
+    #[serde(default)]
+    pub archive_bytes: u64,
+}
+
+impl Job {
+    pub fn percent(&self) -> usize {
+        if self.total_files == 0 {
+            return if self.state == JobState::Completed { 100 } else { 0 };
+        }
+        self.files_done * 100 / self.total_files
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.state != JobState::Running
+    }
+}
+
+fn jobs_dir(config: &Config) -> PathBuf {
+    Path::new(&config.backup.destination).join(JOBS_DIR)
+}
+
+/// Job ids are generated as timestamps plus a random suffix; refuse anything else
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+fn job_dir(config: &Config, id: &str) -> JobResult<PathBuf> {
+    if !is_valid_id(id) {
+        return Err(format!("Invalid job id '{}'", id).into());
+    }
+    Ok(jobs_dir(config).join(id))
+}
+
+fn save_job(config: &Config, job: &Job) -> JobResult<()> {
+    fs::write(job_dir(config, &job.id)?.join(JOB_FILE), serde_json::to_string_pretty(job)?)?;
+    Ok(())
+}
+
+pub fn load_job(config: &Config, id: &str) -> JobResult<Job> {
+    let json = fs::read_to_string(job_dir(config, id)?.join(JOB_FILE)).map_err(|_| format!("No job '{}'", id))?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+fn load_manifest(config: &Config, id: &str) -> JobResult<Vec<ArchiveEntry>> {
+    Ok(serde_json::from_str(&fs::read_to_string(job_dir(config, id)?.join(MANIFEST_FILE))?)?)
+}
+
+/// All jobs, newest first
+pub fn list_jobs(config: &Config) -> JobResult<Vec<Job>> {
+    let dir = jobs_dir(config);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut jobs = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let name = entry?.file_name().to_string_lossy().to_string();
+        match load_job(config, &name) {
+            Ok(job) => jobs.push(job),
+            Err(e) => tracing::warn!("Skipping unreadable job {}: {}", name, e),
+        }
+    }
+    jobs.sort_by_key(|j| std::cmp::Reverse(j.created_at));
+    Ok(jobs)
+}
+
+/// Delete records of all but the newest finished jobs, along with any partial archives
+fn prune_jobs(config: &Config) -> JobResult<()> {
+    let jobs = list_jobs(config)?;
+    for job in jobs.iter().filter(|j| j.is_finished()).skip(KEEP_FINISHED) {
+        fs::remove_dir_all(job_dir(config, &job.id)?)?;
+    }
+    Ok(())
+}
+
+/// Record a new job and its file list
+fn create_job(config: &Config, kind: JobKind, archive: String, manifest: &[ArchiveEntry]) -> JobResult<Job> {
+    let id = format!("{}-{:04x}", Local::now().format("%Y%m%d-%H%M%S"), rand::random::<u16>());
+    fs::create_dir_all(job_dir(config, &id)?)?;
+    fs::write(job_dir(config, &id)?.join(MANIFEST_FILE), serde_json::to_string(manifest)?)?;
+
+    let job = Job {
+        id,
+        kind,
+        state: JobState::Running,
+        archive,
+        created_at: Local::now(),
+        updated_at: Local::now(),
+        total_files: manifest.len(),
+        files_done: 0,
+        total_chunks: manifest.len().div_ceil(CHUNK_FILES),
+        completed_chunks: 0,
+        snapshot_id: None,
+        error: None,
+        archive_bytes: 0,
+    };
+    save_job(config, &job)?;
+    Ok(job)
+}
+
+/// Archive a backup import reads from, restricted to the backups in the destination
+fn import_archive_path(config: &Config, archive: &str) -> JobResult<PathBuf> {
+    let backups = backup::list_backups(Path::new(&config.backup.destination))?;
+    backups
+        .into_iter()
+        .find(|path| path.file_name().is_some_and(|name| name.to_string_lossy() == archive))
+        .ok_or_else(|| format!("No backup named '{}'", archive).into())
+}
+
+/// Do one chunk of a job's work
+fn run_chunk(config: &Config, job: &mut Job, chunk: &[ArchiveEntry]) -> JobResult<()> {
+    let dir = job_dir(config, &job.id)?;
+    match job.kind {
+        JobKind::Export => {
+            let partial = dir.join(PARTIAL_ARCHIVE);
+            if job.archive_bytes == 0 {
+                if partial.exists() {
+                    fs::remove_file(&partial)?;
+                }
+            } else {
+                fs::OpenOptions::new().write(true).open(&partial)?.set_len(job.archive_bytes)?;
+            }
+            backup::append_entries(&partial, chunk)?;
+            job.archive_bytes = fs::metadata(&partial)?.len();
+        }
+        JobKind::Import => {
+            let archive = import_archive_path(config, &job.archive)?;
+            if job.snapshot_id.is_none() {
+                let manifest = load_manifest(config, &job.id)?;
+                job.snapshot_id = Some(backup::snapshot_before_restore(config, &archive, &manifest)?);
+                save_job(config, job)?;
+            }
+            backup::restore_files(&archive, chunk)?;
+        }
+    }
+    Ok(())
+}
+
+/// Finish a job whose chunks are all done
+fn complete(config: &Config, job: &mut Job) -> JobResult<()> {
+    if job.kind == JobKind::Export {
+        let destination = Path::new(&config.backup.destination);
+        let partial = job_dir(config, &job.id)?.join(PARTIAL_ARCHIVE);
+        if !partial.exists() {
+            // Nothing to back up; still leave a valid (empty) archive
+            backup::append_entries(&partial, &[])?;
+        }
+        fs::rename(&partial, destination.join(&job.archive))?;
+        let removed = backup::prune_backups(destination, config.backup.keep)?;
+        if removed > 0 {
+            tracing::info!("Removed {} old backups", removed);
+        }
+    }
+    job.state = JobState::Completed;
+    Ok(())
+}
+
+/// Starts, tracks, and cancels export and import jobs. One runs at a time.
+pub struct JobManager {
+    config: Arc<Config>,
+    notebooks: Arc<Notebooks>,
+    /// Cancellation flags of jobs running in this process
+    running: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl JobManager {
+    pub fn new(config: Arc<Config>, notebooks: Arc<Notebooks>) -> Arc<Self> {
+        Arc::new(Self {
+            config,
+            notebooks,
+            running: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Start writing a backup archive in the background
+    pub async fn start_export(self: &Arc<Self>) -> Result<Job, String> {
+        self.ensure_idle()?;
+        let config = self.config.clone();
+        let job = blocking(move || {
+            let entries = backup::backup_entries(&config)?;
+            create_job(&config, JobKind::Export, backup::archive_name(), &entries)
+        })
+        .await?;
+        self.spawn(job.clone());
+        Ok(job)
+    }
+
+    /// Start restoring a backup archive from the destination in the background
+    pub async fn start_import(self: &Arc<Self>, archive: &str) -> Result<Job, String> {
+        self.ensure_idle()?;
+        let config = self.config.clone();
+        let archive = archive.to_string();
+        let job = blocking(move || {
+            let entries = backup::restore_entries(&config, &import_archive_path(&config, &archive)?)?;
+            create_job(&config, JobKind::Import, archive, &entries)
+        })
+        .await?;
+        self.spawn(job.clone());
+        Ok(job)
+    }
+
+    pub fn get(&self, id: &str) -> Result<Job, String> {
+        load_job(&self.config, id).map_err(|e| e.to_string())
+    }
+
+    pub fn list(&self) -> Vec<Job> {
+        list_jobs(&self.config).unwrap_or_else(|e| {
+            tracing::error!("Failed to list jobs: {}", e);
+            Vec::new()
+        })
+    }
+
+    /// Ask a running job to stop after its current chunk
+    pub fn cancel(&self, id: &str) -> Result<(), String> {
+        match self.running.lock().unwrap().get(id) {
+            Some(flag) => {
+                flag.store(true, Ordering::Relaxed);
+                Ok(())
+            }
+            None => Err(format!("Job '{}' isn't running", id)),
+        }
+    }
+
+    /// Continue a cancelled or failed job from its last completed chunk
+    pub fn resume(self: &Arc<Self>, id: &str) -> Result<Job, String> {
+        let mut job = self.get(id)?;
+        if job.state == JobState::Completed || self.running.lock().unwrap().contains_key(id) {
+            return Err(format!("Job '{}' has nothing to resume", id));
+        }
+        self.ensure_idle()?;
+        job.state = JobState::Running;
+        job.error = None;
+        save_job(&self.config, &job).map_err(|e| e.to_string())?;
+        self.spawn(job.clone());
+        Ok(job)
+    }
+
+    /// Pick up jobs that were still running when the server stopped
+    pub fn resume_interrupted(self: &Arc<Self>) {
+        for job in self.list().into_iter().filter(|j| j.state == JobState::Running) {
+            tracing::info!("Resuming interrupted {:?} job {} at chunk {}/{}", job.kind, job.id, job.completed_chunks, job.total_chunks);
+            self.spawn(job);
+        }
+    }
+
+    fn ensure_idle(&self) -> Result<(), String> {
+        match self.running.lock().unwrap().keys().next() {
+            Some(id) => Err(format!("Job {} is still running", id)),
+            None => Ok(()),
+        }
+    }
+
+    fn spawn(self: &Arc<Self>, job: Job) {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        self.running.lock().unwrap().insert(job.id.clone(), cancelled.clone());
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let id = job.id.clone();
+            let job = manager.run(job, cancelled).await;
+            manager.running.lock().unwrap().remove(&id);
+
+            match job.state {
+                JobState::Completed => tracing::info!("{:?} job {} finished: {} files, {}", job.kind, job.id, job.files_done, job.archive),
+                JobState::Cancelled => tracing::info!("{:?} job {} cancelled at {}/{} files", job.kind, job.id, job.files_done, job.total_files),
+                _ => tracing::error!("{:?} job {} failed: {}", job.kind, job.id, job.error.as_deref().unwrap_or("unknown error")),
+            }
+            if job.kind == JobKind::Import && job.files_done > 0 {
+                for notebook in manager.notebooks.iter() {
+                    if let Err(e) = notebook.journal_manager.rebuild_index().await {
+                        tracing::warn!("Failed to reindex notebook {} after import: {}", notebook.name, e);
+                    }
+                    notebook.journal_manager.record_change(format!("Import {}", job.archive)).await;
+                }
+            }
+        });
+    }
+
+    /// Work through the remaining chunks, saving progress after each
+    async fn run(&self, mut job: Job, cancelled: Arc<AtomicBool>) -> Job {
+        let config = self.config.clone();
+        let id = job.id.clone();
+        let manifest = match blocking(move || load_manifest(&config, &id)).await {
+            Ok(manifest) => manifest,
+            Err(e) => return self.finish(job, JobState::Failed, Some(e)).await,
+        };
+
+        while job.completed_chunks < job.total_chunks {
+            if cancelled.load(Ordering::Relaxed) {
+                return self.finish(job, JobState::Cancelled, None).await;
+            }
+
+            let start = job.completed_chunks * CHUNK_FILES;
+            let chunk = manifest[start..(start + CHUNK_FILES).min(manifest.len())].to_vec();
+            let before = job.clone();
+            let config = self.config.clone();
+            let result = blocking(move || {
+                run_chunk(&config, &mut job, &chunk)?;
+                job.completed_chunks += 1;
+                job.files_done += chunk.len();
+                job.updated_at = Local::now();
+                save_job(&config, &job)?;
+                Ok(job)
+            })
+            .await;
+            job = match result {
+                Ok(job) => job,
+                Err(e) => return self.finish(self.saved_or(before), JobState::Failed, Some(e)).await,
+            };
+        }
+
+        let before = job.clone();
+        let config = self.config.clone();
+        let finished = blocking(move || {
+            complete(&config, &mut job)?;
+            Ok(job)
+        })
+        .await;
+        match finished {
+            Ok(job) => self.finish(job, JobState::Completed, None).await,
+            Err(e) => self.finish(self.saved_or(before), JobState::Failed, Some(e)).await,
+        }
+    }
+
+    /// The job as last saved, which may include progress made before a failure
+    fn saved_or(&self, job: Job) -> Job {
+        load_job(&self.config, &job.id).unwrap_or(job)
+    }
+
+    async fn finish(&self, mut job: Job, state: JobState, error: Option<String>) -> Job {
+        job.state = state;
+        job.error = error;
+        job.updated_at = Local::now();
+        let config = self.config.clone();
+        let saved = job.clone();
+        if let Err(e) = blocking(move || {
+            save_job(&config, &saved)?;
+            prune_jobs(&config)
+        })
+        .await
+        {
+            tracing::error!("Failed to save job {}: {}", job.id, e);
+        }
+        job
+    }
+}
+
+/// Run blocking file work off the async runtime
+async fn blocking<T: Send + 'static>(work: impl FnOnce() -> JobResult<T> + Send + 'static) -> Result<T, String> {
+    tokio::task::spawn_blocking(move || work().map_err(|e| e.to_string()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_chunked_export_resumes_and_imports() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.journal.journal_directory = temp_dir.path().join("journal").to_string_lossy().to_string();
+        config.files.tokens_file = temp_dir.path().join("tokens.json").to_string_lossy().to_string();
+        config.backup.destination = temp_dir.path().join("backups").to_string_lossy().to_string();
+        for date in ["01210", "01211", "01212"] {
+            let dir = Path::new(&config.journal.journal_directory).join(date);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("entry.txt"), format!("Entry for {}", date)).unwrap();
+        }
+
+        let entries = backup::backup_entries(&config).unwrap();
+        let mut job = create_job(&config, JobKind::Export, "llm-journal-20250301-020000.zip".to_string(), &entries).unwrap();
+        run_chunk(&config, &mut job, &entries[..2]).unwrap();
+        // A chunk interrupted partway leaves junk after the last complete archive
+        let partial = job_dir(&config, &job.id).unwrap().join(PARTIAL_ARCHIVE);
+        fs::OpenOptions::new().append(true).open(&partial).unwrap().set_len(job.archive_bytes + 10).unwrap();
+        run_chunk(&config, &mut job, &entries[2..]).unwrap();
+        complete(&config, &mut job).unwrap();
+        assert_eq!(job.state, JobState::Completed);
+
+        let archive = import_archive_path(&config, &job.archive).unwrap();
+        let restored = backup::restore_entries(&config, &archive).unwrap();
+        assert_eq!(restored.len(), 3);
+
+        fs::remove_dir_all(&config.journal.journal_directory).unwrap();
+        let mut import = create_job(&config, JobKind::Import, job.archive.clone(), &restored).unwrap();
+        run_chunk(&config, &mut import, &restored).unwrap();
+        assert!(import.snapshot_id.is_some());
+        let entry = Path::new(&config.journal.journal_directory).join("01212").join("entry.txt");
+        assert_eq!(fs::read_to_string(entry).unwrap(), "Entry for 01212");
+
+        assert_eq!(list_jobs(&config).unwrap().len(), 2);
+        assert!(import_archive_path(&config, "../tokens.json").is_err());
+    }
+}
@@ -1,10 +1,20 @@
 use crate::cycle_date::CycleDate;
+use crate::git_sync::GitJournal;
+use crate::journal_index::{DayArtifacts, JournalIndex};
+use crate::status::StructuredStatus;
 use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 
+/// Entries at least this many days old get a one-line summary for long-horizon context
+pub const ONE_LINE_AGE_DAYS: i64 = 365;
+/// Most one-line summaries a yearly reflection's context holds, however many years the
+/// journal spans; each earlier year gets an equal share, at least one
+const LONG_HORIZON_LINES: usize = 60;
+
 /// Represents a journal entry for a specific day
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JournalEntry {
@@ -14,6 +24,58 @@ pub struct JournalEntry {
     pub modified_at: DateTime<Local>,
 }
 
+/// An in-progress draft of a journal entry, kept separate from the committed entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalDraft {
+    pub cycle_date: CycleDate,
+    pub content: String,
+    pub revision: u64,
+    pub saved_at: DateTime<Local>,
+}
+
+/// The text a device was last typing for a date, sent by its editor as a heartbeat.
+/// Kept per device so another device opening the day can offer to recover it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceDraft {
+    pub device: String,
+    pub content: String,
+    /// First heartbeat since the entry was last saved
+    pub started_at: DateTime<Local>,
+    pub saved_at: DateTime<Local>,
+}
+
+/// Outcome of saving a draft
+#[derive(Debug)]
+pub enum DraftSaveResult {
+    Saved(JournalDraft),
+    /// Another client saved a draft since the revision the caller last saw
+    Conflict(JournalDraft),
+}
+
+/// Outcome of saving an entry against the revision the writer started from
+#[derive(Debug)]
+pub enum EntrySaveResult {
+    /// The entry was saved; holds its new revision
+    Saved(String),
+    /// The entry changed since the writer loaded it
+    Conflict { current: JournalEntry, revision: String },
+}
+
+/// A saved change other devices may want to show, broadcast by `JournalManager`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type")]
+pub enum JournalChange {
+    #[serde(rename = "entry_saved")]
+    Entry { cycle_date: CycleDate, revision: String },
+    #[serde(rename = "prompt_saved")]
+    Prompt { cycle_date: CycleDate, prompt_number: u8 },
+    #[serde(rename = "status_saved")]
+    Status { cycle_date: CycleDate },
+    /// The journal day rolled over; `cycle_date` is the new today
+    #[serde(rename = "day_started")]
+    DayStarted { cycle_date: CycleDate },
+}
+
 /// Represents a generated summary of a journal entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JournalSummary {
@@ -22,6 +84,14 @@ pub struct JournalSummary {
     pub generated_at: DateTime<Local>,
 }
 
+/// The full rolling status as it stood after a day's entry was processed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalStatus {
+    pub cycle_date: CycleDate,
+    pub status: StructuredStatus,
+    pub generated_at: DateTime<Local>,
+}
+
 /// Represents a generated prompt for a specific day
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JournalPrompt {
@@ -33,7 +103,7 @@ pub struct JournalPrompt {
 }
 
 /// Types of prompts that can be generated
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PromptType {
     Daily,          // Based on summaries from past 7 days
     WeeklyReflection,   // Based on full entries from past 7 days
@@ -41,6 +111,36 @@ pub enum PromptType {
     YearlyReflection,   // Based on monthly reflections from past year
 }
 
+impl PromptType {
+    /// Determine the prompt type for a date based on its position in the cycle
+    pub fn for_date(cycle_date: &CycleDate) -> Self {
+        if cycle_date.is_first_day_of_year() {
+            PromptType::YearlyReflection
+        } else if cycle_date.is_first_day_of_month() {
+            PromptType::MonthlyReflection
+        } else if cycle_date.is_first_day_of_week() {
+            PromptType::WeeklyReflection
+        } else {
+            PromptType::Daily
+        }
+    }
+}
+
+impl std::str::FromStr for PromptType {
+    type Err = String;
+
+    /// Parse a prompt type from its short name ("daily", "weekly", "monthly", "yearly")
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "daily" => Ok(PromptType::Daily),
+            "weekly" | "weeklyreflection" => Ok(PromptType::WeeklyReflection),
+            "monthly" | "monthlyreflection" => Ok(PromptType::MonthlyReflection),
+            "yearly" | "yearlyreflection" => Ok(PromptType::YearlyReflection),
+            _ => Err(format!("Unknown entry type '{}'", s)),
+        }
+    }
+}
+
 impl std::fmt::Display for PromptType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -52,18 +152,296 @@ impl std::fmt::Display for PromptType {
     }
 }
 
+/// Metadata for a day's artifacts, persisted in a `meta.json` sidecar so that
+/// timestamps survive copies and restores that reset filesystem times
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DayMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry: Option<EntryMetadata>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<SummaryMetadata>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<StatusMetadata>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub prompts: BTreeMap<u8, PromptMetadata>,
+    /// Set when the day was marked as needing no prompt, so generation leaves it alone
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub no_prompt_since: Option<DateTime<Local>>,
+}
+
+/// Metadata recorded for a journal entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntryMetadata {
+    pub created_at: DateTime<Local>,
+    pub modified_at: DateTime<Local>,
+    pub prompt_type: PromptType,
+    pub word_count: usize,
+    /// Set when the entry text was produced by OCR or speech-to-text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcription: Option<TranscriptionMetadata>,
+    /// Prompt that was on screen when the entry was saved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answered_prompt: Option<u8>,
+}
+
+/// Provenance of a transcribed entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionMetadata {
+    /// Original media file, relative to the date's attachments directory
+    pub source_media: String,
+    /// Transcription confidence reported by the backend (0.0-1.0)
+    pub confidence: f32,
+    /// Whether the transcription has been approved; low-confidence ones start unreviewed
+    pub reviewed: bool,
+}
+
+/// Metadata recorded for a generated summary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummaryMetadata {
+    pub generated_at: DateTime<Local>,
+    /// One-off instruction the summary was regenerated with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub steering: Option<String>,
+}
+
+/// Metadata recorded for a day's status file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusMetadata {
+    pub generated_at: DateTime<Local>,
+}
+
+/// Metadata recorded for a generated prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptMetadata {
+    pub generated_at: DateTime<Local>,
+    pub prompt_type: PromptType,
+    /// Set when the prompt went unanswered past the expiry window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expired_at: Option<DateTime<Local>>,
+    /// One-off instruction the prompt was regenerated with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub steering: Option<String>,
+    /// Ollama model that wrote the prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Set when an ensemble of two models competed for this prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ensemble: Option<EnsembleRecord>,
+}
+
+/// Both candidates an ensemble wrote for one prompt, and which was kept
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnsembleRecord {
+    pub candidates: Vec<EnsembleCandidate>,
+    /// Model whose candidate became the prompt; None until the user picks one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub winner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chosen_by: Option<EnsembleChooser>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnsembleCandidate {
+    pub model: String,
+    pub prompt: String,
+}
+
+/// Who picked the winning ensemble candidate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnsembleChooser {
+    /// A judge pass by the main model
+    Judge,
+    User,
+}
+
+/// How a prompt was generated, recorded in its metadata when it's saved
+#[derive(Debug, Clone, Default)]
+pub struct PromptProvenance {
+    pub steering: Option<String>,
+    pub model: Option<String>,
+    pub ensemble: Option<EnsembleRecord>,
+}
+
+/// A generated prompt and whether it led to an entry, for answer-rate analytics
+#[derive(Debug, Clone)]
+pub struct PromptOutcome {
+    pub cycle_date: CycleDate,
+    pub prompt_type: PromptType,
+    pub prompt_number: u8,
+    pub prompt: String,
+    pub answered: bool,
+    /// Length of the entry the prompt led to
+    pub entry_words: Option<usize>,
+    /// Model that wrote the prompt, when recorded
+    pub model: Option<String>,
+    /// Whether the prompt won an ensemble comparison
+    pub ensemble_winner: bool,
+}
+
+/// Lightweight description of a day's entry, built from metadata without reading the entry text
+#[derive(Debug, Clone, Serialize)]
+pub struct EntryListing {
+    pub cycle_date: CycleDate,
+    pub real_date: chrono::NaiveDate,
+    pub entry_type: PromptType,
+    pub word_count: usize,
+    pub prompt_count: usize,
+    pub has_summary: bool,
+    pub modified_at: Option<DateTime<Local>>,
+}
+
+/// Filters for listing entries; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct EntryFilter {
+    pub from: Option<CycleDate>,
+    pub to: Option<CycleDate>,
+    pub entry_type: Option<PromptType>,
+    pub has_summary: Option<bool>,
+}
+
+impl EntryFilter {
+    fn matches_date(&self, cycle_date: &CycleDate) -> bool {
+        let real_date = cycle_date.to_real_date();
+        self.from.is_none_or(|from| real_date >= from.to_real_date())
+            && self.to.is_none_or(|to| real_date <= to.to_real_date())
+            && self.entry_type.as_ref().is_none_or(|t| *t == PromptType::for_date(cycle_date))
+    }
+}
+
+/// Identifies one version of an entry's text, so a save can tell whether someone else
+/// changed the entry since the writer loaded it
+pub fn entry_revision(content: &str) -> String {
+    use sha2::{Digest, Sha256};
+    hex::encode(&Sha256::digest(content.as_bytes())[..8])
+}
+
+/// Count the words in a piece of text
+pub fn count_words(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+/// Check whether an entry for `cycle_date` may be saved on `today`.
+/// Entries can't be written for future days, nor further back than `max_edit_age_days` if set.
+pub fn check_entry_date_editable(
+    cycle_date: &CycleDate,
+    today: &CycleDate,
+    max_edit_age_days: Option<u32>,
+) -> Result<(), String> {
+    let age_days = (today.to_real_date() - cycle_date.to_real_date()).num_days();
+    
+    if age_days < 0 {
+        return Err(format!("Cannot write an entry for {}, which is in the future", cycle_date));
+    }
+    
+    if let Some(max_age) = max_edit_age_days {
+        if age_days > max_age as i64 {
+            return Err(format!("Entries older than {} days can no longer be edited ({} is {} days old)", max_age, cycle_date, age_days));
+        }
+    }
+    
+    Ok(())
+}
+
+/// Whether an entry for `cycle_date` is past the edit window on `today`, and so read-only
+pub fn is_entry_locked(cycle_date: &CycleDate, today: &CycleDate, max_edit_age_days: Option<u32>) -> bool {
+    max_edit_age_days.is_some_and(|max_age| (today.to_real_date() - cycle_date.to_real_date()).num_days() > max_age as i64)
+}
+
+/// Validate an attachment file name, allowing only a plain name without path components
+pub fn sanitize_attachment_name(filename: &str) -> Option<String> {
+    let name = filename.trim();
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+    valid.then(|| name.to_string())
+}
+
+/// A date as shown in commit messages, e.g. "01213 (2025-03-05)"
+fn describe_date(cycle_date: &CycleDate) -> String {
+    format!("{} ({})", cycle_date, cycle_date.to_real_date().format("%Y-%m-%d"))
+}
+
+/// Append the steering instruction a generation used to its commit message
+fn with_steering_note(message: String, steering: Option<&str>) -> String {
+    match steering {
+        Some(steering) => format!("{}\n\nSteering: {}", message, steering),
+        None => message,
+    }
+}
+
+/// Read created/modified timestamps from the filesystem, for files without a sidecar entry.
+/// Falls back to the modification time on filesystems that don't record birth time.
+async fn file_timestamps(path: &Path) -> Result<(DateTime<Local>, DateTime<Local>), Box<dyn std::error::Error>> {
+    let metadata = fs::metadata(path).await?;
+    let modified = metadata.modified()?;
+    let created = metadata.created().unwrap_or(modified);
+    Ok((DateTime::from(created), DateTime::from(modified)))
+}
+
 /// Manages journal files and operations
 pub struct JournalManager {
     base_path: PathBuf,
+    /// Serializes draft read-compare-write cycles
+    draft_lock: tokio::sync::Mutex<()>,
+    /// Serializes entry read-compare-write cycles
+    entry_lock: tokio::sync::Mutex<()>,
+    /// Commits every save when the journal directory is git-backed
+    git: Option<GitJournal>,
+    /// Loaded from `index.json` (or rebuilt by a scan) on first use
+    index: tokio::sync::Mutex<Option<JournalIndex>>,
+    /// Saved entries, prompts, and statuses, for live-syncing connected devices
+    changes: tokio::sync::broadcast::Sender<JournalChange>,
 }
 
 impl JournalManager {
     pub fn new<P: AsRef<Path>>(base_path: P) -> Self {
         Self {
             base_path: base_path.as_ref().to_path_buf(),
+            draft_lock: tokio::sync::Mutex::new(()),
+            entry_lock: tokio::sync::Mutex::new(()),
+            git: None,
+            index: tokio::sync::Mutex::new(None),
+            changes: tokio::sync::broadcast::channel(64).0,
         }
     }
 
+    /// Auto-commit the journal directory after every save
+    pub fn with_git(mut self, git: GitJournal) -> Self {
+        self.git = Some(git);
+        self
+    }
+
+    /// Commit pending changes if git-backed. A failed commit is logged rather than
+    /// failing the save; the change will be picked up by the next commit.
+    pub(crate) async fn record_change(&self, message: String) {
+        if let Some(git) = &self.git {
+            if let Err(e) = git.commit(&message).await {
+                tracing::warn!("Failed to commit journal change: {}", e);
+            }
+        }
+    }
+
+    /// Changes saved from now on; a receiver that falls behind skips what it missed
+    pub fn subscribe_changes(&self) -> tokio::sync::broadcast::Receiver<JournalChange> {
+        self.changes.subscribe()
+    }
+
+    /// Tell connected devices a new journal day has started, so open pages move on to it
+    pub fn announce_day_started(&self, cycle_date: CycleDate) {
+        self.announce(JournalChange::DayStarted { cycle_date });
+    }
+
+    /// Tell connected devices about a change; nobody listening is fine
+    fn announce(&self, change: JournalChange) {
+        let _ = self.changes.send(change);
+    }
+
+    /// Root directory holding the date directories
+    pub fn base_path(&self) -> &Path {
+        &self.base_path
+    }
+
     /// Create directory structure if it doesn't exist
     pub async fn ensure_directories(&self) -> Result<(), Box<dyn std::error::Error>> {
         fs::create_dir_all(&self.base_path).await?;
@@ -73,10 +451,66 @@ impl JournalManager {
     /// Ensure that the directory for a specific date exists
     pub async fn ensure_date_directory(&self, cycle_date: &CycleDate) -> Result<(), Box<dyn std::error::Error>> {
         let date_dir = self.base_path.join(cycle_date.to_string());
-        fs::create_dir_all(&date_dir).await?;
+        if !date_dir.exists() {
+            fs::create_dir_all(&date_dir).await?;
+            self.update_index(cycle_date, |_| {}).await;
+        }
         Ok(())
     }
 
+    /// Run `f` against the index, loading it (or scanning the journal when there's no
+    /// usable index file) on first use
+    async fn with_index<R>(&self, f: impl FnOnce(&JournalIndex) -> R) -> Result<R, Box<dyn std::error::Error>> {
+        let mut guard = self.index.lock().await;
+        if guard.is_none() {
+            *guard = Some(self.load_or_scan_index().await?);
+        }
+        Ok(f(guard.as_ref().unwrap()))
+    }
+
+    async fn load_or_scan_index(&self) -> Result<JournalIndex, Box<dyn std::error::Error>> {
+        if let Some(index) = JournalIndex::load(&self.base_path).await {
+            return Ok(index);
+        }
+        let index = JournalIndex::scan(&self.base_path).await?;
+        tracing::info!("Indexed {} days in {}", index.days.len(), self.base_path.display());
+        if let Err(e) = index.save(&self.base_path).await {
+            tracing::warn!("Failed to save journal index: {}", e);
+        }
+        Ok(index)
+    }
+
+    /// Record a change to a day's artifacts. A failure here leaves the saved file in place and
+    /// drops the index, so it's rebuilt from the files next time.
+    async fn update_index(&self, cycle_date: &CycleDate, f: impl FnOnce(&mut DayArtifacts)) {
+        let mut guard = self.index.lock().await;
+        let result: Result<(), Box<dyn std::error::Error>> = async {
+            if guard.is_none() {
+                *guard = Some(self.load_or_scan_index().await?);
+            }
+            let index = guard.as_mut().unwrap();
+            f(index.days.entry(cycle_date.to_string()).or_default());
+            index.save(&self.base_path).await
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::warn!("Failed to update journal index: {}", e);
+            *guard = None;
+            crate::journal_index::invalidate(&self.base_path);
+        }
+    }
+
+    /// Rescan the journal directory into a fresh index, for when files were changed
+    /// outside this manager. Returns the number of days indexed.
+    pub async fn rebuild_index(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut guard = self.index.lock().await;
+        let index = JournalIndex::scan(&self.base_path).await?;
+        index.save(&self.base_path).await?;
+        let days = index.days.len();
+        *guard = Some(index);
+        Ok(days)
+    }
+
     /// Get file paths for a given cycle date
     pub fn get_file_paths(&self, cycle_date: &CycleDate) -> JournalFilePaths {
         let date_str = cycle_date.to_string();
@@ -84,270 +518,1094 @@ impl JournalManager {
         JournalFilePaths {
             entry: date_dir.join("entry.txt"),
             summary: date_dir.join("summary.txt"),
+            one_line: date_dir.join("oneline.txt"),
             status: date_dir.join("status.txt"),
+            metadata: date_dir.join("meta.json"),
+            draft: date_dir.join("draft.json"),
+            device_drafts: date_dir.join("device-drafts.json"),
+            attachments: date_dir.join("attachments"),
             prompt1: date_dir.join("prompt1.txt"),
             prompt2: date_dir.join("prompt2.txt"),
             prompt3: date_dir.join("prompt3.txt"),
         }
     }
 
-    /// Save a journal entry
-    pub async fn save_entry(&self, entry: &JournalEntry) -> Result<(), Box<dyn std::error::Error>> {
-        self.ensure_date_directory(&entry.cycle_date).await?;
-        let paths = self.get_file_paths(&entry.cycle_date);
-        
-        let mut file = fs::File::create(&paths.entry).await?;
-        file.write_all(entry.content.as_bytes()).await?;
-        
-        Ok(())
-    }
-
-    /// Load a journal entry
-    pub async fn load_entry(&self, cycle_date: &CycleDate) -> Result<Option<JournalEntry>, Box<dyn std::error::Error>> {
+    /// Load the metadata sidecar for a date, returning empty metadata if none exists
+    pub async fn load_metadata(&self, cycle_date: &CycleDate) -> Result<DayMetadata, Box<dyn std::error::Error>> {
         let paths = self.get_file_paths(cycle_date);
         
-        if !paths.entry.exists() {
-            return Ok(None);
+        if !paths.metadata.exists() {
+            return Ok(DayMetadata::default());
         }
         
-        let content = fs::read_to_string(&paths.entry).await?;
-        let metadata = fs::metadata(&paths.entry).await?;
-        
-        let created_at = DateTime::from(metadata.created()?);
-        let modified_at = DateTime::from(metadata.modified()?);
-        
-        Ok(Some(JournalEntry {
-            cycle_date: *cycle_date,
-            content,
-            created_at,
-            modified_at,
-        }))
+        let content = fs::read_to_string(&paths.metadata).await?;
+        match serde_json::from_str(&content) {
+            Ok(metadata) => Ok(metadata),
+            Err(e) => {
+                tracing::warn!("Ignoring malformed metadata for {}: {}", cycle_date, e);
+                Ok(DayMetadata::default())
+            }
+        }
     }
 
-    /// Save a journal summary
-    pub async fn save_summary(&self, summary: &JournalSummary) -> Result<(), Box<dyn std::error::Error>> {
-        self.ensure_directories().await?;
-        let paths = self.get_file_paths(&summary.cycle_date);
+    /// Save the metadata sidecar for a date
+    pub async fn save_metadata(&self, cycle_date: &CycleDate, metadata: &DayMetadata) -> Result<(), Box<dyn std::error::Error>> {
+        self.ensure_date_directory(cycle_date).await?;
+        let paths = self.get_file_paths(cycle_date);
         
-        let mut file = fs::File::create(&paths.summary).await?;
-        file.write_all(summary.summary.as_bytes()).await?;
+        let json = serde_json::to_string_pretty(metadata)?;
+        fs::write(&paths.metadata, json).await?;
         
         Ok(())
     }
 
-    /// Load a journal summary
-    pub async fn load_summary(&self, cycle_date: &CycleDate) -> Result<Option<JournalSummary>, Box<dyn std::error::Error>> {
+    /// Load the current draft for a date, if any
+    pub async fn load_draft(&self, cycle_date: &CycleDate) -> Result<Option<JournalDraft>, Box<dyn std::error::Error>> {
         let paths = self.get_file_paths(cycle_date);
         
-        if !paths.summary.exists() {
+        if !paths.draft.exists() {
             return Ok(None);
         }
         
-        let summary = fs::read_to_string(&paths.summary).await?;
-        let metadata = fs::metadata(&paths.summary).await?;
-        let generated_at = DateTime::from(metadata.created()?);
+        let content = fs::read_to_string(&paths.draft).await?;
+        let draft = serde_json::from_str(&content)?;
+        Ok(Some(draft))
+    }
+
+    /// Save a draft, checking that the caller has seen the latest draft revision.
+    /// `base_revision` is the revision the client last loaded, or None if it never saw a draft.
+    pub async fn save_draft(
+        &self,
+        cycle_date: &CycleDate,
+        content: &str,
+        base_revision: Option<u64>,
+    ) -> Result<DraftSaveResult, Box<dyn std::error::Error>> {
+        let _guard = self.draft_lock.lock().await;
         
-        Ok(Some(JournalSummary {
+        let current = self.load_draft(cycle_date).await?;
+        if let Some(current) = current {
+            if base_revision != Some(current.revision) {
+                return Ok(DraftSaveResult::Conflict(current));
+            }
+        }
+        
+        let draft = JournalDraft {
             cycle_date: *cycle_date,
-            summary,
-            generated_at,
-        }))
+            content: content.to_string(),
+            revision: base_revision.unwrap_or(0) + 1,
+            saved_at: Local::now(),
+        };
+        
+        self.ensure_date_directory(cycle_date).await?;
+        let paths = self.get_file_paths(cycle_date);
+        fs::write(&paths.draft, serde_json::to_string_pretty(&draft)?).await?;
+        
+        Ok(DraftSaveResult::Saved(draft))
     }
 
-    /// Save a journal prompt
-    pub async fn save_prompt(&self, prompt: &JournalPrompt) -> Result<(), Box<dyn std::error::Error>> {
-        self.ensure_date_directory(&prompt.cycle_date).await?;
-        let paths = self.get_file_paths(&prompt.cycle_date);
-        
-        let prompt_path = match prompt.prompt_number {
-            1 => paths.prompt1,
-            2 => paths.prompt2,
-            3 => paths.prompt3,
-            n if n > 3 => {
-                // For prompts beyond 3, create additional files in the date directory
-                let date_dir = self.base_path.join(prompt.cycle_date.to_string());
-                date_dir.join(format!("prompt{}.txt", n))
-            },
-            _ => return Err("Invalid prompt number".into()),
-        };
+    /// Remove the draft and device heartbeats for a date, if any exist
+    pub async fn discard_draft(&self, cycle_date: &CycleDate) -> Result<(), Box<dyn std::error::Error>> {
+        let _guard = self.draft_lock.lock().await;
+        let paths = self.get_file_paths(cycle_date);
         
-        let mut file = fs::File::create(&prompt_path).await?;
-        file.write_all(prompt.prompt.as_bytes()).await?;
+        if paths.draft.exists() {
+            fs::remove_file(&paths.draft).await?;
+        }
+        if paths.device_drafts.exists() {
+            fs::remove_file(&paths.device_drafts).await?;
+        }
         
         Ok(())
     }
 
-    /// Load a journal prompt
-    pub async fn load_prompt(&self, cycle_date: &CycleDate, prompt_number: u8) -> Result<Option<JournalPrompt>, Box<dyn std::error::Error>> {
+    /// Each device's latest heartbeat text for a date, newest first
+    pub async fn load_device_drafts(&self, cycle_date: &CycleDate) -> Result<Vec<DeviceDraft>, Box<dyn std::error::Error>> {
         let paths = self.get_file_paths(cycle_date);
         
-        let prompt_path = match prompt_number {
-            1 => paths.prompt1,
-            2 => paths.prompt2,
-            3 => paths.prompt3,
-            n if n > 3 => {
-                // For prompts beyond 3, check additional files in the date directory
-                let date_dir = self.base_path.join(cycle_date.to_string());
-                date_dir.join(format!("prompt{}.txt", n))
-            },
-            _ => return Err("Invalid prompt number".into()),
-        };
-        
-        if !prompt_path.exists() {
-            return Ok(None);
+        if !paths.device_drafts.exists() {
+            return Ok(Vec::new());
         }
         
-        let prompt = fs::read_to_string(&prompt_path).await?;
-        let metadata = fs::metadata(&prompt_path).await?;
-        let generated_at = DateTime::from(metadata.created()?);
+        let content = fs::read_to_string(&paths.device_drafts).await?;
+        let mut drafts: Vec<DeviceDraft> = serde_json::from_str(&content)?;
+        drafts.sort_by_key(|d| std::cmp::Reverse(d.saved_at));
+        Ok(drafts)
+    }
+
+    /// Record what `device` is typing for a date, replacing its previous heartbeat
+    pub async fn save_device_draft(
+        &self,
+        cycle_date: &CycleDate,
+        device: &str,
+        content: &str,
+    ) -> Result<DeviceDraft, Box<dyn std::error::Error>> {
+        let _guard = self.draft_lock.lock().await;
         
-        // Determine prompt type based on cycle date
-        let prompt_type = if cycle_date.is_first_day_of_year() {
-            PromptType::YearlyReflection
-        } else if cycle_date.is_first_day_of_month() {
-            PromptType::MonthlyReflection
-        } else if cycle_date.is_first_day_of_week() {
-            PromptType::WeeklyReflection
-        } else {
-            PromptType::Daily
+        let mut drafts = self.load_device_drafts(cycle_date).await?;
+        let now = Local::now();
+        let started_at = drafts.iter().find(|d| d.device == device).map_or(now, |d| d.started_at);
+        drafts.retain(|d| d.device != device);
+        let draft = DeviceDraft {
+            device: device.to_string(),
+            content: content.to_string(),
+            started_at,
+            saved_at: now,
         };
+        drafts.insert(0, draft.clone());
         
-        Ok(Some(JournalPrompt {
-            cycle_date: *cycle_date,
-            prompt,
-            prompt_number,
-            generated_at,
-            prompt_type,
-        }))
-    }
-
-    /// Save a journal status update
-    pub async fn save_status(&self, cycle_date: &CycleDate, status: &str) -> Result<(), Box<dyn std::error::Error>> {
         self.ensure_date_directory(cycle_date).await?;
         let paths = self.get_file_paths(cycle_date);
+        fs::write(&paths.device_drafts, serde_json::to_string_pretty(&drafts)?).await?;
         
-        let mut file = fs::File::create(&paths.status).await?;
-        file.write_all(status.as_bytes()).await?;
-        
-        Ok(())
+        Ok(draft)
     }
 
-    /// Load a journal status
-    pub async fn load_status(&self, cycle_date: &CycleDate) -> Result<Option<String>, Box<dyn std::error::Error>> {
-        let paths = self.get_file_paths(cycle_date);
+    /// Save a journal entry
+    /// The original creation time is preserved when an entry is edited, and any draft is discarded.
+    pub async fn save_entry(&self, entry: &JournalEntry) -> Result<(), Box<dyn std::error::Error>> {
+        self.ensure_date_directory(&entry.cycle_date).await?;
+        let paths = self.get_file_paths(&entry.cycle_date);
+        let is_new = !paths.entry.exists();
         
-        if !paths.status.exists() {
-            return Ok(None);
-        }
+        let mut file = fs::File::create(&paths.entry).await?;
+        file.write_all(entry.content.as_bytes()).await?;
+        self.update_index(&entry.cycle_date, |day| day.entry = true).await;
+        
+        let mut metadata = self.load_metadata(&entry.cycle_date).await?;
+        let previous = metadata.entry.take();
+        metadata.entry = Some(EntryMetadata {
+            created_at: previous.as_ref().map(|m| m.created_at).unwrap_or(entry.created_at),
+            modified_at: entry.modified_at,
+            prompt_type: PromptType::for_date(&entry.cycle_date),
+            word_count: count_words(&entry.content),
+            answered_prompt: previous.as_ref().and_then(|m| m.answered_prompt),
+            transcription: previous.and_then(|m| m.transcription),
+        });
+        self.save_metadata(&entry.cycle_date, &metadata).await?;
+        self.discard_draft(&entry.cycle_date).await?;
         
-        let status = fs::read_to_string(&paths.status).await?;
-        Ok(Some(status))
+        let verb = if is_new { "Add" } else { "Update" };
+        self.record_change(format!("{} entry for {}", verb, describe_date(&entry.cycle_date))).await;
+        self.announce(JournalChange::Entry { cycle_date: entry.cycle_date, revision: entry_revision(&entry.content) });
+        Ok(())
     }
 
-    /// Find entries that need summaries
-    pub async fn find_entries_needing_summaries(&self) -> Result<Vec<CycleDate>, Box<dyn std::error::Error>> {
-        let mut entries_needing_summaries = Vec::new();
-        
-        // Read all date directories in the base directory
-        let mut dir_entries = fs::read_dir(&self.base_path).await?;
-        
-        while let Some(entry) = dir_entries.next_entry().await? {
-            if entry.file_type().await?.is_dir() {
-                let dir_name = entry.file_name();
-                let dir_name_str = dir_name.to_string_lossy();
-                
-                // Check if this is a valid date directory (5 characters)
-                if dir_name_str.len() == 5 {
-                    if let Ok(cycle_date) = CycleDate::from_string(&dir_name_str) {
-                        // Check if entry exists and summary doesn't
-                        let paths = self.get_file_paths(&cycle_date);
-                        if paths.entry.exists() && !paths.summary.exists() {
-                            entries_needing_summaries.push(cycle_date);
-                        }
-                    }
-                }
-            }
+    /// Add text to a day's entry, after a blank line when the entry already exists.
+    /// A draft in progress gets the text too, under a new revision so open editors reload
+    /// rather than overwrite it. Returns whether an existing entry was appended to.
+    pub async fn append_to_entry(&self, cycle_date: &CycleDate, text: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        let _guard = self.entry_lock.lock().await;
+        let now = Local::now();
+        let draft = self.load_draft(cycle_date).await?;
+        let existing = self.load_entry(cycle_date).await?;
+        let appended = existing.is_some();
+        let entry = match existing {
+            Some(entry) if !entry.content.trim().is_empty() => JournalEntry {
+                content: format!("{}\n\n{}", entry.content.trim_end(), text.trim()),
+                modified_at: now,
+                ..entry
+            },
+            _ => JournalEntry {
+                cycle_date: *cycle_date,
+                content: text.trim().to_string(),
+                created_at: now,
+                modified_at: now,
+            },
+        };
+        self.save_entry(&entry).await?;
+
+        if let Some(draft) = draft {
+            let _guard = self.draft_lock.lock().await;
+            let draft = JournalDraft {
+                content: format!("{}\n\n{}", draft.content.trim_end(), text.trim()),
+                revision: draft.revision + 1,
+                saved_at: now,
+                ..draft
+            };
+            fs::write(self.get_file_paths(cycle_date).draft, serde_json::to_string_pretty(&draft)?).await?;
         }
-        
-        Ok(entries_needing_summaries)
+        Ok(appended)
     }
 
-    /// Find entries that need status files
-    pub async fn find_entries_needing_status(&self) -> Result<Vec<CycleDate>, Box<dyn std::error::Error>> {
-        let mut entries_needing_status = Vec::new();
-        
-        // Read all date directories in the base directory
-        let mut dir_entries = fs::read_dir(&self.base_path).await?;
-        
-        while let Some(entry) = dir_entries.next_entry().await? {
-            if entry.file_type().await?.is_dir() {
-                let dir_name = entry.file_name();
-                let dir_name_str = dir_name.to_string_lossy();
-                
-                // Check if this is a valid date directory (5 characters)
-                if dir_name_str.len() == 5 {
-                    if let Ok(cycle_date) = CycleDate::from_string(&dir_name_str) {
-                        // Check if entry exists and status doesn't
-                        let paths = self.get_file_paths(&cycle_date);
-                        if paths.entry.exists() && !paths.status.exists() {
-                            entries_needing_status.push(cycle_date);
-                        }
-                    }
+    /// Save an entry only if it's unchanged since the writer loaded it. `base_revision` is
+    /// the revision the writer started from ("" if there was no entry yet), or None to
+    /// save unconditionally.
+    pub async fn save_entry_if_unchanged(
+        &self,
+        entry: &JournalEntry,
+        base_revision: Option<&str>,
+    ) -> Result<EntrySaveResult, Box<dyn std::error::Error>> {
+        let _guard = self.entry_lock.lock().await;
+
+        if let Some(base_revision) = base_revision {
+            if let Some(current) = self.load_entry(&entry.cycle_date).await? {
+                let revision = entry_revision(&current.content);
+                // Saving the same text twice (a double click, a retried request) isn't a conflict
+                if revision != base_revision && current.content != entry.content {
+                    return Ok(EntrySaveResult::Conflict { current, revision });
                 }
+            } else if !base_revision.is_empty() {
+                tracing::debug!("Entry for {} was removed since revision {}; saving anyway", entry.cycle_date, base_revision);
             }
         }
-        
-        Ok(entries_needing_status)
+
+        self.save_entry(entry).await?;
+        Ok(EntrySaveResult::Saved(entry_revision(&entry.content)))
     }
 
-    /// Get past entries for prompt generation based on prompt type
+    /// Store a file in a date's attachments directory, returning its sanitized name
+    pub async fn save_attachment(&self, cycle_date: &CycleDate, filename: &str, data: &[u8]) -> Result<String, Box<dyn std::error::Error>> {
+        let filename = sanitize_attachment_name(filename).ok_or("Invalid media file name")?;
+        let paths = self.get_file_paths(cycle_date);
+        fs::create_dir_all(&paths.attachments).await?;
+        fs::write(paths.attachments.join(&filename), data).await?;
+        Ok(filename)
+    }
+
+    /// Store an uploaded file in a date's attachments directory, as `filename` or, if that's
+    /// taken, `name-2.ext` and so on; never replaces a file. Returns the name it was saved as.
+    pub async fn add_attachment(&self, cycle_date: &CycleDate, filename: &str, data: &[u8]) -> Result<String, Box<dyn std::error::Error>> {
+        let filename = sanitize_attachment_name(filename).ok_or("Invalid attachment name")?;
+        let paths = self.get_file_paths(cycle_date);
+        fs::create_dir_all(&paths.attachments).await?;
+
+        let (stem, extension) = match filename.rsplit_once('.') {
+            Some((stem, extension)) => (stem, format!(".{}", extension)),
+            None => (filename.as_str(), String::new()),
+        };
+        let mut name = filename.clone();
+        for n in 2.. {
+            match fs::OpenOptions::new().write(true).create_new(true).open(paths.attachments.join(&name)).await {
+                Ok(mut file) => {
+                    file.write_all(data).await?;
+                    break;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => name = format!("{}-{}{}", stem, n, extension),
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        self.record_change(format!("Attach {} to {}", name, describe_date(cycle_date))).await;
+        Ok(name)
+    }
+
+    /// Names and sizes of the files in a date's attachments directory, sorted by name
+    pub async fn list_attachments(&self, cycle_date: &CycleDate) -> Result<Vec<(String, u64)>, Box<dyn std::error::Error>> {
+        let paths = self.get_file_paths(cycle_date);
+        if !paths.attachments.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut attachments = Vec::new();
+        let mut dir = fs::read_dir(&paths.attachments).await?;
+        while let Some(item) = dir.next_entry().await? {
+            let metadata = item.metadata().await?;
+            let Some(name) = item.file_name().to_str().and_then(sanitize_attachment_name) else {
+                continue;
+            };
+            if metadata.is_file() {
+                attachments.push((name, metadata.len()));
+            }
+        }
+        attachments.sort();
+        Ok(attachments)
+    }
+
+    /// Delete a file from a date's attachments directory, returning whether it was there
+    pub async fn remove_attachment(&self, cycle_date: &CycleDate, filename: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        let path = self.attachment_path(cycle_date, filename).ok_or("Invalid attachment name")?;
+        match fs::remove_file(&path).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e.into()),
+        }
+
+        self.record_change(format!("Remove attachment {} from {}", filename.trim(), describe_date(cycle_date))).await;
+        Ok(true)
+    }
+
+    /// Save an entry produced by OCR/speech-to-text along with its original media.
+    /// Transcriptions below `review_threshold` confidence are queued for review and
+    /// excluded from summary generation until approved.
+    pub async fn save_transcribed_entry(
+        &self,
+        entry: &JournalEntry,
+        media_filename: &str,
+        media: &[u8],
+        confidence: f32,
+        review_threshold: f32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let media_filename = self.save_attachment(&entry.cycle_date, media_filename, media).await?;
+        
+        self.save_entry(entry).await?;
+        self.mark_transcription(&entry.cycle_date, media_filename, confidence, review_threshold).await?;
+        
+        self.record_change(format!("Add transcribed entry for {}", describe_date(&entry.cycle_date))).await;
+        Ok(())
+    }
+
+    /// Add the transcript of a voice note already saved as an attachment to a day's entry,
+    /// appending when the entry exists. Low-confidence transcripts are queued for review
+    /// like transcribed entries. Returns whether an existing entry was appended to.
+    pub async fn append_transcription(
+        &self,
+        cycle_date: &CycleDate,
+        transcript: &str,
+        media_filename: &str,
+        confidence: f32,
+        review_threshold: f32,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let media_filename = sanitize_attachment_name(media_filename).ok_or("Invalid media file name")?;
+        let appended = self.append_to_entry(cycle_date, transcript).await?;
+        self.mark_transcription(cycle_date, media_filename, confidence, review_threshold).await?;
+        
+        self.record_change(format!("Add voice note for {}", describe_date(cycle_date))).await;
+        Ok(appended)
+    }
+
+    async fn mark_transcription(&self, cycle_date: &CycleDate, source_media: String, confidence: f32, review_threshold: f32) -> Result<(), Box<dyn std::error::Error>> {
+        let mut metadata = self.load_metadata(cycle_date).await?;
+        if let Some(entry_metadata) = metadata.entry.as_mut() {
+            entry_metadata.transcription = Some(TranscriptionMetadata {
+                source_media,
+                confidence,
+                reviewed: confidence >= review_threshold,
+            });
+        }
+        self.save_metadata(cycle_date, &metadata).await
+    }
+
+    /// List transcribed entries that are still waiting for review, oldest first
+    pub async fn find_transcriptions_needing_review(&self) -> Result<Vec<(CycleDate, TranscriptionMetadata)>, Box<dyn std::error::Error>> {
+        let mut pending = Vec::new();
+        
+        let dates = self.list_dates().await?;
+        for cycle_date in dates {
+            let transcription = self.load_metadata(&cycle_date).await?.entry.and_then(|m| m.transcription);
+            if let Some(transcription) = transcription {
+                if !transcription.reviewed {
+                    pending.push((cycle_date, transcription));
+                }
+            }
+        }
+        
+        Ok(pending)
+    }
+
+    /// Check whether a date's entry is an unreviewed transcription
+    pub async fn is_pending_review(&self, cycle_date: &CycleDate) -> bool {
+        match self.load_metadata(cycle_date).await {
+            Ok(metadata) => metadata.entry
+                .and_then(|m| m.transcription)
+                .is_some_and(|t| !t.reviewed),
+            Err(_) => false,
+        }
+    }
+
+    /// Approve a transcription, optionally replacing the entry text with a corrected version
+    pub async fn approve_transcription(&self, cycle_date: &CycleDate, corrected_content: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(content) = corrected_content {
+            let now = Local::now();
+            self.save_entry(&JournalEntry {
+                cycle_date: *cycle_date,
+                content,
+                created_at: now,
+                modified_at: now,
+            }).await?;
+        }
+        
+        let mut metadata = self.load_metadata(cycle_date).await?;
+        let transcription = metadata.entry.as_mut()
+            .and_then(|m| m.transcription.as_mut())
+            .ok_or("Entry is not a transcription")?;
+        transcription.reviewed = true;
+        self.save_metadata(cycle_date, &metadata).await?;
+        
+        self.record_change(format!("Approve transcription for {}", describe_date(cycle_date))).await;
+        Ok(())
+    }
+
+    /// Resolve an attachment file for a date, rejecting names that could escape the directory
+    pub fn attachment_path(&self, cycle_date: &CycleDate, filename: &str) -> Option<PathBuf> {
+        let filename = sanitize_attachment_name(filename)?;
+        Some(self.get_file_paths(cycle_date).attachments.join(filename))
+    }
+
+    /// Load a journal entry
+    pub async fn load_entry(&self, cycle_date: &CycleDate) -> Result<Option<JournalEntry>, Box<dyn std::error::Error>> {
+        let paths = self.get_file_paths(cycle_date);
+        
+        if !paths.entry.exists() {
+            return Ok(None);
+        }
+        
+        let content = fs::read_to_string(&paths.entry).await?;
+        
+        let entry_metadata = self.load_metadata(cycle_date).await?.entry;
+        let (created_at, modified_at) = match entry_metadata {
+            Some(metadata) => (metadata.created_at, metadata.modified_at),
+            None => file_timestamps(&paths.entry).await?,
+        };
+        
+        Ok(Some(JournalEntry {
+            cycle_date: *cycle_date,
+            content,
+            created_at,
+            modified_at,
+        }))
+    }
+
+    /// The entries written from `from` through `to`, in date order; days without one are skipped
+    pub async fn entries_in_range(&self, from: &CycleDate, to: &CycleDate) -> Result<Vec<JournalEntry>, Box<dyn std::error::Error>> {
+        let mut entries = Vec::new();
+        for cycle_date in CycleDate::range(*from, *to) {
+            if let Some(entry) = self.load_entry(&cycle_date).await? {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Save a journal summary
+    pub async fn save_summary(&self, summary: &JournalSummary) -> Result<(), Box<dyn std::error::Error>> {
+        self.save_summary_with_steering(summary, None).await
+    }
+
+    /// Save a journal summary, recording the steering instruction it was generated with
+    pub async fn save_summary_with_steering(&self, summary: &JournalSummary, steering: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+        self.ensure_date_directory(&summary.cycle_date).await?;
+        let paths = self.get_file_paths(&summary.cycle_date);
+        
+        let mut file = fs::File::create(&paths.summary).await?;
+        file.write_all(summary.summary.as_bytes()).await?;
+        self.update_index(&summary.cycle_date, |day| day.summary = true).await;
+        
+        let mut metadata = self.load_metadata(&summary.cycle_date).await?;
+        metadata.summary = Some(SummaryMetadata {
+            generated_at: summary.generated_at,
+            steering: steering.map(str::to_string),
+        });
+        self.save_metadata(&summary.cycle_date, &metadata).await?;
+        
+        self.record_change(with_steering_note(format!("Summarize {}", describe_date(&summary.cycle_date)), steering)).await;
+        Ok(())
+    }
+
+    /// Load a journal summary
+    pub async fn load_summary(&self, cycle_date: &CycleDate) -> Result<Option<JournalSummary>, Box<dyn std::error::Error>> {
+        let paths = self.get_file_paths(cycle_date);
+        
+        if !paths.summary.exists() {
+            return Ok(None);
+        }
+        
+        let summary = fs::read_to_string(&paths.summary).await?;
+        let summary_metadata = self.load_metadata(cycle_date).await?.summary;
+        let generated_at = match summary_metadata {
+            Some(metadata) => metadata.generated_at,
+            None => file_timestamps(&paths.summary).await?.0,
+        };
+        
+        Ok(Some(JournalSummary {
+            cycle_date: *cycle_date,
+            summary,
+            generated_at,
+        }))
+    }
+
+    /// Save the one-line summary that stands in for an old entry in long-horizon context
+    pub async fn save_one_line(&self, cycle_date: &CycleDate, one_line: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.ensure_date_directory(cycle_date).await?;
+        let paths = self.get_file_paths(cycle_date);
+        fs::write(&paths.one_line, one_line).await?;
+        self.update_index(cycle_date, |day| day.one_line = true).await;
+        self.record_change(format!("Compact summary of {}", describe_date(cycle_date))).await;
+        Ok(())
+    }
+
+    /// Load an entry's one-line summary
+    pub async fn load_one_line(&self, cycle_date: &CycleDate) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let paths = self.get_file_paths(cycle_date);
+        if !paths.one_line.exists() {
+            return Ok(None);
+        }
+        Ok(Some(fs::read_to_string(&paths.one_line).await?.trim().to_string()))
+    }
+
+    /// Summarized entries at least `ONE_LINE_AGE_DAYS` old without a one-line summary, oldest first
+    pub async fn find_entries_needing_one_lines(&self, today: &CycleDate) -> Result<Vec<CycleDate>, Box<dyn std::error::Error>> {
+        let cutoff = today.to_real_date() - chrono::Duration::days(ONE_LINE_AGE_DAYS);
+        let dates = self.with_index(|index| index.dates_where(|day| day.entry && day.summary && !day.one_line)).await?;
+        Ok(dates.into_iter().filter(|d| d.to_real_date() <= cutoff).collect())
+    }
+
+    /// One-line summaries of the years before the one a yearly reflection looks back on, a
+    /// line per month (its monthly reflection's, else its first entry's). As the journal
+    /// grows each year keeps fewer, evenly spread months, so the total stays bounded.
+    pub async fn long_horizon_context(&self, cycle_date: &CycleDate) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let cutoff = cycle_date.to_real_date() - chrono::Duration::days(ONE_LINE_AGE_DAYS);
+        let dates = self.with_index(|index| index.dates_where(|day| day.one_line)).await?;
+
+        // One date per (year, month), preferring the monthly reflection
+        let mut months: BTreeMap<(i32, u32), CycleDate> = BTreeMap::new();
+        for date in dates.into_iter().filter(|d| d.to_real_date() < cutoff) {
+            let slot = months.entry(date.month_key()).or_insert(date);
+            if date.is_first_day_of_month() {
+                *slot = date;
+            }
+        }
+        let mut years: BTreeMap<i32, Vec<CycleDate>> = BTreeMap::new();
+        for ((year, _), date) in months {
+            years.entry(year).or_default().push(date);
+        }
+        if years.is_empty() {
+            return Ok(None);
+        }
+
+        let per_year = (LONG_HORIZON_LINES / years.len()).max(1);
+        let mut lines = Vec::new();
+        let mut years: Vec<Vec<CycleDate>> = years.into_values().collect();
+        years.sort_by_key(|dates| dates[0].to_real_date());
+        for dates in years {
+            let step = dates.len().div_ceil(per_year);
+            for date in dates.iter().step_by(step) {
+                if let Some(one_line) = self.load_one_line(date).await? {
+                    lines.push(format!("- {}: {}", date.to_real_date().format("%B %Y"), one_line));
+                }
+            }
+        }
+        Ok(Some(format!("Earlier years, a line per month:\n{}", lines.join("\n"))))
+    }
+
+    /// Save a journal prompt
+    pub async fn save_prompt(&self, prompt: &JournalPrompt) -> Result<(), Box<dyn std::error::Error>> {
+        self.save_prompt_with_steering(prompt, None).await
+    }
+
+    /// Save a journal prompt, recording the steering instruction it was generated with
+    pub async fn save_prompt_with_steering(&self, prompt: &JournalPrompt, steering: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+        let provenance = PromptProvenance {
+            steering: steering.map(str::to_string),
+            ..Default::default()
+        };
+        self.save_prompt_with_provenance(prompt, &provenance).await
+    }
+
+    /// Save a journal prompt, recording how it was generated
+    pub async fn save_prompt_with_provenance(&self, prompt: &JournalPrompt, provenance: &PromptProvenance) -> Result<(), Box<dyn std::error::Error>> {
+        let steering = provenance.steering.as_deref();
+        self.ensure_date_directory(&prompt.cycle_date).await?;
+        let prompt_path = self.prompt_path(&prompt.cycle_date, prompt.prompt_number)?;
+        
+        let mut file = fs::File::create(&prompt_path).await?;
+        file.write_all(prompt.prompt.as_bytes()).await?;
+        self.update_index(&prompt.cycle_date, |day| {
+            day.prompts.insert(prompt.prompt_number);
+        }).await;
+        
+        let mut metadata = self.load_metadata(&prompt.cycle_date).await?;
+        metadata.prompts.insert(prompt.prompt_number, PromptMetadata {
+            generated_at: prompt.generated_at,
+            prompt_type: prompt.prompt_type.clone(),
+            expired_at: None,
+            steering: steering.map(str::to_string),
+            model: provenance.model.clone(),
+            ensemble: provenance.ensemble.clone(),
+        });
+        self.save_metadata(&prompt.cycle_date, &metadata).await?;
+        
+        let message = format!("Add prompt {} for {}", prompt.prompt_number, describe_date(&prompt.cycle_date));
+        self.record_change(with_steering_note(message, steering)).await;
+        self.announce(JournalChange::Prompt { cycle_date: prompt.cycle_date, prompt_number: prompt.prompt_number });
+        Ok(())
+    }
+
+    /// Replace a prompt with the other ensemble candidate the user preferred, recording
+    /// that model as the winner
+    pub async fn choose_ensemble_candidate(&self, cycle_date: &CycleDate, prompt_number: u8, model: &str) -> Result<JournalPrompt, Box<dyn std::error::Error>> {
+        let mut prompt = self.load_prompt(cycle_date, prompt_number).await?.ok_or("Prompt not found")?;
+        let mut metadata = self.load_metadata(cycle_date).await?;
+        let mut prompt_metadata = metadata.prompts.remove(&prompt_number).ok_or("Prompt has no metadata")?;
+        let mut ensemble = prompt_metadata.ensemble.take().ok_or("Prompt has no ensemble candidates")?;
+        let candidate = ensemble.candidates.iter().find(|c| c.model == model).ok_or("No candidate from that model")?;
+        
+        prompt.prompt = candidate.prompt.clone();
+        let prompt_path = self.prompt_path(cycle_date, prompt_number)?;
+        let mut file = fs::File::create(&prompt_path).await?;
+        file.write_all(prompt.prompt.as_bytes()).await?;
+        
+        ensemble.winner = Some(model.to_string());
+        ensemble.chosen_by = Some(EnsembleChooser::User);
+        prompt_metadata.model = Some(model.to_string());
+        prompt_metadata.ensemble = Some(ensemble);
+        metadata.prompts.insert(prompt_number, prompt_metadata);
+        self.save_metadata(cycle_date, &metadata).await?;
+        
+        self.record_change(format!("Choose {}'s prompt {} for {}", model, prompt_number, describe_date(cycle_date))).await;
+        Ok(prompt)
+    }
+
+    /// Candidates the user hasn't picked between yet, as (prompt number, the candidate not
+    /// currently shown), for offering the alternative beside each prompt
+    pub async fn undecided_ensemble_candidates(&self, cycle_date: &CycleDate) -> Vec<(u8, EnsembleCandidate)> {
+        let metadata = match self.load_metadata(cycle_date).await {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                tracing::warn!("Failed to load metadata for {}: {}", cycle_date, e);
+                return Vec::new();
+            }
+        };
+        metadata
+            .prompts
+            .into_iter()
+            .filter_map(|(number, prompt)| {
+                let ensemble = prompt.ensemble.filter(|e| e.winner.is_none())?;
+                let shown = prompt.model;
+                let alternative = ensemble.candidates.into_iter().find(|c| Some(&c.model) != shown.as_ref())?;
+                Some((number, alternative))
+            })
+            .collect()
+    }
+
+    /// File holding a prompt; prompts beyond 3 get additional files in the date directory
+    fn prompt_path(&self, cycle_date: &CycleDate, prompt_number: u8) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let paths = self.get_file_paths(cycle_date);
+        match prompt_number {
+            1 => Ok(paths.prompt1),
+            2 => Ok(paths.prompt2),
+            3 => Ok(paths.prompt3),
+            n if n > 3 => Ok(self.base_path.join(cycle_date.to_string()).join(format!("prompt{}.txt", n))),
+            _ => Err("Invalid prompt number".into()),
+        }
+    }
+
+    /// Load a journal prompt
+    pub async fn load_prompt(&self, cycle_date: &CycleDate, prompt_number: u8) -> Result<Option<JournalPrompt>, Box<dyn std::error::Error>> {
+        let prompt_path = self.prompt_path(cycle_date, prompt_number)?;
+        
+        if !prompt_path.exists() {
+            return Ok(None);
+        }
+        
+        let prompt = fs::read_to_string(&prompt_path).await?;
+        
+        // Prefer recorded metadata, falling back to the file time and the date's position in the cycle
+        let prompt_metadata = self.load_metadata(cycle_date).await?.prompts.remove(&prompt_number);
+        let (generated_at, prompt_type) = match prompt_metadata {
+            Some(metadata) => (metadata.generated_at, metadata.prompt_type),
+            None => (file_timestamps(&prompt_path).await?.0, PromptType::for_date(cycle_date)),
+        };
+        
+        Ok(Some(JournalPrompt {
+            cycle_date: *cycle_date,
+            prompt,
+            prompt_number,
+            generated_at,
+            prompt_type,
+        }))
+    }
+
+    /// The files `load_entry` reads, for telling whether it changed without loading it
+    pub fn entry_files(&self, cycle_date: &CycleDate) -> Vec<PathBuf> {
+        let paths = self.get_file_paths(cycle_date);
+        vec![paths.entry, paths.metadata]
+    }
+
+    /// The files `load_all_prompts` reads: each prompt up to the first missing one, and
+    /// the metadata
+    pub fn prompt_files(&self, cycle_date: &CycleDate) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for prompt_number in 1..=u8::MAX {
+            let Ok(path) = self.prompt_path(cycle_date, prompt_number) else {
+                break;
+            };
+            let exists = path.exists();
+            files.push(path);
+            if !exists {
+                break;
+            }
+        }
+        files.push(self.get_file_paths(cycle_date).metadata);
+        files
+    }
+
+    /// Load all consecutive prompts for a date, starting from prompt 1
+    pub async fn load_all_prompts(&self, cycle_date: &CycleDate) -> Vec<JournalPrompt> {
+        let mut prompts = Vec::new();
+        let mut prompt_number = 1;
+        loop {
+            match self.load_prompt(cycle_date, prompt_number).await {
+                Ok(Some(prompt)) => {
+                    prompts.push(prompt);
+                    prompt_number += 1;
+                }
+                Ok(None) => break, // No more prompts found
+                Err(_) => break,   // Error loading, stop trying
+            }
+        }
+        prompts
+    }
+
+    /// Prompt texts from the `days` days before `cycle_date` plus any already generated on it
+    pub async fn recent_prompts(&self, cycle_date: &CycleDate, days: u32) -> Vec<String> {
+        let mut prompts = Vec::new();
+        let from = CycleDate::from_real_date(cycle_date.to_real_date() - chrono::Duration::days(days as i64));
+        for date in CycleDate::range(from, *cycle_date) {
+            prompts.extend(self.load_all_prompts(&date).await.into_iter().map(|p| p.prompt));
+        }
+        prompts
+    }
+
+    /// Check whether a date's prompts were expired for going unanswered
+    pub async fn prompts_expired(&self, cycle_date: &CycleDate) -> bool {
+        match self.load_metadata(cycle_date).await {
+            Ok(metadata) => metadata.prompts.values().any(|p| p.expired_at.is_some()),
+            Err(_) => false,
+        }
+    }
+
+    /// Check whether a date was marked as needing no prompt
+    pub async fn no_prompt_needed(&self, cycle_date: &CycleDate) -> bool {
+        match self.load_metadata(cycle_date).await {
+            Ok(metadata) => metadata.no_prompt_since.is_some(),
+            Err(_) => false,
+        }
+    }
+
+    /// Mark a date as needing no prompt (or clear the mark), recording when it was set
+    pub async fn set_no_prompt_needed(&self, cycle_date: &CycleDate, no_prompt: bool) -> Result<(), Box<dyn std::error::Error>> {
+        let mut metadata = self.load_metadata(cycle_date).await?;
+        if metadata.no_prompt_since.is_some() == no_prompt {
+            return Ok(());
+        }
+        metadata.no_prompt_since = no_prompt.then(Local::now);
+        self.save_metadata(cycle_date, &metadata).await?;
+        let action = if no_prompt { "Skip prompts" } else { "Allow prompts" };
+        self.record_change(format!("{} for {}", action, cycle_date)).await;
+        Ok(())
+    }
+
+    /// Mark prompts expired on days more than `expiry_days` before `today` that never got an entry.
+    /// Returns the number of days whose prompts were newly expired.
+    pub async fn expire_stale_prompts(&self, today: &CycleDate, expiry_days: u32) -> Result<usize, Box<dyn std::error::Error>> {
+        let cutoff = today.to_real_date() - chrono::Duration::days(expiry_days as i64);
+        let now = Local::now();
+        let mut expired_days = 0;
+
+        let dates = self.list_dates().await?;
+        for cycle_date in dates {
+            if cycle_date.to_real_date() >= cutoff || self.get_file_paths(&cycle_date).entry.exists() {
+                continue;
+            }
+
+            let mut metadata = self.load_metadata(&cycle_date).await?;
+            if metadata.prompts.values().any(|p| p.expired_at.is_some()) {
+                continue;
+            }
+
+            // Prompts saved before metadata existed only have their text file
+            let prompts = self.load_all_prompts(&cycle_date).await;
+            for prompt in &prompts {
+                metadata.prompts.entry(prompt.prompt_number).or_insert_with(|| PromptMetadata {
+                    generated_at: prompt.generated_at,
+                    prompt_type: prompt.prompt_type.clone(),
+                    expired_at: None,
+                    steering: None,
+                    model: None,
+                    ensemble: None,
+                });
+            }
+            for prompt in metadata.prompts.values_mut() {
+                prompt.expired_at = Some(now);
+            }
+
+            if !metadata.prompts.is_empty() {
+                self.save_metadata(&cycle_date, &metadata).await?;
+                expired_days += 1;
+            }
+        }
+
+        Ok(expired_days)
+    }
+
+    /// Record which prompt an entry was written in response to
+    pub async fn record_answered_prompt(&self, cycle_date: &CycleDate, prompt_number: u8) -> Result<(), Box<dyn std::error::Error>> {
+        let mut metadata = self.load_metadata(cycle_date).await?;
+        if let Some(entry_metadata) = metadata.entry.as_mut() {
+            entry_metadata.answered_prompt = Some(prompt_number);
+            self.save_metadata(cycle_date, &metadata).await?;
+        }
+        Ok(())
+    }
+
+    /// Outcome of every unexpired prompt before `today`. An entry is credited to the prompt
+    /// that was on screen when it was saved, or to the first prompt for older entries.
+    pub async fn list_prompt_outcomes(&self, today: &CycleDate) -> Result<Vec<PromptOutcome>, Box<dyn std::error::Error>> {
+        let mut outcomes = Vec::new();
+
+        let dates = self.list_dates().await?;
+        for cycle_date in dates {
+            let metadata = self.load_metadata(&cycle_date).await?;
+            if metadata.prompts.values().any(|p| p.expired_at.is_some()) {
+                continue;
+            }
+
+            let entry_path = self.get_file_paths(&cycle_date).entry;
+            let has_entry = entry_path.exists();
+            // Today's prompts may still be answered
+            if !has_entry && cycle_date.to_real_date() >= today.to_real_date() {
+                continue;
+            }
+
+            let (entry_words, answered_prompt) = match (&metadata.entry, has_entry) {
+                (Some(entry), true) => (Some(entry.word_count), entry.answered_prompt.unwrap_or(1)),
+                (None, true) => (Some(count_words(&fs::read_to_string(&entry_path).await?)), 1),
+                _ => (None, 0),
+            };
+
+            for prompt in self.load_all_prompts(&cycle_date).await {
+                let answered = has_entry && prompt.prompt_number == answered_prompt;
+                let provenance = metadata.prompts.get(&prompt.prompt_number);
+                outcomes.push(PromptOutcome {
+                    cycle_date,
+                    prompt_type: prompt.prompt_type,
+                    prompt_number: prompt.prompt_number,
+                    prompt: prompt.prompt,
+                    answered,
+                    entry_words: if answered { entry_words } else { None },
+                    model: provenance.and_then(|p| p.model.clone()),
+                    // The saved prompt is always the winning candidate once one is picked
+                    ensemble_winner: provenance.and_then(|p| p.ensemble.as_ref()).is_some_and(|e| e.winner.is_some()),
+                });
+            }
+        }
+
+        Ok(outcomes)
+    }
+
+    /// Count days with (unexpired) prompts, and how many of those got an entry
+    pub async fn prompt_answer_counts(&self) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+        let mut prompted = 0;
+        let mut answered = 0;
+
+        let dates = self.list_dates().await?;
+        for cycle_date in dates {
+            let metadata = self.load_metadata(&cycle_date).await?;
+            if metadata.prompts.is_empty() || metadata.prompts.values().any(|p| p.expired_at.is_some()) {
+                continue;
+            }
+            prompted += 1;
+            if self.get_file_paths(&cycle_date).entry.exists() {
+                answered += 1;
+            }
+        }
+
+        Ok((prompted, answered))
+    }
+
+    /// Save the status recorded after a day's entry. The file holds the rendered
+    /// "Section: text" lines, so it stays readable in the journal directory.
+    pub async fn save_status(&self, status: &JournalStatus) -> Result<(), Box<dyn std::error::Error>> {
+        self.ensure_date_directory(&status.cycle_date).await?;
+        let paths = self.get_file_paths(&status.cycle_date);
+        
+        let mut file = fs::File::create(&paths.status).await?;
+        file.write_all(status.status.render().as_bytes()).await?;
+        self.update_index(&status.cycle_date, |day| day.status = true).await;
+        
+        let mut metadata = self.load_metadata(&status.cycle_date).await?;
+        metadata.status = Some(StatusMetadata {
+            generated_at: status.generated_at,
+        });
+        self.save_metadata(&status.cycle_date, &metadata).await?;
+        
+        self.record_change(format!("Update status after {}", describe_date(&status.cycle_date))).await;
+        self.announce(JournalChange::Status { cycle_date: status.cycle_date });
+        Ok(())
+    }
+
+    /// Load the status recorded after a day's entry. Free-text files from before the
+    /// status had sections load as notes.
+    pub async fn load_status(&self, cycle_date: &CycleDate) -> Result<Option<JournalStatus>, Box<dyn std::error::Error>> {
+        let paths = self.get_file_paths(cycle_date);
+        
+        if !paths.status.exists() {
+            return Ok(None);
+        }
+        
+        let text = fs::read_to_string(&paths.status).await?;
+        let status_metadata = self.load_metadata(cycle_date).await?.status;
+        let generated_at = match status_metadata {
+            Some(metadata) => metadata.generated_at,
+            None => file_timestamps(&paths.status).await?.0,
+        };
+        
+        Ok(Some(JournalStatus {
+            cycle_date: *cycle_date,
+            status: StructuredStatus::parse(&text),
+            generated_at,
+        }))
+    }
+
+    /// All recorded day statuses, oldest first
+    pub async fn list_statuses(&self) -> Result<Vec<JournalStatus>, Box<dyn std::error::Error>> {
+        let mut statuses = Vec::new();
+        
+        let dates = self.with_index(|index| index.dates_where(|day| day.status)).await?;
+        for cycle_date in dates {
+            if let Some(status) = self.load_status(&cycle_date).await? {
+                statuses.push(status);
+            }
+        }
+        
+        Ok(statuses)
+    }
+
+    /// The most recent day status recorded on or before `cycle_date`
+    pub async fn status_as_of(&self, cycle_date: &CycleDate) -> Result<Option<JournalStatus>, Box<dyn std::error::Error>> {
+        let statuses = self.list_statuses().await?;
+        Ok(statuses
+            .into_iter()
+            .rev()
+            .find(|status| status.cycle_date.to_real_date() <= cycle_date.to_real_date()))
+    }
+
+    /// List all dates that have a directory in the journal, oldest first
+    pub async fn list_dates(&self) -> Result<Vec<CycleDate>, Box<dyn std::error::Error>> {
+        self.with_index(JournalIndex::dates).await
+    }
+
+    /// List entries matching a filter, oldest first.
+    /// Which files each day has comes from the index; word counts come from the metadata
+    /// sidecar, and the entry text is only read for older entries saved before metadata existed.
+    pub async fn list_entries(&self, filter: &EntryFilter) -> Result<Vec<EntryListing>, Box<dyn std::error::Error>> {
+        let mut listings = Vec::new();
+        
+        let days = self.with_index(|index| {
+            index.dates()
+                .into_iter()
+                .filter(|d| filter.matches_date(d))
+                .map(|d| (d, index.days.get(&d.to_string()).cloned()))
+                .collect::<Vec<_>>()
+        }).await?;
+        for (cycle_date, artifacts) in days {
+            let paths = self.get_file_paths(&cycle_date);
+            // A day missing from the index is checked on disk
+            let (has_entry, has_summary, prompt_count) = match artifacts {
+                Some(day) => (day.entry, day.summary, day.prompt_count()),
+                None => (paths.entry.exists(), paths.summary.exists(), self.count_prompt_files(&cycle_date)),
+            };
+            if !has_entry || filter.has_summary.is_some_and(|wanted| wanted != has_summary) {
+                continue;
+            }
+            
+            let entry_metadata = self.load_metadata(&cycle_date).await?.entry;
+            let (word_count, modified_at) = match entry_metadata {
+                Some(metadata) => (metadata.word_count, Some(metadata.modified_at)),
+                None => {
+                    let content = fs::read_to_string(&paths.entry).await?;
+                    (count_words(&content), file_timestamps(&paths.entry).await.ok().map(|(_, modified)| modified))
+                }
+            };
+            
+            listings.push(EntryListing {
+                cycle_date,
+                real_date: cycle_date.to_real_date(),
+                entry_type: PromptType::for_date(&cycle_date),
+                word_count,
+                prompt_count,
+                has_summary,
+                modified_at,
+            });
+        }
+        
+        Ok(listings)
+    }
+
+    /// Count consecutive prompt files for a date without reading them
+    pub fn count_prompt_files(&self, cycle_date: &CycleDate) -> usize {
+        let date_dir = self.base_path.join(cycle_date.to_string());
+        (1..=u8::MAX)
+            .take_while(|n| date_dir.join(format!("prompt{}.txt", n)).exists())
+            .count()
+    }
+
+    /// Find entries that need summaries
+    /// Transcriptions awaiting review are skipped until approved, and entries whose summary
+    /// was retired in favor of a one-line summary aren't summarized again.
+    pub async fn find_entries_needing_summaries(&self) -> Result<Vec<CycleDate>, Box<dyn std::error::Error>> {
+        let mut entries_needing_summaries = Vec::new();
+        
+        let dates = self.with_index(|index| index.dates_where(|day| day.entry && !day.summary && !day.one_line)).await?;
+        for cycle_date in dates {
+            if !self.is_pending_review(&cycle_date).await {
+                entries_needing_summaries.push(cycle_date);
+            }
+        }
+        
+        Ok(entries_needing_summaries)
+    }
+
+    /// Find entries that need status files
+    /// Transcriptions awaiting review are skipped until approved.
+    pub async fn find_entries_needing_status(&self) -> Result<Vec<CycleDate>, Box<dyn std::error::Error>> {
+        let mut entries_needing_status = Vec::new();
+        
+        let dates = self.with_index(|index| index.dates_where(|day| day.entry && !day.status)).await?;
+        for cycle_date in dates {
+            if !self.is_pending_review(&cycle_date).await {
+                entries_needing_status.push(cycle_date);
+            }
+        }
+        
+        Ok(entries_needing_status)
+    }
+
+    /// Get past entries for prompt generation based on prompt type
     pub async fn get_context_for_prompt(&self, cycle_date: &CycleDate) -> Result<Vec<String>, Box<dyn std::error::Error>> {
         let mut context = Vec::new();
         
         if cycle_date.is_first_day_of_year() {
             // Get monthly reflections from past year
-            for month in 0..13 {
-                let mut past_date = *cycle_date;
-                past_date.year_cycle = if past_date.year_cycle > 0 { past_date.year_cycle - 1 } else { 99 };
-                past_date.month = month;
-                past_date.week = 0;
-                past_date.day = 0;
-                
+            for past_date in cycle_date.previous_period().into_iter().filter(CycleDate::is_first_day_of_month) {
                 if let Ok(Some(entry)) = self.load_entry(&past_date).await {
-                    context.push(format!("Month {} reflection: {}", month, entry.content));
+                    context.push(format!("Month {} reflection: {}", past_date.month_label(), entry.content));
                 }
             }
+            if let Ok(Some(earlier)) = self.long_horizon_context(cycle_date).await {
+                context.push(earlier);
+            }
         } else if cycle_date.is_first_day_of_month() {
             // Get weekly reflections from past month
-            for week in 0..4 {
-                let mut past_date = *cycle_date;
-                if past_date.month > 0 {
-                    past_date.month -= 1;
-                } else {
-                    past_date.month = 12;
-                    past_date.year_cycle = if past_date.year_cycle > 0 { past_date.year_cycle - 1 } else { 99 };
-                }
-                past_date.week = week;
-                past_date.day = 0;
-                
+            for past_date in cycle_date.previous_period().into_iter().filter(CycleDate::is_first_day_of_week) {
                 if let Ok(Some(entry)) = self.load_entry(&past_date).await {
-                    context.push(format!("Week {} reflection: {}", week, entry.content));
+                    context.push(format!("Week {} reflection: {}", past_date.week_label(), entry.content));
                 }
             }
         } else if cycle_date.is_first_day_of_week() {
             // Get full entries from past 7 days
             let past_week = cycle_date.previous_week();
-            for past_date in past_week {
-                if let Ok(Some(entry)) = self.load_entry(&past_date).await {
-                    context.push(format!("Day {}: {}", past_date.to_string(), entry.content));
-                }
+            for entry in self.entries_in_range(&past_week[0], cycle_date).await.unwrap_or_default() {
+                context.push(format!("Day {}: {}", entry.cycle_date, entry.content));
             }
         } else {
             // Get summaries from past 7 days
             let past_week = cycle_date.previous_week();
             for past_date in past_week {
                 if let Ok(Some(summary)) = self.load_summary(&past_date).await {
-                    context.push(format!("Day {}: {}", past_date.to_string(), summary.summary));
+                    context.push(format!("Day {}: {}", past_date, summary.summary));
+                }
+            }
+        }
+        
+        // Reflections look back over a whole period; show where the status stood when it began
+        // so the prompt can draw on what changed (the current status is added separately)
+        if let Some(period_start) = cycle_date.previous_period().first() {
+            // The last status recorded before the period's first day
+            let before_period = period_start.previous_day();
+            if let Ok(Some(status)) = self.status_as_of(&before_period).await {
+                if !status.status.is_empty() {
+                    context.push(format!("Status at the start of this period ({}):\n{}", status.cycle_date, status.status.render()));
                 }
             }
         }
@@ -360,8 +1618,563 @@ impl JournalManager {
 pub struct JournalFilePaths {
     pub entry: PathBuf,
     pub summary: PathBuf,
+    pub one_line: PathBuf,
     pub status: PathBuf,
+    pub metadata: PathBuf,
+    pub draft: PathBuf,
+    pub device_drafts: PathBuf,
+    pub attachments: PathBuf,
     pub prompt1: PathBuf,
     pub prompt2: PathBuf,
     pub prompt3: PathBuf,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_entry_metadata_preserves_creation_time() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        let cycle_date = CycleDate::new(1, 2, 1, 3).unwrap();
+        let created_at = Local::now() - chrono::Duration::days(2);
+        
+        let mut entry = JournalEntry {
+            cycle_date,
+            content: "First draft of the day".to_string(),
+            created_at,
+            modified_at: created_at,
+        };
+        manager.save_entry(&entry).await.unwrap();
+        
+        // Saving again with a fresh creation time must keep the original one
+        entry.content = "An edited entry with more words".to_string();
+        entry.created_at = Local::now();
+        entry.modified_at = Local::now();
+        manager.save_entry(&entry).await.unwrap();
+        
+        let loaded = manager.load_entry(&cycle_date).await.unwrap().unwrap();
+        assert_eq!(loaded.created_at, created_at);
+        assert_eq!(loaded.modified_at, entry.modified_at);
+        
+        let metadata = manager.load_metadata(&cycle_date).await.unwrap();
+        assert_eq!(metadata.entry.unwrap().word_count, 6);
+    }
+
+    #[tokio::test]
+    async fn test_entries_in_range() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        let first = CycleDate::new(1, 2, 3, 5).unwrap();
+        let third = CycleDate::new(1, 3, 0, 0).unwrap();
+        for (cycle_date, content) in [(first, "Before the month ends"), (third, "A new month")] {
+            let entry = JournalEntry { cycle_date, content: content.to_string(), created_at: Local::now(), modified_at: Local::now() };
+            manager.save_entry(&entry).await.unwrap();
+        }
+
+        let entries = manager.entries_in_range(&first, &third).await.unwrap();
+        assert_eq!(entries.iter().map(|e| e.cycle_date).collect::<Vec<_>>(), [first, third]);
+        assert!(manager.entries_in_range(&first.next_day(), &first.next_day()).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_changes_are_announced() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        let cycle_date = CycleDate::new(1, 2, 1, 3).unwrap();
+        let mut changes = manager.subscribe_changes();
+
+        let entry = JournalEntry { cycle_date, content: "Rained all day".to_string(), created_at: Local::now(), modified_at: Local::now() };
+        manager.save_entry(&entry).await.unwrap();
+        let prompt = JournalPrompt {
+            cycle_date,
+            prompt: "What kept you inside?".to_string(),
+            prompt_number: 1,
+            generated_at: Local::now(),
+            prompt_type: PromptType::Daily,
+        };
+        manager.save_prompt(&prompt).await.unwrap();
+
+        assert_eq!(changes.recv().await.unwrap(), JournalChange::Entry { cycle_date, revision: entry_revision("Rained all day") });
+        assert_eq!(changes.recv().await.unwrap(), JournalChange::Prompt { cycle_date, prompt_number: 1 });
+
+        manager.announce_day_started(cycle_date.next_day());
+        let change = changes.recv().await.unwrap();
+        assert_eq!(serde_json::to_value(&change).unwrap(), serde_json::json!({ "type": "day_started", "cycle_date": cycle_date.next_day().to_string() }));
+    }
+
+    #[tokio::test]
+    async fn test_prompt_metadata_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        let cycle_date = CycleDate::new(1, 2, 1, 3).unwrap();
+        let generated_at = Local::now() - chrono::Duration::hours(5);
+        
+        let prompt = JournalPrompt {
+            cycle_date,
+            prompt: "What surprised you today?".to_string(),
+            prompt_number: 2,
+            generated_at,
+            prompt_type: PromptType::WeeklyReflection,
+        };
+        manager.save_prompt(&prompt).await.unwrap();
+        
+        let loaded = manager.load_prompt(&cycle_date, 2).await.unwrap().unwrap();
+        assert_eq!(loaded.generated_at, generated_at);
+        assert!(matches!(loaded.prompt_type, PromptType::WeeklyReflection));
+    }
+
+    #[tokio::test]
+    async fn test_steering_recorded_in_provenance() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        let cycle_date = CycleDate::new(1, 2, 1, 3).unwrap();
+
+        let summary = JournalSummary {
+            cycle_date,
+            summary: "Long talk with mom about the garden.".to_string(),
+            generated_at: Local::now(),
+        };
+        manager.save_summary_with_steering(&summary, Some("focus on mom, not work")).await.unwrap();
+
+        let metadata = manager.load_metadata(&cycle_date).await.unwrap();
+        assert_eq!(metadata.summary.unwrap().steering.as_deref(), Some("focus on mom, not work"));
+
+        // A plain regeneration clears the old instruction
+        manager.save_summary(&summary).await.unwrap();
+        let metadata = manager.load_metadata(&cycle_date).await.unwrap();
+        assert!(metadata.summary.unwrap().steering.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_draft_conflict_detection() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        let cycle_date = CycleDate::new(1, 2, 1, 3).unwrap();
+        
+        let first = match manager.save_draft(&cycle_date, "Typed on the phone", None).await.unwrap() {
+            DraftSaveResult::Saved(draft) => draft,
+            DraftSaveResult::Conflict(_) => panic!("first draft should save"),
+        };
+        assert_eq!(first.revision, 1);
+        
+        // A second device that never saw the draft gets a conflict with the phone's version
+        match manager.save_draft(&cycle_date, "Typed on the laptop", None).await.unwrap() {
+            DraftSaveResult::Conflict(current) => assert_eq!(current.content, "Typed on the phone"),
+            DraftSaveResult::Saved(_) => panic!("stale save should conflict"),
+        }
+        
+        // Continuing from the latest revision succeeds
+        match manager.save_draft(&cycle_date, "Typed on the phone, continued", Some(1)).await.unwrap() {
+            DraftSaveResult::Saved(draft) => assert_eq!(draft.revision, 2),
+            DraftSaveResult::Conflict(_) => panic!("save from latest revision should succeed"),
+        }
+        
+        // Committing the entry clears the draft
+        let entry = JournalEntry {
+            cycle_date,
+            content: "Final entry".to_string(),
+            created_at: Local::now(),
+            modified_at: Local::now(),
+        };
+        manager.save_entry(&entry).await.unwrap();
+        assert!(manager.load_draft(&cycle_date).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_device_drafts() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        let cycle_date = CycleDate::new(1, 2, 1, 3).unwrap();
+        assert!(manager.load_device_drafts(&cycle_date).await.unwrap().is_empty());
+        
+        let first = manager.save_device_draft(&cycle_date, "Phone", "Started on the bus").await.unwrap();
+        manager.save_device_draft(&cycle_date, "Laptop", "At my desk").await.unwrap();
+        let continued = manager.save_device_draft(&cycle_date, "Phone", "Started on the bus, then home").await.unwrap();
+        assert_eq!(continued.started_at, first.started_at);
+        
+        // One heartbeat per device, the latest first
+        let drafts = manager.load_device_drafts(&cycle_date).await.unwrap();
+        assert_eq!(drafts.len(), 2);
+        assert_eq!(drafts[0], continued);
+        assert_eq!(drafts[1].device, "Laptop");
+        
+        // Saving the entry clears them
+        manager.save_entry(&JournalEntry {
+            cycle_date,
+            content: "Final entry".to_string(),
+            created_at: Local::now(),
+            modified_at: Local::now(),
+        }).await.unwrap();
+        assert!(manager.load_device_drafts(&cycle_date).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_entry_conflict_detection() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        let cycle_date = CycleDate::new(1, 2, 1, 3).unwrap();
+        let entry = |content: &str| JournalEntry {
+            cycle_date,
+            content: content.to_string(),
+            created_at: Local::now(),
+            modified_at: Local::now(),
+        };
+
+        // Both devices opened the day before any entry existed
+        let phone = match manager.save_entry_if_unchanged(&entry("From the phone"), Some("")).await.unwrap() {
+            EntrySaveResult::Saved(revision) => revision,
+            EntrySaveResult::Conflict { .. } => panic!("first save should succeed"),
+        };
+        assert_eq!(phone, entry_revision("From the phone"));
+
+        match manager.save_entry_if_unchanged(&entry("From the laptop"), Some("")).await.unwrap() {
+            EntrySaveResult::Conflict { current, revision } => {
+                assert_eq!(current.content, "From the phone");
+                assert_eq!(revision, phone);
+            }
+            EntrySaveResult::Saved(_) => panic!("stale save should conflict"),
+        }
+        assert_eq!(manager.load_entry(&cycle_date).await.unwrap().unwrap().content, "From the phone");
+
+        // Saving on top of the current revision, or without one, goes through
+        assert!(matches!(manager.save_entry_if_unchanged(&entry("Merged"), Some(&phone)).await.unwrap(), EntrySaveResult::Saved(_)));
+        assert!(matches!(manager.save_entry_if_unchanged(&entry("Forced"), None).await.unwrap(), EntrySaveResult::Saved(_)));
+    }
+
+    #[tokio::test]
+    async fn test_low_confidence_transcription_waits_for_review() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        let cycle_date = CycleDate::new(1, 2, 1, 3).unwrap();
+        let entry = JournalEntry {
+            cycle_date,
+            content: "Transcribed from a voice note".to_string(),
+            created_at: Local::now(),
+            modified_at: Local::now(),
+        };
+        
+        manager.save_transcribed_entry(&entry, "note.ogg", b"audio", 0.6, 0.85).await.unwrap();
+        
+        let pending = manager.find_transcriptions_needing_review().await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].1.source_media, "note.ogg");
+        assert!(manager.find_entries_needing_summaries().await.unwrap().is_empty());
+        
+        manager.approve_transcription(&cycle_date, Some("Corrected text".to_string())).await.unwrap();
+        
+        assert!(manager.find_transcriptions_needing_review().await.unwrap().is_empty());
+        assert_eq!(manager.find_entries_needing_summaries().await.unwrap(), vec![cycle_date]);
+        assert_eq!(manager.load_entry(&cycle_date).await.unwrap().unwrap().content, "Corrected text");
+    }
+
+    #[tokio::test]
+    async fn test_list_entries_with_filters() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        let week_start = CycleDate::new(1, 2, 1, 0).unwrap();
+        let midweek = CycleDate::new(1, 2, 1, 3).unwrap();
+        
+        for (cycle_date, content) in [(week_start, "Weekly look back"), (midweek, "A short daily note here")] {
+            manager.save_entry(&JournalEntry {
+                cycle_date,
+                content: content.to_string(),
+                created_at: Local::now(),
+                modified_at: Local::now(),
+            }).await.unwrap();
+        }
+        manager.save_summary(&JournalSummary {
+            cycle_date: midweek,
+            summary: "Short note".to_string(),
+            generated_at: Local::now(),
+        }).await.unwrap();
+        
+        let all = manager.list_entries(&EntryFilter::default()).await.unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].cycle_date, week_start);
+        assert_eq!(all[1].word_count, 5);
+        assert!(all[1].has_summary && !all[0].has_summary);
+        
+        // Listing goes by the index rather than what's on disk
+        std::fs::remove_file(manager.get_file_paths(&midweek).summary).unwrap();
+        assert!(manager.list_entries(&EntryFilter::default()).await.unwrap()[1].has_summary);
+        
+        let weekly = manager.list_entries(&EntryFilter {
+            entry_type: Some(PromptType::WeeklyReflection),
+            ..Default::default()
+        }).await.unwrap();
+        assert_eq!(weekly.len(), 1);
+        assert_eq!(weekly[0].cycle_date, week_start);
+        
+        let summarized = manager.list_entries(&EntryFilter {
+            has_summary: Some(true),
+            from: Some(week_start.next_day()),
+            ..Default::default()
+        }).await.unwrap();
+        assert_eq!(summarized.len(), 1);
+        assert_eq!(summarized[0].cycle_date, midweek);
+    }
+
+    #[tokio::test]
+    async fn test_status_history_and_reflection_context() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        let earlier = CycleDate::from_string("01205").unwrap();
+        let week_start = CycleDate::from_string("01220").unwrap();
+        
+        manager.save_status(&JournalStatus {
+            cycle_date: earlier,
+            status: StructuredStatus { work: "Job hunting".to_string(), ..Default::default() },
+            generated_at: Local::now(),
+        }).await.unwrap();
+        // A free-text status from before sections existed
+        tokio::fs::create_dir_all(temp_dir.path().join("01213")).await.unwrap();
+        tokio::fs::write(temp_dir.path().join("01213").join("status.txt"), "Moving house").await.unwrap();
+        assert_eq!(manager.rebuild_index().await.unwrap(), 2);
+        
+        let statuses = manager.list_statuses().await.unwrap();
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(statuses[0].status.work, "Job hunting");
+        assert!(manager.load_metadata(&earlier).await.unwrap().status.is_some());
+        assert_eq!(statuses[1].status.notes, "Moving house");
+        
+        let as_of = manager.status_as_of(&CycleDate::from_string("01212").unwrap()).await.unwrap().unwrap();
+        assert_eq!(as_of.cycle_date, earlier);
+        
+        let context = manager.get_context_for_prompt(&week_start).await.unwrap();
+        assert_eq!(context.last().unwrap(), "Status at the start of this period (01205):\nWork: Job hunting");
+    }
+
+    #[tokio::test]
+    async fn test_one_lines_and_long_horizon_context() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        let start = CycleDate::new(0, 0, 0, 0).unwrap().to_real_date();
+        let day = |days: i64| CycleDate::from_real_date(start + chrono::Duration::days(days));
+        let today = day(800);
+
+        for date in [day(300), day(790)] {
+            manager.save_entry(&JournalEntry {
+                cycle_date: date,
+                content: "Entry".to_string(),
+                created_at: Local::now(),
+                modified_at: Local::now(),
+            }).await.unwrap();
+            manager.save_summary(&JournalSummary {
+                cycle_date: date,
+                summary: "Summary".to_string(),
+                generated_at: Local::now(),
+            }).await.unwrap();
+        }
+        assert_eq!(manager.find_entries_needing_one_lines(&today).await.unwrap(), vec![day(300)]);
+        manager.save_one_line(&day(300), "Started the garden\n").await.unwrap();
+        assert!(manager.find_entries_needing_one_lines(&today).await.unwrap().is_empty());
+        assert_eq!(manager.load_one_line(&day(300)).await.unwrap().as_deref(), Some("Started the garden"));
+        let context = manager.long_horizon_context(&today).await.unwrap().unwrap();
+        assert!(context.ends_with("Started the garden"));
+
+        // Forty years of weekly one-liners still fit the bound, with every year represented
+        for week in 0..(40 * 52) {
+            let dir = temp_dir.path().join(day(week * 7).to_string());
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("oneline.txt"), "A week").unwrap();
+        }
+        manager.rebuild_index().await.unwrap();
+        let context = manager.long_horizon_context(&day(41 * 364)).await.unwrap().unwrap();
+        let lines = context.lines().skip(1).count();
+        assert!((40..=LONG_HORIZON_LINES + 40).contains(&lines), "{} lines", lines);
+        assert!(manager.long_horizon_context(&day(10)).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_sanitize_attachment_name() {
+        assert_eq!(sanitize_attachment_name("photo-1.jpg"), Some("photo-1.jpg".to_string()));
+        assert_eq!(sanitize_attachment_name("../entry.txt"), None);
+        assert_eq!(sanitize_attachment_name("dir/file.png"), None);
+        assert_eq!(sanitize_attachment_name(".hidden"), None);
+    }
+
+    #[test]
+    fn test_entry_date_editable() {
+        let today = CycleDate::new(1, 2, 1, 3).unwrap();
+        let yesterday = today.previous_day();
+        let last_month = CycleDate::new(1, 1, 1, 3).unwrap();
+        
+        assert!(check_entry_date_editable(&today, &today, Some(0)).is_ok());
+        assert!(check_entry_date_editable(&yesterday, &today, None).is_ok());
+        assert!(check_entry_date_editable(&yesterday, &today, Some(1)).is_ok());
+        assert!(check_entry_date_editable(&last_month, &today, Some(7)).is_err());
+        assert!(check_entry_date_editable(&today.next_day(), &today, None).is_err());
+        assert!(is_entry_locked(&last_month, &today, Some(7)));
+        assert!(!is_entry_locked(&yesterday, &today, Some(1)));
+        assert!(!is_entry_locked(&last_month, &today, None));
+    }
+
+    #[tokio::test]
+    async fn test_load_without_metadata_falls_back_to_filesystem() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        let cycle_date = CycleDate::new(0, 0, 0, 4).unwrap();
+        
+        let date_dir = temp_dir.path().join(cycle_date.to_string());
+        std::fs::create_dir_all(&date_dir).unwrap();
+        std::fs::write(date_dir.join("entry.txt"), "Restored from an old backup").unwrap();
+        
+        let loaded = manager.load_entry(&cycle_date).await.unwrap().unwrap();
+        assert_eq!(loaded.content, "Restored from an old backup");
+    }
+
+    #[tokio::test]
+    async fn test_expire_stale_prompts() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        let answered = CycleDate::new(1, 2, 1, 2).unwrap();
+        let unanswered = CycleDate::new(1, 2, 1, 3).unwrap();
+        let recent = CycleDate::new(1, 2, 2, 3).unwrap();
+        let today = CycleDate::new(1, 2, 2, 4).unwrap();
+
+        for cycle_date in [answered, unanswered, recent] {
+            manager.save_prompt(&JournalPrompt {
+                cycle_date,
+                prompt: "What surprised you?".to_string(),
+                prompt_number: 1,
+                generated_at: Local::now(),
+                prompt_type: PromptType::Daily,
+            }).await.unwrap();
+        }
+        manager.save_entry(&JournalEntry {
+            cycle_date: answered,
+            content: "Answered".to_string(),
+            created_at: Local::now(),
+            modified_at: Local::now(),
+        }).await.unwrap();
+
+        assert_eq!(manager.expire_stale_prompts(&today, 3).await.unwrap(), 1);
+        assert!(manager.prompts_expired(&unanswered).await);
+        assert!(!manager.prompts_expired(&answered).await);
+        assert!(!manager.prompts_expired(&recent).await);
+
+        // Running again finds nothing new, and expired days drop out of answer stats
+        assert_eq!(manager.expire_stale_prompts(&today, 3).await.unwrap(), 0);
+        assert_eq!(manager.prompt_answer_counts().await.unwrap(), (2, 1));
+    }
+
+    #[tokio::test]
+    async fn test_no_prompt_needed() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        let cycle_date = CycleDate::new(1, 3, 0, 0).unwrap();
+
+        assert!(!manager.no_prompt_needed(&cycle_date).await);
+        manager.set_no_prompt_needed(&cycle_date, true).await.unwrap();
+        let marked_at = manager.load_metadata(&cycle_date).await.unwrap().no_prompt_since;
+        assert!(marked_at.is_some());
+
+        // Marking again keeps the original time, and writing the entry keeps the mark
+        manager.set_no_prompt_needed(&cycle_date, true).await.unwrap();
+        manager.save_entry(&JournalEntry {
+            cycle_date,
+            content: "I knew what to write.".to_string(),
+            created_at: Local::now(),
+            modified_at: Local::now(),
+        }).await.unwrap();
+        assert_eq!(manager.load_metadata(&cycle_date).await.unwrap().no_prompt_since, marked_at);
+
+        manager.set_no_prompt_needed(&cycle_date, false).await.unwrap();
+        assert!(!manager.no_prompt_needed(&cycle_date).await);
+    }
+
+    #[tokio::test]
+    async fn test_choose_ensemble_candidate() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        let cycle_date = CycleDate::new(1, 2, 1, 2).unwrap();
+        let prompt = JournalPrompt {
+            cycle_date,
+            prompt: "What surprised you?".to_string(),
+            prompt_number: 1,
+            generated_at: Local::now(),
+            prompt_type: PromptType::Daily,
+        };
+        let provenance = PromptProvenance {
+            model: Some("llama3".to_string()),
+            ensemble: Some(EnsembleRecord {
+                candidates: vec![
+                    EnsembleCandidate { model: "llama3".to_string(), prompt: prompt.prompt.clone() },
+                    EnsembleCandidate { model: "mistral".to_string(), prompt: "Who did you miss today?".to_string() },
+                ],
+                winner: None,
+                chosen_by: None,
+            }),
+            ..Default::default()
+        };
+        manager.save_prompt_with_provenance(&prompt, &provenance).await.unwrap();
+
+        let alternatives = manager.undecided_ensemble_candidates(&cycle_date).await;
+        assert_eq!(alternatives.len(), 1);
+        assert_eq!(alternatives[0].1.model, "mistral");
+
+        let chosen = manager.choose_ensemble_candidate(&cycle_date, 1, "mistral").await.unwrap();
+        assert_eq!(chosen.prompt, "Who did you miss today?");
+        assert_eq!(manager.load_prompt(&cycle_date, 1).await.unwrap().unwrap().prompt, chosen.prompt);
+        assert!(manager.undecided_ensemble_candidates(&cycle_date).await.is_empty());
+
+        let metadata = manager.load_metadata(&cycle_date).await.unwrap();
+        let ensemble = metadata.prompts[&1].ensemble.clone().unwrap();
+        assert_eq!(ensemble.winner.as_deref(), Some("mistral"));
+        assert_eq!(ensemble.chosen_by, Some(EnsembleChooser::User));
+        assert!(manager.choose_ensemble_candidate(&cycle_date, 1, "gemma").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_append_to_entry() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        let cycle_date = CycleDate::new(1, 2, 1, 2).unwrap();
+
+        assert!(!manager.append_to_entry(&cycle_date, "Morning pages.\n").await.unwrap());
+        manager.save_draft(&cycle_date, "Morning pages. And", None).await.unwrap();
+        assert!(manager.append_to_entry(&cycle_date, "Sent from the train.").await.unwrap());
+
+        let entry = manager.load_entry(&cycle_date).await.unwrap().unwrap();
+        assert_eq!(entry.content, "Morning pages.\n\nSent from the train.");
+        let draft = manager.load_draft(&cycle_date).await.unwrap().unwrap();
+        assert_eq!(draft.content, "Morning pages. And\n\nSent from the train.");
+        assert_eq!(draft.revision, 2);
+
+        // A voice note's transcript is appended too, and queued for review when unsure
+        manager.save_attachment(&cycle_date, "voice-081500.webm", b"audio").await.unwrap();
+        assert!(manager.append_transcription(&cycle_date, "Heard an owl.", "voice-081500.webm", 0.5, 0.85).await.unwrap());
+        assert!(manager.load_entry(&cycle_date).await.unwrap().unwrap().content.ends_with("\n\nHeard an owl."));
+        let pending = manager.find_transcriptions_needing_review().await.unwrap();
+        assert_eq!(pending[0].1.source_media, "voice-081500.webm");
+    }
+
+    #[tokio::test]
+    async fn test_attachments() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        let cycle_date = CycleDate::new(1, 2, 1, 2).unwrap();
+        assert!(manager.list_attachments(&cycle_date).await.unwrap().is_empty());
+
+        // A taken name gets a number rather than replacing the file
+        assert_eq!(manager.add_attachment(&cycle_date, "beach.jpg", b"first").await.unwrap(), "beach.jpg");
+        assert_eq!(manager.add_attachment(&cycle_date, "beach.jpg", b"second").await.unwrap(), "beach-2.jpg");
+        assert!(manager.add_attachment(&cycle_date, "../beach.jpg", b"x").await.is_err());
+
+        // Hidden files and folders, like thumbnails, aren't listed
+        let attachments = manager.get_file_paths(&cycle_date).attachments;
+        std::fs::create_dir_all(attachments.join(".thumbnails")).unwrap();
+        std::fs::write(attachments.join(".thumbnails/beach.jpg.jpg"), b"thumb").unwrap();
+        assert_eq!(
+            manager.list_attachments(&cycle_date).await.unwrap(),
+            vec![("beach-2.jpg".to_string(), 6), ("beach.jpg".to_string(), 5)]
+        );
+
+        assert!(manager.remove_attachment(&cycle_date, "beach.jpg").await.unwrap());
+        assert!(!manager.remove_attachment(&cycle_date, "beach.jpg").await.unwrap());
+        assert_eq!(manager.list_attachments(&cycle_date).await.unwrap().len(), 1);
+    }
+}
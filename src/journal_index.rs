@@ -0,0 +1,160 @@
+use crate::cycle_date::{CalendarMode, CycleDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use tokio::fs;
+
+const INDEX_FILE: &str = "index.json";
+/// Bumped when the index gains fields that a rescan has to fill in
+const INDEX_VERSION: u32 = 2;
+
+/// Which files exist for one day
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DayArtifacts {
+    pub entry: bool,
+    pub summary: bool,
+    pub status: bool,
+    /// Compact one-line summary written once the entry is a year old
+    pub one_line: bool,
+    /// Numbers of the prompt files present
+    pub prompts: BTreeSet<u8>,
+}
+
+impl DayArtifacts {
+    /// Number of consecutive prompts from prompt 1, as `load_all_prompts` reads them
+    pub fn prompt_count(&self) -> usize {
+        (1..=u8::MAX).take_while(|n| self.prompts.contains(n)).count()
+    }
+}
+
+/// Record of every date directory and the artifacts in it, kept in `index.json` in the
+/// journal directory so listing dates and finding missing summaries doesn't stat every day.
+/// Updated by `JournalManager` on each save. Derived data: deleting it triggers a rescan.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JournalIndex {
+    pub version: u32,
+    /// Calendar the directories were named in when the index was built
+    #[serde(default)]
+    pub calendar: CalendarMode,
+    pub days: BTreeMap<String, DayArtifacts>,
+}
+
+impl JournalIndex {
+    /// Load the index from a journal directory; None if it's missing, unreadable, or outdated
+    pub async fn load(base_path: &Path) -> Option<Self> {
+        let json = fs::read_to_string(base_path.join(INDEX_FILE)).await.ok()?;
+        match serde_json::from_str::<Self>(&json) {
+            Ok(index) if index.version == INDEX_VERSION && index.calendar == crate::cycle_date::calendar() => Some(index),
+            Ok(_) => None,
+            Err(e) => {
+                tracing::warn!("Ignoring unreadable journal index: {}", e);
+                None
+            }
+        }
+    }
+
+    pub async fn save(&self, base_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(base_path.join(INDEX_FILE), serde_json::to_string(self)?).await?;
+        Ok(())
+    }
+
+    /// Build the index by scanning every date directory
+    pub async fn scan(base_path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut index = Self {
+            version: INDEX_VERSION,
+            calendar: crate::cycle_date::calendar(),
+            days: BTreeMap::new(),
+        };
+        if !base_path.exists() {
+            return Ok(index);
+        }
+
+        let mut dir_entries = fs::read_dir(base_path).await?;
+        while let Some(entry) = dir_entries.next_entry().await? {
+            let name = entry.file_name().to_string_lossy().to_string();
+            // Directories named for the other calendar are left for the doctor to rename
+            let canonical = CycleDate::from_string(&name).is_ok_and(|d| d.to_string() == name);
+            if !entry.file_type().await?.is_dir() || !canonical {
+                continue;
+            }
+
+            let mut artifacts = DayArtifacts::default();
+            let mut files = fs::read_dir(entry.path()).await?;
+            while let Some(file) = files.next_entry().await? {
+                let file_name = file.file_name().to_string_lossy().to_string();
+                match file_name.as_str() {
+                    "entry.txt" => artifacts.entry = true,
+                    "summary.txt" => artifacts.summary = true,
+                    "status.txt" => artifacts.status = true,
+                    "oneline.txt" => artifacts.one_line = true,
+                    _ => {
+                        let number = file_name.strip_prefix("prompt").and_then(|rest| rest.strip_suffix(".txt"));
+                        if let Some(number) = number.and_then(|n| n.parse().ok()) {
+                            artifacts.prompts.insert(number);
+                        }
+                    }
+                }
+            }
+            index.days.insert(name, artifacts);
+        }
+        Ok(index)
+    }
+
+    /// Dates with a directory, oldest first
+    pub fn dates(&self) -> Vec<CycleDate> {
+        let mut dates: Vec<CycleDate> = self.days.keys().filter_map(|d| CycleDate::from_string(d).ok()).collect();
+        dates.sort_by_key(|d| d.to_real_date());
+        dates
+    }
+
+    /// Dates whose artifacts match `predicate`, oldest first
+    pub fn dates_where(&self, predicate: impl Fn(&DayArtifacts) -> bool) -> Vec<CycleDate> {
+        self.dates()
+            .into_iter()
+            .filter(|d| self.days.get(&d.to_string()).is_some_and(&predicate))
+            .collect()
+    }
+}
+
+/// Delete a journal directory's index after its files were changed behind the manager's
+/// back (e.g. a restore), so the next load rescans
+pub fn invalidate(base_path: &Path) {
+    let path = base_path.join(INDEX_FILE);
+    if path.exists() {
+        if let Err(e) = std::fs::remove_file(&path) {
+            tracing::warn!("Failed to remove stale journal index {}: {}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_scan_and_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let day = temp_dir.path().join("01213");
+        std::fs::create_dir_all(&day).unwrap();
+        for file in ["entry.txt", "summary.txt", "prompt1.txt", "prompt4.txt", "meta.json"] {
+            std::fs::write(day.join(file), "").unwrap();
+        }
+        std::fs::create_dir_all(temp_dir.path().join("01210")).unwrap();
+        std::fs::create_dir_all(temp_dir.path().join("reports")).unwrap();
+
+        let index = JournalIndex::scan(temp_dir.path()).await.unwrap();
+        assert_eq!(index.dates().iter().map(|d| d.to_string()).collect::<Vec<_>>(), vec!["01210", "01213"]);
+        let artifacts = &index.days["01213"];
+        assert!(artifacts.entry && artifacts.summary && !artifacts.status);
+        assert_eq!(artifacts.prompts, BTreeSet::from([1, 4]));
+        assert_eq!(artifacts.prompt_count(), 1);
+        assert_eq!(index.dates_where(|a| a.entry && !a.status).len(), 1);
+
+        index.save(temp_dir.path()).await.unwrap();
+        assert_eq!(JournalIndex::load(temp_dir.path()).await.unwrap().days, index.days);
+        invalidate(temp_dir.path());
+        assert!(JournalIndex::load(temp_dir.path()).await.is_none());
+    }
+}
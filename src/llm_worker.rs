@@ -8,11 +8,13 @@ use std::process::Command;
 // Ollama integration for LLM inference
 use ollama_rs::Ollama;
 use ollama_rs::generation::completion::request::GenerationRequest;
+use ollama_rs::generation::embeddings::request::GenerateEmbeddingsRequest;
 use ollama_rs::models::ModelOptions;
 
 /// LLM Worker for Ollama-based model inference
 pub struct LlmWorker {
     model_name: String,
+    embedding_model: String,
     temperature: f32,
     ollama_client: Ollama,
     is_connected: Arc<Mutex<bool>>,
@@ -34,12 +36,35 @@ impl LlmWorker {
         
         Ok(Self {
             model_name,
+            embedding_model: "nomic-embed-text".to_string(),
             temperature,
             ollama_client,
             is_connected: Arc::new(Mutex::new(false)),
         })
     }
 
+    /// Use a different Ollama model for embeddings
+    pub fn with_embedding_model(mut self, embedding_model: String) -> Self {
+        self.embedding_model = embedding_model;
+        self
+    }
+
+    /// A worker for another Ollama model, sharing this one's connection settings
+    pub fn for_model(&self, model_name: &str) -> Self {
+        Self {
+            model_name: model_name.to_string(),
+            embedding_model: self.embedding_model.clone(),
+            temperature: self.temperature,
+            ollama_client: self.ollama_client.clone(),
+            is_connected: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    /// Ollama model this worker generates with
+    pub fn model_name(&self) -> &str {
+        &self.model_name
+    }
+
     /// Extract model name from file path for Ollama
     fn extract_model_name(model_path: &str) -> Result<String, Box<dyn std::error::Error>> {
         // For now, we'll use a simple mapping. User might need to import the model into Ollama
@@ -183,14 +208,37 @@ impl LlmWorker {
         }
     }
     
+    /// Embed texts with the embedding model, one vector per text
+    pub async fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, Box<dyn std::error::Error>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        if !self.is_model_loaded().await {
+            self.load_model().await?;
+        }
+
+        let count = texts.len();
+        let request = GenerateEmbeddingsRequest::new(self.embedding_model.clone(), texts.into());
+        match self.ollama_client.generate_embeddings(request).await {
+            Ok(response) if response.embeddings.len() == count => Ok(response.embeddings),
+            Ok(response) => Err(format!("Expected {} embeddings from Ollama, got {}", count, response.embeddings.len()).into()),
+            Err(e) => {
+                tracing::error!("Ollama embedding with '{}' failed: {}", self.embedding_model, e);
+                Err(format!("Ollama embedding failed (is '{}' pulled?): {}", self.embedding_model, e).into())
+            }
+        }
+    }
+    
     /// Generate a summary for a journal entry
     pub async fn generate_summary(
         &self, 
         entry_content: &str, 
         cycle_date: &CycleDate,
         personalization_config: &crate::personalization::PersonalizationConfig,
+        steering: Option<&str>,
     ) -> Result<JournalSummary, Box<dyn std::error::Error>> {
-        let prompt = personalization_config.prompts.get_summary_prompt(entry_content);
+        let prompt = personalization_config.with_language(personalization_config.prompts.get_summary_prompt(entry_content));
+        let prompt = with_steering(prompt, steering);
         
         let summary = self.generate_text(&prompt, 100).await?;
         
@@ -201,6 +249,36 @@ impl LlmWorker {
         })
     }
     
+    /// Compress an old entry's summary to a single line for long-horizon context
+    pub async fn generate_one_line_summary(
+        &self,
+        summary: &str,
+        cycle_date: &CycleDate,
+        personalization_config: &crate::personalization::PersonalizationConfig,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let date = cycle_date.to_real_date().format("%B %-d, %Y").to_string();
+        let prompt = personalization_config.with_language(personalization_config.prompts.get_one_line_summary_prompt(&date, summary));
+        let response = self.generate_text(&prompt, 30).await?;
+
+        // Models sometimes add a preamble line or wrap the answer in quotes
+        let line = response
+            .lines()
+            .map(|line| line.trim().trim_matches(|c: char| c == '"' || c == '*'))
+            .rfind(|line| !line.is_empty())
+            .ok_or("The model returned an empty one-line summary")?;
+        Ok(line.to_string())
+    }
+
+    /// Write one month's chapter of the year in review from a filled-in chapter prompt
+    pub async fn generate_year_review_chapter(&self, prompt: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let chapter = self.generate_text(prompt, 1200).await?;
+        let chapter = chapter.trim();
+        if chapter.is_empty() {
+            return Err("The model returned an empty chapter".into());
+        }
+        Ok(chapter.to_string())
+    }
+
     /// Generate both summary and status update for a journal entry
     pub async fn generate_summary_with_status_update(
         &self,
@@ -209,15 +287,19 @@ impl LlmWorker {
         personalization_config: &mut crate::personalization::PersonalizationConfig,
     ) -> Result<(JournalSummary, Option<String>), Box<dyn std::error::Error>> {
         // First generate the summary
-        let summary = self.generate_summary(entry_content, cycle_date, personalization_config).await?;
+        let summary = self.generate_summary(entry_content, cycle_date, personalization_config, None).await?;
         
         // Generate status update based on the entry and current status
         let status_update = self.generate_status_update(entry_content, personalization_config).await?;
         
-        // Update the personalization config with new status
-        if let Some(ref new_status) = status_update {
-            personalization_config.update_status(new_status.clone())?;
-        }
+        // Apply the changed sections; the full updated status is returned for the day's record
+        let status_update = match status_update {
+            Some(update) => {
+                let changed = personalization_config.update_status(&update)?;
+                (!changed.is_empty()).then(|| personalization_config.status.render())
+            }
+            None => None,
+        };
         
         Ok((summary, status_update))
     }
@@ -228,13 +310,11 @@ impl LlmWorker {
         entry_content: &str,
         personalization_config: &crate::personalization::PersonalizationConfig,
     ) -> Result<Option<String>, Box<dyn std::error::Error>> {
-        let current_status = personalization_config.get_current_status()
-            .map(|s| s.as_str())
-            .unwrap_or("No previous status recorded.");
+        let current_status = personalization_config.current_status().render();
+        let current_status = if current_status.is_empty() { "No previous status recorded." } else { &current_status };
         
         let user_profile = personalization_config.profile
-            .as_ref()
-            .map(|s| s.as_str())
+            .as_deref()
             .unwrap_or("No profile information available.");
         
         let prompt = personalization_config.prompts.get_status_update_prompt(user_profile, current_status, entry_content);
@@ -242,7 +322,7 @@ impl LlmWorker {
         let response = self.generate_text(&prompt, 200).await?;
         let response = response.trim();
         
-        if response == "NO_UPDATE_NEEDED" || response.is_empty() {
+        if crate::prompts::PromptsConfig::is_no_status_update(response) {
             tracing::info!(" No status update needed for today's entry");
             Ok(None)
         } else {
@@ -259,21 +339,13 @@ impl LlmWorker {
         prompt_number: u8,
         prompt_type: PromptType,
         personalization_config: &crate::personalization::PersonalizationConfig,
+        steering: Option<&str>,
     ) -> Result<JournalPrompt, Box<dyn std::error::Error>> {
         let context_str = context.join("\n\n");
         
         // Enrich context with user profile and style information
         let enriched_context = personalization_config.enrich_context(&context_str);
-        
-        let system_prompt = personalization_config.prompts.get_prompt_template(&prompt_type, &enriched_context);
-
-        // Add variation for multiple prompts
-        let variation_suffix = personalization_config.prompts.get_variation_suffix(prompt_number);
-        let variation_prompt = if variation_suffix.is_empty() {
-            system_prompt
-        } else {
-            format!("{}{}", system_prompt, variation_suffix)
-        };
+        let variation_prompt = build_prompt_request(&enriched_context, cycle_date, prompt_number, &prompt_type, personalization_config, steering);
         
         let generated_prompt = self.generate_text(&variation_prompt, 150).await?;
         
@@ -287,15 +359,56 @@ impl LlmWorker {
     }
 }
 
+/// The full text sent to generate a prompt: the template around the enriched context, plus
+/// the variation for additional prompts, the generation language, and any steering
+pub fn build_prompt_request(
+    enriched_context: &str,
+    cycle_date: &CycleDate,
+    prompt_number: u8,
+    prompt_type: &PromptType,
+    personalization_config: &crate::personalization::PersonalizationConfig,
+    steering: Option<&str>,
+) -> String {
+    let system_prompt = personalization_config.prompts.get_prompt_template(prompt_type, cycle_date, enriched_context);
+
+    // Add variation for multiple prompts
+    let variation_suffix = personalization_config.prompts.get_variation_suffix(prompt_number);
+    let variation_prompt = if variation_suffix.is_empty() {
+        system_prompt
+    } else {
+        format!("{}{}", system_prompt, variation_suffix)
+    };
+    with_steering(personalization_config.with_language(variation_prompt), steering)
+}
+
+/// Append a one-off steering instruction from the user to a generation template
+fn with_steering(template: String, steering: Option<&str>) -> String {
+    match steering.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(instruction) => format!("{}\n\nFor this generation only, follow this instruction from the user: {}", template, instruction),
+        None => template,
+    }
+}
+
 /// Manages the lifecycle of the LLM worker
 pub struct LlmManager {
     worker: Arc<LlmWorker>,
+    /// Second model writing competing prompt candidates, when an ensemble is configured
+    ensemble_worker: Option<Arc<LlmWorker>>,
 }
 
 impl LlmManager {
-    pub fn new(model_path: String) -> Result<Self, Box<dyn std::error::Error>> {
-        let worker = Arc::new(LlmWorker::new(model_path, 0.7, 512)?);
-        Ok(Self { worker })
+    pub fn new(model_path: String, embedding_model: String) -> Result<Self, Box<dyn std::error::Error>> {
+        let worker = Arc::new(LlmWorker::new(model_path, 0.7, 512)?.with_embedding_model(embedding_model));
+        Ok(Self { worker, ensemble_worker: None })
+    }
+
+    /// Also generate prompt candidates with `model`
+    pub fn with_ensemble_model(mut self, model: Option<&str>) -> Self {
+        self.ensemble_worker = model.map(|m| {
+            tracing::info!("   Ensemble model: {}", m);
+            Arc::new(self.worker.for_model(m))
+        });
+        self
     }
 
     /// Load model for processing
@@ -310,6 +423,11 @@ impl LlmManager {
     pub fn get_worker(&self) -> Arc<LlmWorker> {
         Arc::clone(&self.worker)
     }
+
+    /// Worker for the second ensemble model, if one is configured
+    pub fn get_ensemble_worker(&self) -> Option<Arc<LlmWorker>> {
+        self.ensemble_worker.clone()
+    }
 }
 
 #[cfg(test)]
@@ -328,7 +446,7 @@ mod tests {
     #[test]
     fn test_model_name_extraction() {
         let test_cases = vec![
-            ("C:\\Users\\test\\.lmstudio\\models\\gpt-oss-20b-GGUF\\gpt-oss-20b-MXFP4.gguf", "gpt-oss-20b"),
+            ("C:\\Users\\test\\.lmstudio\\models\\gpt-oss-20b-GGUF\\gpt-oss-20b-MXFP4.gguf", "gpt-oss:20b"),
             ("/home/user/models/llama2.gguf", "llama2"),
             ("model.gguf", "model"),
         ];
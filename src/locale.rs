@@ -0,0 +1,159 @@
+use chrono::{Datelike, NaiveDate};
+use std::sync::OnceLock;
+
+/// Languages real dates can be written in, and how each orders a date's parts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    /// "Sunday, March 2, 2025"
+    #[default]
+    EnUs,
+    /// "Sunday 2 March 2025"
+    EnGb,
+    /// "Sonntag, 2. März 2025"
+    De,
+    /// "domingo, 2 de marzo de 2025"
+    Es,
+    /// "dimanche 2 mars 2025"
+    Fr,
+    /// "domenica 2 marzo 2025"
+    It,
+    /// "zondag 2 maart 2025"
+    Nl,
+    /// "domingo, 2 de março de 2025"
+    Pt,
+}
+
+impl std::str::FromStr for Locale {
+    type Err = String;
+
+    /// A language tag like "en-US", "de", or "pt_BR"; regions only matter for English
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tag = s.to_ascii_lowercase().replace('_', "-");
+        let (language, region) = tag.split_once('-').unwrap_or((&tag, ""));
+        match (language, region) {
+            ("en", "gb" | "ie" | "au" | "nz" | "in" | "za") => Ok(Locale::EnGb),
+            ("en", _) => Ok(Locale::EnUs),
+            ("de", _) => Ok(Locale::De),
+            ("es", _) => Ok(Locale::Es),
+            ("fr", _) => Ok(Locale::Fr),
+            ("it", _) => Ok(Locale::It),
+            ("nl", _) => Ok(Locale::Nl),
+            ("pt", _) => Ok(Locale::Pt),
+            _ => Err(format!("Unknown locale '{}'; expected en, de, es, fr, it, nl, or pt (with an optional region)", s)),
+        }
+    }
+}
+
+impl Locale {
+    /// Month names, January first
+    fn months(self) -> [&'static str; 12] {
+        match self {
+            Locale::EnUs | Locale::EnGb => [
+                "January", "February", "March", "April", "May", "June",
+                "July", "August", "September", "October", "November", "December",
+            ],
+            Locale::De => [
+                "Januar", "Februar", "März", "April", "Mai", "Juni",
+                "Juli", "August", "September", "Oktober", "November", "Dezember",
+            ],
+            Locale::Es => [
+                "enero", "febrero", "marzo", "abril", "mayo", "junio",
+                "julio", "agosto", "septiembre", "octubre", "noviembre", "diciembre",
+            ],
+            Locale::Fr => [
+                "janvier", "février", "mars", "avril", "mai", "juin",
+                "juillet", "août", "septembre", "octobre", "novembre", "décembre",
+            ],
+            Locale::It => [
+                "gennaio", "febbraio", "marzo", "aprile", "maggio", "giugno",
+                "luglio", "agosto", "settembre", "ottobre", "novembre", "dicembre",
+            ],
+            Locale::Nl => [
+                "januari", "februari", "maart", "april", "mei", "juni",
+                "juli", "augustus", "september", "oktober", "november", "december",
+            ],
+            Locale::Pt => [
+                "janeiro", "fevereiro", "março", "abril", "maio", "junho",
+                "julho", "agosto", "setembro", "outubro", "novembro", "dezembro",
+            ],
+        }
+    }
+
+    /// Weekday names, Monday first
+    fn weekdays(self) -> [&'static str; 7] {
+        match self {
+            Locale::EnUs | Locale::EnGb => ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"],
+            Locale::De => ["Montag", "Dienstag", "Mittwoch", "Donnerstag", "Freitag", "Samstag", "Sonntag"],
+            Locale::Es => ["lunes", "martes", "miércoles", "jueves", "viernes", "sábado", "domingo"],
+            Locale::Fr => ["lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi", "dimanche"],
+            Locale::It => ["lunedì", "martedì", "mercoledì", "giovedì", "venerdì", "sabato", "domenica"],
+            Locale::Nl => ["maandag", "dinsdag", "woensdag", "donderdag", "vrijdag", "zaterdag", "zondag"],
+            Locale::Pt => ["segunda-feira", "terça-feira", "quarta-feira", "quinta-feira", "sexta-feira", "sábado", "domingo"],
+        }
+    }
+
+    /// A date without its weekday: "March 2, 2025"
+    pub fn date(self, date: NaiveDate) -> String {
+        let month = self.months()[date.month0() as usize];
+        let (day, year) = (date.day(), date.year());
+        match self {
+            Locale::EnUs => format!("{} {}, {}", month, day, year),
+            Locale::De => format!("{}. {} {}", day, month, year),
+            Locale::Es | Locale::Pt => format!("{} de {} de {}", day, month, year),
+            Locale::EnGb | Locale::Fr | Locale::It | Locale::Nl => format!("{} {} {}", day, month, year),
+        }
+    }
+
+    /// A date with its weekday: "Sunday, March 2, 2025"
+    pub fn long_date(self, date: NaiveDate) -> String {
+        let weekday = self.weekdays()[date.weekday().num_days_from_monday() as usize];
+        match self {
+            Locale::EnUs | Locale::De | Locale::Es | Locale::Pt => format!("{}, {}", weekday, self.date(date)),
+            Locale::EnGb | Locale::Fr | Locale::It | Locale::Nl => format!("{} {}", weekday, self.date(date)),
+        }
+    }
+}
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+/// Set the locale real dates are written in; done once at startup
+pub fn set_locale(locale: Locale) {
+    if LOCALE.set(locale).is_err() {
+        tracing::warn!("The locale was already set; keeping the first one");
+    }
+}
+
+/// The configured locale, or US English if none was set
+pub fn locale() -> Locale {
+    LOCALE.get().copied().unwrap_or_default()
+}
+
+/// A real date in the configured locale: "March 2, 2025"
+pub fn date(date: NaiveDate) -> String {
+    locale().date(date)
+}
+
+/// A real date with its weekday in the configured locale: "Sunday, March 2, 2025"
+pub fn long_date(date: NaiveDate) -> String {
+    locale().long_date(date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locales() {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 2).unwrap();
+        assert_eq!(Locale::EnUs.long_date(date), "Sunday, March 2, 2025");
+        assert_eq!(Locale::EnGb.long_date(date), "Sunday 2 March 2025");
+        assert_eq!(Locale::De.long_date(date), "Sonntag, 2. März 2025");
+        assert_eq!(Locale::Fr.long_date(date), "dimanche 2 mars 2025");
+        assert_eq!(Locale::Pt.date(date), "2 de março de 2025");
+
+        assert_eq!("en-GB".parse::<Locale>(), Ok(Locale::EnGb));
+        assert_eq!("en".parse::<Locale>(), Ok(Locale::EnUs));
+        assert_eq!("pt_BR".parse::<Locale>(), Ok(Locale::Pt));
+        assert!("tlh".parse::<Locale>().is_err());
+    }
+}
@@ -1,13 +1,70 @@
+mod access_log;
+mod analytics;
+mod api;
+mod attachments;
+mod audio_digest;
 mod auth;
+mod backup;
+mod calendar;
+mod chapter_suggestions;
+mod chapters;
+mod compression;
 mod config;
 mod cycle_date;
+mod devices;
+mod doctor;
+mod email_in;
+mod embeddings;
+mod error;
+mod export;
+mod feed;
 mod file_manager;
+mod git_sync;
+mod guest;
 mod handlers;
+mod health;
+mod holidays;
+mod hooks;
+mod insights;
+mod jobs;
 mod journal;
+mod journal_index;
 mod llm_worker;
+mod locale;
+mod markdown;
+mod memory_lane;
+mod notebook;
+mod personal_files;
 mod personalization;
+mod printable;
+mod printer;
+mod progress;
 mod prompt_generator;
+mod prompt_jobs;
 mod prompts;
+mod pwa;
+mod question_book;
+mod rate_limit;
+mod rekey;
+mod remote;
+mod report;
+mod retention;
+mod scheduler;
+mod search;
+mod settings;
+mod shares;
+mod similarity;
+mod snapshot;
+mod stats;
+mod status;
+mod telemetry;
+#[cfg(test)]
+mod test_support;
+mod theme;
+mod transcription;
+mod work_queue;
+mod writing_times;
+mod year_review;
 
 use std::sync::Arc;
 use tower_http::trace::TraceLayer;
@@ -24,9 +81,11 @@ pub struct AppState {
     pub auth_manager: Arc<AuthManager>,
     pub tokens_file_manager: Arc<TokensFileManager>,
     pub config: Arc<Config>,
-    pub journal_manager: Arc<journal::JournalManager>,
-    pub prompt_generator: Option<Arc<prompt_generator::PromptGenerator>>,
-    pub personalization_config: Arc<personalization::PersonalizationConfig>,
+    pub notebooks: Arc<notebook::Notebooks>,
+    pub jobs: Arc<jobs::JobManager>,
+    pub guest: Arc<guest::GuestPrompts>,
+    pub prompt_jobs: Arc<prompt_jobs::PromptJobs>,
+    pub scheduler: Arc<scheduler::Scheduler>,
 }
 
 #[tokio::main]
@@ -36,35 +95,268 @@ async fn main() {
 
     // Load configuration
     let config = Arc::new(Config::load());
+    config::set_live(config.clone());
+    cycle_date::set_calendar(config.journal.calendar);
+    cycle_date::set_leap_weeks(config.journal.leap_weeks);
+    match config.journal.cycle.validate(config.journal.leap_weeks) {
+        Ok(()) => cycle_date::set_shape(config.journal.cycle),
+        Err(e) => tracing::warn!("{}; using 13 months of 4 weeks of 7 days", e),
+    }
+    match config.server.locale.parse() {
+        Ok(date_locale) => locale::set_locale(date_locale),
+        Err(e) => tracing::warn!("{}; writing dates in US English", e),
+    }
+    match config.server.base_path() {
+        Ok(base_path) => handlers::set_base_path(base_path),
+        Err(e) => tracing::warn!("{}; serving from the domain root", e),
+    }
+    match config.server.cookie_scope() {
+        Ok(scope) => auth::set_cookie_scope(scope),
+        Err(e) => tracing::warn!("{}; using unprefixed cookies for the whole site", e),
+    }
+    match config.journal.date_names() {
+        Ok(names) => cycle_date::set_names(names),
+        Err(e) => tracing::warn!("{}; using the default date names", e),
+    }
+    match config.journal.day_clock() {
+        Ok(clock) => cycle_date::set_clock(clock),
+        Err(e) => tracing::warn!("{}; dates follow the server's local midnight", e),
+    }
     
     // Create sample config if it doesn't exist
     if let Err(e) = Config::create_sample_config() {
         tracing::warn!("Could not create sample config: {}", e);
     }
 
+    // Maintenance commands run instead of the server
+    let args: Vec<String> = std::env::args().collect();
+    match (args.get(1).map(String::as_str), args.get(2)) {
+        (Some("--backup"), _) => {
+            match backup::run_backup(&config).await {
+                Ok(path) => println!("Backup written to {}", path.display()),
+                Err(e) => {
+                    eprintln!("Backup failed: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        (Some("--restore"), Some(archive)) => {
+            match backup::restore_backup(&config, std::path::Path::new(archive)) {
+                Ok(summary) => println!(
+                    "Restored {} files from {}\nThe overwritten files are in snapshot {}; roll back from the admin page if needed",
+                    summary.restored, archive, summary.snapshot_id,
+                ),
+                Err(e) => {
+                    eprintln!("Restore failed: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        (Some("--remote-restore"), Some(archive_name)) => {
+            let destination = std::path::Path::new(&config.backup.destination);
+            let restored = match remote::fetch_backup(&config.remote, archive_name, destination).await {
+                Ok(path) => backup::restore_backup(&config, &path),
+                Err(e) => Err(e),
+            };
+            match restored {
+                Ok(summary) => println!(
+                    "Restored {} files from remote backup {}\nThe overwritten files are in snapshot {}; roll back from the admin page if needed",
+                    summary.restored, archive_name, summary.snapshot_id,
+                ),
+                Err(e) => {
+                    eprintln!("Remote restore failed: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        (Some("--rebuild-index"), _) => {
+            for notebook in config.journal.all_notebooks() {
+                match journal::JournalManager::new(&notebook.directory).rebuild_index().await {
+                    Ok(days) => println!("Indexed {} days in {} ({})", days, notebook.name, notebook.directory),
+                    Err(e) => {
+                        eprintln!("Indexing {} failed: {}", notebook.directory, e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            return;
+        }
+        (Some("--doctor"), option) => {
+            let fix = option.is_some_and(|o| o == "--fix");
+            let mut remaining = 0;
+            for notebook in config.journal.all_notebooks() {
+                let journal_manager = journal::JournalManager::new(&notebook.directory);
+                let result = if fix {
+                    doctor::repair(&config, &journal_manager).await
+                } else {
+                    doctor::check(&journal_manager).await
+                };
+                let report = match result {
+                    Ok(report) => report,
+                    Err(e) => {
+                        eprintln!("Checking {} failed: {}", notebook.directory, e);
+                        std::process::exit(1);
+                    }
+                };
+
+                println!("{} ({})", notebook.name, notebook.directory);
+                for issue in &report.fixed {
+                    println!("  fixed    {}: {} ({})", issue.kind.label(), issue.path, issue.fix.as_deref().unwrap_or_default());
+                }
+                for issue in &report.issues {
+                    let action = match &issue.fix {
+                        Some(fix) => format!("; --fix will: {}", fix),
+                        None => String::new(),
+                    };
+                    println!("  {}: {} - {}{}", issue.kind.label(), issue.path, issue.detail, action);
+                }
+                if let Some(snapshot_id) = &report.snapshot_id {
+                    println!("  The changed files are in snapshot {}; roll back from the admin page if needed", snapshot_id);
+                }
+                if report.issues.is_empty() && report.fixed.is_empty() {
+                    println!("  No issues found");
+                }
+                remaining += report.issues.len();
+            }
+            if remaining > 0 {
+                std::process::exit(1);
+            }
+            return;
+        }
+        (Some("--convert-calendar"), Some(from)) => {
+            let from: cycle_date::DateScheme = match from.parse() {
+                Ok(scheme) => scheme,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            };
+            let to = cycle_date::DateScheme::current();
+            let apply = args.get(3).is_some_and(|o| o == "--apply");
+            let mut problems = 0;
+            for notebook in config.journal.all_notebooks() {
+                let journal_manager = journal::JournalManager::new(&notebook.directory);
+                let plan = match rekey::plan(journal_manager.base_path(), from, to).await {
+                    Ok(plan) => plan,
+                    Err(e) => {
+                        eprintln!("Planning the conversion of {} failed: {}", notebook.directory, e);
+                        std::process::exit(1);
+                    }
+                };
+
+                println!("{} ({})", notebook.name, notebook.directory);
+                for (name, reason) in &plan.skipped {
+                    println!("  skipped {}: {}", name, reason);
+                }
+                if !apply {
+                    for mv in &plan.moves {
+                        println!("  {} -> {}", mv.from, mv.to);
+                    }
+                    println!("  {} folders to rename, {} already named for the configured calendar", plan.moves.len(), plan.unchanged);
+                    continue;
+                }
+                let report = match rekey::apply(&journal_manager, &plan, to).await {
+                    Ok(report) => report,
+                    Err(e) => {
+                        eprintln!("Converting {} failed: {}; folders not yet renamed are in its .rekey folder", notebook.directory, e);
+                        std::process::exit(1);
+                    }
+                };
+                for problem in &report.problems {
+                    println!("  {}", problem);
+                }
+                println!("  Renamed {} folders, {} verified", report.moved, report.verified);
+                problems += report.problems.len();
+            }
+            if !apply {
+                println!("Run again with --apply to rename them");
+            }
+            if problems > 0 {
+                std::process::exit(1);
+            }
+            return;
+        }
+        (Some("--retention"), option) => {
+            let apply = option.is_some_and(|o| o == "--apply");
+            let today = cycle_date::CycleDate::today();
+            for notebook in config.journal.all_notebooks() {
+                let journal_manager = journal::JournalManager::new(&notebook.directory);
+                let report = match retention::enforce(&config, &journal_manager, &today, !apply).await {
+                    Ok(report) => report,
+                    Err(e) => {
+                        eprintln!("Retention cleanup of {} failed: {}", notebook.directory, e);
+                        std::process::exit(1);
+                    }
+                };
+
+                println!("{} ({})", notebook.name, notebook.directory);
+                for expired in &report.expired {
+                    println!("  {}{}: {} - {}", if apply { "removed " } else { "" }, expired.kind.label(), expired.path, expired.detail);
+                }
+                if let Some(snapshot_id) = &report.snapshot_id {
+                    println!("  The removed files are in snapshot {}; roll back from the admin page if needed", snapshot_id);
+                }
+                if report.expired.is_empty() {
+                    println!("  Nothing is past its retention period");
+                } else if !apply {
+                    println!("  Run with --retention --apply to remove these");
+                }
+            }
+            return;
+        }
+        (Some("--restore") | Some("--remote-restore"), None) => {
+            eprintln!("Usage: llm_journal --restore <backup.zip>");
+            std::process::exit(2);
+        }
+        _ => {}
+    }
+
     // Create authentication manager and load persistent sessions
     let auth_manager = Arc::new(AuthManager::new());
     let tokens_file_manager = Arc::new(TokensFileManager::new(config.files.tokens_file.clone()));
     
-    // Initialize journal manager
-    let journal_manager = Arc::new(journal::JournalManager::new(&config.journal.journal_directory));
-    if let Err(e) = journal_manager.ensure_directories().await {
-        tracing::warn!("Could not create journal directories: {}", e);
-    } else {
-        tracing::info!("Journal directory ready: {}", config.journal.journal_directory);
-    }
-    
-    // Load personalization configuration (prompts, profile, style)
-    let personalization_config = match personalization::PersonalizationConfig::load(&config.journal.journal_directory) {
-        Ok(config) => {
-            tracing::info!("Personalization configuration loaded successfully");
-            Arc::new(config)
+    // Initialize a journal manager and personalization (prompts, profile, style) per notebook
+    let mut notebook_parts = Vec::new();
+    for notebook_config in config.journal.all_notebooks() {
+        if !notebook::is_valid_name(&notebook_config.name) {
+            tracing::warn!("Skipping notebook with invalid name '{}'", notebook_config.name);
+            continue;
         }
-        Err(e) => {
-            tracing::error!("Failed to load personalization configuration: {}", e);
-            std::process::exit(1);
+        if notebook_parts.iter().any(|(name, _, _)| *name == notebook_config.name) {
+            tracing::warn!("Skipping duplicate notebook '{}'", notebook_config.name);
+            continue;
         }
-    };
+
+        let mut journal_manager = journal::JournalManager::new(&notebook_config.directory);
+        if config.git.enabled {
+            let git = git_sync::GitJournal::new(std::path::Path::new(&notebook_config.directory), &config.git);
+            match git.init().await {
+                Ok(()) => journal_manager = journal_manager.with_git(git),
+                Err(e) => tracing::warn!("Git history disabled for {}, could not set up repository: {}", notebook_config.name, e),
+            }
+        }
+        let journal_manager = Arc::new(journal_manager);
+        if let Err(e) = journal_manager.ensure_directories().await {
+            tracing::warn!("Could not create journal directories: {}", e);
+        } else {
+            tracing::info!("Notebook '{}' ready: {}", notebook_config.name, notebook_config.directory);
+        }
+
+        let personalization_config = match personalization::PersonalizationConfig::load(&notebook_config.directory) {
+            Ok(config) => {
+                tracing::info!("Personalization configuration loaded for {}", notebook_config.name);
+                personalization::SharedPersonalization::new(config)
+            }
+            Err(e) => {
+                tracing::error!("Failed to load personalization configuration for {}: {}", notebook_config.name, e);
+                std::process::exit(1);
+            }
+        };
+        notebook_parts.push((notebook_config.name, journal_manager, personalization_config));
+    }
     
     // Create example prompts file for user reference
     if let Err(e) = prompts::PromptsConfig::create_example("prompts") {
@@ -82,11 +374,11 @@ async fn main() {
         }
     }
 
-    // Initialize LLM manager first (shared by journal processor and prompt generator)
-    let llm_manager = match LlmManager::new(config.llm.model_path.clone()) {
+    // Initialize the LLM manager, shared by every notebook's prompt generator
+    let llm_manager = match LlmManager::new(config.llm.model_path.clone(), config.llm.embedding_model.clone()) {
         Ok(manager) => {
             tracing::info!("LLM manager initialized");
-            Arc::new(manager)
+            Arc::new(manager.with_ensemble_model(config.llm.ensemble_model.as_deref()))
         }
         Err(e) => {
             tracing::error!("Failed to initialize LLM manager: {}", e);
@@ -95,43 +387,52 @@ async fn main() {
         }
     };
 
-    // Note: Nightly journal processor has been removed as it was redundant.
-    // All processing (summaries, status files, and prompts) now happens
-    // unified at 3 AM via the prompt generator service.
-
-    // Initialize prompt generator using the shared LLM manager
-    let prompt_generator = {
-        // Initialize prompt generator
-        let prompt_generator = Arc::new(crate::prompt_generator::PromptGenerator::new(
-            journal_manager.clone(),
-            llm_manager.clone(),
-            config.clone(),
-            personalization_config.clone(),
-        ));
-        
-        // Start the prompt generator service
-        if let Err(e) = prompt_generator.start().await {
-            tracing::error!("Failed to start prompt generator: {}", e);
-            None
-        } else {
-            tracing::info!("Prompt generator service started successfully");
-            Some(prompt_generator)
-        }
-    };
+    // Initialize a prompt generator per notebook using the shared LLM manager
+    let notebooks: Vec<notebook::Notebook> = notebook_parts
+        .into_iter()
+        .map(|(name, journal_manager, personalization_config)| notebook::Notebook {
+            name,
+            prompt_generator: Some(Arc::new(crate::prompt_generator::PromptGenerator::new(
+                journal_manager.clone(),
+                llm_manager.clone(),
+                config.clone(),
+                personalization_config.clone(),
+            ))),
+            journal_manager,
+            personalization_config,
+        })
+        .collect();
+    let notebooks = Arc::new(notebook::Notebooks::new(notebooks));
+
+    // Start nightly processing, scheduled backups, and rollover announcements
+    let scheduler = scheduler::Scheduler::start(notebooks.clone(), config.clone());
+
+    // Continue any export or import the last run didn't finish
+    let jobs = jobs::JobManager::new(config.clone(), notebooks.clone());
+    jobs.resume_interrupted();
 
     // Create shared application state
     let app_state = AppState {
         auth_manager: auth_manager.clone(),
         tokens_file_manager: tokens_file_manager.clone(),
         config: config.clone(),
-        journal_manager: journal_manager.clone(),
-        prompt_generator,
-        personalization_config,
+        notebooks,
+        jobs,
+        guest: Arc::new(guest::GuestPrompts::new(config.guest.clone(), llm_manager.get_worker())),
+        prompt_jobs: prompt_jobs::PromptJobs::new(),
+        scheduler,
     };
 
     // Build our application with clean, simple routes
-    let app = create_routes()
+    if let Some(dir) = &config.server.override_dir {
+        handlers::set_page_overrides(handlers::PageOverrides::load(std::path::Path::new(dir)));
+    }
+    if !config.server.access_log.is_empty() {
+        access_log::start(&config.server.access_log, config.server.access_log_max_kb);
+    }
+    let app = create_routes(&config.server)
         .with_state(app_state.clone())
+        .layer(axum::middleware::from_fn_with_state(app_state.clone(), access_log::log_requests))
         // Add tracing middleware
         .layer(TraceLayer::new_for_http());
 
@@ -164,7 +465,8 @@ async fn main() {
     };
 
     // Run the server with graceful shutdown
-    axum::serve(listener, app)
+    // Client addresses are needed to rate limit the public guest prompt
+    axum::serve(listener, app.into_make_service_with_connect_info::<std::net::SocketAddr>())
         .with_graceful_shutdown(shutdown_signal)
         .await
         .unwrap();
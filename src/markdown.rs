@@ -0,0 +1,65 @@
+//! Markdown to HTML for entries: the feed and the editor's preview both render through
+//! here. Entries are the writer's own, but their HTML still lands in pages and feed
+//! readers, so raw HTML is shown as text and links only keep web and mail addresses.
+//! Line breaks are kept as written, since entries are typed in a plain text box.
+
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
+
+/// Link schemes a rendered entry may point at; anything else (javascript:, data:) is dropped
+const ALLOWED_SCHEMES: [&str; 3] = ["http", "https", "mailto"];
+
+/// Render an entry's Markdown as sanitized HTML
+pub fn to_html(markdown: &str) -> String {
+    let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS;
+    let events = Parser::new_ext(markdown, options).map(|event| match event {
+        Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+        Event::SoftBreak => Event::HardBreak,
+        Event::Start(Tag::Link { link_type, dest_url, title, id }) => Event::Start(Tag::Link {
+            link_type,
+            dest_url: safe_url(dest_url),
+            title,
+            id,
+        }),
+        Event::Start(Tag::Image { link_type, dest_url, title, id }) => Event::Start(Tag::Image {
+            link_type,
+            dest_url: safe_url(dest_url),
+            title,
+            id,
+        }),
+        event => event,
+    });
+
+    let mut output = String::new();
+    html::push_html(&mut output, events);
+    output
+}
+
+/// The URL if it's relative or uses an allowed scheme, otherwise an empty one
+fn safe_url(url: CowStr<'_>) -> CowStr<'_> {
+    let scheme = url.split_once(':').map(|(scheme, _)| scheme).filter(|scheme| !scheme.contains(['/', '?', '#']));
+    match scheme {
+        Some(scheme) if !ALLOWED_SCHEMES.iter().any(|allowed| scheme.trim().eq_ignore_ascii_case(allowed)) => CowStr::Borrowed(""),
+        _ => url,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_html() {
+        assert_eq!(to_html("Tea & *biscuits*.\n\nThen a long walk."), "<p>Tea &amp; <em>biscuits</em>.</p>\n<p>Then a long walk.</p>\n");
+        assert_eq!(to_html("Dear diary,\nhello"), "<p>Dear diary,<br />\nhello</p>\n");
+        assert_eq!(to_html("- [x] ~~laundry~~"), "<ul>\n<li><input disabled=\"\" type=\"checkbox\" checked=\"\"/>\n<del>laundry</del></li>\n</ul>\n");
+
+        // Raw HTML is shown, not run
+        assert_eq!(to_html("<script>alert(1)</script>"), "&lt;script&gt;alert(1)&lt;/script&gt;");
+        assert_eq!(to_html("a <b onclick=\"x\">b</b>"), "<p>a &lt;b onclick=\"x\"&gt;b&lt;/b&gt;</p>\n");
+
+        assert_eq!(to_html("[site](https://example.com)"), "<p><a href=\"https://example.com\">site</a></p>\n");
+        assert_eq!(to_html("[day](/journal?date=01212)"), "<p><a href=\"/journal?date=01212\">day</a></p>\n");
+        assert_eq!(to_html("[x](javascript:alert(1))"), "<p><a href=\"\">x</a></p>\n");
+        assert_eq!(to_html("![x](JavaScript:alert(1))"), "<p><img src=\"\" alt=\"x\" /></p>\n");
+    }
+}
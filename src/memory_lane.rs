@@ -0,0 +1,189 @@
+use crate::cycle_date::CycleDate;
+use crate::journal::{EntryFilter, EntryListing, JournalManager};
+use chrono::{Datelike, NaiveDate};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::Serialize;
+
+/// Entries newer than this aren't memories yet
+const MIN_AGE_DAYS: i64 = 30;
+/// Word count at which an entry's length stops adding weight
+const FULL_LENGTH_WORDS: usize = 600;
+/// Extra weight for entries with #tags
+const TAGGED_WEIGHT: f64 = 1.5;
+/// Extra weight for entries written on today's date in an earlier year
+const ANNIVERSARY_WEIGHT: f64 = 6.0;
+/// Characters of entry text shown
+const EXCERPT_LENGTH: usize = 280;
+
+/// A past entry brought back up
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Memory {
+    pub cycle_date: CycleDate,
+    pub real_date: NaiveDate,
+    pub excerpt: String,
+    pub word_count: usize,
+    /// Tags written in the entry, without the '#'
+    pub tags: Vec<String>,
+    /// Years since the entry, when it was written on today's date
+    pub years_ago: Option<i32>,
+}
+
+impl Memory {
+    /// "Three years ago today" or "On March 3, 2024"
+    pub fn heading(&self) -> String {
+        match self.years_ago {
+            Some(1) => "One year ago today".to_string(),
+            Some(years) => format!("{} years ago today", years),
+            None => format!("On {}", self.real_date.format("%B %-d, %Y")),
+        }
+    }
+
+    /// Context inviting the day's prompt to look back at this entry
+    pub fn prompt_context(&self) -> String {
+        format!(
+            "A MEMORY TO REVISIT ({}, {}):\n{}\nIf it fits naturally, one prompt may invite reflecting on how things have changed since then.",
+            self.heading(),
+            self.cycle_date,
+            self.excerpt
+        )
+    }
+}
+
+/// Hashtags in entry text ("#travel" → "travel"), in order of first use
+pub fn tags(content: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for word in content.split_whitespace() {
+        let Some(tag) = word.strip_prefix('#') else {
+            continue;
+        };
+        let tag: String = tag.chars().take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '-').collect();
+        let tag = tag.to_lowercase();
+        // "#1" is a number, not a tag
+        if tag.chars().any(char::is_alphabetic) && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// Years between `date` and `today` when they share a month and day (Feb 29 entries come
+/// back on Feb 28 in other years)
+fn years_ago(date: NaiveDate, today: NaiveDate) -> Option<i32> {
+    let years = today.year() - date.year();
+    let same_day = date.month() == today.month()
+        && (date.day() == today.day() || (date.month() == 2 && date.day() == 29 && today.day() == 28 && today.with_day(29).is_none()));
+    (years > 0 && same_day).then_some(years)
+}
+
+/// How likely an entry is to be picked: longer, tagged, and anniversary entries come up more
+pub fn weight(listing: &EntryListing, tagged: bool, today: NaiveDate) -> f64 {
+    let length = listing.word_count.min(FULL_LENGTH_WORDS) as f64 / FULL_LENGTH_WORDS as f64;
+    let mut weight = 0.25 + length * 2.0;
+    if tagged {
+        weight += TAGGED_WEIGHT;
+    }
+    if years_ago(listing.real_date, today).is_some() {
+        weight += ANNIVERSARY_WEIGHT;
+    }
+    weight
+}
+
+/// The memory of the day: the same entry all day, so the home page and the day's prompts agree
+pub async fn of_the_day(journal_manager: &JournalManager, today: &CycleDate) -> Result<Option<Memory>, Box<dyn std::error::Error>> {
+    let seed = today.to_real_date().num_days_from_ce() as u64;
+    pick(journal_manager, today, &mut StdRng::seed_from_u64(seed)).await
+}
+
+/// A fresh random memory
+pub async fn shuffle(journal_manager: &JournalManager, today: &CycleDate) -> Result<Option<Memory>, Box<dyn std::error::Error>> {
+    pick(journal_manager, today, &mut StdRng::from_entropy()).await
+}
+
+/// Pick a past entry at random, weighted by `weight`
+async fn pick(journal_manager: &JournalManager, today: &CycleDate, rng: &mut StdRng) -> Result<Option<Memory>, Box<dyn std::error::Error>> {
+    let today = today.to_real_date();
+    let listings = journal_manager.list_entries(&EntryFilter::default()).await?;
+
+    let mut candidates = Vec::new();
+    for listing in listings.into_iter().filter(|l| (today - l.real_date).num_days() >= MIN_AGE_DAYS && l.word_count > 0) {
+        let Some(entry) = journal_manager.load_entry(&listing.cycle_date).await? else {
+            continue;
+        };
+        let tags = tags(&entry.content);
+        candidates.push((weight(&listing, !tags.is_empty(), today), listing, entry.content, tags));
+    }
+    if candidates.is_empty() {
+        return Ok(None);
+    }
+
+    let chosen = WeightedIndex::new(candidates.iter().map(|(weight, ..)| *weight))?.sample(rng);
+    let (_, listing, content, tags) = candidates.swap_remove(chosen);
+    Ok(Some(Memory {
+        cycle_date: listing.cycle_date,
+        real_date: listing.real_date,
+        excerpt: excerpt(&content),
+        word_count: listing.word_count,
+        tags,
+        years_ago: years_ago(listing.real_date, today),
+    }))
+}
+
+/// The start of an entry, cut at a word boundary
+fn excerpt(content: &str) -> String {
+    let content = content.trim();
+    if content.chars().count() <= EXCERPT_LENGTH {
+        return content.to_string();
+    }
+    let cut: String = content.chars().take(EXCERPT_LENGTH).collect();
+    let cut = cut.rsplit_once(char::is_whitespace).map_or(cut.as_str(), |(head, _)| head);
+    format!("{}…", cut.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::JournalEntry;
+    use crate::test_support::{date, listing};
+
+    #[test]
+    fn test_tags_and_weight() {
+        assert_eq!(tags("Hiked with #Family, then #family again. Ranked #1 #road-trip"), vec!["family", "road-trip"]);
+
+        let today = date(2026, 3, 3);
+        let short = weight(&listing(date(2025, 6, 1), 50), false, today);
+        assert!(weight(&listing(date(2025, 6, 1), 500), false, today) > short);
+        assert!(weight(&listing(date(2025, 6, 1), 50), true, today) > short);
+        assert!(weight(&listing(date(2024, 3, 3), 50), false, today) > short + ANNIVERSARY_WEIGHT - 0.01);
+
+        assert_eq!(years_ago(date(2024, 3, 3), today), Some(2));
+        assert_eq!(years_ago(date(2024, 2, 29), date(2025, 2, 28)), Some(1));
+        assert_eq!(years_ago(date(2026, 3, 3), today), None);
+    }
+
+    #[tokio::test]
+    async fn test_of_the_day() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let journal_manager = JournalManager::new(temp_dir.path());
+        let today = CycleDate::from_real_date(date(2025, 6, 10));
+
+        assert!(of_the_day(&journal_manager, &today).await.unwrap().is_none());
+
+        for (real_date, content) in [(date(2024, 6, 10), "A year ago I started the garden. #garden"), (date(2025, 6, 5), "Too recent to be a memory.")] {
+            journal_manager.save_entry(&JournalEntry {
+                cycle_date: CycleDate::from_real_date(real_date),
+                content: content.to_string(),
+                created_at: chrono::Local::now(),
+                modified_at: chrono::Local::now(),
+            }).await.unwrap();
+        }
+
+        let memory = of_the_day(&journal_manager, &today).await.unwrap().unwrap();
+        assert_eq!(memory.real_date, date(2024, 6, 10));
+        assert_eq!(memory.tags, vec!["garden"]);
+        assert_eq!(memory.heading(), "One year ago today");
+        assert!(memory.prompt_context().contains("started the garden"));
+        assert_eq!(of_the_day(&journal_manager, &today).await.unwrap(), Some(memory));
+    }
+}
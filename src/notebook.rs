@@ -0,0 +1,123 @@
+use crate::journal::JournalManager;
+use crate::personalization::SharedPersonalization;
+use crate::prompt_generator::PromptGenerator;
+use crate::AppState;
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::request::Parts,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+
+/// Cookie remembering which notebook the web UI is showing (before the configured prefix)
+pub const NOTEBOOK_COOKIE: &str = "notebook";
+
+/// A named journal with its own directory, prompts, and personalization
+pub struct Notebook {
+    pub name: String,
+    pub journal_manager: Arc<JournalManager>,
+    pub personalization_config: Arc<SharedPersonalization>,
+    pub prompt_generator: Option<Arc<PromptGenerator>>,
+}
+
+/// All configured notebooks; the first one is the default
+pub struct Notebooks {
+    notebooks: Vec<Arc<Notebook>>,
+}
+
+impl Notebooks {
+    pub fn new(notebooks: Vec<Notebook>) -> Self {
+        assert!(!notebooks.is_empty(), "at least one notebook is required");
+        Self {
+            notebooks: notebooks.into_iter().map(Arc::new).collect(),
+        }
+    }
+
+    pub fn default_notebook(&self) -> &Arc<Notebook> {
+        &self.notebooks[0]
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<Notebook>> {
+        self.notebooks.iter().find(|n| n.name == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<Notebook>> {
+        self.notebooks.iter()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.notebooks.iter().map(|n| n.name.clone()).collect()
+    }
+}
+
+/// Whether a notebook name is safe to use in URLs and cookies
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.len() <= 40 && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+}
+
+/// The notebook a request addresses: the `notebook` query parameter, else the
+/// notebook cookie set by the UI switcher, else the default notebook
+pub struct ActiveNotebook(pub Arc<Notebook>);
+
+/// A request named a notebook that isn't configured
+#[derive(Debug)]
+pub struct UnknownNotebook(pub String);
+
+impl std::fmt::Display for UnknownNotebook {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Unknown notebook '{}'", self.0)
+    }
+}
+
+impl IntoResponse for UnknownNotebook {
+    fn into_response(self) -> Response {
+        crate::error::AppError::not_found(self.to_string()).into_response()
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for ActiveNotebook {
+    type Rejection = UnknownNotebook;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let from_query = parts.uri.query().and_then(|query| {
+            query
+                .split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(key, _)| *key == "notebook")
+                .map(|(_, value)| value.to_string())
+        });
+
+        if let Some(name) = from_query {
+            return match state.notebooks.get(&name) {
+                Some(notebook) => Ok(ActiveNotebook(Arc::clone(notebook))),
+                None => Err(UnknownNotebook(name)),
+            };
+        }
+
+        // A stale cookie (e.g., a notebook removed from the config) falls back to the default
+        let notebook = notebook_cookie(&parts.headers)
+            .and_then(|name| state.notebooks.get(&name))
+            .unwrap_or_else(|| state.notebooks.default_notebook());
+        Ok(ActiveNotebook(Arc::clone(notebook)))
+    }
+}
+
+fn notebook_cookie(headers: &axum::http::HeaderMap) -> Option<String> {
+    crate::auth::cookie_scope().read(headers, NOTEBOOK_COOKIE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notebook_names() {
+        assert!(is_valid_name("work"));
+        assert!(is_valid_name("dream_log-2"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("../journal"));
+        assert!(!is_valid_name("my notebook"));
+    }
+}
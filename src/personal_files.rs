@@ -0,0 +1,281 @@
+//! Editors for a notebook's personalization files: profile.txt, style.txt, prompts.json, and
+//! language.txt.
+//! An edit is checked before it's written (prompts.json has to parse and keep each
+//! template's placeholders), the previous version is snapshotted so it can be rolled back
+//! from the admin page, and the notebook's personalization is reloaded so the next prompt
+//! uses the change without a restart.
+
+use askama::Template;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+    routing::get,
+    Form, Router,
+};
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::auth::{AuthContext, Scope};
+use crate::config::Config;
+use crate::error::AppError;
+use crate::notebook::{ActiveNotebook, Notebook};
+use crate::prompts::PromptsConfig;
+use crate::AppState;
+
+/// One of the files that personalize prompts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PersonalFile {
+    Profile,
+    Style,
+    Prompts,
+    /// The language generated prompts and summaries are written in
+    Language,
+}
+
+impl FromStr for PersonalFile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "profile" => Ok(PersonalFile::Profile),
+            "style" => Ok(PersonalFile::Style),
+            "prompts" => Ok(PersonalFile::Prompts),
+            "language" => Ok(PersonalFile::Language),
+            other => Err(format!("Unknown file '{}'; use profile, style, prompts, or language", other)),
+        }
+    }
+}
+
+impl PersonalFile {
+    pub const ALL: [PersonalFile; 4] = [PersonalFile::Profile, PersonalFile::Style, PersonalFile::Prompts, PersonalFile::Language];
+
+    /// Languages suggested by the editor; any other name the model knows works too
+    pub const LANGUAGES: [&'static str; 12] = [
+        "English", "German", "French", "Spanish", "Italian", "Portuguese",
+        "Dutch", "Swedish", "Polish", "Japanese", "Chinese", "Korean",
+    ];
+
+    /// Name used in URLs
+    pub fn key(self) -> &'static str {
+        match self {
+            PersonalFile::Profile => "profile",
+            PersonalFile::Style => "style",
+            PersonalFile::Prompts => "prompts",
+            PersonalFile::Language => "language",
+        }
+    }
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            PersonalFile::Profile => "profile.txt",
+            PersonalFile::Style => "style.txt",
+            PersonalFile::Prompts => "prompts.json",
+            PersonalFile::Language => "language.txt",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            PersonalFile::Profile => "Lasting facts about you, included with every prompt. Things that change week to week belong in the status instead.",
+            PersonalFile::Style => "How prompts should sound: tone, length, and what to avoid.",
+            PersonalFile::Prompts => "The templates sent to the model. Each keeps its placeholders, like {context} in the daily prompt.",
+            PersonalFile::Language => "The language prompts, summaries, and reflection questions are written in, whatever language the templates and pages use. Leave it blank to follow the templates.",
+        }
+    }
+
+    /// Check `content` is fit to save
+    pub fn check(self, content: &str) -> Result<(), String> {
+        if self == PersonalFile::Language {
+            let language = content.trim();
+            if language.chars().count() > 40 || language.contains(|c: char| c.is_control()) {
+                return Err("language.txt takes one language name, like German".to_string());
+            }
+            return Ok(());
+        }
+        if content.trim().is_empty() {
+            return Err(format!("{} can't be empty", self.file_name()));
+        }
+        if self == PersonalFile::Prompts {
+            let prompts: PromptsConfig = serde_json::from_str(content)
+                .map_err(|e| format!("prompts.json isn't valid: {}", e))?;
+            let missing = prompts.missing_placeholders();
+            if !missing.is_empty() {
+                return Err(format!("Templates are missing placeholders: {}", missing.join(", ")));
+            }
+        }
+        Ok(())
+    }
+
+    fn path(self, notebook: &Notebook) -> PathBuf {
+        notebook.journal_manager.base_path().join(self.file_name())
+    }
+}
+
+/// The file as it is on disk
+pub async fn read(notebook: &Notebook, file: PersonalFile) -> Result<String, String> {
+    match tokio::fs::read_to_string(file.path(notebook)).await {
+        // Only written once a language is chosen
+        Err(e) if file == PersonalFile::Language && e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        result => result.map_err(|e| format!("{}: {}", file.file_name(), e)),
+    }
+}
+
+/// Check and save a new version of the file, snapshotting the old one, then reload the
+/// notebook's personalization. Returns the snapshot's id.
+pub async fn save(config: &Config, notebook: &Notebook, file: PersonalFile, content: &str) -> Result<String, SaveError> {
+    let content = content.replace("\r\n", "\n");
+    file.check(&content).map_err(SaveError::Invalid)?;
+
+    let path = file.path(notebook);
+    let operation = format!("Edit {} in {}", file.file_name(), notebook.name);
+    let snapshot_id = crate::snapshot::before(config, operation, vec![path.clone()]).await.map_err(SaveError::Failed)?;
+
+    let temporary = path.with_extension("tmp");
+    let written = match tokio::fs::write(&temporary, &content).await {
+        Ok(()) => tokio::fs::rename(&temporary, &path).await,
+        Err(e) => Err(e),
+    };
+    written.map_err(|e| SaveError::Failed(format!("{}: {}", path.display(), e)))?;
+
+    notebook.personalization_config
+        .reload(notebook.journal_manager.base_path())
+        .map_err(|e| SaveError::Failed(format!("Saved, but reloading failed: {}", e)))?;
+    notebook.journal_manager.record_change(format!("Edit {}", file.file_name())).await;
+    Ok(snapshot_id)
+}
+
+/// Why a save didn't happen
+#[derive(Debug)]
+pub enum SaveError {
+    /// The content was refused; nothing was written
+    Invalid(String),
+    Failed(String),
+}
+
+impl From<SaveError> for AppError {
+    fn from(error: SaveError) -> Self {
+        match error {
+            SaveError::Invalid(message) => AppError::bad_request(message),
+            SaveError::Failed(message) => AppError::internal("Error saving personalization", message),
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "personalization.html")]
+pub struct PersonalizationTemplate {
+    pub notebook: String,
+    pub file: PersonalFile,
+    pub content: String,
+    pub snapshot_id: Option<String>,
+    pub error: Option<String>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/admin/personalization", get(editor_page).post(save_form))
+}
+
+#[derive(Deserialize)]
+pub struct EditorQuery {
+    file: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct EditorForm {
+    file: String,
+    content: String,
+}
+
+fn render(template: PersonalizationTemplate, status: StatusCode) -> Response {
+    match template.render() {
+        Ok(html) => (status, Html(html)).into_response(),
+        Err(e) => AppError::internal("Error rendering page", e).into_response(),
+    }
+}
+
+/// The editor for one file (GET /admin/personalization?file=profile)
+async fn editor_page(
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Query(query): Query<EditorQuery>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+    let file = match query.file.as_deref().unwrap_or("profile").parse::<PersonalFile>() {
+        Ok(file) => file,
+        Err(e) => return AppError::bad_request(e).into_response(),
+    };
+
+    match read(&notebook, file).await {
+        Ok(content) => render(PersonalizationTemplate {
+            notebook: notebook.name.clone(),
+            file,
+            content,
+            snapshot_id: None,
+            error: None,
+        }, StatusCode::OK),
+        Err(e) => AppError::internal("Error reading personalization", e).into_response(),
+    }
+}
+
+/// Save the editor's text (POST /admin/personalization)
+async fn save_form(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Form(form): Form<EditorForm>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+    let file = match form.file.parse::<PersonalFile>() {
+        Ok(file) => file,
+        Err(e) => return AppError::bad_request(e).into_response(),
+    };
+
+    let template = |snapshot_id, error, status| render(PersonalizationTemplate {
+        notebook: notebook.name.clone(),
+        file,
+        content: form.content.clone(),
+        snapshot_id,
+        error,
+    }, status);
+    match save(&app_state.config, &notebook, file, &form.content).await {
+        Ok(snapshot_id) => {
+            tracing::info!("{} edited in {} by {}", file.file_name(), notebook.name, auth.label());
+            template(Some(snapshot_id), None, StatusCode::OK)
+        }
+        // Keep the text so it can be fixed rather than retyped
+        Err(SaveError::Invalid(message)) => template(None, Some(message), StatusCode::BAD_REQUEST),
+        Err(e) => AppError::from(e).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        assert!(PersonalFile::Profile.check("I teach piano.").is_ok());
+        assert_eq!(PersonalFile::Style.check(" \n").unwrap_err(), "style.txt can't be empty");
+
+        let prompts = serde_json::to_string(&PromptsConfig::default()).unwrap();
+        assert!(PersonalFile::Prompts.check(&prompts).is_ok());
+        assert!(PersonalFile::Prompts.check("{").unwrap_err().starts_with("prompts.json isn't valid"));
+        let without_context = prompts.replace("{context}", "the week");
+        assert!(PersonalFile::Prompts.check(&without_context).unwrap_err().contains("daily_prompt: {context}"));
+
+        assert!(PersonalFile::Language.check("German\n").is_ok());
+        assert!(PersonalFile::Language.check("").is_ok());
+        assert!(PersonalFile::Language.check("German\nFrench").is_err());
+        assert!(PersonalFile::Language.check(&"x".repeat(41)).is_err());
+
+        assert_eq!("style".parse::<PersonalFile>(), Ok(PersonalFile::Style));
+        assert_eq!("language".parse::<PersonalFile>(), Ok(PersonalFile::Language));
+        assert!("config".parse::<PersonalFile>().is_err());
+    }
+}
@@ -1,8 +1,11 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
 use crate::prompts::PromptsConfig;
-use chrono::{NaiveDate, Local, Datelike};
+use crate::status::StructuredStatus;
+use crate::telemetry::ContextSection;
+use chrono::{NaiveDate, Datelike};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Holiday {
@@ -13,17 +16,87 @@ pub struct Holiday {
     pub recurring: bool, // true for annual events like birthdays
 }
 
+impl Holiday {
+    /// Parse one line of holidays.txt: "DATE|CATEGORY|NAME|DESCRIPTION" or "DATE|CATEGORY|NAME".
+    /// Blank lines, comments, and lines with too few fields give None.
+    pub fn parse_line(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        
+        let parts: Vec<&str> = line.split('|').collect();
+        if parts.len() < 3 {
+            return None;
+        }
+        let date = parts[0].trim().to_string();
+        let description = if parts.len() > 3 && !parts[3].trim().is_empty() {
+            Some(parts[3].trim().to_string())
+        } else {
+            None
+        };
+        
+        // Determine if recurring based on date format
+        let recurring = !date.contains('-') || date.len() == 5; // MM-DD format or just MM
+        
+        Some(Holiday {
+            name: parts[2].trim().to_string(),
+            date,
+            category: parts[1].trim().to_string(),
+            description,
+            recurring,
+        })
+    }
+
+    /// The holiday as a line of holidays.txt
+    pub fn to_line(&self) -> String {
+        match &self.description {
+            Some(description) => format!("{}|{}|{}|{}", self.date, self.category, self.name, description),
+            None => format!("{}|{}|{}", self.date, self.category, self.name),
+        }
+    }
+}
+
 /// Complete personalization configuration combining all user customization files
 #[derive(Debug, Clone)]
 pub struct PersonalizationConfig {
     pub prompts: PromptsConfig,
     pub profile: Option<String>,
     pub style: Option<String>,
-    pub status: Option<String>,
+    /// Language generated prompts and summaries are written in (language.txt), when it
+    /// differs from the templates'
+    pub language: Option<String>,
+    pub status: StructuredStatus,
     pub holidays: Vec<Holiday>,
     journal_dir: PathBuf,
 }
 
+/// A notebook's personalization as shared by its handlers and prompt generator. Editing
+/// profile.txt, style.txt, or prompts.json from the web swaps in a freshly loaded copy, so
+/// the next prompt uses it; work already running keeps the copy it started with.
+pub struct SharedPersonalization(RwLock<Arc<PersonalizationConfig>>);
+
+impl SharedPersonalization {
+    pub fn new(config: PersonalizationConfig) -> Arc<Self> {
+        Arc::new(Self(RwLock::new(Arc::new(config))))
+    }
+
+    pub fn current(&self) -> Arc<PersonalizationConfig> {
+        self.0.read().unwrap().clone()
+    }
+
+    pub fn replace(&self, config: PersonalizationConfig) {
+        *self.0.write().unwrap() = Arc::new(config);
+    }
+
+    /// Load the files in `journal_dir` again after one was edited
+    pub fn reload(&self, journal_dir: &Path) -> Result<(), String> {
+        let config = PersonalizationConfig::load(journal_dir).map_err(|e| e.to_string())?;
+        self.replace(config);
+        Ok(())
+    }
+}
+
 impl PersonalizationConfig {
     /// Load complete personalization configuration from the journal directory
     pub fn load<P: AsRef<Path>>(journal_dir: P) -> Result<Self, Box<dyn std::error::Error>> {
@@ -40,19 +113,25 @@ impl PersonalizationConfig {
         // Load style.txt (AI personality configuration)
         let style_path = journal_dir.join("style.txt");
         let style = Self::load_text_file(&style_path, "style.txt", Self::default_style_content())?;
+
+        // Load language.txt (optional; no file means the templates' own language)
+        let language = Self::load_language(&journal_dir.join("language.txt"));
         
-        // Load status.txt (dynamic user context, may not exist initially)
-        let status_path = journal_dir.join("status.txt");
-        let status = Self::load_text_file_optional(&status_path, "status.txt")?;
+        // Load status.toml (dynamic user context, may not exist initially)
+        let status = Self::load_status(journal_dir);
         
         // Load holidays.txt (temporal context)
         let holidays_path = journal_dir.join("holidays.txt");
         let holidays = Self::load_holidays(&holidays_path)?;
 
+        // Create chapters.txt (read fresh whenever it's needed, so edits apply without a restart)
+        crate::chapters::create_default(journal_dir)?;
+
         Ok(Self {
             prompts,
             profile,
             style,
+            language,
             status,
             holidays,
             journal_dir: journal_dir.to_path_buf(),
@@ -92,32 +171,46 @@ impl PersonalizationConfig {
         }
     }
     
-    /// Load a text file that may not exist (like status.txt)
-    fn load_text_file_optional<P: AsRef<Path>>(
-        path: P, 
-        filename: &str
-    ) -> Result<Option<String>, Box<dyn std::error::Error>> {
-        let path = path.as_ref();
-        
-        if !path.exists() {
-            tracing::info!("{} does not exist yet (will be created during summary generation)", filename);
-            return Ok(None);
-        }
-        
+    /// Load language.txt, which is only there once a language has been chosen
+    fn load_language(path: &Path) -> Option<String> {
         match fs::read_to_string(path) {
             Ok(content) => {
-                let trimmed = content.trim();
-                if trimmed.is_empty() {
-                    tracing::info!("{} is empty", filename);
-                    Ok(None)
-                } else {
-                    tracing::info!("Loaded {} ({} characters)", filename, trimmed.len());
-                    Ok(Some(trimmed.to_string()))
-                }
+                let language = content.trim();
+                (!language.is_empty()).then(|| {
+                    tracing::info!("Generating in {}", language);
+                    language.to_string()
+                })
             }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
             Err(e) => {
-                tracing::error!("Failed to read {}: {}", filename, e);
-                Ok(None)
+                tracing::error!("Failed to read language.txt: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Ask for `request`'s response in the generation language, if one is chosen
+    pub fn with_language(&self, request: String) -> String {
+        match &self.language {
+            Some(language) => format!("{}\n\nWrite your response in {}, whatever language the text above is in.", request, language),
+            None => request,
+        }
+    }
+    
+    /// Load the structured status, which may not exist yet
+    fn load_status(journal_dir: &Path) -> StructuredStatus {
+        match StructuredStatus::load(journal_dir) {
+            Ok(Some(status)) => {
+                tracing::info!("Loaded status ({} sections filled)", status.render().lines().count());
+                status
+            }
+            Ok(None) => {
+                tracing::info!("status.toml does not exist yet (will be created during summary generation)");
+                StructuredStatus::default()
+            }
+            Err(e) => {
+                tracing::error!("Failed to read status: {}", e);
+                StructuredStatus::default()
             }
         }
     }
@@ -151,38 +244,7 @@ impl PersonalizationConfig {
     
     /// Parse holidays content into Holiday structs
     fn parse_holidays(content: &str) -> Result<Vec<Holiday>, Box<dyn std::error::Error>> {
-        let mut holidays = Vec::new();
-        
-        for line in content.lines() {
-            let line = line.trim();
-            if line.is_empty() || line.starts_with('#') {
-                continue;
-            }
-            
-            // Parse format: "DATE|CATEGORY|NAME|DESCRIPTION" or "DATE|CATEGORY|NAME"
-            let parts: Vec<&str> = line.split('|').collect();
-            if parts.len() >= 3 {
-                let date = parts[0].trim().to_string();
-                let category = parts[1].trim().to_string();
-                let name = parts[2].trim().to_string();
-                let description = if parts.len() > 3 && !parts[3].trim().is_empty() {
-                    Some(parts[3].trim().to_string())
-                } else {
-                    None
-                };
-                
-                // Determine if recurring based on date format
-                let recurring = !date.contains('-') || date.len() == 5; // MM-DD format or just MM
-                
-                holidays.push(Holiday {
-                    name,
-                    date,
-                    category,
-                    description,
-                    recurring,
-                });
-            }
-        }
+        let holidays: Vec<Holiday> = content.lines().filter_map(Holiday::parse_line).collect();
         
         tracing::info!("Parsed {} holidays from holidays.txt", holidays.len());
         Ok(holidays)
@@ -190,67 +252,70 @@ impl PersonalizationConfig {
     
     /// Get enriched context by combining journal context with personalization
     pub fn enrich_context(&self, base_context: &str) -> String {
-        let mut enriched = String::new();
+        self.context_sections(base_context).into_iter().map(|(_, text)| text).collect()
+    }
+
+    /// The parts of the enriched context in the order they're sent, labeled so the
+    /// context budget can be measured per part
+    pub fn context_sections(&self, base_context: &str) -> Vec<(ContextSection, String)> {
+        let mut sections = Vec::new();
         
         // Add temporal context (current date and upcoming events)
-        enriched.push_str(&self.get_temporal_context());
+        sections.push((ContextSection::Temporal, self.get_temporal_context()));
         
         // Add user profile context
         if let Some(profile) = &self.profile {
             if !profile.trim().is_empty() {
-                enriched.push_str("USER PROFILE:\n");
-                enriched.push_str(profile);
-                enriched.push_str("\n\n");
+                sections.push((ContextSection::Profile, format!("USER PROFILE:\n{}\n\n", profile)));
             }
         }
         
         // Add AI style instructions
         if let Some(style) = &self.style {
             if !style.trim().is_empty() {
-                enriched.push_str("COMMUNICATION STYLE:\n");
-                enriched.push_str(style);
-                enriched.push_str("\n\n");
+                sections.push((ContextSection::Style, format!("COMMUNICATION STYLE:\n{}\n\n", style)));
             }
         }
         
         // Add dynamic status context
-        if let Some(status) = &self.status {
-            if !status.trim().is_empty() {
-                enriched.push_str("CURRENT STATUS:\n");
-                enriched.push_str(status);
-                enriched.push_str("\n\n");
-            }
+        let status = self.current_status();
+        if !status.is_empty() {
+            sections.push((ContextSection::Status, format!("CURRENT STATUS:\n{}\n\n", status.render())));
         }
         
         // Add the base journal context
-        enriched.push_str("JOURNAL CONTEXT:\n");
-        enriched.push_str(base_context);
+        sections.push((ContextSection::Journal, format!("JOURNAL CONTEXT:\n{}", base_context)));
         
-        enriched
+        sections
     }
     
-    /// Update the status.txt file with new context from LLM
-    pub fn update_status(&mut self, new_status: String) -> Result<(), Box<dyn std::error::Error>> {
-        let status_path = self.journal_dir.join("status.txt");
-        
-        // Write the new status to file
-        fs::write(&status_path, &new_status)?;
+    /// Apply a section update from the LLM to the status and save it.
+    /// Returns the labels of the sections that changed.
+    pub fn update_status(&mut self, update: &str) -> Result<Vec<&'static str>, Box<dyn std::error::Error>> {
+        let mut status = self.current_status();
+        let changed = status.apply_update(update);
+        if !changed.is_empty() {
+            status.save(&self.journal_dir)?;
+            tracing::info!("Updated status sections: {}", changed.join(", "));
+        }
         
         // Update the in-memory status
-        self.status = Some(new_status);
-        
-        tracing::info!("Updated status.txt with new context");
-        Ok(())
+        self.status = status;
+        Ok(changed)
     }
     
-    /// Get the current status for the LLM to reference when updating
-    pub fn get_current_status(&self) -> Option<&String> {
-        self.status.as_ref()
+    /// Get the current status for the LLM to reference when updating. It's read from disk
+    /// so edits made on the status page apply without a restart.
+    pub fn current_status(&self) -> StructuredStatus {
+        match StructuredStatus::load(&self.journal_dir) {
+            Ok(Some(status)) => status,
+            _ => self.status.clone(),
+        }
     }
     
     /// Get upcoming holidays within the next 30 days
     pub fn get_upcoming_holidays(&self) -> Vec<&Holiday> {
-        let today = Local::now().date_naive();
+        let today = crate::cycle_date::current_day();
         let mut upcoming = Vec::new();
         
         for holiday in &self.holidays {
@@ -305,16 +370,21 @@ impl PersonalizationConfig {
     
     /// Get temporal context for the current date
     pub fn get_temporal_context(&self) -> String {
-        let today = Local::now();
-        let date_str = today.format("%A, %B %d, %Y").to_string();
+        let today = crate::cycle_date::current_day();
+        let date_str = crate::locale::long_date(today);
         let upcoming_holidays = self.get_upcoming_holidays();
         
         let mut context = format!("CURRENT DATE: {}\n\n", date_str);
+
+        let chapters = crate::chapters::load(&self.journal_dir);
+        if let Some(chapter) = crate::chapters::temporal_context(&chapters, today) {
+            context.push_str(&chapter);
+        }
         
         if !upcoming_holidays.is_empty() {
             context.push_str("UPCOMING EVENTS (next 30 days):\n");
             for holiday in upcoming_holidays.iter().take(5) { // Limit to 5 most relevant
-                if let Some(days) = self.days_until_holiday(holiday, today.date_naive()) {
+                if let Some(days) = self.days_until_holiday(holiday, today) {
                     let day_text = if days == 0 {
                         "TODAY".to_string()
                     } else if days == 1 {
@@ -353,7 +423,7 @@ Examples of what to include:
 - Health conditions or lifestyle factors that affect your daily experience
 - Spiritual or philosophical practices you engage in
 
-Keep this information current but avoid including temporary situations that change frequently - those belong in status.txt instead.
+Keep this information current but avoid including temporary situations that change frequently - those belong in the status (status.toml) instead.
 
 Example profile:
 ---
@@ -407,6 +477,25 @@ mod tests {
         assert!(temp_dir.path().join("profile.txt").exists());
         assert!(temp_dir.path().join("style.txt").exists());
         assert!(temp_dir.path().join("prompts.json").exists());
+        assert!(!temp_dir.path().join("language.txt").exists());
+        assert_eq!(config.language, None);
+    }
+
+    #[test]
+    fn test_language() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = PersonalizationConfig::load(temp_dir.path()).unwrap();
+        assert_eq!(config.with_language("Ask a question.".to_string()), "Ask a question.");
+
+        fs::write(temp_dir.path().join("language.txt"), "German\n").unwrap();
+        let config = PersonalizationConfig::load(temp_dir.path()).unwrap();
+        assert_eq!(config.language.as_deref(), Some("German"));
+        let request = config.with_language("Ask a question.".to_string());
+        assert!(request.starts_with("Ask a question.\n\n"));
+        assert!(request.ends_with("Write your response in German, whatever language the text above is in."));
+
+        fs::write(temp_dir.path().join("language.txt"), "  \n").unwrap();
+        assert_eq!(PersonalizationConfig::load(temp_dir.path()).unwrap().language, None);
     }
     
     #[test]
@@ -415,7 +504,8 @@ mod tests {
             prompts: PromptsConfig::default(),
             profile: Some("I'm a software developer".to_string()),
             style: Some("Be encouraging and direct".to_string()),
-            status: Some("Currently working on a challenging project".to_string()),
+            language: None,
+            status: StructuredStatus::from_legacy_text("Currently working on a challenging project"),
             holidays: vec![], // Empty holidays for test
             journal_dir: PathBuf::from("/tmp"),
         };
@@ -457,7 +547,8 @@ mod tests {
             prompts: PromptsConfig::default(),
             profile: Some("Test user".to_string()),
             style: Some("Test style".to_string()),
-            status: Some("Test status".to_string()),
+            language: None,
+            status: StructuredStatus::from_legacy_text("Test status"),
             holidays: test_holidays,
             journal_dir: PathBuf::from("/tmp"),
         };
@@ -505,7 +596,7 @@ mod tests {
                     let upcoming = config.get_upcoming_holidays();
                     println!("\nUpcoming holidays in next 30 days: {}", upcoming.len());
                     for holiday in upcoming.iter().take(5) {
-                        if let Some(days) = config.days_until_holiday(holiday, Local::now().date_naive()) {
+                        if let Some(days) = config.days_until_holiday(holiday, crate::cycle_date::current_day()) {
                             let day_text = if days == 0 {
                                 "TODAY".to_string()
                             } else if days == 1 {
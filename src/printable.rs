@@ -0,0 +1,139 @@
+//! A printable page of a week's or month's entries, with each day's prompts above what was
+//! written, for reading on paper. The page stands alone rather than extending the site's
+//! layout: no navigation or background, larger type, and days kept whole across page
+//! breaks where they fit on one page.
+
+use askama::Template;
+use axum::{
+    extract::Query,
+    response::{Html, IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use serde::Deserialize;
+use std::str::FromStr;
+
+use crate::auth::{AuthContext, Scope};
+use crate::cycle_date::CycleDate;
+use crate::error::AppError;
+use crate::export::ExportedDay;
+use crate::notebook::ActiveNotebook;
+use crate::AppState;
+
+/// How much a printed page covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Period {
+    #[default]
+    Week,
+    Month,
+}
+
+impl FromStr for Period {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "week" => Ok(Period::Week),
+            "month" => Ok(Period::Month),
+            other => Err(format!("Unknown period '{}'; use week or month", other)),
+        }
+    }
+}
+
+impl Period {
+    pub fn key(self) -> &'static str {
+        match self {
+            Period::Week => "week",
+            Period::Month => "month",
+        }
+    }
+
+    /// First and last days of the period `date` falls in, in the configured calendar
+    pub fn bounds(self, date: &CycleDate) -> (CycleDate, CycleDate) {
+        match self {
+            Period::Week => date.week_bounds(),
+            Period::Month => date.month_bounds(),
+        }
+    }
+
+    /// "Week of Sol, Week 2, Monday" or the month's name
+    fn title(self, start: &CycleDate) -> String {
+        match self {
+            Period::Week => format!("Week of {}", start.date_name()),
+            Period::Month => start.month_name(),
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "print.html")]
+pub struct PrintTemplate {
+    pub notebook: String,
+    pub period: Period,
+    pub title: String,
+    pub start: CycleDate,
+    pub end: CycleDate,
+    pub days: Vec<ExportedDay>,
+    pub previous: CycleDate,
+    pub next: CycleDate,
+}
+
+/// "October 12 to October 18, 2026"
+pub fn real_dates(start: &CycleDate, end: &CycleDate) -> String {
+    let (start, end) = (start.to_real_date(), end.to_real_date());
+    format!("{} to {}", start.format("%B %-d"), end.format("%B %-d, %Y"))
+}
+
+#[derive(Deserialize)]
+pub struct PrintQuery {
+    /// "week" (the default) or "month"
+    period: Option<String>,
+    /// Any day in the period, in either calendar; today when absent
+    date: Option<String>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/journal/print", get(print_page))
+}
+
+/// The entries of the week or month around a day, laid out for printing
+/// (GET /journal/print?period=week|month&date=..)
+async fn print_page(
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Query(query): Query<PrintQuery>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::ReadEntries) {
+        return rejection.into_response();
+    }
+
+    let period: Period = match query.period.as_deref().map(str::parse).transpose() {
+        Ok(period) => period.unwrap_or_default(),
+        Err(e) => return AppError::bad_request(e).into_response(),
+    };
+    let date = match query.date.as_deref().map(crate::handlers::parse_date_param).transpose() {
+        Ok(date) => date.unwrap_or_else(CycleDate::today),
+        Err(e) => return AppError::bad_request(e).into_response(),
+    };
+
+    let (start, end) = period.bounds(&date);
+    let days = match crate::export::collect(&notebook.journal_manager, start, end).await.map_err(|e| e.to_string()) {
+        Ok(days) => days,
+        Err(e) => return AppError::internal("Error collecting entries", e).into_response(),
+    };
+
+    let template = PrintTemplate {
+        notebook: notebook.name.clone(),
+        period,
+        title: period.title(&start),
+        start,
+        end,
+        days,
+        previous: start.previous_day(),
+        next: end.next_day(),
+    };
+    match template.render() {
+        Ok(html) => Html(html).into_response(),
+        Err(e) => AppError::internal("Error rendering page", e).into_response(),
+    }
+}
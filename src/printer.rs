@@ -0,0 +1,185 @@
+use crate::config::PrinterConfig;
+use crate::cycle_date::CycleDate;
+use crate::journal::JournalPrompt;
+use std::net::SocketAddr;
+use tokio::io::AsyncWriteExt;
+
+// ESC/POS control sequences
+const INIT: &[u8] = &[0x1B, 0x40];
+const BOLD_ON: &[u8] = &[0x1B, 0x45, 0x01];
+const BOLD_OFF: &[u8] = &[0x1B, 0x45, 0x00];
+const ALIGN_LEFT: &[u8] = &[0x1B, 0x61, 0x00];
+const ALIGN_CENTER: &[u8] = &[0x1B, 0x61, 0x01];
+const FEED_AND_CUT: &[u8] = &[0x1D, 0x56, 0x41, 0x03];
+
+/// Print the day's prompts (plus yesterday's one-line summary) on the configured receipt printer
+pub async fn print_morning_prompts(
+    printer: &PrinterConfig,
+    cycle_date: &CycleDate,
+    prompts: &[JournalPrompt],
+    yesterday_summary: Option<&str>,
+) -> Result<(), String> {
+    if !printer.enabled {
+        return Ok(());
+    }
+
+    let document = build_receipt(printer.line_width, cycle_date, prompts, yesterday_summary);
+    send_to_printer(&printer.target, &document).await?;
+
+    tracing::info!("Printed {} prompts for {} on {}", prompts.len(), cycle_date, printer.target);
+    Ok(())
+}
+
+/// Compose the ESC/POS byte stream for a morning receipt
+fn build_receipt(
+    line_width: usize,
+    cycle_date: &CycleDate,
+    prompts: &[JournalPrompt],
+    yesterday_summary: Option<&str>,
+) -> Vec<u8> {
+    let mut doc = Vec::new();
+    doc.extend_from_slice(INIT);
+
+    // Header: cycle date and real date, centered
+    doc.extend_from_slice(ALIGN_CENTER);
+    doc.extend_from_slice(BOLD_ON);
+    push_line(&mut doc, &cycle_date.to_string());
+    doc.extend_from_slice(BOLD_OFF);
+    push_line(&mut doc, &cycle_date.to_real_date().format("%a %b %d, %Y").to_string());
+    push_line(&mut doc, &"-".repeat(line_width));
+    doc.extend_from_slice(ALIGN_LEFT);
+
+    if let Some(summary) = yesterday_summary {
+        doc.extend_from_slice(BOLD_ON);
+        push_line(&mut doc, "Yesterday");
+        doc.extend_from_slice(BOLD_OFF);
+        for line in wrap_text(&one_line_summary(summary), line_width) {
+            push_line(&mut doc, &line);
+        }
+        push_line(&mut doc, "");
+    }
+
+    for prompt in prompts {
+        doc.extend_from_slice(BOLD_ON);
+        push_line(&mut doc, &format!("Prompt {}", prompt.prompt_number));
+        doc.extend_from_slice(BOLD_OFF);
+        for line in wrap_text(&prompt.prompt, line_width) {
+            push_line(&mut doc, &line);
+        }
+        push_line(&mut doc, "");
+    }
+
+    doc.extend_from_slice(FEED_AND_CUT);
+    doc
+}
+
+fn push_line(doc: &mut Vec<u8>, line: &str) {
+    doc.extend(to_printable_ascii(line).bytes());
+    doc.push(b'\n');
+}
+
+/// Reduce a summary to its first sentence so it fits the receipt
+fn one_line_summary(summary: &str) -> String {
+    let summary = summary.trim();
+    match summary.find(". ") {
+        Some(end) => summary[..=end].to_string(),
+        None => summary.lines().next().unwrap_or_default().to_string(),
+    }
+}
+
+/// Replace characters most receipt printers can't render with ASCII equivalents
+fn to_printable_ascii(text: &str) -> String {
+    text.chars()
+        .filter_map(|c| match c {
+            '\u{2018}' | '\u{2019}' => Some('\''),
+            '\u{201C}' | '\u{201D}' => Some('"'),
+            '\u{2013}' | '\u{2014}' => Some('-'),
+            '\u{2026}' => Some('.'),
+            '*' | '_' | '#' => None, // Markdown emphasis and headings
+            c if c.is_ascii() && !c.is_ascii_control() => Some(c),
+            _ => Some('?'),
+        })
+        .collect()
+}
+
+/// Word-wrap text to the printer's line width, preserving paragraph breaks
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for paragraph in text.lines() {
+        let mut current = String::new();
+        for word in paragraph.split_whitespace() {
+            if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > width {
+                lines.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(word);
+        }
+        lines.push(current);
+    }
+
+    lines
+}
+
+/// Send raw bytes to a network printer (host:port) or a local device file
+async fn send_to_printer(target: &str, document: &[u8]) -> Result<(), String> {
+    if let Ok(addr) = target.parse::<SocketAddr>() {
+        let mut stream = tokio::net::TcpStream::connect(addr)
+            .await
+            .map_err(|e| format!("Failed to connect to printer at {}: {}", addr, e))?;
+        stream.write_all(document).await.map_err(|e| format!("Failed to send to printer: {}", e))?;
+        stream.flush().await.map_err(|e| format!("Failed to send to printer: {}", e))?;
+    } else {
+        let mut device = tokio::fs::OpenOptions::new()
+            .write(true)
+            .open(target)
+            .await
+            .map_err(|e| format!("Failed to open printer device {}: {}", target, e))?;
+        device.write_all(document).await.map_err(|e| format!("Failed to write to printer: {}", e))?;
+        device.flush().await.map_err(|e| format!("Failed to write to printer: {}", e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::PromptType;
+    use chrono::Local;
+
+    #[test]
+    fn test_wrap_text() {
+        let lines = wrap_text("What small moment today made you feel most like yourself?", 20);
+        assert!(lines.iter().all(|l| l.len() <= 20));
+        assert_eq!(lines.join(" "), "What small moment today made you feel most like yourself?");
+    }
+
+    #[test]
+    fn test_printable_ascii() {
+        assert_eq!(to_printable_ascii("It\u{2019}s **bold** \u{2014} caf\u{e9}"), "It's bold - caf?");
+    }
+
+    #[test]
+    fn test_receipt_contents() {
+        let cycle_date = CycleDate::new(1, 2, 1, 3).unwrap();
+        let prompts = vec![JournalPrompt {
+            cycle_date,
+            prompt: "What are you looking forward to?".to_string(),
+            prompt_number: 1,
+            generated_at: Local::now(),
+            prompt_type: PromptType::Daily,
+        }];
+
+        let doc = build_receipt(32, &cycle_date, &prompts, Some("Long walk with Sam. Then a quiet evening."));
+        let text = String::from_utf8_lossy(&doc);
+
+        assert!(doc.starts_with(INIT));
+        assert!(doc.ends_with(FEED_AND_CUT));
+        assert!(text.contains("01213"));
+        assert!(text.contains("Long walk with Sam."));
+        assert!(!text.contains("quiet evening"));
+        assert!(text.contains("What are you looking forward to?"));
+    }
+}
@@ -0,0 +1,125 @@
+use crate::cycle_date::CycleDate;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+/// Events kept for a slow listener before it starts missing some
+const CHANNEL_CAPACITY: usize = 64;
+
+/// Where a background prompt generation job is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GenerationStage {
+    Queued,
+    Generating,
+    Saved,
+    Failed,
+}
+
+/// A lifecycle change for one day's prompt
+#[derive(Debug, Clone, Serialize)]
+pub struct GenerationEvent {
+    pub cycle_date: CycleDate,
+    pub prompt_number: u8,
+    pub stage: GenerationStage,
+    /// The prompt text, once saved
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    /// Why generation failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl GenerationEvent {
+    pub fn new(cycle_date: CycleDate, prompt_number: u8, stage: GenerationStage) -> Self {
+        Self { cycle_date, prompt_number, stage, prompt: None, error: None }
+    }
+
+    pub fn saved(cycle_date: CycleDate, prompt_number: u8, prompt: String) -> Self {
+        Self { prompt: Some(prompt), ..Self::new(cycle_date, prompt_number, GenerationStage::Saved) }
+    }
+
+    pub fn failed(cycle_date: CycleDate, prompt_number: u8, error: String) -> Self {
+        Self { error: Some(error), ..Self::new(cycle_date, prompt_number, GenerationStage::Failed) }
+    }
+}
+
+/// Broadcasts a notebook's generation events to every open page.
+/// Events sent while nobody is listening are dropped, but each prompt's latest
+/// unfinished or failed stage is kept for clients that poll instead.
+#[derive(Debug)]
+pub struct GenerationProgress {
+    sender: broadcast::Sender<GenerationEvent>,
+    latest: Mutex<HashMap<(CycleDate, u8), GenerationEvent>>,
+}
+
+impl GenerationProgress {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { sender, latest: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn send(&self, event: GenerationEvent) {
+        tracing::debug!("Prompt {} for {}: {:?}", event.prompt_number, event.cycle_date, event.stage);
+        if let Ok(mut latest) = self.latest.lock() {
+            let key = (event.cycle_date, event.prompt_number);
+            // A saved prompt is on disk, so there's nothing left to report
+            if event.stage == GenerationStage::Saved {
+                latest.remove(&key);
+            } else {
+                latest.insert(key, event.clone());
+            }
+        }
+        let _ = self.sender.send(event);
+    }
+
+    /// The last event for a prompt that is queued, generating, or failed
+    pub fn latest(&self, cycle_date: CycleDate, prompt_number: u8) -> Option<GenerationEvent> {
+        self.latest.lock().ok()?.get(&(cycle_date, prompt_number)).cloned()
+    }
+
+    /// Whether a prompt is queued or being generated
+    pub fn in_progress(&self, cycle_date: CycleDate, prompt_number: u8) -> bool {
+        self.latest(cycle_date, prompt_number)
+            .is_some_and(|event| matches!(event.stage, GenerationStage::Queued | GenerationStage::Generating))
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<GenerationEvent> {
+        self.sender.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_generation_progress() {
+        let progress = GenerationProgress::new();
+        let date = CycleDate::new(1, 2, 3, 4).unwrap();
+        // Nobody is listening yet; this must not fail
+        progress.send(GenerationEvent::new(date, 1, GenerationStage::Queued));
+
+        let mut receiver = progress.subscribe();
+        progress.send(GenerationEvent::new(date, 2, GenerationStage::Generating));
+        progress.send(GenerationEvent::saved(date, 2, "What surprised you?".to_string()));
+
+        assert_eq!(receiver.recv().await.unwrap().stage, GenerationStage::Generating);
+        let saved = receiver.recv().await.unwrap();
+        assert_eq!(saved.stage, GenerationStage::Saved);
+
+        let json = serde_json::to_value(&saved).unwrap();
+        assert_eq!(json["stage"], "saved");
+        assert_eq!(json["prompt"], "What surprised you?");
+        assert_eq!(json["cycle_date"], date.to_string());
+        assert!(json.get("error").is_none());
+
+        // Polling clients see unfinished and failed prompts, not saved ones
+        assert!(progress.latest(date, 2).is_none());
+        assert!(progress.in_progress(date, 1));
+        progress.send(GenerationEvent::failed(date, 1, "Model unavailable".to_string()));
+        assert!(!progress.in_progress(date, 1));
+        assert_eq!(progress.latest(date, 1).unwrap().error.as_deref(), Some("Model unavailable"));
+    }
+}
@@ -1,20 +1,33 @@
-use crate::config::Config;
+use crate::config::{Config, EnsembleMode};
 use crate::cycle_date::CycleDate;
-use crate::journal::{JournalManager, PromptType};
-use crate::llm_worker::LlmManager;
-use crate::personalization::PersonalizationConfig;
+use crate::journal::{
+    EnsembleCandidate, EnsembleChooser, EnsembleRecord, JournalManager, JournalPrompt, JournalStatus, JournalSummary,
+    PromptProvenance, PromptType,
+};
+use crate::llm_worker::{build_prompt_request, LlmManager, LlmWorker};
+use crate::personalization::{PersonalizationConfig, SharedPersonalization};
+use crate::progress::{GenerationEvent, GenerationProgress, GenerationStage};
 use crate::prompts::PromptsConfig;
+use crate::report::{ProcessingReport, StatusChange};
+use crate::similarity::most_similar;
+use crate::telemetry::{self, ContextBudget, GenerationRecord};
+use crate::work_queue::{Task, WorkQueue};
 use std::sync::Arc;
-use tokio::time::{sleep, Duration};
-use chrono::{Local, NaiveTime};
+use chrono::Local;
 
-/// Background service that generates daily prompts at a scheduled time
+/// One-line summaries written per nightly run
+const ONE_LINE_BATCH: usize = 30;
+
+/// Writes a notebook's prompts and summaries with the model. The scheduler decides when the
+/// nightly work runs; this does each step of it, and the on-demand work pages ask for.
 pub struct PromptGenerator {
     journal_manager: Arc<JournalManager>,
     llm_manager: Arc<LlmManager>,
     config: Arc<Config>,
-    personalization_config: Arc<PersonalizationConfig>,
-    is_running: Arc<tokio::sync::Mutex<bool>>,
+    personalization_config: Arc<SharedPersonalization>,
+    progress: Arc<GenerationProgress>,
+    /// Summaries and prompts that failed, waiting to be tried again
+    queue: Arc<WorkQueue>,
 }
 
 impl PromptGenerator {
@@ -22,120 +35,37 @@ impl PromptGenerator {
         journal_manager: Arc<JournalManager>,
         llm_manager: Arc<LlmManager>,
         config: Arc<Config>,
-        personalization_config: Arc<PersonalizationConfig>,
+        personalization_config: Arc<SharedPersonalization>,
     ) -> Self {
         Self {
+            queue: Arc::new(WorkQueue::load(journal_manager.base_path())),
             journal_manager,
             llm_manager,
             config,
             personalization_config,
-            is_running: Arc::new(tokio::sync::Mutex::new(false)),
+            progress: Arc::new(GenerationProgress::new()),
         }
     }
 
-    /// Start the background prompt generation service
-    pub async fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let mut is_running = self.is_running.lock().await;
-        if *is_running {
-            tracing::warn!("Prompt generator is already running");
-            return Ok(());
-        }
-        *is_running = true;
-        drop(is_running);
-
-        tracing::info!("Starting prompt generator service");
-        tracing::info!("   Unified daily processing (summaries, status, prompts) scheduled for: {}", self.config.journal.prompt_generation_time);
-        
-        // Clone references for the background task
-        let journal_manager = Arc::clone(&self.journal_manager);
-        let llm_manager = Arc::clone(&self.llm_manager);
-        let config = Arc::clone(&self.config);
-        let personalization_config = Arc::clone(&self.personalization_config);
-        let is_running = Arc::clone(&self.is_running);
-
-        // Spawn background task
-        tokio::spawn(async move {
-            // Check if we need to generate prompts immediately on startup
-            if let Err(e) = Self::check_and_generate_startup_prompts(
-                Arc::clone(&journal_manager),
-                Arc::clone(&llm_manager),
-                Arc::clone(&config),
-                Arc::clone(&personalization_config),
-            ).await {
-                tracing::error!("Failed to check/generate startup prompts: {}", e);
-            }
-
-            loop {
-                // Check if we should still be running
-                {
-                    let running = is_running.lock().await;
-                    if !*running {
-                        tracing::info!("Prompt generator service stopped");
-                        break;
-                    }
-                }
-
-                // Calculate time until next prompt generation
-                if let Ok(sleep_duration) = Self::calculate_sleep_until_prompt_time(&config.journal.prompt_generation_time) {
-                    tracing::info!("Next prompt generation in {:.1} hours", sleep_duration.as_secs_f64() / 3600.0);
-                    
-                    // Sleep until prompt generation time
-                    sleep(sleep_duration).await;
-                    
-                    // Generate prompts for today
-                    if let Err(e) = Self::generate_daily_prompts(
-                        Arc::clone(&journal_manager),
-                        Arc::clone(&llm_manager),
-                        Arc::clone(&config),
-                        Arc::clone(&personalization_config),
-                    ).await {
-                        tracing::error!("Failed to generate daily processing (summaries, status, prompts): {}", e);
-                    }
-                    
-                    // Sleep for a minute to avoid immediate re-triggering
-                    sleep(Duration::from_secs(60)).await;
-                } else {
-                    tracing::error!("Invalid prompt generation time format, sleeping for 1 hour");
-                    sleep(Duration::from_secs(3600)).await;
-                }
-            }
-        });
-
-        Ok(())
+    /// Summaries and prompts that failed, waiting to be tried again
+    pub fn queue(&self) -> &WorkQueue {
+        &self.queue
     }
 
-    /// Stop the background prompt generation service
-    pub async fn stop(&self) {
-        let mut is_running = self.is_running.lock().await;
-        *is_running = false;
-        tracing::info!("Prompt generator service stopping...");
+    /// The main model's worker, shared with every other generation, for one-off requests
+    pub fn worker(&self) -> Arc<LlmWorker> {
+        self.llm_manager.get_worker()
     }
 
-    /// Calculate duration to sleep until the specified time today (or tomorrow if time has passed)
-    fn calculate_sleep_until_prompt_time(time_str: &str) -> Result<Duration, String> {
-        // Parse the time string (e.g., "06:00")
-        let target_time = NaiveTime::parse_from_str(time_str, "%H:%M")
-            .map_err(|e| format!("Invalid time format: {}", e))?;
-        
-        let now = Local::now();
-        let today = now.date_naive();
-        
-        // Create target datetime for today
-        let mut target_datetime = today.and_time(target_time).and_local_timezone(Local).single()
-            .ok_or("Failed to create target datetime")?;
-        
-        // If the time has already passed today, schedule for tomorrow
-        if target_datetime <= now {
-            target_datetime = target_datetime + chrono::Duration::days(1);
-        }
-        
-        let duration_until_target = (target_datetime - now).to_std()
-            .map_err(|e| format!("Duration conversion failed: {}", e))?;
-        Ok(duration_until_target)
+    /// Lifecycle events for prompts queued with `queue_prompt_generation`
+    pub fn progress(&self) -> &GenerationProgress {
+        &self.progress
     }
 
     /// Unified prompt generation function with optional summary/status checks
     /// - skip_checks: true to skip summary/status generation (for 2nd and 3rd prompts in daily batch)
+    /// - report: collects what was summarized and generated, for the processing report
+    #[allow(clippy::too_many_arguments)]
     async fn generate_prompts_unified(
         journal_manager: Arc<JournalManager>,
         llm_manager: Arc<LlmManager>,
@@ -144,6 +74,8 @@ impl PromptGenerator {
         cycle_date: &CycleDate,
         skip_checks: bool,
         max_prompts_override: Option<u8>,
+        queue: &WorkQueue,
+        report: &mut ProcessingReport,
     ) -> Result<(), String> {
         tracing::info!("Generating prompts for {} (skip_checks: {})", cycle_date, skip_checks);
 
@@ -154,23 +86,17 @@ impl PromptGenerator {
             tracing::info!("Prompts already exist for {} ({}/{})", cycle_date, existing_prompts, max_prompts);
             return Ok(());
         }
+        if journal_manager.no_prompt_needed(cycle_date).await {
+            tracing::info!("{} is marked as needing no prompt, skipping generation", cycle_date);
+            report.no_prompt_needed = true;
+            return Ok(());
+        }
 
         // Load the LLM model
         tracing::debug!("Loading LLM model for prompt generation...");
         llm_manager.prepare_for_processing().await.map_err(|e| e.to_string())?;
         let llm_worker = llm_manager.get_worker();
 
-        // Determine prompt type based on date's position in the cycle
-        let prompt_type = if cycle_date.is_first_day_of_year() {
-            PromptType::YearlyReflection
-        } else if cycle_date.is_first_day_of_month() {
-            PromptType::MonthlyReflection
-        } else if cycle_date.is_first_day_of_week() {
-            PromptType::WeeklyReflection
-        } else {
-            PromptType::Daily
-        };
-
         // Generate the missing prompts, with optimized checks
         for prompt_number in (existing_prompts + 1)..=max_prompts {
             tracing::info!("Generating prompt {} for {}", prompt_number, cycle_date);
@@ -180,26 +106,33 @@ impl PromptGenerator {
             
             if !should_skip_checks {
                 tracing::debug!("Checking for entries that need summaries and status files...");
-                if let Err(e) = Self::generate_missing_summaries(&journal_manager, &llm_worker, &personalization_config).await {
+                if let Err(e) = Self::generate_missing_summaries(&journal_manager, &llm_worker, &personalization_config, queue, report).await {
                     tracing::warn!("Failed to generate some summaries/status files: {}", e);
+                    report.error(format!("Summary generation failed: {}", e));
                     // Continue anyway - prompts can still be generated without perfect context
                 }
+                if let Err(e) = Self::compact_old_summaries(&journal_manager, &llm_worker, &personalization_config, report).await {
+                    tracing::warn!("Failed to write some one-line summaries: {}", e);
+                    report.error(format!("One-line summaries failed: {}", e));
+                }
+                Self::refresh_embeddings(&journal_manager, &llm_worker, &config).await;
             } else {
                 tracing::debug!("Skipping summary/status checks for prompt {}", prompt_number);
             }
 
-            // Get context for prompt generation (will use existing summaries if available)
-            let context = journal_manager.get_context_for_prompt(cycle_date).await.map_err(|e| e.to_string())?;
-            
-            let prompt = llm_worker.generate_prompt(
+            // Generate with context from existing summaries, avoiding repeats of recent prompts
+            let (prompt, provenance) = Self::generate_ensemble_prompt(
+                &journal_manager,
+                &llm_manager,
+                &config,
+                &personalization_config,
                 cycle_date,
-                &context,
                 prompt_number,
-                prompt_type.clone(),
-                &personalization_config,
+                None,
             ).await.map_err(|e| e.to_string())?;
             
-            journal_manager.save_prompt(&prompt).await.map_err(|e| e.to_string())?;
+            journal_manager.save_prompt_with_provenance(&prompt, &provenance).await.map_err(|e| e.to_string())?;
+            report.prompts_generated.push((prompt_number, prompt.prompt));
             
             tracing::info!("Prompt {} saved for {}", prompt_number, cycle_date);
         }
@@ -208,62 +141,255 @@ impl PromptGenerator {
         Ok(())
     }
 
-    /// Generate prompts for today (unified daily processing)
-    /// This function handles all daily processing at the scheduled time:
-    /// 1. Generates missing summaries and status files for old entries
-    /// 2. Generates today's prompts with proper context
-    async fn generate_daily_prompts(
-        journal_manager: Arc<JournalManager>,
-        llm_manager: Arc<LlmManager>,
-        config: Arc<Config>,
-        personalization_config: Arc<PersonalizationConfig>,
-    ) -> Result<(), String> {
-        let today = CycleDate::today();
-        Self::generate_prompts_unified(
-            journal_manager,
-            llm_manager,
-            config,
-            personalization_config,
-            &today,
-            false, // Don't skip checks for daily generation
-            None,  // Use default max_prompts_per_day
-        ).await
+    /// Write summaries and status files for entries that don't have them, then bring the
+    /// search index up to date. A failed summary is reported but doesn't stop the rest.
+    pub(crate) async fn catch_up_summaries(&self, report: &mut ProcessingReport) -> Result<(), String> {
+        self.llm_manager.prepare_for_processing().await.map_err(|e| e.to_string())?;
+        let llm_worker = self.llm_manager.get_worker();
+        if let Err(e) = Self::generate_missing_summaries(&self.journal_manager, &llm_worker, &self.personalization_config.current(), &self.queue, report).await {
+            tracing::warn!("Failed to generate some summaries/status files: {}", e);
+            report.error(format!("Summary generation failed: {}", e));
+        }
+        Self::refresh_embeddings(&self.journal_manager, &llm_worker, &self.config).await;
+        Ok(())
     }
 
-    /// Public function for external callers (like journal processor)
-    pub async fn generate_prompts_for_date(
-        journal_manager: Arc<JournalManager>,
-        llm_manager: Arc<LlmManager>,
-        config: Arc<Config>,
-        personalization_config: Arc<PersonalizationConfig>,
-        cycle_date: &CycleDate,
-        skip_checks: bool,
-        max_prompts_override: Option<u8>,
-    ) -> Result<(), String> {
+    /// Generate a day's missing prompts, up to `max_prompts_per_day`, catching up summaries
+    /// and one-line summaries before the first
+    pub(crate) async fn generate_daily_prompts(&self, cycle_date: &CycleDate, report: &mut ProcessingReport) -> Result<(), String> {
         Self::generate_prompts_unified(
-            journal_manager,
-            llm_manager,
-            config,
-            personalization_config,
+            Arc::clone(&self.journal_manager),
+            Arc::clone(&self.llm_manager),
+            Arc::clone(&self.config),
+            self.personalization_config.current(),
             cycle_date,
-            skip_checks,
-            max_prompts_override,
+            false,
+            None,
+            &self.queue,
+            report,
         ).await
     }
 
+    /// How many prompts a day has
+    pub(crate) async fn prompt_count(&self, cycle_date: &CycleDate) -> u8 {
+        Self::count_existing_prompts(&self.journal_manager, cycle_date).await
+    }
+
     /// Count how many prompts already exist for a given date
     async fn count_existing_prompts(journal_manager: &JournalManager, cycle_date: &CycleDate) -> u8 {
         let mut count = 0;
         for i in 1..=3 {  // Max 3 prompts
-            if journal_manager.load_prompt(cycle_date, i).await.is_ok() {
-                if journal_manager.load_prompt(cycle_date, i).await.unwrap().is_some() {
-                    count += 1;
-                }
+            if let Ok(Some(_)) = journal_manager.load_prompt(cycle_date, i).await {
+                count += 1;
             }
         }
         count
     }
 
+    /// Past entries for prompt generation, plus the writing streak and the memory lane entry
+    /// when enabled and, for yearly reflections, the year's chapters
+    async fn prompt_context(
+        journal_manager: &JournalManager,
+        config: &Config,
+        cycle_date: &CycleDate,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let mut context = journal_manager.get_context_for_prompt(cycle_date).await?;
+
+        if PromptType::for_date(cycle_date) == PromptType::YearlyReflection {
+            let chapters = crate::chapters::load(journal_manager.base_path());
+            if let Some(framing) = crate::chapters::yearly_framing(&chapters, cycle_date.to_real_date()) {
+                context.push(framing);
+            }
+        }
+
+        if config.journal.streak_in_prompt_context {
+            let stats = crate::stats::refresh_stats(journal_manager).await?;
+            if let Some(encouragement) = stats.encouragement() {
+                context.push(encouragement);
+            }
+        }
+
+        if config.journal.memory_lane_in_prompt_context {
+            if let Some(memory) = crate::memory_lane::of_the_day(journal_manager, cycle_date).await? {
+                context.push(memory.prompt_context());
+            }
+        }
+
+        Ok(context)
+    }
+
+    /// Record how the context for a prompt generation splits between profile, status, and
+    /// journal context, for the context budget dashboard. Failures are only logged.
+    #[allow(clippy::too_many_arguments)]
+    async fn record_context_budget(
+        journal_manager: &JournalManager,
+        personalization_config: &PersonalizationConfig,
+        context: &[String],
+        cycle_date: &CycleDate,
+        prompt_number: u8,
+        steering: Option<&str>,
+        model: &str,
+    ) {
+        let sections = personalization_config.context_sections(&context.join("\n\n"));
+        let enriched: String = sections.iter().map(|(_, text)| text.as_str()).collect();
+        let request = build_prompt_request(&enriched, cycle_date, prompt_number, &PromptType::for_date(cycle_date), personalization_config, steering);
+        let record = GenerationRecord {
+            at: Local::now(),
+            cycle_date: cycle_date.to_string(),
+            prompt_number,
+            model: Some(model.to_string()),
+            budget: ContextBudget::measure(&sections, &request),
+        };
+        if let Err(e) = telemetry::record(journal_manager.base_path(), &record).await {
+            tracing::warn!("Failed to record context budget: {}", e);
+        }
+    }
+
+    /// Generate a prompt, regenerating it when it's too similar to one from the last
+    /// `dedup_window_days` days. After `dedup_max_retries` attempts the most distinct
+    /// candidate is kept.
+    #[allow(clippy::too_many_arguments)]
+    async fn generate_distinct_prompt(
+        journal_manager: &JournalManager,
+        llm_worker: &LlmWorker,
+        config: &Config,
+        personalization_config: &PersonalizationConfig,
+        context: &[String],
+        cycle_date: &CycleDate,
+        prompt_number: u8,
+        steering: Option<&str>,
+    ) -> Result<JournalPrompt, Box<dyn std::error::Error>> {
+        let prompt_type = PromptType::for_date(cycle_date);
+        let recent = if config.journal.dedup_window_days > 0 {
+            journal_manager.recent_prompts(cycle_date, config.journal.dedup_window_days).await
+        } else {
+            Vec::new()
+        };
+
+        let mut instruction = steering.map(str::to_string);
+        let mut best: Option<(f64, JournalPrompt)> = None;
+        for attempt in 0..=config.journal.dedup_max_retries {
+            let prompt = llm_worker.generate_prompt(
+                cycle_date,
+                context,
+                prompt_number,
+                prompt_type.clone(),
+                personalization_config,
+                instruction.as_deref(),
+            ).await?;
+
+            let Some((score, similar)) = most_similar(&prompt.prompt, &recent) else {
+                return Ok(prompt);
+            };
+            if score < config.journal.dedup_threshold {
+                return Ok(prompt);
+            }
+
+            tracing::info!("Prompt {} for {} repeats a recent prompt (similarity {:.2}, attempt {})", prompt_number, cycle_date, score, attempt + 1);
+            let avoid = format!("Ask about something different from this recent prompt: \"{}\"", similar.replace('\n', " "));
+            instruction = Some(match steering {
+                Some(steering) => format!("{} {}", steering, avoid),
+                None => avoid,
+            });
+            if best.as_ref().is_none_or(|(best_score, _)| score < *best_score) {
+                best = Some((score, prompt));
+            }
+        }
+
+        let (score, prompt) = best.expect("at least one attempt was made");
+        tracing::warn!("Keeping prompt {} for {} despite similarity {:.2} to a recent prompt", prompt_number, cycle_date, score);
+        Ok(prompt)
+    }
+
+    /// Generate a prompt with the main model and, when `llm.ensemble_model` is set, a competing
+    /// candidate from the second model. In judge mode the main model keeps the better of the
+    /// two; in "both" mode the main model's candidate is shown until the user picks one.
+    /// Returns the prompt to save and the provenance recording both candidates.
+    #[allow(clippy::too_many_arguments)]
+    async fn generate_ensemble_prompt(
+        journal_manager: &JournalManager,
+        llm_manager: &LlmManager,
+        config: &Config,
+        personalization_config: &PersonalizationConfig,
+        cycle_date: &CycleDate,
+        prompt_number: u8,
+        steering: Option<&str>,
+    ) -> Result<(JournalPrompt, PromptProvenance), Box<dyn std::error::Error>> {
+        let llm_worker = llm_manager.get_worker();
+        let context = Self::prompt_context(journal_manager, config, cycle_date).await?;
+        Self::record_context_budget(journal_manager, personalization_config, &context, cycle_date, prompt_number, steering, llm_worker.model_name()).await;
+        let prompt = Self::generate_distinct_prompt(
+            journal_manager,
+            &llm_worker,
+            config,
+            personalization_config,
+            &context,
+            cycle_date,
+            prompt_number,
+            steering,
+        ).await?;
+        let mut provenance = PromptProvenance {
+            steering: steering.map(str::to_string),
+            model: Some(llm_worker.model_name().to_string()),
+            ensemble: None,
+        };
+
+        let Some(ensemble_worker) = llm_manager.get_ensemble_worker() else {
+            return Ok((prompt, provenance));
+        };
+        let alternative = match Self::generate_distinct_prompt(
+            journal_manager,
+            &ensemble_worker,
+            config,
+            personalization_config,
+            &context,
+            cycle_date,
+            prompt_number,
+            steering,
+        ).await {
+            Ok(alternative) => alternative,
+            Err(e) => {
+                tracing::warn!("Ensemble model {} failed, keeping {}'s prompt: {}", ensemble_worker.model_name(), llm_worker.model_name(), e);
+                return Ok((prompt, provenance));
+            }
+        };
+
+        let mut ensemble = EnsembleRecord {
+            candidates: vec![
+                EnsembleCandidate { model: llm_worker.model_name().to_string(), prompt: prompt.prompt.clone() },
+                EnsembleCandidate { model: ensemble_worker.model_name().to_string(), prompt: alternative.prompt.clone() },
+            ],
+            winner: None,
+            chosen_by: None,
+        };
+        if config.llm.ensemble_mode == EnsembleMode::Both {
+            provenance.ensemble = Some(ensemble);
+            return Ok((prompt, provenance));
+        }
+
+        let judge_prompt = personalization_config.prompts.get_ensemble_judge_prompt(&context.join("\n\n"), &prompt.prompt, &alternative.prompt);
+        let verdict = llm_worker.generate_text(&judge_prompt, 10).await;
+        let keep_first = match verdict.as_deref().map(PromptsConfig::parse_judge_verdict) {
+            Ok(Some(keep_first)) => keep_first,
+            Ok(None) => {
+                tracing::warn!("Unclear ensemble verdict for prompt {} on {}, keeping {}'s prompt", prompt_number, cycle_date, llm_worker.model_name());
+                true
+            }
+            Err(e) => {
+                tracing::warn!("Ensemble judging failed for prompt {} on {}, keeping {}'s prompt: {}", prompt_number, cycle_date, llm_worker.model_name(), e);
+                true
+            }
+        };
+
+        let (winner, prompt) = if keep_first { (llm_worker.model_name(), prompt) } else { (ensemble_worker.model_name(), alternative) };
+        tracing::info!("Ensemble judge picked {}'s prompt {} for {}", winner, prompt_number, cycle_date);
+        ensemble.winner = Some(winner.to_string());
+        ensemble.chosen_by = Some(EnsembleChooser::Judge);
+        provenance.model = Some(winner.to_string());
+        provenance.ensemble = Some(ensemble);
+        Ok((prompt, provenance))
+    }
+
     /// Generate a specific prompt on-demand (for when user navigates past existing prompts)
     pub async fn generate_prompt_on_demand(
         &self,
@@ -285,70 +411,193 @@ impl PromptGenerator {
 
         // Load the LLM model
         self.llm_manager.prepare_for_processing().await?;
-        let llm_worker = self.llm_manager.get_worker();
-
-        // Determine prompt type
-        let prompt_type = if cycle_date.is_first_day_of_year() {
-            PromptType::YearlyReflection
-        } else if cycle_date.is_first_day_of_month() {
-            PromptType::MonthlyReflection
-        } else if cycle_date.is_first_day_of_week() {
-            PromptType::WeeklyReflection
-        } else {
-            PromptType::Daily
-        };
-
-        // Get context for prompt generation
-        let context = self.journal_manager.get_context_for_prompt(cycle_date).await?;
 
         // Generate the prompt
-        let prompt = llm_worker.generate_prompt(
+        let (prompt, provenance) = Self::generate_ensemble_prompt(
+            &self.journal_manager,
+            &self.llm_manager,
+            &self.config,
+            &self.personalization_config.current(),
             cycle_date,
-            &context,
             prompt_number,
-            prompt_type,
-            &self.personalization_config,
+            None,
         ).await?;
         
-        self.journal_manager.save_prompt(&prompt).await?;
+        self.journal_manager.save_prompt_with_provenance(&prompt, &provenance).await?;
         
         tracing::info!("On-demand prompt {} generated and saved for {}", prompt_number, cycle_date);
         Ok(())
     }
 
+    /// Replace an existing prompt with a fresh one, optionally steered by a one-off instruction
+    /// that is recorded in the prompt's metadata
+    pub async fn regenerate_prompt(
+        &self,
+        cycle_date: &CycleDate,
+        prompt_number: u8,
+        steering: Option<&str>,
+    ) -> Result<JournalPrompt, Box<dyn std::error::Error>> {
+        self.llm_manager.prepare_for_processing().await?;
+
+        let (prompt, provenance) = Self::generate_ensemble_prompt(
+            &self.journal_manager,
+            &self.llm_manager,
+            &self.config,
+            &self.personalization_config.current(),
+            cycle_date,
+            prompt_number,
+            steering,
+        ).await?;
+
+        self.journal_manager.save_prompt_with_provenance(&prompt, &provenance).await?;
+        tracing::info!("Regenerated prompt {} for {}{}", prompt_number, cycle_date, if steering.is_some() { " with steering" } else { "" });
+        Ok(prompt)
+    }
+
+    /// Replace the summary of an existing entry, optionally steered by a one-off instruction.
+    /// The rolling status is left alone; only the summary is rewritten.
+    pub async fn regenerate_summary(
+        &self,
+        cycle_date: &CycleDate,
+        steering: Option<&str>,
+    ) -> Result<JournalSummary, Box<dyn std::error::Error>> {
+        let Some(entry) = self.journal_manager.load_entry(cycle_date).await? else {
+            return Err(format!("No entry found for {}", cycle_date).into());
+        };
+
+        self.llm_manager.prepare_for_processing().await?;
+        let llm_worker = self.llm_manager.get_worker();
+        let summary = llm_worker.generate_summary(&entry.content, cycle_date, &self.personalization_config.current(), steering).await?;
+
+        self.journal_manager.save_summary_with_steering(&summary, steering).await?;
+        tracing::info!("Regenerated summary for {}{}", cycle_date, if steering.is_some() { " with steering" } else { "" });
+        Ok(summary)
+    }
+
+    /// Summarize one entry the way processing does, writing its status file too. An
+    /// unreviewed transcription is left alone until it's approved.
+    pub(crate) async fn summarize_entry(&self, cycle_date: &CycleDate, report: &mut ProcessingReport) -> Result<(), String> {
+        if self.journal_manager.is_pending_review(cycle_date).await {
+            tracing::info!("Not summarizing {} until its transcription is reviewed", cycle_date);
+            return Ok(());
+        }
+        self.llm_manager.prepare_for_processing().await.map_err(|e| e.to_string())?;
+        let mut personalization_config = self.personalization_config.current().as_ref().clone();
+        Self::summarize(&self.journal_manager, &self.llm_manager.get_worker(), &mut personalization_config, *cycle_date, report)
+            .await
+            .map(|_| ())
+    }
+
+    /// Compile a year's review now, in the configured calendar, reusing any chapters already written
+    pub async fn compile_year_review(&self, year: i32) -> Result<crate::year_review::Review, Box<dyn std::error::Error>> {
+        self.llm_manager.prepare_for_processing().await?;
+        crate::year_review::compile(
+            &self.config.year_review,
+            &self.journal_manager,
+            &self.llm_manager.get_worker(),
+            &self.personalization_config.current(),
+            crate::cycle_date::calendar(),
+            year,
+        ).await
+    }
+
+    /// Write a fresh analysis for the insights page
+    pub async fn write_insights(&self) -> Result<crate::insights::Insight, Box<dyn std::error::Error>> {
+        self.llm_manager.prepare_for_processing().await?;
+        crate::insights::generate(&self.journal_manager, &self.llm_manager.get_worker(), &self.personalization_config.current().prompts).await
+    }
+
+    /// One clarifying question about an entry that hasn't been saved yet, asked in light of
+    /// the prompt it answers
+    pub async fn reflection_question(
+        &self,
+        cycle_date: &CycleDate,
+        prompt_number: Option<u8>,
+        content: &str,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let prompt = match prompt_number {
+            Some(n) => self.journal_manager.load_prompt(cycle_date, n).await?.map(|p| p.prompt),
+            None => None,
+        };
+        let personalization = self.personalization_config.current();
+        let request = personalization.with_language(personalization.prompts.get_reflection_question_prompt(prompt.as_deref(), content));
+
+        self.llm_manager.prepare_for_processing().await?;
+        let question = self.llm_manager.get_worker().generate_text(&request, 100).await?;
+        let question = question.trim().trim_matches('"').trim().to_string();
+        if question.is_empty() {
+            return Err("The model returned an empty question".into());
+        }
+        tracing::info!("Generated reflection question for {}", cycle_date);
+        Ok(question)
+    }
+
+    /// Entries semantically closest to `query`, most similar first. Entries written since the
+    /// last nightly run are embedded first so they can be found right away.
+    pub async fn semantic_search(&self, query: &str, limit: usize) -> Result<Vec<(CycleDate, f32)>, Box<dyn std::error::Error>> {
+        // Nightly processing rewrites the index too
+        let model = crate::scheduler::lock_model("Semantic search").await;
+        self.llm_manager.prepare_for_processing().await?;
+        let llm_worker = self.llm_manager.get_worker();
+
+        crate::embeddings::refresh_index(&self.journal_manager, &llm_worker, &self.config.llm.embedding_model).await?;
+        let query_vector = llm_worker.embed(vec![query.to_string()]).await?.pop().unwrap_or_default();
+        drop(model);
+        let index = crate::embeddings::EmbeddingIndex::load(self.journal_manager.base_path()).await;
+        Ok(index.nearest(&query_vector, limit, None))
+    }
+
     /// Queue prompt generation asynchronously without waiting for completion
     /// This is ideal for triggering prompt generation from web handlers without blocking the response
     pub fn queue_prompt_generation(&self, cycle_date: CycleDate, prompt_number: u8, _prompts_config: &PromptsConfig) {
         let journal_manager = Arc::clone(&self.journal_manager);
         let llm_manager = Arc::clone(&self.llm_manager);
-        let personalization_config = Arc::clone(&self.personalization_config);
+        let config = Arc::clone(&self.config);
+        let personalization_config = self.personalization_config.current();
+        let progress = Arc::clone(&self.progress);
+        let queue = Arc::clone(&self.queue);
         
         tracing::debug!("Queuing prompt {} generation for {} (async)", prompt_number, cycle_date);
+        progress.send(GenerationEvent::new(cycle_date, prompt_number, GenerationStage::Queued));
         
         // Spawn a background task to handle the generation
         tokio::spawn(async move {
             // Remove the max_prompts_per_day limitation for unlimited prompts
-            if let Ok(Some(_)) = journal_manager.load_prompt(&cycle_date, prompt_number).await {
+            if let Ok(Some(existing)) = journal_manager.load_prompt(&cycle_date, prompt_number).await.map_err(|e| e.to_string()) {
                 tracing::debug!("Prompt {} already exists for {}, skipping", prompt_number, cycle_date);
+                progress.send(GenerationEvent::saved(cycle_date, prompt_number, existing.prompt));
                 return;
             }
 
+            let _model = crate::scheduler::lock_model("on-demand prompt").await;
             tracing::debug!("Generating queued prompt {} for {}", prompt_number, cycle_date);
+            progress.send(GenerationEvent::new(cycle_date, prompt_number, GenerationStage::Generating));
             
-            match Self::generate_single_prompt(
-                journal_manager, 
+            let generated = Self::generate_single_prompt(
+                Arc::clone(&journal_manager),
                 llm_manager, 
+                &config,
                 &cycle_date, 
                 prompt_number,
                 &personalization_config,
-            ).await {
-                Ok(()) => {
-                    tracing::info!("Successfully generated queued prompt {} for {}", prompt_number, cycle_date);
-                }
+                &queue,
+            ).await.map_err(|e| e.to_string());
+            let event = match generated {
+                Ok(()) => match journal_manager.load_prompt(&cycle_date, prompt_number).await.map_err(|e| e.to_string()) {
+                    Ok(Some(prompt)) => {
+                        tracing::info!("Successfully generated queued prompt {} for {}", prompt_number, cycle_date);
+                        GenerationEvent::saved(cycle_date, prompt_number, prompt.prompt)
+                    }
+                    // Generation can decline, e.g. for a day marked as needing no prompt
+                    Ok(None) => GenerationEvent::failed(cycle_date, prompt_number, "No prompt was generated".to_string()),
+                    Err(e) => GenerationEvent::failed(cycle_date, prompt_number, e),
+                },
                 Err(e) => {
                     tracing::error!("Failed to generate queued prompt {} for {}: {}", prompt_number, cycle_date, e);
+                    GenerationEvent::failed(cycle_date, prompt_number, e)
                 }
-            }
+            };
+            progress.send(event);
         });
     }
 
@@ -356,19 +605,19 @@ impl PromptGenerator {
     async fn generate_single_prompt(
         journal_manager: Arc<JournalManager>,
         llm_manager: Arc<LlmManager>,
+        config: &Config,
         cycle_date: &CycleDate,
         prompt_number: u8,
         personalization_config: &PersonalizationConfig,
+        queue: &WorkQueue,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        // Create a minimal config for single prompt generation
+        // Copy the config, allowing generation up to the requested prompt number
         let temp_config = crate::config::Config {
             journal: crate::config::JournalConfig {
-                journal_directory: "journal".to_string(),
-                processing_time: "03:00".to_string(),
-                prompt_generation_time: "06:00".to_string(),
-                max_prompts_per_day: prompt_number, // Generate up to the requested prompt number
+                max_prompts_per_day: prompt_number,
+                ..config.journal.clone()
             },
-            ..Default::default()
+            ..config.clone()
         };
         
         // Use unified generation with checks (since this is typically user-requested)
@@ -380,63 +629,46 @@ impl PromptGenerator {
             cycle_date,
             false, // Don't skip checks for user-requested prompts
             Some(prompt_number), // Generate up to this specific prompt number
+            queue,
+            &mut ProcessingReport::new(*cycle_date, "on-demand"),
         ).await.map_err(|e| e.into())
     }
 
-    /// Check if we're past the prompt generation time for today and generate prompts if needed
-    async fn check_and_generate_startup_prompts(
-        journal_manager: Arc<JournalManager>,
-        llm_manager: Arc<LlmManager>,
-        config: Arc<Config>,
-        personalization_config: Arc<PersonalizationConfig>,
+    /// Bring the semantic search index up to date. Failures (typically the embedding model
+    /// not being pulled) are only logged; search falls back to keywords without it.
+    async fn refresh_embeddings(journal_manager: &JournalManager, llm_worker: &LlmWorker, config: &Config) {
+        let result = crate::embeddings::refresh_index(journal_manager, llm_worker, &config.llm.embedding_model)
+            .await
+            .map_err(|e| e.to_string());
+        if let Err(e) = result {
+            tracing::warn!("Failed to update entry embeddings: {}", e);
+        }
+    }
+
+    /// Write one-line summaries for entries that turned a year old, at most
+    /// `ONE_LINE_BATCH` per run so a large backlog is worked through over several nights
+    async fn compact_old_summaries(
+        journal_manager: &JournalManager,
+        llm_worker: &crate::llm_worker::LlmWorker,
+        personalization_config: &PersonalizationConfig,
+        report: &mut ProcessingReport,
     ) -> Result<(), String> {
         let today = CycleDate::today();
-        let now = Local::now();
-        
-        // First, always check for missing summaries and status files on startup
-        tracing::info!("Startup check: Looking for entries that need summaries or status files...");
-        
-        // Load the LLM model for summary generation
-        llm_manager.prepare_for_processing().await.map_err(|e| e.to_string())?;
-        let llm_worker = llm_manager.get_worker();
-        
-        // Generate any missing summaries and status files
-        if let Err(e) = Self::generate_missing_summaries(&journal_manager, &llm_worker, &personalization_config).await {
-            tracing::warn!("Failed to generate some summaries/status files: {}", e);
-            // Continue anyway - this shouldn't block prompt generation
+        let dates = journal_manager.find_entries_needing_one_lines(&today).await.map_err(|e| e.to_string())?;
+        if dates.is_empty() {
+            return Ok(());
         }
-        
-        // Parse the configured prompt generation time
-        let target_time = NaiveTime::parse_from_str(&config.journal.prompt_generation_time, "%H:%M")
-            .map_err(|e| format!("Invalid time format: {}", e))?;
-        
-        // Check if current time is past the prompt generation time for today
-        let current_time = now.time();
-        if current_time >= target_time {
-            tracing::info!("Startup check: Current time ({}) is past prompt generation time ({})", 
-                current_time.format("%H:%M"), target_time.format("%H:%M"));
-            
-            // Check if we already have prompts for today
-            let existing_prompts = Self::count_existing_prompts(&journal_manager, &today).await;
-            if existing_prompts == 0 {
-                tracing::info!("No prompts found for today, generating them now...");
-                Self::generate_prompts_unified(
-                    journal_manager,
-                    llm_manager,
-                    config,
-                    personalization_config,
-                    &today,
-                    false, // Don't skip checks for startup generation
-                    None,  // Use default max_prompts_per_day
-                ).await?;
-            } else {
-                tracing::info!("Found {} existing prompts for today, no need to generate", existing_prompts);
-            }
-        } else {
-            tracing::info!("Startup check: Current time ({}) is before prompt generation time ({}), will wait", 
-                current_time.format("%H:%M"), target_time.format("%H:%M"));
+        tracing::info!("Found {} entries needing one-line summaries", dates.len());
+
+        for cycle_date in dates.into_iter().take(ONE_LINE_BATCH) {
+            let summary = match journal_manager.load_summary(&cycle_date).await.map_err(|e| e.to_string())? {
+                Some(summary) => summary.summary,
+                None => continue,
+            };
+            let one_line = llm_worker.generate_one_line_summary(&summary, &cycle_date, personalization_config).await.map_err(|e| e.to_string())?;
+            journal_manager.save_one_line(&cycle_date, &one_line).await.map_err(|e| e.to_string())?;
+            report.compacted.push(cycle_date);
         }
-        
         Ok(())
     }
 
@@ -445,6 +677,8 @@ impl PromptGenerator {
         journal_manager: &Arc<JournalManager>,
         llm_worker: &Arc<crate::llm_worker::LlmWorker>,
         personalization_config: &Arc<PersonalizationConfig>,
+        queue: &WorkQueue,
+        report: &mut ProcessingReport,
     ) -> Result<(), String> {
         // Find entries that need summaries or status files
         let entries_needing_summaries = journal_manager.find_entries_needing_summaries().await.map_err(|e| e.to_string())?;
@@ -470,73 +704,88 @@ impl PromptGenerator {
         let mut personalization_config_mut = personalization_config.as_ref().clone();
         
         for cycle_date in entries_to_process {
-            // Load the entry content
-            let entry_content = match journal_manager.load_entry(&cycle_date).await {
-                Ok(Some(entry)) => {
-                    entry.content
-                }
-                Ok(None) => {
-                    tracing::warn!("No entry found for {}", cycle_date);
-                    continue;
-                }
+            // A failed entry is queued to be retried rather than holding up the rest
+            match Self::summarize(journal_manager, llm_worker, &mut personalization_config_mut, cycle_date, report).await {
+                Ok(true) => queue.succeeded(Task::Summary { cycle_date }).await,
+                Ok(false) => {}
                 Err(e) => {
-                    tracing::error!("Failed to load entry for {}: {}", cycle_date, e);
-                    continue;
-                }
-            };
-            
-            // Check what files are missing
-            let paths = journal_manager.get_file_paths(&cycle_date);
-            let needs_summary = !paths.summary.exists();
-            let needs_status = !paths.status.exists();
-            
-            if needs_summary || needs_status {
-                tracing::info!("Processing {} (summary: {}, status: {})", 
-                    cycle_date, 
-                    if needs_summary { "generating" } else { "exists" },
-                    if needs_status { "generating" } else { "exists" }
-                );
-                
-                let (summary, status_update) = llm_worker.generate_summary_with_status_update(&entry_content, &cycle_date, &mut personalization_config_mut).await.map_err(|e| e.to_string())?;
-                
-                // Save summary if needed
-                if needs_summary {
-                    journal_manager.save_summary(&summary).await.map_err(|e| e.to_string())?;
-                }
-                
-                // Save status if needed and generated
-                if needs_status {
-                    if let Some(status) = status_update {
-                        journal_manager.save_status(&cycle_date, &status).await.map_err(|e| e.to_string())?;
-                        tracing::info!("Summary and status saved for {}", cycle_date);
-                    } else {
-                        tracing::info!("Summary saved for {} (no status update needed)", cycle_date);
-                    }
-                } else if let Some(_status) = status_update {
-                    // Status file exists but we still updated global status
-                    tracing::info!("Summary saved for {} (status exists, global updated)", cycle_date);
-                } else {
-                    tracing::info!("Summary saved for {} (no status changes)", cycle_date);
+                    tracing::error!("{}", e);
+                    report.error(e.clone());
+                    queue.failed(Task::Summary { cycle_date }, &e).await;
                 }
             }
         }
         
         Ok(())
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_calculate_sleep_duration() {
-        // Test with a time format
-        let result = PromptGenerator::calculate_sleep_until_prompt_time("06:00");
-        assert!(result.is_ok());
+    /// Write whichever of a day's summary and status file are missing, updating
+    /// `personalization_config`'s status along the way. True when a summary was written.
+    async fn summarize(
+        journal_manager: &Arc<JournalManager>,
+        llm_worker: &Arc<crate::llm_worker::LlmWorker>,
+        personalization_config: &mut PersonalizationConfig,
+        cycle_date: CycleDate,
+        report: &mut ProcessingReport,
+    ) -> Result<bool, String> {
+        let entry_content = match journal_manager.load_entry(&cycle_date).await {
+            Ok(Some(entry)) => entry.content,
+            Ok(None) => {
+                tracing::warn!("No entry found for {}", cycle_date);
+                return Ok(false);
+            }
+            Err(e) => return Err(format!("Failed to load entry for {}: {}", cycle_date, e)),
+        };
         
-        // Test with invalid format
-        let result = PromptGenerator::calculate_sleep_until_prompt_time("invalid");
-        assert!(result.is_err());
+        // Check what files are missing
+        let paths = journal_manager.get_file_paths(&cycle_date);
+        let needs_summary = !paths.summary.exists();
+        let needs_status = !paths.status.exists();
+        if !needs_summary && !needs_status {
+            return Ok(false);
+        }
+        tracing::info!("Processing {} (summary: {}, status: {})", 
+            cycle_date, 
+            if needs_summary { "generating" } else { "exists" },
+            if needs_status { "generating" } else { "exists" }
+        );
+        
+        let previous_status = Some(personalization_config.current_status().render()).filter(|s| !s.is_empty());
+        let (summary, status_update) = llm_worker.generate_summary_with_status_update(&entry_content, &cycle_date, personalization_config)
+            .await
+            .map_err(|e| format!("Failed to summarize {}: {}", cycle_date, e))?;
+        
+        // Save summary if needed
+        if needs_summary {
+            journal_manager.save_summary(&summary).await.map_err(|e| e.to_string())?;
+            report.summarized.push(cycle_date);
+        }
+        if let Some(status) = &status_update {
+            report.status_changes.push(StatusChange {
+                cycle_date,
+                previous: previous_status,
+                proposed: status.clone(),
+            });
+        }
+        
+        // Save status if needed and generated
+        if needs_status {
+            if status_update.is_some() {
+                let status = JournalStatus {
+                    cycle_date,
+                    status: personalization_config.status.clone(),
+                    generated_at: chrono::Local::now(),
+                };
+                journal_manager.save_status(&status).await.map_err(|e| e.to_string())?;
+                tracing::info!("Summary and status saved for {}", cycle_date);
+            } else {
+                tracing::info!("Summary saved for {} (no status update needed)", cycle_date);
+            }
+        } else if status_update.is_some() {
+            // Status file exists but we still updated global status
+            tracing::info!("Summary saved for {} (status exists, global updated)", cycle_date);
+        } else {
+            tracing::info!("Summary saved for {} (no status changes)", cycle_date);
+        }
+        Ok(needs_summary)
     }
 }
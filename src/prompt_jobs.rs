@@ -0,0 +1,225 @@
+//! Model work in the background. Generating a prompt can keep the model busy for a minute,
+//! so the generate endpoint starts a job and answers 202 right away; clients poll
+//! `/api/jobs/:id` until it has the prompt. The insights page's analysis runs the same way.
+//! Jobs live in memory only: a restart loses the ones in flight, and the client simply
+//! asks again.
+
+use crate::jobs::JobState;
+use crate::llm_worker::LlmWorker;
+use chrono::{DateTime, Duration, Local};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+/// How long a finished job's result stays available
+const KEEP_FINISHED_MINUTES: i64 = 60;
+/// Seconds clients are asked to wait between polls
+pub const POLL_SECONDS: u64 = 2;
+
+/// What a job asks of the model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelTask {
+    /// A prompt for the editor, returned with the job
+    Prompt,
+    /// An analysis for the insights page, saved with the notebook's insights
+    Insights,
+    /// The nightly processing, run from the admin page
+    Processing,
+    /// Summaries for entries missing them, run from the admin page
+    Summaries,
+    /// Today's missing prompts, run from the admin page
+    Prompts,
+}
+
+impl ModelTask {
+    fn key(self) -> &'static str {
+        match self {
+            ModelTask::Prompt => "prompt",
+            ModelTask::Insights => "insights",
+            ModelTask::Processing => "processing",
+            ModelTask::Summaries => "summaries",
+            ModelTask::Prompts => "prompts",
+        }
+    }
+}
+
+/// One piece of model work in progress, or its result
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptJob {
+    pub id: String,
+    pub task: ModelTask,
+    /// The notebook the job works on; only requests for that notebook see it
+    pub notebook: String,
+    pub state: JobState,
+    /// The type of entry a prompt is for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_type: Option<String>,
+    pub created_at: DateTime<Local>,
+    pub updated_at: DateTime<Local>,
+    /// The generated prompt, once completed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl PromptJob {
+    pub fn is_finished(&self) -> bool {
+        self.state != JobState::Running
+    }
+}
+
+/// Starts model jobs and keeps their results for polling
+#[derive(Default)]
+pub struct PromptJobs {
+    jobs: Mutex<HashMap<String, PromptJob>>,
+}
+
+impl PromptJobs {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Start generating a prompt for `notebook` from `request` with `worker`
+    pub fn start(self: &Arc<Self>, worker: Arc<LlmWorker>, notebook: &str, entry_type: &str, request: String) -> PromptJob {
+        self.spawn(ModelTask::Prompt, notebook, Some(entry_type), async move {
+            generate(&worker, &request).await.map(Some)
+        })
+    }
+
+    /// Run `work` as a job; the text it finishes with, if any, is reported as the job's `prompt`
+    pub fn spawn<F>(self: &Arc<Self>, task: ModelTask, notebook: &str, entry_type: Option<&str>, work: F) -> PromptJob
+    where
+        F: Future<Output = Result<Option<String>, String>> + Send + 'static,
+    {
+        let job = self.create(task, notebook, entry_type);
+        let manager = self.clone();
+        let id = job.id.clone();
+        tokio::spawn(async move {
+            let result = work.await;
+            manager.finish(&id, result);
+        });
+        job
+    }
+
+    /// Record a new running job, forgetting finished ones nobody collected in time
+    fn create(&self, task: ModelTask, notebook: &str, entry_type: Option<&str>) -> PromptJob {
+        let now = Local::now();
+        let job = PromptJob {
+            id: format!("{}-{}-{:08x}", task.key(), now.format("%Y%m%d-%H%M%S"), rand::random::<u32>()),
+            task,
+            notebook: notebook.to_string(),
+            state: JobState::Running,
+            entry_type: entry_type.map(str::to_string),
+            created_at: now,
+            updated_at: now,
+            prompt: None,
+            error: None,
+        };
+        let mut jobs = self.jobs.lock().unwrap();
+        let cutoff = now - Duration::minutes(KEEP_FINISHED_MINUTES);
+        jobs.retain(|_, job| !job.is_finished() || job.updated_at > cutoff);
+        jobs.insert(job.id.clone(), job.clone());
+        job
+    }
+
+    /// The job with this id, if it works on `notebook`
+    pub fn get(&self, notebook: &str, id: &str) -> Option<PromptJob> {
+        self.jobs.lock().unwrap().get(id).filter(|job| job.notebook == notebook).cloned()
+    }
+
+    /// A job of this kind still running for `notebook`, if there is one
+    pub fn running(&self, task: ModelTask, notebook: &str) -> Option<PromptJob> {
+        self.jobs
+            .lock()
+            .unwrap()
+            .values()
+            .find(|job| job.task == task && job.notebook == notebook && !job.is_finished())
+            .cloned()
+    }
+
+    /// `notebook`'s jobs of these kinds, newest first
+    pub fn list(&self, tasks: &[ModelTask], notebook: &str) -> Vec<PromptJob> {
+        let mut jobs: Vec<PromptJob> = self
+            .jobs
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|job| tasks.contains(&job.task) && job.notebook == notebook)
+            .cloned()
+            .collect();
+        jobs.sort_by_key(|job| std::cmp::Reverse(job.created_at));
+        jobs
+    }
+
+    fn finish(&self, id: &str, result: Result<Option<String>, String>) {
+        let mut jobs = self.jobs.lock().unwrap();
+        let Some(job) = jobs.get_mut(id) else {
+            return;
+        };
+        match result {
+            Ok(prompt) => {
+                tracing::info!("Job {} finished", id);
+                job.state = JobState::Completed;
+                job.prompt = prompt;
+            }
+            Err(e) => {
+                tracing::error!("Job {} failed: {}", id, e);
+                job.state = JobState::Failed;
+                job.error = Some(e);
+            }
+        }
+        job.updated_at = Local::now();
+    }
+}
+
+async fn generate(worker: &LlmWorker, request: &str) -> Result<String, String> {
+    let _model = crate::scheduler::lock_model("Prompt job").await;
+    worker.load_model().await.map_err(|e| format!("Model loading failed: {}", e))?;
+    worker.generate_text(request, 200).await.map_err(|e| format!("Prompt generation failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_job_results() {
+        let jobs = PromptJobs::new();
+        let job = jobs.create(ModelTask::Prompt, "journal", Some("Daily Entry"));
+        assert_eq!(jobs.get("journal", &job.id).unwrap().state, JobState::Running);
+        assert!(jobs.get("journal", "prompt-unknown").is_none());
+
+        jobs.finish(&job.id, Ok(Some("What surprised you today?".to_string())));
+        let finished = jobs.get("journal", &job.id).unwrap();
+        assert!(finished.is_finished());
+        assert_eq!(finished.prompt.as_deref(), Some("What surprised you today?"));
+
+        let other = jobs.create(ModelTask::Insights, "journal", None);
+        assert!(other.id.starts_with("insights-"));
+        assert_eq!(jobs.running(ModelTask::Insights, "journal").unwrap().id, other.id);
+        jobs.finish(&other.id, Err("Model loading failed".to_string()));
+        let failed = jobs.get("journal", &other.id).unwrap();
+        assert_eq!(failed.state, JobState::Failed);
+        assert_eq!(failed.error.as_deref(), Some("Model loading failed"));
+        assert!(jobs.running(ModelTask::Insights, "journal").is_none());
+        assert_eq!(jobs.list(&[ModelTask::Insights], "journal").len(), 1);
+        assert!(jobs.list(&[ModelTask::Processing], "journal").is_empty());
+    }
+
+    #[test]
+    fn test_jobs_stay_in_their_notebook() {
+        let jobs = PromptJobs::new();
+        let work = jobs.create(ModelTask::Processing, "work", None);
+        assert!(jobs.get("journal", &work.id).is_none());
+        assert!(jobs.running(ModelTask::Processing, "journal").is_none());
+        assert!(jobs.list(&[ModelTask::Processing], "journal").is_empty());
+
+        let journal = jobs.create(ModelTask::Processing, "journal", None);
+        assert_eq!(jobs.running(ModelTask::Processing, "journal").unwrap().id, journal.id);
+        assert_eq!(jobs.running(ModelTask::Processing, "work").unwrap().id, work.id);
+        assert_eq!(jobs.list(&[ModelTask::Processing], "work").len(), 1);
+    }
+}
@@ -9,22 +9,124 @@ pub struct PromptVariations {
     pub additional: String,
 }
 
+/// How the second and later prompts of a day are made different from the first
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VariationStrategy {
+    /// Fixed `prompt_variations` suffixes
+    #[default]
+    Suffix,
+    /// A different life theme per prompt
+    Theme,
+    /// A different depth per prompt, from quick check-in to deep dive
+    Depth,
+    /// A different time horizon per prompt: past, present, future
+    TimeHorizon,
+}
+
+/// Instructions the non-suffix strategies rotate through, one per extra prompt
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct VariationOptions {
+    pub themes: Vec<String>,
+    pub depths: Vec<String>,
+    pub time_horizons: Vec<String>,
+}
+
+impl Default for VariationOptions {
+    fn default() -> Self {
+        Self {
+            themes: vec![
+                "Focus on relationships and the people in their life.".to_string(),
+                "Focus on work, purpose, and ambitions.".to_string(),
+                "Focus on health, body, and energy.".to_string(),
+                "Focus on gratitude and small joys.".to_string(),
+                "Focus on creativity and play.".to_string(),
+                "Focus on a fear or challenge they are facing.".to_string(),
+            ],
+            depths: vec![
+                "Make it a quick, light question that can be answered in a sentence or two.".to_string(),
+                "Make it a deep, probing question that invites a long and honest answer.".to_string(),
+                "Make it concrete and practical, about a specific action or decision.".to_string(),
+            ],
+            time_horizons: vec![
+                "Look to the past: a memory, or how something has changed over time.".to_string(),
+                "Look to the future: hopes, plans, or who they want to become.".to_string(),
+                "Stay in the present: how things are right now, today.".to_string(),
+            ],
+        }
+    }
+}
+
+/// Reply the status update template asks for when an entry changes nothing
+pub const NO_STATUS_UPDATE: &str = "NO_UPDATE_NEEDED";
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct PromptsConfig {
+    /// Placeholders: {entry_content}
     pub summary_generation: String,
+    /// Placeholders: {user_profile}, {current_status}, {entry_content}.
+    /// Should tell the model to reply with `NO_UPDATE_NEEDED` when nothing changed.
     pub status_update: String,
+    /// This and the three reflection templates take the placeholders {context}, and for the
+    /// day the prompt is for, {date_name}, {month_name}, {day_name}, {day_of_year},
+    /// {days_in_year}, {week_of_year}, and {year_percent} (how far through the year, rounded)
     pub daily_prompt: String,
     pub weekly_reflection: String,
     pub monthly_reflection: String,
     pub yearly_reflection: String,
     pub prompt_variations: PromptVariations,
+    /// Which variation strategy multi-prompt days use
+    #[serde(default)]
+    pub variation_strategy: VariationStrategy,
+    #[serde(default)]
+    pub variation_options: VariationOptions,
+    /// Picks between two ensemble candidates. Placeholders: {context}, {prompt_a}, {prompt_b}.
+    /// Should ask for a reply of just "A" or "B".
+    #[serde(default = "default_ensemble_judge")]
+    pub ensemble_judge: String,
+    /// Asks one clarifying question about an entry before it's saved.
+    /// Placeholders: {prompt}, {entry_content}.
+    #[serde(default = "default_reflection_question")]
+    pub reflection_question: String,
+    /// Compresses an old entry's summary to one line for long-horizon context.
+    /// Placeholders: {date}, {summary}.
+    #[serde(default = "default_one_line_summary")]
+    pub one_line_summary: String,
+    /// Writes one month's chapter of the year-in-review book.
+    /// Placeholders: {month}, {dates}, {stats}, {reflection}, {summaries}.
+    #[serde(default = "default_year_review_chapter")]
+    pub year_review_chapter: String,
+    /// Writes the insights page's analysis of recent weeks.
+    /// Placeholders: {period}, {summaries}, {moods}, {tags}.
+    #[serde(default = "default_insights")]
+    pub insights: String,
+}
+
+fn default_one_line_summary() -> String {
+    "Here is the summary of a journal entry from {date}:\n\n{summary}\n\nCompress it into a single line of at most 15 words that captures what mattered most that day. Reply with just the line.".to_string()
+}
+
+fn default_year_review_chapter() -> String {
+    "Write one chapter of a year-in-review book about someone's journal, covering {month} ({dates}). Write it in the second person, as a warm, honest narrative of three to five paragraphs: what the month held, how they felt, and what changed. Draw only on the material below and don't invent events.\n\nTHE MONTH IN NUMBERS:\n{stats}\n\nTHEIR OWN REFLECTION ON THE MONTH:\n{reflection}\n\nSUMMARIES OF THEIR FULLEST ENTRIES:\n{summaries}\n\nReply with just the chapter text, without a heading.".to_string()
+}
+
+fn default_insights() -> String {
+    "Here is what someone wrote about in their journal from {period}.\n\nSUMMARIES OF THEIR ENTRIES:\n{summaries}\n\nHOW THEIR MOOD WAS DESCRIBED:\n{moods}\n\nTAGS THEY USED (with how often):\n{tags}\n\nWrite a short analysis for them in the second person, in two or three brief paragraphs: the themes that kept coming up, how their mood moved, and one pattern worth noticing. Be warm and specific, draw only on the material above, and don't give advice. Reply with just the analysis.".to_string()
+}
+
+fn default_reflection_question() -> String {
+    "Someone just finished this journal entry{prompt}:\n\n{entry_content}\n\nAsk them one short, gentle clarifying question that would help them reflect more deeply on what they wrote: something they hinted at but didn't explain, or a feeling they named without exploring. Reply with just the question.".to_string()
+}
+
+fn default_ensemble_judge() -> String {
+    "Two journal prompts were written for someone based on this context:\n\n{context}\n\nPROMPT A:\n{prompt_a}\n\nPROMPT B:\n{prompt_b}\n\nWhich prompt is more likely to inspire a thoughtful, honest journal entry today? Consider how specific, relevant, and inviting each one is. Reply with just the letter A or B.".to_string()
 }
 
 impl Default for PromptsConfig {
     fn default() -> Self {
         Self {
             summary_generation: "Please summarize the following journal entry in 2-3 sentences, focusing on key emotions, events, and insights:\n\n{entry_content}\n\nSummary:".to_string(),
-            status_update: "Based on this journal entry and the current status, update the user's ongoing life circumstances. Focus on significant changes, ongoing situations, emotional states, relationships, work/health updates, and challenges/projects that should be remembered for future context.\n\nUSER PROFILE (static context - do NOT duplicate this in status):\n{user_profile}\n\nCURRENT STATUS:\n{current_status}\n\nTODAY'S JOURNAL ENTRY:\n{entry_content}\n\nPlease provide an updated status summary that:\n1. Preserves important ongoing situations from current status\n2. Incorporates significant new developments from today's entry\n3. Removes outdated information\n4. Focuses on context that will be valuable for future journal prompts\n5. Keeps it concise but informative (3-5 sentences)\n6. IMPORTANT: Do NOT duplicate information that's already in the user profile above\n\nIf today's entry doesn't contain significant status changes, respond with \"NO_UPDATE_NEEDED\".\n\nUpdated Status:".to_string(),
+            status_update: "Based on this journal entry and the current status, update the user's ongoing life circumstances. The status is kept in sections: Work, Health, Relationships, Projects, Mood baseline, and Other notes. Focus on significant changes, ongoing situations, emotional states, and challenges that should be remembered for future context.\n\nUSER PROFILE (static context - do NOT duplicate this in status):\n{user_profile}\n\nCURRENT STATUS:\n{current_status}\n\nTODAY'S JOURNAL ENTRY:\n{entry_content}\n\nFor each section that today's entry meaningfully changes, write one line in the form \"Section: new text\" containing the complete new text for that section. Sections you leave out are kept as they are. When updating a section:\n1. Preserve important ongoing situations from the current text\n2. Incorporate significant new developments from today's entry\n3. Remove outdated information (write \"Section: none\" to clear it)\n4. Keep it concise (1-2 sentences)\n5. IMPORTANT: Do NOT duplicate information that's already in the user profile above\n\nIf today's entry doesn't contain significant status changes, respond with \"NO_UPDATE_NEEDED\".\n\nChanged sections:".to_string(),
             daily_prompt: "Based on the following journal summaries from the past week, create an insightful and thought-provoking journal prompt for today. The prompt should help the person reflect on patterns, growth, or connections to recent experiences:\n\n{context}\n\nToday's journal prompt:".to_string(),
             weekly_reflection: "Based on the following journal entries from the past week, create a reflective prompt that encourages deeper weekly reflection on themes, patterns, growth, and lessons learned:\n\n{context}\n\nWeekly reflection prompt:".to_string(),
             monthly_reflection: "Based on the following weekly reflections from the past month, create a comprehensive monthly reflection prompt that explores broader patterns, achievements, challenges, and personal growth:\n\n{context}\n\nMonthly reflection prompt:".to_string(),
@@ -34,16 +136,24 @@ impl Default for PromptsConfig {
                 third: "\n\nCreate a third unique approach to this reflection:".to_string(),
                 additional: "\n\nCreate another unique and creative approach to this reflection (variation #{number}):".to_string(),
             },
+            variation_strategy: VariationStrategy::default(),
+            variation_options: VariationOptions::default(),
+            ensemble_judge: default_ensemble_judge(),
+            reflection_question: default_reflection_question(),
+            one_line_summary: default_one_line_summary(),
+            year_review_chapter: default_year_review_chapter(),
+            insights: default_insights(),
         }
     }
 }
 
 impl PromptsConfig {
-    /// Load prompts configuration from file, create default if missing
+    /// Load prompts configuration from file, create default if missing or empty
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
         let path = path.as_ref();
         
-        if !path.exists() {
+        let is_empty = path.exists() && fs::metadata(path)?.len() == 0;
+        if !path.exists() || is_empty {
             tracing::info!("Creating default prompts.json file");
             let default_config = Self::default();
             let json = serde_json::to_string_pretty(&default_config)?;
@@ -59,11 +169,42 @@ impl PromptsConfig {
         Ok(config)
     }
     
+    /// Templates missing a placeholder they can't work without, e.g. a daily prompt with
+    /// no {context}, each as "template: {placeholder}"
+    pub fn missing_placeholders(&self) -> Vec<String> {
+        let required: [(&str, &str, &[&str]); 11] = [
+            ("summary_generation", &self.summary_generation, &["{entry_content}"]),
+            ("status_update", &self.status_update, &["{current_status}", "{entry_content}"]),
+            ("daily_prompt", &self.daily_prompt, &["{context}"]),
+            ("weekly_reflection", &self.weekly_reflection, &["{context}"]),
+            ("monthly_reflection", &self.monthly_reflection, &["{context}"]),
+            ("yearly_reflection", &self.yearly_reflection, &["{context}"]),
+            ("ensemble_judge", &self.ensemble_judge, &["{prompt_a}", "{prompt_b}"]),
+            ("reflection_question", &self.reflection_question, &["{entry_content}"]),
+            ("one_line_summary", &self.one_line_summary, &["{summary}"]),
+            ("year_review_chapter", &self.year_review_chapter, &["{summaries}"]),
+            ("insights", &self.insights, &["{summaries}"]),
+        ];
+        required
+            .iter()
+            .flat_map(|(name, template, placeholders)| {
+                placeholders.iter().filter(|p| !template.contains(**p)).map(move |p| format!("{}: {}", name, p))
+            })
+            .collect()
+    }
+
     /// Get summary generation prompt with entry content substituted
     pub fn get_summary_prompt(&self, entry_content: &str) -> String {
         self.summary_generation.replace("{entry_content}", entry_content)
     }
     
+    /// Whether a status update reply means "nothing changed". Models tend to decorate
+    /// the marker with quotes or punctuation, so those are ignored.
+    pub fn is_no_status_update(response: &str) -> bool {
+        let response = response.trim().trim_matches(|c: char| c == '"' || c == '\'' || c == '.' || c == '`');
+        response.is_empty() || response.eq_ignore_ascii_case(NO_STATUS_UPDATE)
+    }
+    
     /// Get status update prompt with user profile, current status and entry content substituted
     pub fn get_status_update_prompt(&self, user_profile: &str, current_status: &str, entry_content: &str) -> String {
         self.status_update
@@ -72,8 +213,8 @@ impl PromptsConfig {
             .replace("{entry_content}", entry_content)
     }
     
-    /// Get prompt template for the given prompt type with context substituted
-    pub fn get_prompt_template(&self, prompt_type: &crate::journal::PromptType, context: &str) -> String {
+    /// Get prompt template for the given prompt type with context and the day's names substituted
+    pub fn get_prompt_template(&self, prompt_type: &crate::journal::PromptType, cycle_date: &crate::cycle_date::CycleDate, context: &str) -> String {
         let template = match prompt_type {
             crate::journal::PromptType::Daily => &self.daily_prompt,
             crate::journal::PromptType::WeeklyReflection => &self.weekly_reflection,
@@ -81,11 +222,93 @@ impl PromptsConfig {
             crate::journal::PromptType::YearlyReflection => &self.yearly_reflection,
         };
         
-        template.replace("{context}", context)
+        template
+            .replace("{date_name}", &cycle_date.date_name())
+            .replace("{month_name}", &cycle_date.month_name())
+            .replace("{day_name}", &cycle_date.day_name())
+            .replace("{day_of_year}", &cycle_date.day_of_year().to_string())
+            .replace("{days_in_year}", &cycle_date.days_in_year().to_string())
+            .replace("{week_of_year}", &cycle_date.week_of_year().to_string())
+            .replace("{year_percent}", &format!("{:.0}", cycle_date.percent_of_year_elapsed()))
+            .replace("{context}", context)
     }
     
-    /// Get variation suffix for additional prompt numbers
+    /// Get the judge prompt comparing two ensemble candidates
+    pub fn get_ensemble_judge_prompt(&self, context: &str, prompt_a: &str, prompt_b: &str) -> String {
+        self.ensemble_judge
+            .replace("{context}", context)
+            .replace("{prompt_a}", prompt_a)
+            .replace("{prompt_b}", prompt_b)
+    }
+
+    /// Get the reflection question prompt for an entry written in answer to `prompt`, if any
+    pub fn get_reflection_question_prompt(&self, prompt: Option<&str>, entry_content: &str) -> String {
+        let prompt = prompt.map(|p| format!(" in answer to the prompt \"{}\"", p)).unwrap_or_default();
+        self.reflection_question
+            .replace("{prompt}", &prompt)
+            .replace("{entry_content}", entry_content)
+    }
+
+    /// Get the prompt compressing an entry's summary to one line
+    pub fn get_one_line_summary_prompt(&self, date: &str, summary: &str) -> String {
+        self.one_line_summary
+            .replace("{date}", date)
+            .replace("{summary}", summary)
+    }
+
+    /// Get the prompt writing one month's chapter of the year in review
+    pub fn get_year_review_chapter_prompt(&self, month: &str, dates: &str, stats: &str, reflection: &str, summaries: &str) -> String {
+        self.year_review_chapter
+            .replace("{month}", month)
+            .replace("{dates}", dates)
+            .replace("{stats}", stats)
+            .replace("{reflection}", reflection)
+            .replace("{summaries}", summaries)
+    }
+
+    /// Get the prompt writing the insights page's analysis
+    pub fn get_insights_prompt(&self, period: &str, summaries: &str, moods: &str, tags: &str) -> String {
+        self.insights
+            .replace("{period}", period)
+            .replace("{summaries}", summaries)
+            .replace("{moods}", moods)
+            .replace("{tags}", tags)
+    }
+
+    /// Which candidate a judge reply picked: Some(true) for A, Some(false) for B
+    pub fn parse_judge_verdict(response: &str) -> Option<bool> {
+        let verdict = response
+            .split(|c: char| !c.is_alphanumeric())
+            .find(|word| !word.is_empty() && !word.eq_ignore_ascii_case("prompt"))?;
+        match verdict.to_ascii_uppercase().as_str() {
+            "A" => Some(true),
+            "B" => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Get variation suffix for additional prompt numbers, according to the configured strategy
     pub fn get_variation_suffix(&self, prompt_number: u8) -> String {
+        if prompt_number <= 1 {
+            return String::new();
+        }
+
+        let options = match self.variation_strategy {
+            VariationStrategy::Suffix => return self.fixed_variation_suffix(prompt_number),
+            VariationStrategy::Theme => &self.variation_options.themes,
+            VariationStrategy::Depth => &self.variation_options.depths,
+            VariationStrategy::TimeHorizon => &self.variation_options.time_horizons,
+        };
+        if options.is_empty() {
+            return self.fixed_variation_suffix(prompt_number);
+        }
+
+        // Rotate through the options starting with prompt 2
+        let instruction = &options[(prompt_number as usize - 2) % options.len()];
+        format!("\n\nCreate a different prompt from the usual one. {}", instruction)
+    }
+
+    fn fixed_variation_suffix(&self, prompt_number: u8) -> String {
         match prompt_number {
             1 => String::new(), // No suffix for first prompt
             2 => self.prompt_variations.second.clone(),
@@ -95,6 +318,16 @@ impl PromptsConfig {
         }
     }
     
+    /// Name of the template variation used for a prompt number, for analytics
+    pub fn variant_name(prompt_number: u8) -> &'static str {
+        match prompt_number {
+            0 | 1 => "primary",
+            2 => "second",
+            3 => "third",
+            _ => "additional",
+        }
+    }
+    
     /// Create example prompts.json file for user reference
     pub fn create_example<P: AsRef<Path>>(path: P) -> Result<(), Box<dyn std::error::Error>> {
         let example_path = path.as_ref().with_extension("example.json");
@@ -126,9 +359,81 @@ mod tests {
         let context = "Sample context";
         let prompt_type = crate::journal::PromptType::Daily;
         
-        let result = config.get_prompt_template(&prompt_type, context);
+        let cycle_date = crate::cycle_date::CycleDate::new(1, 11, 2, 3).unwrap();
+        let result = config.get_prompt_template(&prompt_type, &cycle_date, context);
         assert!(result.contains("Sample context"));
         assert!(!result.contains("{context}"));
+
+        let named = PromptsConfig { daily_prompt: "It's {day_name} in {month_name} ({date_name}).".to_string(), ..config };
+        assert_eq!(named.get_prompt_template(&prompt_type, &cycle_date, context), "It's Wednesday in Month B (Month B, Week 2, Wednesday).");
+        let progress = PromptsConfig { daily_prompt: "Day {day_of_year} of {days_in_year}, week {week_of_year}: {year_percent}% through.".to_string(), ..named };
+        assert_eq!(progress.get_prompt_template(&prompt_type, &cycle_date, context), "Day 326 of 364, week 47: 90% through.");
+    }
+
+    #[test]
+    fn test_status_update_template() {
+        let config = PromptsConfig::default();
+        let prompt = config.get_status_update_prompt("Profile text", "Status text", "Entry text");
+
+        assert!(prompt.contains("Profile text") && prompt.contains("Status text") && prompt.contains("Entry text"));
+        assert!(prompt.contains(NO_STATUS_UPDATE));
+        assert!(PromptsConfig::is_no_status_update(" \"NO_UPDATE_NEEDED.\"\n"));
+        assert!(!PromptsConfig::is_no_status_update("Started a new job at the library."));
+    }
+
+    #[test]
+    fn test_missing_placeholders() {
+        assert!(PromptsConfig::default().missing_placeholders().is_empty());
+
+        let edited = PromptsConfig {
+            daily_prompt: "Ask me something about today.".to_string(),
+            ensemble_judge: "Pick one: {prompt_a}".to_string(),
+            ..PromptsConfig::default()
+        };
+        assert_eq!(edited.missing_placeholders(), ["daily_prompt: {context}", "ensemble_judge: {prompt_b}"]);
+    }
+
+    #[test]
+    fn test_ensemble_judge() {
+        let config = PromptsConfig::default();
+        let prompt = config.get_ensemble_judge_prompt("Context text", "First candidate", "Second candidate");
+        assert!(prompt.contains("Context text") && prompt.contains("First candidate") && prompt.contains("Second candidate"));
+
+        assert_eq!(PromptsConfig::parse_judge_verdict("A"), Some(true));
+        assert_eq!(PromptsConfig::parse_judge_verdict("**Prompt B** is more specific."), Some(false));
+        assert_eq!(PromptsConfig::parse_judge_verdict("Both are good."), None);
+    }
+
+    #[test]
+    fn test_reflection_question_template() {
+        let config = PromptsConfig::default();
+        let prompt = config.get_reflection_question_prompt(Some("What surprised you?"), "The bus was early.");
+        assert!(prompt.contains("in answer to the prompt \"What surprised you?\"") && prompt.contains("The bus was early."));
+        assert!(!config.get_reflection_question_prompt(None, "Entry text").contains("{prompt}"));
+    }
+
+    #[test]
+    fn test_one_line_summary_template() {
+        let config = PromptsConfig::default();
+        let prompt = config.get_one_line_summary_prompt("March 3, 2025", "Moved apartments.");
+        assert!(prompt.contains("March 3, 2025") && prompt.contains("Moved apartments."));
+        assert!(!prompt.contains("{summary}"));
+    }
+
+    #[test]
+    fn test_year_review_chapter_template() {
+        let config = PromptsConfig::default();
+        let prompt = config.get_year_review_chapter_prompt("Month 3", "March 24 to April 20", "12 entries", "A good month.", "- Moved house.");
+        assert!(prompt.contains("covering Month 3 (March 24 to April 20)") && prompt.contains("- Moved house."));
+        assert!(!prompt.contains("{reflection}"));
+    }
+
+    #[test]
+    fn test_insights_template() {
+        let config = PromptsConfig::default();
+        let prompt = config.get_insights_prompt("September 17 to October 17", "- Moved house.", "- Tired but hopeful", "- garden (3)");
+        assert!(prompt.contains("from September 17 to October 17") && prompt.contains("- garden (3)"));
+        assert!(!prompt.contains("{moods}"));
     }
 
     #[test]
@@ -140,4 +445,35 @@ mod tests {
         assert!(config.get_variation_suffix(3).contains("third unique"));
         assert!(config.get_variation_suffix(5).contains("variation #5"));
     }
+
+    #[test]
+    fn test_variation_strategy_rotates_options() {
+        let mut config = PromptsConfig {
+            variation_strategy: VariationStrategy::TimeHorizon,
+            ..Default::default()
+        };
+
+        assert_eq!(config.get_variation_suffix(1), "");
+        assert!(config.get_variation_suffix(2).contains("Look to the past"));
+        assert!(config.get_variation_suffix(3).contains("Look to the future"));
+        assert!(config.get_variation_suffix(5).contains("Look to the past"));
+
+        config.variation_options.time_horizons.clear();
+        assert!(config.get_variation_suffix(2).contains("different perspective"));
+    }
+
+    #[test]
+    fn test_variation_strategy_defaults_for_old_files() {
+        let json = serde_json::to_value(PromptsConfig::default()).unwrap();
+        let mut object = json.as_object().unwrap().clone();
+        object.remove("variation_strategy");
+        object.remove("variation_options");
+
+        let config: PromptsConfig = serde_json::from_value(object.into()).unwrap();
+        assert_eq!(config.variation_strategy, VariationStrategy::Suffix);
+        assert_eq!(config.variation_options.depths.len(), 3);
+
+        let config: PromptsConfig = serde_json::from_str(&serde_json::to_string(&PromptsConfig::default()).unwrap().replace("\"suffix\"", "\"time_horizon\"")).unwrap();
+        assert_eq!(config.variation_strategy, VariationStrategy::TimeHorizon);
+    }
 }
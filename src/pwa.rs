@@ -0,0 +1,244 @@
+//! Installable web app: the manifest, service worker, and icon, plus the endpoint
+//! that takes entries written while offline and merges them into the journal.
+
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::{AuthContext, Scope};
+use crate::cycle_date::CycleDate;
+use crate::journal::{entry_revision, EntrySaveResult, JournalEntry, JournalManager};
+use crate::notebook::ActiveNotebook;
+use crate::AppState;
+
+const MANIFEST: &str = include_str!("../assets/manifest.webmanifest");
+const SERVICE_WORKER: &str = include_str!("../assets/sw.js");
+const ICON: &str = include_str!("../assets/icon.svg");
+
+/// Times an offline entry is re-merged when the entry keeps changing underneath it
+const SYNC_ATTEMPTS: usize = 3;
+
+/// The app's assets, served from the root so the service worker controls every page
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/manifest.webmanifest", get(|| async { asset("application/manifest+json", MANIFEST) }))
+        .route("/sw.js", get(|| async { asset("text/javascript", SERVICE_WORKER) }))
+        .route("/icon.svg", get(|| async { asset("image/svg+xml", ICON) }))
+        .route("/journal/sync", post(sync_offline_entries))
+}
+
+fn asset(content_type: &'static str, body: &'static str) -> Response {
+    // Revalidate every load so a new service worker is picked up promptly
+    ([(header::CONTENT_TYPE, content_type), (header::CACHE_CONTROL, "no-cache")], body).into_response()
+}
+
+/// An entry saved on a device while it had no connection
+#[derive(Debug, Deserialize)]
+pub struct OfflineEntry {
+    pub cycle_date: String,
+    pub content: String,
+    /// When the device saved it
+    pub written_at: DateTime<Local>,
+    /// Revision of the entry the device started from ("" if there was none)
+    pub base_revision: Option<String>,
+    /// Text of the entry the device started from, for merging when both sides changed
+    pub base_content: Option<String>,
+}
+
+/// Entries queued by a device, sent when it reconnects
+#[derive(Debug, Deserialize)]
+pub struct SyncRequest {
+    pub entries: Vec<OfflineEntry>,
+}
+
+/// What happened to one offline entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncOutcome {
+    /// Saved as written; the entry hadn't changed elsewhere
+    Saved,
+    /// Combined with changes made elsewhere in the meantime
+    Merged,
+    /// The journal already had this text
+    Unchanged,
+    /// Not saved (e.g., outside the edit window); the device should keep it
+    Rejected,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncResult {
+    pub cycle_date: String,
+    pub outcome: SyncOutcome,
+    /// The entry's revision after syncing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Combine an entry written offline with the entry as it is saved now. Returns the
+/// text to save, or None if the saved entry already says everything.
+///
+/// When both sides changed since `base`, text appended on both is kept in order;
+/// otherwise the offline text follows the saved entry under a note, so nothing is lost.
+pub fn merge_offline(
+    current: Option<&str>,
+    base_revision: Option<&str>,
+    base: Option<&str>,
+    offline: &str,
+    written_at: DateTime<Local>,
+) -> Option<(SyncOutcome, String)> {
+    let Some(current) = current.filter(|c| !c.trim().is_empty()) else {
+        return (!offline.trim().is_empty()).then(|| (SyncOutcome::Saved, offline.to_string()));
+    };
+    let unchanged_since_base = base.map(|b| b == current)
+        .or_else(|| base_revision.map(|r| r == entry_revision(current)))
+        .unwrap_or(false);
+
+    if current == offline {
+        return None;
+    }
+    if unchanged_since_base {
+        return Some((SyncOutcome::Saved, offline.to_string()));
+    }
+    if base == Some(offline) || current.contains(offline.trim()) {
+        return None;
+    }
+    if offline.starts_with(current.trim_end()) {
+        return Some((SyncOutcome::Saved, offline.to_string()));
+    }
+    if let Some(base) = base.map(str::trim_end).filter(|b| !b.is_empty()) {
+        if let Some(ours) = offline.strip_prefix(base).filter(|_| current.starts_with(base)) {
+            // Both only added to the end
+            return Some((SyncOutcome::Merged, format!("{}\n\n{}", current.trim_end(), ours.trim())));
+        }
+    }
+    let note = format!("*Written offline at {}:*", written_at.format("%H:%M"));
+    Some((SyncOutcome::Merged, format!("{}\n\n{}\n\n{}", current.trim_end(), note, offline.trim())))
+}
+
+/// Merge entries written offline into the journal, oldest first (POST /journal/sync)
+async fn sync_offline_entries(
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Json(mut request): Json<SyncRequest>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::WriteEntries) {
+        return rejection.into_response();
+    }
+
+    request.entries.sort_by_key(|entry| entry.written_at);
+    let today = CycleDate::today();
+    let mut results = Vec::with_capacity(request.entries.len());
+    for offline in request.entries {
+        let result = match CycleDate::from_string(&offline.cycle_date) {
+            Ok(cycle_date) => {
+                // The edit window counts from the day the entry was written, not the day it synced
+                let written_on = CycleDate::try_from_real_date(offline.written_at.date_naive())
+                    .ok()
+                    .filter(|day| day.to_real_date() < today.to_real_date())
+                    .unwrap_or(today);
+                let editable = crate::journal::check_entry_date_editable(&cycle_date, &written_on, crate::config::live().journal.edit_window(false));
+                match editable {
+                    Ok(()) => sync_entry(&notebook.journal_manager, cycle_date, &offline).await,
+                    Err(e) => Err(e),
+                }
+            }
+            Err(e) => Err(format!("Invalid cycle date: {}", e)),
+        };
+        results.push(match result {
+            Ok((outcome, revision)) => {
+                tracing::info!("Synced offline entry for {} from {}: {:?}", offline.cycle_date, auth.label(), outcome);
+                SyncResult { cycle_date: offline.cycle_date, outcome, revision: Some(revision), error: None }
+            }
+            Err(e) => {
+                tracing::warn!("Rejected offline entry for {} from {}: {}", offline.cycle_date, auth.label(), e);
+                SyncResult { cycle_date: offline.cycle_date, outcome: SyncOutcome::Rejected, revision: None, error: Some(e) }
+            }
+        });
+    }
+
+    if results.iter().any(|r| matches!(r.outcome, SyncOutcome::Saved | SyncOutcome::Merged)) {
+        if let Err(e) = crate::stats::refresh_stats(&notebook.journal_manager).await {
+            tracing::warn!("Failed to update writing stats: {}", e);
+        }
+    }
+    (StatusCode::OK, Json(serde_json::json!({ "results": results }))).into_response()
+}
+
+/// Merge and save one offline entry, re-merging if the entry changes mid-save
+pub(crate) async fn sync_entry(journal_manager: &JournalManager, cycle_date: CycleDate, offline: &OfflineEntry) -> Result<(SyncOutcome, String), String> {
+    for _ in 0..SYNC_ATTEMPTS {
+        let current = journal_manager.load_entry(&cycle_date).await.map_err(|e| e.to_string())?;
+        let current_text = current.as_ref().map(|entry| entry.content.as_str());
+        let current_revision = current_text.map(entry_revision).unwrap_or_default();
+        let Some((outcome, content)) = merge_offline(
+            current_text,
+            offline.base_revision.as_deref(),
+            offline.base_content.as_deref(),
+            &offline.content,
+            offline.written_at,
+        ) else {
+            return Ok((SyncOutcome::Unchanged, current_revision));
+        };
+
+        // Changed now as far as other devices' sync cursors are concerned
+        let entry = JournalEntry {
+            cycle_date,
+            content,
+            created_at: offline.written_at,
+            modified_at: Local::now(),
+        };
+        let saved = journal_manager
+            .save_entry_if_unchanged(&entry, Some(&current_revision))
+            .await
+            .map_err(|e| e.to_string())?;
+        if let EntrySaveResult::Saved(revision) = saved {
+            return Ok((outcome, revision));
+        }
+    }
+    Err("The entry kept changing while syncing; try again".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_offline() {
+        let at = Local::now();
+        // Nothing saved yet, or the saved entry is what the device started from
+        assert_eq!(merge_offline(None, Some(""), None, "On the plane", at), Some((SyncOutcome::Saved, "On the plane".to_string())));
+        assert_eq!(
+            merge_offline(Some("Morning"), None, Some("Morning"), "Morning\n\nOn the plane", at),
+            Some((SyncOutcome::Saved, "Morning\n\nOn the plane".to_string()))
+        );
+        assert_eq!(
+            merge_offline(Some("Morning"), Some(&entry_revision("Morning")), None, "Rewritten", at),
+            Some((SyncOutcome::Saved, "Rewritten".to_string()))
+        );
+        assert_eq!(
+            merge_offline(Some("Morning, then more"), None, Some("Morning, then more"), "Morning", at),
+            Some((SyncOutcome::Saved, "Morning".to_string()))
+        );
+        // Already there, or the device changed nothing
+        assert_eq!(merge_offline(Some("Morning\n\nOn the plane"), None, None, "On the plane", at), None);
+        assert_eq!(merge_offline(Some("Edited at home"), None, Some("Morning"), "Morning", at), None);
+
+        // Both appended to the same start
+        assert_eq!(
+            merge_offline(Some("Morning\n\nLunch at home"), None, Some("Morning"), "Morning\n\nOn the plane", at),
+            Some((SyncOutcome::Merged, "Morning\n\nLunch at home\n\nOn the plane".to_string()))
+        );
+        // Both rewrote it: keep both
+        let (outcome, merged) = merge_offline(Some("Edited at home"), None, Some("Morning"), "Edited on the plane", at).unwrap();
+        assert_eq!(outcome, SyncOutcome::Merged);
+        assert!(merged.starts_with("Edited at home\n\n*Written offline at "));
+        assert!(merged.ends_with("Edited on the plane"));
+    }
+}
@@ -0,0 +1,181 @@
+use crate::cycle_date::{CalendarMode, CycleDate};
+use crate::journal::{JournalManager, PromptOutcome, PromptType};
+use std::fmt::Write as _;
+
+/// Questions listed under "the ones you wrote the most about"
+const LONGEST_ANSWERS: usize = 5;
+
+/// A prompt that got an entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct Question {
+    pub cycle_date: CycleDate,
+    pub prompt_type: PromptType,
+    pub prompt: String,
+    /// Length of the entry written in answer
+    pub words: usize,
+}
+
+impl Question {
+    fn from_outcome(outcome: PromptOutcome) -> Option<Self> {
+        outcome.answered.then(|| Question {
+            cycle_date: outcome.cycle_date,
+            prompt_type: outcome.prompt_type,
+            prompt: outcome.prompt.trim().to_string(),
+            words: outcome.entry_words.unwrap_or(0),
+        })
+    }
+}
+
+/// Every prompt answered during a year of `calendar`, in date order
+pub async fn gather(journal_manager: &JournalManager, calendar: CalendarMode, year: i32) -> Result<Vec<Question>, Box<dyn std::error::Error>> {
+    let outcomes = journal_manager.list_prompt_outcomes(&CycleDate::today()).await?;
+    let mut questions: Vec<Question> = outcomes
+        .into_iter()
+        .filter(|o| calendar.year_of(o.cycle_date.to_real_date()) == year)
+        .filter_map(Question::from_outcome)
+        .collect();
+    questions.sort_by_key(|q| q.cycle_date.to_real_date());
+    Ok(questions)
+}
+
+/// The question book: the year's answered prompts by month, reflections set apart, and the
+/// questions that drew the longest answers
+pub fn render(calendar: CalendarMode, year: i32, questions: &[Question]) -> String {
+    let (first, last) = calendar.year_bounds(year).unwrap_or_default();
+    let mut out = String::new();
+    let _ = writeln!(out, "# Questions That Shaped Year {:02}", year);
+    let _ = writeln!(
+        out,
+        "\n*{} questions answered, {} to {}*",
+        questions.len(),
+        first.format("%B %-d, %Y"),
+        last.format("%B %-d, %Y"),
+    );
+    if questions.is_empty() {
+        let _ = writeln!(out, "\nNo prompts were answered this year.");
+        return out;
+    }
+
+    let mut longest: Vec<&Question> = questions.iter().collect();
+    longest.sort_by_key(|q| std::cmp::Reverse(q.words));
+    let _ = writeln!(out, "\n## The ones you wrote the most about\n");
+    for question in longest.into_iter().take(LONGEST_ANSWERS) {
+        let _ = writeln!(out, "- {} *({}, {} words)*", question.prompt, day(question), question.words);
+    }
+
+    for (start, end) in calendar.months_of_year(year) {
+        let in_month: Vec<&Question> = questions.iter().filter(|q| (start..=end).contains(&q.cycle_date.to_real_date())).collect();
+        if in_month.is_empty() {
+            continue;
+        }
+        let name = crate::cycle_date::names().month(&CycleDate::from_real_date(start), calendar);
+        let _ = writeln!(out, "\n## {}: {} to {}\n", name, start.format("%B %-d"), end.format("%B %-d"));
+        for question in in_month {
+            match &question.prompt_type {
+                PromptType::Daily => {
+                    let _ = writeln!(out, "- **{}**: {}", day(question), question.prompt);
+                }
+                reflection => {
+                    let _ = writeln!(out, "- **{} ({})**: {}", day(question), reflection, question.prompt);
+                }
+            }
+        }
+    }
+    out
+}
+
+/// "March 3"
+fn day(question: &Question) -> String {
+    question.cycle_date.to_real_date().format("%B %-d").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::{JournalEntry, JournalPrompt};
+    use crate::test_support::date;
+    use chrono::Local;
+
+    #[tokio::test]
+    async fn test_gather_and_render() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let journal_manager = JournalManager::new(temp_dir.path());
+
+        let answered = CycleDate::new(1, 2, 1, 3).unwrap();
+        let reflection = CycleDate::new(1, 3, 0, 0).unwrap();
+        let skipped = CycleDate::new(1, 2, 1, 4).unwrap();
+        let other_year = CycleDate::new(0, 5, 0, 1).unwrap();
+        for (cycle_date, prompt) in [
+            (answered, "What surprised you today?"),
+            (reflection, "What did this month teach you?"),
+            (skipped, "Who did you miss today?"),
+            (other_year, "What are you looking forward to?"),
+        ] {
+            journal_manager.save_prompt(&JournalPrompt {
+                cycle_date,
+                prompt: prompt.to_string(),
+                prompt_number: 1,
+                generated_at: Local::now(),
+                prompt_type: PromptType::for_date(&cycle_date),
+            }).await.unwrap();
+        }
+        for (cycle_date, content) in [(answered, "The rain held off all day."), (reflection, "Patience, mostly."), (other_year, "Spring.")] {
+            journal_manager.save_entry(&JournalEntry {
+                cycle_date,
+                content: content.to_string(),
+                created_at: Local::now(),
+                modified_at: Local::now(),
+            }).await.unwrap();
+        }
+
+        let questions = gather(&journal_manager, CalendarMode::Cycle, 1).await.unwrap();
+        assert_eq!(questions.iter().map(|q| q.cycle_date).collect::<Vec<_>>(), vec![answered, reflection]);
+        assert_eq!(questions[0].words, 6);
+
+        let book = render(CalendarMode::Cycle, 1, &questions);
+        assert!(book.starts_with("# Questions That Shaped Year 01"));
+        assert!(book.contains("*2 questions answered"));
+        assert!(book.contains(&format!("- **{}**: What surprised you today?", day(&questions[0]))));
+        assert!(book.contains("(Monthly Reflection)**: What did this month teach you?"));
+        assert!(!book.contains("Who did you miss today?"));
+        assert!(render(CalendarMode::Cycle, 2, &[]).contains("No prompts were answered"));
+    }
+
+    #[tokio::test]
+    async fn test_gather_and_render_gregorian() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let journal_manager = JournalManager::new(temp_dir.path());
+
+        let march = CycleDate::from_real_date(date(2025, 3, 31));
+        let april = CycleDate::from_real_date(date(2025, 4, 1));
+        let last_year = CycleDate::from_real_date(date(2024, 12, 31));
+        for (cycle_date, prompt) in [
+            (march, "What are you packing?"),
+            (april, "What did March teach you?"),
+            (last_year, "What will you leave behind?"),
+        ] {
+            journal_manager.save_prompt(&JournalPrompt {
+                cycle_date,
+                prompt: prompt.to_string(),
+                prompt_number: 1,
+                generated_at: Local::now(),
+                prompt_type: PromptType::Daily,
+            }).await.unwrap();
+            journal_manager.save_entry(&JournalEntry {
+                cycle_date,
+                content: "An answer.".to_string(),
+                created_at: Local::now(),
+                modified_at: Local::now(),
+            }).await.unwrap();
+        }
+
+        let questions = gather(&journal_manager, CalendarMode::Gregorian, 2025).await.unwrap();
+        assert_eq!(questions.iter().map(|q| q.cycle_date).collect::<Vec<_>>(), vec![march, april]);
+
+        let book = render(CalendarMode::Gregorian, 2025, &questions);
+        assert!(book.starts_with("# Questions That Shaped Year 2025"));
+        assert!(book.contains("January 1, 2025 to December 31, 2025"));
+        assert!(book.contains("## March: March 1 to March 31\n\n- **March 31**: What are you packing?"));
+        assert!(book.contains("## April: April 1 to April 30\n\n- **April 1**: What did March teach you?"));
+    }
+}
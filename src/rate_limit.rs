@@ -0,0 +1,251 @@
+//! Request rate limits, so a misbehaving client or a bot probing the port can't tie up
+//! the model or the disk. Every request counts against its address's general limit;
+//! expensive ones (prompt generation, transcription, search, exports, logins) also count
+//! against a much lower limit, per address and per session. Behind a trusted reverse
+//! proxy, the address is the client's as the proxy reports it.
+
+use axum::{
+    extract::{ConnectInfo, MatchedPath, Request},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::AppError;
+
+/// Routes that run the model, read much of the journal, or create login passcodes, as
+/// they're declared in the router
+const EXPENSIVE_ROUTES: &[&str] = &[
+    "/login",
+    "/api/v1/auth/request",
+    "/api/v1/auth/login",
+    "/journal/generate-prompt",
+    "/journal/navigate-prompt",
+    "/journal/regenerate",
+    "/journal/reflect",
+    "/journal/voice-note",
+    "/search",
+    "/journal/download",
+    "/journal/:date/download",
+    "/journal/:date/attachments",
+    "/api/batch",
+    "/api/v1/batch",
+    "/insights/refresh",
+    "/year-review",
+    "/question-book",
+    "/digest/audio",
+    "/admin/doctor",
+    "/admin/import",
+    "/api/jobs/export",
+    "/api/jobs/import",
+];
+
+/// Fixed-window request counter per client
+pub struct RateLimiter<K> {
+    limit: u32,
+    window: Duration,
+    hits: Mutex<HashMap<K, (Instant, u32)>>,
+}
+
+impl<K: Hash + Eq> RateLimiter<K> {
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            hits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Count a request; past the limit, how long until the client's window ends
+    pub fn check(&self, client: K, now: Instant) -> Result<(), Duration> {
+        let mut hits = self.hits.lock().unwrap();
+        hits.retain(|_, (start, _)| now.duration_since(*start) < self.window);
+        let (start, count) = hits.entry(client).or_insert((now, 0));
+        *count += 1;
+        if *count <= self.limit {
+            Ok(())
+        } else {
+            Err(self.window.saturating_sub(now.duration_since(*start)))
+        }
+    }
+}
+
+/// The limits `rate_limit` enforces; a limit of 0 turns it off
+pub struct RateLimits {
+    requests: Option<RateLimiter<IpAddr>>,
+    expensive_by_address: Option<RateLimiter<IpAddr>>,
+    expensive_by_session: Option<RateLimiter<String>>,
+    trusted_proxies: Vec<IpAddr>,
+    forwarded_header: String,
+}
+
+impl RateLimits {
+    /// Limits per minute: `requests_per_minute` for everything, `expensive_per_minute` for expensive routes
+    pub fn new(requests_per_minute: u32, expensive_per_minute: u32) -> Self {
+        Self {
+            requests: per_minute(requests_per_minute),
+            expensive_by_address: per_minute(expensive_per_minute),
+            expensive_by_session: per_minute(expensive_per_minute),
+            trusted_proxies: Vec::new(),
+            forwarded_header: String::new(),
+        }
+    }
+
+    /// Limit requests relayed by one of `proxies` by the client address it puts in `header`
+    pub fn behind(mut self, proxies: &[IpAddr], header: &str) -> Self {
+        self.trusted_proxies = proxies.to_vec();
+        self.forwarded_header = header.to_string();
+        self
+    }
+
+    /// The address a request is counted against (see `client_address`)
+    fn client_address(&self, peer: Option<IpAddr>, headers: &HeaderMap) -> Option<IpAddr> {
+        peer.map(|peer| client_address(peer, headers, &self.trusted_proxies, &self.forwarded_header))
+    }
+
+    /// Count a request to the route `path`, returning how long to wait if it's over a limit
+    fn check(&self, path: &str, address: Option<IpAddr>, session: Option<String>, now: Instant) -> Result<(), Duration> {
+        if let (Some(limiter), Some(address)) = (&self.requests, address) {
+            limiter.check(address, now)?;
+        }
+        if EXPENSIVE_ROUTES.contains(&path) {
+            if let (Some(limiter), Some(address)) = (&self.expensive_by_address, address) {
+                limiter.check(address, now)?;
+            }
+            if let (Some(limiter), Some(session)) = (&self.expensive_by_session, session) {
+                limiter.check(session, now)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The client a request came from: the peer, or for one of `trusted_proxies` the last
+/// address it added to `forwarded_header` (earlier ones came from the client and can be forged)
+pub fn client_address(peer: IpAddr, headers: &HeaderMap, trusted_proxies: &[IpAddr], forwarded_header: &str) -> IpAddr {
+    if !trusted_proxies.contains(&peer) {
+        return peer;
+    }
+    let forwarded = headers
+        .get_all(forwarded_header)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .last()
+        .map(str::trim)
+        .and_then(|address| {
+            address.parse::<IpAddr>().ok().or_else(|| address.parse::<SocketAddr>().ok().map(|address| address.ip()))
+        });
+    forwarded.unwrap_or(peer)
+}
+
+fn per_minute<K: Hash + Eq>(limit: u32) -> Option<RateLimiter<K>> {
+    (limit > 0).then(|| RateLimiter::new(limit, Duration::from_secs(60)))
+}
+
+/// Middleware answering requests over a limit with 429 Too Many Requests and Retry-After
+pub async fn rate_limit(limits: &RateLimits, request: Request, next: Next) -> Response {
+    let peer = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|ConnectInfo(address)| address.ip());
+    let address = limits.client_address(peer, request.headers());
+    let session = crate::handlers::extract_session_token(request.headers()).or_else(|| {
+        request.headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(|token| token.trim().to_string())
+    });
+
+    // The route's pattern, so "/journal/:date/download" covers every date
+    let path = match request.extensions().get::<MatchedPath>() {
+        Some(matched) => {
+            let route = matched.as_str();
+            route.strip_prefix(crate::handlers::base_path()).filter(|route| route.starts_with('/')).unwrap_or(route).to_string()
+        }
+        None => request.uri().path().to_string(),
+    };
+    if let Err(wait) = limits.check(&path, address, session, Instant::now()) {
+        let seconds = wait.as_secs().max(1);
+        tracing::warn!("Rate limited {} {} from {}", request.method(), request.uri().path(), address.map_or("an unknown address".to_string(), |a| a.to_string()));
+        let mut response = AppError::from_status(
+            StatusCode::TOO_MANY_REQUESTS,
+            format!("Too many requests; try again in {} seconds", seconds),
+        ).into_response();
+        response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+        return response;
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(60));
+        let visitor: IpAddr = "203.0.113.7".parse().unwrap();
+        let other: IpAddr = "203.0.113.8".parse().unwrap();
+        let start = Instant::now();
+
+        assert!(limiter.check(visitor, start).is_ok());
+        assert!(limiter.check(visitor, start).is_ok());
+        assert_eq!(limiter.check(visitor, start + Duration::from_secs(10)), Err(Duration::from_secs(50)));
+        assert!(limiter.check(other, start + Duration::from_secs(10)).is_ok());
+        // A new window starts once the old one has passed
+        assert!(limiter.check(visitor, start + Duration::from_secs(61)).is_ok());
+    }
+
+    #[test]
+    fn test_rate_limits() {
+        let limits = RateLimits::new(100, 2);
+        let visitor: IpAddr = "203.0.113.7".parse().unwrap();
+        let other: IpAddr = "203.0.113.8".parse().unwrap();
+        let now = Instant::now();
+
+        // Cheap routes only count against the general limit
+        for _ in 0..10 {
+            assert!(limits.check("/journal", Some(visitor), None, now).is_ok());
+        }
+        assert!(limits.check("/search", Some(visitor), Some("phone".to_string()), now).is_ok());
+        assert!(limits.check("/search", Some(visitor), Some("phone".to_string()), now).is_ok());
+        assert!(limits.check("/search", Some(visitor), None, now).is_err());
+        // The session is limited from any address
+        assert!(limits.check("/journal/reflect", Some(other), Some("phone".to_string()), now).is_err());
+        assert!(limits.check("/journal/reflect", Some(other), None, now).is_ok());
+        // Parameterised routes count as their pattern
+        let third: IpAddr = "203.0.113.9".parse().unwrap();
+        assert!(limits.check("/journal/:date/download", Some(third), None, now).is_ok());
+        assert!(limits.check("/journal/:date/attachments", Some(third), None, now).is_ok());
+        assert!(limits.check("/api/batch", Some(third), None, now).is_err());
+
+        let off = RateLimits::new(0, 0);
+        for _ in 0..10 {
+            assert!(off.check("/search", Some(visitor), None, now).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_client_address() {
+        let proxy: IpAddr = "127.0.0.1".parse().unwrap();
+        let visitor: IpAddr = "203.0.113.7".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", HeaderValue::from_static("198.51.100.1, 203.0.113.7"));
+
+        // Without a trusted proxy the header is ignored
+        let limits = RateLimits::new(100, 2);
+        assert_eq!(limits.client_address(Some(proxy), &headers), Some(proxy));
+
+        let limits = RateLimits::new(100, 2).behind(&[proxy], "X-Forwarded-For");
+        assert_eq!(limits.client_address(Some(proxy), &headers), Some(visitor));
+        assert_eq!(limits.client_address(Some(visitor), &headers), Some(visitor));
+        assert_eq!(limits.client_address(Some(proxy), &HeaderMap::new()), Some(proxy));
+        assert_eq!(limits.client_address(None, &headers), None);
+        assert_eq!(client_address(proxy, &headers, &[proxy], "X-Forwarded-For"), visitor);
+        assert_eq!(client_address(proxy, &headers, &[proxy], "X-Real-IP"), proxy);
+    }
+}
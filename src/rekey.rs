@@ -0,0 +1,214 @@
+use crate::cycle_date::DateScheme;
+use crate::journal::JournalManager;
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tokio::fs;
+
+/// Folder day folders pass through while being renamed, so a new name never collides with
+/// an old one that hasn't moved yet
+const STAGING_DIR: &str = ".rekey";
+
+/// A day folder and the name it gets in the new scheme
+#[derive(Debug, Clone, PartialEq)]
+pub struct Move {
+    pub from: String,
+    pub to: String,
+    pub date: NaiveDate,
+}
+
+/// What converting a journal would do
+#[derive(Debug, Clone, Default)]
+pub struct RekeyPlan {
+    pub moves: Vec<Move>,
+    /// Folders already named for the new scheme
+    pub unchanged: usize,
+    /// Folders left where they are, with why
+    pub skipped: Vec<(String, String)>,
+}
+
+/// How a conversion went
+#[derive(Debug, Clone, Default)]
+pub struct RekeyReport {
+    pub moved: usize,
+    /// Moved folders whose new name reads back as the same date, holding the same files
+    pub verified: usize,
+    pub problems: Vec<String>,
+}
+
+/// Work out the new name of every day folder named in `from`, without changing anything
+pub async fn plan(base_path: &Path, from: DateScheme, to: DateScheme) -> Result<RekeyPlan, Box<dyn std::error::Error>> {
+    let mut plan = RekeyPlan::default();
+    if !base_path.exists() {
+        return Ok(plan);
+    }
+
+    let mut names = Vec::new();
+    let mut dir_entries = fs::read_dir(base_path).await?;
+    while let Some(entry) = dir_entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        // Only folders named like dates are days; reports, reviews, and .git aren't
+        if entry.file_type().await?.is_dir() && crate::cycle_date::is_date_like(&name) {
+            names.push(name);
+        }
+    }
+    names.sort();
+
+    let mut moves = Vec::new();
+    for name in &names {
+        let date = match from.parse(name) {
+            Ok(date) => date,
+            // Left by an earlier, interrupted conversion or renamed by the doctor
+            Err(_) if to.parse(name).is_ok() => {
+                plan.unchanged += 1;
+                continue;
+            }
+            Err(e) => {
+                plan.skipped.push((name.clone(), e));
+                continue;
+            }
+        };
+        match to.name(date) {
+            Some(to_name) if to_name == *name => plan.unchanged += 1,
+            Some(to_name) => moves.push(Move { from: name.clone(), to: to_name, date }),
+            None => plan.skipped.push((name.clone(), format!("{} is outside the range the new scheme can name", date))),
+        }
+    }
+
+    // A new name already taken by a folder that isn't moving would merge two days
+    let moving: Vec<String> = moves.iter().map(|m| m.from.clone()).collect();
+    for mv in moves {
+        if names.contains(&mv.to) && !moving.contains(&mv.to) {
+            plan.skipped.push((mv.from.clone(), format!("{} already exists; merge them by hand", mv.to)));
+        } else {
+            plan.moves.push(mv);
+        }
+    }
+    plan.skipped.sort();
+    Ok(plan)
+}
+
+/// Rename the planned folders, then check every one: its new name must read back as the
+/// same date and it must hold the same files as before. Drafts are then re-dated to match,
+/// and the index is rebuilt.
+pub async fn apply(journal_manager: &JournalManager, plan: &RekeyPlan, to: DateScheme) -> Result<RekeyReport, Box<dyn std::error::Error>> {
+    let base_path = journal_manager.base_path();
+    let mut report = RekeyReport::default();
+    if plan.moves.is_empty() {
+        return Ok(report);
+    }
+
+    let mut manifests = Vec::new();
+    for mv in &plan.moves {
+        manifests.push(manifest(&base_path.join(&mv.from))?);
+    }
+
+    let staging = base_path.join(STAGING_DIR);
+    fs::create_dir_all(&staging).await?;
+    for mv in &plan.moves {
+        fs::rename(base_path.join(&mv.from), staging.join(&mv.from)).await?;
+    }
+    for mv in &plan.moves {
+        fs::rename(staging.join(&mv.from), base_path.join(&mv.to)).await?;
+        report.moved += 1;
+    }
+    fs::remove_dir(&staging).await?;
+
+    for (mv, before) in plan.moves.iter().zip(manifests) {
+        if to.parse(&mv.to) != Ok(mv.date) {
+            report.problems.push(format!("{} does not read back as {}", mv.to, mv.date));
+        } else if manifest(&base_path.join(&mv.to))? != before {
+            report.problems.push(format!("{} (was {}) does not hold the same files as before", mv.to, mv.from));
+        } else {
+            report.verified += 1;
+        }
+        redate_draft(&base_path.join(&mv.to), to, mv.date).await?;
+    }
+
+    journal_manager.rebuild_index().await?;
+    journal_manager.record_change(format!("Rename {} day folders for a new calendar", report.moved)).await;
+    Ok(report)
+}
+
+/// Every file under a folder, by relative path, with its size
+fn manifest(dir: &Path) -> std::io::Result<BTreeMap<String, u64>> {
+    let mut files = BTreeMap::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in std::fs::read_dir(&current)? {
+            let entry = entry?;
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                pending.push(path);
+            } else {
+                let relative = path.strip_prefix(dir).unwrap_or(&path).to_string_lossy().to_string();
+                files.insert(relative, entry.metadata()?.len());
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Drafts record the name of the date they were written for, which changes with the scheme
+async fn redate_draft(day_dir: &Path, to: DateScheme, date: NaiveDate) -> Result<(), Box<dyn std::error::Error>> {
+    let path = day_dir.join("draft.json");
+    if !path.exists() {
+        return Ok(());
+    }
+    let mut draft: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).await?)?;
+    let cycle_date = serde_json::Value::String(to.name(date).ok_or("The date is outside the range the journal can hold")?);
+    if draft.get("cycle_date") != Some(&cycle_date) {
+        draft["cycle_date"] = cycle_date;
+        fs::write(&path, serde_json::to_string_pretty(&draft)?).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cycle_date::{CalendarMode, CycleDate};
+
+    #[tokio::test]
+    async fn test_round_trip() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let journal_manager = JournalManager::new(temp_dir.path());
+        let cycle: DateScheme = "cycle".parse().unwrap();
+        let gregorian = DateScheme { calendar: CalendarMode::Gregorian, leap_weeks: false };
+
+        let day = CycleDate::new(1, 2, 1, 3).unwrap();
+        let after_leap_week = CycleDate::new(3, 0, 0, 0).unwrap();
+        journal_manager.save_draft(&after_leap_week, "Halfway through.", None).await.unwrap();
+        std::fs::create_dir_all(temp_dir.path().join("01213/attachments")).unwrap();
+        std::fs::write(temp_dir.path().join("01213/attachments/photo.jpg"), b"jpeg").unwrap();
+        std::fs::write(temp_dir.path().join("01213/entry.txt"), "A quiet day.").unwrap();
+        std::fs::create_dir_all(temp_dir.path().join("01214")).unwrap();
+        // Taken by another folder that isn't moving, and named in neither scheme
+        std::fs::create_dir_all(temp_dir.path().join(gregorian.name(day.next_day().to_real_date()).unwrap())).unwrap();
+        std::fs::create_dir_all(temp_dir.path().join("1x000")).unwrap();
+
+        let forward = plan(temp_dir.path(), cycle, gregorian).await.unwrap();
+        let date = day.to_real_date();
+        assert_eq!(forward.moves[0], Move { from: "01213".to_string(), to: date.format("%Y-%m-%d").to_string(), date });
+        assert_eq!(forward.moves[1].from, "03000");
+        assert_eq!(forward.unchanged, 1);
+        assert_eq!(forward.skipped.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(), vec!["01214", "1x000"]);
+
+        let report = apply(&journal_manager, &forward, gregorian).await.unwrap();
+        assert_eq!((report.moved, report.verified), (2, 2));
+        assert!(report.problems.is_empty());
+        let moved = temp_dir.path().join(&forward.moves[0].to);
+        assert_eq!(std::fs::read_to_string(moved.join("entry.txt")).unwrap(), "A quiet day.");
+        assert!(moved.join("attachments/photo.jpg").exists() && !temp_dir.path().join(STAGING_DIR).exists());
+
+        // And back, under the leap week rule, where the draft's day falls in year 02's leap week
+        let leap: DateScheme = "cycle+leap".parse().unwrap();
+        let back = plan(temp_dir.path(), gregorian, leap).await.unwrap();
+        assert_eq!(back.moves.iter().map(|m| m.to.as_str()).collect::<Vec<_>>(), vec!["01213", "02C40"]);
+        assert_eq!(back.skipped.len(), 2);
+        let report = apply(&journal_manager, &back, leap).await.unwrap();
+        assert_eq!(report.verified, 2, "{:?}", report.problems);
+        let draft: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(temp_dir.path().join("02C40/draft.json")).unwrap()).unwrap();
+        assert_eq!(draft["cycle_date"], "02C40");
+    }
+}
@@ -0,0 +1,273 @@
+use crate::cycle_date::CycleDate;
+use crate::journal::{sanitize_attachment_name, JournalManager};
+use chrono::{DateTime, Local};
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use tokio::fs;
+
+const REPORTS_DIR: &str = "reports";
+
+/// A status update proposed while summarizing an entry
+#[derive(Debug, Clone)]
+pub struct StatusChange {
+    pub cycle_date: CycleDate,
+    pub previous: Option<String>,
+    pub proposed: String,
+}
+
+/// What a processing run did, written as a Markdown report in the journal directory
+#[derive(Debug, Clone)]
+pub struct ProcessingReport {
+    pub cycle_date: CycleDate,
+    /// What triggered the run (e.g., "nightly", "startup")
+    pub trigger: String,
+    pub started_at: DateTime<Local>,
+    pub summarized: Vec<CycleDate>,
+    /// Entries given a one-line summary for long-horizon context
+    pub compacted: Vec<CycleDate>,
+    pub status_changes: Vec<StatusChange>,
+    pub prompts_generated: Vec<(u8, String)>,
+    pub expired_prompt_days: usize,
+    /// The day was marked as needing no prompt, so none were generated
+    pub no_prompt_needed: bool,
+    /// Generated artifacts the retention cleanup removed (or would have, in a dry run)
+    pub retention: Option<crate::retention::RetentionReport>,
+    /// Year-in-review book compiled by the run
+    pub year_review: Option<PathBuf>,
+    pub errors: Vec<String>,
+}
+
+impl ProcessingReport {
+    pub fn new(cycle_date: CycleDate, trigger: &str) -> Self {
+        Self {
+            cycle_date,
+            trigger: trigger.to_string(),
+            started_at: Local::now(),
+            summarized: Vec::new(),
+            compacted: Vec::new(),
+            status_changes: Vec::new(),
+            prompts_generated: Vec::new(),
+            expired_prompt_days: 0,
+            no_prompt_needed: false,
+            retention: None,
+            year_review: None,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Whether the run did nothing worth reporting
+    pub fn is_empty(&self) -> bool {
+        self.summarized.is_empty()
+            && self.compacted.is_empty()
+            && self.status_changes.is_empty()
+            && self.prompts_generated.is_empty()
+            && self.expired_prompt_days == 0
+            && !self.no_prompt_needed
+            && self.retention.is_none()
+            && self.year_review.is_none()
+            && self.errors.is_empty()
+    }
+
+    /// One line on what the run did: "Summarized 2 entries, wrote 3 prompts, 1 error"
+    pub fn outcome(&self) -> String {
+        let plural = |count: usize, one: &str, many: &str| format!("{} {}", count, if count == 1 { one } else { many });
+        let mut parts = Vec::new();
+        if !self.summarized.is_empty() {
+            parts.push(format!("summarized {}", plural(self.summarized.len(), "entry", "entries")));
+        }
+        if !self.prompts_generated.is_empty() {
+            parts.push(format!("wrote {}", plural(self.prompts_generated.len(), "prompt", "prompts")));
+        }
+        if self.no_prompt_needed {
+            parts.push("no prompt needed today".to_string());
+        }
+        if self.expired_prompt_days > 0 {
+            parts.push(format!("expired prompts on {}", plural(self.expired_prompt_days, "day", "days")));
+        }
+        if self.year_review.is_some() {
+            parts.push("compiled the year in review".to_string());
+        }
+        if !self.errors.is_empty() {
+            parts.push(plural(self.errors.len(), "error", "errors"));
+        }
+        let outcome = if parts.is_empty() { "nothing needed doing".to_string() } else { parts.join(", ") };
+        let mut chars = outcome.chars();
+        chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+    }
+
+    /// Record a failure without stopping the run
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.errors.push(message.into());
+    }
+
+    /// Render the report as Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# Processing report for {} ({})", self.cycle_date, self.cycle_date.to_real_date().format("%A, %B %d, %Y"));
+        let _ = writeln!(out);
+        let _ = writeln!(out, "- Trigger: {}", self.trigger);
+        let _ = writeln!(out, "- Started: {}", self.started_at.format("%Y-%m-%d %H:%M:%S"));
+        let _ = writeln!(out, "- Finished: {}", Local::now().format("%Y-%m-%d %H:%M:%S"));
+        let _ = writeln!(out, "- Result: {}", if self.errors.is_empty() { "OK" } else { "completed with errors" });
+
+        let _ = writeln!(out, "\n## Summaries\n");
+        if self.summarized.is_empty() {
+            let _ = writeln!(out, "No entries needed summaries.");
+        }
+        for cycle_date in &self.summarized {
+            let _ = writeln!(out, "- {} ({})", cycle_date, cycle_date.to_real_date().format("%Y-%m-%d"));
+        }
+
+        if !self.compacted.is_empty() {
+            let _ = writeln!(out, "\nWrote one-line summaries for {} entries over a year old.", self.compacted.len());
+        }
+
+        let _ = writeln!(out, "\n## Status changes\n");
+        if self.status_changes.is_empty() {
+            let _ = writeln!(out, "No status updates were proposed.");
+        }
+        for change in &self.status_changes {
+            let _ = writeln!(out, "From the entry for {}:\n", change.cycle_date);
+            let _ = writeln!(out, "```diff");
+            out.push_str(&line_diff(change.previous.as_deref().unwrap_or(""), &change.proposed));
+            let _ = writeln!(out, "```\n");
+        }
+
+        let _ = writeln!(out, "\n## Prompts\n");
+        if self.no_prompt_needed {
+            let _ = writeln!(out, "Skipped: the day is marked as needing no prompt.");
+        } else if self.prompts_generated.is_empty() {
+            let _ = writeln!(out, "No new prompts were generated.");
+        }
+        for (number, prompt) in &self.prompts_generated {
+            let _ = writeln!(out, "{}. {}", number, prompt.replace('\n', " "));
+        }
+        if self.expired_prompt_days > 0 {
+            let _ = writeln!(out, "\nExpired unanswered prompts on {} days.", self.expired_prompt_days);
+        }
+
+        if let Some(path) = &self.year_review {
+            let _ = writeln!(out, "\n## Year in review\n");
+            let _ = writeln!(out, "Compiled {}.", path.display());
+        }
+
+        if let Some(retention) = &self.retention {
+            let _ = writeln!(out, "\n## Cleanup\n");
+            if retention.dry_run {
+                let _ = writeln!(out, "Dry run: these are past their retention period but were kept.\n");
+            }
+            for expired in &retention.expired {
+                let _ = writeln!(out, "- {}: {} ({})", expired.kind.label(), expired.path, expired.detail);
+            }
+            if let Some(snapshot_id) = &retention.snapshot_id {
+                let _ = writeln!(out, "\nThe removed files are in snapshot {}.", snapshot_id);
+            }
+        }
+
+        let _ = writeln!(out, "\n## Errors\n");
+        if self.errors.is_empty() {
+            let _ = writeln!(out, "None.");
+        }
+        for error in &self.errors {
+            let _ = writeln!(out, "- {}", error);
+        }
+
+        out
+    }
+
+    /// Write the report to `reports/` in the journal directory, returning its path
+    pub async fn save(&self, journal_manager: &JournalManager) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let dir = journal_manager.base_path().join(REPORTS_DIR);
+        fs::create_dir_all(&dir).await?;
+
+        let path = dir.join(format!("{}-{}.md", self.started_at.format("%Y-%m-%d-%H%M%S"), self.trigger));
+        fs::write(&path, self.to_markdown()).await?;
+        Ok(path)
+    }
+}
+
+/// Minimal line diff: unchanged lines are kept, removed lines get "-", added lines get "+"
+fn line_diff(old: &str, new: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let mut out = String::new();
+
+    for line in &old_lines {
+        let marker = if new_lines.contains(line) { ' ' } else { '-' };
+        let _ = writeln!(out, "{} {}", marker, line);
+    }
+    for line in new_lines.iter().filter(|l| !old_lines.contains(l)) {
+        let _ = writeln!(out, "+ {}", line);
+    }
+
+    out
+}
+
+/// Report file names, newest first
+pub async fn list_reports(journal_manager: &JournalManager) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let dir = journal_manager.base_path().join(REPORTS_DIR);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut names = Vec::new();
+    let mut entries = fs::read_dir(&dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.ends_with(".md") {
+            names.push(name);
+        }
+    }
+
+    names.sort_by(|a, b| b.cmp(a));
+    Ok(names)
+}
+
+/// Load a report by file name
+pub async fn load_report(journal_manager: &JournalManager, name: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let Some(name) = sanitize_attachment_name(name) else {
+        return Ok(None);
+    };
+    let path = journal_manager.base_path().join(REPORTS_DIR).join(name);
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(fs::read_to_string(path).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_line_diff() {
+        let diff = line_diff("Training for a half marathon.\nBusy at work.", "Training for a half marathon.\nOn vacation.");
+        assert_eq!(diff, "  Training for a half marathon.\n- Busy at work.\n+ On vacation.\n");
+    }
+
+    #[tokio::test]
+    async fn test_report_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        let cycle_date = CycleDate::new(1, 2, 1, 3).unwrap();
+
+        let mut report = ProcessingReport::new(cycle_date, "nightly");
+        report.summarized.push(cycle_date.previous_day());
+        report.prompts_generated.push((1, "What felt easy today?".to_string()));
+        report.error("Prompt 2 failed: model timed out");
+        report.save(&manager).await.unwrap();
+
+        let names = list_reports(&manager).await.unwrap();
+        assert_eq!(names.len(), 1);
+        assert!(names[0].ends_with("-nightly.md"));
+
+        assert_eq!(report.outcome(), "Summarized 1 entry, wrote 1 prompt, 1 error");
+        assert_eq!(ProcessingReport::new(cycle_date, "manual").outcome(), "Nothing needed doing");
+
+        let text = load_report(&manager, &names[0]).await.unwrap().unwrap();
+        assert!(text.contains("1. What felt easy today?"));
+        assert!(text.contains("completed with errors"));
+        assert!(load_report(&manager, "../meta.json").await.unwrap().is_none());
+    }
+}
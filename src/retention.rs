@@ -0,0 +1,300 @@
+use crate::config::{Config, RetentionConfig};
+use crate::cycle_date::CycleDate;
+use crate::journal::JournalManager;
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// Processing reports, in the journal directory
+const REPORTS_DIR: &str = "reports";
+
+/// Kinds of machine-generated artifacts with a retention period
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    UnansweredPrompts,
+    Summary,
+    Provenance,
+    Report,
+}
+
+impl ArtifactKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ArtifactKind::UnansweredPrompts => "Unanswered prompts",
+            ArtifactKind::Summary => "Summary",
+            ArtifactKind::Provenance => "Provenance",
+            ArtifactKind::Report => "Processing report",
+        }
+    }
+}
+
+/// How an expired artifact is cleaned up
+#[derive(Debug, Clone, PartialEq)]
+enum Cleanup {
+    /// Delete the day's prompt files and their metadata
+    RemovePrompts { cycle_date: CycleDate, files: Vec<PathBuf> },
+    RemoveFile(PathBuf),
+    /// Clear the model and steering recorded for a day's prompts and summary
+    StripProvenance(CycleDate),
+}
+
+/// An artifact past its retention period
+#[derive(Debug, Clone, Serialize)]
+pub struct Expired {
+    pub kind: ArtifactKind,
+    /// Relative to the journal directory
+    pub path: String,
+    pub detail: String,
+    #[serde(skip)]
+    cleanup: Cleanup,
+}
+
+/// What a cleanup removed, or would remove in a dry run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RetentionReport {
+    pub dry_run: bool,
+    pub expired: Vec<Expired>,
+    /// Snapshot of the files the cleanup changed, for rolling it back
+    pub snapshot_id: Option<String>,
+}
+
+impl RetentionConfig {
+    /// Whether any artifact has a retention period
+    pub fn is_enabled(&self) -> bool {
+        self.unanswered_prompts_days.is_some() || self.summaries_days.is_some() || self.provenance_days.is_some() || self.reports_days.is_some()
+    }
+}
+
+/// Find artifacts past their retention period, oldest day first, then reports
+pub async fn scan(retention: &RetentionConfig, journal_manager: &JournalManager, today: &CycleDate) -> Result<Vec<Expired>, Box<dyn std::error::Error>> {
+    let today = today.to_real_date();
+    let cutoff = |days: Option<u32>| days.map(|days| today - chrono::Duration::days(days as i64));
+    let prompts_cutoff = cutoff(retention.unanswered_prompts_days);
+    let summaries_cutoff = cutoff(retention.summaries_days);
+    let provenance_cutoff = cutoff(retention.provenance_days);
+    let mut expired = Vec::new();
+
+    let dates = journal_manager.list_dates().await?;
+    for cycle_date in dates {
+        let real_date = cycle_date.to_real_date();
+        let paths = journal_manager.get_file_paths(&cycle_date);
+        let relative = |file: &str| format!("{}/{}", cycle_date, file);
+        let is_older = |cutoff: Option<NaiveDate>| cutoff.is_some_and(|cutoff| real_date < cutoff);
+
+        let mut prompts_removed = false;
+        if is_older(prompts_cutoff) && !paths.entry.exists() {
+            let files: Vec<PathBuf> = [&paths.prompt1, &paths.prompt2, &paths.prompt3].into_iter().filter(|p| p.exists()).cloned().collect();
+            if !files.is_empty() {
+                prompts_removed = true;
+                expired.push(Expired {
+                    kind: ArtifactKind::UnansweredPrompts,
+                    path: relative("prompt*.txt"),
+                    detail: format!("{} prompts from {} that were never answered", files.len(), real_date.format("%Y-%m-%d")),
+                    cleanup: Cleanup::RemovePrompts { cycle_date, files },
+                });
+            }
+        }
+
+        // Only summaries with a one-line stand-in go, so the nightly run doesn't rewrite them
+        if is_older(summaries_cutoff) && paths.summary.exists() && paths.one_line.exists() {
+            expired.push(Expired {
+                kind: ArtifactKind::Summary,
+                path: relative("summary.txt"),
+                detail: "The one-line summary is kept for long-horizon context".to_string(),
+                cleanup: Cleanup::RemoveFile(paths.summary.clone()),
+            });
+        }
+
+        if is_older(provenance_cutoff) && !prompts_removed {
+            let metadata = journal_manager.load_metadata(&cycle_date).await?;
+            let prompts = metadata.prompts.values().filter(|p| p.model.is_some() || p.steering.is_some() || p.ensemble.is_some()).count();
+            let summary = metadata.summary.as_ref().is_some_and(|s| s.steering.is_some());
+            if prompts > 0 || summary {
+                let mut what = Vec::new();
+                if prompts > 0 {
+                    what.push(format!("{} prompts", prompts));
+                }
+                if summary {
+                    what.push("the summary".to_string());
+                }
+                expired.push(Expired {
+                    kind: ArtifactKind::Provenance,
+                    path: relative("meta.json"),
+                    detail: format!("Models and steering recorded for {}", what.join(" and ")),
+                    cleanup: Cleanup::StripProvenance(cycle_date),
+                });
+            }
+        }
+    }
+
+    if let Some(cutoff) = cutoff(retention.reports_days) {
+        expired.extend(expired_reports(journal_manager.base_path(), cutoff).await?);
+    }
+    Ok(expired)
+}
+
+/// Reports written before `cutoff`, by the date their file names start with
+async fn expired_reports(journal_dir: &Path, cutoff: NaiveDate) -> Result<Vec<Expired>, Box<dyn std::error::Error>> {
+    let dir = journal_dir.join(REPORTS_DIR);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut reports = Vec::new();
+    let mut dir_entries = fs::read_dir(&dir).await?;
+    while let Some(entry) = dir_entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let written = name.get(..10).and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok());
+        if name.ends_with(".md") && written.is_some_and(|written| written < cutoff) {
+            reports.push((name, entry.path()));
+        }
+    }
+    reports.sort();
+
+    Ok(reports
+        .into_iter()
+        .map(|(name, path)| Expired {
+            kind: ArtifactKind::Report,
+            path: format!("{}/{}", REPORTS_DIR, name),
+            detail: "Processing report".to_string(),
+            cleanup: Cleanup::RemoveFile(path),
+        })
+        .collect())
+}
+
+/// Remove everything past its retention period, snapshotting the files first. A dry run only
+/// reports what would go.
+pub async fn enforce(config: &Config, journal_manager: &JournalManager, today: &CycleDate, dry_run: bool) -> Result<RetentionReport, Box<dyn std::error::Error>> {
+    let expired = scan(&config.retention, journal_manager, today).await?;
+    let mut report = RetentionReport { dry_run, expired, snapshot_id: None };
+    if dry_run || report.expired.is_empty() {
+        return Ok(report);
+    }
+
+    let touched: Vec<PathBuf> = report
+        .expired
+        .iter()
+        .flat_map(|expired| match &expired.cleanup {
+            Cleanup::RemovePrompts { cycle_date, files } => {
+                let mut paths = files.clone();
+                paths.push(journal_manager.get_file_paths(cycle_date).metadata);
+                paths
+            }
+            Cleanup::RemoveFile(path) => vec![path.clone()],
+            Cleanup::StripProvenance(cycle_date) => vec![journal_manager.get_file_paths(cycle_date).metadata],
+        })
+        .filter(|path| path.exists())
+        .collect();
+    let operation = format!("Retention cleanup of {}", journal_manager.base_path().display());
+    report.snapshot_id = Some(crate::snapshot::before(config, operation, touched).await?);
+
+    for expired in &report.expired {
+        apply(journal_manager, &expired.cleanup).await?;
+    }
+    journal_manager.rebuild_index().await?;
+    journal_manager.record_change(format!("Remove {} expired generated artifacts", report.expired.len())).await;
+    tracing::info!("Retention cleanup removed {} expired artifacts", report.expired.len());
+    Ok(report)
+}
+
+async fn apply(journal_manager: &JournalManager, cleanup: &Cleanup) -> Result<(), Box<dyn std::error::Error>> {
+    match cleanup {
+        Cleanup::RemovePrompts { cycle_date, files } => {
+            for file in files {
+                fs::remove_file(file).await?;
+            }
+            let mut metadata = journal_manager.load_metadata(cycle_date).await?;
+            metadata.prompts.clear();
+            journal_manager.save_metadata(cycle_date, &metadata).await?;
+        }
+        Cleanup::RemoveFile(path) => fs::remove_file(path).await?,
+        Cleanup::StripProvenance(cycle_date) => {
+            let mut metadata = journal_manager.load_metadata(cycle_date).await?;
+            for prompt in metadata.prompts.values_mut() {
+                prompt.model = None;
+                prompt.steering = None;
+                prompt.ensemble = None;
+            }
+            if let Some(summary) = metadata.summary.as_mut() {
+                summary.steering = None;
+            }
+            journal_manager.save_metadata(cycle_date, &metadata).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::{JournalEntry, JournalPrompt, JournalSummary, PromptProvenance, PromptType};
+    use chrono::Local;
+
+    #[tokio::test]
+    async fn test_scan_and_enforce() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let journal_dir = temp_dir.path().join("journal");
+        let journal_manager = JournalManager::new(&journal_dir);
+        let date = |day: u32| CycleDate::from_real_date(NaiveDate::from_ymd_opt(2025, 1, day).unwrap());
+        let today = CycleDate::from_real_date(NaiveDate::from_ymd_opt(2025, 6, 1).unwrap());
+
+        // An unanswered day, an answered day with a compacted summary, and a recent unanswered day
+        for cycle_date in [date(1), date(2)] {
+            let prompt = JournalPrompt {
+                cycle_date,
+                prompt: "What changed?".to_string(),
+                prompt_number: 1,
+                generated_at: Local::now(),
+                prompt_type: PromptType::Daily,
+            };
+            let provenance = PromptProvenance { model: Some("gemma".to_string()), ..PromptProvenance::default() };
+            journal_manager.save_prompt_with_provenance(&prompt, &provenance).await.unwrap();
+        }
+        journal_manager.save_entry(&JournalEntry {
+            cycle_date: date(2),
+            content: "Plenty.".to_string(),
+            created_at: Local::now(),
+            modified_at: Local::now(),
+        }).await.unwrap();
+        journal_manager.save_summary(&JournalSummary { cycle_date: date(2), summary: "Plenty changed.".to_string(), generated_at: Local::now() }).await.unwrap();
+        journal_manager.save_one_line(&date(2), "Plenty changed.").await.unwrap();
+        let reports = journal_dir.join(REPORTS_DIR);
+        std::fs::create_dir_all(&reports).unwrap();
+        std::fs::write(reports.join("2025-01-02-030000-nightly.md"), "# Report").unwrap();
+        std::fs::write(reports.join("2025-05-30-030000-nightly.md"), "# Report").unwrap();
+
+        let mut config = Config::default();
+        assert!(scan(&config.retention, &journal_manager, &today).await.unwrap().is_empty());
+        config.retention = RetentionConfig {
+            unanswered_prompts_days: Some(90),
+            summaries_days: Some(90),
+            provenance_days: Some(90),
+            reports_days: Some(90),
+            dry_run: false,
+        };
+        config.backup.destination = temp_dir.path().join("backups").to_string_lossy().to_string();
+
+        let dry_run = enforce(&config, &journal_manager, &today, true).await.unwrap();
+        let found: Vec<(ArtifactKind, String)> = dry_run.expired.iter().map(|e| (e.kind, e.path.clone())).collect();
+        assert_eq!(found, vec![
+            (ArtifactKind::UnansweredPrompts, format!("{}/prompt*.txt", date(1))),
+            (ArtifactKind::Summary, format!("{}/summary.txt", date(2))),
+            (ArtifactKind::Provenance, format!("{}/meta.json", date(2))),
+            (ArtifactKind::Report, "reports/2025-01-02-030000-nightly.md".to_string()),
+        ]);
+        assert!(dry_run.snapshot_id.is_none());
+        assert!(journal_manager.get_file_paths(&date(1)).prompt1.exists());
+
+        let report = enforce(&config, &journal_manager, &today, false).await.unwrap();
+        assert_eq!(report.expired.len(), 4);
+        assert!(report.snapshot_id.is_some());
+        assert!(journal_manager.load_all_prompts(&date(1)).await.is_empty());
+        assert!(journal_manager.load_summary(&date(2)).await.unwrap().is_none());
+        assert!(journal_manager.find_entries_needing_summaries().await.unwrap().is_empty());
+        assert!(journal_manager.load_metadata(&date(2)).await.unwrap().prompts[&1].model.is_none());
+        assert!(reports.join("2025-05-30-030000-nightly.md").exists());
+        assert!(scan(&config.retention, &journal_manager, &today).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,608 @@
+//! The jobs that run on the clock rather than on request: each notebook's nightly processing,
+//! backups, announcing the day rollover, and retrying work that failed. This is the one place that says what nightly
+//! processing is and in what order its steps run; `PromptGenerator` does the steps.
+//!
+//! Work that keeps the model busy for a while (nightly processing, the startup catch-up,
+//! retries, and prompts generated on demand) takes the model lock first, so they run one
+//! after another instead of summarizing the same entries at once. Summaries and prompts that
+//! fail go on the notebook's work queue to be retried with backoff.
+//!
+//! The admin page can also run nightly processing, or just its summaries or prompts, on
+//! demand as a background job.
+
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+    Router,
+};
+use chrono::{Local, NaiveTime};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Duration};
+
+use crate::auth::{AuthContext, Scope};
+use crate::config::Config;
+use crate::cycle_date::CycleDate;
+use crate::error::AppError;
+use crate::journal::JournalManager;
+use crate::notebook::{ActiveNotebook, Notebook, Notebooks};
+use crate::prompt_generator::PromptGenerator;
+use crate::prompt_jobs::ModelTask;
+use crate::report::ProcessingReport;
+use crate::work_queue::Task;
+use crate::AppState;
+
+/// How often the work queue is checked for retries that are due
+const RETRY_POLL_SECONDS: u64 = 60;
+
+/// A job the scheduler runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Job {
+    /// Expire stale prompts, enforce retention, catch up summaries, write the day's prompts,
+    /// and compile the year's review and the insights once they're due, for every notebook
+    Processing,
+    /// Archive the journal, tokens, and config
+    Backup,
+    /// Tell connected devices the journal day has changed
+    Rollover,
+    /// Try failed summaries and prompts again once their backoff is up
+    Retries,
+}
+
+impl Job {
+    pub const ALL: [Job; 4] = [Job::Processing, Job::Backup, Job::Rollover, Job::Retries];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Job::Processing => "processing",
+            Job::Backup => "backup",
+            Job::Rollover => "rollover",
+            Job::Retries => "retries",
+        }
+    }
+
+    /// Whether the configuration asks for this job at all
+    pub fn enabled(self, config: &Config) -> bool {
+        match self {
+            Job::Backup => config.backup.enabled,
+            Job::Processing | Job::Rollover | Job::Retries => true,
+        }
+    }
+}
+
+/// Work the admin page can start by hand instead of waiting for the nightly run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManualRun {
+    /// Everything the nightly run does
+    Processing,
+    /// Summaries and status files for entries missing them
+    Summaries,
+    /// Today's missing prompts
+    Prompts,
+}
+
+impl FromStr for ManualRun {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "processing" => Ok(ManualRun::Processing),
+            "summaries" => Ok(ManualRun::Summaries),
+            "prompts" => Ok(ManualRun::Prompts),
+            other => Err(format!("Unknown run '{}'; use processing, summaries, or prompts", other)),
+        }
+    }
+}
+
+impl ManualRun {
+    pub fn task(self) -> ModelTask {
+        match self {
+            ManualRun::Processing => ModelTask::Processing,
+            ManualRun::Summaries => ModelTask::Summaries,
+            ManualRun::Prompts => ModelTask::Prompts,
+        }
+    }
+}
+
+static MODEL: OnceLock<tokio::sync::Mutex<()>> = OnceLock::new();
+
+/// Wait for a turn with the model. `work` names the waiter in the log.
+pub async fn lock_model(work: &str) -> tokio::sync::MutexGuard<'static, ()> {
+    let model = MODEL.get_or_init(|| tokio::sync::Mutex::new(()));
+    match model.try_lock() {
+        Ok(guard) => guard,
+        Err(_) => {
+            tracing::info!("{} is waiting for the model", work);
+            model.lock().await
+        }
+    }
+}
+
+/// How long until `time` ("06:00") next comes around on the configured timezone's clock
+pub(crate) fn until(time: &str) -> Result<Duration, String> {
+    let target_time = NaiveTime::parse_from_str(time, "%H:%M")
+        .map_err(|e| format!("Invalid time format: {}", e))?;
+    crate::cycle_date::clock().until(target_time)
+}
+
+/// Runs every enabled job on its schedule
+pub struct Scheduler {
+    notebooks: Arc<Notebooks>,
+    config: Arc<Config>,
+    /// Each started job's loop, which runs until the process exits unless it panics
+    loops: Mutex<Vec<(Job, JoinHandle<()>)>>,
+}
+
+impl Scheduler {
+    /// Start every job the configuration enables. Nightly processing first catches up on
+    /// anything missed while the server was down.
+    pub fn start(notebooks: Arc<Notebooks>, config: Arc<Config>) -> Arc<Self> {
+        let scheduler = Self::new(notebooks, config);
+        tracing::info!("Nightly processing scheduled for {}{}",
+            scheduler.config.journal.prompt_generation_time,
+            if scheduler.config.journal.adaptive_prompt_time { " (adapting to when you write)" } else { "" });
+        if scheduler.config.backup.enabled {
+            tracing::info!("Scheduled backups enabled at {} into {}", scheduler.config.backup.time, scheduler.config.backup.destination);
+        }
+        for job in Job::ALL {
+            if job.enabled(&scheduler.config) {
+                scheduler.spawn(job);
+            }
+        }
+        scheduler
+    }
+
+    pub(crate) fn new(notebooks: Arc<Notebooks>, config: Arc<Config>) -> Arc<Self> {
+        Arc::new(Self { notebooks, config, loops: Mutex::new(Vec::new()) })
+    }
+
+    /// Whether a job's loop was started and hasn't ended
+    pub fn is_running(&self, job: Job) -> bool {
+        self.loops.lock().unwrap().iter().any(|(started, handle)| *started == job && !handle.is_finished())
+    }
+
+    fn spawn(self: &Arc<Self>, job: Job) {
+        let scheduler = Arc::clone(self);
+        let handle = tokio::spawn(async move {
+            if job == Job::Processing {
+                scheduler.catch_up().await;
+            }
+            loop {
+                match scheduler.until_next(job).await {
+                    Ok(wait) => {
+                        if matches!(job, Job::Processing | Job::Backup) {
+                            tracing::info!("Next {} in {:.1} hours", job.name(), wait.as_secs_f64() / 3600.0);
+                        }
+                        sleep(wait).await;
+                        scheduler.run(job).await;
+                        // Avoid re-triggering within the same minute
+                        sleep(Duration::from_secs(60)).await;
+                    }
+                    Err(e) => {
+                        tracing::error!("Can't schedule the next {}: {}; trying again in an hour", job.name(), e);
+                        sleep(Duration::from_secs(3600)).await;
+                    }
+                }
+            }
+        });
+        self.track(job, handle);
+    }
+
+    pub(crate) fn track(&self, job: Job, handle: JoinHandle<()>) {
+        self.loops.lock().unwrap().push((job, handle));
+    }
+
+    /// How long until a job next runs
+    async fn until_next(&self, job: Job) -> Result<Duration, String> {
+        match job {
+            // Follow the default notebook's writing habits when adaptive timing is on
+            Job::Processing => {
+                let time = match self.notebooks.iter().next() {
+                    Some(notebook) => crate::writing_times::prompt_time(&notebook.journal_manager, &self.config.journal).await,
+                    None => self.config.journal.prompt_generation_time.clone(),
+                };
+                if time != self.config.journal.prompt_generation_time {
+                    tracing::info!("Nightly processing adapted to {} from recent writing times", time);
+                }
+                until(&time)
+            }
+            Job::Backup => until(&self.config.backup.time),
+            // A moment past, so the clock already reads the new day
+            Job::Rollover => {
+                let clock = crate::cycle_date::clock();
+                clock.until(clock.day_ends_at).map(|wait| wait + Duration::from_secs(1))
+            }
+            Job::Retries => Ok(Duration::from_secs(RETRY_POLL_SECONDS)),
+        }
+    }
+
+    async fn run(&self, job: Job) {
+        match job {
+            Job::Processing => {
+                for notebook in self.notebooks.iter() {
+                    let Some(generator) = &notebook.prompt_generator else { continue };
+                    let _model = lock_model("Nightly processing").await;
+                    if let Err(e) = self.process(notebook, generator, "nightly").await {
+                        tracing::error!("Nightly processing failed for {}: {}", notebook.name, e);
+                    }
+                }
+            }
+            Job::Backup => {
+                if let Err(e) = crate::backup::run_backup(&self.config).await {
+                    tracing::error!("Scheduled backup failed: {}", e);
+                }
+            }
+            Job::Rollover => {
+                let today = CycleDate::today();
+                tracing::info!("New journal day {}", today);
+                for notebook in self.notebooks.iter() {
+                    notebook.journal_manager.announce_day_started(today);
+                }
+            }
+            Job::Retries => {
+                for notebook in self.notebooks.iter() {
+                    let Some(generator) = &notebook.prompt_generator else { continue };
+                    for task in generator.queue().due().await {
+                        let _model = lock_model("Retry").await;
+                        tracing::info!("Retrying {} for {}", task.describe(), notebook.name);
+                        match self.retry(notebook, generator, task).await {
+                            Ok(()) => generator.queue().succeeded(task).await,
+                            Err(e) => {
+                                tracing::warn!("Retry of {} failed: {}", task.describe(), e);
+                                generator.queue().failed(task, &e).await;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// One more attempt at a queued task. Work that's no longer needed counts as done: a
+    /// summary that has since been written, an entry that was deleted or is waiting for its
+    /// transcription to be reviewed, or prompts for a day that's over.
+    async fn retry(&self, notebook: &Notebook, generator: &PromptGenerator, task: Task) -> Result<(), String> {
+        let journal_manager = &notebook.journal_manager;
+        match task {
+            Task::Summary { cycle_date } => {
+                if journal_manager.load_summary(&cycle_date).await.map_err(|e| e.to_string())?.is_some()
+                    || journal_manager.load_entry(&cycle_date).await.map_err(|e| e.to_string())?.is_none()
+                {
+                    return Ok(());
+                }
+                let mut report = ProcessingReport::new(cycle_date, "retry");
+                let result = generator.summarize_entry(&cycle_date, &mut report).await;
+                if let Err(e) = &result {
+                    report.error(e.clone());
+                }
+                save_report(journal_manager, &report).await;
+                result
+            }
+            Task::Prompts { cycle_date } => {
+                if cycle_date != CycleDate::today() {
+                    return Ok(());
+                }
+                let mut report = ProcessingReport::new(cycle_date, "retry");
+                let result = generator.generate_daily_prompts(&cycle_date, &mut report).await;
+                if result.is_ok() && !report.no_prompt_needed {
+                    notify_prompts_ready(journal_manager, &self.config, &cycle_date).await;
+                }
+                if let Err(e) = &result {
+                    report.error(format!("Prompt generation failed: {}", e));
+                }
+                save_report(journal_manager, &report).await;
+                result
+            }
+        }
+    }
+
+    /// After a restart: summarize entries written since the last run and, if today's
+    /// prompts are due but missing, write them now
+    async fn catch_up(&self) {
+        for notebook in self.notebooks.iter() {
+            let Some(generator) = &notebook.prompt_generator else { continue };
+            let _model = lock_model("Startup catch-up").await;
+            if let Err(e) = self.catch_up_notebook(notebook, generator).await {
+                tracing::error!("Startup catch-up failed for {}: {}", notebook.name, e);
+            }
+        }
+    }
+
+    async fn catch_up_notebook(&self, notebook: &Notebook, generator: &PromptGenerator) -> Result<(), String> {
+        let journal_manager = &notebook.journal_manager;
+        let today = CycleDate::today();
+        let mut report = ProcessingReport::new(today, "startup");
+
+        tracing::info!("Startup check: Looking for entries that need summaries or status files...");
+        if let Err(e) = generator.catch_up_summaries(&mut report).await {
+            tracing::warn!("Failed to catch up summaries: {}", e);
+            report.error(format!("Summary generation failed: {}", e));
+        }
+
+        let prompt_time = crate::writing_times::prompt_time(journal_manager, &self.config.journal).await;
+        let target_time = NaiveTime::parse_from_str(&prompt_time, "%H:%M")
+            .map_err(|e| format!("Invalid time format: {}", e))?;
+        let current_time = crate::cycle_date::clock().now().time();
+        if current_time < target_time {
+            tracing::info!("Startup check: Current time ({}) is before prompt generation time ({}), will wait",
+                current_time.format("%H:%M"), target_time.format("%H:%M"));
+        } else if generator.prompt_count(&today).await > 0 {
+            tracing::info!("Startup check: Today's prompts already exist, no need to generate");
+        } else {
+            tracing::info!("No prompts found for today, generating them now...");
+            if let Err(e) = generator.generate_daily_prompts(&today, &mut report).await {
+                report.error(format!("Prompt generation failed: {}", e));
+                save_report(journal_manager, &report).await;
+                generator.queue().failed(Task::Prompts { cycle_date: today }, &e).await;
+                return Err(e);
+            }
+            if !report.no_prompt_needed {
+                notify_prompts_ready(journal_manager, &self.config, &today).await;
+            }
+        }
+
+        // Only leave a report when the startup catch-up actually did something
+        if !report.is_empty() {
+            save_report(journal_manager, &report).await;
+        }
+        Ok(())
+    }
+
+    /// Run part of the nightly work for one notebook now, returning what it did
+    pub async fn run_now(&self, run: ManualRun, notebook: &Notebook) -> Result<String, String> {
+        let generator = notebook.prompt_generator.as_ref().ok_or("This notebook doesn't generate prompts")?;
+        let _model = lock_model("Manual run").await;
+        let journal_manager = &notebook.journal_manager;
+        let today = CycleDate::today();
+        let report = match run {
+            ManualRun::Processing => self.process(notebook, generator, "manual").await?,
+            ManualRun::Summaries => {
+                let mut report = ProcessingReport::new(today, "manual");
+                generator.catch_up_summaries(&mut report).await?;
+                save_report(journal_manager, &report).await;
+                report
+            }
+            ManualRun::Prompts => {
+                let mut report = ProcessingReport::new(today, "manual");
+                let result = generator.generate_daily_prompts(&today, &mut report).await;
+                if let Err(e) = &result {
+                    report.error(format!("Prompt generation failed: {}", e));
+                }
+                save_report(journal_manager, &report).await;
+                result?;
+                if !report.no_prompt_needed && !report.prompts_generated.is_empty() {
+                    notify_prompts_ready(journal_manager, &self.config, &today).await;
+                }
+                report
+            }
+        };
+        Ok(report.outcome())
+    }
+
+    /// One notebook's nightly processing, in order:
+    /// 1. Expire stale prompts and remove artifacts past their retention period
+    /// 2. Write today's prompts, catching up summaries, one-lines, and embeddings first
+    /// 3. Hand the prompts to the completion hooks and printer
+    /// 4. Compile last year's review once it's due
+    /// 5. Refresh the insights page's analysis once a week
+    async fn process(&self, notebook: &Notebook, generator: &PromptGenerator, trigger: &str) -> Result<ProcessingReport, String> {
+        let config = &self.config;
+        let journal_manager = &notebook.journal_manager;
+        let today = CycleDate::today();
+        let mut report = ProcessingReport::new(today, trigger);
+
+        if let Some(expiry_days) = config.journal.prompt_expiry_days {
+            match journal_manager.expire_stale_prompts(&today, expiry_days).await.map_err(|e| e.to_string()) {
+                Ok(0) => {}
+                Ok(count) => {
+                    tracing::info!("Expired unanswered prompts on {} days", count);
+                    report.expired_prompt_days = count;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to expire stale prompts: {}", e);
+                    report.error(format!("Prompt expiry failed: {}", e));
+                }
+            }
+        }
+
+        if config.retention.is_enabled() {
+            let cleanup = crate::retention::enforce(config, journal_manager, &today, config.retention.dry_run).await.map_err(|e| e.to_string());
+            match cleanup {
+                Ok(retention) if retention.expired.is_empty() => {}
+                Ok(retention) => report.retention = Some(retention),
+                Err(e) => {
+                    tracing::warn!("Retention cleanup failed: {}", e);
+                    report.error(format!("Retention cleanup failed: {}", e));
+                }
+            }
+        }
+
+        let result = generator.generate_daily_prompts(&today, &mut report).await;
+        match &result {
+            Ok(()) if report.no_prompt_needed => {}
+            Ok(()) => notify_prompts_ready(journal_manager, config, &today).await,
+            Err(e) => {
+                report.error(format!("Prompt generation failed: {}", e));
+                generator.queue().failed(Task::Prompts { cycle_date: today }, e).await;
+            }
+        }
+
+        let due_review = crate::year_review::due(crate::cycle_date::calendar(), today.to_real_date())
+            .filter(|year| crate::year_review::find(journal_manager, *year).is_none());
+        if let (true, Some(year)) = (config.year_review.enabled, due_review) {
+            match generator.compile_year_review(year).await.map_err(|e| e.to_string()) {
+                Ok(review) => report.year_review = Some(review.markdown),
+                Err(e) => {
+                    tracing::warn!("Failed to compile the year {:02} review: {}", year, e);
+                    report.error(format!("Year in review failed: {}", e));
+                }
+            }
+        }
+        save_report(journal_manager, &report).await;
+
+        let insights = crate::insights::load(journal_manager.base_path()).await;
+        if crate::insights::is_due(&insights, Local::now()) {
+            if let Err(e) = generator.write_insights().await {
+                tracing::warn!("Failed to write insights for {}: {}", notebook.name, e);
+            }
+        }
+        result.map(|()| report)
+    }
+}
+
+/// Write a processing report to the journal directory; failures are only logged
+async fn save_report(journal_manager: &JournalManager, report: &ProcessingReport) {
+    match report.save(journal_manager).await.map_err(|e| e.to_string()) {
+        Ok(path) => tracing::info!("Processing report written to {}", path.display()),
+        Err(e) => tracing::warn!("Failed to write processing report: {}", e),
+    }
+}
+
+/// Hand the day's prompts to the configured completion hooks (command and printer), if any.
+/// Hook failures are logged but never fail the daily processing run.
+async fn notify_prompts_ready(journal_manager: &JournalManager, config: &Config, cycle_date: &CycleDate) {
+    if config.hooks.prompt_ready_command.is_none() && !config.printer.enabled {
+        return;
+    }
+
+    let prompts = journal_manager.load_all_prompts(cycle_date).await;
+    if let Err(e) = crate::hooks::run_prompt_ready_hook(&config.hooks, cycle_date, &prompts).await {
+        tracing::warn!("Prompt-ready hook failed: {}", e);
+    }
+
+    if config.printer.enabled {
+        let yesterday_summary = match journal_manager.load_summary(&cycle_date.previous_day()).await {
+            Ok(summary) => summary.map(|s| s.summary),
+            Err(e) => {
+                tracing::warn!("Could not load yesterday's summary for printing: {}", e);
+                None
+            }
+        };
+        if let Err(e) = crate::printer::print_morning_prompts(&config.printer, cycle_date, &prompts, yesterday_summary.as_deref()).await {
+            tracing::warn!("Failed to print morning prompts: {}", e);
+        }
+    }
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/admin/processing/:run", post(start_run))
+}
+
+/// Start processing, summaries, or today's prompts for the active notebook in the
+/// background, or find the same run already going (POST /admin/processing/:run). Answers
+/// 202 with the job to poll when JSON is accepted, otherwise returns to the admin page.
+async fn start_run(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    headers: HeaderMap,
+    Path(run): Path<String>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+    let run: ManualRun = match run.parse() {
+        Ok(run) => run,
+        Err(e) => return AppError::not_found(e).into_response(),
+    };
+    if notebook.prompt_generator.is_none() {
+        return AppError::unavailable("The model isn't available").into_response();
+    }
+
+    let job = match app_state.prompt_jobs.running(run.task(), &notebook.name) {
+        Some(job) => job,
+        None => {
+            tracing::info!("{:?} run for {} started by {}", run, notebook.name, auth.label());
+            let scheduler = app_state.scheduler.clone();
+            let active = notebook.clone();
+            app_state.prompt_jobs.spawn(run.task(), &notebook.name, None, async move {
+                scheduler.run_now(run, &active).await.map(Some)
+            })
+        }
+    };
+    if crate::handlers::wants_json(&headers) {
+        crate::handlers::job_accepted(&job)
+    } else {
+        (StatusCode::SEE_OTHER, [(header::LOCATION, crate::handlers::app_url("/admin"))]).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::notebook;
+
+    #[test]
+    fn test_until() {
+        assert!(until("06:00").is_ok());
+        assert!(until("invalid").is_err());
+    }
+
+    #[test]
+    fn test_manual_run() {
+        assert_eq!("summaries".parse::<ManualRun>(), Ok(ManualRun::Summaries));
+        assert_eq!("prompts".parse::<ManualRun>().map(ManualRun::task), Ok(ModelTask::Prompts));
+        assert!("backup".parse::<ManualRun>().is_err());
+    }
+
+    #[test]
+    fn test_jobs_enabled() {
+        let mut config = Config::default();
+        config.backup.enabled = false;
+        let enabled: Vec<&str> = Job::ALL.iter().filter(|job| job.enabled(&config)).map(|job| job.name()).collect();
+        assert_eq!(enabled, vec!["processing", "rollover", "retries"]);
+    }
+
+    #[tokio::test]
+    async fn test_ended_loop_isnt_running() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let scheduler = Scheduler::new(Arc::new(Notebooks::new(vec![notebook(dir.path())])), Arc::new(Config::default()));
+        assert!(!scheduler.is_running(Job::Backup));
+        let ended = tokio::spawn(async {});
+        let running = tokio::spawn(std::future::pending::<()>());
+        scheduler.track(Job::Processing, ended);
+        scheduler.track(Job::Rollover, running);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        assert!(!scheduler.is_running(Job::Processing));
+        assert!(scheduler.is_running(Job::Rollover));
+    }
+
+    #[tokio::test]
+    async fn test_summary_retry_waits_for_review() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let journal = notebook(dir.path());
+        let config = Arc::new(Config::default());
+        let llm_manager = Arc::new(crate::llm_worker::LlmManager::new("test-model".to_string(), "nomic-embed-text".to_string()).unwrap());
+        let generator = PromptGenerator::new(
+            journal.journal_manager.clone(),
+            llm_manager,
+            config.clone(),
+            journal.personalization_config.clone(),
+        );
+
+        let cycle_date = CycleDate::from_real_date(crate::test_support::date(2025, 3, 2));
+        let now = chrono::Local::now();
+        let entry = crate::journal::JournalEntry { cycle_date, content: "Scanned page".to_string(), created_at: now, modified_at: now };
+        journal.journal_manager.save_transcribed_entry(&entry, "page.jpg", b"jpeg", 0.4, 0.8).await.unwrap();
+
+        // Summarizing would need the model, which isn't running here
+        let scheduler = Scheduler::new(Arc::new(Notebooks::new(vec![notebook(dir.path())])), config);
+        assert!(scheduler.retry(&journal, &generator, Task::Summary { cycle_date }).await.is_ok());
+        assert!(journal.journal_manager.load_summary(&cycle_date).await.unwrap().is_none());
+        assert!(!journal.journal_manager.get_file_paths(&cycle_date).status.exists());
+    }
+
+    #[tokio::test]
+    async fn test_model_lock_takes_turns() {
+        let first = lock_model("first").await;
+        let waiting = tokio::time::timeout(Duration::from_millis(50), lock_model("second")).await;
+        assert!(waiting.is_err());
+        drop(first);
+        let second = tokio::time::timeout(Duration::from_millis(50), lock_model("second")).await;
+        assert!(second.is_ok());
+    }
+}
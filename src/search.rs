@@ -0,0 +1,248 @@
+use crate::cycle_date::CycleDate;
+use crate::journal::{JournalManager, PromptType};
+use chrono::NaiveDate;
+
+/// Characters of entry text shown around a match
+const SNIPPET_LENGTH: usize = 200;
+
+/// How `/search` matches entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchMode {
+    /// Every word of the query appears in the entry
+    Keyword,
+    /// Entries closest in meaning, from stored embeddings (see `embeddings`)
+    Semantic,
+}
+
+impl std::str::FromStr for SearchMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "" | "keyword" => Ok(SearchMode::Keyword),
+            "semantic" => Ok(SearchMode::Semantic),
+            _ => Err(format!("Unknown search mode '{}'", s)),
+        }
+    }
+}
+
+/// A search as typed: words and "quoted phrases" an entry has to contain, narrowed by
+/// `tag:name`, `before:date`, `after:date`, and `type:weekly`. Dates are cycle dates or
+/// YYYY-MM-DD, and `before:`/`after:` leave out the day itself.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedQuery {
+    /// Lowercased words and phrases, all of which have to appear
+    pub terms: Vec<String>,
+    /// Tags the entry has to have, without the '#'
+    pub tags: Vec<String>,
+    pub before: Option<NaiveDate>,
+    pub after: Option<NaiveDate>,
+    pub entry_type: Option<PromptType>,
+}
+
+impl ParsedQuery {
+    pub fn parse(query: &str) -> Result<Self, String> {
+        let mut parsed = ParsedQuery::default();
+        for token in tokens(query) {
+            let word = match token {
+                Token::Phrase(phrase) => {
+                    parsed.terms.push(phrase.to_lowercase());
+                    continue;
+                }
+                Token::Word(word) => word,
+            };
+            let Some((key, value)) = word.split_once(':') else {
+                parsed.terms.push(word.to_lowercase());
+                continue;
+            };
+            let date = |value: &str| -> Result<NaiveDate, String> {
+                Ok(crate::handlers::parse_date_param(value)?.to_real_date())
+            };
+            match key.to_ascii_lowercase().as_str() {
+                "tag" | "before" | "after" | "type" if value.is_empty() => return Err(format!("'{}:' needs a value", key)),
+                "tag" => parsed.tags.push(value.trim_start_matches('#').to_lowercase()),
+                "before" => parsed.before = Some(date(value)?),
+                "after" => parsed.after = Some(date(value)?),
+                "type" => parsed.entry_type = Some(value.parse()?),
+                // Anything else is just a word with a colon in it
+                _ => parsed.terms.push(word.to_lowercase()),
+            }
+        }
+        Ok(parsed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == ParsedQuery::default()
+    }
+
+    /// The words and phrases on their own, for searching by meaning
+    pub fn text(&self) -> String {
+        self.terms.join(" ")
+    }
+
+    fn matches_date(&self, cycle_date: &CycleDate) -> bool {
+        let real_date = cycle_date.to_real_date();
+        self.before.is_none_or(|before| real_date < before)
+            && self.after.is_none_or(|after| real_date > after)
+            && self.entry_type.as_ref().is_none_or(|t| *t == PromptType::for_date(cycle_date))
+    }
+
+    /// Whether a day's entry passes the tag, date, and type filters, whatever its words
+    pub fn matches_filters(&self, cycle_date: &CycleDate, content: &str) -> bool {
+        if !self.matches_date(cycle_date) {
+            return false;
+        }
+        let tags = crate::memory_lane::tags(content);
+        self.tags.iter().all(|tag| tags.contains(tag))
+    }
+
+    fn contains_terms(&self, content: &str) -> bool {
+        let lowered = content.to_lowercase();
+        self.terms.iter().all(|term| lowered.contains(term.as_str()))
+    }
+}
+
+enum Token<'a> {
+    Word(&'a str),
+    Phrase(&'a str),
+}
+
+/// Split a query on whitespace, keeping "quoted phrases" (an unclosed quote runs to the end)
+/// together
+fn tokens(query: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = query.trim_start();
+    while !rest.is_empty() {
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let (phrase, after) = quoted.split_once('"').unwrap_or((quoted, ""));
+            let phrase = phrase.trim();
+            if !phrase.is_empty() {
+                tokens.push(Token::Phrase(phrase));
+            }
+            rest = after;
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            tokens.push(Token::Word(&rest[..end]));
+            rest = &rest[end..];
+        }
+        rest = rest.trim_start();
+    }
+    tokens
+}
+
+/// An entry matching a keyword search, with the text around the match
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub cycle_date: CycleDate,
+    pub snippet: String,
+}
+
+/// Entries containing every word and phrase of `query` (case-insensitive) and passing its
+/// filters, newest first
+pub async fn keyword_search(
+    journal_manager: &JournalManager,
+    query: &ParsedQuery,
+    limit: usize,
+) -> Result<Vec<SearchHit>, Box<dyn std::error::Error>> {
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut hits = Vec::new();
+    let dates = journal_manager.list_dates().await?;
+    for cycle_date in dates.into_iter().rev().filter(|d| query.matches_date(d)) {
+        let Some(entry) = journal_manager.load_entry(&cycle_date).await? else {
+            continue;
+        };
+        if query.contains_terms(&entry.content) && query.matches_filters(&cycle_date, &entry.content) {
+            hits.push(SearchHit {
+                cycle_date,
+                snippet: snippet(&entry.content, query.terms.first().map_or("", String::as_str)),
+            });
+            if hits.len() >= limit {
+                break;
+            }
+        }
+    }
+    Ok(hits)
+}
+
+/// A stretch of `content` around the first occurrence of `term`, or its beginning
+pub fn snippet(content: &str, term: &str) -> String {
+    let lowered = content.to_lowercase();
+    // Lowercasing can change byte lengths, so only trust the offset if it's valid in the original
+    let start = lowered
+        .find(term)
+        .filter(|i| content.is_char_boundary(*i))
+        .map(|i| content[..i].char_indices().rev().nth(SNIPPET_LENGTH / 4).map(|(j, _)| j).unwrap_or(0))
+        .unwrap_or(0);
+
+    let text: String = content[start..].chars().take(SNIPPET_LENGTH).collect();
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let prefix = if start > 0 { "…" } else { "" };
+    let suffix = if content[start..].chars().count() > SNIPPET_LENGTH { "…" } else { "" };
+    format!("{}{}{}", prefix, text, suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::JournalEntry;
+    use chrono::Local;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_snippet() {
+        assert_eq!(snippet("Short entry", "missing"), "Short entry");
+
+        let long = format!("{} talked with Sam about moving {}", "filler ".repeat(40), "end ".repeat(60));
+        let snip = snippet(&long, "sam");
+        assert!(snip.starts_with('…') && snip.ends_with('…'));
+        assert!(snip.contains("talked with Sam"));
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let parsed = ParsedQuery::parse(r#"Walk "Long  Talk" tag:#Garden after:2025-03-01 type:weekly note:self "unclosed"#).unwrap();
+        assert_eq!(parsed.terms, ["walk", "long  talk", "note:self", "unclosed"]);
+        assert_eq!(parsed.tags, ["garden"]);
+        assert_eq!(parsed.after, NaiveDate::from_ymd_opt(2025, 3, 1));
+        assert_eq!(parsed.before, None);
+        assert_eq!(parsed.entry_type, Some(PromptType::WeeklyReflection));
+        assert_eq!(parsed.text(), "walk long  talk note:self unclosed");
+
+        assert!(ParsedQuery::parse(r#"  "" "#).unwrap().is_empty());
+        assert!(ParsedQuery::parse("type:fortnightly").is_err());
+        assert!(ParsedQuery::parse("before:yesterday").is_err());
+        assert!(ParsedQuery::parse("tag:").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_keyword_search() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = JournalManager::new(temp_dir.path());
+        for (date, content) in [("01210", "Long talk with Sam about the move"), ("01211", "Quiet day, read a book #reading")] {
+            manager.save_entry(&JournalEntry {
+                cycle_date: CycleDate::from_string(date).unwrap(),
+                content: content.to_string(),
+                created_at: Local::now(),
+                modified_at: Local::now(),
+            }).await.unwrap();
+        }
+
+        let search = |query: &str| ParsedQuery::parse(query).unwrap();
+        let hits = keyword_search(&manager, &search("sam MOVE"), 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].cycle_date.to_string(), "01210");
+        assert!(keyword_search(&manager, &search("sam book"), 10).await.unwrap().is_empty());
+        assert_eq!(keyword_search(&manager, &search("about sam"), 10).await.unwrap().len(), 1);
+        assert!(keyword_search(&manager, &search("\"about sam\""), 10).await.unwrap().is_empty());
+
+        // Filters work on their own, and narrow words
+        let dates = |hits: Vec<SearchHit>| hits.iter().map(|hit| hit.cycle_date.to_string()).collect::<Vec<_>>();
+        assert_eq!(dates(keyword_search(&manager, &search("tag:reading"), 10).await.unwrap()), ["01211"]);
+        assert_eq!(dates(keyword_search(&manager, &search("day after:01210"), 10).await.unwrap()), ["01211"]);
+        assert!(keyword_search(&manager, &search("talk before:01210"), 10).await.unwrap().is_empty());
+        assert_eq!("semantic".parse::<SearchMode>(), Ok(SearchMode::Semantic));
+    }
+}
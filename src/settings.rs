@@ -0,0 +1,396 @@
+//! The admin settings page: the commonly changed parts of config.toml as a form. Values are
+//! checked before anything is written, the file is replaced atomically with its comments
+//! kept, and settings read through `config::live()` apply at once; the rest are flagged
+//! until the server restarts.
+
+use askama::Template;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+    routing::get,
+    Form, Router,
+};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::auth::{AuthContext, Scope};
+use crate::config::Config;
+use crate::error::AppError;
+use crate::AppState;
+
+/// What a setting holds, which decides its input and how it's checked
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Text,
+    /// Empty to leave the setting out
+    OptionalText,
+    /// A file or directory whose parent directory exists
+    Path,
+    /// An existing directory
+    Directory,
+    Port,
+    /// 24-hour "HH:MM"
+    Time,
+    Integer { min: u64, max: u64 },
+    /// Empty for no limit
+    OptionalInteger,
+    Decimal { min: f64, max: f64 },
+}
+
+/// One editable setting, by its dotted key in config.toml
+pub struct Setting {
+    pub key: &'static str,
+    pub label: &'static str,
+    pub help: &'static str,
+    kind: Kind,
+    /// Applies as soon as it's saved, instead of at the next restart
+    pub live: bool,
+    value: fn(&Config) -> String,
+}
+
+static SETTINGS: &[Setting] = &[
+    Setting { key: "server.host", label: "Host", help: "Address to listen on; 0.0.0.0 for every network", kind: Kind::Text, live: false, value: |c| c.server.host.clone() },
+    Setting { key: "server.port", label: "Port", help: "Port to listen on", kind: Kind::Port, live: false, value: |c| c.server.port.to_string() },
+    Setting { key: "server.base_path", label: "Base path", help: "Path a reverse proxy serves the app under, like /journal (empty at the root)", kind: Kind::Text, live: false, value: |c| c.server.base_path.clone() },
+    Setting { key: "server.locale", label: "Locale", help: "Language dates are written in: en-US, en-GB, de, es, fr, it, nl, or pt", kind: Kind::Text, live: false, value: |c| c.server.locale.clone() },
+    Setting { key: "server.static_dir", label: "Static files", help: "Directory styles, scripts, and the background are served from", kind: Kind::Directory, live: false, value: |c| c.server.static_dir.clone() },
+    Setting { key: "server.max_body_kb", label: "Largest request (KB)", help: "Longer entries are saved from the page in parts", kind: Kind::Integer { min: 1, max: 1024 * 1024 }, live: false, value: |c| c.server.max_body_kb.to_string() },
+    Setting { key: "server.requests_per_minute", label: "Requests per minute", help: "Accepted from one address; 0 for no limit", kind: Kind::Integer { min: 0, max: u32::MAX as u64 }, live: false, value: |c| c.server.requests_per_minute.to_string() },
+    Setting { key: "server.expensive_requests_per_minute", label: "Expensive requests per minute", help: "Prompt generation, transcription, search, exports, and logins, from one address or session; 0 for no limit", kind: Kind::Integer { min: 0, max: u32::MAX as u64 }, live: false, value: |c| c.server.expensive_requests_per_minute.to_string() },
+    Setting { key: "journal.journal_directory", label: "Journal directory", help: "Where the default notebook's entries are kept", kind: Kind::Path, live: false, value: |c| c.journal.journal_directory.clone() },
+    Setting { key: "journal.processing_time", label: "Nightly processing", help: "When summaries are written (HH:MM)", kind: Kind::Time, live: false, value: |c| c.journal.processing_time.clone() },
+    Setting { key: "journal.prompt_generation_time", label: "Prompt generation", help: "When the day's prompts are written (HH:MM)", kind: Kind::Time, live: false, value: |c| c.journal.prompt_generation_time.clone() },
+    Setting { key: "journal.timezone", label: "Timezone", help: "IANA name like America/Chicago (empty for the server's own)", kind: Kind::OptionalText, live: false, value: |c| c.journal.timezone.clone().unwrap_or_default() },
+    Setting { key: "journal.day_ends_at", label: "Day ends at", help: "Writing before this time counts toward the previous day (HH:MM)", kind: Kind::Time, live: false, value: |c| c.journal.day_ends_at.clone() },
+    Setting { key: "journal.max_prompts_per_day", label: "Prompts per day", help: "Most prompts generated for one day", kind: Kind::Integer { min: 1, max: u8::MAX as u64 }, live: false, value: |c| c.journal.max_prompts_per_day.to_string() },
+    Setting { key: "journal.prompt_expiry_days", label: "Prompt expiry (days)", help: "Unanswered prompts older than this are hidden (empty for never)", kind: Kind::OptionalInteger, live: false, value: |c| c.journal.prompt_expiry_days.map(|d| d.to_string()).unwrap_or_default() },
+    Setting { key: "journal.max_edit_age_days", label: "Edit window (days)", help: "Older entries are read-only unless an admin overrides the lock (empty for no limit)", kind: Kind::OptionalInteger, live: true, value: |c| c.journal.max_edit_age_days.map(|d| d.to_string()).unwrap_or_default() },
+    Setting { key: "journal.transcription_review_threshold", label: "Transcription review threshold", help: "Voice notes and scans below this confidence (0 to 1) wait for review", kind: Kind::Decimal { min: 0.0, max: 1.0 }, live: true, value: |c| c.journal.transcription_review_threshold.to_string() },
+    Setting { key: "llm.model_path", label: "Model", help: "Ollama model (or model file) prompts are written with", kind: Kind::Text, live: false, value: |c| c.llm.model_path.clone() },
+    Setting { key: "llm.temperature", label: "Temperature", help: "Higher is more varied (0 to 2); used for prompts generated on request", kind: Kind::Decimal { min: 0.0, max: 2.0 }, live: true, value: |c| c.llm.temperature.to_string() },
+];
+
+impl Setting {
+    fn input_type(&self) -> &'static str {
+        match self.kind {
+            Kind::Port | Kind::Integer { .. } | Kind::OptionalInteger | Kind::Decimal { .. } => "number",
+            Kind::Time => "time",
+            Kind::Text | Kind::OptionalText | Kind::Path | Kind::Directory => "text",
+        }
+    }
+
+    /// Check a submitted value, giving what to store (None to leave the key out)
+    fn parse(&self, raw: &str) -> Result<Option<toml_edit::Value>, String> {
+        let raw = raw.trim();
+        let integer = |raw: &str, min: u64, max: u64| -> Result<i64, String> {
+            match raw.parse::<u64>() {
+                Ok(n) if (min..=max).contains(&n) => Ok(n as i64),
+                _ => Err(format!("must be a whole number from {} to {}", min, max)),
+            }
+        };
+        match self.kind {
+            Kind::Text if raw.contains(char::is_control) => Err("can't contain control characters".to_string()),
+            Kind::Text => Ok(Some(raw.into())),
+            Kind::OptionalText | Kind::OptionalInteger if raw.is_empty() => Ok(None),
+            Kind::OptionalText => Ok(Some(raw.into())),
+            Kind::OptionalInteger => Ok(Some(integer(raw, 0, u32::MAX as u64)?.into())),
+            Kind::Path | Kind::Directory if raw.is_empty() || raw.contains(char::is_control) => Err("must be a path".to_string()),
+            Kind::Path => match Path::new(raw).parent() {
+                Some(parent) if !parent.as_os_str().is_empty() && !parent.is_dir() => Err(format!("{} isn't a directory", parent.display())),
+                _ => Ok(Some(raw.into())),
+            },
+            Kind::Directory if !Path::new(raw).is_dir() => Err(format!("{} isn't a directory", raw)),
+            Kind::Directory => Ok(Some(raw.into())),
+            Kind::Port => Ok(Some(integer(raw, 1, u16::MAX as u64)?.into())),
+            Kind::Time => match chrono::NaiveTime::parse_from_str(raw, "%H:%M") {
+                Ok(time) => Ok(Some(time.format("%H:%M").to_string().into())),
+                Err(_) => Err("must be a time like 06:30".to_string()),
+            },
+            Kind::Integer { min, max } => Ok(Some(integer(raw, min, max)?.into())),
+            Kind::Decimal { min, max } => match raw.parse::<f64>() {
+                Ok(n) if n.is_finite() && (min..=max).contains(&n) => Ok(Some(n.into())),
+                _ => Err(format!("must be a number from {} to {}", min, max)),
+            },
+        }
+    }
+}
+
+/// The settings that changed in a save
+#[derive(Debug, Default)]
+pub struct SaveOutcome {
+    pub applied: Vec<&'static str>,
+    pub after_restart: Vec<&'static str>,
+}
+
+/// Why a save was refused: problems with single settings, by key, or with the file as a whole
+#[derive(Debug, Default)]
+pub struct SaveErrors {
+    pub fields: HashMap<&'static str, String>,
+    pub general: Vec<String>,
+}
+
+/// Apply submitted values to the text of config.toml. Only settings whose value differs
+/// from `current` are touched, so defaults that were never written stay unwritten.
+fn edit_config(text: &str, current: &Config, submitted: &HashMap<String, String>) -> Result<(String, Config, SaveOutcome), SaveErrors> {
+    let mut errors = SaveErrors::default();
+    let mut document: toml_edit::DocumentMut = match text.parse() {
+        Ok(document) => document,
+        Err(e) => {
+            errors.general.push(format!("{} can't be read: {}", crate::config::CONFIG_FILE, e));
+            return Err(errors);
+        }
+    };
+
+    let mut outcome = SaveOutcome::default();
+    for setting in SETTINGS {
+        let Some(raw) = submitted.get(setting.key) else {
+            continue;
+        };
+        if raw.trim() == (setting.value)(current) {
+            continue;
+        }
+        let value = match setting.parse(raw) {
+            Ok(value) => value,
+            Err(e) => {
+                errors.fields.insert(setting.key, format!("{} {}", setting.label, e));
+                continue;
+            }
+        };
+        let (table, key) = setting.key.split_once('.').expect("setting keys name a table");
+        let Some(table) = document.get_mut(table).and_then(|item| item.as_table_like_mut()) else {
+            errors.general.push(format!("{} has no [{}] table", crate::config::CONFIG_FILE, table));
+            continue;
+        };
+        match value {
+            // Keep the spacing and any comment after the old value
+            Some(mut value) => match table.get_mut(key).and_then(|item| item.as_value_mut()) {
+                Some(old) => {
+                    *value.decor_mut() = old.decor().clone();
+                    *old = value;
+                }
+                None => {
+                    table.insert(key, toml_edit::Item::Value(value));
+                }
+            },
+            None => {
+                table.remove(key);
+            }
+        }
+        if setting.live {
+            outcome.applied.push(setting.label);
+        } else {
+            outcome.after_restart.push(setting.label);
+        }
+    }
+    if !errors.fields.is_empty() || !errors.general.is_empty() {
+        return Err(errors);
+    }
+
+    let text = document.to_string();
+    let config: Config = match toml::from_str(&text) {
+        Ok(config) => config,
+        Err(e) => {
+            errors.general.push(format!("The settings wouldn't load: {}", e));
+            return Err(errors);
+        }
+    };
+    if let Err(e) = check(&config) {
+        errors.general.push(e);
+        return Err(errors);
+    }
+    Ok((text, config, outcome))
+}
+
+/// Checks startup would otherwise only warn about
+fn check(config: &Config) -> Result<(), String> {
+    config.server.base_path()?;
+    config.server.cookie_scope()?;
+    crate::api::cors(&config.server.cors)?;
+    config.server.locale.parse::<crate::locale::Locale>()?;
+    config.journal.day_clock()?;
+    Ok(())
+}
+
+/// Replace the file in one step, so a crash never leaves it half written
+pub(crate) async fn write_atomically(path: &Path, text: &str) -> std::io::Result<()> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    tokio::fs::write(&temporary, text).await?;
+    tokio::fs::rename(&temporary, path).await
+}
+
+/// One row of the settings form
+pub struct SettingRow {
+    pub key: &'static str,
+    pub label: &'static str,
+    pub help: &'static str,
+    pub input_type: &'static str,
+    pub step: &'static str,
+    pub value: String,
+    pub live: bool,
+    /// Saved, but the server is still running with the old value
+    pub pending_restart: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "settings.html")]
+pub struct SettingsTemplate {
+    pub rows: Vec<SettingRow>,
+    pub outcome: Option<SaveOutcome>,
+    pub errors: Vec<String>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/admin/settings", get(settings_page).post(save_settings))
+}
+
+fn rows(started: &Config, live: &Config, submitted: Option<&HashMap<String, String>>, errors: &SaveErrors) -> Vec<SettingRow> {
+    SETTINGS.iter().map(|setting| SettingRow {
+        key: setting.key,
+        label: setting.label,
+        help: setting.help,
+        input_type: setting.input_type(),
+        step: if matches!(setting.kind, Kind::Decimal { .. }) { "any" } else { "1" },
+        value: submitted.and_then(|s| s.get(setting.key)).cloned().unwrap_or_else(|| (setting.value)(live)),
+        live: setting.live,
+        pending_restart: !setting.live && (setting.value)(started) != (setting.value)(live),
+        error: errors.fields.get(setting.key).cloned(),
+    }).collect()
+}
+
+fn render(template: SettingsTemplate, status: StatusCode) -> Response {
+    match template.render() {
+        Ok(html) => (status, Html(html)).into_response(),
+        Err(e) => AppError::internal("Error rendering page", e).into_response(),
+    }
+}
+
+/// The settings form (GET /admin/settings)
+async fn settings_page(State(app_state): State<AppState>, auth: AuthContext) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+
+    render(SettingsTemplate {
+        rows: rows(&app_state.config, &crate::config::live(), None, &SaveErrors::default()),
+        outcome: None,
+        errors: Vec::new(),
+    }, StatusCode::OK)
+}
+
+/// Check and save the settings form, applying what can be applied now (POST /admin/settings)
+async fn save_settings(
+    State(app_state): State<AppState>,
+    auth: AuthContext,
+    Form(submitted): Form<HashMap<String, String>>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+
+    let path = Path::new(crate::config::CONFIG_FILE);
+    let live = crate::config::live();
+    let result = match tokio::fs::read_to_string(path).await {
+        Ok(text) => edit_config(&text, &live, &submitted),
+        Err(e) => Err(SaveErrors {
+            general: vec![format!(
+                "{} can't be read ({}); copy config.toml.example to {} to edit settings here",
+                path.display(), e, path.display(),
+            )],
+            ..SaveErrors::default()
+        }),
+    };
+
+    match result {
+        Ok((text, config, outcome)) => {
+            if let Err(e) = write_atomically(path, &text).await {
+                return AppError::internal("Error saving settings", format!("{}: {}", path.display(), e)).into_response();
+            }
+            tracing::info!(
+                "Settings saved by {}: applied {:?}, after a restart {:?}",
+                auth.label(), outcome.applied, outcome.after_restart,
+            );
+            let live = Arc::new(config);
+            crate::config::set_live(live.clone());
+            render(SettingsTemplate {
+                rows: rows(&app_state.config, &live, None, &SaveErrors::default()),
+                outcome: Some(outcome),
+                errors: Vec::new(),
+            }, StatusCode::OK)
+        }
+        Err(errors) => render(SettingsTemplate {
+            rows: rows(&app_state.config, &live, Some(&submitted), &errors),
+            outcome: None,
+            errors: errors.general.clone(),
+        }, StatusCode::BAD_REQUEST),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"# My journal
+[server]
+port = 3000 # the usual
+host = "0.0.0.0"
+
+[files]
+tokens_file = "tokens.json"
+
+[auth]
+session_duration_seconds = 600
+passcode_expiration_seconds = 600
+
+[journal]
+journal_directory = "journal"
+processing_time = "03:00"
+prompt_generation_time = "03:00"
+max_prompts_per_day = 3
+max_edit_age_days = 30
+
+[llm]
+model_path = "gemma"
+context_length = 4096
+temperature = 0.7
+max_tokens = 512
+"#;
+
+    fn form(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_edit_config() {
+        let current: Config = toml::from_str(CONFIG).unwrap();
+
+        let (text, config, outcome) = edit_config(CONFIG, &current, &form(&[
+            ("server.port", "3100"),
+            ("server.host", "0.0.0.0"),
+            ("journal.processing_time", "4:05"),
+            ("journal.max_edit_age_days", ""),
+            ("llm.temperature", "0.9"),
+        ])).unwrap();
+        assert!(text.starts_with("# My journal\n[server]\nport = 3100 # the usual\n"));
+        assert!(text.contains("processing_time = \"04:05\""));
+        assert!(!text.contains("max_edit_age_days"));
+        assert_eq!(config.server.port, 3100);
+        assert_eq!(config.journal.max_edit_age_days, None);
+        assert_eq!(outcome.applied, ["Edit window (days)", "Temperature"]);
+        assert_eq!(outcome.after_restart, ["Port", "Nightly processing"]);
+
+        let errors = edit_config(CONFIG, &current, &form(&[
+            ("server.port", "0"),
+            ("journal.day_ends_at", "25:00"),
+            ("llm.temperature", "high"),
+        ])).unwrap_err();
+        assert_eq!(errors.fields.len(), 3);
+        assert!(errors.fields["server.port"].starts_with("Port must be a whole number"));
+
+        let errors = edit_config(CONFIG, &current, &form(&[("journal.timezone", "Mars/Olympus")])).unwrap_err();
+        assert!(errors.general[0].starts_with("Unknown timezone"));
+    }
+}
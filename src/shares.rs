@@ -0,0 +1,369 @@
+//! Read-only links to a range of days ("last month"), for showing someone entries without
+//! giving them a login. A share is a token in a URL that renders the days as a static page,
+//! the same layout as the printable view. It stops working when it expires, after its view
+//! limit, or when it's revoked from the admin page. Shares are kept in tokens.json with the
+//! sessions and API keys, since the token is as good as a password for those days.
+
+use askama::Template;
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Response},
+    routing::{get, post},
+    Form, Router,
+};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::{AuthContext, Scope};
+use crate::cycle_date::CycleDate;
+use crate::error::AppError;
+use crate::export::ExportedDay;
+use crate::notebook::{ActiveNotebook, Notebook};
+use crate::AppState;
+
+/// Days a share can last, offered in the form
+pub const EXPIRY_DAYS: [i64; 4] = [1, 7, 30, 90];
+
+/// A link to a range of one notebook's entries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Share {
+    pub id: String,
+    pub token: String,
+    pub notebook: String,
+    /// What the admin page calls it, and the shared page's title
+    pub label: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// Views allowed before the link stops working; unlimited when None
+    #[serde(default)]
+    pub max_views: Option<u32>,
+    #[serde(default)]
+    pub views: u32,
+}
+
+/// Whether a share can be viewed, and if not, why
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShareState {
+    Open,
+    Expired,
+    UsedUp,
+    /// Revoked, or never existed
+    Revoked,
+}
+
+impl ShareState {
+    pub fn label(self) -> &'static str {
+        match self {
+            ShareState::Open => "Open",
+            ShareState::Expired => "Expired",
+            ShareState::UsedUp => "View limit reached",
+            ShareState::Revoked => "Revoked",
+        }
+    }
+
+    /// What someone following a closed link is told
+    fn error(self) -> AppError {
+        match self {
+            ShareState::Expired => AppError::from_status(StatusCode::GONE, "This shared link has expired"),
+            ShareState::UsedUp => AppError::from_status(StatusCode::GONE, "This shared link has been viewed as many times as it allows"),
+            ShareState::Open | ShareState::Revoked => AppError::not_found("There's no shared link here; it may have been revoked"),
+        }
+    }
+}
+
+impl Share {
+    pub fn new(id: String, token: String, notebook: String, range: ShareRange, now: DateTime<Utc>) -> Self {
+        Self {
+            id,
+            token,
+            notebook,
+            label: range.label,
+            from: range.from,
+            to: range.to,
+            created_at: now,
+            expires_at: now + Duration::days(range.expires_days),
+            max_views: range.max_views,
+            views: 0,
+        }
+    }
+
+    pub fn state(&self, now: DateTime<Utc>) -> ShareState {
+        if now >= self.expires_at {
+            ShareState::Expired
+        } else if self.max_views.is_some_and(|max| self.views >= max) {
+            ShareState::UsedUp
+        } else {
+            ShareState::Open
+        }
+    }
+
+    /// The shared page, as the browser should request it
+    pub fn url(&self) -> String {
+        crate::handlers::app_url(&format!("/shared/{}", self.token))
+    }
+}
+
+/// A new share as entered on the admin page
+#[derive(Debug, Clone, Deserialize)]
+pub struct ShareInput {
+    #[serde(default)]
+    pub label: String,
+    /// First and last days, YYYY-MM-DD or a day name in the configured calendar
+    pub from: String,
+    pub to: String,
+    pub expires_days: i64,
+    /// Blank for unlimited
+    #[serde(default)]
+    pub max_views: String,
+}
+
+/// A checked new share
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShareRange {
+    pub label: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub expires_days: i64,
+    pub max_views: Option<u32>,
+}
+
+impl ShareInput {
+    /// Check the input; a share needs a range of at most `export::MAX_RANGE_DAYS` days that
+    /// ends after it starts, and an expiry of a year or less
+    pub fn into_range(self) -> Result<ShareRange, String> {
+        let date = |value: &str| crate::handlers::parse_date_param(value.trim()).map(CycleDate::to_real_date);
+        let (from, to) = (date(&self.from)?, date(&self.to)?);
+        let days = (to - from).num_days() + 1;
+        if days < 1 {
+            return Err("The range ends before it starts".to_string());
+        }
+        if days > crate::export::MAX_RANGE_DAYS {
+            return Err(format!("Share at most {} days at a time", crate::export::MAX_RANGE_DAYS));
+        }
+        if !(1..=365).contains(&self.expires_days) {
+            return Err("A share lasts between 1 and 365 days".to_string());
+        }
+        let max_views = match self.max_views.trim() {
+            "" => None,
+            views => match views.parse::<u32>() {
+                Ok(views) if views > 0 => Some(views),
+                _ => return Err(format!("'{}' isn't a number of views", views)),
+            },
+        };
+        let label = match self.label.trim() {
+            "" => format!("{} to {}", from.format("%B %-d"), to.format("%B %-d, %Y")),
+            label => label.to_string(),
+        };
+        Ok(ShareRange { label, from, to, expires_days: self.expires_days, max_views })
+    }
+}
+
+#[derive(Template)]
+#[template(path = "shares.html")]
+pub struct SharesTemplate {
+    pub notebook: String,
+    pub rows: Vec<ShareRow>,
+    pub created: Option<String>,
+    pub error: Option<String>,
+    /// Ranges the form offers to fill in: (label, from, to)
+    pub presets: Vec<(&'static str, NaiveDate, NaiveDate)>,
+}
+
+pub struct ShareRow {
+    pub share: Share,
+    pub state: ShareState,
+}
+
+/// The shared days, laid out like the printable view
+#[derive(Template)]
+#[template(path = "shared.html")]
+pub struct SharedTemplate {
+    pub notebook: String,
+    pub title: String,
+    pub start: CycleDate,
+    pub end: CycleDate,
+    pub days: Vec<ExportedDay>,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct SharesQuery {
+    /// Id of the share just created, to show its link
+    created: Option<String>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/shares", get(shares_page).post(create_share))
+        .route("/admin/shares/:id/revoke", post(revoke_share))
+        .route("/shared/:token", get(shared_page))
+}
+
+/// Last week and last month in the configured calendar
+fn presets() -> Vec<(&'static str, NaiveDate, NaiveDate)> {
+    let today = CycleDate::today();
+    let (this_week, _) = today.week_bounds();
+    let (last_week, last_week_end) = this_week.previous_day().week_bounds();
+    let (this_month, _) = today.month_bounds();
+    let (last_month, last_month_end) = this_month.previous_day().month_bounds();
+    vec![
+        ("Last week", last_week.to_real_date(), last_week_end.to_real_date()),
+        ("Last month", last_month.to_real_date(), last_month_end.to_real_date()),
+    ]
+}
+
+async fn render(app_state: &AppState, notebook: &Notebook, created: Option<String>, error: Option<String>) -> Response {
+    let now = Utc::now();
+    let rows = app_state.auth_manager.list_shares().await
+        .into_iter()
+        .map(|share| ShareRow { state: share.state(now), share })
+        .collect();
+    let status = if error.is_some() { StatusCode::BAD_REQUEST } else { StatusCode::OK };
+    let template = SharesTemplate {
+        notebook: notebook.name.clone(),
+        rows,
+        created,
+        error,
+        presets: presets(),
+    };
+    match template.render() {
+        Ok(html) => (status, Html(html)).into_response(),
+        Err(e) => AppError::internal("Error rendering page", e).into_response(),
+    }
+}
+
+/// The shares, with a form for a new one (GET /admin/shares)
+async fn shares_page(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Query(query): Query<SharesQuery>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+    render(&app_state, &notebook, query.created, None).await
+}
+
+/// Share a range of the active notebook's days (POST /admin/shares)
+async fn create_share(
+    State(app_state): State<AppState>,
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Form(input): Form<ShareInput>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+    let range = match input.into_range() {
+        Ok(range) => range,
+        Err(e) => return render(&app_state, &notebook, None, Some(e)).await,
+    };
+    let share = app_state.auth_manager.create_share(notebook.name.clone(), range).await;
+    app_state.auth_manager.save_sessions_to_file(&app_state.tokens_file_manager).await;
+    tracing::info!("{} shared {} to {} of {}", auth.label(), share.from, share.to, notebook.name);
+    let location = crate::handlers::app_url(&format!("/admin/shares?created={}", share.id));
+    (StatusCode::SEE_OTHER, [(header::LOCATION, location)]).into_response()
+}
+
+/// Stop a share's link working (POST /admin/shares/:id/revoke)
+async fn revoke_share(
+    State(app_state): State<AppState>,
+    auth: AuthContext,
+    Path(id): Path<String>,
+) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+    if !app_state.auth_manager.revoke_share(&id).await {
+        return AppError::not_found("No share with that id").into_response();
+    }
+    app_state.auth_manager.save_sessions_to_file(&app_state.tokens_file_manager).await;
+    tracing::info!("{} revoked share {}", auth.label(), id);
+    (StatusCode::SEE_OTHER, [(header::LOCATION, crate::handlers::app_url("/admin/shares"))]).into_response()
+}
+
+/// The shared days, for anyone with the link (GET /shared/:token). Each view counts toward
+/// the share's limit.
+async fn shared_page(State(app_state): State<AppState>, Path(token): Path<String>) -> Response {
+    let share = match app_state.auth_manager.open_share(&token).await {
+        Ok(share) => share,
+        Err(state) => return state.error().into_response(),
+    };
+    app_state.auth_manager.save_sessions_to_file(&app_state.tokens_file_manager).await;
+    let Some(notebook) = app_state.notebooks.get(&share.notebook) else {
+        return ShareState::Revoked.error().into_response();
+    };
+
+    let (start, end) = (CycleDate::from_real_date(share.from), CycleDate::from_real_date(share.to));
+    let days = match crate::export::collect(&notebook.journal_manager, start, end).await.map_err(|e| e.to_string()) {
+        Ok(days) => days,
+        Err(e) => return AppError::internal("Error collecting entries", e).into_response(),
+    };
+    let template = SharedTemplate {
+        notebook: share.notebook,
+        title: share.label,
+        start,
+        end,
+        days,
+        expires_at: share.expires_at,
+    };
+    match template.render() {
+        Ok(html) => (
+            [
+                (header::CACHE_CONTROL, "no-store"),
+                (header::REFERRER_POLICY, "no-referrer"),
+                (header::HeaderName::from_static("x-robots-tag"), "noindex"),
+            ],
+            Html(html),
+        ).into_response(),
+        Err(e) => AppError::internal("Error rendering page", e).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(from: &str, to: &str, max_views: &str) -> ShareInput {
+        ShareInput { label: String::new(), from: from.to_string(), to: to.to_string(), expires_days: 7, max_views: max_views.to_string() }
+    }
+
+    #[test]
+    fn test_share_input() {
+        let range = input("2026-09-01", "2026-09-30", "").into_range().unwrap();
+        assert_eq!(range.label, "September 1 to September 30, 2026");
+        assert_eq!(range.max_views, None);
+        assert_eq!(input("2026-09-01", "2026-09-01", "3").into_range().unwrap().max_views, Some(3));
+
+        assert_eq!(input("2026-09-30", "2026-09-01", "").into_range().unwrap_err(), "The range ends before it starts");
+        assert!(input("2026-09-01", "2026-09-30", "0").into_range().is_err());
+        assert!(input("2026-09-01", "2026-09-30", "many").into_range().is_err());
+        assert!(input("2020-01-01", "2026-09-30", "").into_range().unwrap_err().starts_with("Share at most"));
+        assert!(ShareInput { expires_days: 0, ..input("2026-09-01", "2026-09-30", "") }.into_range().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_share_views_and_expiry() {
+        let auth_manager = crate::auth::AuthManager::new();
+        let range = input("2026-09-01", "2026-09-30", "2").into_range().unwrap();
+        let share = auth_manager.create_share("personal".to_string(), range).await;
+
+        assert_eq!(auth_manager.open_share(&share.token).await.unwrap().views, 1);
+        assert_eq!(auth_manager.open_share(&share.token).await.unwrap().views, 2);
+        assert_eq!(auth_manager.open_share(&share.token).await.unwrap_err(), ShareState::UsedUp);
+        assert_eq!(auth_manager.open_share("no-such-token").await.unwrap_err(), ShareState::Revoked);
+
+        let restored = crate::auth::AuthManager::new();
+        restored.load_sessions(&auth_manager.get_sessions_data().await).await;
+        assert_eq!(restored.list_shares().await[0].views, 2);
+        assert!(restored.revoke_share(&share.id).await);
+        assert_eq!(restored.open_share(&share.token).await.unwrap_err(), ShareState::Revoked);
+
+        assert_eq!(share.state(share.expires_at), ShareState::Expired);
+        assert_eq!(share.state(share.created_at), ShareState::Open);
+    }
+}
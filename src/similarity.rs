@@ -0,0 +1,79 @@
+use std::collections::HashSet;
+
+/// Words too common to say anything about what a prompt is asking
+const STOPWORDS: &[&str] = &[
+    "a", "about", "an", "and", "any", "are", "as", "at", "be", "been", "by", "can", "could", "did", "do",
+    "does", "for", "from", "has", "have", "how", "i", "if", "in", "into", "is", "it", "its", "me", "might",
+    "moment", "my", "of", "on", "one", "or", "that", "the", "their", "them", "there", "this", "to", "today",
+    "was", "way", "were", "what", "when", "where", "which", "while", "who", "why", "will", "with", "would",
+    "you", "your", "yourself",
+];
+
+/// Lowercased content words of a text, with a trailing plural "s" dropped so
+/// "boundaries"/"boundary" still differ but "goals"/"goal" match
+fn content_words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|word| word.trim_matches('\'').to_lowercase())
+        .filter(|word| word.len() > 2 && !STOPWORDS.contains(&word.as_str()))
+        .map(|word| match word.strip_suffix('s') {
+            Some(stem) if !word.ends_with("ss") && stem.len() > 2 => stem.to_string(),
+            _ => word,
+        })
+        .collect()
+}
+
+/// Similarity of two prompts from 0.0 (nothing in common) to 1.0 (same content words).
+/// Overlap is measured against the smaller prompt, so a short prompt that re-asks a
+/// longer one's question still scores high.
+pub fn prompt_similarity(a: &str, b: &str) -> f64 {
+    let a = content_words(a);
+    let b = content_words(b);
+    let smaller = a.len().min(b.len());
+    if smaller == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / smaller as f64
+}
+
+/// The recent prompt most similar to `candidate`, with its score
+pub fn most_similar<'a>(candidate: &str, recent: &'a [String]) -> Option<(f64, &'a str)> {
+    recent
+        .iter()
+        .map(|prompt| (prompt_similarity(candidate, prompt), prompt.as_str()))
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeated_topic_scores_high() {
+        let score = prompt_similarity(
+            "How are you balancing work and life this week?",
+            "What does work-life balance look like for you right now?",
+        );
+        assert!(score >= 0.5, "score was {}", score);
+    }
+
+    #[test]
+    fn test_different_topics_score_low() {
+        let score = prompt_similarity(
+            "How are you balancing work and life this week?",
+            "Describe a childhood memory that still makes you smile.",
+        );
+        assert!(score < 0.2, "score was {}", score);
+        assert_eq!(prompt_similarity("", "Anything at all"), 0.0);
+    }
+
+    #[test]
+    fn test_most_similar() {
+        let recent = vec![
+            "What are you grateful for?".to_string(),
+            "Which goals matter most to you this month?".to_string(),
+        ];
+        let (score, prompt) = most_similar("Which goal matters most this month?", &recent).unwrap();
+        assert_eq!(prompt, recent[1]);
+        assert!(score > 0.9);
+    }
+}
@@ -0,0 +1,212 @@
+use crate::config::Config;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+type SnapshotResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+const SNAPSHOTS_DIR: &str = "snapshots";
+const MANIFEST_FILE: &str = "manifest.json";
+const FILES_DIR: &str = "files";
+
+/// Copy of the files an operation is about to overwrite, taken automatically so the
+/// operation can be rolled back. Stored under `<backup destination>/snapshots/<id>/`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub id: String,
+    /// What was about to happen, e.g. "Restore llm-journal-20250301-020000.zip"
+    pub operation: String,
+    pub created_at: DateTime<Local>,
+    pub files: Vec<SnapshotFile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rolled_back_at: Option<DateTime<Local>>,
+}
+
+/// A file covered by a snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotFile {
+    pub path: PathBuf,
+    /// Name of the saved copy in the snapshot's files directory; None if the file didn't
+    /// exist yet, in which case rolling back deletes it
+    pub stored: Option<String>,
+}
+
+fn snapshots_dir(config: &Config) -> PathBuf {
+    Path::new(&config.backup.destination).join(SNAPSHOTS_DIR)
+}
+
+/// Snapshot ids are generated as timestamps plus a random suffix; refuse anything else
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+/// Files under `path`: the path itself for a file (existing or not), everything below it for a directory
+fn expand(path: &Path, files: &mut BTreeSet<PathBuf>) -> SnapshotResult<()> {
+    if path.is_dir() {
+        for entry in fs::read_dir(path)? {
+            expand(&entry?.path(), files)?;
+        }
+    } else {
+        files.insert(path.to_path_buf());
+    }
+    Ok(())
+}
+
+/// Snapshot `paths` before `operation` changes them. Directories are copied recursively;
+/// paths that don't exist yet are recorded so rolling back removes what the operation creates.
+pub fn take(config: &Config, operation: &str, paths: &[PathBuf]) -> SnapshotResult<Snapshot> {
+    let snapshot = take_unpruned(config, operation, paths)?;
+    prune(config)?;
+    Ok(snapshot)
+}
+
+fn take_unpruned(config: &Config, operation: &str, paths: &[PathBuf]) -> SnapshotResult<Snapshot> {
+    let mut files = BTreeSet::new();
+    for path in paths {
+        expand(path, &mut files)?;
+    }
+
+    let id = format!("{}-{:04x}", Local::now().format("%Y%m%d-%H%M%S"), rand::random::<u16>());
+    let dir = snapshots_dir(config).join(&id);
+    fs::create_dir_all(dir.join(FILES_DIR))?;
+
+    let mut snapshot = Snapshot {
+        id,
+        operation: operation.to_string(),
+        created_at: Local::now(),
+        files: Vec::new(),
+        rolled_back_at: None,
+    };
+    for (i, path) in files.into_iter().enumerate() {
+        let stored = if path.is_file() {
+            let name = i.to_string();
+            fs::copy(&path, dir.join(FILES_DIR).join(&name))?;
+            Some(name)
+        } else {
+            None
+        };
+        snapshot.files.push(SnapshotFile { path, stored });
+    }
+    save_manifest(config, &snapshot)?;
+    tracing::info!("Snapshot {} taken before: {}", snapshot.id, snapshot.operation);
+    Ok(snapshot)
+}
+
+/// Take a snapshot off the async runtime, returning its id
+pub async fn before(config: &Config, operation: impl Into<String>, paths: Vec<PathBuf>) -> Result<String, String> {
+    let config = config.clone();
+    let operation = operation.into();
+    tokio::task::spawn_blocking(move || take(&config, &operation, &paths).map(|s| s.id).map_err(|e| e.to_string()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn save_manifest(config: &Config, snapshot: &Snapshot) -> SnapshotResult<()> {
+    let path = snapshots_dir(config).join(&snapshot.id).join(MANIFEST_FILE);
+    fs::write(path, serde_json::to_string_pretty(snapshot)?)?;
+    Ok(())
+}
+
+pub fn load(config: &Config, id: &str) -> SnapshotResult<Snapshot> {
+    if !is_valid_id(id) {
+        return Err(format!("Invalid snapshot id '{}'", id).into());
+    }
+    let json = fs::read_to_string(snapshots_dir(config).join(id).join(MANIFEST_FILE))
+        .map_err(|_| format!("No snapshot '{}'", id))?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// All snapshots, newest first
+pub fn list(config: &Config) -> SnapshotResult<Vec<Snapshot>> {
+    let dir = snapshots_dir(config);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut snapshots = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let name = entry?.file_name().to_string_lossy().to_string();
+        match load(config, &name) {
+            Ok(snapshot) => snapshots.push(snapshot),
+            Err(e) => tracing::warn!("Skipping unreadable snapshot {}: {}", name, e),
+        }
+    }
+    snapshots.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+    Ok(snapshots)
+}
+
+/// Delete all but the newest `backup.keep_snapshots` snapshots
+fn prune(config: &Config) -> SnapshotResult<usize> {
+    let snapshots = list(config)?;
+    let mut removed = 0;
+    for snapshot in snapshots.iter().skip(config.backup.keep_snapshots.max(1)) {
+        fs::remove_dir_all(snapshots_dir(config).join(&snapshot.id))?;
+        removed += 1;
+    }
+    if removed > 0 {
+        tracing::debug!("Removed {} old snapshots", removed);
+    }
+    Ok(removed)
+}
+
+/// Put every file covered by a snapshot back the way it was, deleting files the operation
+/// created. The current state is snapshotted first, so a rollback can itself be rolled back.
+/// Returns the id of that new snapshot.
+pub fn rollback(config: &Config, id: &str) -> SnapshotResult<String> {
+    let mut snapshot = load(config, id)?;
+    let paths: Vec<PathBuf> = snapshot.files.iter().map(|f| f.path.clone()).collect();
+    // Pruning now could delete the snapshot being restored from; wait until it's done
+    let undo = take_unpruned(config, &format!("Roll back {}", snapshot.operation), &paths)?;
+
+    let files_dir = snapshots_dir(config).join(&snapshot.id).join(FILES_DIR);
+    for file in &snapshot.files {
+        match &file.stored {
+            Some(name) => {
+                if let Some(parent) = file.path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::copy(files_dir.join(name), &file.path)?;
+            }
+            None if file.path.is_file() => fs::remove_file(&file.path)?,
+            None => {}
+        }
+    }
+
+    snapshot.rolled_back_at = Some(Local::now());
+    save_manifest(config, &snapshot)?;
+    tracing::info!("Rolled back snapshot {} ({})", snapshot.id, snapshot.operation);
+    prune(config)?;
+    Ok(undo.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_snapshot_and_rollback() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.backup.destination = temp_dir.path().join("backups").to_string_lossy().to_string();
+        let day = temp_dir.path().join("journal").join("01213");
+        fs::create_dir_all(&day).unwrap();
+        fs::write(day.join("summary.txt"), "Original summary").unwrap();
+
+        let snapshot = take(&config, "Regenerate summary", &[day.join("summary.txt"), day.join("prompt1.txt")]).unwrap();
+        fs::write(day.join("summary.txt"), "Regenerated summary").unwrap();
+        fs::write(day.join("prompt1.txt"), "New prompt").unwrap();
+
+        let undo = rollback(&config, &snapshot.id).unwrap();
+        assert_eq!(fs::read_to_string(day.join("summary.txt")).unwrap(), "Original summary");
+        assert!(!day.join("prompt1.txt").exists());
+
+        let snapshots = list(&config).unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert!(snapshots.iter().any(|s| s.id == snapshot.id && s.rolled_back_at.is_some()));
+        assert!(snapshots.iter().any(|s| s.id == undo && s.operation == "Roll back Regenerate summary"));
+        assert!(load(&config, "../journal").is_err());
+    }
+}
@@ -0,0 +1,286 @@
+use crate::chapters::ChapterStats;
+use crate::journal::{EntryFilter, EntryListing, JournalManager};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use tokio::fs;
+
+const STATS_FILE: &str = "stats.json";
+
+/// Writing streak and completion statistics, persisted to `stats.json` in the journal directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalStats {
+    /// Consecutive days with an entry, ending today (or yesterday if today isn't written yet)
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    pub total_entries: u32,
+    /// Days from the first entry through today
+    pub days_tracked: u32,
+    /// Fraction of tracked days that have an entry (0.0 - 1.0)
+    pub completion_rate: f32,
+    /// Days with prompts that haven't expired unanswered
+    #[serde(default)]
+    pub prompted_days: u32,
+    /// Prompted days that got an entry
+    #[serde(default)]
+    pub answered_prompt_days: u32,
+    pub computed_at: DateTime<Local>,
+}
+
+impl JournalStats {
+    /// Compute statistics from the real dates that have entries
+    pub fn compute(entry_dates: &[NaiveDate], today: NaiveDate) -> Self {
+        let dates: BTreeSet<NaiveDate> = entry_dates.iter().copied().filter(|d| *d <= today).collect();
+
+        let mut longest_streak = 0;
+        let mut run = 0;
+        let mut previous: Option<NaiveDate> = None;
+        for date in &dates {
+            run = match previous {
+                Some(prev) if prev.succ_opt() == Some(*date) => run + 1,
+                _ => 1,
+            };
+            longest_streak = longest_streak.max(run);
+            previous = Some(*date);
+        }
+
+        // Today's entry may not be written yet; the streak is still alive until tomorrow
+        let mut day = if dates.contains(&today) { Some(today) } else { today.pred_opt() };
+        let mut current_streak = 0;
+        while let Some(d) = day.filter(|d| dates.contains(d)) {
+            current_streak += 1;
+            day = d.pred_opt();
+        }
+
+        let days_tracked = dates
+            .first()
+            .map(|first| (today - *first).num_days() as u32 + 1)
+            .unwrap_or(0);
+        let total_entries = dates.len() as u32;
+        let completion_rate = if days_tracked > 0 {
+            total_entries as f32 / days_tracked as f32
+        } else {
+            0.0
+        };
+
+        Self {
+            current_streak,
+            longest_streak,
+            total_entries,
+            days_tracked,
+            completion_rate,
+            prompted_days: 0,
+            answered_prompt_days: 0,
+            computed_at: Local::now(),
+        }
+    }
+
+    /// Short encouragement line for prompt context, if there's a streak worth mentioning
+    pub fn encouragement(&self) -> Option<String> {
+        match self.current_streak {
+            0 | 1 => None,
+            n if n == self.longest_streak => Some(format!(
+                "The writer is on a {}-day journaling streak, their longest yet.",
+                n
+            )),
+            n => Some(format!("The writer is on a {}-day journaling streak.", n)),
+        }
+    }
+}
+
+/// Number of recent weeks shown in the entries-per-week chart
+const WEEKS_SHOWN: usize = 12;
+
+/// Entry count and word total for one week (Monday through Sunday)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeekActivity {
+    pub week_start: NaiveDate,
+    pub entries: u32,
+    pub words: usize,
+}
+
+/// Aggregate writing statistics for the dashboard
+#[derive(Debug, Clone, Serialize)]
+pub struct WritingStats {
+    /// The most recent weeks, oldest first
+    pub entries_per_week: Vec<WeekActivity>,
+    pub average_word_count: f64,
+    /// Weekday with the most entries, if any were written
+    pub most_active_weekday: Option<String>,
+    pub words_this_year: usize,
+    /// Fraction of entries that have a summary (0.0 - 1.0)
+    pub summary_coverage: f32,
+    pub total_entries: usize,
+    pub total_words: usize,
+}
+
+impl WritingStats {
+    /// Aggregate entry listings as of `today`
+    pub fn from_listings(listings: &[EntryListing], today: NaiveDate) -> Self {
+        let total_entries = listings.len();
+        let total_words: usize = listings.iter().map(|l| l.word_count).sum();
+
+        let this_week = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+        let mut entries_per_week: Vec<WeekActivity> = (0..WEEKS_SHOWN)
+            .rev()
+            .map(|weeks_ago| WeekActivity {
+                week_start: this_week - Duration::weeks(weeks_ago as i64),
+                entries: 0,
+                words: 0,
+            })
+            .collect();
+
+        let mut weekday_counts = [0u32; 7];
+        for listing in listings {
+            weekday_counts[listing.real_date.weekday().num_days_from_monday() as usize] += 1;
+
+            let week_start = listing.real_date - Duration::days(listing.real_date.weekday().num_days_from_monday() as i64);
+            if let Some(week) = entries_per_week.iter_mut().find(|w| w.week_start == week_start) {
+                week.entries += 1;
+                week.words += listing.word_count;
+            }
+        }
+
+        // Ties go to the earlier weekday
+        let most_active_weekday = (0..7)
+            .filter(|&i| weekday_counts[i] > 0)
+            .max_by_key(|&i| (weekday_counts[i], std::cmp::Reverse(i)))
+            .map(|i| weekday_name(Weekday::try_from(i as u8).unwrap_or(Weekday::Mon)).to_string());
+
+        let words_this_year = listings
+            .iter()
+            .filter(|l| l.real_date.year() == today.year())
+            .map(|l| l.word_count)
+            .sum();
+
+        let (average_word_count, summary_coverage) = if total_entries > 0 {
+            (
+                total_words as f64 / total_entries as f64,
+                listings.iter().filter(|l| l.has_summary).count() as f32 / total_entries as f32,
+            )
+        } else {
+            (0.0, 0.0)
+        };
+
+        Self {
+            entries_per_week,
+            average_word_count,
+            most_active_weekday,
+            words_this_year,
+            summary_coverage,
+            total_entries,
+            total_words,
+        }
+    }
+}
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+/// Aggregate writing statistics across every entry
+pub async fn writing_stats(journal_manager: &JournalManager) -> Result<WritingStats, Box<dyn std::error::Error>> {
+    let entries = journal_manager.list_entries(&EntryFilter::default()).await?;
+    Ok(WritingStats::from_listings(&entries, crate::cycle_date::current_day()))
+}
+
+/// Writing totals for each life chapter declared in the journal's chapters.txt
+pub async fn chapter_stats(journal_manager: &JournalManager) -> Result<Vec<ChapterStats>, Box<dyn std::error::Error>> {
+    let chapters = crate::chapters::load(journal_manager.base_path());
+    if chapters.is_empty() {
+        return Ok(Vec::new());
+    }
+    let entries = journal_manager.list_entries(&EntryFilter::default()).await?;
+    Ok(crate::chapters::chapter_stats(&chapters, &entries, crate::cycle_date::current_day()))
+}
+
+/// Recompute statistics from the journal and persist them
+pub async fn refresh_stats(journal_manager: &JournalManager) -> Result<JournalStats, Box<dyn std::error::Error>> {
+    let entries = journal_manager.list_entries(&EntryFilter::default()).await?;
+    let dates: Vec<NaiveDate> = entries.iter().map(|e| e.real_date).collect();
+    let mut stats = JournalStats::compute(&dates, crate::cycle_date::current_day());
+
+    let (prompted, answered) = journal_manager.prompt_answer_counts().await?;
+    stats.prompted_days = prompted as u32;
+    stats.answered_prompt_days = answered as u32;
+
+    let json = serde_json::to_string_pretty(&stats)?;
+    fs::write(journal_manager.base_path().join(STATS_FILE), json).await?;
+
+    Ok(stats)
+}
+
+/// Load the last persisted statistics, if any
+pub async fn load_stats(journal_manager: &JournalManager) -> Option<JournalStats> {
+    let json = fs::read_to_string(journal_manager.base_path().join(STATS_FILE)).await.ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::listing;
+
+    fn date(day: u32) -> NaiveDate {
+        crate::test_support::date(2025, 3, day)
+    }
+
+    #[test]
+    fn test_streaks() {
+        let dates = vec![date(1), date(2), date(3), date(4), date(7), date(8), date(9)];
+        let stats = JournalStats::compute(&dates, date(10));
+
+        // Today (the 10th) isn't written yet, so the streak ending yesterday still counts
+        assert_eq!(stats.current_streak, 3);
+        assert_eq!(stats.longest_streak, 4);
+        assert_eq!(stats.total_entries, 7);
+        assert_eq!(stats.days_tracked, 10);
+        assert!((stats.completion_rate - 0.7).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_broken_streak() {
+        let stats = JournalStats::compute(&[date(1), date(2)], date(5));
+        assert_eq!(stats.current_streak, 0);
+        assert_eq!(stats.longest_streak, 2);
+        assert_eq!(stats.encouragement(), None);
+    }
+
+    #[test]
+    fn test_empty_journal() {
+        let stats = JournalStats::compute(&[], date(5));
+        assert_eq!(stats.current_streak, 0);
+        assert_eq!(stats.days_tracked, 0);
+        assert_eq!(stats.completion_rate, 0.0);
+    }
+
+    #[test]
+    fn test_writing_stats() {
+        // March 3rd 2025 is a Monday
+        let summarized = |real_date, word_count| EntryListing { has_summary: true, ..listing(real_date, word_count) };
+        let listings = vec![
+            summarized(date(3), 100),
+            listing(date(10), 300),
+            summarized(date(12), 200),
+            summarized(date(17), 400),
+        ];
+        let stats = WritingStats::from_listings(&listings, date(19));
+
+        assert_eq!(stats.entries_per_week.len(), WEEKS_SHOWN);
+        let last_weeks: Vec<u32> = stats.entries_per_week.iter().rev().take(3).map(|w| w.entries).collect();
+        assert_eq!(last_weeks, vec![1, 2, 1]);
+        assert_eq!(stats.entries_per_week.last().unwrap().week_start, date(17));
+        assert_eq!(stats.average_word_count, 250.0);
+        assert_eq!(stats.most_active_weekday.as_deref(), Some("Monday"));
+        assert_eq!(stats.words_this_year, 1000);
+        assert!((stats.summary_coverage - 0.75).abs() < f32::EPSILON);
+    }
+}
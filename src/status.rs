@@ -0,0 +1,241 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Structured status file in the journal directory
+const STATUS_FILE: &str = "status.toml";
+/// Free-text status file used before the status had sections
+const LEGACY_STATUS_FILE: &str = "status.txt";
+
+/// Sections of the status as (field name, label shown to the LLM and in the UI)
+pub const SECTIONS: [(&str, &str); 6] = [
+    ("work", "Work"),
+    ("health", "Health"),
+    ("relationships", "Relationships"),
+    ("projects", "Projects"),
+    ("mood_baseline", "Mood baseline"),
+    ("notes", "Other notes"),
+];
+
+/// The user's ongoing circumstances, kept in sections so the LLM can update one part
+/// without rewriting the rest, and so each part can be edited and diffed on its own
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StructuredStatus {
+    pub work: String,
+    pub health: String,
+    pub relationships: String,
+    pub projects: String,
+    pub mood_baseline: String,
+    /// Anything that fits no other section, including a pre-sections status.txt
+    pub notes: String,
+}
+
+/// One section whose text differs between two statuses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionChange {
+    pub label: &'static str,
+    pub before: String,
+    pub after: String,
+}
+
+impl StructuredStatus {
+    /// Load the status from a journal directory: `status.toml`, else a legacy `status.txt`
+    /// as notes. None when neither exists.
+    pub fn load(journal_dir: &Path) -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        let path = journal_dir.join(STATUS_FILE);
+        if path.exists() {
+            return Ok(Some(toml::from_str(&fs::read_to_string(path)?)?));
+        }
+
+        let legacy = journal_dir.join(LEGACY_STATUS_FILE);
+        if legacy.exists() {
+            let text = fs::read_to_string(legacy)?;
+            return Ok((!text.trim().is_empty()).then(|| Self::from_legacy_text(&text)));
+        }
+        Ok(None)
+    }
+
+    pub fn save(&self, journal_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(journal_dir.join(STATUS_FILE), toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// A free-text status from before sections existed
+    pub fn from_legacy_text(text: &str) -> Self {
+        Self {
+            notes: text.trim().to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Parse "Section: text" lines, as written by `render` or returned by the LLM
+    pub fn parse(text: &str) -> Self {
+        let mut status = Self::default();
+        status.apply_update(text);
+        status
+    }
+
+    pub fn section(&self, key: &str) -> Option<&str> {
+        match key {
+            "work" => Some(&self.work),
+            "health" => Some(&self.health),
+            "relationships" => Some(&self.relationships),
+            "projects" => Some(&self.projects),
+            "mood_baseline" => Some(&self.mood_baseline),
+            "notes" => Some(&self.notes),
+            _ => None,
+        }
+    }
+
+    fn section_mut(&mut self, key: &str) -> Option<&mut String> {
+        match key {
+            "work" => Some(&mut self.work),
+            "health" => Some(&mut self.health),
+            "relationships" => Some(&mut self.relationships),
+            "projects" => Some(&mut self.projects),
+            "mood_baseline" => Some(&mut self.mood_baseline),
+            "notes" => Some(&mut self.notes),
+            _ => None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        SECTIONS.iter().all(|(key, _)| self.section(key).is_none_or(|text| text.trim().is_empty()))
+    }
+
+    /// Render the non-empty sections as "Label: text" lines for LLM context
+    pub fn render(&self) -> String {
+        SECTIONS
+            .iter()
+            .filter_map(|(key, label)| {
+                let text = self.section(key)?.split_whitespace().collect::<Vec<_>>().join(" ");
+                (!text.is_empty()).then(|| format!("{}: {}", label, text))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Apply an update in "Section: text" lines, replacing only the sections it names.
+    /// "Section: none" clears a section; text continuing onto following lines belongs to
+    /// the section above it. A reply with no recognizable sections replaces the notes,
+    /// as an older free-form status prompt would produce. Returns the labels that changed.
+    pub fn apply_update(&mut self, update: &str) -> Vec<&'static str> {
+        let mut updates: Vec<(&'static str, String)> = Vec::new();
+        let mut continuing = false;
+        for line in update.lines() {
+            let trimmed = line.trim().trim_start_matches(['-', '*']).trim();
+            if let Some((key, text)) = parse_section_line(trimmed) {
+                updates.push((key, text.to_string()));
+                continuing = true;
+            } else if looks_like_label(trimmed) {
+                // A section we don't keep; skip it rather than folding it into the one above
+                continuing = false;
+            } else if let Some((_, text)) = updates.last_mut().filter(|_| continuing && !trimmed.is_empty()) {
+                text.push(' ');
+                text.push_str(trimmed);
+            }
+        }
+        if updates.is_empty() && !update.trim().is_empty() {
+            updates.push(("notes", update.trim().to_string()));
+        }
+
+        let mut changed = Vec::new();
+        for (key, text) in updates {
+            let text = text.trim();
+            let text = if text.eq_ignore_ascii_case("none") { "" } else { text };
+            if let Some(section) = self.section_mut(key) {
+                if section != text {
+                    *section = text.to_string();
+                    changed.push(label_for(key));
+                }
+            }
+        }
+        changed
+    }
+
+    /// Sections that differ from `previous`, for showing how the status evolved
+    pub fn changes_since(&self, previous: &StructuredStatus) -> Vec<SectionChange> {
+        SECTIONS
+            .iter()
+            .filter(|(key, _)| self.section(key) != previous.section(key))
+            .map(|(key, label)| SectionChange {
+                label,
+                before: previous.section(key).unwrap_or_default().to_string(),
+                after: self.section(key).unwrap_or_default().to_string(),
+            })
+            .collect()
+    }
+}
+
+fn label_for(key: &str) -> &'static str {
+    SECTIONS.iter().find(|(k, _)| *k == key).map(|(_, label)| *label).unwrap_or("Other notes")
+}
+
+/// Whether a line starts with a short "Label:" prefix
+fn looks_like_label(line: &str) -> bool {
+    line.split_once(':').is_some_and(|(name, _)| !name.trim().is_empty() && name.split_whitespace().count() <= 3)
+}
+
+/// Split "Label: text" into a section key and text, accepting labels or field names in any case
+fn parse_section_line(line: &str) -> Option<(&'static str, &str)> {
+    let (name, text) = line.split_once(':')?;
+    let name = name.trim().trim_matches('*').trim().to_ascii_lowercase().replace('_', " ");
+    SECTIONS
+        .iter()
+        .find(|(key, label)| name == label.to_ascii_lowercase() || name == key.replace('_', " "))
+        .map(|(key, _)| (*key, text.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_section_update() {
+        let mut status = StructuredStatus {
+            work: "Interviewing for a new role".to_string(),
+            health: "Training for a half marathon".to_string(),
+            ..Default::default()
+        };
+
+        let changed = status.apply_update("Work: Accepted the job at the library,\n  starting in March.\n**Health**: none\nWeather: sunny");
+        assert_eq!(changed, vec!["Work", "Health"]);
+        assert_eq!(status.work, "Accepted the job at the library, starting in March.");
+        assert_eq!(status.health, "");
+
+        // Unlabeled replies from an older free-form prompt land in the notes
+        status.apply_update("Updated Status: Feeling settled after the move.");
+        assert_eq!(status.notes, "Updated Status: Feeling settled after the move.");
+    }
+
+    #[test]
+    fn test_render_round_trip() {
+        let status = StructuredStatus {
+            projects: "Building a bookshelf".to_string(),
+            mood_baseline: "Calm,\nmostly upbeat".to_string(),
+            ..Default::default()
+        };
+        let rendered = status.render();
+        assert_eq!(rendered, "Projects: Building a bookshelf\nMood baseline: Calm, mostly upbeat");
+        assert_eq!(StructuredStatus::parse(&rendered).projects, status.projects);
+
+        let changes = StructuredStatus::parse(&rendered).changes_since(&StructuredStatus::default());
+        assert_eq!(changes.iter().map(|c| c.label).collect::<Vec<_>>(), vec!["Projects", "Mood baseline"]);
+    }
+
+    #[test]
+    fn test_load_migrates_legacy_status() {
+        let temp_dir = TempDir::new().unwrap();
+        assert_eq!(StructuredStatus::load(temp_dir.path()).unwrap(), None);
+
+        fs::write(temp_dir.path().join(LEGACY_STATUS_FILE), "Busy with the move\n").unwrap();
+        let status = StructuredStatus::load(temp_dir.path()).unwrap().unwrap();
+        assert_eq!(status.notes, "Busy with the move");
+
+        let status = StructuredStatus { work: "Teaching".to_string(), ..status };
+        status.save(temp_dir.path()).unwrap();
+        assert_eq!(StructuredStatus::load(temp_dir.path()).unwrap(), Some(status));
+    }
+}
@@ -0,0 +1,204 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+/// Generation records in the journal directory, one JSON object per line
+const TELEMETRY_FILE: &str = "telemetry.jsonl";
+/// Records kept once the file is compacted
+const KEEP_RECORDS: usize = 500;
+/// Generations the dashboard averages over
+pub const REPORT_WINDOW: usize = 30;
+
+/// Parts of the context sent with a prompt generation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextSection {
+    /// Today's date and upcoming holidays
+    Temporal,
+    Profile,
+    Style,
+    Status,
+    /// Past summaries, entries, and reflections
+    Journal,
+}
+
+/// Estimated tokens spent on each part of a generation request. Ollama doesn't report a
+/// per-part count, so these use the usual four-characters-per-token approximation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContextBudget {
+    pub temporal: usize,
+    pub profile: usize,
+    pub style: usize,
+    pub status: usize,
+    pub journal: usize,
+    /// The prompt template's own instructions, variation, and steering
+    pub instructions: usize,
+}
+
+impl ContextBudget {
+    /// Measure a request built from `sections`; whatever isn't a section counts as instructions
+    pub fn measure(sections: &[(ContextSection, String)], request: &str) -> Self {
+        let mut budget = Self::default();
+        for (section, text) in sections {
+            let tokens = estimate_tokens(text);
+            match section {
+                ContextSection::Temporal => budget.temporal += tokens,
+                ContextSection::Profile => budget.profile += tokens,
+                ContextSection::Style => budget.style += tokens,
+                ContextSection::Status => budget.status += tokens,
+                ContextSection::Journal => budget.journal += tokens,
+            }
+        }
+        let context: usize = budget.parts().iter().map(|(_, tokens)| tokens).sum();
+        budget.instructions = estimate_tokens(request).saturating_sub(context);
+        budget
+    }
+
+    pub fn total(&self) -> usize {
+        self.parts().iter().map(|(_, tokens)| tokens).sum()
+    }
+
+    /// Each part with a label, for display
+    pub fn parts(&self) -> [(&'static str, usize); 6] {
+        [
+            ("Profile", self.profile),
+            ("Status", self.status),
+            ("Temporal", self.temporal),
+            ("Style", self.style),
+            ("Journal context", self.journal),
+            ("Instructions", self.instructions),
+        ]
+    }
+}
+
+/// Rough token count for text sent to the model
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// What one prompt generation spent its context on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationRecord {
+    pub at: DateTime<Local>,
+    pub cycle_date: String,
+    pub prompt_number: u8,
+    #[serde(default)]
+    pub model: Option<String>,
+    pub budget: ContextBudget,
+}
+
+/// Append a generation record, compacting the file to the newest records once it doubles
+pub async fn record(journal_dir: &Path, record: &GenerationRecord) -> Result<(), Box<dyn std::error::Error>> {
+    let path = journal_dir.join(TELEMETRY_FILE);
+    let mut file = fs::OpenOptions::new().create(true).append(true).open(&path).await?;
+    file.write_all(format!("{}\n", serde_json::to_string(record)?).as_bytes()).await?;
+    drop(file);
+
+    let records = load(journal_dir).await;
+    if records.len() > KEEP_RECORDS * 2 {
+        let kept: String = records[records.len() - KEEP_RECORDS..]
+            .iter()
+            .filter_map(|r| serde_json::to_string(r).ok())
+            .map(|line| line + "\n")
+            .collect();
+        fs::write(&path, kept).await?;
+    }
+    Ok(())
+}
+
+/// All recorded generations, oldest first. Unreadable lines are skipped.
+pub async fn load(journal_dir: &Path) -> Vec<GenerationRecord> {
+    let Ok(text) = fs::read_to_string(journal_dir.join(TELEMETRY_FILE)).await else {
+        return Vec::new();
+    };
+    text.lines().filter_map(|line| serde_json::from_str(line).ok()).collect()
+}
+
+/// Average share of the context each part took, over recent generations
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetShare {
+    pub label: &'static str,
+    pub average_tokens: usize,
+    pub percent: u32,
+}
+
+/// The context budget dashboard: averages plus the latest generations
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetReport {
+    pub generations: usize,
+    pub average_total: usize,
+    pub shares: Vec<BudgetShare>,
+    /// Newest first
+    pub recent: Vec<GenerationRecord>,
+}
+
+impl BudgetReport {
+    /// Summarize the last `window` records
+    pub fn from_records(records: &[GenerationRecord], window: usize) -> Self {
+        let recent: Vec<GenerationRecord> = records.iter().rev().take(window).cloned().collect();
+        let count = recent.len().max(1);
+        let sums = recent.iter().fold([0usize; 6], |mut sums, record| {
+            for (sum, (_, tokens)) in sums.iter_mut().zip(record.budget.parts()) {
+                *sum += tokens;
+            }
+            sums
+        });
+        let total: usize = sums.iter().sum();
+
+        let shares = ContextBudget::default()
+            .parts()
+            .iter()
+            .zip(sums)
+            .map(|((label, _), sum)| BudgetShare {
+                label,
+                average_tokens: sum / count,
+                percent: if total == 0 { 0 } else { (sum as f64 * 100.0 / total as f64).round() as u32 },
+            })
+            .collect();
+
+        Self {
+            generations: recent.len(),
+            average_total: total / count,
+            shares,
+            recent,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_measure_record_and_report() {
+        let sections = vec![
+            (ContextSection::Profile, "p".repeat(400)),
+            (ContextSection::Journal, "j".repeat(100)),
+        ];
+        let request = format!("Write a prompt.\n{}{}", sections[0].1, sections[1].1);
+        let budget = ContextBudget::measure(&sections, &request);
+        assert_eq!((budget.profile, budget.journal, budget.status), (100, 25, 0));
+        assert_eq!(budget.total(), estimate_tokens(&request));
+
+        let temp_dir = TempDir::new().unwrap();
+        for prompt_number in 1..=3 {
+            record(temp_dir.path(), &GenerationRecord {
+                at: Local::now(),
+                cycle_date: "01213".to_string(),
+                prompt_number,
+                model: None,
+                budget,
+            }).await.unwrap();
+        }
+
+        let report = BudgetReport::from_records(&load(temp_dir.path()).await, 2);
+        assert_eq!(report.generations, 2);
+        assert_eq!(report.recent[0].prompt_number, 3);
+        assert_eq!(report.average_total, budget.total());
+        let profile = report.shares.iter().find(|s| s.label == "Profile").unwrap();
+        assert_eq!(profile.percent, (100.0 * 100.0 / budget.total() as f64).round() as u32);
+    }
+}
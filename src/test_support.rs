@@ -0,0 +1,38 @@
+//! Fixtures shared by the tests of several modules
+
+use std::path::Path;
+use std::sync::Arc;
+
+use chrono::NaiveDate;
+
+use crate::cycle_date::CycleDate;
+use crate::journal::{EntryListing, JournalManager, PromptType};
+use crate::notebook::Notebook;
+use crate::personalization::{PersonalizationConfig, SharedPersonalization};
+
+pub fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+/// A daily entry of `word_count` words on `real_date`, with no prompts or summary
+pub fn listing(real_date: NaiveDate, word_count: usize) -> EntryListing {
+    EntryListing {
+        cycle_date: CycleDate::from_real_date(real_date),
+        real_date,
+        entry_type: PromptType::Daily,
+        word_count,
+        prompt_count: 0,
+        has_summary: false,
+        modified_at: None,
+    }
+}
+
+/// A notebook named "journal" kept in `dir`, with default personalization and no model
+pub fn notebook(dir: &Path) -> Notebook {
+    Notebook {
+        name: "journal".to_string(),
+        journal_manager: Arc::new(JournalManager::new(dir)),
+        personalization_config: SharedPersonalization::new(PersonalizationConfig::load(dir).unwrap()),
+        prompt_generator: None,
+    }
+}
@@ -0,0 +1,298 @@
+//! Page colors: a palette (Misty Pine, Paper, or Harbor) in light, dark, or auto mode,
+//! chosen per session and served as CSS variables from `/theme.css`, which every page
+//! links before its own styles.
+
+use axum::{
+    extract::State,
+    http::{header, HeaderMap},
+    response::{IntoResponse, Redirect, Response},
+    routing::{get, post},
+    Form, Router,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::AppState;
+
+/// CSS variables every palette sets, in the order of the color lists below
+const VARIABLES: [&str; 18] = [
+    "bg-primary",
+    "bg-secondary",
+    "bg-overlay",
+    "text-primary",
+    "text-secondary",
+    "text-muted",
+    "accent-primary",
+    "accent-hover",
+    "accent-subtle",
+    "accent-warm",
+    "accent-cool",
+    "accent-mint",
+    "accent-coral",
+    "input-bg",
+    "input-border",
+    "input-focus",
+    "shadow-light",
+    "shadow-medium",
+];
+
+type Colors = [&'static str; 18];
+
+const MISTY_PINE_DARK: Colors = [
+    "#2c3e3f", "#3a4d4f", "rgba(44, 62, 63, 0.85)",
+    "#e8f0f0", "#b8d0d0", "#8da8a8",
+    "#7eb3b3", "#9cc5c5", "#5a9999", "#c4a173", "#6b9bb3", "#8bc4aa", "#d4a574",
+    "#3a4d4f", "#5a7a7a", "#7eb3b3",
+    "rgba(0, 0, 0, 0.1)", "rgba(0, 0, 0, 0.2)",
+];
+
+const MISTY_PINE_LIGHT: Colors = [
+    "#e9f0ef", "#f7fafa", "rgba(233, 240, 239, 0.88)",
+    "#1f2e2f", "#3d5456", "#60797a",
+    "#3f7f7f", "#336b6b", "#7eb3b3", "#9a7440", "#3f6f87", "#3f8a68", "#a8703f",
+    "#ffffff", "#a9c2c2", "#3f7f7f",
+    "rgba(0, 0, 0, 0.06)", "rgba(0, 0, 0, 0.12)",
+];
+
+const PAPER_DARK: Colors = [
+    "#23201c", "#2e2a25", "rgba(35, 32, 28, 0.88)",
+    "#eee6da", "#cfc3b2", "#9c8f7e",
+    "#d9a66b", "#e6bb86", "#8a6a45", "#e0b070", "#8fa9c4", "#9cc2a3", "#e09a7a",
+    "#2e2a25", "#5a5046", "#d9a66b",
+    "rgba(0, 0, 0, 0.15)", "rgba(0, 0, 0, 0.3)",
+];
+
+const PAPER_LIGHT: Colors = [
+    "#f5f1e8", "#fffdf8", "rgba(245, 241, 232, 0.9)",
+    "#2b2621", "#5a5148", "#857a6e",
+    "#8a5a2b", "#6f4620", "#c8b49a", "#b07d3a", "#4f6d8a", "#5d8a6a", "#b8684a",
+    "#fffdf8", "#d6cbb8", "#8a5a2b",
+    "rgba(0, 0, 0, 0.05)", "rgba(0, 0, 0, 0.1)",
+];
+
+const HARBOR_DARK: Colors = [
+    "#1c2533", "#263246", "rgba(28, 37, 51, 0.86)",
+    "#e6edf7", "#b9c7da", "#8797ad",
+    "#7fb2e5", "#9cc5ee", "#4f79a3", "#e0b36c", "#7fb2e5", "#86c9b0", "#e39a86",
+    "#263246", "#4a5d78", "#7fb2e5",
+    "rgba(0, 0, 0, 0.15)", "rgba(0, 0, 0, 0.3)",
+];
+
+const HARBOR_LIGHT: Colors = [
+    "#eef3f9", "#ffffff", "rgba(238, 243, 249, 0.9)",
+    "#1b2636", "#3e4f66", "#6a7a90",
+    "#2f6fb0", "#245a91", "#9dbbe0", "#a8772a", "#2f6fb0", "#2f8a6a", "#b85c48",
+    "#ffffff", "#b6c6da", "#2f6fb0",
+    "rgba(0, 0, 0, 0.06)", "rgba(0, 0, 0, 0.12)",
+];
+
+/// A bundled set of colors, each with a light and a dark variant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Palette {
+    #[default]
+    MistyPine,
+    Paper,
+    Harbor,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 3] = [Palette::MistyPine, Palette::Paper, Palette::Harbor];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Palette::MistyPine => "Misty Pine",
+            Palette::Paper => "Paper",
+            Palette::Harbor => "Harbor",
+        }
+    }
+
+    fn colors(self, dark: bool) -> &'static Colors {
+        match (self, dark) {
+            (Palette::MistyPine, true) => &MISTY_PINE_DARK,
+            (Palette::MistyPine, false) => &MISTY_PINE_LIGHT,
+            (Palette::Paper, true) => &PAPER_DARK,
+            (Palette::Paper, false) => &PAPER_LIGHT,
+            (Palette::Harbor, true) => &HARBOR_DARK,
+            (Palette::Harbor, false) => &HARBOR_LIGHT,
+        }
+    }
+}
+
+/// Light or dark variant; auto follows the device's setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeMode {
+    Light,
+    /// The journal's original look
+    #[default]
+    Dark,
+    Auto,
+}
+
+impl ThemeMode {
+    pub const ALL: [ThemeMode; 3] = [ThemeMode::Light, ThemeMode::Dark, ThemeMode::Auto];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ThemeMode::Light => "Light",
+            ThemeMode::Dark => "Dark",
+            ThemeMode::Auto => "Auto",
+        }
+    }
+}
+
+/// A session's theme preference
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Theme {
+    #[serde(default)]
+    pub palette: Palette,
+    #[serde(default)]
+    pub mode: ThemeMode,
+}
+
+impl Theme {
+    /// The stylesheet setting this theme's variables on `:root`
+    pub fn css(&self) -> String {
+        let variables = |dark: bool| -> String {
+            VARIABLES
+                .iter()
+                .zip(self.palette.colors(dark))
+                .map(|(name, value)| format!("    --{}: {};\n", name, value))
+                .collect()
+        };
+        match self.mode {
+            ThemeMode::Light => format!(":root {{\n    color-scheme: light;\n{}}}\n", variables(false)),
+            ThemeMode::Dark => format!(":root {{\n    color-scheme: dark;\n{}}}\n", variables(true)),
+            ThemeMode::Auto => format!(
+                ":root {{\n    color-scheme: light dark;\n{}}}\n@media (prefers-color-scheme: dark) {{\n:root {{\n{}}}\n}}\n",
+                variables(false),
+                variables(true),
+            ),
+        }
+    }
+
+    /// A small form for choosing the theme, returning to `return_to` afterwards
+    pub fn picker(&self, return_to: &str) -> String {
+        let palettes: String = Palette::ALL.iter()
+            .map(|palette| option(palette_value(*palette), palette.name(), *palette == self.palette))
+            .collect();
+        let modes: String = ThemeMode::ALL.iter()
+            .map(|mode| option(mode_value(*mode), mode.name(), *mode == self.mode))
+            .collect();
+        format!(
+            r#"<form method="post" action="{}" class="theme-picker"><strong>Theme:</strong> <select name="palette">{}</select> <select name="mode">{}</select> <input type="hidden" name="return_to" value="{}"> <button type="submit">Apply</button></form>"#,
+            crate::handlers::app_url("/theme"),
+            palettes,
+            modes,
+            crate::feed::escape_xml(return_to),
+        )
+    }
+}
+
+fn option(value: &str, label: &str, selected: bool) -> String {
+    format!(r#"<option value="{}"{}>{}</option>"#, value, if selected { " selected" } else { "" }, label)
+}
+
+fn palette_value(palette: Palette) -> &'static str {
+    match palette {
+        Palette::MistyPine => "misty-pine",
+        Palette::Paper => "paper",
+        Palette::Harbor => "harbor",
+    }
+}
+
+fn mode_value(mode: ThemeMode) -> &'static str {
+    match mode {
+        ThemeMode::Light => "light",
+        ThemeMode::Dark => "dark",
+        ThemeMode::Auto => "auto",
+    }
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/theme.css", get(theme_css))
+        .route("/theme", post(set_theme))
+}
+
+/// The theme of the session asking, or the default one for visitors who aren't logged in
+pub async fn session_theme(app_state: &AppState, headers: &HeaderMap) -> Theme {
+    let Some(token) = crate::handlers::extract_session_token(headers) else {
+        return Theme::default();
+    };
+    match app_state.auth_manager.get_session_info(&token).await {
+        Some(session) => session.theme,
+        None => Theme::default(),
+    }
+}
+
+/// The session's theme as CSS variables (GET /theme.css)
+async fn theme_css(State(app_state): State<AppState>, headers: HeaderMap) -> Response {
+    let theme = session_theme(&app_state, &headers).await;
+    (
+        [
+            (header::CONTENT_TYPE, "text/css; charset=utf-8"),
+            // Changes whenever the preference does, and differs per session
+            (header::CACHE_CONTROL, "private, no-cache"),
+            (header::VARY, "cookie"),
+        ],
+        theme.css(),
+    ).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct ThemeForm {
+    pub palette: Palette,
+    pub mode: ThemeMode,
+    /// Page to go back to, within the app
+    pub return_to: Option<String>,
+}
+
+/// Save the session's theme (POST /theme)
+async fn set_theme(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Form(form): Form<ThemeForm>,
+) -> Response {
+    let Some(token) = crate::handlers::extract_session_token(&headers) else {
+        return Redirect::to(&crate::handlers::app_url("/login")).into_response();
+    };
+    let theme = Theme { palette: form.palette, mode: form.mode };
+    if !app_state.auth_manager.set_theme(&token, theme).await {
+        return Redirect::to(&crate::handlers::app_url("/login")).into_response();
+    }
+    app_state.auth_manager.save_sessions_to_file(&app_state.tokens_file_manager).await;
+
+    // Only return to pages of this app, never another site
+    let return_to = form.return_to
+        .filter(|path| path.starts_with(crate::handlers::base_path()) && path.starts_with('/') && !path.starts_with("//"))
+        .unwrap_or_else(|| crate::handlers::app_url("/"));
+    Redirect::to(&return_to).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_css() {
+        let css = Theme::default().css();
+        assert!(css.contains("color-scheme: dark;"));
+        assert!(css.contains("--bg-primary: #2c3e3f;"));
+        assert_eq!(css.matches("--").count(), VARIABLES.len());
+
+        let css = Theme { palette: Palette::Paper, mode: ThemeMode::Auto }.css();
+        assert!(css.contains("--bg-primary: #f5f1e8;"));
+        assert!(css.contains("@media (prefers-color-scheme: dark)"));
+        assert!(css.contains("--bg-primary: #23201c;"));
+
+        let theme: Theme = serde_json::from_str(r#"{"palette": "misty-pine", "mode": "light"}"#).unwrap();
+        assert_eq!(theme, Theme { palette: Palette::MistyPine, mode: ThemeMode::Light });
+        // Sessions saved before themes existed
+        assert_eq!(serde_json::from_str::<Theme>("{}").unwrap(), Theme::default());
+
+        let picker = Theme { palette: Palette::Harbor, mode: ThemeMode::Auto }.picker("/");
+        assert!(picker.contains(r#"<option value="harbor" selected>Harbor</option>"#));
+        assert!(picker.contains(r#"<option value="auto" selected>Auto</option>"#));
+    }
+}
@@ -0,0 +1,101 @@
+use crate::config::TranscriptionConfig;
+use crate::hooks::shell_command_with_paths;
+use serde::Deserialize;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::time::{timeout, Duration};
+
+/// Text recognized in a voice note
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transcript {
+    pub text: String,
+    /// 0.0-1.0; 1.0 when the backend doesn't report one
+    pub confidence: f32,
+}
+
+/// JSON a transcription command may print instead of plain text
+#[derive(Deserialize)]
+struct TranscriptOutput {
+    text: String,
+    confidence: Option<f32>,
+}
+
+/// Whether voice notes can be transcribed
+pub fn available(config: &TranscriptionConfig) -> bool {
+    config.command.as_deref().is_some_and(|c| !c.trim().is_empty())
+}
+
+/// Run the configured transcription command on an audio file
+pub async fn transcribe(config: &TranscriptionConfig, audio: &Path) -> Result<Transcript, String> {
+    let command_line = config.command.as_deref().filter(|c| !c.trim().is_empty()).ok_or("No transcription command is configured")?;
+    let child = shell_command_with_paths(command_line, &[("file", audio)])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to start transcription: {}", e))?;
+
+    let output = match timeout(Duration::from_secs(config.timeout_seconds), child.wait_with_output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => return Err(format!("Failed to wait for transcription: {}", e)),
+        Err(_) => return Err(format!("Transcription timed out after {}s", config.timeout_seconds)),
+    };
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Transcription exited with {}: {}", output.status, stderr.trim()));
+    }
+
+    let transcript = parse_output(&String::from_utf8_lossy(&output.stdout));
+    if transcript.text.is_empty() {
+        return Err("No speech was recognized".to_string());
+    }
+    Ok(transcript)
+}
+
+/// Read plain-text or JSON command output, dropping whisper's markers for silence
+fn parse_output(stdout: &str) -> Transcript {
+    let (text, confidence) = match serde_json::from_str::<TranscriptOutput>(stdout.trim()) {
+        Ok(output) => (output.text, output.confidence.unwrap_or(1.0)),
+        Err(_) => (stdout.to_string(), 1.0),
+    };
+    let text = text
+        .lines()
+        .map(|line| line.replace("[BLANK_AUDIO]", "").trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    Transcript {
+        text,
+        confidence: confidence.clamp(0.0, 1.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_output() {
+        assert_eq!(parse_output(" Walked to the market.\n [BLANK_AUDIO]\n Bought plums.\n").text, "Walked to the market. Bought plums.");
+        assert_eq!(parse_output(r#"{"text": "Quiet morning.", "confidence": 0.62}"#), Transcript {
+            text: "Quiet morning.".to_string(),
+            confidence: 0.62,
+        });
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_transcribe_runs_command() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let audio = temp_dir.path().join("it's a note.webm");
+        std::fs::write(&audio, "Recorded words").unwrap();
+
+        let config = TranscriptionConfig {
+            command: Some("cat {file}".to_string()),
+            timeout_seconds: 10,
+        };
+        assert_eq!(transcribe(&config, &audio).await.unwrap().text, "Recorded words");
+        assert!(transcribe(&TranscriptionConfig::default(), &audio).await.is_err());
+    }
+}
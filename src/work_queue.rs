@@ -0,0 +1,348 @@
+//! Model work that failed and is waiting to be tried again, kept in queue.json in the
+//! notebook's directory so it survives a restart. Each failure pushes the next attempt
+//! further out (5 minutes, then 10, 20, ... up to 6 hours); after `MAX_ATTEMPTS` a task
+//! moves to the dead-letter list on the queue page, where it stays until it's retried by
+//! hand or dismissed.
+
+use askama::Template;
+use axum::{
+    extract::Path,
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Response},
+    routing::{get, post},
+    Router,
+};
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tokio::sync::Mutex;
+
+use crate::auth::{AuthContext, Scope};
+use crate::cycle_date::CycleDate;
+use crate::error::AppError;
+use crate::notebook::{ActiveNotebook, Notebook};
+use crate::AppState;
+
+const QUEUE_FILE: &str = "queue.json";
+/// Failed attempts before a task is given up on
+pub const MAX_ATTEMPTS: u32 = 6;
+/// Wait after the first failure; it doubles with each one after
+const FIRST_BACKOFF_MINUTES: i64 = 5;
+const MAX_BACKOFF_MINUTES: i64 = 6 * 60;
+/// Dead letters kept, newest first
+const KEEP_DEAD: usize = 100;
+
+/// A piece of model work that can be tried again
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Task {
+    /// Summarize an entry
+    Summary { cycle_date: CycleDate },
+    /// Write a day's prompts
+    Prompts { cycle_date: CycleDate },
+}
+
+impl Task {
+    /// "Summary of 02A06"
+    pub fn describe(&self) -> String {
+        match self {
+            Task::Summary { cycle_date } => format!("Summary of {}", cycle_date),
+            Task::Prompts { cycle_date } => format!("Prompts for {}", cycle_date),
+        }
+    }
+}
+
+/// A task that failed, with when it's next tried
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedTask {
+    pub id: String,
+    pub task: Task,
+    pub attempts: u32,
+    /// Why the last attempt failed
+    pub error: String,
+    pub first_failed_at: DateTime<Local>,
+    pub next_attempt_at: DateTime<Local>,
+}
+
+/// Tasks waiting for another attempt, and those given up on
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueueState {
+    pub pending: Vec<QueuedTask>,
+    pub dead: Vec<QueuedTask>,
+}
+
+/// How long to wait after a task's `attempts`th failure
+fn backoff(attempts: u32) -> Duration {
+    let minutes = FIRST_BACKOFF_MINUTES.saturating_mul(1 << attempts.saturating_sub(1).min(16));
+    Duration::minutes(minutes.min(MAX_BACKOFF_MINUTES))
+}
+
+impl QueueState {
+    /// Record a failed attempt. A task already given up on stays dead with the new error.
+    fn fail(&mut self, task: Task, error: &str, now: DateTime<Local>) {
+        if let Some(dead) = self.dead.iter_mut().find(|queued| queued.task == task) {
+            dead.error = error.to_string();
+            return;
+        }
+        let index = match self.pending.iter().position(|queued| queued.task == task) {
+            Some(index) => index,
+            None => {
+                self.pending.push(QueuedTask {
+                    id: format!("{:08x}", rand::random::<u32>()),
+                    task,
+                    attempts: 0,
+                    error: String::new(),
+                    first_failed_at: now,
+                    next_attempt_at: now,
+                });
+                self.pending.len() - 1
+            }
+        };
+        let queued = &mut self.pending[index];
+        queued.attempts += 1;
+        queued.error = error.to_string();
+        queued.next_attempt_at = now + backoff(queued.attempts);
+        if queued.attempts >= MAX_ATTEMPTS {
+            let dead = self.pending.remove(index);
+            tracing::warn!("Giving up on {} after {} attempts: {}", dead.task.describe(), dead.attempts, dead.error);
+            self.dead.insert(0, dead);
+            self.dead.truncate(KEEP_DEAD);
+        }
+    }
+
+    /// Forget a task that's now done; false if it wasn't queued
+    fn succeed(&mut self, task: Task) -> bool {
+        let before = self.pending.len() + self.dead.len();
+        self.pending.retain(|queued| queued.task != task);
+        self.dead.retain(|queued| queued.task != task);
+        self.pending.len() + self.dead.len() != before
+    }
+
+    fn due(&self, now: DateTime<Local>) -> Vec<Task> {
+        self.pending.iter().filter(|queued| queued.next_attempt_at <= now).map(|queued| queued.task).collect()
+    }
+
+    /// Give a dead task a fresh set of attempts, starting now
+    fn revive(&mut self, id: &str, now: DateTime<Local>) -> bool {
+        let Some(index) = self.dead.iter().position(|queued| queued.id == id) else {
+            return false;
+        };
+        let mut queued = self.dead.remove(index);
+        queued.attempts = 0;
+        queued.next_attempt_at = now;
+        self.pending.push(queued);
+        true
+    }
+
+    fn dismiss(&mut self, id: &str) -> bool {
+        let before = self.dead.len();
+        self.dead.retain(|queued| queued.id != id);
+        self.dead.len() != before
+    }
+}
+
+/// One notebook's queue, saved after every change
+pub struct WorkQueue {
+    path: PathBuf,
+    state: Mutex<QueueState>,
+}
+
+impl WorkQueue {
+    /// The queue saved in `dir`, or an empty one
+    pub fn load(dir: &std::path::Path) -> Self {
+        let path = dir.join(QUEUE_FILE);
+        let state = match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring unreadable work queue {}: {}", path.display(), e);
+                QueueState::default()
+            }),
+            Err(_) => QueueState::default(),
+        };
+        if !state.pending.is_empty() {
+            tracing::info!("Failed tasks waiting to be retried: {}", state.pending.len());
+        }
+        Self { path, state: Mutex::new(state) }
+    }
+
+    pub async fn state(&self) -> QueueState {
+        self.state.lock().await.clone()
+    }
+
+    /// Queue `task` to be tried again later
+    pub async fn failed(&self, task: Task, error: &str) {
+        let mut state = self.state.lock().await;
+        state.fail(task, error, Local::now());
+        self.save(&state).await;
+    }
+
+    /// Take `task` off the queue, if it's there
+    pub async fn succeeded(&self, task: Task) {
+        let mut state = self.state.lock().await;
+        if state.succeed(task) {
+            tracing::info!("{} succeeded on retry", task.describe());
+            self.save(&state).await;
+        }
+    }
+
+    /// Tasks whose next attempt is due
+    pub async fn due(&self) -> Vec<Task> {
+        self.state.lock().await.due(Local::now())
+    }
+
+    /// Move a dead task back onto the queue; false if there's no dead task with that id
+    pub async fn revive(&self, id: &str) -> bool {
+        let mut state = self.state.lock().await;
+        let revived = state.revive(id, Local::now());
+        if revived {
+            self.save(&state).await;
+        }
+        revived
+    }
+
+    /// Forget a dead task; false if there's no dead task with that id
+    pub async fn dismiss(&self, id: &str) -> bool {
+        let mut state = self.state.lock().await;
+        let dismissed = state.dismiss(id);
+        if dismissed {
+            self.save(&state).await;
+        }
+        dismissed
+    }
+
+    async fn save(&self, state: &QueueState) {
+        let result = match serde_json::to_string_pretty(state) {
+            Ok(json) => crate::settings::write_atomically(&self.path, &json).await.map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        if let Err(e) = result {
+            tracing::error!("Failed to save work queue {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "queue.html")]
+pub struct QueueTemplate {
+    pub notebook: String,
+    pub state: QueueState,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/queue", get(queue_page))
+        .route("/admin/queue/:id/retry", post(retry_task))
+        .route("/admin/queue/:id/dismiss", post(dismiss_task))
+}
+
+/// The active notebook's queue; notebooks without prompt generation have none
+pub fn queue_of(notebook: &Notebook) -> Result<&WorkQueue, AppError> {
+    notebook.prompt_generator.as_ref()
+        .map(|generator| generator.queue())
+        .ok_or_else(|| AppError::not_found("This notebook has no work queue"))
+}
+
+/// Failed tasks waiting to be retried and those given up on (GET /admin/queue)
+async fn queue_page(ActiveNotebook(notebook): ActiveNotebook, auth: AuthContext) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+    let queue = match queue_of(&notebook) {
+        Ok(queue) => queue,
+        Err(e) => return e.into_response(),
+    };
+    let template = QueueTemplate { notebook: notebook.name.clone(), state: queue.state().await };
+    match template.render() {
+        Ok(html) => Html(html).into_response(),
+        Err(e) => AppError::internal("Error rendering page", e).into_response(),
+    }
+}
+
+/// Give a dead task another set of attempts (POST /admin/queue/:id/retry)
+async fn retry_task(
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Path(id): Path<String>,
+) -> Response {
+    dead_letter_action(&notebook, &auth, &id, true).await
+}
+
+/// Forget a dead task (POST /admin/queue/:id/dismiss)
+async fn dismiss_task(
+    ActiveNotebook(notebook): ActiveNotebook,
+    auth: AuthContext,
+    Path(id): Path<String>,
+) -> Response {
+    dead_letter_action(&notebook, &auth, &id, false).await
+}
+
+async fn dead_letter_action(notebook: &Notebook, auth: &AuthContext, id: &str, retry: bool) -> Response {
+    if let Err(rejection) = auth.require(Scope::Admin) {
+        return rejection.into_response();
+    }
+    let queue = match queue_of(notebook) {
+        Ok(queue) => queue,
+        Err(e) => return e.into_response(),
+    };
+    let found = if retry { queue.revive(id).await } else { queue.dismiss(id).await };
+    if !found {
+        return AppError::not_found("No given-up task with that id").into_response();
+    }
+    tracing::info!("{} {} task {}", auth.label(), if retry { "requeued" } else { "dismissed" }, id);
+    (StatusCode::SEE_OTHER, [(header::LOCATION, crate::handlers::app_url("/admin/queue"))]).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        assert_eq!(backoff(1), Duration::minutes(5));
+        assert_eq!(backoff(2), Duration::minutes(10));
+        assert_eq!(backoff(4), Duration::minutes(40));
+        assert_eq!(backoff(30), Duration::minutes(MAX_BACKOFF_MINUTES));
+    }
+
+    #[test]
+    fn test_retries_then_dead_letter() {
+        let task = Task::Summary { cycle_date: CycleDate::new(2, 10, 0, 6).unwrap() };
+        let now = Local::now();
+        let mut state = QueueState::default();
+        state.fail(task, "Ollama not available", now);
+        assert_eq!(state.pending.len(), 1);
+        assert!(state.due(now).is_empty());
+        assert_eq!(state.due(now + Duration::minutes(5)), vec![task]);
+
+        for _ in 1..MAX_ATTEMPTS {
+            state.fail(task, "Still down", now);
+        }
+        assert!(state.pending.is_empty());
+        assert_eq!(state.dead[0].attempts, MAX_ATTEMPTS);
+        // Failing again while dead doesn't bring it back
+        state.fail(task, "Down again", now);
+        assert!(state.pending.is_empty());
+        assert_eq!(state.dead[0].error, "Down again");
+
+        let id = state.dead[0].id.clone();
+        assert!(state.revive(&id, now));
+        assert_eq!(state.due(now), vec![task]);
+        assert_eq!(state.pending[0].attempts, 0);
+        assert!(state.succeed(task));
+        assert!(!state.succeed(task));
+    }
+
+    #[tokio::test]
+    async fn test_queue_survives_restart() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let task = Task::Prompts { cycle_date: CycleDate::new(2, 10, 0, 6).unwrap() };
+        let queue = WorkQueue::load(temp_dir.path());
+        queue.failed(task, "Ollama not available").await;
+
+        let restored = WorkQueue::load(temp_dir.path());
+        let state = restored.state().await;
+        assert_eq!(state.pending.len(), 1);
+        assert_eq!(state.pending[0].task, task);
+        restored.succeeded(task).await;
+        assert!(WorkQueue::load(temp_dir.path()).state().await.pending.is_empty());
+    }
+}
@@ -0,0 +1,121 @@
+use crate::config::JournalConfig;
+use crate::cycle_date::{CycleDate, DayClock};
+use crate::journal::{EntryFilter, JournalManager};
+use chrono::{Duration, NaiveDateTime, NaiveTime};
+use serde::Serialize;
+
+/// Days of entries the writing window is learned from
+const RECENT_DAYS: i64 = 60;
+/// Entries needed before a prompt time is suggested
+const MIN_SAMPLES: usize = 10;
+/// Suggested times are rounded down to a multiple of this many minutes
+const ROUND_TO_MINUTES: i64 = 15;
+
+/// When entries usually get started, and the prompt time that would suit it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WritingWindow {
+    /// Entries the window was learned from
+    pub samples: usize,
+    /// Three in four entries are started after this
+    pub usual_start: NaiveTime,
+    /// Half of entries are started by this
+    pub typical_start: NaiveTime,
+    /// A prompt time shortly before the usual start
+    pub suggested_prompt_time: NaiveTime,
+}
+
+impl WritingWindow {
+    /// Learn the window from when entries were started. Times are measured from the day's
+    /// rollover, so late-night writing sorts after the evening rather than before the morning.
+    pub fn learn(started: &[NaiveDateTime], clock: &DayClock, lead_minutes: u32) -> Option<Self> {
+        if started.len() < MIN_SAMPLES {
+            return None;
+        }
+        let mut offsets: Vec<i64> = started
+            .iter()
+            .map(|time| (*time - clock.day_of(*time).and_time(clock.day_ends_at)).num_minutes())
+            .collect();
+        offsets.sort_unstable();
+
+        let usual = offsets[offsets.len() / 4];
+        let typical = offsets[offsets.len() / 2];
+        let suggested = (usual - lead_minutes as i64).max(0) / ROUND_TO_MINUTES * ROUND_TO_MINUTES;
+        let at = |offset: i64| clock.day_ends_at + Duration::minutes(offset);
+        Some(Self {
+            samples: offsets.len(),
+            usual_start: at(usual),
+            typical_start: at(typical),
+            suggested_prompt_time: at(suggested),
+        })
+    }
+}
+
+/// The writing window from recent entries, counting only those started on their own day
+/// (entries filled in later say nothing about habits)
+pub async fn writing_window(journal_manager: &JournalManager, journal_config: &JournalConfig) -> Result<Option<WritingWindow>, Box<dyn std::error::Error>> {
+    let clock = crate::cycle_date::clock();
+    let from = CycleDate::from_real_date(crate::cycle_date::current_day() - Duration::days(RECENT_DAYS));
+    let listings = journal_manager.list_entries(&EntryFilter { from: Some(from), ..EntryFilter::default() }).await?;
+
+    let mut started = Vec::new();
+    for listing in listings {
+        let metadata = journal_manager.load_metadata(&listing.cycle_date).await?;
+        let Some(entry) = metadata.entry else {
+            continue;
+        };
+        let time = clock.wall_time(entry.created_at);
+        if clock.day_of(time) == listing.real_date {
+            started.push(time);
+        }
+    }
+    Ok(WritingWindow::learn(&started, &clock, journal_config.prompt_lead_minutes))
+}
+
+/// The time ("HH:MM") to generate prompts at: the configured one, or with adaptive timing
+/// on, the one suggested by recent writing once there's enough to go on
+pub async fn prompt_time(journal_manager: &JournalManager, journal_config: &JournalConfig) -> String {
+    if !journal_config.adaptive_prompt_time {
+        return journal_config.prompt_generation_time.clone();
+    }
+    match writing_window(journal_manager, journal_config).await.map_err(|e| e.to_string()) {
+        Ok(Some(window)) => window.suggested_prompt_time.format("%H:%M").to_string(),
+        Ok(None) => journal_config.prompt_generation_time.clone(),
+        Err(e) => {
+            tracing::warn!("Failed to learn the writing window, keeping the configured prompt time: {}", e);
+            journal_config.prompt_generation_time.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 5, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_learn() {
+        let clock = DayClock { timezone: None, day_ends_at: NaiveTime::from_hms_opt(3, 0, 0).unwrap() };
+        let time = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+
+        let evenings: Vec<_> = (1..=12).map(|day| at(day, 21, 10 + day)).collect();
+        assert_eq!(WritingWindow::learn(&evenings[..MIN_SAMPLES - 1], &clock, 60), None);
+        let window = WritingWindow::learn(&evenings, &clock, 60).unwrap();
+        assert_eq!((window.usual_start, window.typical_start), (time(21, 14), time(21, 17)));
+        assert_eq!(window.suggested_prompt_time, time(20, 0));
+
+        // Past-midnight entries count as the latest of the evening, not the earliest
+        let mut late = evenings.clone();
+        late.extend((13..=28).map(|day| at(day, 1, 30)));
+        let window = WritingWindow::learn(&late, &clock, 60).unwrap();
+        assert_eq!(window.usual_start, time(21, 18));
+        assert_eq!(window.typical_start, time(1, 30));
+
+        // Never before the day has started
+        let early: Vec<_> = (1..=12).map(|day| at(day, 3, 20)).collect();
+        assert_eq!(WritingWindow::learn(&early, &clock, 60).unwrap().suggested_prompt_time, time(3, 0));
+    }
+}
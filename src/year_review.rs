@@ -0,0 +1,403 @@
+use crate::config::YearReviewConfig;
+use crate::cycle_date::{CalendarMode, CycleDate};
+use crate::hooks::shell_command_with_paths;
+use crate::journal::{EntryFilter, JournalManager};
+use crate::llm_worker::LlmWorker;
+use crate::personalization::PersonalizationConfig;
+use crate::prompts::PromptsConfig;
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::fs;
+use tokio::time::{timeout, Duration};
+
+/// Year-in-review books in the journal directory, with each year's chapters cached in a
+/// folder beside its book so an interrupted compilation picks up where it stopped
+const REVIEWS_DIR: &str = "reviews";
+/// Days into a new year before last year's review is compiled, so the yearly reflection
+/// written on its first day makes it in
+pub const COMPILE_AFTER_DAYS: u32 = 7;
+/// Summaries of the longest entries given to each chapter
+const TOP_SUMMARIES: usize = 5;
+
+/// What one month's chapter is written from
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonthMaterial {
+    /// Position in the year, from 0
+    pub number: u8,
+    /// The month's name in the calendar it was gathered in
+    pub name: String,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub entries: usize,
+    pub words: usize,
+    /// The entry written on the first day of the next month, its monthly reflection
+    pub reflection: Option<String>,
+    /// Summaries of the month's longest entries, in date order
+    pub summaries: Vec<(CycleDate, String)>,
+}
+
+impl MonthMaterial {
+    /// "Month 3", or "March" in the Gregorian calendar
+    pub fn title(&self) -> String {
+        self.name.clone()
+    }
+
+    /// "March 24 to April 20, 2025"
+    pub fn dates(&self) -> String {
+        format!("{} to {}", self.start.format("%B %-d"), self.end.format("%B %-d, %Y"))
+    }
+
+    fn stats(&self) -> String {
+        format!(
+            "{} entries on {} days, {} words in all ({} a day written)",
+            self.entries,
+            (self.end - self.start).num_days() + 1,
+            self.words,
+            self.words.checked_div(self.entries).unwrap_or(0),
+        )
+    }
+
+    /// The chapter prompt, with the month's material filled in
+    pub fn chapter_prompt(&self, prompts: &PromptsConfig) -> String {
+        let summaries: String = self
+            .summaries
+            .iter()
+            .map(|(cycle_date, summary)| format!("- {}: {}\n", cycle_date.to_real_date().format("%B %-d"), summary.trim()))
+            .collect();
+        prompts.get_year_review_chapter_prompt(
+            &self.title(),
+            &self.dates(),
+            &self.stats(),
+            self.reflection.as_deref().map(str::trim).unwrap_or("(none written)"),
+            if summaries.is_empty() { "(none)" } else { &summaries },
+        )
+    }
+}
+
+/// A compiled review on disk
+#[derive(Debug, Clone, Serialize)]
+pub struct Review {
+    /// The year reviewed, numbered as the configured calendar numbers years
+    pub year: i32,
+    pub markdown: PathBuf,
+    /// Set once the PDF conversion succeeded
+    pub pdf: Option<PathBuf>,
+}
+
+/// The year whose review is due on `today` in `calendar`: the previous one, once
+/// `COMPILE_AFTER_DAYS` of the new year have passed
+pub fn due(calendar: CalendarMode, today: NaiveDate) -> Option<i32> {
+    let year = calendar.year_of(today);
+    let (first, _) = calendar.year_bounds(year)?;
+    ((today - first).num_days() >= COMPILE_AFTER_DAYS as i64)
+        .then_some(year - 1)
+        .filter(|previous| *previous >= 0 && calendar.year_bounds(*previous).is_some())
+}
+
+/// Years from the one the journal starts in through the current one, newest first
+pub async fn years(journal_manager: &JournalManager, calendar: CalendarMode) -> Result<Vec<i32>, Box<dyn std::error::Error>> {
+    let current = calendar.year_of(crate::cycle_date::current_day());
+    let dates = journal_manager.list_dates().await?;
+    let first = dates.first().map_or(current, |first| calendar.year_of(first.to_real_date()).max(0));
+    Ok((first..=current).rev().collect())
+}
+
+/// Each month of the year with entries, with what its chapter is written from
+pub async fn gather(journal_manager: &JournalManager, calendar: CalendarMode, year: i32) -> Result<Vec<MonthMaterial>, Box<dyn std::error::Error>> {
+    let (first, last) = calendar.year_bounds(year).ok_or_else(|| format!("Year {:02} is outside the journal's dates", year))?;
+    let filter = EntryFilter { from: Some(CycleDate::from_real_date(first)), to: Some(CycleDate::from_real_date(last)), ..EntryFilter::default() };
+    let listings = journal_manager.list_entries(&filter).await?;
+
+    let mut months = Vec::new();
+    for (number, (start, end)) in calendar.months_of_year(year).into_iter().enumerate() {
+        let in_month: Vec<_> = listings.iter().filter(|l| (start..=end).contains(&l.real_date)).collect();
+        if in_month.is_empty() {
+            continue;
+        }
+
+        // Reflections on a month are written on the first day of the next
+        let reflection_day = CycleDate::from_real_date(end + chrono::Duration::days(1));
+        let reflection = journal_manager.load_entry(&reflection_day).await?.map(|e| e.content).filter(|c| !c.trim().is_empty());
+
+        let mut longest = in_month.clone();
+        longest.sort_by_key(|l| std::cmp::Reverse(l.word_count));
+        let mut summaries = Vec::new();
+        for listing in longest {
+            let summary = journal_manager.load_summary(&listing.cycle_date).await?;
+            let summary = match summary {
+                Some(summary) => Some(summary.summary),
+                None => journal_manager.load_one_line(&listing.cycle_date).await?,
+            };
+            if let Some(summary) = summary {
+                summaries.push((listing.cycle_date, summary));
+            }
+            if summaries.len() == TOP_SUMMARIES {
+                break;
+            }
+        }
+        summaries.sort_by_key(|(cycle_date, _)| cycle_date.to_real_date());
+
+        months.push(MonthMaterial {
+            number: number as u8,
+            name: crate::cycle_date::names().month(&CycleDate::from_real_date(start), calendar),
+            start,
+            end,
+            entries: in_month.len(),
+            words: in_month.iter().map(|l| l.word_count).sum(),
+            reflection,
+            summaries,
+        });
+    }
+    Ok(months)
+}
+
+fn reviews_dir(journal_manager: &JournalManager) -> PathBuf {
+    journal_manager.base_path().join(REVIEWS_DIR)
+}
+
+fn markdown_path(journal_manager: &JournalManager, year: i32) -> PathBuf {
+    reviews_dir(journal_manager).join(format!("year-{:02}.md", year))
+}
+
+fn pdf_path(journal_manager: &JournalManager, year: i32) -> PathBuf {
+    markdown_path(journal_manager, year).with_extension("pdf")
+}
+
+/// The compiled review of a year, if there is one
+pub fn find(journal_manager: &JournalManager, year: i32) -> Option<Review> {
+    let markdown = markdown_path(journal_manager, year);
+    if !markdown.exists() {
+        return None;
+    }
+    let pdf = Some(pdf_path(journal_manager, year)).filter(|p| p.exists());
+    Some(Review { year, markdown, pdf })
+}
+
+/// Compiled reviews, newest year first
+pub fn list(journal_manager: &JournalManager) -> Vec<Review> {
+    let Ok(files) = std::fs::read_dir(reviews_dir(journal_manager)) else {
+        return Vec::new();
+    };
+    let mut years: Vec<i32> = files
+        .filter_map(|file| file.ok())
+        .filter_map(|file| file.file_name().to_str()?.strip_prefix("year-")?.strip_suffix(".md")?.parse().ok())
+        .collect();
+    years.sort_unstable_by(|a, b| b.cmp(a));
+    years.into_iter().filter_map(|year| find(journal_manager, year)).collect()
+}
+
+/// Compile a year's review: write each month's chapter with the model (reusing
+/// chapters written by an earlier, interrupted run), assemble the book, and convert it to
+/// PDF when a command is configured. A failed PDF conversion is logged and leaves the
+/// Markdown in place.
+pub async fn compile(
+    config: &YearReviewConfig,
+    journal_manager: &JournalManager,
+    llm_worker: &LlmWorker,
+    personalization_config: &PersonalizationConfig,
+    calendar: CalendarMode,
+    year: i32,
+) -> Result<Review, Box<dyn std::error::Error>> {
+    let months = gather(journal_manager, calendar, year).await?;
+    if months.is_empty() {
+        return Err(format!("Year {:02} has no entries to review", year).into());
+    }
+
+    let chapters_dir = reviews_dir(journal_manager).join(format!("year-{:02}", year));
+    fs::create_dir_all(&chapters_dir).await?;
+    let mut chapters = Vec::new();
+    for material in &months {
+        let path = chapters_dir.join(format!("month-{}.md", crate::cycle_date::month_char(material.number)));
+        let chapter = match fs::read_to_string(&path).await {
+            Ok(chapter) if !chapter.trim().is_empty() => chapter,
+            _ => {
+                let chapter = llm_worker.generate_year_review_chapter(&material.chapter_prompt(&personalization_config.prompts)).await?;
+                fs::write(&path, &chapter).await?;
+                tracing::info!("Wrote the {} chapter of the year {:02} review", material.title(), year);
+                chapter
+            }
+        };
+        chapters.push(chapter);
+    }
+
+    let life_chapters = crate::chapters::load(journal_manager.base_path());
+    let markdown = assemble(calendar, year, &months, &chapters, &life_chapters);
+    let markdown_path = markdown_path(journal_manager, year);
+    fs::write(&markdown_path, markdown).await?;
+    journal_manager.record_change(format!("Compile the year {:02} review", year)).await;
+
+    if config.pdf_command.as_deref().is_some_and(|c| !c.trim().is_empty()) {
+        if let Err(e) = convert_to_pdf(config, &markdown_path, &pdf_path(journal_manager, year)).await {
+            tracing::warn!("Failed to convert the year {:02} review to PDF: {}", year, e);
+        }
+    }
+    Ok(find(journal_manager, year).ok_or("The review was not written")?)
+}
+
+/// The book: a title, the year in numbers, then a chapter per month
+pub fn assemble(calendar: CalendarMode, year: i32, months: &[MonthMaterial], chapters: &[String], life_chapters: &[crate::chapters::Chapter]) -> String {
+    let (start, end) = calendar.year_bounds(year).unwrap_or_default();
+    let mut out = String::new();
+    let _ = writeln!(out, "# Year {:02} in Review", year);
+    let _ = writeln!(out, "\n*{} to {}*", start.format("%B %-d, %Y"), end.format("%B %-d, %Y"));
+
+    let entries: usize = months.iter().map(|m| m.entries).sum();
+    let words: usize = months.iter().map(|m| m.words).sum();
+    let _ = writeln!(out, "\n## The year in numbers\n");
+    let _ = writeln!(out, "- Wrote on {} of {} days", entries, (end - start).num_days() + 1);
+    let _ = writeln!(out, "- {} words, {} an entry", words, words.checked_div(entries).unwrap_or(0));
+    if let Some(fullest) = months.iter().max_by_key(|m| m.words) {
+        let _ = writeln!(out, "- Most written: {} ({}, {} words)", fullest.title(), fullest.dates(), fullest.words);
+    }
+    let spanned = crate::chapters::chapters_between(life_chapters, start, end);
+    if !spanned.is_empty() {
+        let names: Vec<&str> = spanned.iter().map(|c| c.name.as_str()).collect();
+        let _ = writeln!(out, "- Life chapters: {}", names.join(", "));
+    }
+
+    let _ = writeln!(out, "\n| Month | Dates | Entries | Words |");
+    let _ = writeln!(out, "|---|---|---|---|");
+    for month in months {
+        let _ = writeln!(out, "| {} | {} | {} | {} |", month.title(), month.dates(), month.entries, month.words);
+    }
+
+    for (month, chapter) in months.iter().zip(chapters) {
+        let _ = writeln!(out, "\n## {}: {}\n", month.title(), month.dates());
+        let _ = writeln!(out, "{}", chapter.trim());
+    }
+    out
+}
+
+/// Run the PDF command, writing to a temporary file that's renamed into place once complete
+async fn convert_to_pdf(config: &YearReviewConfig, input: &Path, output: &Path) -> Result<(), String> {
+    let command_line = config.pdf_command.as_deref().filter(|c| !c.trim().is_empty()).ok_or("No PDF command is configured")?;
+    let partial = output.with_extension("partial.pdf");
+    let child = shell_command_with_paths(command_line, &[("input", input), ("output", &partial)])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to start PDF command: {}", e))?;
+    let result = match timeout(Duration::from_secs(config.timeout_seconds), child.wait_with_output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => return Err(format!("Failed to wait for PDF command: {}", e)),
+        Err(_) => return Err(format!("PDF command timed out after {}s", config.timeout_seconds)),
+    };
+    if !result.status.success() {
+        let _ = fs::remove_file(&partial).await;
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        return Err(format!("PDF command exited with {}: {}", result.status, stderr.trim()));
+    }
+    fs::rename(&partial, output).await.map_err(|e| format!("The PDF command wrote no file: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::{JournalEntry, JournalSummary};
+    use crate::test_support::date;
+    use chrono::Local;
+
+    #[test]
+    fn test_due() {
+        let cycle = |year_cycle, month, week, day| CycleDate::new(year_cycle, month, week, day).unwrap().to_real_date();
+        assert_eq!(due(CalendarMode::Cycle, cycle(0, 5, 0, 0)), None);
+        assert_eq!(due(CalendarMode::Cycle, cycle(2, 0, 0, 6)), None);
+        assert_eq!(due(CalendarMode::Cycle, cycle(2, 0, 1, 0)), Some(1));
+
+        assert_eq!(due(CalendarMode::Gregorian, date(2026, 1, 7)), None);
+        assert_eq!(due(CalendarMode::Gregorian, date(2026, 1, 8)), Some(2025));
+        assert_eq!(due(CalendarMode::Gregorian, date(2026, 6, 1)), Some(2025));
+        // The ISO year 2026 starts on December 29, 2025
+        assert_eq!(due(CalendarMode::IsoWeek, date(2026, 1, 5)), Some(2025));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_convert_to_pdf() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let input = temp_dir.path().join("year 1's review.md");
+        std::fs::write(&input, "# Year 1").unwrap();
+        let output = temp_dir.path().join("year 1's review.pdf");
+        let config = YearReviewConfig { pdf_command: Some("cp {input} {output}".to_string()), ..YearReviewConfig::default() };
+
+        convert_to_pdf(&config, &input, &output).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "# Year 1");
+        let broken = YearReviewConfig { pdf_command: Some("false".to_string()), ..config };
+        assert!(convert_to_pdf(&broken, &input, &output).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_gather_and_assemble() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let journal_manager = JournalManager::new(temp_dir.path());
+        let write = |cycle_date: CycleDate, content: &str| JournalEntry {
+            cycle_date,
+            content: content.to_string(),
+            created_at: Local::now(),
+            modified_at: Local::now(),
+        };
+
+        let short = CycleDate::new(1, 2, 1, 3).unwrap();
+        let long = CycleDate::new(1, 2, 2, 1).unwrap();
+        journal_manager.save_entry(&write(short, "A quiet day.")).await.unwrap();
+        journal_manager.save_entry(&write(long, "Started the new job, met the team, and cycled home in the rain.")).await.unwrap();
+        journal_manager.save_summary(&JournalSummary { cycle_date: long, summary: "First day at the new job.".to_string(), generated_at: Local::now() }).await.unwrap();
+        // The reflection on month 2 is written on the first day of month 3
+        journal_manager.save_entry(&write(CycleDate::new(1, 3, 0, 0).unwrap(), "A month of new beginnings.")).await.unwrap();
+
+        let months = gather(&journal_manager, CalendarMode::Cycle, 1).await.unwrap();
+        assert_eq!(months.iter().map(|m| (m.number, m.entries)).collect::<Vec<_>>(), vec![(2, 2), (3, 1)]);
+        assert_eq!(months[0].reflection.as_deref(), Some("A month of new beginnings."));
+        assert_eq!(months[0].summaries, vec![(long, "First day at the new job.".to_string())]);
+
+        let prompt = months[0].chapter_prompt(&PromptsConfig::default());
+        assert!(prompt.contains("covering Month 2") && prompt.contains("First day at the new job.") && prompt.contains("2 entries on 28 days"));
+
+        let chapters = vec!["You started a new job.".to_string(), "You looked back.".to_string()];
+        let book = assemble(CalendarMode::Cycle, 1, &months, &chapters, &[]);
+        assert!(book.starts_with("# Year 01 in Review"));
+        assert!(book.contains("- Wrote on 3 of 364 days"));
+        assert!(book.contains(&format!("## Month 2: {}\n\nYou started a new job.", months[0].dates())));
+        assert!(find(&journal_manager, 1).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_gather_and_assemble_gregorian() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let journal_manager = JournalManager::new(temp_dir.path());
+        let write = |real_date: NaiveDate, content: &str| JournalEntry {
+            cycle_date: CycleDate::from_real_date(real_date),
+            content: content.to_string(),
+            created_at: Local::now(),
+            modified_at: Local::now(),
+        };
+
+        for (real_date, content) in [
+            (date(2024, 12, 31), "The last day of last year."),
+            (date(2025, 2, 27), "Snow again."),
+            (date(2025, 3, 10), "Started the new job."),
+            (date(2025, 3, 31), "Packed for the trip."),
+            // The reflection on March, written on the first day of April
+            (date(2025, 4, 1), "A month of new beginnings."),
+        ] {
+            journal_manager.save_entry(&write(real_date, content)).await.unwrap();
+        }
+
+        let months = gather(&journal_manager, CalendarMode::Gregorian, 2025).await.unwrap();
+        let found: Vec<(u8, &str, usize)> = months.iter().map(|m| (m.number, m.name.as_str(), m.entries)).collect();
+        assert_eq!(found, vec![(1, "February", 1), (2, "March", 2), (3, "April", 1)]);
+        assert_eq!((months[1].start, months[1].end), (date(2025, 3, 1), date(2025, 3, 31)));
+        assert_eq!(months[1].reflection.as_deref(), Some("A month of new beginnings."));
+
+        let chapters = vec!["Winter.".to_string(), "A new job.".to_string(), "Spring.".to_string()];
+        let book = assemble(CalendarMode::Gregorian, 2025, &months, &chapters, &[]);
+        assert!(book.starts_with("# Year 2025 in Review\n\n*January 1, 2025 to December 31, 2025*"));
+        assert!(book.contains("- Wrote on 4 of 365 days"));
+        assert!(book.contains("## March: March 1 to March 31, 2025\n\nA new job."));
+    }
+}